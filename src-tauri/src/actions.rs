@@ -1,11 +1,34 @@
+use crate::ai_toolkit::acronyms::apply_acronyms;
+use crate::ai_toolkit::code_dictation::write_code;
+use crate::ai_toolkit::lists::format_lists;
+use crate::ai_toolkit::markdown::write_markdown;
+use crate::ai_toolkit::paragraphs::{split_paragraphs, SENTENCES_PER_PARAGRAPH};
+use crate::ai_toolkit::phone_numbers::format_phone_numbers;
+use crate::ai_toolkit::profanity::mask_profanity;
+use crate::ai_toolkit::redaction::redact_sensitive;
+use crate::ai_toolkit::repetitions::collapse_repetitions;
+use crate::ai_toolkit::replacement_rules::apply_replacement_rules;
+use crate::ai_toolkit::run_on_sentences::split_long_sentences;
+use crate::ai_toolkit::spoken_emoji::convert_spoken_emoji;
+use crate::ai_toolkit::spoken_punctuation::apply_spoken_punctuation;
+use crate::ai_toolkit::typography::smart_typography;
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 use crate::apple_intelligence;
 use crate::audio_feedback::{play_feedback_sound, play_feedback_sound_blocking, SoundType};
-use crate::managers::ai_enhancement::AiEnhancementManager;
+use crate::managers::ai_enhancement::{
+    abort_enhancement, cancel_refinement, punctuates_fallback, record_pasted_transcript,
+    request_key, supersede_last_enhancement, AiEnhancementManager, AiSkipReason, EnhanceMode,
+    EnhancedText, EnhancementOutcome, SharedEnhancementDedup,
+};
+use crate::managers::ai_enhancement_queue::{take_turn, wait_for_turn, SharedEnhancementQueue};
+use crate::managers::ai_request_dedup::DedupRequest;
 use crate::managers::audio::AudioRecordingManager;
-use crate::managers::history::HistoryManager;
+use crate::managers::history::{AiEnhancementInfo, HistoryManager};
 use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, AppSettings, APPLE_INTELLIGENCE_PROVIDER_ID};
+use crate::settings::{
+    get_settings, AiCapitalizationStyle, AiFeatures, AiRuleStage, AiTone, AppSettings,
+    APPLE_INTELLIGENCE_PROVIDER_ID,
+};
 use crate::shortcut;
 use crate::tray::{change_tray_icon, TrayIconState};
 use crate::utils::{self, show_recording_overlay, show_transcribing_overlay};
@@ -20,7 +43,9 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 use tauri::AppHandle;
+use tauri::Emitter;
 use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
 // Shortcut Action Trait
 pub trait ShortcutAction: Send + Sync {
@@ -29,51 +54,144 @@ pub trait ShortcutAction: Send + Sync {
 }
 
 // Transcribe Action
-struct TranscribeAction;
+struct TranscribeAction {
+    /// What enhancement makes of the transcript
+    mode: TranscribeMode,
+    /// Tone to rewrite the text in, in place of the one in the AI settings
+    tone: Option<AiTone>,
+    /// Capitalization in place of the one in the AI settings
+    capitalization: Option<AiCapitalizationStyle>,
+}
+
+/// Enhancement a transcribe shortcut asks for
+#[derive(Clone, Copy)]
+enum TranscribeMode {
+    Correct,
+    /// Paste a summary of the transcript instead of the corrected text
+    Summarize,
+    /// Paste the corrected text laid out as an email
+    Email,
+    /// Paste the corrected text with its dictated structure as Markdown
+    Markdown,
+    /// Paste dictated code with its symbols and identifiers written out
+    Code,
+}
+
+impl TranscribeMode {
+    fn enhance_mode(self, settings: &AppSettings) -> EnhanceMode {
+        match self {
+            TranscribeMode::Correct => EnhanceMode::Correct,
+            TranscribeMode::Summarize => EnhanceMode::summarize(settings),
+            TranscribeMode::Email => EnhanceMode::EmailFormat,
+            TranscribeMode::Markdown => EnhanceMode::Markdown,
+            TranscribeMode::Code => EnhanceMode::Code,
+        }
+    }
+}
 
 async fn maybe_ai_enhance_transcription(
     app: &AppHandle,
     transcription: &str,
+    features: &AiFeatures,
+    mode: EnhanceMode,
 ) -> Option<String> {
     let settings = get_settings(app);
-    
+
     if !settings.ai_enhancement_enabled {
         return None;
     }
 
     let model = settings.ai_selected_model.clone()?;
-    
-    // Skip very short text
-    if transcription.split_whitespace().count() < 5 {
-        return None;
-    }
 
     // Get AI manager
     let ai_manager = app.try_state::<Arc<tokio::sync::Mutex<AiEnhancementManager>>>()?;
     let mut manager = ai_manager.lock().await;
 
-    // Enhance with timeout
-    match tokio::time::timeout(
-        tokio::time::Duration::from_secs(5),
-        manager.enhance_text(transcription, &model, &settings.ai_features),
-    )
-    .await
+    // Enhancement is bounded by its own timeout and keeps the original
+    // transcript when it fails
+    match manager
+        .enhance_text(transcription, &model, features, mode)
+        .await
     {
-        Ok(Ok(enhanced)) => {
-            debug!("AI enhancement successful");
-            Some(enhanced)
-        }
-        Ok(Err(e)) => {
+        Ok(result) => text_to_paste(result, &settings, mode),
+        Err(e) => {
             debug!("AI enhancement failed: {}", e);
             None
         }
-        Err(_) => {
-            debug!("AI enhancement timed out");
+    }
+}
+
+/// The text an enhancement's `result` has pasted in place of the
+/// transcript, or `None` to paste the transcript formatted by rule
+fn text_to_paste(
+    result: EnhancedText,
+    settings: &AppSettings,
+    mode: EnhanceMode,
+) -> Option<String> {
+    match result.outcome {
+        EnhancementOutcome::Enhanced => {
+            debug!("AI enhancement successful");
+            Some(result.text)
+        }
+        // A snippet is inserted whether or not the model got to run
+        EnhancementOutcome::Skipped {
+            reason: AiSkipReason::Snippet,
+        } => {
+            debug!("Inserted a snippet");
+            Some(result.text)
+        }
+        // The original punctuated by rule in place of a failed enhancement
+        outcome if punctuates_fallback(settings, mode, &outcome) => {
+            debug!(
+                "AI enhancement fell back to basic punctuation: {:?}",
+                outcome
+            );
+            Some(result.text)
+        }
+        outcome => {
+            debug!("AI enhancement not applied: {:?}", outcome);
             None
         }
     }
 }
 
+/// Write spoken punctuation, collapse repetitions, format phone numbers,
+/// split run-on sentences and lay out lists and paragraphs by rule, for
+/// when the model didn't get to do it, and a note's dictated structure as
+/// Markdown in `mode`. Lists go before paragraphs, which are only added to
+/// unbroken text, and sentences are split before they are grouped into
+/// paragraphs. Code is only written
+/// by its own rules.
+fn format_without_ai(text: &str, features: &AiFeatures, mode: EnhanceMode) -> String {
+    if mode == EnhanceMode::Code {
+        return write_code(text);
+    }
+
+    let mut text = text.to_string();
+    if features.spoken_punctuation {
+        text = apply_spoken_punctuation(&text, &features.spoken_commands);
+    }
+    if features.remove_repetitions {
+        text = collapse_repetitions(&text);
+    }
+    if features.format_phone_numbers {
+        text = format_phone_numbers(&text, features.phone_style);
+    }
+    if mode == EnhanceMode::Markdown {
+        text = write_markdown(&text);
+    }
+    if features.format_lists {
+        text = format_lists(&text, features.list_style);
+    }
+    if features.split_long_sentences {
+        text = split_long_sentences(&text, features.max_sentence_words as usize);
+    }
+    if features.split_paragraphs {
+        text = split_paragraphs(&text, SENTENCES_PER_PARAGRAPH);
+    }
+    text
+}
+
 async fn maybe_post_process_transcription(
     settings: &AppSettings,
     transcription: &str,
@@ -296,6 +414,25 @@ impl ShortcutAction for TranscribeAction {
         let tm = app.state::<Arc<TranscriptionManager>>();
         tm.initiate_model_load();
 
+        // A new dictation makes the previous one's enhancement stale, so
+        // paste its transcript as dictated rather than over the new context
+        if abort_enhancement(app) {
+            debug!("Aborted the previous enhancement for a new recording");
+        }
+        // Nor is a better take on the last one worth offering any more
+        if cancel_refinement(app) {
+            debug!("Cancelled the last refinement for a new recording");
+        }
+        supersede_last_enhancement(app);
+
+        // Load the AI model while the user speaks. A busy manager is
+        // enhancing, so its model is loaded already.
+        if let Some(ai_manager) = app.try_state::<Arc<tokio::sync::Mutex<AiEnhancementManager>>>() {
+            if let Ok(manager) = ai_manager.try_lock() {
+                manager.warm_up();
+            }
+        }
+
         let binding_id = binding_id.to_string();
         change_tray_icon(app, TrayIconState::Recording);
         show_recording_overlay(app);
@@ -368,6 +505,8 @@ impl ShortcutAction for TranscribeAction {
         let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
         let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
         let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+        let eq = Arc::clone(&app.state::<SharedEnhancementQueue>());
+        let dedup = Arc::clone(&app.state::<SharedEnhancementDedup>());
 
         change_tray_icon(app, TrayIconState::Transcribing);
         show_transcribing_overlay(app);
@@ -379,6 +518,7 @@ impl ShortcutAction for TranscribeAction {
         play_feedback_sound(app, SoundType::Stop);
 
         let binding_id = binding_id.to_string(); // Clone binding_id for the async task
+        let (transcribe_mode, tone, capitalization) = (self.mode, self.tone, self.capitalization);
 
         tauri::async_runtime::spawn(async move {
             let binding_id = binding_id.clone(); // Clone for the inner async task
@@ -409,10 +549,180 @@ impl ShortcutAction for TranscribeAction {
                             let mut final_text = transcription.clone();
                             let mut post_processed_text: Option<String> = None;
                             let mut post_process_prompt: Option<String> = None;
+                            let mut ai_enhancement = AiEnhancementInfo::default();
+
+                            let mode = transcribe_mode.enhance_mode(&settings);
+                            let mut features = settings.ai_features.clone();
+                            if tone.is_some() {
+                                features.tone = tone;
+                            }
+                            if let Some(style) = capitalization {
+                                features.capitalization_style = style;
+                            }
+
+                            // The same transcript dictated twice at once, as when
+                            // a shortcut fires twice, is enhanced only once
+                            let request = match &settings.ai_selected_model {
+                                Some(model) if settings.ai_enhancement_enabled => Some(dedup.join(
+                                    request_key(&settings, &transcription, model, &features, mode),
+                                )),
+                                _ => None,
+                            };
+
+                            // Enhanced transcripts take turns, holding theirs until
+                            // pasted so they paste in the order they were dictated.
+                            // One sharing another's enhancement doesn't need one.
+                            let following = matches!(request, Some(DedupRequest::Follow(_)));
+                            let _turn = if settings.ai_enhancement_enabled && !following {
+                                match wait_for_turn(&ah, &eq).await {
+                                    Some(turn) => Some(turn),
+                                    None => {
+                                        // Dropped for a newer transcript: kept in
+                                        // history, but not pasted
+                                        if let Err(e) = hm
+                                            .save_transcription(
+                                                samples_clone,
+                                                transcription,
+                                                None,
+                                                None,
+                                                ai_enhancement,
+                                            )
+                                            .await
+                                        {
+                                            error!(
+                                                "Failed to save transcription to history: {}",
+                                                e
+                                            );
+                                        }
+                                        return;
+                                    }
+                                }
+                            } else {
+                                None
+                            };
+
+                            // The user's own rules go around everything else,
+                            // the model included
+                            let rules = &settings.ai_replacement_rules;
+                            if settings.ai_enhancement_enabled {
+                                let ruled = apply_replacement_rules(
+                                    &final_text,
+                                    rules,
+                                    AiRuleStage::BeforeAi,
+                                );
+                                if ruled != final_text {
+                                    final_text = ruled.clone();
+                                    post_processed_text = Some(ruled);
+                                }
+                            }
 
                             // Step 1: AI enhancement (if enabled)
-                            if let Some(ai_enhanced) = maybe_ai_enhance_transcription(&ah, &transcription).await {
+                            let enhance = || {
+                                maybe_ai_enhance_transcription(&ah, &final_text, &features, mode)
+                            };
+                            let enhanced = match request {
+                                Some(DedupRequest::Lead(lead)) => {
+                                    let enhanced = enhance().await;
+                                    lead.finish(enhanced.clone());
+                                    enhanced
+                                }
+                                // Enhanced on its own when the other one was dropped
+                                Some(DedupRequest::Follow(follow)) => match follow.result().await {
+                                    Some(enhanced) => enhanced,
+                                    None => enhance().await,
+                                },
+                                None => enhance().await,
+                            };
+                            if let Some(ai_enhanced) = enhanced {
                                 final_text = ai_enhanced.clone();
+                                post_processed_text = Some(ai_enhanced);
+                                ai_enhancement = AiEnhancementInfo {
+                                    mode: Some(mode.label().to_string()),
+                                    tone: features
+                                        .tone
+                                        .filter(|_| mode.applies_tone())
+                                        .map(|tone| tone.label().to_string()),
+                                };
+                            } else {
+                                let formatted = format_without_ai(&final_text, &features, mode);
+                                if formatted != final_text {
+                                    final_text = formatted.clone();
+                                    post_processed_text = Some(formatted);
+                                    // A note is still written as Markdown,
+                                    // and code as code
+                                    if matches!(mode, EnhanceMode::Markdown | EnhanceMode::Code) {
+                                        ai_enhancement.mode = Some(mode.label().to_string());
+                                    }
+                                }
+                            }
+
+                            if settings.ai_enhancement_enabled {
+                                let ruled = apply_replacement_rules(
+                                    &final_text,
+                                    rules,
+                                    AiRuleStage::AfterAi,
+                                );
+                                if ruled != final_text {
+                                    final_text = ruled.clone();
+                                    post_processed_text = Some(ruled);
+                                }
+                            }
+
+                            // Acronyms are cased by rule, whether the model ran or
+                            // not, and the rules for prose leave code alone
+                            if settings.ai_enhancement_enabled && mode.writes_prose() {
+                                let cased = apply_acronyms(&final_text, &settings.ai_acronyms);
+                                if cased != final_text {
+                                    final_text = cased.clone();
+                                    post_processed_text = Some(cased);
+                                }
+                            }
+
+                            // Spoken emoji were kept from the model, and are
+                            // written by rule either way
+                            if features.convert_spoken_emoji && mode.writes_prose() {
+                                let converted =
+                                    convert_spoken_emoji(&final_text, &features.spoken_emoji);
+                                if converted != final_text {
+                                    final_text = converted.clone();
+                                    post_processed_text = Some(converted);
+                                }
+                            }
+
+                            // Typesetting is done by rule, with or without the model
+                            if features.smart_typography && mode.writes_prose() {
+                                let typeset = smart_typography(&final_text);
+                                if typeset != final_text {
+                                    final_text = typeset.clone();
+                                    post_processed_text = Some(typeset);
+                                }
+                            }
+
+                            // So is masking, which also catches profanity the
+                            // model wrote out in full
+                            if features.mask_profanity {
+                                let masked = mask_profanity(
+                                    &final_text,
+                                    &features.profane_words,
+                                    features.profanity_mask,
+                                );
+                                if masked != final_text {
+                                    final_text = masked.clone();
+                                    post_processed_text = Some(masked);
+                                }
+                            }
+
+                            // Redacted last, from the text with its protected
+                            // spans restored, and announced so the
+                            // placeholders come as no surprise
+                            if features.redact_sensitive {
+                                let (redacted, counts) =
+                                    redact_sensitive(&final_text, &features.redaction);
+                                if counts.total() > 0 {
+                                    final_text = redacted.clone();
+                                    post_processed_text = Some(redacted);
+                                    let _ = ah.emit("ai-redactions-applied", counts);
+                                }
                             }
 
                             // Step 2: Check if Chinese variant conversion is needed
@@ -451,6 +761,7 @@ impl ShortcutAction for TranscribeAction {
                                         transcription_for_history,
                                         post_processed_text,
                                         post_process_prompt,
+                                        ai_enhancement,
                                     )
                                     .await
                                 {
@@ -458,6 +769,12 @@ impl ShortcutAction for TranscribeAction {
                                 }
                             });
 
+                            // Recorded here rather than after enhancing, so undo
+                            // follows the order texts are pasted in
+                            if settings.ai_enhancement_enabled {
+                                record_pasted_transcript(&ah, &transcription, &final_text);
+                            }
+
                             // Paste the final text (either processed or original)
                             let ah_clone = ah.clone();
                             let paste_time = Instant::now();
@@ -516,6 +833,46 @@ impl ShortcutAction for CancelAction {
     }
 }
 
+// Reenhance Action
+struct ReenhanceAction;
+
+impl ShortcutAction for ReenhanceAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Run a heavier pass over the text pasted last and copy the result,
+        // leaving what was pasted alone
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let (Some(ai_manager), Some(queue)) = (
+                app.try_state::<Arc<tokio::sync::Mutex<AiEnhancementManager>>>(),
+                app.try_state::<SharedEnhancementQueue>(),
+            ) else {
+                return;
+            };
+            let overrides = get_settings(&app).ai_reenhance;
+            let result = {
+                let _slot = take_turn(&queue).await;
+                ai_manager.lock().await.reenhance_last(&overrides).await
+            };
+            match result {
+                Ok(reenhanced) => {
+                    if let Err(e) = app.clipboard().write_text(reenhanced.text.trim()) {
+                        error!("Failed to copy the enhanced text: {}", e);
+                    }
+                    let _ = app.emit("ai-enhancement-reenhanced", reenhanced);
+                }
+                Err(e) => {
+                    debug!("Enhancing the last text again failed: {}", e);
+                    let _ = app.emit("ai-enhancement-reenhance-failed", e.to_string());
+                }
+            }
+        });
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for a second pass
+    }
+}
+
 // Test Action
 struct TestAction;
 
@@ -544,7 +901,63 @@ pub static ACTION_MAP: Lazy<HashMap<String, Arc<dyn ShortcutAction>>> = Lazy::ne
     let mut map = HashMap::new();
     map.insert(
         "transcribe".to_string(),
-        Arc::new(TranscribeAction) as Arc<dyn ShortcutAction>,
+        Arc::new(TranscribeAction {
+            mode: TranscribeMode::Correct,
+            tone: None,
+            capitalization: None,
+        }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "transcribe_summary".to_string(),
+        Arc::new(TranscribeAction {
+            mode: TranscribeMode::Summarize,
+            tone: None,
+            capitalization: None,
+        }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "transcribe_professional".to_string(),
+        Arc::new(TranscribeAction {
+            mode: TranscribeMode::Correct,
+            tone: Some(AiTone::Professional),
+            capitalization: None,
+        }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "transcribe_email".to_string(),
+        Arc::new(TranscribeAction {
+            mode: TranscribeMode::Email,
+            tone: None,
+            capitalization: None,
+        }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "transcribe_markdown".to_string(),
+        Arc::new(TranscribeAction {
+            mode: TranscribeMode::Markdown,
+            tone: None,
+            capitalization: None,
+        }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "transcribe_code".to_string(),
+        Arc::new(TranscribeAction {
+            mode: TranscribeMode::Code,
+            tone: None,
+            capitalization: None,
+        }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "transcribe_heading".to_string(),
+        Arc::new(TranscribeAction {
+            mode: TranscribeMode::Correct,
+            tone: None,
+            capitalization: Some(AiCapitalizationStyle::Title),
+        }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "reenhance_last".to_string(),
+        Arc::new(ReenhanceAction) as Arc<dyn ShortcutAction>,
     );
     map.insert(
         "cancel".to_string(),
@@ -556,3 +969,53 @@ pub static ACTION_MAP: Lazy<HashMap<String, Arc<dyn ShortcutAction>>> = Lazy::ne
     );
     map
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::ai_enhancement::{punctuate_fallback, AiFallbackReason};
+    use crate::settings::get_default_settings;
+
+    const TRANSCRIPT: &str = "so can you call me when i land at the airport tonight";
+
+    /// What the dictation path pastes when enhancement of `TRANSCRIPT` in
+    /// `mode` ends with `outcome`
+    fn pasted(outcome: EnhancementOutcome, settings: &AppSettings, mode: EnhanceMode) -> String {
+        let result = EnhancedText {
+            text: TRANSCRIPT.to_string(),
+            outcome,
+        };
+        let result = punctuate_fallback(result, TRANSCRIPT, settings, mode);
+        text_to_paste(result, settings, mode)
+            .unwrap_or_else(|| format_without_ai(TRANSCRIPT, &settings.ai_features, mode))
+    }
+
+    #[test]
+    fn test_failed_enhancement_pastes_the_transcript_punctuated_by_rule() {
+        let timeout = EnhancementOutcome::Fallback {
+            reason: AiFallbackReason::Timeout,
+        };
+        let mut settings = get_default_settings();
+        assert_eq!(
+            pasted(timeout.clone(), &settings, EnhanceMode::Correct),
+            TRANSCRIPT
+        );
+
+        settings.ai_offline_fallback = true;
+        assert_eq!(
+            pasted(timeout.clone(), &settings, EnhanceMode::Correct),
+            "So can you call me when I land at the airport tonight?"
+        );
+        // Code is only written by its own rules
+        assert_eq!(
+            pasted(timeout, &settings, EnhanceMode::Code),
+            write_code(TRANSCRIPT)
+        );
+
+        // An aborted enhancement isn't pasted as if it had failed
+        let aborted = EnhancementOutcome::Fallback {
+            reason: AiFallbackReason::Aborted,
+        };
+        assert_eq!(pasted(aborted, &settings, EnhanceMode::Correct), TRANSCRIPT);
+    }
+}
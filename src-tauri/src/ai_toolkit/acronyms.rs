@@ -0,0 +1,212 @@
+use super::protected_spans::protect_spans;
+use crate::settings::AiAcronym;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Acronyms fixed by default, as they are written
+const DEFAULT_ACRONYMS: &[&str] = &[
+    "AI", "API", "ASAP", "AWS", "CEO", "CFO", "CLI", "CPU", "CSS", "CTO", "DNS", "ETA", "FAQ",
+    "FYI", "GPU", "HR", "HTML", "HTTP", "HTTPS", "iOS", "JSON", "KPI", "macOS", "OAuth", "PDF",
+    "PR", "QA", "ROI", "SDK", "SQL", "SSD", "UI", "URL", "USB", "UX", "VPN",
+];
+
+/// Acronyms that are also everyday words ("it", "us", "am"), listed but only
+/// fixed once the user turns them on
+const COMMON_WORD_ACRONYMS: &[&str] = &["AM", "ID", "IT", "RAM", "US", "WHO"];
+
+/// Longest acronym accepted, in characters
+const MAX_ACRONYM_CHARS: usize = 16;
+
+/// The acronym list new settings start with
+pub fn default_acronyms() -> Vec<AiAcronym> {
+    let acronym = |enabled| {
+        move |written_form: &&str| AiAcronym {
+            written_form: written_form.to_string(),
+            enabled,
+        }
+    };
+    DEFAULT_ACRONYMS
+        .iter()
+        .map(acronym(true))
+        .chain(COMMON_WORD_ACRONYMS.iter().map(acronym(false)))
+        .collect()
+}
+
+/// Trim an acronym and check it is a single word of letters and digits
+pub fn normalize_acronym(written_form: &str) -> Result<String> {
+    let written_form = written_form.trim();
+    if written_form.chars().count() < 2 {
+        return Err(anyhow!("An acronym needs at least two letters"));
+    }
+    if written_form.chars().count() > MAX_ACRONYM_CHARS {
+        return Err(anyhow!(
+            "Acronyms can be at most {} characters long",
+            MAX_ACRONYM_CHARS
+        ));
+    }
+    if !written_form.chars().all(char::is_alphanumeric) {
+        return Err(anyhow!(
+            "{:?} isn't a single word of letters and digits",
+            written_form
+        ));
+    }
+    Ok(written_form.to_string())
+}
+
+/// Whether `a` and `b` are the same acronym, in any casing, the way they
+/// are matched in the text
+pub fn same_acronym(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+/// Write every whole-word occurrence of an enabled acronym, in any casing,
+/// the way it is stored, and its plural with a lowercase "s" ("apis" →
+/// "APIs"). URLs, paths and code are left as they are.
+pub fn apply_acronyms(text: &str, acronyms: &[AiAcronym]) -> String {
+    let written: HashMap<String, &str> = acronyms
+        .iter()
+        .filter(|acronym| acronym.enabled)
+        .map(|acronym| {
+            (
+                acronym.written_form.to_lowercase(),
+                acronym.written_form.as_str(),
+            )
+        })
+        .collect();
+    if written.is_empty() {
+        return text.to_string();
+    }
+
+    let protected = protect_spans(text);
+    let mut fixed = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in protected.text().chars().chain(std::iter::once(' ')) {
+        if c.is_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+
+        fixed.push_str(&fix_word(&word, &written));
+        word.clear();
+        fixed.push(c);
+    }
+    fixed.pop();
+
+    protected
+        .restore(&fixed)
+        .unwrap_or_else(|_| text.to_string())
+}
+
+fn fix_word(word: &str, written: &HashMap<String, &str>) -> String {
+    let key = word.to_lowercase();
+    if let Some(acronym) = written.get(&key) {
+        return acronym.to_string();
+    }
+    // "its" and "ids" are words of their own
+    let plural = key
+        .strip_suffix('s')
+        .filter(|stem| {
+            !COMMON_WORD_ACRONYMS
+                .iter()
+                .any(|w| w.eq_ignore_ascii_case(stem))
+        })
+        .and_then(|stem| written.get(stem));
+    match plural {
+        Some(acronym) if !word.ends_with('S') || word == word.to_uppercase() => {
+            format!("{}s", acronym)
+        }
+        _ => word.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acronym(written_form: &str, enabled: bool) -> AiAcronym {
+        AiAcronym {
+            written_form: written_form.to_string(),
+            enabled,
+        }
+    }
+
+    #[test]
+    fn test_default_acronyms() {
+        let defaults = default_acronyms();
+        let cases = [
+            ("the api returns json", "the API returns JSON"),
+            ("Write the Sql query", "Write the SQL query"),
+            ("log in with oauth on ios", "log in with OAuth on iOS"),
+            ("two apis and three PDFs", "two APIs and three PDFs"),
+            ("(api), api. api's", "(API), API. API's"),
+            ("the gpu-bound job", "the GPU-bound job"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(apply_acronyms(input, &defaults), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_common_words_are_left_alone_by_default() {
+        let defaults = default_acronyms();
+        let cases = [
+            "it works for us",
+            "I am here",
+            "who is it",
+            "ram it into place",
+            "my id card",
+            "rapid apiary design",
+            "",
+        ];
+        for input in cases {
+            assert_eq!(apply_acronyms(input, &defaults), input, "{:?}", input);
+        }
+
+        // Until the user turns them on
+        let mut enabled = defaults;
+        for acronym in enabled.iter_mut() {
+            acronym.enabled = true;
+        }
+        assert_eq!(
+            apply_acronyms("it works for us, its ids too", &enabled),
+            "IT works for US, its ids too"
+        );
+    }
+
+    #[test]
+    fn test_user_acronyms() {
+        let acronyms = [acronym("KubeRA", true), acronym("TPS", false)];
+        assert_eq!(
+            apply_acronyms("ask kubera about the tps report", &acronyms),
+            "ask KubeRA about the tps report"
+        );
+    }
+
+    #[test]
+    fn test_leaves_urls_and_code_alone() {
+        let defaults = default_acronyms();
+        assert_eq!(
+            apply_acronyms(
+                "see https://api.example.com/json and `api.get()` api",
+                &defaults
+            ),
+            "see https://api.example.com/json and `api.get()` API"
+        );
+    }
+
+    #[test]
+    fn test_normalize_acronym() {
+        assert_eq!(normalize_acronym("  OAuth ").unwrap(), "OAuth");
+        assert_eq!(normalize_acronym("K8S").unwrap(), "K8S");
+        assert!(normalize_acronym("A").is_err());
+        assert!(normalize_acronym("TL;DR").is_err());
+        assert!(normalize_acronym("two words").is_err());
+        assert!(normalize_acronym(&"A".repeat(17)).is_err());
+    }
+
+    #[test]
+    fn test_same_acronym() {
+        assert!(same_acronym("OAuth", " oauth "));
+        assert!(!same_acronym("API", "APIs"));
+    }
+}
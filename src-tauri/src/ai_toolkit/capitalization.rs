@@ -0,0 +1,181 @@
+use crate::settings::AiCapitalizationStyle;
+
+/// Marks a sentence may open with before its first word
+const OPENING_MARKS: &[char] = &['"', '\'', '(', '[', '“', '‘', '«', '¿', '¡'];
+
+/// The English pronoun "I" on its own and in its contractions
+const PRONOUN_FORMS: &[&str] = &[
+    "i", "i'm", "i'll", "i've", "i'd", "i’m", "i’ll", "i’ve", "i’d",
+];
+
+/// Put the first letter of `text` and, in English text, each "i" standing
+/// alone in the case `style` asks for, whatever the model made of them. The
+/// first letter only changes where the rest of its word is lowercase, so
+/// "iPhone" and "API" keep their casing in every style.
+pub fn enforce_capitalization(text: &str, style: AiCapitalizationStyle, english: bool) -> String {
+    if style == AiCapitalizationStyle::Preserve {
+        return text.to_string();
+    }
+
+    let mut first = true;
+    text.split_inclusive(char::is_whitespace)
+        .map(|segment| {
+            let (word, space) = segment.split_at(segment.trim_end().len());
+            if word.is_empty() {
+                return segment.to_string();
+            }
+            let mut word = word.to_string();
+            if english {
+                word = case_pronoun(&word, style);
+            }
+            if first {
+                first = false;
+                word = case_first_letter(&word, style);
+            }
+            word + space
+        })
+        .collect()
+}
+
+/// `word` with "i" in the case of `style` when it is the pronoun
+pub fn case_pronoun(word: &str, style: AiCapitalizationStyle) -> String {
+    let core = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’');
+    if !PRONOUN_FORMS.contains(&core.to_lowercase().as_str()) {
+        return word.to_string();
+    }
+
+    let start = word.len()
+        - word
+            .trim_start_matches(|c: char| c != 'i' && c != 'I')
+            .len();
+    let pronoun = match style {
+        AiCapitalizationStyle::Lowercase => "i",
+        _ => "I",
+    };
+    format!("{}{}{}", &word[..start], pronoun, &word[start + 1..])
+}
+
+/// `word` with its first letter, after any opening mark, in the case of
+/// `style`
+pub fn case_first_letter(word: &str, style: AiCapitalizationStyle) -> String {
+    let start = word.len() - word.trim_start_matches(OPENING_MARKS).len();
+    let mut rest = word[start..].chars();
+    let Some(first) = rest.next().filter(|c| c.is_alphabetic()) else {
+        return word.to_string();
+    };
+    if !rest.filter(|c| c.is_alphabetic()).all(char::is_lowercase) {
+        return word.to_string();
+    }
+
+    let cased: String = match style {
+        AiCapitalizationStyle::Lowercase => first.to_lowercase().collect(),
+        _ => first.to_uppercase().collect(),
+    };
+    format!(
+        "{}{}{}",
+        &word[..start],
+        cased,
+        &word[start + first.len_utf8()..]
+    )
+}
+
+/// `text` with its first letter or digit in uppercase, after any opening
+/// mark, for text that starts a sentence
+pub fn capitalize_first(text: &str) -> String {
+    match text.char_indices().find(|(_, c)| c.is_alphanumeric()) {
+        Some((index, first)) => format!(
+            "{}{}{}",
+            &text[..index],
+            first.to_uppercase(),
+            &text[index + first.len_utf8()..]
+        ),
+        None => text.to_string(),
+    }
+}
+
+/// `replacement` in the casing of the `written` word it stands for: all
+/// capitals, capitalized or lowercase
+pub fn in_casing_of(written: &str, replacement: &str) -> String {
+    let letters = || written.chars().filter(|c| c.is_alphabetic());
+    if letters().count() > 1 && letters().all(char::is_uppercase) {
+        return replacement.to_uppercase();
+    }
+    if written.chars().next().is_some_and(char::is_uppercase) {
+        return capitalize_first(replacement);
+    }
+    replacement.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capitalizes_the_start_and_the_pronoun() {
+        let cases = [
+            ("so i think i'm done", "So I think I'm done"),
+            ("\"well,\" i said", "\"Well,\" I said"),
+            ("iPhone sales are up", "iPhone sales are up"),
+            ("Quarterly Results for I/O", "Quarterly Results for I/O"),
+            ("it's mine (i think).", "It's mine (I think)."),
+        ];
+        for (input, expected) in cases {
+            for style in [
+                AiCapitalizationStyle::Sentence,
+                AiCapitalizationStyle::Title,
+            ] {
+                assert_eq!(
+                    enforce_capitalization(input, style, true),
+                    expected,
+                    "{:?} in {:?}",
+                    input,
+                    style
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lowercases_the_start_and_the_pronoun() {
+        let cases = [
+            ("The model said I'm fine", "the model said i'm fine"),
+            ("API keys are rotated", "API keys are rotated"),
+            ("I think so", "i think so"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                enforce_capitalization(input, AiCapitalizationStyle::Lowercase, true),
+                expected,
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_keeps_the_pronoun_alone_outside_english() {
+        // "i" is "the" in Italian and "and" in Polish
+        assert_eq!(
+            enforce_capitalization("i ragazzi e i cani", AiCapitalizationStyle::Sentence, false),
+            "I ragazzi e i cani"
+        );
+    }
+
+    #[test]
+    fn test_preserve_and_layout() {
+        let text = "  hello i\n\nsecond  line ";
+        assert_eq!(
+            enforce_capitalization(text, AiCapitalizationStyle::Preserve, true),
+            text
+        );
+        assert_eq!(
+            enforce_capitalization(text, AiCapitalizationStyle::Sentence, true),
+            "  Hello I\n\nsecond  line "
+        );
+        // Placeholders for protected spans are left as they are
+        assert_eq!(
+            enforce_capitalization("⟦H1⟧ is the link", AiCapitalizationStyle::Lowercase, true),
+            "⟦H1⟧ is the link"
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use super::graphemes::{is_blank, trim, trim_end_graphemes, trim_start_graphemes};
+use super::ollama_client::OllamaError;
+use anyhow::Result;
+use log::warn;
+use std::future::Future;
+
+/// Rough token count, about four characters per token for English text
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Split `text` into chunks of at most `max_tokens` estimated tokens, breaking
+/// between sentences where possible and between words otherwise. The chunks
+/// are consecutive slices of `text`, so concatenating them gives it back.
+pub fn split_into_chunks(text: &str, max_tokens: usize) -> Vec<&str> {
+    let max_tokens = max_tokens.max(1);
+    let pieces = sentences(text).into_iter().flat_map(|sentence| {
+        if estimate_tokens(sentence) > max_tokens {
+            words(sentence)
+        } else {
+            vec![sentence]
+        }
+    });
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut end = 0;
+    for piece in pieces {
+        let piece_end = end + piece.len();
+        if end > start && estimate_tokens(&text[start..piece_end]) > max_tokens {
+            chunks.push(&text[start..end]);
+            start = end;
+        }
+        end = piece_end;
+    }
+    if end > start {
+        chunks.push(&text[start..end]);
+    }
+
+    chunks
+}
+
+/// Sentences of `text`, each with the whitespace that follows it. A period
+/// only ends a sentence when followed by whitespace, so "3.5" stays intact.
+pub fn sentences(text: &str) -> Vec<&str> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let ends_sentence = match c {
+            '\n' => true,
+            '.' | '!' | '?' | '…' => {
+                !matches!(chars.peek(), Some((_, next)) if !next.is_whitespace())
+            }
+            _ => false,
+        };
+        if !ends_sentence {
+            continue;
+        }
+
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+        spans.push(&text[start..end]);
+        start = end;
+    }
+    if start < text.len() {
+        spans.push(&text[start..]);
+    }
+
+    spans
+}
+
+/// Words of `text`, each with the whitespace that follows it
+fn words(text: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut in_space = false;
+
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() {
+            in_space = true;
+        } else if in_space {
+            pieces.push(&text[start..i]);
+            start = i;
+            in_space = false;
+        }
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+
+    pieces
+}
+
+/// Put `replacement` in place of the text of `original`, keeping the exact
+/// whitespace `original` starts and ends with. Only whitespace standing on
+/// its own counts, not a space carrying a combining mark.
+pub fn with_outer_whitespace(original: &str, replacement: &str) -> String {
+    let core = trim(original);
+    if core.is_empty() {
+        return original.to_string();
+    }
+
+    let leading = &original[..original.len() - trim_start_graphemes(original, is_blank).len()];
+    let trailing = &original[trim_end_graphemes(original, is_blank).len()..];
+    format!("{}{}{}", leading, trim(replacement), trailing)
+}
+
+/// Enhance `chunks` one after another and join the results. `enhance` gets the
+/// chunk index, the trimmed chunk text and the output produced so far.
+///
+/// A chunk that fails keeps its original text. The whole transcript fails only
+/// when every chunk did, or straight away when the model is missing.
+pub async fn enhance_chunks<'a, F, Fut>(chunks: &[&'a str], mut enhance: F) -> Result<String>
+where
+    F: FnMut(usize, &'a str, String) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let mut output = String::new();
+    let mut last_error = None;
+    let mut enhanced_any = false;
+
+    for (index, &chunk) in chunks.iter().enumerate() {
+        if chunk.trim().is_empty() {
+            output.push_str(chunk);
+            continue;
+        }
+
+        match enhance(index, chunk.trim(), output.clone()).await {
+            Ok(enhanced) => {
+                output.push_str(&with_outer_whitespace(chunk, &enhanced));
+                enhanced_any = true;
+            }
+            Err(e) => {
+                if matches!(
+                    e.downcast_ref::<OllamaError>(),
+                    Some(OllamaError::ModelNotFound { .. })
+                ) {
+                    return Err(e);
+                }
+                warn!(
+                    "Enhancing chunk {}/{} failed, keeping its original text: {}",
+                    index + 1,
+                    chunks.len(),
+                    e
+                );
+                output.push_str(chunk);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if !enhanced_any => Err(e),
+        _ => Ok(output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    const MAX_TOKENS: usize = 250;
+
+    /// A 1,500 word transcript with varied sentence lengths and punctuation
+    fn long_transcript() -> String {
+        let mut text = String::new();
+        let mut words = 0;
+        let mut i = 0;
+        while words < 1500 {
+            let sentence = match i % 4 {
+                0 => format!("So item {} costs about 3.5 dollars, order more. ", i),
+                1 => format!("Did you see the report on section {} yesterday? ", i),
+                2 => format!("The team in building {} did a great job!\n", i),
+                _ => format!("Let's follow up next week… maybe after {} meetings.  ", i),
+            };
+            words += sentence.split_whitespace().count();
+            text.push_str(&sentence);
+            i += 1;
+        }
+        text
+    }
+
+    #[test]
+    fn test_long_transcript_splits_losslessly_at_sentences() {
+        let text = long_transcript();
+        assert!(text.split_whitespace().count() >= 1500);
+
+        let chunks = split_into_chunks(&text, MAX_TOKENS);
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat(), text);
+
+        for chunk in &chunks {
+            assert!(estimate_tokens(chunk) <= MAX_TOKENS);
+            let last = chunk.trim_end().chars().last().unwrap();
+            assert!(
+                matches!(last, '.' | '?' | '!' | '…'),
+                "chunk ends mid-sentence: {:?}",
+                chunk
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_on_sentence_splits_between_words() {
+        let text = "and then we went ".repeat(200);
+
+        let chunks = split_into_chunks(&text, 50);
+
+        assert_eq!(chunks.concat(), text);
+        for chunk in &chunks {
+            assert!(estimate_tokens(chunk) <= 50);
+            assert!(chunk.ends_with(' '));
+        }
+    }
+
+    #[test]
+    fn test_rejoin_preserves_spacing_at_seams() {
+        let text = long_transcript();
+        let chunks = split_into_chunks(&text, MAX_TOKENS);
+
+        let joined = tauri::async_runtime::block_on(enhance_chunks(&chunks, |_, chunk, _| {
+            let upper = chunk.to_uppercase();
+            async move { Ok(upper) }
+        }))
+        .unwrap();
+
+        assert_eq!(joined, text.to_uppercase());
+    }
+
+    #[test]
+    fn test_outer_whitespace_round_trips() {
+        // The model's output, whitespace and all, in place of the transcript
+        let cases = [
+            ("see you soon ", "See you soon.\n", "See you soon. "),
+            ("see you soon\n", "  See you soon.", "See you soon.\n"),
+            ("    see you soon", "See you soon.", "    See you soon."),
+            ("\n\n", "Hello.", "\n\n"),
+        ];
+        for (original, output, expected) in cases {
+            assert_eq!(
+                with_outer_whitespace(original, output),
+                expected,
+                "{:?}",
+                original
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejoin_preserves_paragraphs() {
+        let text = "  first paragraph.\n\nsecond paragraph.\nthird line. \n";
+        let chunks = split_into_chunks(text, 4);
+        assert!(chunks.len() > 1);
+
+        let joined = tauri::async_runtime::block_on(enhance_chunks(&chunks, |_, chunk, _| {
+            let output = format!("\n{}\n", chunk.to_uppercase());
+            async move { Ok(output) }
+        }))
+        .unwrap();
+
+        assert_eq!(joined, text.to_uppercase());
+    }
+
+    #[test]
+    fn test_failed_chunk_keeps_original_text() {
+        let text = "First sentence here. Second sentence here. Third sentence here.";
+        let chunks = split_into_chunks(text, 6);
+        assert_eq!(chunks.len(), 3);
+
+        let joined = tauri::async_runtime::block_on(enhance_chunks(&chunks, |index, chunk, _| {
+            let chunk = chunk.to_string();
+            async move {
+                if index == 1 {
+                    Err(anyhow!("timed out"))
+                } else {
+                    Ok(chunk.replace("here", "there"))
+                }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            joined,
+            "First sentence there. Second sentence here. Third sentence there."
+        );
+    }
+
+    #[test]
+    fn test_fails_when_no_chunk_succeeds() {
+        let chunks = split_into_chunks("One two three. Four five six.", 4);
+
+        let result = tauri::async_runtime::block_on(enhance_chunks(&chunks, |_, _, _| async {
+            Err::<String, _>(anyhow!("connection refused"))
+        }));
+
+        assert!(result.is_err());
+    }
+}
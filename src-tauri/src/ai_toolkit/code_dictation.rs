@@ -0,0 +1,506 @@
+use super::repetitions::is_punctuation;
+use super::tokens::token_ranges;
+use std::ops::Range;
+
+/// How a written symbol sits between the words around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Spacing {
+    /// Between words, with a space on each side: `a = b`
+    Spaced,
+    /// Joining the words around it: `max_retries`, `config.load`
+    Joined,
+    /// Ending the word before it: `foo(x)`, `a, b`
+    Closing,
+    /// Starting the word after it: `--workspace`, `!done`
+    Prefix,
+    /// Opened before the words it quotes and closed after them: `"hi there"`
+    Quote,
+}
+
+/// Spoken symbols, each before the shorter ones it starts with so "equals
+/// equals" isn't read as two "equals"
+const SYMBOLS: &[(&[&str], &str, Spacing)] = &[
+    (&["equals", "equals"], "==", Spacing::Spaced),
+    (&["double", "equals"], "==", Spacing::Spaced),
+    (&["not", "equals"], "!=", Spacing::Spaced),
+    (&["plus", "equals"], "+=", Spacing::Spaced),
+    (&["minus", "equals"], "-=", Spacing::Spaced),
+    (&["less", "than"], "<", Spacing::Spaced),
+    (&["greater", "than"], ">", Spacing::Spaced),
+    (&["fat", "arrow"], "=>", Spacing::Spaced),
+    (&["and", "and"], "&&", Spacing::Spaced),
+    (&["pipe", "pipe"], "||", Spacing::Spaced),
+    (&["colon", "colon"], "::", Spacing::Joined),
+    (&["double", "colon"], "::", Spacing::Joined),
+    (&["open", "paren"], "(", Spacing::Joined),
+    (&["left", "paren"], "(", Spacing::Joined),
+    (&["close", "paren"], ")", Spacing::Closing),
+    (&["right", "paren"], ")", Spacing::Closing),
+    (&["open", "bracket"], "[", Spacing::Joined),
+    (&["close", "bracket"], "]", Spacing::Closing),
+    (&["open", "brace"], "{", Spacing::Spaced),
+    (&["close", "brace"], "}", Spacing::Spaced),
+    (&["dollar", "sign"], "$", Spacing::Prefix),
+    (&["at", "sign"], "@", Spacing::Prefix),
+    (&["double", "quote"], "\"", Spacing::Quote),
+    (&["single", "quote"], "'", Spacing::Quote),
+    (&["new", "line"], "\n", Spacing::Joined),
+    (&["equals"], "=", Spacing::Spaced),
+    (&["plus"], "+", Spacing::Spaced),
+    (&["minus"], "-", Spacing::Spaced),
+    (&["times"], "*", Spacing::Spaced),
+    (&["star"], "*", Spacing::Spaced),
+    (&["percent"], "%", Spacing::Spaced),
+    (&["caret"], "^", Spacing::Spaced),
+    (&["ampersand"], "&", Spacing::Spaced),
+    (&["pipe"], "|", Spacing::Spaced),
+    (&["arrow"], "->", Spacing::Spaced),
+    (&["underscore"], "_", Spacing::Joined),
+    (&["dot"], ".", Spacing::Joined),
+    (&["slash"], "/", Spacing::Joined),
+    (&["backslash"], "\\", Spacing::Joined),
+    (&["comma"], ",", Spacing::Closing),
+    (&["semicolon"], ";", Spacing::Closing),
+    (&["colon"], ":", Spacing::Closing),
+    (&["dash"], "-", Spacing::Prefix),
+    (&["bang"], "!", Spacing::Prefix),
+    (&["hash"], "#", Spacing::Prefix),
+    (&["tilde"], "~", Spacing::Prefix),
+    (&["quote"], "\"", Spacing::Quote),
+    (&["backtick"], "`", Spacing::Quote),
+];
+
+/// How the words after a casing command are joined into an identifier
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Casing {
+    Camel,
+    Pascal,
+    Snake,
+    Kebab,
+    Constant,
+}
+
+const CASINGS: &[(&[&str], Casing)] = &[
+    (&["screaming", "snake", "case"], Casing::Constant),
+    (&["constant", "case"], Casing::Constant),
+    (&["camel", "case"], Casing::Camel),
+    (&["pascal", "case"], Casing::Pascal),
+    (&["snake", "case"], Casing::Snake),
+    (&["kebab", "case"], Casing::Kebab),
+];
+
+/// Said after the words of an identifier, where the next word isn't a
+/// symbol or number that ends it anyway
+const END_CASE: &[&str] = &["end", "case"];
+
+/// Said before a word to write it as it is, like "literal dot" for "dot"
+const LITERAL: &str = "literal";
+
+const UNITS: &[&str] = &[
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: &[&str] = &[
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// Punctuation the transcriber adds around words, which in code has to be
+/// said to be written
+const SENTENCE_PUNCTUATION: &[char] = &['.', ',', '?', '!', ';', ':'];
+
+/// A whitespace-separated word of the transcript: as written without the
+/// punctuation the transcriber added, the lowercase form it is compared by,
+/// and whether the transcriber ended a sentence with it
+struct Word<'a> {
+    text: &'a str,
+    key: String,
+    ends_sentence: bool,
+    /// Whether the transcriber put any punctuation after it
+    pauses: bool,
+    /// Whether a line break comes before it
+    on_new_line: bool,
+}
+
+/// Written text and whether it takes a space before and after it
+struct Piece {
+    text: String,
+    before: bool,
+    after: bool,
+}
+
+impl Piece {
+    fn word(text: String) -> Self {
+        Self {
+            text,
+            before: true,
+            after: true,
+        }
+    }
+}
+
+/// Write code dictated as words: spoken symbols as the symbols ("max
+/// retries underscore count" as `max retries_count`), number words as
+/// digits and casing commands as identifiers ("camel case foo bar" as
+/// `fooBar`). The punctuation and capitals the transcriber adds as if it
+/// were prose are dropped, so only what was said is written. "literal"
+/// writes the word after it as it is.
+///
+/// Casing takes the words after the command up to a symbol, a number,
+/// another command or "end case", and a command with none is kept as said.
+pub fn write_code(text: &str) -> String {
+    let words = words(text);
+    let mut pieces: Vec<Piece> = vec![];
+    let mut open_quotes: Vec<&str> = vec![];
+    let mut sentence_start = true;
+
+    let mut i = 0;
+    while i < words.len() {
+        if i > 0 && words[i].on_new_line {
+            pieces.push(joined("\n"));
+        }
+
+        if words[i].key == LITERAL && i + 1 < words.len() {
+            pieces.push(Piece::word(words[i + 1].text.to_string()));
+            i += 2;
+        } else if let Some((casing, len)) = casing_at(&words, i) {
+            let command = i..i + len;
+            i += len;
+            let mut parts = vec![];
+            while i < words.len() && !ends_identifier(&words, i) {
+                parts.push(words[i].key.as_str());
+                i += 1;
+                if words[i - 1].pauses {
+                    break;
+                }
+            }
+            if words
+                .get(i..i + END_CASE.len())
+                .is_some_and(|said| said_as(said, END_CASE))
+            {
+                i += END_CASE.len();
+            }
+            if parts.is_empty() {
+                // Nothing to case, so the command was only words
+                pieces.extend(
+                    words[command]
+                        .iter()
+                        .map(|word| Piece::word(word.key.clone())),
+                );
+            } else {
+                pieces.push(Piece::word(identifier(&parts, casing)));
+            }
+        } else if let Some((symbol, spacing, len)) = symbol_at(&words, i) {
+            let (before, after) = match spacing {
+                Spacing::Spaced => (true, true),
+                Spacing::Joined => (false, false),
+                Spacing::Closing => (false, true),
+                Spacing::Prefix => (true, false),
+                Spacing::Quote if open_quotes.last() == Some(&symbol) => {
+                    open_quotes.pop();
+                    (false, true)
+                }
+                Spacing::Quote => {
+                    open_quotes.push(symbol);
+                    (true, false)
+                }
+            };
+            pieces.push(Piece {
+                text: symbol.to_string(),
+                before,
+                after,
+            });
+            i += len;
+        } else if let Some((number, len)) = number_at(&words, i) {
+            pieces.push(Piece::word(number.to_string()));
+            i += len;
+        } else {
+            let word = words[i].text;
+            let word = if sentence_start && is_capitalized(word) {
+                word.to_lowercase()
+            } else {
+                word.to_string()
+            };
+            pieces.push(Piece::word(word));
+            i += 1;
+        }
+        sentence_start = words[i - 1].ends_sentence;
+    }
+
+    let mut code = String::new();
+    let mut space_after = false;
+    for piece in pieces {
+        if space_after && piece.before && !code.is_empty() && !code.ends_with('\n') {
+            code.push(' ');
+        }
+        code.push_str(&piece.text);
+        space_after = piece.after;
+    }
+
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    format!("{}{}{}", leading, code, trailing)
+}
+
+/// Byte ranges of the words of `text` that aren't plain words, like
+/// symbols, numbers and identifiers, which the model has no business
+/// changing in code
+pub fn code_spans(text: &str) -> Vec<Range<usize>> {
+    token_ranges(text)
+        .filter(|range| !text[range.clone()].chars().all(char::is_alphabetic))
+        .collect()
+}
+
+fn joined(text: &str) -> Piece {
+    Piece {
+        text: text.to_string(),
+        before: false,
+        after: false,
+    }
+}
+
+/// Whether `said` is the phrase `phrase`, without punctuation breaking it
+/// up
+fn said_as(said: &[Word], phrase: &[&str]) -> bool {
+    said.len() == phrase.len()
+        && said.iter().zip(phrase).all(|(word, key)| word.key == *key)
+        && said[..said.len() - 1].iter().all(|word| !word.pauses)
+}
+
+fn symbol_at(words: &[Word], i: usize) -> Option<(&'static str, Spacing, usize)> {
+    SYMBOLS
+        .iter()
+        .find(|(phrase, _, _)| {
+            words
+                .get(i..i + phrase.len())
+                .is_some_and(|said| said_as(said, phrase))
+        })
+        .map(|(phrase, symbol, spacing)| (*symbol, *spacing, phrase.len()))
+}
+
+fn casing_at(words: &[Word], i: usize) -> Option<(Casing, usize)> {
+    CASINGS
+        .iter()
+        .find(|(phrase, _)| {
+            words
+                .get(i..i + phrase.len())
+                .is_some_and(|said| said_as(said, phrase))
+        })
+        .map(|(phrase, casing)| (*casing, phrase.len()))
+}
+
+/// The number said at `words[i]`, as in "five" or "twenty five", and how
+/// many words it takes up
+fn number_at(words: &[Word], i: usize) -> Option<(usize, usize)> {
+    let key = words[i].key.as_str();
+    if let Some(unit) = UNITS.iter().position(|unit| *unit == key) {
+        return Some((unit, 1));
+    }
+    let tens = TENS.iter().position(|tens| *tens == key)?;
+    let tens = (tens + 2) * 10;
+    let unit = words
+        .get(i + 1)
+        .filter(|_| !words[i].pauses)
+        .and_then(|next| UNITS[1..10].iter().position(|unit| *unit == next.key));
+    match unit {
+        Some(unit) => Some((tens + unit + 1, 2)),
+        None => Some((tens, 1)),
+    }
+}
+
+/// Whether the words of an identifier end before `words[i]`
+fn ends_identifier(words: &[Word], i: usize) -> bool {
+    words[i].key == LITERAL
+        || words[i].on_new_line
+        || symbol_at(words, i).is_some()
+        || number_at(words, i).is_some()
+        || casing_at(words, i).is_some()
+        || words
+            .get(i..i + END_CASE.len())
+            .is_some_and(|said| said_as(said, END_CASE))
+}
+
+fn identifier(parts: &[&str], casing: Casing) -> String {
+    let capitalized = |part: &&str| {
+        let mut chars = part.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    match casing {
+        Casing::Camel => {
+            let rest: String = parts[1..].iter().map(capitalized).collect();
+            format!("{}{}", parts[0], rest)
+        }
+        Casing::Pascal => parts.iter().map(capitalized).collect(),
+        Casing::Snake => parts.join("_"),
+        Casing::Kebab => parts.join("-"),
+        Casing::Constant => parts.join("_").to_uppercase(),
+    }
+}
+
+/// A word the transcriber capitalized for starting a sentence, rather than
+/// one written in capitals on purpose like "API"
+fn is_capitalized(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(char::is_uppercase) && chars.all(char::is_lowercase)
+}
+
+fn words(text: &str) -> Vec<Word<'_>> {
+    let mut words: Vec<Word> = vec![];
+    let mut end = 0;
+    for range in token_ranges(text) {
+        let raw = &text[range.clone()];
+        let written = raw.trim_end_matches(SENTENCE_PUNCTUATION);
+        // A word that is all punctuation, like "...", is kept as it is
+        let written = if written.is_empty() { raw } else { written };
+        words.push(Word {
+            text: written,
+            key: raw.trim_matches(is_punctuation).to_lowercase(),
+            ends_sentence: raw[written.len()..].contains(['.', '?', '!']),
+            pauses: written.len() < raw.len(),
+            on_new_line: text[end..range.start].contains('\n'),
+        });
+        end = range.end;
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spoken_symbols() {
+        for (said, written) in [
+            (
+                "set max retries underscore count to five",
+                "set max retries_count to 5",
+            ),
+            ("x equals y plus one", "x = y + 1"),
+            ("a equals equals b", "a == b"),
+            ("a not equals b", "a != b"),
+            ("count plus equals twenty five", "count += 25"),
+            (
+                "if a less than b and and c greater than d",
+                "if a < b && c > d",
+            ),
+            ("print open paren x comma y close paren", "print(x, y)"),
+            ("items open bracket zero close bracket", "items[0]"),
+            ("config dot load open paren close paren", "config.load()"),
+            ("std colon colon env", "std::env"),
+            ("src slash main dot rs", "src/main.rs"),
+            ("cargo test dash dash workspace", "cargo test --workspace"),
+            (
+                "git commit dash m quote fix the build quote",
+                "git commit -m \"fix the build\"",
+            ),
+            ("echo dollar sign home", "echo $home"),
+            ("cat log pipe grep error", "cat log | grep error"),
+            ("bang done", "!done"),
+            (
+                "fn main open paren close paren arrow result",
+                "fn main() -> result",
+            ),
+            ("x fat arrow x times two", "x => x * 2"),
+            ("let a equals b semicolon", "let a = b;"),
+            ("key colon value", "key: value"),
+            ("open brace a colon one close brace", "{ a: 1 }"),
+            ("single quote hi single quote", "'hi'"),
+            ("run backtick make backtick", "run `make`"),
+            ("c colon backslash users", "c:\\users"),
+            ("at sign override", "@override"),
+            ("hash include", "#include"),
+            ("tilde slash code", "~/code"),
+            ("a percent b caret c", "a % b ^ c"),
+            ("x minus equals one", "x -= 1"),
+        ] {
+            assert_eq!(write_code(said), written, "{:?}", said);
+        }
+    }
+
+    #[test]
+    fn test_casing_commands() {
+        for (said, written) in [
+            ("camel case foo bar", "fooBar"),
+            ("pascal case user account", "UserAccount"),
+            ("snake case max retries count", "max_retries_count"),
+            ("kebab case main menu", "main-menu"),
+            ("screaming snake case max size", "MAX_SIZE"),
+            ("constant case api key", "API_KEY"),
+            (
+                "let camel case user name equals none",
+                "let userName = none",
+            ),
+            (
+                "snake case retry count end case to three",
+                "retry_count to 3",
+            ),
+            ("camel case is ready open paren close paren", "isReady()"),
+            ("Camel case, foo bar.", "fooBar"),
+            ("camel case", "camel case"),
+            ("Snake case.", "snake case"),
+            ("x equals snake case", "x = snake case"),
+            (
+                "pascal case http client dot camel case send request",
+                "HttpClient.sendRequest",
+            ),
+        ] {
+            assert_eq!(write_code(said), written, "{:?}", said);
+        }
+    }
+
+    #[test]
+    fn test_transcriber_punctuation_is_dropped() {
+        for (said, written) in [
+            ("Set max retries to five.", "set max retries to 5"),
+            ("Import os. Import sys.", "import os import sys"),
+            ("Print open paren, x, close paren.", "print(x)"),
+            ("call the API dot get", "call the API.get"),
+            ("open paren...", "("),
+        ] {
+            assert_eq!(write_code(said), written, "{:?}", said);
+        }
+    }
+
+    #[test]
+    fn test_literal_words_and_lines() {
+        assert_eq!(write_code("literal dot files"), "dot files");
+        assert_eq!(write_code("literal equals sign"), "equals sign");
+        assert_eq!(
+            write_code("x equals one new line y equals two"),
+            "x = 1\ny = 2"
+        );
+        assert_eq!(write_code("x equals one\ny equals two"), "x = 1\ny = 2");
+        assert_eq!(write_code(" ls dash la "), " ls -la ");
+        assert_eq!(write_code(""), "");
+    }
+
+    #[test]
+    fn test_code_spans() {
+        let code = "set max_retries_count = 5 for users";
+        let spans: Vec<&str> = code_spans(code)
+            .into_iter()
+            .map(|span| &code[span])
+            .collect();
+        assert_eq!(spans, vec!["max_retries_count", "=", "5"]);
+    }
+}
@@ -0,0 +1,284 @@
+use super::capitalization::{capitalize_first, in_casing_of};
+use crate::settings::AiContractionStyle;
+use std::ops::Range;
+
+/// Negations written either way, with what they stand for. "Can't" stands
+/// for "cannot", so "can not" as in "can not only" is never contracted.
+const NEGATIONS: &[(&str, &str)] = &[
+    ("don't", "do not"),
+    ("doesn't", "does not"),
+    ("didn't", "did not"),
+    ("isn't", "is not"),
+    ("aren't", "are not"),
+    ("wasn't", "was not"),
+    ("weren't", "were not"),
+    ("haven't", "have not"),
+    ("hasn't", "has not"),
+    ("hadn't", "had not"),
+    ("won't", "will not"),
+    ("wouldn't", "would not"),
+    ("can't", "cannot"),
+    ("couldn't", "could not"),
+    ("shouldn't", "should not"),
+    ("mustn't", "must not"),
+    ("needn't", "need not"),
+];
+
+/// Contractions of a pronoun, which are only ever written out by rule: the
+/// long form can't always be contracted ("I know who you are" is no "who
+/// you're"). Those ending in "'s" and "'d" stand for two things ("it's" is
+/// "it is" or "it has") and are left to the model, as is "let's".
+const PRONOUN_CONTRACTIONS: &[(&str, &str)] = &[
+    ("i'm", "i am"),
+    ("you're", "you are"),
+    ("we're", "we are"),
+    ("they're", "they are"),
+    ("i've", "i have"),
+    ("you've", "you have"),
+    ("we've", "we have"),
+    ("they've", "they have"),
+    ("i'll", "i will"),
+    ("you'll", "you will"),
+    ("he'll", "he will"),
+    ("she'll", "she will"),
+    ("it'll", "it will"),
+    ("we'll", "we will"),
+    ("they'll", "they will"),
+    ("that'll", "that will"),
+];
+
+/// Write the contractions of `text` in `style`, for those the model missed:
+/// "don't" as "do not" when expanding, and "do not" as "don't" when
+/// contracting. Only the unambiguous ones of [`NEGATIONS`] and
+/// [`PRONOUN_CONTRACTIONS`] are touched, so a possessive like "John's" never
+/// is, and neither is anything inside double quotes, which is someone's own
+/// words.
+pub fn apply_contraction_style(text: &str, style: AiContractionStyle) -> String {
+    if style == AiContractionStyle::Preserve {
+        return text.to_string();
+    }
+    let quoted = quoted_ranges(text);
+    let words: Vec<Range<usize>> = words(text)
+        .into_iter()
+        .filter(|word| !quoted.iter().any(|quote| quote.contains(&word.start)))
+        .collect();
+
+    let mut result = String::with_capacity(text.len());
+    let mut end = 0;
+    let mut i = 0;
+    while i < words.len() {
+        let found = match style {
+            AiContractionStyle::Expand => expansion(&text[words[i].clone()]).map(|long| (long, 1)),
+            AiContractionStyle::Contract => contraction(text, &words[i..]),
+            AiContractionStyle::Preserve => None,
+        };
+        let Some((replacement, taken)) = found else {
+            i += 1;
+            continue;
+        };
+        let range = words[i].start..words[i + taken - 1].end;
+        result.push_str(&text[end..range.start]);
+        result.push_str(&cased_like(&text[range.clone()], replacement));
+        end = range.end;
+        i += taken;
+    }
+    result.push_str(&text[end..]);
+    result
+}
+
+/// What the contraction `word` stands for, if it is one written out by rule
+fn expansion(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase().replace('’', "'");
+    NEGATIONS
+        .iter()
+        .chain(PRONOUN_CONTRACTIONS)
+        .find(|(short, _)| *short == word)
+        .map(|(_, long)| *long)
+}
+
+/// The negation the first words of `words` in `text` contract to, and how
+/// many words it takes. Its words have to be apart by a single space, so a
+/// line break or a comma is never contracted across.
+fn contraction(text: &str, words: &[Range<usize>]) -> Option<(&'static str, usize)> {
+    NEGATIONS.iter().find_map(|(short, long)| {
+        let parts: Vec<&str> = long.split(' ').collect();
+        let taken = words.get(..parts.len())?;
+        let adjacent = taken
+            .windows(2)
+            .all(|pair| &text[pair[0].end..pair[1].start] == " ");
+        let matches = taken
+            .iter()
+            .zip(&parts)
+            .all(|(word, part)| text[word.clone()].to_lowercase() == *part);
+        (adjacent && matches).then_some((*short, parts.len()))
+    })
+}
+
+/// Byte ranges of `text` between double quotes, straight or curly. An
+/// unclosed quote runs to the end of the text.
+fn quoted_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut open = None;
+    for (index, c) in text.char_indices() {
+        match (c, open) {
+            ('"', None) | ('“', None) => open = Some(index),
+            ('"', Some(start)) | ('”', Some(start)) => {
+                ranges.push(start..index);
+                open = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = open {
+        ranges.push(start..text.len());
+    }
+    ranges
+}
+
+/// Byte ranges of the words of `text`: letters, with the apostrophes
+/// between them, so that the quote marks around 'don't' aren't part of it
+fn words(text: &str) -> Vec<Range<usize>> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut words = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].1.is_alphabetic() {
+            i += 1;
+            continue;
+        }
+        let start = chars[i].0;
+        let mut j = i + 1;
+        while let Some(&(_, c)) = chars.get(j) {
+            let inner_apostrophe = matches!(c, '\'' | '’')
+                && chars
+                    .get(j + 1)
+                    .is_some_and(|(_, next)| next.is_alphabetic());
+            if !c.is_alphabetic() && !inner_apostrophe {
+                break;
+            }
+            j += 1;
+        }
+        let end = chars.get(j).map_or(text.len(), |(index, _)| *index);
+        words.push(start..end);
+        i = j;
+    }
+    words
+}
+
+/// `replacement` in the casing of `written`, with "I" always a capital
+fn cased_like(written: &str, replacement: &str) -> String {
+    let cased = in_casing_of(written, replacement);
+    if cased.starts_with("i ") || cased.starts_with("i'") {
+        capitalize_first(&cased)
+    } else {
+        cased
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_contraction_tables_are_distinct() {
+        let mut seen = HashSet::new();
+        for (short, long) in NEGATIONS.iter().chain(PRONOUN_CONTRACTIONS) {
+            assert!(short.contains('\''), "{}", short);
+            assert!(
+                !short.ends_with("'s") && !short.ends_with("'d"),
+                "{}",
+                short
+            );
+            assert!(seen.insert(*short), "{} is listed twice", short);
+            assert!(seen.insert(*long), "{} is listed twice", long);
+        }
+    }
+
+    #[test]
+    fn test_expands_contractions() {
+        assert_eq!(
+            apply_contraction_style(
+                "I'm sure we won't miss it, but they can't come and I don't know why.",
+                AiContractionStyle::Expand
+            ),
+            "I am sure we will not miss it, but they cannot come and I do not know why."
+        );
+        assert_eq!(
+            apply_contraction_style(
+                "Don’t go. DON'T! You've seen it",
+                AiContractionStyle::Expand
+            ),
+            "Do not go. DO NOT! You have seen it"
+        );
+    }
+
+    #[test]
+    fn test_contracts_negations() {
+        assert_eq!(
+            apply_contraction_style(
+                "I do not think it is not ready, we cannot wait and Will not.",
+                AiContractionStyle::Contract
+            ),
+            "I don't think it isn't ready, we can't wait and Won't."
+        );
+        // Never across a comma or a line break, nor a pronoun's long form
+        for text in [
+            "Do, not that",
+            "we do\nnot",
+            "I know who you are",
+            "I am here",
+        ] {
+            assert_eq!(
+                apply_contraction_style(text, AiContractionStyle::Contract),
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_possessives_and_ambiguous_forms_are_kept() {
+        let text = "John's book isn't here, it's late and he'd left, let's go to James' place.";
+        assert_eq!(
+            apply_contraction_style(text, AiContractionStyle::Expand),
+            "John's book is not here, it's late and he'd left, let's go to James' place."
+        );
+        let text = "It is John's and he had left";
+        assert_eq!(
+            apply_contraction_style(text, AiContractionStyle::Contract),
+            text
+        );
+    }
+
+    #[test]
+    fn test_quotes() {
+        // Double quotes are someone's own words
+        let text = "She said \"don't touch it\" and didn't. He wrote “we do not”";
+        assert_eq!(
+            apply_contraction_style(text, AiContractionStyle::Expand),
+            "She said \"don't touch it\" and did not. He wrote “we do not”"
+        );
+        assert_eq!(
+            apply_contraction_style(text, AiContractionStyle::Contract),
+            text
+        );
+        // Single quotes around a negation aren't mistaken for its apostrophe
+        assert_eq!(
+            apply_contraction_style("the 'don't' sign", AiContractionStyle::Expand),
+            "the 'do not' sign"
+        );
+        assert_eq!(
+            apply_contraction_style("the 'do not' sign", AiContractionStyle::Contract),
+            "the 'don't' sign"
+        );
+    }
+
+    #[test]
+    fn test_preserve_changes_nothing() {
+        let text = "I don't know, we do not care";
+        assert_eq!(
+            apply_contraction_style(text, AiContractionStyle::Preserve),
+            text
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use super::graphemes::{is_unspaced_script, to_nfc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Most tokens (words, spaces and punctuation marks) either text may have to
+/// be diffed
+const MAX_DIFF_TOKENS: usize = 2000;
+
+/// Most tokens the texts may differ by before the diff is given up on. Past
+/// this the text was rewritten rather than corrected, and a diff of it isn't
+/// worth reading.
+const MAX_DIFF_EDITS: usize = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    Equal,
+    /// Only in the enhanced text
+    Insert,
+    /// Only in the original text
+    Delete,
+}
+
+/// A run of text that both texts share, or that only one of them has
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct DiffOp {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// Word-level diff turning `original` into `enhanced`. Punctuation marks and
+/// runs of whitespace are tokens of their own, so an added comma shows up as
+/// just the comma. Both are compared in NFC, so an accent composed one way
+/// in one and the other way in the other isn't a change. `None` when the
+/// texts are too long or too different for the diff to be cheap or useful.
+pub fn diff_words(original: &str, enhanced: &str) -> Option<Vec<DiffOp>> {
+    let (original, enhanced) = (to_nfc(original), to_nfc(enhanced));
+    let old = tokens(&original);
+    let new = tokens(&enhanced);
+    if old.len() > MAX_DIFF_TOKENS || new.len() > MAX_DIFF_TOKENS {
+        return None;
+    }
+
+    let mut ops: Vec<DiffOp> = Vec::new();
+    let mut deleted = String::new();
+    let mut inserted = String::new();
+    for (kind, token) in shortest_edit(&old, &new)? {
+        match kind {
+            DiffKind::Delete => deleted.push_str(token),
+            DiffKind::Insert => inserted.push_str(token),
+            DiffKind::Equal => {
+                flush_changes(&mut ops, &mut deleted, &mut inserted);
+                push_op(&mut ops, DiffKind::Equal, token);
+            }
+        }
+    }
+    flush_changes(&mut ops, &mut deleted, &mut inserted);
+    Some(ops)
+}
+
+/// Add the pending change, removals before additions
+fn flush_changes(ops: &mut Vec<DiffOp>, deleted: &mut String, inserted: &mut String) {
+    if !deleted.is_empty() {
+        push_op(ops, DiffKind::Delete, deleted);
+        deleted.clear();
+    }
+    if !inserted.is_empty() {
+        push_op(ops, DiffKind::Insert, inserted);
+        inserted.clear();
+    }
+}
+
+fn push_op(ops: &mut Vec<DiffOp>, kind: DiffKind, text: &str) {
+    match ops.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => ops.push(DiffOp {
+            kind,
+            text: text.to_string(),
+        }),
+    }
+}
+
+/// Myers' algorithm: the shortest list of token deletions and insertions
+/// turning `old` into `new`, with the tokens kept between them, or `None`
+/// past `MAX_DIFF_EDITS`
+fn shortest_edit<'a>(old: &[&'a str], new: &[&'a str]) -> Option<Vec<(DiffKind, &'a str)>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m).min(MAX_DIFF_EDITS as isize);
+    let offset = max + 1;
+    let index = |k: isize| (k + offset) as usize;
+
+    // Furthest x reached on each diagonal k = x - y, before each round
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let mut done = false;
+    for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let down = k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]);
+            let mut x = if down {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                done = true;
+                break;
+            }
+        }
+        if done {
+            break;
+        }
+    }
+    if !done {
+        return None;
+    }
+
+    // Walk back from the end through the rounds to recover the edits
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let down = k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]);
+        let prev_k = if down { k + 1 } else { k - 1 };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            edits.push((DiffKind::Equal, old[x as usize - 1]));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push((DiffKind::Insert, new[y as usize - 1]));
+            } else {
+                edits.push((DiffKind::Delete, old[x as usize - 1]));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    Some(edits)
+}
+
+/// Runs of letters and digits, runs of whitespace and single other grapheme
+/// clusters, none of which is ever split, so an accent stays on its letter
+/// and a joined emoji stays whole. Each character of a script written
+/// without spaces, like Chinese, is a word of its own, so that changing one
+/// doesn't mark a whole sentence changed.
+fn tokens(text: &str) -> Vec<&str> {
+    let class = |grapheme: &str| {
+        let first = grapheme.chars().next().unwrap_or_default();
+        if is_unspaced_script(first) {
+            3
+        } else if first.is_alphanumeric() {
+            0
+        } else if grapheme.chars().all(char::is_whitespace) {
+            1
+        } else {
+            2
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous = None;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        let current = class(grapheme);
+        if i > start && (previous != Some(current) || current >= 2) {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        previous = Some(current);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::unicode_samples::{has_split_clusters, sample_texts};
+
+    fn op(kind: DiffKind, text: &str) -> DiffOp {
+        DiffOp {
+            kind,
+            text: text.to_string(),
+        }
+    }
+
+    /// The original, then the enhanced text, put back together from `ops`
+    fn sides(ops: &[DiffOp]) -> (String, String) {
+        let side = |skipped: DiffKind| {
+            ops.iter()
+                .filter(|op| op.kind != skipped)
+                .map(|op| op.text.as_str())
+                .collect()
+        };
+        (side(DiffKind::Insert), side(DiffKind::Delete))
+    }
+
+    #[test]
+    fn test_punctuation_only_changes() {
+        let ops = diff_words("so we ship it", "So, we ship it.").unwrap();
+        assert_eq!(
+            ops,
+            vec![
+                op(DiffKind::Delete, "so"),
+                op(DiffKind::Insert, "So,"),
+                op(DiffKind::Equal, " we ship it"),
+                op(DiffKind::Insert, "."),
+            ]
+        );
+
+        let ops = diff_words("wait, what", "wait… what?").unwrap();
+        assert_eq!(
+            ops,
+            vec![
+                op(DiffKind::Equal, "wait"),
+                op(DiffKind::Delete, ","),
+                op(DiffKind::Insert, "…"),
+                op(DiffKind::Equal, " what"),
+                op(DiffKind::Insert, "?"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_case_only_changes() {
+        let ops = diff_words("ask john about the api", "Ask John about the API").unwrap();
+        assert_eq!(
+            ops,
+            vec![
+                op(DiffKind::Delete, "ask"),
+                op(DiffKind::Insert, "Ask"),
+                op(DiffKind::Equal, " "),
+                op(DiffKind::Delete, "john"),
+                op(DiffKind::Insert, "John"),
+                op(DiffKind::Equal, " about the "),
+                op(DiffKind::Delete, "api"),
+                op(DiffKind::Insert, "API"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reordered_words() {
+        let original = "send it to me tomorrow";
+        let enhanced = "Tomorrow, send it to me.";
+        let ops = diff_words(original, enhanced).unwrap();
+
+        assert_eq!(sides(&ops), (original.to_string(), enhanced.to_string()));
+        // The words that kept their order are shared, the moved one isn't
+        assert!(ops.contains(&op(DiffKind::Equal, "send it to me")));
+        assert!(ops.contains(&op(DiffKind::Delete, " tomorrow")));
+    }
+
+    #[test]
+    fn test_identical_and_empty_texts() {
+        assert_eq!(
+            diff_words("all good", "all good").unwrap(),
+            vec![op(DiffKind::Equal, "all good")]
+        );
+        assert_eq!(diff_words("", "").unwrap(), vec![]);
+        assert_eq!(
+            diff_words("", "Hi.").unwrap(),
+            vec![op(DiffKind::Insert, "Hi.")]
+        );
+    }
+
+    #[test]
+    fn test_clusters_and_cjk_characters() {
+        let ops = diff_words("see you there 👨‍👩‍👧", "See you there 👨‍👩‍👧‍👦!").unwrap();
+        assert!(ops.contains(&op(DiffKind::Delete, "👨‍👩‍👧")));
+        assert!(ops.contains(&op(DiffKind::Insert, "👨‍👩‍👧‍👦!")));
+
+        // A changed character of Japanese is all that changed
+        let ops = diff_words("東京に行きます", "東京へ行きます。").unwrap();
+        assert_eq!(
+            ops,
+            vec![
+                op(DiffKind::Equal, "東京"),
+                op(DiffKind::Delete, "に"),
+                op(DiffKind::Insert, "へ"),
+                op(DiffKind::Equal, "行きます"),
+                op(DiffKind::Insert, "。"),
+            ]
+        );
+
+        // Composed or not, the accent is the same
+        assert_eq!(
+            diff_words("cafe\u{301} au lait", "café au lait").unwrap(),
+            vec![op(DiffKind::Equal, "café au lait")]
+        );
+    }
+
+    #[test]
+    fn test_random_texts_diff_in_whole_clusters() {
+        let texts = sample_texts(200, 16);
+        for pair in texts.windows(2) {
+            let Some(ops) = diff_words(&pair[0], &pair[1]) else {
+                continue;
+            };
+            assert_eq!(sides(&ops), (to_nfc(&pair[0]), to_nfc(&pair[1])));
+            for op in &ops {
+                assert!(!has_split_clusters(&op.text), "{:?} in {:?}", op, pair);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gives_up_on_long_or_rewritten_texts() {
+        let long = "word ".repeat(MAX_DIFF_TOKENS);
+        assert!(diff_words(&long, &long).is_none());
+
+        let original = "a ".repeat(MAX_DIFF_EDITS);
+        let rewritten = "b ".repeat(MAX_DIFF_EDITS);
+        assert!(diff_words(&original, &rewritten).is_none());
+        assert!(diff_words(&original, &original).is_some());
+    }
+}
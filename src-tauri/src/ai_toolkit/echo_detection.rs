@@ -0,0 +1,278 @@
+use super::output_guard::{OutputCheck, RejectedOutput};
+use anyhow::Result;
+use log::info;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Openers models put in front of the corrected text, matched at the start
+/// of the output
+const PREAMBLES: &[&str] = &[
+    "sure",
+    "certainly",
+    "of course",
+    "absolutely",
+    "okay",
+    "ok",
+    "here is",
+    "here's",
+    "here are",
+    "below is",
+    "the corrected text",
+    "the corrected version",
+    "i have corrected",
+    "i've corrected",
+    "i corrected",
+];
+
+/// Openers of commentary models add after the corrected text. Everything
+/// from the first such line on is dropped.
+const EXPLANATIONS: &[&str] = &[
+    "note",
+    "explanation",
+    "changes made",
+    "changes",
+    "corrections made",
+    "i have",
+    "i've",
+    "i made",
+    "i corrected",
+    "i fixed",
+    "i removed",
+    "i changed",
+    "i kept",
+    "let me know",
+    "i hope",
+    "this corrected",
+    "the corrected text",
+    "the text has been",
+];
+
+/// Prompt lines shorter than this are too generic to count as echoed
+const MIN_ECHO_CHARS: usize = 12;
+
+/// Share of the remaining output's words that must also occur in the
+/// transcript for it to count as a correction once an echo was removed
+const MIN_SHARED_WORDS: f64 = 0.5;
+
+/// Outputs with an echo removed since startup, for tuning the prompt
+static ECHOES_DETECTED: AtomicU64 = AtomicU64::new(0);
+
+/// Remove text the model echoed instead of just correcting `input`: a
+/// preamble like "Sure! Here is the corrected text:", lines copied from the
+/// instructions in `prompt` and explanations after the corrected text. Lines
+/// that start the way a line of the transcript does are always kept.
+///
+/// Fails with [`RejectedOutput`] when nothing related to the transcript is
+/// left once the echo is gone.
+pub fn remove_echo(input: &str, prompt: &str, output: &str) -> Result<String> {
+    let transcript = normalize(input);
+    let mut lines: Vec<&str> = output.lines().collect();
+
+    let mut echoed = strip_preambles(&mut lines, &transcript);
+    echoed |= strip_instruction_lines(&mut lines, &transcript, &prompt.replacen(input, "", 1));
+    echoed |= strip_explanations(&mut lines, &transcript);
+
+    let remainder = lines.join("\n").trim().to_string();
+    if !echoed {
+        return Ok(remainder);
+    }
+
+    let count = ECHOES_DETECTED.fetch_add(1, Ordering::Relaxed) + 1;
+    info!(
+        "Removed prompt echo from model output ({} this session)",
+        count
+    );
+
+    if shared_word_ratio(&remainder, &transcript) < MIN_SHARED_WORDS {
+        return Err(RejectedOutput(
+            OutputCheck::Echo,
+            format!("nothing but an echo of the prompt is left: {:?}", remainder),
+        )
+        .into());
+    }
+    Ok(remainder)
+}
+
+fn strip_preambles(lines: &mut Vec<&str>, transcript: &str) -> bool {
+    let mut stripped = false;
+
+    while let Some(first) = lines.iter().position(|line| !line.trim().is_empty()) {
+        let line = lines[first].trim();
+        if !starts_with_any(line, PREAMBLES) || in_transcript(line, transcript) {
+            break;
+        }
+
+        if let Some((_, rest)) = line.split_once(':') {
+            // "Sure! Here is the corrected text: ..." on a single line
+            lines[first] = rest;
+        } else if lines[first + 1..].iter().any(|l| !l.trim().is_empty()) {
+            lines.remove(first);
+        } else {
+            break;
+        }
+        stripped = true;
+    }
+
+    stripped
+}
+
+fn strip_instruction_lines(lines: &mut Vec<&str>, transcript: &str, instructions: &str) -> bool {
+    let echoable: Vec<String> = instructions
+        .lines()
+        .map(normalize)
+        .filter(|line| line.len() >= MIN_ECHO_CHARS && !transcript.contains(line.as_str()))
+        .collect();
+
+    let before = lines.len();
+    lines.retain(|line| {
+        let line = normalize(line);
+        line.len() < MIN_ECHO_CHARS
+            || transcript.contains(&line)
+            || !echoable
+                .iter()
+                .any(|echo| echo.contains(&line) || line.contains(echo.as_str()))
+    });
+
+    lines.len() < before
+}
+
+fn strip_explanations(lines: &mut Vec<&str>, transcript: &str) -> bool {
+    let Some(body) = lines.iter().position(|line| !line.trim().is_empty()) else {
+        return false;
+    };
+
+    let explanation = lines.iter().skip(body + 1).position(|line| {
+        let line = line.trim().trim_start_matches(['(', '*', '_']);
+        starts_with_any(line, EXPLANATIONS) && !in_transcript(line, transcript)
+    });
+
+    match explanation {
+        Some(offset) => {
+            lines.truncate(body + 1 + offset);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether `line` starts with one of `patterns` as whole words
+fn starts_with_any(line: &str, patterns: &[&str]) -> bool {
+    let line = format!("{} ", normalize(line));
+    patterns
+        .iter()
+        .any(|pattern| line.starts_with(&format!("{} ", pattern)))
+}
+
+/// Whether the transcript contains the first few words of `line`
+fn in_transcript(line: &str, transcript: &str) -> bool {
+    let line = normalize(line);
+    let opening: Vec<&str> = line.split_whitespace().take(3).collect();
+    !opening.is_empty() && format!(" {} ", transcript).contains(&format!(" {} ", opening.join(" ")))
+}
+
+fn shared_word_ratio(text: &str, transcript: &str) -> f64 {
+    let transcript_words: Vec<&str> = transcript.split_whitespace().collect();
+    let text = normalize(text);
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+
+    let shared = words
+        .iter()
+        .filter(|word| transcript_words.contains(word))
+        .count();
+    shared as f64 / words.len() as f64
+}
+
+/// Lowercase words of `text` separated by single spaces, without punctuation
+fn normalize(text: &str) -> String {
+    let text: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            '’' => '\'',
+            c if c.is_alphanumeric() || c == '\'' => c,
+            _ => ' ',
+        })
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICTATION: &str = "so um can you please call me back when you get this";
+    const CORRECTED: &str = "Can you please call me back when you get this?";
+
+    fn prompt(text: &str) -> String {
+        format!(
+            "You are a text correction assistant. Fix transcription errors ONLY.\n\n\
+             CRITICAL RULES:\n\
+             1. Output ONLY the corrected text - absolutely NO explanations, quotes, or commentary\n\
+             2. Keep the EXACT same meaning and tone\n\n\
+             Corrections to apply:\n\
+             - Add proper punctuation (periods, commas, question marks)\n\
+             - Remove filler words like 'um', 'uh', 'like' (only when used as fillers, not as verbs)\n\n\
+             Text: {}\n\nCorrected:",
+            text
+        )
+    }
+
+    /// Observed outputs that should come back as [`CORRECTED`]
+    const ECHOES: &[&str] = &[
+        "Sure! Here is the corrected text:\nCan you please call me back when you get this?",
+        "Sure!\n\nHere's the corrected text:\n\nCan you please call me back when you get this?",
+        "Here is the corrected text: Can you please call me back when you get this?",
+        "Of course, here you go:\nCan you please call me back when you get this?",
+        "- Add proper punctuation (periods, commas, question marks)\nCan you please call me back when you get this?",
+        "2. Keep the EXACT same meaning and tone\nCan you please call me back when you get this?",
+        "Can you please call me back when you get this?\n\nNote: I removed the filler words \"so\" and \"um\".",
+        "Can you please call me back when you get this?\n\nI made the following changes:\n- Added a question mark\n- Removed \"um\"",
+        "Can you please call me back when you get this?\n(Note: the meaning is unchanged.)",
+        "Can you please call me back when you get this?\n\nLet me know if you need anything else!",
+    ];
+
+    /// Observed outputs with nothing usable left once the echo is removed
+    const REJECTED: &[&str] = &[
+        "Sure! Here is the corrected text:",
+        "Sure! Here is an example:\nThe quick brown fox jumps over the lazy dog.",
+        "- Add proper punctuation (periods, commas, question marks)\n- Remove filler words like 'um', 'uh', 'like'",
+    ];
+
+    #[test]
+    fn test_removes_observed_echoes() {
+        for output in ECHOES {
+            let cleaned = remove_echo(DICTATION, &prompt(DICTATION), output).unwrap();
+            assert_eq!(cleaned, CORRECTED, "output: {:?}", output);
+        }
+    }
+
+    #[test]
+    fn test_rejects_outputs_that_are_only_echo() {
+        for output in REJECTED {
+            let result = remove_echo(DICTATION, &prompt(DICTATION), output);
+            assert!(result.is_err(), "output: {:?}", output);
+        }
+    }
+
+    #[test]
+    fn test_keeps_transcript_text_that_looks_like_echo() {
+        let input =
+            "okay so i have a meeting at three\ni have to leave early\nnote to self buy milk";
+        let output =
+            "Okay, so I have a meeting at three.\nI have to leave early.\nNote to self: buy milk.";
+
+        assert_eq!(remove_echo(input, &prompt(input), output).unwrap(), output);
+    }
+
+    #[test]
+    fn test_keeps_dictated_instructions() {
+        let input =
+            "remember to add proper punctuation periods commas question marks to the report";
+        let output =
+            "Remember to add proper punctuation (periods, commas, question marks) to the report.";
+
+        assert_eq!(remove_echo(input, &prompt(input), output).unwrap(), output);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Most results a cache can be set to hold
+pub const MAX_CACHE_SIZE: u32 = 1000;
+
+/// Enhanced texts from recent transcripts, so a phrase dictated again isn't
+/// sent to the model again. Results are only reused in the context they were
+/// made in: the whole cache is dropped as soon as the features, model or
+/// prompt it is used with change.
+#[derive(Debug)]
+pub struct EnhancementCache {
+    capacity: usize,
+    context: Option<u64>,
+    entries: HashMap<u64, Entry>,
+    /// Bumped on every use, to find the least recently used entry
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    output: String,
+    last_used: u64,
+}
+
+impl EnhancementCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            context: None,
+            entries: HashMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The result cached for `text` in `context`, a description of
+    /// everything else the result depends on
+    pub fn get(&mut self, context: &str, text: &str) -> Option<String> {
+        self.enter(context);
+        self.clock += 1;
+        match self.entries.get_mut(&text_key(text)) {
+            Some(entry) => {
+                entry.last_used = self.clock;
+                self.hits += 1;
+                Some(entry.output.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Remember `output` as the result for `text` in `context`, dropping the
+    /// least recently used result when the cache is full
+    pub fn insert(&mut self, context: &str, text: &str, output: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.enter(context);
+        self.clock += 1;
+
+        let key = text_key(text);
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            self.evict(self.entries.len() + 1 - self.capacity);
+        }
+        self.entries.insert(
+            key,
+            Entry {
+                output: output.to_string(),
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Hold at most `capacity` results from now on
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        if self.entries.len() > capacity {
+            self.evict(self.entries.len() - capacity);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.context = None;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Drop every result made in another context than `context`
+    fn enter(&mut self, context: &str) {
+        let context = hash(context);
+        if self.context != Some(context) {
+            self.entries.clear();
+            self.context = Some(context);
+        }
+    }
+
+    fn evict(&mut self, count: usize) {
+        let mut by_age: Vec<(u64, u64)> = self
+            .entries
+            .iter()
+            .map(|(key, entry)| (entry.last_used, *key))
+            .collect();
+        by_age.sort_unstable();
+        for (_, key) in by_age.into_iter().take(count) {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// Key for `text` that ignores how it is spaced, as the transcription
+/// engine isn't consistent about that
+fn text_key(text: &str) -> u64 {
+    hash(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn hash(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTEXT: &str = "model=llama3.2:1b features=punctuation";
+
+    #[test]
+    fn test_hits_and_misses() {
+        let mut cache = EnhancementCache::new(10);
+        assert_eq!(cache.get(CONTEXT, "sounds good thanks"), None);
+
+        cache.insert(CONTEXT, "sounds good thanks", "Sounds good, thanks!");
+        assert_eq!(
+            cache.get(CONTEXT, "sounds good thanks").as_deref(),
+            Some("Sounds good, thanks!")
+        );
+        assert_eq!(
+            cache.get(CONTEXT, "  sounds good\nthanks ").as_deref(),
+            Some("Sounds good, thanks!")
+        );
+        assert_eq!(cache.get(CONTEXT, "Sounds good thanks"), None);
+
+        assert_eq!((cache.hits(), cache.misses()), (2, 2));
+    }
+
+    #[test]
+    fn test_evicts_the_least_recently_used() {
+        let mut cache = EnhancementCache::new(2);
+        cache.insert(CONTEXT, "one", "One.");
+        cache.insert(CONTEXT, "two", "Two.");
+        assert!(cache.get(CONTEXT, "one").is_some());
+
+        cache.insert(CONTEXT, "three", "Three.");
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(CONTEXT, "two").is_none());
+        assert!(cache.get(CONTEXT, "one").is_some());
+        assert!(cache.get(CONTEXT, "three").is_some());
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(CONTEXT, "three").is_some());
+
+        cache.set_capacity(0);
+        cache.insert(CONTEXT, "four", "Four.");
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn test_context_changes_invalidate() {
+        let mut cache = EnhancementCache::new(10);
+        cache.insert(CONTEXT, "one", "One.");
+        cache.insert(CONTEXT, "two", "Two.");
+
+        let other = "model=qwen2.5:3b features=punctuation";
+        assert_eq!(cache.get(other, "one"), None);
+        assert_eq!(cache.len(), 0);
+
+        // Going back doesn't bring the old results back either
+        assert_eq!(cache.get(CONTEXT, "two"), None);
+
+        cache.insert(CONTEXT, "one", "One.");
+        cache.clear();
+        assert_eq!(cache.get(CONTEXT, "one"), None);
+    }
+}
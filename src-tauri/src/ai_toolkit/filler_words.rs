@@ -0,0 +1,224 @@
+use super::capitalization::capitalize_first;
+use super::repetitions::{ends_sentence, is_punctuation, trailing_punctuation, words, Word};
+
+/// Hesitation sounds, which are never part of what was meant and are
+/// dropped wherever they are said
+const HESITATIONS: &[&str] = &["um", "umm", "uh", "uhh", "uhm", "erm", "hmm"];
+
+/// Punctuation that closes a clause, so that a filler after it starts one
+const CLAUSE_ENDS: &[char] = &[',', ';', ':', '.', '!', '?'];
+
+/// Trim each filler and drop blank ones and ones already listed in another
+/// casing
+pub fn normalize_filler_words(fillers: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for filler in fillers {
+        let filler = filler
+            .trim_matches(is_punctuation)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !filler.is_empty()
+            && !normalized
+                .iter()
+                .any(|existing| existing.to_lowercase() == filler.to_lowercase())
+        {
+            normalized.push(filler);
+        }
+    }
+    normalized
+}
+
+/// Drop the fillers of `fillers` that can't be meant, so the model is left
+/// with fewer to find: hesitation sounds anywhere, and other fillers only
+/// where they make up a clause of their own. "Like, I was there" loses its
+/// "Like", while "I like it" and "it looks like rain" keep theirs. Text made
+/// of nothing but fillers is left as it is.
+pub fn strip_fillers(text: &str, fillers: &[String]) -> String {
+    let mut phrases: Vec<Vec<String>> = fillers
+        .iter()
+        .map(|filler| {
+            filler
+                .split_whitespace()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>()
+        })
+        .filter(|phrase| !phrase.is_empty())
+        .collect();
+    // The longest phrase wins, so "you know" goes before "you" could
+    phrases.sort_by_key(|phrase| std::cmp::Reverse(phrase.len()));
+
+    let mut words = words(text);
+    let mut i = 0;
+    while i < words.len() {
+        match filler_at(&words, i, &phrases) {
+            Some(n) => remove_filler(&mut words, i, n),
+            None => i += 1,
+        }
+    }
+    if words.is_empty() {
+        return text.to_string();
+    }
+
+    words
+        .iter()
+        .map(|word| format!("{}{}", word.text, word.space))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// How many words the filler starting at `i` takes, when it can be dropped
+fn filler_at(words: &[Word], i: usize, phrases: &[Vec<String>]) -> Option<usize> {
+    phrases.iter().find_map(|phrase| {
+        let n = phrase.len();
+        let candidate = words.get(i..i + n)?;
+        let matches = candidate.iter().zip(phrase).all(|(word, key)| {
+            word.key == *key && word.text.starts_with(|c: char| !is_punctuation(c))
+        }) && candidate[..n - 1]
+            .iter()
+            .all(|word| trailing_punctuation(&word.text).is_empty());
+        if !matches {
+            return None;
+        }
+
+        let hesitation = n == 1 && HESITATIONS.contains(&phrase[0].as_str());
+        let starts_clause = i == 0 || ends_clause(&words[i - 1].text);
+        let ends = i + n == words.len() || ends_clause(&candidate[n - 1].text);
+        (hesitation || (starts_clause && ends)).then_some(n)
+    })
+}
+
+fn ends_clause(word: &str) -> bool {
+    trailing_punctuation(word).contains(CLAUSE_ENDS)
+}
+
+/// Remove the `n` words at `i`, keeping the sentence around them punctuated
+/// and capitalized: "So, um, I" becomes "So I" and "fine, you know." becomes
+/// "fine."
+fn remove_filler(words: &mut Vec<Word>, i: usize, n: usize) {
+    let last = &words[i + n - 1];
+    let ending = trailing_punctuation(&last.text).to_string();
+    let space = last.space.clone();
+    let starts_sentence = i == 0 || ends_sentence(&words[i - 1].text);
+    let capitalized = words[i].text.starts_with(char::is_uppercase);
+    words.drain(i..i + n);
+
+    let at_end = i == words.len();
+    if let Some(before) = i.checked_sub(1).map(|before| &mut words[before]) {
+        if ends_sentence(&ending) {
+            // The sentence now ends on the word before
+            let kept = before.text.trim_end_matches(is_punctuation);
+            before.text = format!("{}{}", kept, ending.trim_start_matches(','));
+        } else if (at_end || ending == ",") && before.text.ends_with(',') {
+            // A comma setting the filler off has nothing left to set off
+            before.text.pop();
+        }
+        // A paragraph break after the filler outlasts it
+        if space.contains('\n') {
+            before.space = space;
+        }
+    }
+
+    if starts_sentence && capitalized {
+        if let Some(next) = words.get_mut(i) {
+            next.text = capitalize_first(&next.text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fillers(list: &[&str]) -> Vec<String> {
+        list.iter().map(|filler| filler.to_string()).collect()
+    }
+
+    #[test]
+    fn test_strips_hesitations_anywhere() {
+        let list = fillers(&["um", "uh"]);
+        let cases = [
+            (
+                "So, um, I was, uh, thinking we could meet later.",
+                "So I was thinking we could meet later.",
+            ),
+            ("we um need more time", "we need more time"),
+            ("Um, so we ship on Friday.", "So we ship on Friday."),
+            ("We need more time, uh.", "We need more time."),
+            ("Is it done? Um", "Is it done?"),
+            ("Bring an umbrella.", "Bring an umbrella."),
+            ("um", "um"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(strip_fillers(input, &list), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_strips_multi_word_fillers_set_off_as_clauses() {
+        let list = fillers(&["you know", "I mean", "sort of", "basically"]);
+        let cases = [
+            ("So, you know, it works.", "So it works."),
+            ("It was fine, you know.", "It was fine."),
+            ("I mean, we could try.", "We could try."),
+            (
+                "Basically, we ship it. Then we wait",
+                "We ship it. Then we wait",
+            ),
+            ("It was, sort of, a mess.", "It was a mess."),
+            // Not a clause of their own, so they may be meant
+            ("Do you know the way?", "Do you know the way?"),
+            ("I mean what I say.", "I mean what I say."),
+            ("It's a sort of cake.", "It's a sort of cake."),
+            ("You, know this already.", "You, know this already."),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(strip_fillers(input, &list), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_keeps_like_as_a_verb() {
+        let list = fillers(&["like"]);
+        let kept = [
+            "I like it.",
+            "I'd like to come.",
+            "It looks like rain.",
+            "Things I like, like dogs.",
+            "What do you like?",
+        ];
+        for input in kept {
+            assert_eq!(strip_fillers(input, &list), input, "{:?}", input);
+        }
+        assert_eq!(strip_fillers("Like, I was there.", &list), "I was there.");
+    }
+
+    #[test]
+    fn test_only_strips_listed_fillers() {
+        // "like" is kept for whoever took it off the list
+        let list = fillers(&["um"]);
+        assert_eq!(
+            strip_fillers("Like, I was, um, there.", &list),
+            "Like, I was there."
+        );
+        assert_eq!(strip_fillers("Um, hi.", &[]), "Um, hi.");
+    }
+
+    #[test]
+    fn test_keeps_layout() {
+        let list = fillers(&["um"]);
+        assert_eq!(
+            strip_fillers("First line um\n\nSecond  paragraph", &list),
+            "First line\n\nSecond  paragraph"
+        );
+    }
+
+    #[test]
+    fn test_normalize_filler_words() {
+        assert_eq!(
+            normalize_filler_words(fillers(&[" you   know ", "um,", "UM", "", "..."])),
+            vec!["you know", "um"]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Every GGUF file starts with these four bytes
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+/// Check that `path` points at a GGUF file and return its absolute path, which
+/// is what the Modelfile needs since Ollama resolves it on its own side
+pub fn validate_gguf_file(path: &Path) -> Result<PathBuf> {
+    if !path.is_file() {
+        return Err(anyhow!("File not found: {}", path.display()));
+    }
+
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .map_err(|e| anyhow!("Could not read {}: {}", path.display(), e))?;
+
+    if &magic != GGUF_MAGIC {
+        return Err(anyhow!("{} is not a GGUF model file", path.display()));
+    }
+
+    Ok(path.canonicalize()?)
+}
+
+/// Ollama model names may not contain whitespace and must not be empty
+pub fn validate_model_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Model name is required"));
+    }
+    if name.chars().any(char::is_whitespace) {
+        return Err(anyhow!("Model name must not contain spaces"));
+    }
+    Ok(name.to_lowercase())
+}
+
+/// Minimal Modelfile that builds a model straight from a GGUF file
+pub fn gguf_modelfile(path: &Path) -> String {
+    format!("FROM {}\n", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("handy-{}-{}.gguf", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_accepts_gguf_magic() {
+        let path = temp_file("valid", b"GGUF\x03\x00\x00\x00rest");
+
+        let validated = validate_gguf_file(&path).unwrap();
+        assert!(validated.is_absolute());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let not_gguf = temp_file("safetensors", b"{\"__metadata__\":{}}");
+        let too_short = temp_file("short", b"GG");
+
+        assert!(validate_gguf_file(&not_gguf).is_err());
+        assert!(validate_gguf_file(&too_short).is_err());
+        assert!(validate_gguf_file(Path::new("/nonexistent/model.gguf")).is_err());
+
+        let _ = std::fs::remove_file(&not_gguf);
+        let _ = std::fs::remove_file(&too_short);
+    }
+
+    #[test]
+    fn test_validate_model_name() {
+        assert_eq!(validate_model_name(" My-Model:q4 ").unwrap(), "my-model:q4");
+        assert!(validate_model_name("").is_err());
+        assert!(validate_model_name("my model").is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// `text` in Unicode normalization form C, with each accented letter one
+/// composed character wherever there is one, however the model wrote it
+pub fn to_nfc(text: &str) -> String {
+    match is_nfc_quick(text.chars()) {
+        IsNormalized::Yes => text.to_string(),
+        _ => text.nfc().collect(),
+    }
+}
+
+/// `text` without whitespace at either end. Unlike [`str::trim`], a
+/// combining mark written on a space keeps it rather than being left on
+/// its own.
+pub fn trim(text: &str) -> &str {
+    trim_end_graphemes(trim_start_graphemes(text, is_blank), is_blank)
+}
+
+/// `text` without the grapheme clusters at its start that `trimmed` accepts
+pub fn trim_start_graphemes(text: &str, trimmed: impl Fn(&str) -> bool) -> &str {
+    let start = text
+        .grapheme_indices(true)
+        .find(|(_, grapheme)| !trimmed(grapheme))
+        .map_or(text.len(), |(i, _)| i);
+    &text[start..]
+}
+
+/// `text` without the grapheme clusters at its end that `trimmed` accepts
+pub fn trim_end_graphemes(text: &str, trimmed: impl Fn(&str) -> bool) -> &str {
+    let end = text
+        .grapheme_indices(true)
+        .rev()
+        .find(|(_, grapheme)| !trimmed(grapheme))
+        .map_or(0, |(i, grapheme)| i + grapheme.len());
+    &text[..end]
+}
+
+/// `text` after `c`, when it starts with `c` standing on its own rather than
+/// carrying a combining mark or joined into an emoji
+pub fn strip_prefix_grapheme(text: &str, c: char) -> Option<&str> {
+    let first = text.graphemes(true).next()?;
+    is_char(first, c).then(|| &text[first.len()..])
+}
+
+/// `text` before `c`, when it ends with `c` standing on its own
+pub fn strip_suffix_grapheme(text: &str, c: char) -> Option<&str> {
+    let last = text.graphemes(true).next_back()?;
+    is_char(last, c).then(|| &text[..text.len() - last.len()])
+}
+
+/// Whether `grapheme` is nothing but whitespace
+pub fn is_blank(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+fn is_char(grapheme: &str, c: char) -> bool {
+    let mut chars = grapheme.chars();
+    chars.next() == Some(c) && chars.next().is_none()
+}
+
+/// Whether `c` belongs to a script written without spaces between words,
+/// whose characters each stand as a word of their own
+pub fn is_unspaced_script(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' // Hiragana and Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK Unified Ideographs Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+        | '\u{FF66}'..='\u{FF9F}' // Halfwidth Katakana
+        | '\u{20000}'..='\u{2FFFF}' // Supplementary Ideographic Plane and beyond
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accents_are_composed() {
+        assert_eq!(to_nfc("cafe\u{301} nai\u{308}ve"), "café naïve");
+        assert_eq!(to_nfc("café"), "café");
+        // Emoji and CJK have nothing to compose
+        assert_eq!(to_nfc("👨‍👩‍👧 日本語。"), "👨‍👩‍👧 日本語。");
+    }
+
+    #[test]
+    fn test_trimming_keeps_clusters_whole() {
+        assert_eq!(trim("  hello 👋🏽\n"), "hello 👋🏽");
+        assert_eq!(trim("\u{3000}日本語。\u{3000}"), "日本語。");
+        // A combining mark on a space isn't left without it
+        assert_eq!(trim(" \u{301}x "), " \u{301}x");
+        assert_eq!(trim(" \n "), "");
+
+        let emoji = |grapheme: &str| !grapheme.chars().any(char::is_alphanumeric);
+        assert_eq!(trim_end_graphemes("done 👨‍👩‍👧", emoji), "done");
+        assert_eq!(trim_start_graphemes("🇯🇵 Tokyo", emoji), "Tokyo");
+    }
+
+    #[test]
+    fn test_only_whole_graphemes_are_stripped() {
+        assert_eq!(strip_prefix_grapheme("\"hi\"", '"'), Some("hi\""));
+        assert_eq!(strip_suffix_grapheme("\"hi\"", '"'), Some("\"hi"));
+        assert_eq!(strip_prefix_grapheme("\"\u{301}hi", '"'), None);
+        assert_eq!(strip_suffix_grapheme("hi\"\u{301}", '"'), None);
+        // The fire ends a joined emoji rather than standing on its own
+        assert_eq!(strip_suffix_grapheme("❤\u{FE0F}\u{200D}🔥", '🔥'), None);
+        assert_eq!(strip_suffix_grapheme("", '"'), None);
+    }
+}
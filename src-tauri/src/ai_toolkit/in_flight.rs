@@ -0,0 +1,129 @@
+use std::fmt;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+
+/// Error an enhancement taken back by [`InFlight::abort`] ends with
+#[derive(Debug)]
+pub struct Aborted;
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AI enhancement aborted")
+    }
+}
+
+impl std::error::Error for Aborted {}
+
+/// An enhancement taken back by [`InFlight::abort`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbortedEnhancement {
+    pub request_id: String,
+    /// The transcript to paste instead
+    pub original: String,
+}
+
+struct Running {
+    request_id: String,
+    original: String,
+    abort: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct State {
+    running: Option<Running>,
+    /// The enhancement aborted last
+    aborted: Option<String>,
+}
+
+/// The enhancement being generated, if any. Its result is delivered by
+/// whichever of [`settle`](Self::settle) and [`abort`](Self::abort) gets to
+/// it first, so an enhancement finishing while it is aborted is never both
+/// pasted and reported as aborted.
+#[derive(Default)]
+pub struct InFlight {
+    state: Mutex<State>,
+}
+
+impl InFlight {
+    /// Track a new enhancement, replacing any earlier one. The receiver
+    /// resolves with `Ok` once it is aborted.
+    pub fn start(&self, request_id: &str, original: &str) -> oneshot::Receiver<()> {
+        let (abort, aborted) = oneshot::channel();
+        self.state.lock().unwrap().running = Some(Running {
+            request_id: request_id.to_string(),
+            original: original.to_string(),
+            abort,
+        });
+        aborted
+    }
+
+    /// Claim an enhancement's result for delivery. `false` when it was
+    /// aborted first, and the original transcript was delivered instead.
+    pub fn settle(&self, request_id: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if matches!(&state.running, Some(running) if running.request_id == request_id) {
+            state.running = None;
+        }
+        state.aborted.as_deref() != Some(request_id)
+    }
+
+    /// Abort the running enhancement, unless its result was already claimed
+    pub fn abort(&self) -> Option<AbortedEnhancement> {
+        let mut state = self.state.lock().unwrap();
+        let running = state.running.take()?;
+        state.aborted = Some(running.request_id.clone());
+        // The enhancement may have stopped waiting for the abort already
+        let _ = running.abort.send(());
+        Some(AbortedEnhancement {
+            request_id: running.request_id,
+            original: running.original,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abort_wins_over_a_later_result() {
+        let in_flight = InFlight::default();
+        let mut aborted = in_flight.start("enhance-1", "um send it");
+
+        assert_eq!(
+            in_flight.abort(),
+            Some(AbortedEnhancement {
+                request_id: "enhance-1".to_string(),
+                original: "um send it".to_string(),
+            })
+        );
+        assert_eq!(aborted.try_recv(), Ok(()));
+        assert!(!in_flight.settle("enhance-1"));
+    }
+
+    #[test]
+    fn test_result_wins_over_a_later_abort() {
+        let in_flight = InFlight::default();
+        let _aborted = in_flight.start("enhance-1", "um send it");
+
+        assert!(in_flight.settle("enhance-1"));
+        assert_eq!(in_flight.abort(), None);
+    }
+
+    #[test]
+    fn test_only_the_latest_enhancement_is_tracked() {
+        let in_flight = InFlight::default();
+        assert_eq!(in_flight.abort(), None);
+
+        let mut first = in_flight.start("enhance-1", "first");
+        let _second = in_flight.start("enhance-2", "second");
+        // Replacing the first doesn't abort it
+        assert!(first.try_recv().is_err());
+        assert!(in_flight.settle("enhance-1"));
+
+        assert_eq!(in_flight.abort().unwrap().original, "second");
+        assert!(!in_flight.settle("enhance-2"));
+        // Enhancements that were never tracked are always delivered
+        assert!(in_flight.settle("enhance-3"));
+    }
+}
@@ -0,0 +1,216 @@
+use super::repetitions::trim_punctuation;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Longest phrase, in words, whose repetition is looked for
+const MAX_REPEATED_WORDS: usize = 4;
+
+/// Digits, and English words that are or go with a number, whether a
+/// cardinal, an ordinal or a fraction, in the plural too
+static NUMBER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\d|\b(?:zero|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve|(?:thir|four|fif|six|seven|eigh|nine)teen(?:th)?|(?:twen|thir|for|fif|six|seven|eigh|nine)t(?:y|ies|ieth)|hundred|thousand|million|billion|trillion|first|second|third|fourth|fifth|sixth|seventh|eighth|ninth|tenth|eleventh|twelfth|half|halves|quarter|dozen)(?:s|th|ths)?\b",
+    )
+    .unwrap()
+});
+
+/// Words naming a day, a month or a time of day without a number
+static DATE_OR_TIME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:january|february|march|april|may|june|july|august|september|october|november|december|monday|tuesday|wednesday|thursday|friday|saturday|sunday|today|tonight|tomorrow|yesterday|noon|midday|midnight|morning|afternoon|evening|night|weekend|week|fortnight|month|year)s?\b",
+    )
+    .unwrap()
+});
+
+/// Units spoken without a number, like "kilometers per hour"
+static UNIT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:(?:milli|centi|kilo)?(?:meter|metre|gram|liter|litre)|inch|inches|feet|foot|yard|mile|ounce|pound|gallon|pint|ton|tonne|degree|celsius|fahrenheit|mph|kph|(?:kilo|mega|giga|tera)?(?:byte|bit|hertz)|percent)s?\b",
+    )
+    .unwrap()
+});
+
+/// Currency symbols, and currencies spoken without a number
+static CURRENCY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)[$€£¥₹¢]|\b(?:dollar|buck|cent|euro|pound|quid|yen|yuan|rupee|franc|peso|grand)s?\b",
+    )
+    .unwrap()
+});
+
+/// The instructions that only some transcripts have anything for, and
+/// whether one could. Every check errs on the side of keeping the
+/// instruction, as one left out that was needed costs more than the tokens
+/// one kept for nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionTriggers {
+    pub numbers: bool,
+    pub fillers: bool,
+    pub dates_times: bool,
+    pub units: bool,
+    pub currency: bool,
+    pub phone_numbers: bool,
+    pub repetitions: bool,
+}
+
+impl InstructionTriggers {
+    /// Every instruction kept, for when the checks are off or don't know
+    /// the language
+    pub const ALL: Self = Self {
+        numbers: true,
+        fillers: true,
+        dates_times: true,
+        units: true,
+        currency: true,
+        phone_numbers: true,
+        repetitions: true,
+    };
+
+    /// What the English transcript `text` could use, with `fillers` as the
+    /// filler words and phrases to remove
+    pub fn detect(text: &str, fillers: &[String]) -> Self {
+        let numbers = NUMBER.is_match(text);
+        Self {
+            numbers,
+            fillers: has_filler(text, fillers),
+            dates_times: numbers || DATE_OR_TIME.is_match(text),
+            units: numbers || UNIT.is_match(text),
+            currency: numbers || CURRENCY.is_match(text),
+            // Phone numbers are spoken digit by digit
+            phone_numbers: numbers,
+            repetitions: has_repetition(text),
+        }
+    }
+}
+
+/// Whether any of `fillers` is said in `text`, as whole words in any casing
+/// and with anything but a word between the words of a phrase
+fn has_filler(text: &str, fillers: &[String]) -> bool {
+    let phrases: Vec<String> = fillers
+        .iter()
+        .map(|filler| {
+            filler
+                .split_whitespace()
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(r"\W+")
+        })
+        .filter(|phrase| !phrase.is_empty())
+        .collect();
+    if phrases.is_empty() {
+        return false;
+    }
+    // A pattern that won't build is as good as a match
+    Regex::new(&format!(r"(?i)\b(?:{})\b", phrases.join("|")))
+        .map_or(true, |pattern| pattern.is_match(text))
+}
+
+/// Whether a word or a phrase of a few is said twice in a row, in any
+/// casing, or a word is broken off to be said again ("th- the")
+fn has_repetition(text: &str) -> bool {
+    if text
+        .split_whitespace()
+        .any(|word| word.len() > 1 && word.ends_with('-'))
+    {
+        return true;
+    }
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| trim_punctuation(word).to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect();
+    (1..=MAX_REPEATED_WORDS).any(|n| {
+        words
+            .windows(2 * n)
+            .any(|window| window[..n] == window[n..])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fillers(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_numbers_as_digits_or_words() {
+        for text in [
+            "call me at 5",
+            "it costs twenty-five",
+            "she came in third",
+            "about a quarter of them",
+            "hundreds of people",
+            "the Seventies were fun",
+            "page ٣",
+        ] {
+            let triggers = InstructionTriggers::detect(text, &[]);
+            assert!(triggers.numbers, "{:?}", text);
+            assert!(triggers.phone_numbers, "{:?}", text);
+            assert!(triggers.dates_times && triggers.units && triggers.currency);
+        }
+
+        // Words that only contain a number word don't count
+        let triggers = InstructionTriggers::detect("someone often tends to wonder", &[]);
+        assert!(!triggers.numbers);
+        assert!(!triggers.phone_numbers);
+    }
+
+    #[test]
+    fn test_dates_units_and_currency_without_numbers() {
+        let triggers = InstructionTriggers::detect("see you tomorrow morning", &[]);
+        assert!(triggers.dates_times);
+        assert!(!triggers.units && !triggers.currency);
+
+        let triggers = InstructionTriggers::detect("a few kilometers away", &[]);
+        assert!(triggers.units);
+        assert!(!triggers.dates_times && !triggers.currency);
+
+        let triggers = InstructionTriggers::detect("it was a few bucks", &[]);
+        assert!(triggers.currency);
+        let triggers = InstructionTriggers::detect("it was €", &[]);
+        assert!(triggers.currency);
+
+        let triggers = InstructionTriggers::detect("send the draft when you can", &[]);
+        assert!(!triggers.dates_times && !triggers.units && !triggers.currency);
+    }
+
+    #[test]
+    fn test_fillers_are_matched_as_whole_words() {
+        let list = fillers(&["um", "you know", "like"]);
+        assert!(InstructionTriggers::detect("Um, send it", &list).fillers);
+        assert!(InstructionTriggers::detect("it was, you know, fine", &list).fillers);
+        assert!(InstructionTriggers::detect("it was you, know what", &list).fillers);
+        // "Like" a verb is still a candidate, the model tells which it is
+        assert!(InstructionTriggers::detect("I like it", &list).fillers);
+
+        assert!(!InstructionTriggers::detect("the umbrella is likely here", &list).fillers);
+        assert!(!InstructionTriggers::detect("um", &[]).fillers);
+        // Fillers with characters special to a pattern are taken literally
+        assert!(!InstructionTriggers::detect("so uh yes", &fillers(&["u.h"])).fillers);
+    }
+
+    #[test]
+    fn test_repeated_words_and_phrases() {
+        for text in [
+            "the the report",
+            "I think, I think we should",
+            "we should go we should go now",
+            "It's th- the last one",
+        ] {
+            assert!(
+                InstructionTriggers::detect(text, &[]).repetitions,
+                "{:?}",
+                text
+            );
+        }
+        for text in ["I think we should", "one by one", "a - b"] {
+            assert!(
+                !InstructionTriggers::detect(text, &[]).repetitions,
+                "{:?}",
+                text
+            );
+        }
+    }
+}
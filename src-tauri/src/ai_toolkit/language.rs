@@ -0,0 +1,184 @@
+use crate::settings::{AiExample, AiLanguagePolicy};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use whatlang::Lang;
+
+/// Below this confidence a transcript is treated as mixed-language or too
+/// short to tell, and is never skipped for its language
+const MIN_CONFIDENCE: f64 = 0.5;
+
+/// Language detected in a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct DetectedLanguage {
+    /// ISO 639-3 code, like "eng" or "deu"
+    pub code: String,
+    /// English name of the language, like "German"
+    pub name: String,
+    pub confidence: f64,
+}
+
+impl DetectedLanguage {
+    pub fn is_confident(&self) -> bool {
+        self.confidence >= MIN_CONFIDENCE
+    }
+}
+
+/// Best guess at the language of `text`, however unsure
+pub fn detect_language(text: &str) -> Option<DetectedLanguage> {
+    let info = whatlang::detect(text)?;
+    Some(DetectedLanguage {
+        code: info.lang().code().to_string(),
+        name: info.lang().eng_name().to_string(),
+        confidence: info.confidence(),
+    })
+}
+
+/// Whether enhancement should be skipped for a transcript in `language`
+/// under `policy`. Uncertain detections are always enhanced.
+pub fn should_skip_language(
+    language: Option<&DetectedLanguage>,
+    policy: AiLanguagePolicy,
+    configured: &[String],
+) -> bool {
+    match (policy, language) {
+        (AiLanguagePolicy::SkipUnconfigured, Some(language)) if language.is_confident() => {
+            !configured
+                .iter()
+                .any(|code| code.eq_ignore_ascii_case(&language.code))
+        }
+        _ => false,
+    }
+}
+
+/// The user's examples that suit a transcript in `language`: those in the
+/// same language and those too short to tell
+pub fn examples_in_language(
+    examples: &[AiExample],
+    language: Option<&DetectedLanguage>,
+) -> Vec<AiExample> {
+    let Some(language) = language.filter(|language| language.is_confident()) else {
+        return examples.to_vec();
+    };
+
+    examples
+        .iter()
+        .filter(|example| match detect_language(&example.input) {
+            Some(detected) if detected.is_confident() => detected.code == language.code,
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// English name of the language with ISO 639-3 `code`, like "German" for "deu"
+pub fn language_name(code: &str) -> Option<&'static str> {
+    Lang::from_code(code.to_lowercase()).map(|lang| lang.eng_name())
+}
+
+/// Check a language code before it is saved
+pub fn validate_language_code(code: &str) -> Result<()> {
+    match Lang::from_code(code.to_lowercase()) {
+        Some(_) => Ok(()),
+        None => Err(anyhow!(
+            "Unknown language code \"{}\", expected an ISO 639-3 code like \"eng\"",
+            code
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(code: &str, confidence: f64) -> DetectedLanguage {
+        DetectedLanguage {
+            code: code.to_string(),
+            name: String::new(),
+            confidence,
+        }
+    }
+
+    #[test]
+    fn test_detects_english_and_german() {
+        let english = detect_language(
+            "so um can you please call me back when you get this and we can talk about the plan",
+        )
+        .unwrap();
+        assert_eq!(english.code, "eng");
+        assert_eq!(english.name, "English");
+        assert!(english.is_confident());
+
+        let german = detect_language(
+            "also ähm kannst du mich bitte zurückrufen wenn du das hier bekommst dann reden wir",
+        )
+        .unwrap();
+        assert_eq!(german.code, "deu");
+        assert_eq!(german.name, "German");
+        assert!(german.is_confident());
+    }
+
+    #[test]
+    fn test_skips_only_confident_unconfigured_languages() {
+        let configured = vec!["eng".to_string()];
+        let skip = |detected: Option<&DetectedLanguage>, policy| {
+            should_skip_language(detected, policy, &configured)
+        };
+
+        assert!(skip(
+            Some(&language("deu", 0.9)),
+            AiLanguagePolicy::SkipUnconfigured
+        ));
+        assert!(!skip(
+            Some(&language("eng", 0.9)),
+            AiLanguagePolicy::SkipUnconfigured
+        ));
+        // Mixed-language utterances are enhanced with the language kept
+        assert!(!skip(
+            Some(&language("deu", 0.2)),
+            AiLanguagePolicy::SkipUnconfigured
+        ));
+        assert!(!skip(None, AiLanguagePolicy::SkipUnconfigured));
+        assert!(!skip(
+            Some(&language("deu", 0.9)),
+            AiLanguagePolicy::Preserve
+        ));
+    }
+
+    #[test]
+    fn test_examples_in_language() {
+        let example = |input: &str| AiExample {
+            input: input.to_string(),
+            output: input.to_string(),
+        };
+        let examples = vec![
+            example("please send the quarterly report to the whole team by friday"),
+            example("bitte schick den quartalsbericht bis freitag an das ganze team"),
+            example("ok"),
+        ];
+
+        let german = examples_in_language(&examples, Some(&language("deu", 0.9)));
+        assert_eq!(german, vec![examples[1].clone(), examples[2].clone()]);
+
+        assert_eq!(examples_in_language(&examples, None), examples);
+        assert_eq!(
+            examples_in_language(&examples, Some(&language("deu", 0.2))),
+            examples
+        );
+    }
+
+    #[test]
+    fn test_language_name() {
+        assert_eq!(language_name("deu"), Some("German"));
+        assert_eq!(language_name("ENG"), Some("English"));
+        assert_eq!(language_name("xx"), None);
+    }
+
+    #[test]
+    fn test_validate_language_code() {
+        assert!(validate_language_code("eng").is_ok());
+        assert!(validate_language_code("DEU").is_ok());
+        assert!(validate_language_code("en").is_err());
+        assert!(validate_language_code("klingon").is_err());
+    }
+}
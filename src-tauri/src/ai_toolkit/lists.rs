@@ -0,0 +1,299 @@
+use crate::settings::AiListStyle;
+
+/// Spoken list markers, each family in item order. "number" is followed by
+/// the count, as in "number one", "number two".
+const ORDINALS: &[&str] = &[
+    "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth", "tenth",
+];
+const ORDINAL_ADVERBS: &[&str] = &[
+    "firstly",
+    "secondly",
+    "thirdly",
+    "fourthly",
+    "fifthly",
+    "sixthly",
+    "seventhly",
+    "eighthly",
+    "ninthly",
+    "tenthly",
+];
+const NUMBERS: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+];
+
+/// Words that make the ordinal after them part of the sentence, as in
+/// "the first time" or "at first"
+const ORDINAL_BLOCKERS: &[&str] = &[
+    "a", "at", "each", "every", "for", "her", "his", "its", "my", "our", "the", "their", "this",
+    "that", "your",
+];
+
+/// Fewer items than this is just a sentence that happens to say "first"
+const MIN_ITEMS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    Ordinal,
+    OrdinalAdverb,
+    Number,
+}
+
+/// A spoken list marker: its family, its position in the list and the byte
+/// range of the words saying it
+#[derive(Debug, Clone, Copy)]
+struct Marker {
+    family: Family,
+    index: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Lay out enumerations spoken with "first ... second ..." or "number one
+/// ... number two ..." as a list in `style`, one item per line after the
+/// sentence introducing them. Everything outside the enumerations is kept as
+/// it is, and markers of another kind inside an item stay part of its text.
+pub fn format_lists(text: &str, style: AiListStyle) -> String {
+    let markers = find_markers(text);
+    let Some(run) = first_run(&markers) else {
+        return text.to_string();
+    };
+
+    // The last item ends with its sentence, and whatever follows is kept
+    let last = run[run.len() - 1];
+    let tail = &text[last.end..];
+    let item_end = last.end + sentence_end(tail).unwrap_or(tail.len());
+
+    let mut items: Vec<&str> = run
+        .windows(2)
+        .map(|pair| item_text(&text[pair[0].end..pair[1].start]))
+        .collect();
+    items.push(item_text(&text[last.end..item_end]));
+    if items.iter().any(|item| item.is_empty()) {
+        return text.to_string();
+    }
+
+    let mut formatted = String::new();
+    let intro = text[..run[0].start].trim_end();
+    let intro = intro.trim_end_matches([',', '.', ':', ';']);
+    if !intro.is_empty() {
+        formatted.push_str(intro);
+        formatted.push_str(":\n");
+    }
+
+    let lines: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| match style {
+            AiListStyle::Dash => format!("- {}", item),
+            AiListStyle::Numbered => format!("{}. {}", i + 1, item),
+        })
+        .collect();
+    formatted.push_str(&lines.join("\n"));
+
+    let rest = text[item_end..].trim();
+    if !rest.is_empty() {
+        formatted.push('\n');
+        formatted.push_str(&format_lists(rest, style));
+    }
+    formatted
+}
+
+/// Every spoken list marker in `text`, in order
+fn find_markers(text: &str) -> Vec<Marker> {
+    let words = words(text);
+    let mut markers = Vec::new();
+
+    for (i, (start, end, word)) in words.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|p| words[p].2.as_str());
+        let next = |offset: usize| words.get(i + offset).map(|w| w.2.as_str());
+
+        let found = if let Some(index) = position(ORDINALS, word) {
+            let blocked = previous.is_some_and(|p| ORDINAL_BLOCKERS.contains(&p))
+                || (next(1) == Some("of") && next(2) == Some("all"));
+            (!blocked).then_some((Family::Ordinal, index, *end))
+        } else if let Some(index) = position(ORDINAL_ADVERBS, word) {
+            Some((Family::OrdinalAdverb, index, *end))
+        } else if word == "number" {
+            next(1)
+                .and_then(|count| position(NUMBERS, count))
+                .map(|index| (Family::Number, index, words[i + 1].1))
+        } else {
+            None
+        };
+
+        if let Some((family, index, end)) = found {
+            markers.push(Marker {
+                family,
+                index,
+                start: *start,
+                end,
+            });
+        }
+    }
+
+    markers
+}
+
+/// The first run of markers of one family counting up from the first item
+fn first_run(markers: &[Marker]) -> Option<Vec<Marker>> {
+    markers.iter().enumerate().find_map(|(i, first)| {
+        if first.index != 0 {
+            return None;
+        }
+
+        let mut run = vec![*first];
+        for marker in markers[i + 1..]
+            .iter()
+            .filter(|marker| marker.family == first.family)
+        {
+            if marker.index != run.len() {
+                break;
+            }
+            run.push(*marker);
+        }
+        (run.len() >= MIN_ITEMS).then_some(run)
+    })
+}
+
+/// Text of one item, without the punctuation and "and" joining it to the next
+fn item_text(text: &str) -> &str {
+    let mut item = text.trim_start_matches([',', ':', ' ']).trim();
+    loop {
+        let trimmed = item.trim_end_matches([',', '.', ';', ' ']);
+        let trimmed = match trimmed.rsplit_once(' ') {
+            Some((rest, last)) if last.eq_ignore_ascii_case("and") => rest,
+            _ => trimmed,
+        };
+        if trimmed == item {
+            return item;
+        }
+        item = trimmed;
+    }
+}
+
+/// Byte offset just past the first sentence-ending punctuation in `text`
+fn sentence_end(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if matches!(c, '.' | '!' | '?')
+            && !matches!(chars.peek(), Some((_, next)) if !next.is_whitespace())
+        {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// Words of `text` with their byte ranges, lowercased and without the
+/// punctuation around them
+fn words(text: &str) -> Vec<(usize, usize, String)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                let word = text[s..i]
+                    .trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase();
+                words.push((s, i, word));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn position(family: &[&str], word: &str) -> Option<usize> {
+    family.iter().position(|candidate| *candidate == word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dashes(text: &str) -> String {
+        format_lists(text, AiListStyle::Dash)
+    }
+
+    #[test]
+    fn test_formats_spoken_enumerations() {
+        let cases = [
+            (
+                "I need three things first the report second the slides third the budget",
+                "I need three things:\n- the report\n- the slides\n- the budget",
+            ),
+            (
+                "I need three things. First, the report. Second, the slides. Third, the budget.",
+                "I need three things:\n- the report\n- the slides\n- the budget",
+            ),
+            (
+                "Bring number one a laptop number two a charger and number three snacks",
+                "Bring:\n- a laptop\n- a charger\n- snacks",
+            ),
+            (
+                "Firstly, we cut costs, and secondly, we hire.",
+                "- we cut costs\n- we hire",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(dashes(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_numbered_style() {
+        assert_eq!(
+            format_lists(
+                "Agenda: first budget, second hiring.",
+                AiListStyle::Numbered
+            ),
+            "Agenda:\n1. budget\n2. hiring"
+        );
+    }
+
+    #[test]
+    fn test_keeps_the_sentences_around_the_list() {
+        assert_eq!(
+            dashes(
+                "Quick update. The plan is first ship it, second tell people. Questions welcome!"
+            ),
+            "Quick update. The plan is:\n- ship it\n- tell people\nQuestions welcome!"
+        );
+    }
+
+    #[test]
+    fn test_nested_sounding_enumerations() {
+        // Markers of another kind stay inside the item they were said in
+        assert_eq!(
+            dashes(
+                "First the report, which has number one a summary and number two an appendix. \
+                 Second the slides."
+            ),
+            "- the report, which has number one a summary and number two an appendix\n- the slides"
+        );
+
+        // Two lists one after the other
+        assert_eq!(
+            dashes("Today first email second calls. Tomorrow number one gym number two groceries."),
+            "Today:\n- email\n- calls\nTomorrow:\n- gym\n- groceries"
+        );
+    }
+
+    #[test]
+    fn test_leaves_ordinary_sentences_alone() {
+        let cases = [
+            "First of all, thanks for coming. Second of all, sorry I'm late.",
+            "At first I didn't like it, but the second time was better.",
+            "It was my first day and I came second in the race.",
+            "Wait a second, we're number one!",
+            "First, let me say thanks.",
+            "The third and second options are both fine.",
+            "",
+        ];
+        for input in cases {
+            assert_eq!(dashes(input), input, "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,487 @@
+use super::capitalization::capitalize_first;
+use super::protected_spans::fenced_code_spans;
+use super::repetitions::{is_punctuation, trailing_punctuation};
+use super::tokens::{keyed_tokens, phrase_at, Token};
+use std::ops::Range;
+
+/// What a spoken structure command starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    Heading(usize),
+    Bullet,
+    Numbered,
+    CodeStart,
+    CodeEnd,
+}
+
+/// Spoken structure commands, each before the shorter ones it contains so
+/// "end code block" isn't read as "code block"
+const COMMANDS: &[(&[&str], Command)] = &[
+    (&["end", "code", "block"], Command::CodeEnd),
+    (&["close", "code", "block"], Command::CodeEnd),
+    (&["start", "code", "block"], Command::CodeStart),
+    (&["open", "code", "block"], Command::CodeStart),
+    (&["code", "block"], Command::CodeStart),
+    (&["heading", "level", "one"], Command::Heading(1)),
+    (&["heading", "level", "two"], Command::Heading(2)),
+    (&["heading", "level", "three"], Command::Heading(3)),
+    (&["heading", "one"], Command::Heading(1)),
+    (&["heading", "two"], Command::Heading(2)),
+    (&["heading", "three"], Command::Heading(3)),
+    (&["subheading"], Command::Heading(3)),
+    (&["heading"], Command::Heading(2)),
+    (&["bullet", "point"], Command::Bullet),
+    (&["new", "bullet"], Command::Bullet),
+    (&["next", "bullet"], Command::Bullet),
+    (&["numbered", "item"], Command::Numbered),
+    (&["numbered", "point"], Command::Numbered),
+];
+
+/// Languages said after "code block" that become its info string
+const CODE_LANGUAGES: &[&str] = &[
+    "bash",
+    "c",
+    "css",
+    "go",
+    "html",
+    "java",
+    "javascript",
+    "json",
+    "kotlin",
+    "python",
+    "ruby",
+    "rust",
+    "shell",
+    "sql",
+    "swift",
+    "typescript",
+    "yaml",
+];
+
+/// Spoken emphasis, said around the words it applies to ("italics really
+/// important end italics"), and the marker written for it
+const EMPHASIS: &[(&[&str], &str)] = &[(&["italics", "italic"], "*"), (&["bold"], "**")];
+
+/// Words that make a command after them something talked about, as in "the
+/// bullet point about pricing"
+const NOUN_MARKERS: &[&str] = &[
+    "a", "an", "the", "my", "your", "his", "her", "its", "our", "their", "this", "that", "these",
+    "those", "every", "each", "another", "one",
+];
+
+/// What a block of the note is written as
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Block {
+    Text,
+    Heading(usize),
+    Bullet,
+    Numbered,
+    Code,
+}
+
+/// Write the structure dictated into a note as Markdown: "heading project
+/// status" as `## Project status`, "bullet point" and "numbered item" as
+/// list items, "code block ... end code block" as a fenced block and
+/// "italics ... end italics" or "bold ... end bold" as emphasis. Everything
+/// else is kept as it is, so text without commands comes back unchanged,
+/// and so is a command with nothing after it to apply to.
+///
+/// A heading runs to the end of its sentence or line, and has to start one
+/// itself, so "we are heading home" stays a sentence. The other commands
+/// can come mid-sentence, but not after a word like "the" that makes them
+/// something talked about.
+pub fn write_markdown(text: &str) -> String {
+    let blocks = blocks(text);
+    if blocks.len() == 1 && blocks[0].0 == Block::Text {
+        return emphasize(text);
+    }
+
+    let mut note = String::new();
+    let mut previous = None;
+    let mut number = 0;
+    for (block, range, language) in blocks {
+        let content = content(&text[range]);
+        if content.is_empty() && block != Block::Code {
+            continue;
+        }
+        let content = content.trim_end_matches([',', ';']);
+
+        number = if block == Block::Numbered {
+            number + 1
+        } else {
+            0
+        };
+        let written = match block {
+            Block::Text => capitalize_first(&emphasize(content)),
+            Block::Heading(level) => format!(
+                "{} {}",
+                "#".repeat(level),
+                capitalize_first(&emphasize(content.trim_end_matches(['.', ':'])))
+            ),
+            Block::Bullet => format!("- {}", capitalize_first(&emphasize(content))),
+            Block::Numbered => format!("{}. {}", number, capitalize_first(&emphasize(content))),
+            Block::Code => format!("```{}\n{}\n```", language.unwrap_or_default(), content),
+        };
+
+        // List items follow each other on their own lines, and everything
+        // else is a paragraph of its own
+        match previous {
+            None => {}
+            Some(previous)
+                if previous == block && matches!(block, Block::Bullet | Block::Numbered) =>
+            {
+                note.push('\n')
+            }
+            Some(_) => note.push_str("\n\n"),
+        }
+        note.push_str(&written);
+        previous = Some(block);
+    }
+
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    format!("{}{}{}", leading, note, trailing)
+}
+
+/// Byte ranges of the Markdown syntax in `text`: heading and list markers,
+/// emphasis markers and whole fenced code blocks. Kept from the model and
+/// the rule passes after it, which are only meant to correct the words.
+pub fn markdown_spans(text: &str) -> Vec<Range<usize>> {
+    let fences = fenced_code_spans(text);
+    let mut spans = fences.clone();
+
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        if fences.iter().any(|fence| fence.contains(&start)) {
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+        if let Some(len) = block_marker(&line[indent..]) {
+            spans.push(start + indent..start + indent + len);
+        }
+        spans.extend(
+            emphasis_markers(line)
+                .into_iter()
+                .map(|marker| start + marker.start..start + marker.end),
+        );
+    }
+
+    spans.sort_by_key(|span| span.start);
+    spans
+}
+
+/// The blocks of `text` its structure commands start, with the language of
+/// each code block
+fn blocks(text: &str) -> Vec<(Block, Range<usize>, Option<&'static str>)> {
+    let tokens = keyed_tokens(text);
+    // Each block goes with where it was said to start, its command included
+    let mut blocks = vec![];
+    let mut block = Block::Text;
+    let mut said = 0;
+    let mut start = 0;
+    let mut language = None;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let command = command_at(text, &tokens, i, block);
+        let Some((command, len)) = command else {
+            // A line break ends a heading or item, and so does the end of a
+            // heading's sentence
+            let ends_block = i > 0 && {
+                let gap = &text[tokens[i - 1].range.end..token.range.start];
+                let ended = trailing_punctuation(&text[tokens[i - 1].range.clone()]);
+                match block {
+                    Block::Heading(_) => gap.contains('\n') || ended.contains(['.', '!', '?']),
+                    Block::Bullet | Block::Numbered => gap.contains('\n'),
+                    Block::Text | Block::Code => false,
+                }
+            };
+            if ends_block {
+                blocks.push((block, said, start..token.range.start, language.take()));
+                block = Block::Text;
+                said = token.range.start;
+                start = token.range.start;
+            }
+            i += 1;
+            continue;
+        };
+
+        blocks.push((block, said, start..token.range.start, language.take()));
+        said = token.range.start;
+        block = match command {
+            Command::Heading(level) => Block::Heading(level),
+            Command::Bullet => Block::Bullet,
+            Command::Numbered => Block::Numbered,
+            Command::CodeStart => Block::Code,
+            Command::CodeEnd => Block::Text,
+        };
+        i += len;
+        start = tokens[i - 1].range.end;
+
+        // "code block python" starts a Python block
+        let unbroken = trailing_punctuation(&text[tokens[i - 1].range.clone()]).is_empty();
+        if command == Command::CodeStart && unbroken {
+            let said = tokens.get(i).map(|token| token.key.as_str());
+            if let Some(found) = CODE_LANGUAGES.iter().find(|name| Some(**name) == said) {
+                language = Some(*found);
+                start = tokens[i].range.end;
+                i += 1;
+            }
+        }
+    }
+    blocks.push((block, said, start..text.len(), language));
+
+    // A heading or list item with nothing in it is kept as the words that
+    // were said, in the block before it
+    let mut blocks = blocks.into_iter().fold(
+        Vec::<(Block, Range<usize>, Option<&'static str>)>::new(),
+        |mut kept, (block, said, range, language)| {
+            let bare = matches!(block, Block::Heading(_) | Block::Bullet | Block::Numbered)
+                && content(&text[range.clone()])
+                    .trim_matches(is_punctuation)
+                    .is_empty();
+            match kept.last_mut() {
+                Some(last) if bare => last.1.end = range.end,
+                None if bare => kept.push((Block::Text, said..range.end, None)),
+                _ => kept.push((block, range, language)),
+            }
+            kept
+        },
+    );
+
+    // Nothing said before the first command
+    blocks.retain(|(block, range, _)| {
+        *block != Block::Text || !text[range.clone()].trim().is_empty()
+    });
+    if blocks.is_empty() {
+        blocks.push((Block::Text, 0..text.len(), None));
+    }
+    blocks
+}
+
+/// What a block holds, without the space and punctuation around its command
+fn content(text: &str) -> &str {
+    text.trim().trim_start_matches([',', ';', ':']).trim_start()
+}
+
+/// The structure command said at `tokens[i]` in `block`, and how many
+/// tokens it takes up
+fn command_at(text: &str, tokens: &[Token], i: usize, block: Block) -> Option<(Command, usize)> {
+    let (words, command) = COMMANDS
+        .iter()
+        .find(|(words, _)| phrase_at(text, tokens, i, words))?;
+
+    // Only the end of a code block ends it, and it only ends one
+    let in_code = block == Block::Code;
+    if in_code != (*command == Command::CodeEnd) {
+        return None;
+    }
+
+    let previous = i.checked_sub(1).map(|j| &tokens[j]);
+    let starts_sentence = previous.is_none_or(|previous| {
+        let gap = &text[previous.range.end..tokens[i].range.start];
+        let ended = trailing_punctuation(&text[previous.range.clone()]);
+        gap.contains('\n') || ended.contains(['.', '!', '?', ':'])
+    });
+    let after_noun_marker = previous.is_some_and(|previous| {
+        trailing_punctuation(&text[previous.range.clone()]).is_empty()
+            && NOUN_MARKERS.contains(&previous.key.as_str())
+    });
+    let said = match command {
+        Command::Heading(_) => starts_sentence,
+        _ => !after_noun_marker,
+    };
+    said.then_some((*command, words.len()))
+}
+
+/// `text` with spoken emphasis written as Markdown. Emphasis that is never
+/// ended is left as it was said.
+fn emphasize(text: &str) -> String {
+    let tokens = keyed_tokens(text);
+    let mut emphasized = String::with_capacity(text.len());
+    let mut end = 0;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let Some((names, marker)) = EMPHASIS
+            .iter()
+            .find(|(names, _)| names.contains(&tokens[i].key.as_str()))
+        else {
+            i += 1;
+            continue;
+        };
+        let ended = (i + 2..tokens.len()).find(|&j| {
+            tokens[j - 1].key == "end"
+                && names.contains(&tokens[j].key.as_str())
+                && trailing_punctuation(&text[tokens[j - 1].range.clone()]).is_empty()
+        });
+        let Some(close) = ended.filter(|&close| close - 1 > i + 1) else {
+            i += 1;
+            continue;
+        };
+
+        let inner = &text[tokens[i + 1].range.start..tokens[close - 2].range.end];
+        let inner_punctuation = trailing_punctuation(inner);
+        let inner = &inner[..inner.len() - inner_punctuation.len()];
+        emphasized.push_str(&text[end..tokens[i].range.start]);
+        emphasized.push_str(&format!("{}{}{}", marker, inner, marker));
+        emphasized.push_str(inner_punctuation);
+        emphasized.push_str(trailing_punctuation(&text[tokens[close].range.clone()]));
+        end = tokens[close].range.end;
+        i = close + 1;
+    }
+    emphasized.push_str(&text[end..]);
+    emphasized
+}
+
+/// Length of the heading or list marker starting `line`, if it has one
+fn block_marker(line: &str) -> Option<usize> {
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    if (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+        return Some(hashes);
+    }
+    if ["- ", "* ", "+ "]
+        .iter()
+        .any(|marker| line.starts_with(marker))
+    {
+        return Some(1);
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    (digits > 0 && line[digits..].starts_with(". ")).then_some(digits + 1)
+}
+
+/// Byte ranges of the paired `*` and `**` markers in `line`. A marker opens
+/// at the start of a word and closes at the end of one, so a masked "f***"
+/// isn't taken for one.
+fn emphasis_markers(line: &str) -> Vec<Range<usize>> {
+    let mut runs = vec![];
+    let mut from = 0;
+    while let Some(start) = line[from..].find('*').map(|i| from + i) {
+        let len = line[start..].len() - line[start..].trim_start_matches('*').len();
+        runs.push(start..start + len);
+        from = start + len;
+    }
+
+    let before = |run: &Range<usize>| line[..run.start].chars().next_back();
+    let after = |run: &Range<usize>| line[run.end..].chars().next();
+    let mut markers = vec![];
+    let mut open: Option<Range<usize>> = None;
+    for run in runs {
+        if run.len() > 2 {
+            open = None;
+            continue;
+        }
+        if let Some(opened) = open.as_ref().filter(|opened| opened.len() == run.len()) {
+            let closes = before(&run).is_some_and(|c| !c.is_whitespace())
+                && after(&run).is_none_or(|c| c.is_whitespace() || is_punctuation(c));
+            if closes {
+                markers.push(opened.clone());
+                markers.push(run);
+                open = None;
+                continue;
+            }
+        }
+        let opens = before(&run).is_none_or(|c| c.is_whitespace() || c == '(')
+            && after(&run).is_some_and(|c| !c.is_whitespace());
+        open = opens.then_some(run);
+    }
+    markers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSIONS: &str = include_str!("fixtures/markdown_dictation.txt");
+    const NOTES: &str = include_str!("fixtures/markdown_dictation_notes.md");
+
+    /// Separates the notes in `fixtures/markdown_dictation_notes.md`, one per
+    /// line of `fixtures/markdown_dictation.txt`
+    const NOTE_SEPARATOR: &str = "\n=====\n";
+
+    #[test]
+    fn test_dictated_note_fixtures() {
+        let notes: Vec<&str> = NOTES.trim_end().split(NOTE_SEPARATOR).collect();
+        assert_eq!(SESSIONS.lines().count(), notes.len());
+        for (session, note) in SESSIONS.lines().zip(notes) {
+            assert_eq!(write_markdown(session), note);
+        }
+    }
+
+    #[test]
+    fn test_headings_only_start_a_sentence() {
+        assert_eq!(
+            write_markdown("heading project status. we shipped it"),
+            "## Project status\n\nWe shipped it"
+        );
+        assert_eq!(
+            write_markdown("Heading one, Roadmap\nheading three details"),
+            "# Roadmap\n\n### Details"
+        );
+        let text = "we are heading home after the bullet point review";
+        assert_eq!(write_markdown(text), text);
+        assert_eq!(
+            write_markdown("heading level two project plan"),
+            "## Project plan"
+        );
+    }
+
+    #[test]
+    fn test_commands_with_nothing_to_apply_to_are_kept() {
+        for text in ["bullet point", "numbered item", "heading", "heading two"] {
+            assert_eq!(write_markdown(text), text);
+        }
+        assert_eq!(
+            write_markdown("bullet point milk bullet point"),
+            "- Milk bullet point"
+        );
+    }
+
+    #[test]
+    fn test_lists_and_code_blocks() {
+        assert_eq!(
+            write_markdown("groceries: bullet point milk bullet point eggs, numbered item call mom numbered item pay rent"),
+            "Groceries:\n\n- Milk\n- Eggs\n\n1. Call mom\n2. Pay rent"
+        );
+        assert_eq!(
+            write_markdown("run this. code block bash cargo test end code block then push"),
+            "Run this.\n\n```bash\ncargo test\n```\n\nThen push"
+        );
+        // A block left open runs to the end
+        assert_eq!(
+            write_markdown("code block let x equals one"),
+            "```\nlet x equals one\n```"
+        );
+    }
+
+    #[test]
+    fn test_spoken_emphasis() {
+        assert_eq!(
+            write_markdown(
+                "this is italics really important end italics, so bold read it end bold."
+            ),
+            "this is *really important*, so **read it**."
+        );
+        let text = "a bold plan in italics";
+        assert_eq!(write_markdown(text), text);
+    }
+
+    #[test]
+    fn test_markdown_spans() {
+        let note = "## Status\n\n- Shipped *on time*\n1. Call f***\n\n```\nlet x = 1;\n```";
+        let spans: Vec<&str> = markdown_spans(note)
+            .into_iter()
+            .map(|span| &note[span])
+            .collect();
+        assert_eq!(
+            spans,
+            vec!["##", "-", "*", "*", "1.", "```\nlet x = 1;\n```"]
+        );
+
+        assert!(markdown_spans("Just a sentence, with 2.5 * 3 in it").is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use super::transport::{ByteStream, OllamaTransport, TransportResponse};
+use anyhow::{anyhow, Result};
+use futures_util::{stream, StreamExt};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Replays canned responses in order and records every request made
+pub struct MockTransport {
+    replies: Mutex<VecDeque<MockReply>>,
+    requests: Mutex<Vec<(String, String, Option<serde_json::Value>)>>,
+}
+
+pub enum MockReply {
+    Body(u16, &'static str),
+    Stream(u16, Vec<&'static str>),
+    /// A stream that stays open after its chunks, like a slow download
+    Hang(u16, Vec<&'static str>),
+    Unreachable,
+}
+
+impl MockTransport {
+    pub fn new(replies: Vec<MockReply>) -> Self {
+        Self {
+            replies: Mutex::new(replies.into()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn next(&self, method: &str, path: &str, body: Option<&serde_json::Value>) -> MockReply {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), path.to_string(), body.cloned()));
+        self.replies
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("unexpected request")
+    }
+
+    fn respond(reply: MockReply) -> Result<TransportResponse> {
+        match reply {
+            MockReply::Body(status, body) => Ok(TransportResponse {
+                status,
+                body: body.to_string(),
+            }),
+            MockReply::Unreachable => Err(anyhow!("connection refused")),
+            MockReply::Stream(..) | MockReply::Hang(..) => {
+                panic!("stream reply for buffered request")
+            }
+        }
+    }
+
+    pub fn requests(&self) -> Vec<(String, String, Option<serde_json::Value>)> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl OllamaTransport for MockTransport {
+    async fn get(&self, path: &str, _timeout: Option<Duration>) -> Result<TransportResponse> {
+        Self::respond(self.next("GET", path, None))
+    }
+
+    async fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+        _timeout: Option<Duration>,
+    ) -> Result<TransportResponse> {
+        Self::respond(self.next("POST", path, Some(body)))
+    }
+
+    async fn post_stream(&self, path: &str, body: &serde_json::Value) -> Result<(u16, ByteStream)> {
+        match self.next("POST", path, Some(body)) {
+            MockReply::Stream(status, chunks) => {
+                let chunks: Vec<Result<Vec<u8>>> = chunks
+                    .into_iter()
+                    .map(|c| Ok(c.as_bytes().to_vec()))
+                    .collect();
+                Ok((status, Box::pin(stream::iter(chunks))))
+            }
+            MockReply::Hang(status, chunks) => {
+                let chunks: Vec<Result<Vec<u8>>> = chunks
+                    .into_iter()
+                    .map(|c| Ok(c.as_bytes().to_vec()))
+                    .collect();
+                Ok((
+                    status,
+                    Box::pin(stream::iter(chunks).chain(stream::pending())),
+                ))
+            }
+            MockReply::Unreachable => Err(anyhow!("connection refused")),
+            MockReply::Body(..) => panic!("buffered reply for streaming request"),
+        }
+    }
+
+    async fn delete(&self, path: &str, body: &serde_json::Value) -> Result<TransportResponse> {
+        Self::respond(self.next("DELETE", path, Some(body)))
+    }
+}
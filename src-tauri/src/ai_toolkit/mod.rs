@@ -1,6 +1,63 @@
+#[cfg(test)]
+pub(crate) mod mock_transport;
+pub mod acronyms;
+pub mod capitalization;
+pub mod chunking;
+pub mod code_dictation;
+pub mod contractions;
+pub mod diff;
+pub mod echo_detection;
+pub mod enhancement_cache;
+pub mod filler_words;
+pub mod gguf;
+pub mod graphemes;
+pub mod in_flight;
+pub mod instruction_triggers;
+pub mod language;
+pub mod lists;
+pub mod markdown;
+pub mod model_registry;
+pub mod model_updates;
+pub mod number_format;
+pub mod number_words;
+pub mod ndjson;
+pub mod offline_enhancement;
 pub mod ollama_client;
+pub mod output_budget;
+pub mod output_cleanup;
+pub mod output_guard;
+pub mod paragraphs;
+pub mod phone_numbers;
+pub mod post_processing;
+pub mod profanity;
+pub mod proper_nouns;
+pub mod prompt_template;
+pub mod protected_spans;
+pub mod recent_transcripts;
+pub mod redaction;
+pub mod repetitions;
+pub mod replacement_rules;
+pub mod retry;
+pub mod run_on_sentences;
+pub mod session_context;
+pub mod snippets;
+pub mod spelling_variants;
+pub mod spoken_emoji;
+pub mod spoken_punctuation;
+pub mod storage;
 pub mod system_info;
+pub mod tokens;
+pub mod transport;
+pub mod typography;
+#[cfg(test)]
+pub(crate) mod unicode_samples;
+#[cfg(unix)]
+pub mod unix_transport;
+pub mod vocabulary;
 
-pub use ollama_client::OllamaClient;
-pub use system_info::{get_available_models, get_system_info, recommend_ai_model, AiModelInfo, SystemInfo};
+pub use ollama_client::{OllamaClient, OllamaRunningModel};
+pub use system_info::{
+    detect_num_thread, get_available_models, get_system_info, hardware_fingerprint,
+    recommend_ai_model, AiModelInfo, SystemInfo,
+};
 
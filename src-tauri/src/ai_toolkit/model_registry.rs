@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+
+/// Registry Ollama pulls from when a name doesn't say which
+const DEFAULT_REGISTRY: &str = "registry.ollama.ai";
+
+/// Namespace of the official models, like `llama3.2`
+const DEFAULT_NAMESPACE: &str = "library";
+
+/// A model name taken apart the way Ollama stores and fetches it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelReference {
+    pub registry: String,
+    /// Namespace and model, like `library/llama3.2`
+    pub repository: String,
+    pub tag: String,
+}
+
+/// Take apart a name handed to Ollama, such as `llama3.2:1b` or
+/// `myregistry.local/library/llama3.2:1b`. A port is part of the registry,
+/// not a tag.
+pub fn parse_model_name(name: &str) -> ModelReference {
+    let (path, tag) = match name.rsplit_once(':').filter(|(_, tag)| !tag.contains('/')) {
+        Some((path, tag)) => (path, tag),
+        None => (name, "latest"),
+    };
+    let parts: Vec<&str> = path.split('/').collect();
+    let (registry, repository) = match parts.as_slice() {
+        [model] => (DEFAULT_REGISTRY, format!("{}/{}", DEFAULT_NAMESPACE, model)),
+        [namespace, model] => (DEFAULT_REGISTRY, format!("{}/{}", namespace, model)),
+        [registry, rest @ ..] => (*registry, rest.join("/")),
+        [] => (DEFAULT_REGISTRY, path.to_string()),
+    };
+    ModelReference {
+        registry: registry.to_string(),
+        repository,
+        tag: tag.to_string(),
+    }
+}
+
+/// Clean up a registry prefix such as `myregistry.local/library`. Returns `None`
+/// for an empty prefix, meaning models come from the default Ollama registry.
+pub fn normalize_registry_prefix(prefix: &str) -> Result<Option<String>> {
+    let prefix = prefix.trim().trim_matches('/');
+    if prefix.is_empty() {
+        return Ok(None);
+    }
+
+    if prefix.contains("://") {
+        return Err(anyhow!(
+            "Registry prefix should be a host and path like myregistry.local/library, without a scheme"
+        ));
+    }
+    if prefix.chars().any(char::is_whitespace) {
+        return Err(anyhow!("Registry prefix must not contain spaces"));
+    }
+
+    Ok(Some(prefix.to_string()))
+}
+
+/// Name to hand to Ollama for a model id shown in Handy. Bare ids like
+/// `llama3.2:1b` get the registry prefix; ids that already name a namespace
+/// or registry are passed through untouched.
+pub fn qualify_model_id(model_id: &str, prefix: Option<&str>) -> String {
+    match prefix {
+        Some(prefix) if !model_id.contains('/') => format!("{}/{}", prefix, model_id),
+        _ => model_id.to_string(),
+    }
+}
+
+/// Model id to show in Handy for a name reported by Ollama, the inverse of
+/// [`qualify_model_id`]. Names from other registries are left as they are.
+pub fn display_model_id(name: &str, prefix: Option<&str>) -> String {
+    prefix
+        .and_then(|prefix| name.strip_prefix(prefix))
+        .and_then(|rest| rest.strip_prefix('/'))
+        .filter(|id| !id.is_empty() && !id.contains('/'))
+        .map(str::to_string)
+        .unwrap_or_else(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: Option<&str> = Some("myregistry.local/library");
+
+    #[test]
+    fn test_round_trip_between_display_and_registry_ids() {
+        for id in ["llama3.2:1b", "gemma2:2b", "qwen2.5"] {
+            let qualified = qualify_model_id(id, PREFIX);
+            assert_eq!(qualified, format!("myregistry.local/library/{}", id));
+            assert_eq!(display_model_id(&qualified, PREFIX), id);
+        }
+    }
+
+    #[test]
+    fn test_without_prefix_ids_are_unchanged() {
+        assert_eq!(qualify_model_id("llama3.2:1b", None), "llama3.2:1b");
+        assert_eq!(display_model_id("llama3.2:1b", None), "llama3.2:1b");
+    }
+
+    #[test]
+    fn test_names_outside_the_registry_are_left_alone() {
+        // Already qualified ids are not prefixed twice
+        assert_eq!(
+            qualify_model_id("other.registry/library/gemma2:2b", PREFIX),
+            "other.registry/library/gemma2:2b"
+        );
+        assert_eq!(
+            qualify_model_id("user/custom:latest", PREFIX),
+            "user/custom:latest"
+        );
+
+        // Models pulled from the default registry keep their names
+        assert_eq!(display_model_id("gemma2:2b", PREFIX), "gemma2:2b");
+        assert_eq!(
+            display_model_id("myregistry.local/library-extra/gemma2:2b", PREFIX),
+            "myregistry.local/library-extra/gemma2:2b"
+        );
+    }
+
+    #[test]
+    fn test_normalize_registry_prefix() {
+        assert_eq!(normalize_registry_prefix("  ").unwrap(), None);
+        assert_eq!(
+            normalize_registry_prefix(" myregistry.local/library/ ").unwrap(),
+            Some("myregistry.local/library".to_string())
+        );
+        assert!(normalize_registry_prefix("https://myregistry.local").is_err());
+        assert!(normalize_registry_prefix("my registry").is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::ai_toolkit::model_registry::parse_model_name;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::time::Duration;
+
+/// Manifest type Ollama stores, whose digest is the one it reports
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.docker.distribution.manifest.v2+json";
+
+/// Upper bound for asking the registry about one model
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether an installed model has a newer version in its registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiModelUpdateCheck {
+    pub model: String,
+    pub update_available: bool,
+    /// Why the registry couldn't tell, in which case no update is reported
+    pub error: Option<String>,
+}
+
+/// Sent as `ai-model-updated` once the download of an update finishes
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelUpdated {
+    pub model: String,
+    /// The installed copy changed. `false` when it was already the latest.
+    pub updated: bool,
+}
+
+/// URL of the manifest for `name`, qualified the way it is handed to
+/// Ollama, such as `llama3.2:1b` or `myregistry.local/library/llama3.2:1b`.
+/// Insecure registries are asked over plain HTTP, like Ollama does.
+pub fn manifest_url(name: &str, insecure: bool) -> String {
+    let reference = parse_model_name(name);
+    let scheme = if insecure { "http" } else { "https" };
+    format!(
+        "{}://{}/v2/{}/manifests/{}",
+        scheme, reference.registry, reference.repository, reference.tag
+    )
+}
+
+/// Whether two manifest digests are the same. Ollama lists them as bare
+/// hex, while registries prefix the algorithm.
+pub fn same_digest(a: &str, b: &str) -> bool {
+    let bare = |digest: &str| {
+        let digest = digest.trim();
+        digest
+            .strip_prefix("sha256:")
+            .unwrap_or(digest)
+            .to_ascii_lowercase()
+    };
+    bare(a) == bare(b)
+}
+
+/// Digest of the manifest the registry serves at `url` now, asked with a
+/// HEAD request so nothing is downloaded
+pub async fn registry_digest(client: &reqwest::Client, url: &str) -> Result<String> {
+    let response = client
+        .head(url)
+        .header(reqwest::header::ACCEPT, MANIFEST_MEDIA_TYPE)
+        .timeout(REGISTRY_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| anyhow!("The model registry couldn't be reached: {}", e))?;
+
+    match response.status().as_u16() {
+        200..=299 => {}
+        404 => return Err(anyhow!("The model registry no longer has this model")),
+        status => return Err(anyhow!("The model registry answered with HTTP {}", status)),
+    }
+    response
+        .headers()
+        .get("docker-content-digest")
+        .and_then(|digest| digest.to_str().ok())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("The model registry didn't report the model's digest"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_url_for_official_models() {
+        assert_eq!(
+            manifest_url("llama3.2:1b", false),
+            "https://registry.ollama.ai/v2/library/llama3.2/manifests/1b"
+        );
+        assert_eq!(
+            manifest_url("qwen2.5", false),
+            "https://registry.ollama.ai/v2/library/qwen2.5/manifests/latest"
+        );
+        assert_eq!(
+            manifest_url("someone/custom:q4", false),
+            "https://registry.ollama.ai/v2/someone/custom/manifests/q4"
+        );
+    }
+
+    #[test]
+    fn test_manifest_url_for_other_registries() {
+        assert_eq!(
+            manifest_url("myregistry.local/library/gemma2:2b", false),
+            "https://myregistry.local/v2/library/gemma2/manifests/2b"
+        );
+        // A port is part of the registry, not a tag
+        assert_eq!(
+            manifest_url("mirror:5000/library/gemma2", true),
+            "http://mirror:5000/v2/library/gemma2/manifests/latest"
+        );
+    }
+
+    #[test]
+    fn test_digests_compare_with_or_without_algorithm() {
+        assert!(same_digest("a80c4f17acd5", "sha256:a80c4f17acd5"));
+        assert!(same_digest("sha256:A80C4F17ACD5", "a80c4f17acd5"));
+        assert!(!same_digest("a80c4f17acd5", "sha256:0ef4fa0e1d41"));
+    }
+}
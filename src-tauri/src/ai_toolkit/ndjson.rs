@@ -0,0 +1,126 @@
+/// Splits a byte stream of newline-delimited JSON into complete lines.
+///
+/// Ollama streams one JSON object per line, but transports and proxies are
+/// free to re-chunk the body anywhere, including inside a multi-byte UTF-8
+/// character. Bytes are buffered until a full line has arrived, so objects
+/// and code points split across chunks are reassembled intact.
+#[derive(Debug, Default)]
+pub struct NdjsonLineDecoder {
+    buffer: Vec<u8>,
+}
+
+impl NdjsonLineDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next chunk and return every line it completed, without the
+    /// `\n` or `\r\n` terminator. Blank lines are skipped.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut rest = chunk;
+
+        // A newline byte never occurs inside a multi-byte UTF-8 sequence, so
+        // every complete line is also a complete run of characters
+        while let Some(newline) = rest.iter().position(|b| *b == b'\n') {
+            self.buffer.extend_from_slice(&rest[..newline]);
+            rest = &rest[newline + 1..];
+            lines.extend(self.take_line());
+        }
+        self.buffer.extend_from_slice(rest);
+
+        lines
+    }
+
+    /// Return whatever is left once the stream has ended, for a final line
+    /// sent without a trailing newline
+    pub fn finish(&mut self) -> Option<String> {
+        self.take_line()
+    }
+
+    fn take_line(&mut self) -> Option<String> {
+        let mut line = std::mem::take(&mut self.buffer);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Recorded `/api/pull` output
+    const PULL_STREAM: &str = concat!(
+        "{\"status\":\"pulling manifest\"}\n",
+        "{\"status\":\"pulling 74701a8c35f6\",\"digest\":\"sha256:74701a8c35f6\",\"total\":1321082688,\"completed\":5242880}\n",
+        "{\"status\":\"verifying sha256 digest\"}\n",
+        "{\"status\":\"writing manifest\"}\n",
+        "{\"status\":\"success\"}\n",
+    );
+
+    /// Recorded `/api/generate` output with multi-byte characters, sent with
+    /// CRLF line endings as some proxies do
+    const GENERATE_STREAM: &str = concat!(
+        "{\"model\":\"gemma2:2b\",\"response\":\"Le café\",\"done\":false}\r\n",
+        "{\"model\":\"gemma2:2b\",\"response\":\" naïve — \",\"done\":false}\r\n",
+        "{\"model\":\"gemma2:2b\",\"response\":\"日本語 👍\",\"done\":false}\r\n",
+        "{\"model\":\"gemma2:2b\",\"response\":\"\",\"done\":true,\"eval_count\":6,\"eval_duration\":120000000}",
+    );
+
+    fn expected_lines(stream: &str) -> Vec<String> {
+        stream
+            .split('\n')
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn decode(chunks: &[&[u8]]) -> Vec<String> {
+        let mut decoder = NdjsonLineDecoder::new();
+        let mut lines: Vec<String> = chunks.iter().flat_map(|c| decoder.push(c)).collect();
+        lines.extend(decoder.finish());
+        lines
+    }
+
+    #[test]
+    fn test_split_at_every_offset_is_lossless() {
+        for stream in [PULL_STREAM, GENERATE_STREAM] {
+            let bytes = stream.as_bytes();
+            let expected = expected_lines(stream);
+
+            for offset in 0..=bytes.len() {
+                let (head, tail) = bytes.split_at(offset);
+                assert_eq!(decode(&[head, tail]), expected, "split at byte {}", offset);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_size_chunks_are_lossless() {
+        for stream in [PULL_STREAM, GENERATE_STREAM] {
+            let bytes = stream.as_bytes();
+            let expected = expected_lines(stream);
+
+            for size in 1..=16 {
+                let chunks: Vec<&[u8]> = bytes.chunks(size).collect();
+                assert_eq!(decode(&chunks), expected, "chunks of {} bytes", size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_blank_lines_are_skipped() {
+        assert_eq!(
+            decode(&[b"\n\r\n{\"a\":1}\n  \n".as_slice()]),
+            vec!["{\"a\":1}".to_string()]
+        );
+        assert_eq!(decode(&[b"".as_slice()]), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,203 @@
+use super::tokens::tokens_between;
+use crate::settings::AiNumberLocale;
+
+/// Languages whose numbers are written "1.234,5"
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &["German", "Dutch", "Italian", "Spanish", "Portuguese"];
+
+/// Words that make the number after them a version rather than an amount
+const VERSION_WORDS: &[&str] = &["version", "v", "release", "build"];
+
+/// Spaces that hold a number together, as between "1 234" and "%"
+const NO_BREAK_SPACE: char = '\u{a0}';
+const NARROW_NO_BREAK_SPACE: char = '\u{202f}';
+
+/// The locale numbers are written for, `Auto` following `language` (an
+/// English name like "German") and US style when it is unknown
+pub fn resolve_number_locale(locale: AiNumberLocale, language: Option<&str>) -> AiNumberLocale {
+    match (locale, language) {
+        (AiNumberLocale::Auto, Some("French")) => AiNumberLocale::FrFr,
+        (AiNumberLocale::Auto, Some(language)) if DECIMAL_COMMA_LANGUAGES.contains(&language) => {
+            AiNumberLocale::DeDe
+        }
+        (AiNumberLocale::Auto, _) => AiNumberLocale::EnUs,
+        (locale, _) => locale,
+    }
+}
+
+/// Thousand separator, decimal mark and space before "%" in `locale`
+fn separators(locale: AiNumberLocale) -> (char, char, Option<char>) {
+    match locale {
+        AiNumberLocale::Auto | AiNumberLocale::EnUs => (',', '.', None),
+        AiNumberLocale::DeDe => ('.', ',', Some(NO_BREAK_SPACE)),
+        AiNumberLocale::FrFr => (NARROW_NO_BREAK_SPACE, ',', Some(NO_BREAK_SPACE)),
+    }
+}
+
+/// Rewrite the separators of numbers the model wrote for another locale
+/// ("1,234.5" → "1.234,5"). Only numbers standing as a word of their own
+/// whose digits group by three are touched, so versions ("2.0.1"), IP
+/// addresses, dates and times are left as they are, and so is a number
+/// like "1,234" that reads as either locale.
+pub fn fix_number_separators(text: &str, locale: AiNumberLocale) -> String {
+    let (_, _, percent_space) = separators(locale);
+    let tokens = tokens_between(text, |c: char| {
+        c.is_whitespace() && c != NO_BREAK_SPACE && c != NARROW_NO_BREAK_SPACE
+    });
+    let mut result = String::with_capacity(text.len());
+    for (i, (token, space)) in tokens.iter().enumerate() {
+        let after_version = i
+            .checked_sub(1)
+            .map(|before| {
+                tokens[before]
+                    .0
+                    .trim_matches(|c: char| !c.is_alphanumeric())
+            })
+            .is_some_and(|before| VERSION_WORDS.contains(&before.to_lowercase().as_str()));
+        let Some((start, end)) = number_at(token).filter(|_| !after_version) else {
+            result.push_str(token);
+            result.push_str(space);
+            continue;
+        };
+
+        let number = &token[start..end];
+        result.push_str(&token[..start]);
+        result.push_str(&reformat(number, locale).unwrap_or_else(|| number.to_string()));
+        let rest = &token[end..];
+        let percent = rest.trim_start_matches([NO_BREAK_SPACE, NARROW_NO_BREAK_SPACE]);
+        if percent.starts_with('%') {
+            result.extend(percent_space);
+            result.push_str(percent);
+            result.push_str(space);
+        } else if rest.is_empty()
+            && tokens
+                .get(i + 1)
+                .is_some_and(|(next, _)| next.starts_with('%'))
+            && !space.contains('\n')
+        {
+            // "10 %" split by an ordinary space
+            result.extend(percent_space);
+        } else {
+            result.push_str(rest);
+            result.push_str(space);
+        }
+    }
+    result
+}
+
+/// Byte range of the number in `token`, when the token is a number with
+/// nothing but punctuation around it
+fn number_at(token: &str) -> Option<(usize, usize)> {
+    let start = token.find(|c: char| c.is_ascii_digit())?;
+    if token[..start].contains(char::is_alphanumeric) {
+        return None;
+    }
+    let end = token[start..]
+        .find(|c: char| !c.is_ascii_digit() && !is_separator(c))
+        .map_or(token.len(), |end| start + end);
+    let end = start + token[start..end].trim_end_matches(is_separator).len();
+    if token[end..].contains(char::is_alphanumeric) {
+        return None;
+    }
+    Some((start, end))
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '.' | ',' | NO_BREAK_SPACE | NARROW_NO_BREAK_SPACE)
+}
+
+/// `number` written with the separators of `locale`, or `None` when its
+/// digits don't group as thousands or it reads the same in either locale
+fn reformat(number: &str, locale: AiNumberLocale) -> Option<String> {
+    let runs: Vec<&str> = number.split(is_separator).collect();
+    let marks: Vec<char> = number.chars().filter(|&c| is_separator(c)).collect();
+    let (&last, groups) = marks.split_last()?;
+    let is_space = |c: char| c == NO_BREAK_SPACE || c == NARROW_NO_BREAK_SPACE;
+    let grouped = |runs: &[&str]| {
+        (1..=3).contains(&runs[0].len()) && runs[1..].iter().all(|run| run.len() == 3)
+    };
+
+    let (whole, decimals) = if groups.iter().all(|&mark| mark == last) {
+        if marks.len() > 1 || is_space(last) {
+            if !grouped(&runs) {
+                return None;
+            }
+            (&runs[..], None)
+        } else if runs[0].len() <= 3 && runs[1].len() == 3 {
+            // "1,234" is a thousand in one locale and a fraction in the other
+            return None;
+        } else {
+            (&runs[..1], Some(runs[1]))
+        }
+    } else {
+        let group = groups[0];
+        if groups.iter().any(|&mark| mark != group) || is_space(last) {
+            return None;
+        }
+        let whole = &runs[..runs.len() - 1];
+        if !grouped(whole) {
+            return None;
+        }
+        (whole, runs.last().copied())
+    };
+
+    let (thousands, decimal, _) = separators(locale);
+    let mut formatted = whole.join(&thousands.to_string());
+    if let Some(decimals) = decimals {
+        formatted.push(decimal);
+        formatted.push_str(decimals);
+    }
+    Some(formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Numbers as the model may write them, and how each locale fixes them
+    const NUMBER_FORMATS: &str = include_str!("fixtures/number_formats.txt");
+
+    #[test]
+    fn test_number_formats_fixture() {
+        let locales = [
+            AiNumberLocale::EnUs,
+            AiNumberLocale::DeDe,
+            AiNumberLocale::FrFr,
+        ];
+        for line in NUMBER_FORMATS.lines().filter(|line| !line.starts_with('#')) {
+            let columns: Vec<&str> = line.split('|').map(str::trim).collect();
+            assert_eq!(columns.len(), 1 + locales.len(), "{:?}", line);
+            for (locale, expected) in locales.iter().zip(&columns[1..]) {
+                assert_eq!(
+                    fix_number_separators(columns[0], *locale),
+                    *expected,
+                    "{:?} for {:?}",
+                    columns[0],
+                    locale
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_keeps_layout() {
+        assert_eq!(
+            fix_number_separators("  Total: 1,234.5\n\nNext  line 10 %", AiNumberLocale::EnUs),
+            "  Total: 1,234.5\n\nNext  line 10%"
+        );
+    }
+
+    #[test]
+    fn test_resolve_number_locale() {
+        let cases = [
+            (AiNumberLocale::Auto, None, AiNumberLocale::EnUs),
+            (AiNumberLocale::Auto, Some("English"), AiNumberLocale::EnUs),
+            (AiNumberLocale::Auto, Some("German"), AiNumberLocale::DeDe),
+            (AiNumberLocale::Auto, Some("French"), AiNumberLocale::FrFr),
+            (AiNumberLocale::FrFr, Some("German"), AiNumberLocale::FrFr),
+            (AiNumberLocale::EnUs, Some("French"), AiNumberLocale::EnUs),
+        ];
+        for (locale, language, expected) in cases {
+            assert_eq!(resolve_number_locale(locale, language), expected);
+        }
+    }
+}
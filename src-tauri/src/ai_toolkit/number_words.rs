@@ -0,0 +1,421 @@
+use super::capitalization::capitalize_first;
+use super::tokens::tokens;
+use anyhow::{anyhow, Result};
+
+/// Numbers below this can be written as words, which is as far as
+/// [`number_word`] goes
+pub const MAX_NUMBER_STYLE_THRESHOLD: u32 = 100;
+
+const UNITS: &[&str] = &[
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: &[&str] = &[
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const UNIT_ORDINALS: &[&str] = &[
+    "zeroth",
+    "first",
+    "second",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "eighth",
+    "ninth",
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+];
+const TENS_ORDINALS: &[&str] = &[
+    "twentieth",
+    "thirtieth",
+    "fortieth",
+    "fiftieth",
+    "sixtieth",
+    "seventieth",
+    "eightieth",
+    "ninetieth",
+];
+
+/// Words scaling the number before them, which only digits leave out
+const SCALES: &[&str] = &["hundred", "thousand", "million", "billion", "trillion"];
+
+/// Units, currencies and times a number is measured in, which keep it in
+/// digits ("5 km", "3 pm")
+const MEASURES: &[&str] = &[
+    "%", "percent", "°", "°c", "°f", "degrees", "mm", "cm", "m", "km", "ft", "mi", "mg", "g", "kg",
+    "oz", "lb", "lbs", "ml", "l", "kb", "mb", "gb", "tb", "hz", "khz", "mhz", "ghz", "px", "pt",
+    "mph", "kph", "km/h", "am", "pm", "a.m", "p.m", "dollars", "euros", "pounds", "cents", "usd",
+    "eur", "gbp",
+];
+
+/// Words that make the number after them a label rather than an amount
+const LABELS: &[&str] = &[
+    "page", "chapter", "section", "figure", "table", "step", "room", "version", "v", "no",
+    "number", "level", "episode", "season", "issue", "part", "grade",
+];
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+    "jan",
+    "feb",
+    "mar",
+    "apr",
+    "jun",
+    "jul",
+    "aug",
+    "sep",
+    "sept",
+    "oct",
+    "nov",
+    "dec",
+];
+
+/// Words joining the two ends of a range, which are written alike
+const RANGE_WORDS: &[&str] = &["to", "through"];
+
+/// Check the number below which numbers are written as words
+pub fn validate_number_style_threshold(threshold: u32) -> Result<()> {
+    if threshold > MAX_NUMBER_STYLE_THRESHOLD {
+        return Err(anyhow!(
+            "Numbers can be written as words below at most {}, not {}",
+            MAX_NUMBER_STYLE_THRESHOLD,
+            threshold
+        ));
+    }
+    Ok(())
+}
+
+/// `n` written out, like "seven" or "forty-two", for `n` below 100
+pub fn number_word(n: u32) -> Option<String> {
+    match n {
+        0..=19 => Some(UNITS[n as usize].to_string()),
+        20..=99 if n.is_multiple_of(10) => Some(TENS[n as usize / 10 - 2].to_string()),
+        20..=99 => Some(format!(
+            "{}-{}",
+            TENS[n as usize / 10 - 2],
+            UNITS[n as usize % 10]
+        )),
+        _ => None,
+    }
+}
+
+/// Ordinal `n` written out, like "third" or "twenty-first", for `n` below
+/// 100
+pub fn ordinal_word(n: u32) -> Option<String> {
+    match n {
+        0..=19 => Some(UNIT_ORDINALS[n as usize].to_string()),
+        20..=99 if n.is_multiple_of(10) => Some(TENS_ORDINALS[n as usize / 10 - 2].to_string()),
+        20..=99 => Some(format!(
+            "{}-{}",
+            TENS[n as usize / 10 - 2],
+            UNIT_ORDINALS[n as usize % 10]
+        )),
+        _ => None,
+    }
+}
+
+/// Whether `word`, in lowercase, is part of a number written out, like
+/// "forty", "hundred" or "third", which may end up in digits
+pub fn is_number_word(word: &str) -> bool {
+    word.split('-').all(|part| {
+        [UNITS, TENS, UNIT_ORDINALS, TENS_ORDINALS, SCALES]
+            .iter()
+            .any(|words| words.contains(&part))
+    })
+}
+
+/// Ordinal `n` in digits, like "3rd" or "12th"
+pub fn ordinal_digits(n: u32) -> String {
+    format!("{}{}", n, ordinal_suffix(n))
+}
+
+fn ordinal_suffix(n: u32) -> &'static str {
+    match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// A number standing as a word of its own, as the model wrote it
+#[derive(Clone, Copy)]
+struct Number {
+    value: u32,
+    ordinal: bool,
+    /// Whether it stays in digits whatever its value
+    kept: bool,
+}
+
+/// Write the whole numbers below `threshold` that the model put in digits
+/// as words, ordinals included ("3rd" → "third"), for the house style of
+/// spelling out small numbers. A number starting a sentence is capitalized.
+/// Numbers measured in a unit or currency, in dates, after labels like
+/// "page", and list markers stay in digits, and so do both ends of a range
+/// when either does ("5 to 12"). A threshold of 0 keeps every number in
+/// digits.
+pub fn spell_small_numbers(text: &str, threshold: u32) -> String {
+    if threshold == 0 {
+        return text.to_string();
+    }
+
+    let tokens = tokens(text);
+    let key = |i: usize| {
+        tokens.get(i).map(|(token, _)| {
+            token
+                .trim_matches(|c: char| !c.is_alphanumeric() && !"%°".contains(c))
+                .to_lowercase()
+        })
+    };
+    let line_start =
+        |i: usize| i == 0 || tokens[i - 1].0.is_empty() || tokens[i - 1].1.contains('\n');
+    let sentence_start = |i: usize| {
+        line_start(i)
+            || tokens[i - 1]
+                .0
+                .trim_end_matches(['"', '\'', ')', '”', '’'])
+                .ends_with(['.', '!', '?'])
+    };
+
+    let mut numbers: Vec<Option<Number>> = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, (token, space))| {
+            let (_, core, after) = split_core(token);
+            let (value, ordinal) = parse_number(core)?;
+            let before = i.checked_sub(1).and_then(key).unwrap_or_default();
+            let next = key(i + 1).unwrap_or_default();
+            let measured = MEASURES.contains(&next.as_str()) && after.is_empty();
+            let dated = MONTHS.contains(&before.as_str())
+                || MONTHS.contains(&next.as_str())
+                || (next == "of"
+                    && key(i + 2).is_some_and(|month| MONTHS.contains(&month.as_str())));
+            let labelled = LABELS.contains(&before.as_str());
+            let list_marker = line_start(i) && (after == "." || after == ")") && !space.is_empty();
+            Some(Number {
+                value,
+                ordinal,
+                kept: measured || dated || labelled || list_marker || value >= threshold,
+            })
+        })
+        .collect();
+
+    // Both ends of a range are written alike
+    for i in 0..tokens.len().saturating_sub(2) {
+        let ranged = key(i + 1).is_some_and(|word| RANGE_WORDS.contains(&word.as_str()));
+        if let (true, Some(from), Some(to)) = (ranged, numbers[i], numbers[i + 2]) {
+            if from.kept || to.kept {
+                numbers[i] = Some(Number { kept: true, ..from });
+                numbers[i + 2] = Some(Number { kept: true, ..to });
+            }
+        }
+    }
+
+    let mut result = String::with_capacity(text.len());
+    for (i, ((token, space), number)) in tokens.iter().zip(&numbers).enumerate() {
+        let word = number.filter(|number| !number.kept).and_then(|number| {
+            if number.ordinal {
+                ordinal_word(number.value)
+            } else {
+                number_word(number.value)
+            }
+        });
+        match word {
+            Some(word) => {
+                let (before, _, after) = split_core(token);
+                result.push_str(before);
+                if sentence_start(i) {
+                    result.push_str(&capitalize_first(&word));
+                } else {
+                    result.push_str(&word);
+                }
+                result.push_str(after);
+            }
+            None => result.push_str(token),
+        }
+        result.push_str(space);
+    }
+    result
+}
+
+/// The value of a whole number or ordinal written in digits, like "7" or
+/// "3rd", and whether it is an ordinal
+fn parse_number(core: &str) -> Option<(u32, bool)> {
+    let digits = core.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if digits.len() > 1 && digits.starts_with('0') {
+        return None;
+    }
+    let value: u32 = digits.parse().ok()?;
+    let suffix = &core[digits.len()..];
+    if suffix.is_empty() {
+        Some((value, false))
+    } else if suffix.eq_ignore_ascii_case(ordinal_suffix(value)) {
+        Some((value, true))
+    } else {
+        None
+    }
+}
+
+/// `token` split into the punctuation before its word, the word and the
+/// punctuation after it
+fn split_core(token: &str) -> (&str, &str, &str) {
+    let start = token.len() - token.trim_start_matches(['(', '"', '\'', '“', '‘']).len();
+    let rest = &token[start..];
+    let core = rest.trim_end_matches([',', '.', ';', ':', '!', '?', ')', '"', '\'', '”', '’']);
+    (&token[..start], core, &rest[core.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_numbers_are_spelled_out() {
+        for (written, spelled) in [
+            (
+                "We have 3 kids and 12 cousins.",
+                "We have three kids and 12 cousins.",
+            ),
+            (
+                "She came 3rd out of 15, I came 11th.",
+                "She came third out of 15, I came 11th.",
+            ),
+            ("It took 9 tries (not 2).", "It took nine tries (not two)."),
+            ("Call me in 0 minutes", "Call me in zero minutes"),
+            ("She is 10 today", "She is 10 today"),
+            (
+                "Version 3.5 has 1.5 times the speed",
+                "Version 3.5 has 1.5 times the speed",
+            ),
+            ("Ages 3-5 and the 90s", "Ages 3-5 and the 90s"),
+        ] {
+            assert_eq!(spell_small_numbers(written, 10), spelled, "{:?}", written);
+        }
+    }
+
+    #[test]
+    fn test_numbers_starting_a_sentence() {
+        for (written, spelled) in [
+            ("3 people came.", "Three people came."),
+            (
+                "We left. 2 stayed! 4 more? 5th place.",
+                "We left. Two stayed! Four more? Fifth place.",
+            ),
+            ("\"7 days,\" she said.", "\"Seven days,\" she said."),
+            ("Notes\n2 of them are done", "Notes\nTwo of them are done"),
+            // Digits the threshold keeps aren't spelled even here
+            ("25 people came.", "25 people came."),
+        ] {
+            assert_eq!(spell_small_numbers(written, 10), spelled, "{:?}", written);
+        }
+    }
+
+    #[test]
+    fn test_ranges_are_written_alike() {
+        for (written, spelled) in [
+            ("It takes 5 to 7 days", "It takes five to seven days"),
+            ("It takes 5 to 12 days", "It takes 5 to 12 days"),
+            ("It takes 12 to 5 days", "It takes 12 to 5 days"),
+            ("From 2 through 4", "From two through four"),
+            ("Walk 5 to 7 km", "Walk 5 to 7 km"),
+            ("Pick 2 or 3", "Pick two or three"),
+        ] {
+            assert_eq!(spell_small_numbers(written, 10), spelled, "{:?}", written);
+        }
+    }
+
+    #[test]
+    fn test_measured_dated_and_labelled_numbers_keep_digits() {
+        for text in [
+            "Walk 5 km and lift 3 kg",
+            "It rose 4 % to 9 percent",
+            "Meet at 3 pm or 4 a.m.",
+            "It costs 5 dollars",
+            "Due March 3 or 4 June, or the 5th of May",
+            "See page 4, chapter 2 and version 3",
+            "1. Milk\n2) Eggs",
+            "$5 and 5% and 5°",
+        ] {
+            assert_eq!(spell_small_numbers(text, 10), text, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_threshold() {
+        let text = "We had 1 cat, 4 dogs and 21 fish, the 42nd in town.";
+        assert_eq!(spell_small_numbers(text, 0), text);
+        assert_eq!(
+            spell_small_numbers(text, 2),
+            "We had one cat, 4 dogs and 21 fish, the 42nd in town."
+        );
+        assert_eq!(
+            spell_small_numbers(text, 100),
+            "We had one cat, four dogs and twenty-one fish, the forty-second in town."
+        );
+        assert_eq!(spell_small_numbers(" 3  \n", 10), " Three  \n");
+
+        assert!(validate_number_style_threshold(0).is_ok());
+        assert!(validate_number_style_threshold(100).is_ok());
+        assert!(validate_number_style_threshold(101).is_err());
+    }
+
+    #[test]
+    fn test_words_and_ordinals() {
+        assert_eq!(number_word(0).unwrap(), "zero");
+        assert_eq!(number_word(40).unwrap(), "forty");
+        assert_eq!(number_word(99).unwrap(), "ninety-nine");
+        assert_eq!(number_word(100), None);
+        assert_eq!(ordinal_word(12).unwrap(), "twelfth");
+        assert_eq!(ordinal_word(30).unwrap(), "thirtieth");
+        assert_eq!(ordinal_word(23).unwrap(), "twenty-third");
+        assert_eq!(ordinal_digits(1), "1st");
+        assert_eq!(ordinal_digits(12), "12th");
+        assert_eq!(ordinal_digits(22), "22nd");
+        assert_eq!(ordinal_digits(113), "113th");
+        assert!(parse_number("3th").is_none());
+        assert!(parse_number("07").is_none());
+    }
+}
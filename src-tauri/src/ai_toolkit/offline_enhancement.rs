@@ -0,0 +1,256 @@
+use super::capitalization::{case_first_letter, case_pronoun};
+use super::repetitions::{ends_sentence, trailing_punctuation, trim_punctuation};
+use crate::settings::AiCapitalizationStyle;
+
+/// Abbreviations whose period doesn't end a sentence, without it
+const ABBREVIATIONS: &[&str] = &[
+    "e.g", "i.e", "etc", "vs", "mr", "mrs", "ms", "dr", "st", "jr", "sr", "prof", "approx", "a.m",
+    "p.m",
+];
+
+/// Words a question may open with before the words that make it one, as in
+/// "so can you"
+const LEAD_INS: &[&str] = &[
+    "so", "and", "but", "ok", "okay", "hey", "well", "also", "oh", "um", "uh",
+];
+
+const QUESTION_WORDS: &[&str] = &[
+    "what", "where", "when", "why", "who", "whom", "whose", "which", "how",
+];
+
+/// Question words with "is" run on, which ask on their own
+const QUESTION_CONTRACTIONS: &[&str] = &["what's", "where's", "when's", "why's", "who's", "how's"];
+
+/// Words "how" asks with, as in "how long will it take"
+const HOW_MODIFIERS: &[&str] = &["many", "much", "long", "often", "far", "old"];
+
+const BE_VERBS: &[&str] = &[
+    "am", "is", "are", "was", "were", "isn't", "aren't", "wasn't", "weren't",
+];
+
+const MODALS: &[&str] = &[
+    "can",
+    "could",
+    "will",
+    "would",
+    "shall",
+    "should",
+    "may",
+    "might",
+    "can't",
+    "couldn't",
+    "won't",
+    "wouldn't",
+    "shouldn't",
+];
+
+const DO_HAVE: &[&str] = &[
+    "does", "did", "doesn't", "didn't", "has", "hasn't", "haven't", "hadn't",
+];
+
+/// "Do" and "have" start orders as often as questions ("do it now", "have
+/// someone call"), so only a pronoun after them makes a question
+const ORDERING_VERBS: &[&str] = &["do", "have"];
+
+const PRONOUNS: &[&str] = &["i", "you", "he", "she", "we", "they"];
+
+const INDEFINITES: &[&str] = &[
+    "anyone",
+    "anybody",
+    "someone",
+    "somebody",
+    "everyone",
+    "everybody",
+];
+
+/// Basic punctuation and casing for `text` by rule, for when no model can
+/// correct it: each sentence starts with a capital, and the text ends with a
+/// period, or a question mark when it plainly asks something. In English
+/// text each "i" standing alone is capitalized too. Words are never added,
+/// dropped or moved, and the marks the transcript has are kept as they are.
+///
+/// The transcription layer reports no pause timings, so sentences end only
+/// where the transcript ends one or breaks the line. Words that read like
+/// addresses, and words cased on purpose like "iPhone", keep their casing.
+pub fn enhance_offline(text: &str, english: bool) -> String {
+    let mut enhanced = String::with_capacity(text.len() + 1);
+    let mut starts_sentence = true;
+    // Whether the last word left its sentence open
+    let mut open = false;
+    // The opening words of the sentence the text ends with
+    let mut sentence: Vec<String> = Vec::new();
+    for segment in text.split_inclusive(char::is_whitespace) {
+        let (word, space) = segment.split_at(segment.trim_end().len());
+        if word.is_empty() {
+            starts_sentence |= segment.contains('\n');
+            enhanced.push_str(segment);
+            continue;
+        }
+
+        let mut word = word.to_string();
+        if english {
+            word = case_pronoun(&word, AiCapitalizationStyle::Sentence);
+        }
+        if starts_sentence {
+            sentence.clear();
+            if !is_address(&word) {
+                word = case_first_letter(&word, AiCapitalizationStyle::Sentence);
+            }
+        }
+        if sentence.len() < 5 {
+            sentence.push(trim_punctuation(&word).to_lowercase().replace('’', "'"));
+        }
+        open = !ends_sentence_here(&word);
+        starts_sentence = !open || space.contains('\n');
+        enhanced.push_str(&word);
+        enhanced.push_str(space);
+    }
+
+    // Only a sentence ending on a letter of a cased script or a digit is
+    // closed, so quotes, emoji and text in scripts with marks of their own
+    // are left alone
+    let end = enhanced.trim_end().len();
+    let closable = enhanced[..end]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_lowercase() || c.is_uppercase() || c.is_ascii_digit());
+    if closable && open {
+        let mark = if english && asks(&sentence) { '?' } else { '.' };
+        enhanced.insert(end, mark);
+    }
+    enhanced
+}
+
+/// Whether `word` closes its sentence, rather than an abbreviation, an
+/// initial or a trailing-off ellipsis
+fn ends_sentence_here(word: &str) -> bool {
+    if !ends_sentence(word) {
+        return false;
+    }
+    let trailing = trailing_punctuation(word);
+    if trailing.contains("..") || trailing.contains('…') {
+        return false;
+    }
+    let core = trim_punctuation(word).to_lowercase();
+    let initial = core.chars().count() == 1 && core.chars().all(char::is_alphabetic);
+    !(trailing.starts_with('.') && (initial || ABBREVIATIONS.contains(&core.as_str())))
+}
+
+/// Whether `word` is a link, an address or a file name, which are written
+/// in lowercase on purpose
+fn is_address(word: &str) -> bool {
+    let core = trim_punctuation(word);
+    core.contains(['@', '/', '\\', '.'])
+}
+
+/// Whether a sentence opening with `words`, in lowercase, plainly asks a
+/// question: a question word before a verb, as in "where is", or a verb
+/// before its subject, as in "can you"
+fn asks(words: &[String]) -> bool {
+    let mut words = words.iter().map(String::as_str).peekable();
+    while words.next_if(|word| LEAD_INS.contains(word)).is_some() {}
+    let Some(first) = words.next() else {
+        return false;
+    };
+    let mut second = words.next().unwrap_or("");
+    if QUESTION_CONTRACTIONS.contains(&first) {
+        return true;
+    }
+    if QUESTION_WORDS.contains(&first) {
+        if first == "how" && HOW_MODIFIERS.contains(&second) {
+            second = words.next().unwrap_or("");
+        }
+        return [BE_VERBS, MODALS, DO_HAVE, ORDERING_VERBS]
+            .iter()
+            .any(|verbs| verbs.contains(&second));
+    }
+
+    // What each kind of verb asks about besides a person, and "do" and
+    // "have" only about a person
+    let others: &[&str] = if BE_VERBS.contains(&first) {
+        &["it", "there", "this", "that", "these", "those"]
+    } else if MODALS.contains(&first) {
+        &["it", "there"]
+    } else if DO_HAVE.contains(&first) {
+        &["it", "this", "that"]
+    } else if ORDERING_VERBS.contains(&first) {
+        return PRONOUNS.contains(&second);
+    } else {
+        return false;
+    };
+    PRONOUNS.contains(&second) || INDEFINITES.contains(&second) || others.contains(&second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Transcripts and what they become without a model, from
+    /// `fixtures/offline_enhancement.txt`
+    const TRANSCRIPTS: &str = include_str!("fixtures/offline_enhancement.txt");
+
+    fn cases() -> impl Iterator<Item = (&'static str, &'static str)> {
+        TRANSCRIPTS
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .map(|line| {
+                let (transcript, enhanced) = line.split_once(" | ").unwrap();
+                (transcript, enhanced)
+            })
+    }
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| trim_punctuation(word).to_lowercase())
+            .collect()
+    }
+
+    #[test]
+    fn test_offline_enhancement_fixture() {
+        for (transcript, expected) in cases() {
+            let enhanced = enhance_offline(transcript, true);
+            assert_eq!(enhanced, expected, "{:?}", transcript);
+            // Only casing and punctuation change
+            assert_eq!(words(&enhanced), words(transcript), "{:?}", transcript);
+        }
+    }
+
+    #[test]
+    fn test_keeps_layout_and_outer_whitespace() {
+        assert_eq!(
+            enhance_offline("  so i left\n\nthen we ate ", true),
+            "  So I left\n\nThen we ate. "
+        );
+        assert_eq!(enhance_offline("", true), "");
+        assert_eq!(enhance_offline("   ", true), "   ");
+    }
+
+    #[test]
+    fn test_outside_english_only_sentences_are_cased() {
+        assert_eq!(
+            enhance_offline("i ragazzi e i cani", false),
+            "I ragazzi e i cani."
+        );
+        // A question in another language isn't recognized, but still closed
+        assert_eq!(
+            enhance_offline("kannst du mich anrufen", false),
+            "Kannst du mich anrufen."
+        );
+        // Scripts without case have marks of their own
+        assert_eq!(enhance_offline("我们明天见", false), "我们明天见");
+    }
+
+    #[test]
+    fn test_typical_utterances_take_under_a_millisecond() {
+        let transcripts: Vec<&str> = cases().map(|(transcript, _)| transcript).collect();
+        let started = Instant::now();
+        for transcript in &transcripts {
+            enhance_offline(transcript, true);
+        }
+        // Generous for an unoptimized build on a loaded machine, and still
+        // far off what a model would take
+        let average = started.elapsed() / transcripts.len() as u32;
+        assert!(average < Duration::from_millis(1), "{:?}", average);
+    }
+}
@@ -1,14 +1,115 @@
+use super::ndjson::NdjsonLineDecoder;
+use super::transport::{
+    ByteStream, HostTransport, OllamaTransport, ReqwestTransport, TransportResponse,
+};
 use anyhow::{anyhow, Result};
+use futures_util::{Stream, StreamExt};
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
-const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+pub const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// Upper bound for availability probes so an unreachable host fails fast
+const AVAILABILITY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Upper bound for a single non-streaming generate request
+const GENERATE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a pull may go without receiving any bytes before it is considered stalled
+pub const DEFAULT_PULL_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Copying a large GGUF into Ollama's blob store can go quiet for a while
+const CREATE_STALL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Tokens generated since startup, as reported by Ollama
+static GENERATED_TOKENS: AtomicU64 = AtomicU64::new(0);
+
+/// Nanoseconds Ollama spent generating `GENERATED_TOKENS`
+static GENERATION_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// Tokens generated and nanoseconds spent generating them since startup,
+/// across every host. Callers measure throughput from the difference between
+/// two readings.
+pub fn generation_totals() -> (u64, u64) {
+    (
+        GENERATED_TOKENS.load(Ordering::Relaxed),
+        GENERATION_NANOS.load(Ordering::Relaxed),
+    )
+}
+
+/// Errors from the Ollama API that callers need to tell apart
+#[derive(Debug, Clone, PartialEq)]
+pub enum OllamaError {
+    /// The pull stream stopped producing data. Carries the last progress Ollama
+    /// reported so a retry can pick up from the partially downloaded layers.
+    Stalled {
+        completed: Option<u64>,
+        total: Option<u64>,
+    },
+    /// None of the configured Ollama hosts responded
+    Unavailable,
+    /// The requested model is not installed on the Ollama host
+    ModelNotFound { model: String },
+    /// Ollama could not establish a trusted connection to the model registry,
+    /// e.g. a mirror with a self-signed certificate or serving plain HTTP
+    RegistryTls { message: String },
+    /// Creating a model failed because Ollama doesn't support its architecture
+    UnsupportedArchitecture { message: String },
+    /// The Ollama host ran out of disk space while writing model data
+    InsufficientDiskSpace,
+    /// Generation stopped at the `num_predict` limit, so the output is
+    /// likely cut off mid-sentence
+    Truncated { tokens: u32 },
+}
+
+impl fmt::Display for OllamaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OllamaError::Stalled { completed, total } => match (completed, total) {
+                (Some(c), Some(t)) => write!(f, "Model pull stalled at {} of {} bytes", c, t),
+                _ => write!(f, "Model pull stalled"),
+            },
+            OllamaError::Unavailable => {
+                write!(f, "Ollama is not available. Please ensure Ollama is running.")
+            }
+            OllamaError::ModelNotFound { model } => {
+                write!(f, "Model '{}' is not installed in Ollama", model)
+            }
+            OllamaError::RegistryTls { message } => write!(
+                f,
+                "Could not connect securely to the model registry ({}). \
+                 If the registry uses a self-signed certificate or plain HTTP, allow insecure registries.",
+                message
+            ),
+            OllamaError::UnsupportedArchitecture { message } => write!(
+                f,
+                "This model's architecture is not supported by the installed Ollama version ({})",
+                message
+            ),
+            OllamaError::InsufficientDiskSpace => {
+                write!(f, "Not enough disk space on the Ollama host to store the model")
+            }
+            OllamaError::Truncated { tokens } => {
+                write!(f, "The model's output was cut off at {} tokens", tokens)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OllamaError {}
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct OllamaModel {
     pub name: String,
     pub size: u64,
     pub modified_at: String,
+    /// Digest of the model's manifest, which changes when it is updated
+    pub digest: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,17 +118,204 @@ struct OllamaGenerateRequest {
     prompt: String,
     stream: bool,
     options: OllamaOptions,
+    /// How long Ollama keeps the model loaded afterwards, like "300s"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
+}
+
+/// The same generation asked of Ollama's chat endpoint, with the
+/// instructions and the text they apply to in separate messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<OllamaChatMessage>,
+    stream: bool,
+    options: OllamaOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct OllamaOptions {
-    temperature: f32,
-    num_predict: i32,
+struct OllamaChatMessage {
+    role: String,
+    content: String,
+}
+
+/// What a generation asks of the model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prompt<'a> {
+    /// A single prompt the model continues, sent to `/api/generate`
+    Completion(&'a str),
+    /// Instructions in the system role and the text they apply to in the
+    /// user role, sent to `/api/chat`. The model's chat template keeps the
+    /// two apart, so the text is less likely to be taken for instructions.
+    Chat { system: &'a str, user: &'a str },
+}
+
+impl<'a> From<&'a str> for Prompt<'a> {
+    fn from(prompt: &'a str) -> Self {
+        Prompt::Completion(prompt)
+    }
+}
+
+impl<'a> From<&'a String> for Prompt<'a> {
+    fn from(prompt: &'a String) -> Self {
+        Prompt::Completion(prompt)
+    }
+}
+
+impl Prompt<'_> {
+    /// The endpoint to post to and the body to post for generating with
+    /// `model` and `options`
+    fn request(
+        self,
+        model: &str,
+        stream: bool,
+        options: &OllamaOptions,
+    ) -> Result<(&'static str, serde_json::Value)> {
+        let keep_alive = options.keep_alive.map(keep_alive_param);
+        Ok(match self {
+            Prompt::Completion(prompt) => (
+                "/api/generate",
+                serde_json::to_value(OllamaGenerateRequest {
+                    model: model.to_string(),
+                    prompt: prompt.to_string(),
+                    stream,
+                    options: options.clone(),
+                    keep_alive,
+                })?,
+            ),
+            Prompt::Chat { system, user } => (
+                "/api/chat",
+                serde_json::to_value(OllamaChatRequest {
+                    model: model.to_string(),
+                    messages: [("system", system), ("user", user)]
+                        .into_iter()
+                        .map(|(role, content)| OllamaChatMessage {
+                            role: role.to_string(),
+                            content: content.to_string(),
+                        })
+                        .collect(),
+                    stream,
+                    options: options.clone(),
+                    keep_alive,
+                })?,
+            ),
+        })
+    }
+}
+
+/// Generation options forwarded to Ollama. Unset fields are left to Ollama's defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaOptions {
+    pub temperature: f32,
+    pub num_predict: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_thread: Option<u32>,
+    /// Number of layers to offload to the GPU, 0 forces CPU-only inference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_gpu: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub main_gpu: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    /// How long Ollama keeps the model loaded after a request. Sent beside
+    /// the options rather than in them; `None` leaves Ollama's default.
+    #[serde(skip)]
+    pub keep_alive: Option<Duration>,
+}
+
+impl OllamaOptions {
+    fn uses_gpu_options(&self) -> bool {
+        self.num_gpu.is_some() || self.main_gpu.is_some()
+    }
+}
+
+fn keep_alive_param(keep_alive: Duration) -> String {
+    format!("{}s", keep_alive.as_secs())
 }
 
+impl Default for OllamaOptions {
+    fn default() -> Self {
+        Self {
+            temperature: 0.1, // Low temperature for consistent corrections
+            num_predict: 512, // Limit output length
+            num_thread: None,
+            num_gpu: None,
+            main_gpu: None,
+            repeat_penalty: None,
+            keep_alive: None,
+        }
+    }
+}
+
+/// A generate response, or a chat response with its text in `message`
 #[derive(Debug, Clone, Deserialize)]
 struct OllamaGenerateResponse {
+    #[serde(default)]
     response: String,
+    #[serde(default)]
+    message: Option<OllamaChatMessage>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+    #[serde(default)]
+    eval_duration: Option<u64>,
+    #[serde(default)]
+    load_duration: Option<u64>,
+}
+
+impl OllamaGenerateResponse {
+    fn text(&self) -> &str {
+        match &self.message {
+            Some(message) => &message.content,
+            None => &self.response,
+        }
+    }
+}
+
+/// What Ollama reports about a finished generation. Older versions leave
+/// some of it out.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GenerateStats {
+    /// Nanoseconds spent loading the model, close to none when it already was
+    pub load_nanos: Option<u64>,
+    pub output_tokens: Option<u64>,
+    /// Nanoseconds spent generating the output tokens
+    pub generation_nanos: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OllamaVersionResponse {
+    version: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OllamaErrorResponse {
+    error: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PullProgress {
+    status: String,
+    #[serde(default)]
+    completed: Option<u64>,
+    #[serde(default)]
+    total: Option<u64>,
+}
+
+/// A model currently loaded into memory by Ollama
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct OllamaRunningModel {
+    pub name: String,
+    pub size: u64,
+    /// Bytes of the model resident in GPU memory; 0 means it runs on the CPU
+    #[serde(default)]
+    pub size_vram: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OllamaPsResponse {
+    models: Vec<OllamaRunningModel>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -40,40 +328,60 @@ struct OllamaModelInfo {
     name: String,
     size: u64,
     modified_at: String,
+    #[serde(default)]
+    digest: String,
 }
 
-pub struct OllamaClient {
-    base_url: String,
-    client: reqwest::Client,
+pub struct OllamaClient<T: OllamaTransport = HostTransport> {
+    transport: T,
 }
 
 impl OllamaClient {
     pub fn new() -> Self {
-        Self {
-            base_url: OLLAMA_BASE_URL.to_string(),
-            client: reqwest::Client::new(),
-        }
+        Self::with_transport(HostTransport::Http(ReqwestTransport::new(OLLAMA_BASE_URL)))
+    }
+
+    /// Connect to an `http(s)://` host or, on unix platforms, a `unix://` socket path
+    pub fn with_base_url(base_url: &str) -> Result<Self> {
+        Ok(Self::with_transport(HostTransport::from_base_url(base_url)?))
+    }
+}
+
+impl<T: OllamaTransport> OllamaClient<T> {
+    pub fn with_transport(transport: T) -> Self {
+        Self { transport }
     }
 
-    /// Check if Ollama is running
+    #[cfg(test)]
+    pub(crate) fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Check if Ollama is running. Gives up after a couple of seconds so an
+    /// unreachable host doesn't leave the UI waiting on the OS TCP timeout.
     pub async fn is_available(&self) -> bool {
-        self.client
-            .get(format!("{}/api/tags", self.base_url))
-            .send()
+        self.transport
+            .get("/api/tags", Some(AVAILABILITY_TIMEOUT))
             .await
             .is_ok()
     }
 
-    /// List all downloaded models
-    pub async fn list_models(&self) -> Result<Vec<OllamaModel>> {
+    /// Health check returning the Ollama server version, bounded by the same
+    /// short timeout as `is_available`
+    pub async fn version(&self) -> Result<String> {
         let response = self
-            .client
-            .get(format!("{}/api/tags", self.base_url))
-            .send()
-            .await?
-            .json::<OllamaListResponse>()
+            .transport
+            .get("/api/version", Some(AVAILABILITY_TIMEOUT))
             .await?;
 
+        Ok(parse_json::<OllamaVersionResponse>(response)?.version)
+    }
+
+    /// List all downloaded models
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>> {
+        let response = self.transport.get("/api/tags", None).await?;
+        let response = parse_json::<OllamaListResponse>(response)?;
+
         Ok(response
             .models
             .into_iter()
@@ -81,93 +389,222 @@ impl OllamaClient {
                 name: m.name,
                 size: m.size,
                 modified_at: m.modified_at,
+                digest: m.digest,
             })
             .collect())
     }
 
-    /// Generate text completion
-    pub async fn generate(&self, model: &str, prompt: &str) -> Result<String> {
+    /// Generate text completion, returned exactly as the model wrote it.
+    /// A [`Prompt::Chat`] is answered by the chat endpoint instead. Dropping the future cancels the request: its connection is closed,
+    /// and Ollama stops generating for it.
+    ///
+    /// Fails with [`OllamaError::Truncated`] when the output ran into
+    /// `num_predict`.
+    pub async fn generate<'a>(
+        &self,
+        model: &str,
+        prompt: impl Into<Prompt<'a>>,
+        options: &OllamaOptions,
+    ) -> Result<String> {
+        let (text, _) = self.generate_with_stats(model, prompt, options).await?;
+        Ok(text)
+    }
+
+    /// Generate text completion like [`generate`](Self::generate), along with
+    /// the timings Ollama reports for it
+    pub async fn generate_with_stats<'a>(
+        &self,
+        model: &str,
+        prompt: impl Into<Prompt<'a>>,
+        options: &OllamaOptions,
+    ) -> Result<(String, GenerateStats)> {
+        let prompt = prompt.into();
+        let mut options = options.clone();
+
+        let response = loop {
+            let (path, request) = prompt.request(model, false, &options)?;
+            let response = self
+                .transport
+                .post(path, &request, Some(GENERATE_TIMEOUT))
+                .await
+                .map_err(|e| anyhow!("Failed to generate: {}", e))?;
+
+            if response.is_success() {
+                break response;
+            }
+
+            if drop_rejected_gpu_options(response.status, &response.body, &mut options) {
+                continue;
+            }
+
+            return Err(generate_error(response.status, &response.body, model));
+        };
+
+        let result = serde_json::from_str::<OllamaGenerateResponse>(&response.body)
+            .map_err(|e| anyhow!("Failed to parse response: {}", e))?;
+        record_generate_stats(&result, &options);
+        check_truncation(&result, &options)?;
+
+        let stats = GenerateStats {
+            load_nanos: result.load_duration,
+            output_tokens: result.eval_count,
+            generation_nanos: result.eval_duration,
+        };
+        Ok((result.text().to_string(), stats))
+    }
+
+    /// Load `model` ahead of use with a throwaway request, keeping it loaded
+    /// for `keep_alive`. `options` should match the ones later requests use,
+    /// since Ollama reloads a model whose load options change.
+    pub async fn warm_up(
+        &self,
+        model: &str,
+        options: &OllamaOptions,
+        keep_alive: Duration,
+    ) -> Result<()> {
         let request = OllamaGenerateRequest {
             model: model.to_string(),
-            prompt: prompt.to_string(),
+            prompt: String::new(),
             stream: false,
             options: OllamaOptions {
-                temperature: 0.1,  // Low temperature for consistent corrections
-                num_predict: 512,  // Limit output length
+                num_predict: 1,
+                ..options.clone()
             },
+            keep_alive: Some(keep_alive_param(keep_alive)),
         };
 
         let response = self
-            .client
-            .post(format!("{}/api/generate", self.base_url))
-            .json(&request)
-            .timeout(std::time::Duration::from_secs(30))
-            .send()
+            .transport
+            .post(
+                "/api/generate",
+                &serde_json::to_value(&request)?,
+                Some(GENERATE_TIMEOUT),
+            )
             .await
-            .map_err(|e| anyhow!("Failed to generate: {}", e))?;
+            .map_err(|e| anyhow!("Failed to warm up {}: {}", model, e))?;
 
-        if !response.status().is_success() {
-            return Err(anyhow!("Ollama returned error: {}", response.status()));
+        if !response.is_success() {
+            return Err(generate_error(response.status, &response.body, model));
         }
+        Ok(())
+    }
 
-        let result = response
-            .json::<OllamaGenerateResponse>()
+    /// Unload `model` from memory now, with an empty request that asks
+    /// Ollama to keep it loaded for no time. The next request loads it again.
+    pub async fn unload(&self, model: &str) -> Result<()> {
+        let request = OllamaGenerateRequest {
+            model: model.to_string(),
+            prompt: String::new(),
+            stream: false,
+            options: OllamaOptions {
+                num_predict: 1,
+                ..Default::default()
+            },
+            keep_alive: Some(keep_alive_param(Duration::ZERO)),
+        };
+
+        let response = self
+            .transport
+            .post(
+                "/api/generate",
+                &serde_json::to_value(&request)?,
+                Some(GENERATE_TIMEOUT),
+            )
             .await
-            .map_err(|e| anyhow!("Failed to parse response: {}", e))?;
+            .map_err(|e| anyhow!("Failed to unload {}: {}", model, e))?;
 
-        Ok(result.response.trim().to_string())
+        if !response.is_success() {
+            return Err(generate_error(response.status, &response.body, model));
+        }
+        Ok(())
     }
 
-    /// Pull a model from Ollama library with progress callback
-    pub async fn pull_model_with_progress<F>(&self, model: &str, progress_callback: F) -> Result<()>
+    /// Generate text completion, handing each fragment of the response to
+    /// `on_text` as Ollama produces it. Returns the full response as it is,
+    /// like [`generate`](Self::generate).
+    ///
+    /// Fails if no data arrives for the generate timeout. Cancelled by
+    /// dropping the future, like [`generate`](Self::generate).
+    pub async fn generate_stream<'a, F>(
+        &self,
+        model: &str,
+        prompt: impl Into<Prompt<'a>>,
+        options: &OllamaOptions,
+        mut on_text: F,
+    ) -> Result<String>
     where
-        F: Fn(String, Option<u64>, Option<u64>) + Send + 'static,
+        F: FnMut(&str),
     {
-        use futures_util::StreamExt;
-        
-        #[derive(Serialize)]
-        struct PullRequest {
-            name: String,
-        }
+        let prompt = prompt.into();
+        let mut options = options.clone();
 
-        #[derive(Deserialize)]
-        struct PullProgress {
-            status: String,
-            #[serde(default)]
-            completed: Option<u64>,
-            #[serde(default)]
-            total: Option<u64>,
-        }
+        let stream = loop {
+            let (path, request) = prompt.request(model, true, &options)?;
+            let (status, stream) = self
+                .transport
+                .post_stream(path, &request)
+                .await
+                .map_err(|e| anyhow!("Failed to generate: {}", e))?;
 
-        let request = PullRequest {
-            name: model.to_string(),
+            if (200..300).contains(&status) {
+                break stream;
+            }
+
+            let body = read_body(stream).await;
+            if drop_rejected_gpu_options(status, &body, &mut options) {
+                continue;
+            }
+
+            return Err(generate_error(status, &body, model));
         };
 
+        let result = read_generate_stream(stream, GENERATE_TIMEOUT, &mut on_text).await?;
+        record_generate_stats(&result, &options);
+        check_truncation(&result, &options)?;
+
+        Ok(result.response)
+    }
+
+    /// List the models currently loaded in memory, including how much of each
+    /// is resident on the GPU
+    pub async fn list_running_models(&self) -> Result<Vec<OllamaRunningModel>> {
         let response = self
-            .client
-            .post(format!("{}/api/pull", self.base_url))
-            .json(&request)
-            .send()
+            .transport
+            .get("/api/ps", Some(AVAILABILITY_TIMEOUT))
             .await?;
 
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to pull model: {}", response.status()));
-        }
-
-        // Stream the response and report progress
-        let mut stream = response.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let bytes = chunk?;
-            // Try to parse each line as JSON
-            if let Ok(text) = String::from_utf8(bytes.to_vec()) {
-                for line in text.lines() {
-                    if let Ok(progress) = serde_json::from_str::<PullProgress>(line) {
-                        progress_callback(progress.status, progress.completed, progress.total);
-                    }
-                }
-            }
+        Ok(parse_json::<OllamaPsResponse>(response)?.models)
+    }
+
+    /// Pull a model from Ollama library with progress callback.
+    ///
+    /// Fails with [`OllamaError::Stalled`] if no data arrives for `stall_timeout`.
+    pub async fn pull_model_with_progress<F>(
+        &self,
+        model: &str,
+        stall_timeout: Duration,
+        insecure: bool,
+        progress_callback: F,
+    ) -> Result<()>
+    where
+        F: Fn(String, Option<u64>, Option<u64>) + Send + 'static,
+    {
+        let mut request = serde_json::json!({ "name": model });
+        if insecure {
+            // Lets Ollama pull from registries without a trusted TLS certificate
+            request["insecure"] = serde_json::Value::Bool(true);
+        }
+
+        let (status, stream) = self.transport.post_stream("/api/pull", &request).await?;
+
+        if !(200..300).contains(&status) {
+            return Err(anyhow!("Failed to pull model: {}", status));
         }
 
+        read_pull_stream(stream, stall_timeout, &progress_callback)
+            .await
+            .map_err(pull_error)?;
+
         // Give Ollama a moment to finalize
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
@@ -176,31 +613,57 @@ impl OllamaClient {
 
     /// Pull a model from Ollama library (simple version without progress)
     pub async fn pull_model(&self, model: &str) -> Result<()> {
-        self.pull_model_with_progress(model, |_status, _completed, _total| {
+        self.pull_model_with_progress(model, DEFAULT_PULL_STALL_TIMEOUT, false, |_status, _completed, _total| {
             // No-op callback
         }).await
     }
 
-    /// Delete a model
-    pub async fn delete_model(&self, model: &str) -> Result<()> {
-        #[derive(Serialize)]
-        struct DeleteRequest {
-            name: String,
+    /// Create a model from a Modelfile, reporting Ollama's status lines through
+    /// `progress_callback` the same way pulls do
+    pub async fn create_model_with_progress<F>(
+        &self,
+        name: &str,
+        modelfile: &str,
+        progress_callback: F,
+    ) -> Result<()>
+    where
+        F: Fn(String, Option<u64>, Option<u64>) + Send + 'static,
+    {
+        // `name` is what older Ollama releases expect, `model` the current field
+        let request = serde_json::json!({
+            "model": name,
+            "name": name,
+            "modelfile": modelfile,
+            "stream": true,
+        });
+
+        let (status, stream) = self.transport.post_stream("/api/create", &request).await?;
+
+        if !(200..300).contains(&status) {
+            return Err(anyhow!("Failed to create model: {}", status));
         }
 
-        let request = DeleteRequest {
-            name: model.to_string(),
-        };
+        read_pull_stream(stream, CREATE_STALL_TIMEOUT, &progress_callback)
+            .await
+            .map_err(create_error)
+    }
 
-        let response = self
-            .client
-            .delete(format!("{}/api/delete", self.base_url))
-            .json(&request)
-            .send()
-            .await?;
+    /// Delete a model. Deleting a model that isn't installed fails with
+    /// [`OllamaError::ModelNotFound`].
+    pub async fn delete_model(&self, model: &str) -> Result<()> {
+        let request = serde_json::json!({ "name": model });
+
+        let response = self.transport.delete("/api/delete", &request).await?;
 
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to delete model: {}", response.status()));
+        if response.status == 404 {
+            return Err(OllamaError::ModelNotFound {
+                model: model.to_string(),
+            }
+            .into());
+        }
+
+        if !response.is_success() {
+            return Err(anyhow!("Failed to delete model: {}", response.status));
         }
 
         Ok(())
@@ -213,3 +676,815 @@ impl Default for OllamaClient {
     }
 }
 
+/// Deserialize a successful JSON response body
+fn parse_json<R: DeserializeOwned>(response: TransportResponse) -> Result<R> {
+    if !response.is_success() {
+        return Err(anyhow!("Ollama returned error: {}", response.status));
+    }
+
+    serde_json::from_str(&response.body).map_err(|e| anyhow!("Failed to parse response: {}", e))
+}
+
+/// Map a failed generate response to an error, recognising a missing model.
+///
+/// Current Ollama versions answer with a 404 for unknown models while older ones
+/// use a 400; both carry an `{"error": "model '...' not found, ..."}` body.
+fn generate_error(status: u16, body: &str, model: &str) -> anyhow::Error {
+    let message = serde_json::from_str::<OllamaErrorResponse>(body)
+        .map(|r| r.error)
+        .unwrap_or_else(|_| body.trim().to_string());
+    let lower = message.to_lowercase();
+
+    if (status == 404 || status == 400) && lower.contains("model") && lower.contains("not found") {
+        return OllamaError::ModelNotFound {
+            model: model.to_string(),
+        }
+        .into();
+    }
+
+    if message.is_empty() {
+        anyhow!("Ollama returned error: {}", status)
+    } else {
+        anyhow!("Ollama returned error {}: {}", status, message)
+    }
+}
+
+/// Whether a 400 response body complains about the GPU placement options
+fn is_gpu_option_error(body: &str) -> bool {
+    let lower = body.to_lowercase();
+    lower.contains("num_gpu") || lower.contains("main_gpu")
+}
+
+/// Some hosts reject GPU placement options outright. Strips them from
+/// `options` and returns true when the request should be retried without them.
+fn drop_rejected_gpu_options(status: u16, body: &str, options: &mut OllamaOptions) -> bool {
+    if status != 400 || !options.uses_gpu_options() || !is_gpu_option_error(body) {
+        return false;
+    }
+
+    warn!(
+        "Ollama rejected GPU offload options, retrying without them: {}",
+        body.trim()
+    );
+    options.num_gpu = None;
+    options.main_gpu = None;
+    true
+}
+
+/// Log the generation speed Ollama reports and add it to the totals
+fn record_generate_stats(result: &OllamaGenerateResponse, options: &OllamaOptions) {
+    if let (Some(tokens), Some(duration_ns)) = (result.eval_count, result.eval_duration) {
+        GENERATED_TOKENS.fetch_add(tokens, Ordering::Relaxed);
+        GENERATION_NANOS.fetch_add(duration_ns, Ordering::Relaxed);
+        let seconds = duration_ns as f64 / 1_000_000_000.0;
+        debug!(
+            "Generated {} tokens in {:.2}s ({:.1} tokens/s, num_thread: {})",
+            tokens,
+            seconds,
+            if seconds > 0.0 { tokens as f64 / seconds } else { 0.0 },
+            options
+                .num_thread
+                .map_or("default".to_string(), |n| n.to_string())
+        );
+    }
+}
+
+/// Fail when generation used up the whole `num_predict` budget, as the model
+/// was most likely stopped before it finished
+fn check_truncation(result: &OllamaGenerateResponse, options: &OllamaOptions) -> Result<()> {
+    match result.eval_count {
+        Some(tokens) if options.num_predict > 0 && tokens >= options.num_predict as u64 => {
+            Err(OllamaError::Truncated {
+                tokens: tokens as u32,
+            }
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Collect the body of a failed streaming request for error reporting
+async fn read_body(stream: ByteStream) -> String {
+    let chunks: Vec<_> = stream.collect().await;
+    let bytes: Vec<u8> = chunks.into_iter().flatten().flatten().collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Consume a streaming generate response, passing each fragment to `on_text`.
+/// Returns the concatenated response along with the statistics Ollama sends
+/// on its final line.
+async fn read_generate_stream<S, B, E, F>(
+    stream: S,
+    chunk_timeout: Duration,
+    on_text: &mut F,
+) -> Result<OllamaGenerateResponse>
+where
+    S: Stream<Item = std::result::Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: Into<anyhow::Error>,
+    F: FnMut(&str),
+{
+    futures_util::pin_mut!(stream);
+
+    let mut result = OllamaGenerateResponse {
+        response: String::new(),
+        message: None,
+        eval_count: None,
+        eval_duration: None,
+        load_duration: None,
+    };
+    let mut decoder = NdjsonLineDecoder::new();
+
+    loop {
+        let chunk = match tokio::time::timeout(chunk_timeout, stream.next()).await {
+            Ok(Some(chunk)) => chunk.map_err(Into::<anyhow::Error>::into)?,
+            Ok(None) => break,
+            Err(_) => return Err(anyhow!("Ollama stopped responding while generating")),
+        };
+
+        for line in decoder.push(chunk.as_ref()) {
+            handle_generate_line(&line, &mut result, on_text)?;
+        }
+    }
+
+    if let Some(line) = decoder.finish() {
+        handle_generate_line(&line, &mut result, on_text)?;
+    }
+    Ok(result)
+}
+
+/// Parse one NDJSON line of generate output. Error lines abort generation.
+fn handle_generate_line<F>(
+    text: &str,
+    result: &mut OllamaGenerateResponse,
+    on_text: &mut F,
+) -> Result<()>
+where
+    F: FnMut(&str),
+{
+    if let Ok(error) = serde_json::from_str::<OllamaErrorResponse>(text) {
+        return Err(anyhow!("Ollama returned error: {}", error.error));
+    }
+
+    let fragment = serde_json::from_str::<OllamaGenerateResponse>(text)
+        .map_err(|e| anyhow!("Failed to parse response: {}", e))?;
+    let text = fragment.text();
+    if !text.is_empty() {
+        on_text(text);
+        result.response.push_str(text);
+    }
+    result.eval_count = fragment.eval_count.or(result.eval_count);
+    result.eval_duration = fragment.eval_duration.or(result.eval_duration);
+
+    Ok(())
+}
+
+/// Consume a pull response stream, reporting progress for every status line.
+///
+/// Each chunk must arrive within `stall_timeout`, otherwise the pull is abandoned
+/// with [`OllamaError::Stalled`] carrying the last known progress.
+async fn read_pull_stream<S, B, E, F>(
+    stream: S,
+    stall_timeout: Duration,
+    progress_callback: &F,
+) -> Result<()>
+where
+    S: Stream<Item = std::result::Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: Into<anyhow::Error>,
+    F: Fn(String, Option<u64>, Option<u64>),
+{
+    futures_util::pin_mut!(stream);
+
+    let mut completed = None;
+    let mut total = None;
+    let mut decoder = NdjsonLineDecoder::new();
+
+    loop {
+        let chunk = match tokio::time::timeout(stall_timeout, stream.next()).await {
+            Ok(Some(chunk)) => chunk.map_err(Into::<anyhow::Error>::into)?,
+            Ok(None) => break,
+            Err(_) => return Err(OllamaError::Stalled { completed, total }.into()),
+        };
+
+        for line in decoder.push(chunk.as_ref()) {
+            handle_pull_line(&line, &mut completed, &mut total, progress_callback)?;
+        }
+    }
+
+    match decoder.finish() {
+        Some(line) => handle_pull_line(&line, &mut completed, &mut total, progress_callback),
+        None => Ok(()),
+    }
+}
+
+/// Certificate and protocol failures Ollama reports when a registry isn't trusted
+fn is_registry_tls_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["x509", "tls:", "certificate", "http response to https client"]
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+/// Turn a failed pull stream into the error reported to callers. Typed errors
+/// such as stalls pass through untouched.
+fn pull_error(e: anyhow::Error) -> anyhow::Error {
+    if e.downcast_ref::<OllamaError>().is_some() {
+        return e;
+    }
+
+    let message = e.to_string();
+    if is_registry_tls_error(&message) {
+        return OllamaError::RegistryTls { message }.into();
+    }
+    anyhow!("Failed to pull model: {}", message)
+}
+
+/// Turn a failed create stream into the error reported to callers
+fn create_error(e: anyhow::Error) -> anyhow::Error {
+    if e.downcast_ref::<OllamaError>().is_some() {
+        return e;
+    }
+
+    let message = e.to_string();
+    let lower = message.to_lowercase();
+    if lower.contains("no space left") || lower.contains("not enough space") {
+        return OllamaError::InsufficientDiskSpace.into();
+    }
+    if lower.contains("architecture") || lower.contains("unsupported model") {
+        return OllamaError::UnsupportedArchitecture { message }.into();
+    }
+    anyhow!("Failed to create model: {}", message)
+}
+
+/// Parse one NDJSON line of pull output. Error lines abort the pull.
+fn handle_pull_line<F>(
+    text: &str,
+    completed: &mut Option<u64>,
+    total: &mut Option<u64>,
+    progress_callback: &F,
+) -> Result<()>
+where
+    F: Fn(String, Option<u64>, Option<u64>),
+{
+    if let Ok(error) = serde_json::from_str::<OllamaErrorResponse>(text) {
+        return Err(anyhow!(error.error));
+    }
+
+    if let Ok(progress) = serde_json::from_str::<PullProgress>(text) {
+        if progress.completed.is_some() {
+            *completed = progress.completed;
+        }
+        if progress.total.is_some() {
+            *total = progress.total;
+        }
+        progress_callback(progress.status, progress.completed, progress.total);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::mock_transport::{MockReply, MockTransport};
+    use futures_util::stream;
+    use std::sync::{Arc, Mutex};
+
+    fn model_not_found(model: &str) -> Option<OllamaError> {
+        Some(OllamaError::ModelNotFound {
+            model: model.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_generate_error_model_not_found_404() {
+        let body = r#"{"error":"model \"llama3.2:1b\" not found, try pulling it first"}"#;
+        let err = generate_error(404, body, "llama3.2:1b");
+        assert_eq!(
+            err.downcast_ref::<OllamaError>().cloned(),
+            model_not_found("llama3.2:1b")
+        );
+    }
+
+    #[test]
+    fn test_generate_error_model_not_found_legacy_400() {
+        let body = r#"{"error":"model 'gemma2:2b' not found, try pulling it first"}"#;
+        let err = generate_error(400, body, "gemma2:2b");
+        assert_eq!(
+            err.downcast_ref::<OllamaError>().cloned(),
+            model_not_found("gemma2:2b")
+        );
+    }
+
+    #[test]
+    fn test_generate_error_other_failures_stay_generic() {
+        let err = generate_error(500, r#"{"error":"out of memory"}"#, "gemma2:2b");
+        assert!(err.downcast_ref::<OllamaError>().is_none());
+        assert!(err.to_string().contains("out of memory"));
+
+        let err = generate_error(400, r#"{"error":"invalid options"}"#, "gemma2:2b");
+        assert!(err.downcast_ref::<OllamaError>().is_none());
+    }
+
+    #[test]
+    fn test_gpu_option_errors_are_recognised() {
+        assert!(is_gpu_option_error(
+            r#"{"error":"invalid value for option num_gpu"}"#
+        ));
+        assert!(is_gpu_option_error(r#"{"error":"main_gpu out of range"}"#));
+        assert!(!is_gpu_option_error(r#"{"error":"invalid prompt"}"#));
+    }
+
+    #[test]
+    fn test_is_available_fails_fast_for_unreachable_host() {
+        // 10.255.255.1 is non-routable, so the connection attempt never completes
+        let client = OllamaClient::with_base_url("http://10.255.255.1:11434").unwrap();
+        let start = std::time::Instant::now();
+
+        let available = tauri::async_runtime::block_on(client.is_available());
+
+        assert!(!available);
+        assert!(start.elapsed() < Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn test_pull_stream_reports_stall_with_last_progress() {
+        let chunks: Vec<std::result::Result<Vec<u8>, std::io::Error>> = vec![Ok(
+            b"{\"status\":\"pulling abc\",\"completed\":10,\"total\":100}\n".to_vec(),
+        )];
+        let stream = stream::iter(chunks).chain(stream::pending());
+
+        let result = tauri::async_runtime::block_on(read_pull_stream(
+            stream,
+            Duration::from_millis(50),
+            &|_: String, _: Option<u64>, _: Option<u64>| {},
+        ));
+
+        let err = result.expect_err("pull should stall");
+        assert_eq!(
+            err.downcast_ref::<OllamaError>(),
+            Some(&OllamaError::Stalled {
+                completed: Some(10),
+                total: Some(100),
+            })
+        );
+    }
+
+    #[test]
+    fn test_pull_stream_completes() {
+        let chunks: Vec<std::result::Result<Vec<u8>, std::io::Error>> = vec![
+            Ok(b"{\"status\":\"pulling manifest\"}\n".to_vec()),
+            Ok(b"{\"status\":\"success\"}\n".to_vec()),
+        ];
+        let statuses = Mutex::new(Vec::new());
+
+        let result = tauri::async_runtime::block_on(read_pull_stream(
+            stream::iter(chunks),
+            Duration::from_millis(50),
+            &|status: String, _: Option<u64>, _: Option<u64>| {
+                statuses.lock().unwrap().push(status)
+            },
+        ));
+
+        assert!(result.is_ok());
+        assert_eq!(
+            statuses.into_inner().unwrap(),
+            vec!["pulling manifest".to_string(), "success".to_string()]
+        );
+    }
+
+    fn mock_client(replies: Vec<MockReply>) -> OllamaClient<MockTransport> {
+        OllamaClient::with_transport(MockTransport::new(replies))
+    }
+
+    #[test]
+    fn test_is_available_when_host_unreachable() {
+        let client = mock_client(vec![MockReply::Unreachable]);
+        assert!(!tauri::async_runtime::block_on(client.is_available()));
+    }
+
+    #[test]
+    fn test_list_models_parses_tags() {
+        let client = mock_client(vec![MockReply::Body(
+            200,
+            r#"{"models":[{"name":"llama3.2:1b","model":"llama3.2:1b","size":1321098329,"modified_at":"2024-10-01T12:00:00Z","digest":"abc","details":{}}]}"#,
+        )]);
+
+        let models = tauri::async_runtime::block_on(client.list_models()).unwrap();
+
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "llama3.2:1b");
+        assert_eq!(models[0].size, 1321098329);
+        assert_eq!(models[0].digest, "abc");
+    }
+
+    #[test]
+    fn test_generate_keeps_response_whitespace() {
+        let client = mock_client(vec![MockReply::Body(
+            200,
+            r#"{"response":"  Hello, world.\n","done":true}"#,
+        )]);
+
+        let result = tauri::async_runtime::block_on(client.generate(
+            "gemma2:2b",
+            "prompt",
+            &OllamaOptions::default(),
+        ));
+
+        assert_eq!(result.unwrap(), "  Hello, world.\n");
+    }
+
+    #[test]
+    fn test_generate_reports_output_cut_off_at_num_predict() {
+        let client = mock_client(vec![
+            MockReply::Body(
+                200,
+                r#"{"response":"So the meeting is at","done":true,"eval_count":8}"#,
+            ),
+            MockReply::Stream(
+                200,
+                vec![
+                    "{\"response\":\"So the\",\"done\":false}\n",
+                    "{\"response\":\"\",\"done\":true,\"eval_count\":7}\n",
+                ],
+            ),
+        ]);
+        let options = OllamaOptions {
+            num_predict: 8,
+            ..Default::default()
+        };
+
+        let err = tauri::async_runtime::block_on(client.generate("gemma2:2b", "prompt", &options))
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OllamaError>(),
+            Some(&OllamaError::Truncated { tokens: 8 })
+        );
+
+        // Output that stops short of the limit is complete
+        let text = tauri::async_runtime::block_on(client.generate_stream(
+            "gemma2:2b",
+            "prompt",
+            &options,
+            |_| {},
+        ))
+        .unwrap();
+        assert_eq!(text, "So the");
+    }
+
+    #[test]
+    fn test_generate_reports_timings() {
+        let client = mock_client(vec![MockReply::Body(
+            200,
+            r#"{"response":"Hello.","done":true,"load_duration":2000000,"eval_count":3,"eval_duration":60000000}"#,
+        )]);
+
+        let (text, stats) = tauri::async_runtime::block_on(client.generate_with_stats(
+            "gemma2:2b",
+            "prompt",
+            &OllamaOptions::default(),
+        ))
+        .unwrap();
+
+        assert_eq!(text, "Hello.");
+        assert_eq!(
+            stats,
+            GenerateStats {
+                load_nanos: Some(2_000_000),
+                output_tokens: Some(3),
+                generation_nanos: Some(60_000_000),
+            }
+        );
+    }
+
+    #[test]
+    fn test_warm_up_request_shape() {
+        let client = mock_client(vec![MockReply::Body(200, r#"{"response":"","done":true}"#)]);
+        let options = OllamaOptions {
+            num_thread: Some(4),
+            ..Default::default()
+        };
+
+        tauri::async_runtime::block_on(client.warm_up(
+            "gemma2:2b",
+            &options,
+            Duration::from_secs(300),
+        ))
+        .unwrap();
+
+        let requests = client.transport.requests();
+        assert_eq!(requests.len(), 1);
+        let (method, path, body) = &requests[0];
+        assert_eq!((method.as_str(), path.as_str()), ("POST", "/api/generate"));
+        let body = body.as_ref().unwrap();
+        assert_eq!(body["model"], "gemma2:2b");
+        assert_eq!(body["prompt"], "");
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["keep_alive"], "300s");
+        // Only the output is cut short, the load options are kept
+        assert_eq!(body["options"]["num_predict"], 1);
+        assert_eq!(body["options"]["num_thread"], 4);
+    }
+
+    #[test]
+    fn test_unload_and_keep_alive_request_shape() {
+        let client = mock_client(vec![
+            MockReply::Body(200, r#"{"response":"ok","done":true}"#),
+            MockReply::Body(200, r#"{"response":"","done":true}"#),
+        ]);
+        let options = OllamaOptions {
+            keep_alive: Some(Duration::from_secs(660)),
+            ..Default::default()
+        };
+
+        tauri::async_runtime::block_on(async {
+            client.generate("gemma2:2b", "prompt", &options).await?;
+            client.unload("gemma2:2b").await
+        })
+        .unwrap();
+
+        let requests = client.transport.requests();
+        let generate = requests[0].2.as_ref().unwrap();
+        assert_eq!(generate["keep_alive"], "660s");
+        assert!(generate["options"].get("keep_alive").is_none());
+        let unload = requests[1].2.as_ref().unwrap();
+        assert_eq!(unload["model"], "gemma2:2b");
+        assert_eq!(unload["prompt"], "");
+        assert_eq!(unload["keep_alive"], "0s");
+    }
+
+    #[test]
+    fn test_chat_prompt_keeps_the_text_in_the_user_message() {
+        let client = mock_client(vec![
+            MockReply::Body(
+                200,
+                r#"{"message":{"role":"assistant","content":"Hello, world."},"done":true}"#,
+            ),
+            MockReply::Stream(
+                200,
+                vec![
+                    "{\"message\":{\"role\":\"assistant\",\"content\":\"Hello,\"},\"done\":false}\n",
+                    "{\"message\":{\"role\":\"assistant\",\"content\":\" world.\"},\"done\":true}\n",
+                ],
+            ),
+        ]);
+        let prompt = Prompt::Chat {
+            system: "Correct the text.",
+            user: "hello world",
+        };
+
+        let (generated, streamed) = tauri::async_runtime::block_on(async {
+            let generated = client
+                .generate("gemma2:2b", prompt, &OllamaOptions::default())
+                .await?;
+            let streamed = client
+                .generate_stream("gemma2:2b", prompt, &OllamaOptions::default(), |_| {})
+                .await?;
+            anyhow::Ok((generated, streamed))
+        })
+        .unwrap();
+        assert_eq!(generated, "Hello, world.");
+        assert_eq!(streamed, "Hello, world.");
+
+        for (_, path, body) in client.transport.requests() {
+            assert_eq!(path, "/api/chat");
+            let body = body.unwrap();
+            assert!(body.get("prompt").is_none());
+            assert_eq!(body["messages"][0]["role"], "system");
+            assert_eq!(body["messages"][0]["content"], "Correct the text.");
+            assert_eq!(body["messages"][1]["role"], "user");
+            assert_eq!(body["messages"][1]["content"], "hello world");
+        }
+    }
+
+    #[test]
+    fn test_generate_maps_missing_model() {
+        let client = mock_client(vec![MockReply::Body(
+            404,
+            r#"{"error":"model 'gemma2:2b' not found, try pulling it first"}"#,
+        )]);
+
+        let err = tauri::async_runtime::block_on(client.generate(
+            "gemma2:2b",
+            "prompt",
+            &OllamaOptions::default(),
+        ))
+        .unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<OllamaError>().cloned(),
+            model_not_found("gemma2:2b")
+        );
+    }
+
+    #[test]
+    fn test_generate_retries_without_rejected_gpu_options() {
+        let client = mock_client(vec![
+            MockReply::Body(400, r#"{"error":"invalid option num_gpu"}"#),
+            MockReply::Body(200, r#"{"response":"ok"}"#),
+        ]);
+        let options = OllamaOptions {
+            num_gpu: Some(0),
+            ..Default::default()
+        };
+
+        let result =
+            tauri::async_runtime::block_on(client.generate("gemma2:2b", "prompt", &options));
+
+        assert_eq!(result.unwrap(), "ok");
+        let requests = client.transport.requests();
+        assert_eq!(requests.len(), 2);
+        let retried_options = &requests[1].2.as_ref().unwrap()["options"];
+        assert!(retried_options.get("num_gpu").is_none());
+    }
+
+    #[test]
+    fn test_pull_handles_lines_split_across_chunks() {
+        let client = mock_client(vec![MockReply::Stream(
+            200,
+            vec![
+                "{\"status\":\"pulling manifest\"}\n{\"status\":\"downl",
+                "oading\",\"completed\":5,\"total\":10}\n",
+                "{\"status\":\"success\"}",
+            ],
+        )]);
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let recorded = statuses.clone();
+
+        let result = tauri::async_runtime::block_on(client.pull_model_with_progress(
+            "gemma2:2b",
+            Duration::from_secs(1),
+            false,
+            move |status, _, _| recorded.lock().unwrap().push(status),
+        ));
+
+        assert!(result.is_ok());
+        assert_eq!(
+            *statuses.lock().unwrap(),
+            vec!["pulling manifest", "downloading", "success"]
+        );
+    }
+
+    #[test]
+    fn test_generate_stream_reassembles_text_split_at_any_byte() {
+        let body = concat!(
+            "{\"response\":\"Le café\",\"done\":false}\r\n",
+            "{\"response\":\" naïve — 日本語 👍\",\"done\":false}\r\n",
+            "{\"response\":\"\",\"done\":true,\"eval_count\":6,\"eval_duration\":1000}",
+        )
+        .as_bytes();
+
+        for offset in 0..=body.len() {
+            let (head, tail) = body.split_at(offset);
+            let chunks: Vec<std::result::Result<Vec<u8>, std::io::Error>> =
+                vec![Ok(head.to_vec()), Ok(tail.to_vec())];
+            let mut streamed = String::new();
+
+            let result = tauri::async_runtime::block_on(read_generate_stream(
+                stream::iter(chunks),
+                Duration::from_secs(1),
+                &mut |text: &str| streamed.push_str(text),
+            ))
+            .unwrap();
+
+            assert_eq!(result.response, "Le café naïve — 日本語 👍", "split at byte {}", offset);
+            assert_eq!(streamed, result.response);
+            assert_eq!(result.eval_count, Some(6));
+        }
+    }
+
+    #[test]
+    fn test_generate_stream_maps_missing_model() {
+        let client = mock_client(vec![MockReply::Stream(
+            404,
+            vec!["{\"error\":\"model 'gemma2:2b' not found, try pulling it first\"}"],
+        )]);
+
+        let err = tauri::async_runtime::block_on(client.generate_stream(
+            "gemma2:2b",
+            "prompt",
+            &OllamaOptions::default(),
+            |_| {},
+        ))
+        .unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<OllamaError>().cloned(),
+            model_not_found("gemma2:2b")
+        );
+        assert_eq!(client.transport.requests()[0].2.as_ref().unwrap()["stream"], true);
+    }
+
+    #[test]
+    fn test_pull_fails_on_error_line() {
+        let client = mock_client(vec![MockReply::Stream(
+            200,
+            vec![
+                "{\"status\":\"pulling manifest\"}\n",
+                "{\"error\":\"pull model manifest: file does not exist\"}\n",
+            ],
+        )]);
+
+        let err = tauri::async_runtime::block_on(client.pull_model("missing:latest")).unwrap_err();
+
+        assert!(err.to_string().contains("file does not exist"));
+    }
+
+    #[test]
+    fn test_create_model_streams_status_and_maps_errors() {
+        let client = mock_client(vec![
+            MockReply::Stream(
+                200,
+                vec![
+                    "{\"status\":\"parsing modelfile\"}\n",
+                    "{\"status\":\"success\"}\n",
+                ],
+            ),
+            MockReply::Stream(
+                200,
+                vec!["{\"error\":\"unsupported architecture \\\"mamba2\\\"\"}\n"],
+            ),
+            MockReply::Stream(
+                200,
+                vec!["{\"error\":\"write /models/blobs/sha256-abc: no space left on device\"}\n"],
+            ),
+        ]);
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let recorded = statuses.clone();
+
+        tauri::async_runtime::block_on(client.create_model_with_progress(
+            "my-model",
+            "FROM /models/my-model.gguf\n",
+            move |status, _, _| recorded.lock().unwrap().push(status),
+        ))
+        .unwrap();
+        assert_eq!(*statuses.lock().unwrap(), vec!["parsing modelfile", "success"]);
+        let request = client.transport.requests()[0].2.clone().unwrap();
+        assert_eq!(request["modelfile"], "FROM /models/my-model.gguf\n");
+
+        let noop = |_: String, _: Option<u64>, _: Option<u64>| {};
+        let err = tauri::async_runtime::block_on(client.create_model_with_progress(
+            "my-model", "FROM x", noop,
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<OllamaError>(),
+            Some(OllamaError::UnsupportedArchitecture { .. })
+        ));
+
+        let err = tauri::async_runtime::block_on(client.create_model_with_progress(
+            "my-model", "FROM x", noop,
+        ))
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OllamaError>(),
+            Some(&OllamaError::InsufficientDiskSpace)
+        );
+    }
+
+    #[test]
+    fn test_pull_surfaces_registry_tls_errors() {
+        let client = mock_client(vec![MockReply::Stream(
+            200,
+            vec![
+                "{\"error\":\"pull model manifest: tls: failed to verify certificate: x509: certificate signed by unknown authority\"}\n",
+            ],
+        )]);
+
+        let err = tauri::async_runtime::block_on(client.pull_model_with_progress(
+            "mirror.local/library/gemma2:2b",
+            Duration::from_secs(1),
+            true,
+            |_: String, _: Option<u64>, _: Option<u64>| {},
+        ))
+        .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<OllamaError>(),
+            Some(OllamaError::RegistryTls { .. })
+        ));
+        let request = &client.transport.requests()[0];
+        assert_eq!(request.2.as_ref().unwrap()["insecure"], true);
+    }
+
+    #[test]
+    fn test_delete_model() {
+        let client = mock_client(vec![MockReply::Body(200, ""), MockReply::Body(404, "")]);
+
+        assert!(tauri::async_runtime::block_on(client.delete_model("gemma2:2b")).is_ok());
+        let err = tauri::async_runtime::block_on(client.delete_model("gemma2:2b")).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OllamaError>().cloned(),
+            model_not_found("gemma2:2b")
+        );
+
+        let requests = client.transport.requests();
+        assert_eq!(requests[0].0, "DELETE");
+        assert_eq!(requests[0].1, "/api/delete");
+        assert_eq!(
+            requests[0].2,
+            Some(serde_json::json!({ "name": "gemma2:2b" }))
+        );
+    }
+}
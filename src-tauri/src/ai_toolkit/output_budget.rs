@@ -0,0 +1,118 @@
+use super::chunking::estimate_tokens;
+
+/// Output tokens allowed per estimated token of input. Corrections come out
+/// about as long as what was dictated, a little longer with punctuation
+/// added.
+const OUTPUT_TOKENS_PER_INPUT_TOKEN: f64 = 1.3;
+
+/// Added to every budget, for short inputs that gain a lot of punctuation
+const OUTPUT_HEADROOM: usize = 64;
+
+/// Smallest budget handed out, however short the input
+pub const MIN_OUTPUT_BUDGET: usize = 128;
+
+/// Context window Ollama gives a model unless told otherwise, shared by the
+/// prompt and the output
+pub const DEFAULT_CONTEXT_TOKENS: usize = 2048;
+
+/// Tokens a prompt takes besides its transcript when it is cut down to its
+/// instructions, set aside when telling how much transcript fits
+const PROMPT_OVERHEAD_TOKENS: usize = 512;
+
+/// The `num_predict` to generate a rewrite of `input` with: enough for
+/// output somewhat longer than it, but no more than what's left of
+/// `context_tokens` after `prompt`, and never under [`MIN_OUTPUT_BUDGET`]
+pub fn output_budget(input: &str, prompt: &str, context_tokens: usize) -> i32 {
+    let available = context_tokens.saturating_sub(estimate_tokens(prompt));
+    wanted_output(estimate_tokens(input))
+        .min(available)
+        .max(MIN_OUTPUT_BUDGET) as i32
+}
+
+/// Tokens the prompt for rewriting `input` can take, leaving the rest of
+/// `context_tokens` for output somewhat longer than it. Anything over is cut
+/// off the front of the prompt by Ollama, instructions first.
+pub fn prompt_budget(input: &str, context_tokens: usize) -> usize {
+    let output = wanted_output(estimate_tokens(input)).max(MIN_OUTPUT_BUDGET);
+    context_tokens.saturating_sub(output)
+}
+
+/// Most estimated tokens of transcript that fit `context_tokens` along
+/// with a prompt's instructions and the output, beyond which it has to be
+/// enhanced in chunks
+pub fn max_input_tokens(context_tokens: usize) -> usize {
+    let room = context_tokens.saturating_sub(PROMPT_OVERHEAD_TOKENS + OUTPUT_HEADROOM);
+    // The transcript is in the prompt once, and comes out a little longer
+    (room as f64 / (1.0 + OUTPUT_TOKENS_PER_INPUT_TOKEN)).floor() as usize
+}
+
+/// Output tokens wanted for a rewrite of `input_tokens` tokens
+fn wanted_output(input_tokens: usize) -> usize {
+    (input_tokens as f64 * OUTPUT_TOKENS_PER_INPUT_TOKEN).ceil() as usize + OUTPUT_HEADROOM
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_input_gets_the_floor() {
+        assert_eq!(
+            output_budget("send it", "Fix: send it", DEFAULT_CONTEXT_TOKENS),
+            MIN_OUTPUT_BUDGET as i32
+        );
+    }
+
+    #[test]
+    fn test_long_transcript_gets_more_than_the_old_fixed_limit() {
+        // About 600 tokens, which a fixed limit of 512 cut off mid-sentence
+        let transcript = "so we went over the numbers for the third quarter again and ".repeat(40);
+        let prompt = format!("Correct this transcript:\n{}", transcript);
+
+        let budget = output_budget(&transcript, &prompt, 4096);
+        assert!(budget > 512, "budget {}", budget);
+        assert!(budget as usize >= estimate_tokens(&transcript));
+    }
+
+    #[test]
+    fn test_budget_fits_in_what_the_prompt_leaves_of_the_context() {
+        let transcript = "so we went over the numbers for the third quarter again and ".repeat(40);
+        let prompt = format!("Correct this transcript:\n{}", transcript);
+
+        let budget = output_budget(&transcript, &prompt, 1024);
+        assert_eq!(budget as usize, 1024 - estimate_tokens(&prompt));
+        // A prompt filling the context still leaves the floor
+        assert_eq!(
+            output_budget(&transcript, &prompt, 512),
+            MIN_OUTPUT_BUDGET as i32
+        );
+    }
+
+    #[test]
+    fn test_prompt_budget_leaves_room_for_the_output() {
+        assert_eq!(
+            prompt_budget("send it", DEFAULT_CONTEXT_TOKENS),
+            DEFAULT_CONTEXT_TOKENS - MIN_OUTPUT_BUDGET
+        );
+
+        let transcript = "so we went over the numbers for the third quarter again and ".repeat(40);
+        let budget = prompt_budget(&transcript, DEFAULT_CONTEXT_TOKENS);
+        let prompt = "x".repeat(budget * 4);
+        assert_eq!(
+            output_budget(&transcript, &prompt, DEFAULT_CONTEXT_TOKENS) as usize,
+            DEFAULT_CONTEXT_TOKENS - budget
+        );
+    }
+
+    #[test]
+    fn test_longest_input_fits_with_its_output() {
+        let max = max_input_tokens(DEFAULT_CONTEXT_TOKENS);
+        let transcript = "word ".repeat(max * 4 / 5);
+        assert!(estimate_tokens(&transcript) <= max);
+        assert!(
+            prompt_budget(&transcript, DEFAULT_CONTEXT_TOKENS)
+                >= estimate_tokens(&transcript) + PROMPT_OVERHEAD_TOKENS
+        );
+        assert_eq!(max_input_tokens(256), 0);
+    }
+}
@@ -0,0 +1,182 @@
+use super::graphemes::{strip_prefix_grapheme, strip_suffix_grapheme, trim};
+
+/// Labels models put in front of the corrected text despite being told not to
+const LABELS: &[&str] = &[
+    "corrected text",
+    "corrected transcript",
+    "corrected version",
+    "corrected",
+    "enhanced text",
+    "fixed text",
+    "output",
+    "result",
+    "summary",
+];
+
+/// Opening and closing quote characters a whole output may be wrapped in
+const QUOTE_PAIRS: &[(char, char)] = &[
+    ('"', '"'),
+    ('\'', '\''),
+    ('“', '”'),
+    ('‘', '’'),
+    ('«', '»'),
+    ('「', '」'),
+    ('『', '』'),
+];
+
+type Strip = fn(&str) -> Option<&str>;
+
+const STRIPPERS: [Strip; 3] = [strip_fence, strip_label, strip_quotes];
+
+/// Remove code fences, a leading "Corrected text:" style label and a pair of
+/// quotes that `output` is wrapped in as a whole, peeling nested wrappers one
+/// layer at a time. Wrappers the transcript `input` itself has are kept, as
+/// are quotes that only enclose part of the text. Whitespace and quotes are
+/// only removed where they stand on their own, never from under a combining
+/// mark.
+pub fn clean_output(input: &str, output: &str) -> String {
+    let input = trim(input);
+    let mut text = trim(output);
+
+    'peel: loop {
+        for strip in STRIPPERS {
+            if strip(input).is_some() {
+                continue;
+            }
+            if let Some(inner) = strip(text).map(trim).filter(|inner| !inner.is_empty()) {
+                text = inner;
+                continue 'peel;
+            }
+        }
+        return text.to_string();
+    }
+}
+
+/// Body of a single fenced block, without an info string such as `text`
+fn strip_fence(text: &str) -> Option<&str> {
+    let body = text.strip_prefix("```")?.strip_suffix("```")?;
+    if body.contains("```") {
+        return None;
+    }
+
+    match body.split_once('\n') {
+        Some((info, rest)) if is_info_string(info.trim()) => Some(rest),
+        _ => Some(body),
+    }
+}
+
+fn is_info_string(line: &str) -> bool {
+    line.chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '+'))
+}
+
+/// Text after a known label, which may be in markdown bold or a heading
+fn strip_label(text: &str) -> Option<&str> {
+    let (label, rest) = text.split_once(':')?;
+    let label = label.trim_matches(|c: char| c == '*' || c == '#' || c.is_whitespace());
+    if !LABELS.iter().any(|known| label.eq_ignore_ascii_case(known)) {
+        return None;
+    }
+
+    // "**Corrected text:**" leaves the closing bold marker behind
+    Some(rest.trim_start_matches('*'))
+}
+
+/// Text inside a pair of quotes, provided no other opening quote of the same
+/// kind sits between them. A closing smart quote inside may be an apostrophe.
+fn strip_quotes(text: &str) -> Option<&str> {
+    QUOTE_PAIRS.iter().find_map(|&(open, close)| {
+        let inner = strip_suffix_grapheme(strip_prefix_grapheme(text, open)?, close)?;
+        (!inner.contains(open)).then_some(inner)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICTATION: &str = "so um can you please call me back when you get this";
+
+    #[test]
+    fn test_cleans_observed_outputs() {
+        let cases = [
+            (
+                "\"Can you please call me back?\"",
+                "Can you please call me back?",
+            ),
+            (
+                "“Can you please call me back?”",
+                "Can you please call me back?",
+            ),
+            ("‘I’ll call you back.’", "I’ll call you back."),
+            ("```\nCan you call me back?\n```", "Can you call me back?"),
+            (
+                "```text\nCan you call me back?\n```",
+                "Can you call me back?",
+            ),
+            ("```Can you call me back?```", "Can you call me back?"),
+            (
+                "Corrected text: Please call me back.",
+                "Please call me back.",
+            ),
+            (
+                "**Corrected text:**\nPlease call me back.",
+                "Please call me back.",
+            ),
+            ("## Result:\nPlease call me back.", "Please call me back."),
+            (
+                "Corrected Transcript:\n\"Please call me back.\"",
+                "Please call me back.",
+            ),
+            (
+                "```markdown\n“Please call me back.”\n```",
+                "Please call me back.",
+            ),
+            ("  \"Please call me back.\"\n\n", "Please call me back."),
+            (
+                "He said \"call me\" and left.",
+                "He said \"call me\" and left.",
+            ),
+            (
+                "\"Yes,\" she said, \"call me.\"",
+                "\"Yes,\" she said, \"call me.\"",
+            ),
+            ("Note: call me back.", "Note: call me back."),
+            ("\"\"", "\"\""),
+        ];
+
+        for (output, expected) in cases {
+            assert_eq!(
+                clean_output(DICTATION, output),
+                expected,
+                "output: {:?}",
+                output
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrappers_of_emoji_and_cjk_text() {
+        for (output, expected) in [
+            ("「東京に行きます。」", "東京に行きます。"),
+            ("\"See you there 👨‍👩‍👧\"", "See you there 👨‍👩‍👧"),
+            ("\u{3000}“Café au lait ☕”\u{3000}", "Café au lait ☕"),
+            // A quote carrying a combining mark isn't one to strip
+            ("\"\u{301}Odd\"", "\"\u{301}Odd\""),
+        ] {
+            assert_eq!(clean_output(DICTATION, output), expected);
+        }
+    }
+
+    #[test]
+    fn test_keeps_wrappers_present_in_the_transcript() {
+        assert_eq!(
+            clean_output("\"to be or not to be\"", "\"To be, or not to be.\""),
+            "\"To be, or not to be.\""
+        );
+        assert_eq!(
+            clean_output("result: we won the game", "Result: We won the game."),
+            "Result: We won the game."
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use super::number_words::is_number_word;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Model output that was generated fine but can't be trusted as a correction
+/// of the transcript, so the original text should be used instead
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedOutput(pub OutputCheck, pub String);
+
+/// The check a model output failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputCheck {
+    /// Far longer or shorter than the transcript
+    Length,
+    /// Nothing but the prompt repeated back
+    Echo,
+    /// Protected spans lost or duplicated
+    Placeholders,
+    /// No text at all, even after a retry
+    Empty,
+    /// Too few of the transcript's words kept, as when the model answered
+    /// or carried out the dictated text instead of correcting it
+    Overlap,
+}
+
+impl fmt::Display for RejectedOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rejected model output: {}", self.1)
+    }
+}
+
+impl std::error::Error for RejectedOutput {}
+
+/// Below this many words a couple of merged or split words swing the ratios
+/// too far for them to mean anything, so short transcripts aren't checked
+const MIN_GUARDED_WORDS: usize = 6;
+
+/// Share of the transcript's distinct words a correction keeps at least.
+/// Fixing spelling and grammar changes a few of them, while a model that
+/// took the transcript for instructions, answering a question in it or
+/// writing the poem it asks for, keeps hardly any.
+pub const MIN_WORD_OVERLAP: f64 = 0.5;
+
+/// Spoken commands for breaks, which the email layout turns into whitespace
+const SPOKEN_BREAKS: &[[&str; 2]] = &[["new", "line"], ["new", "paragraph"], ["next", "paragraph"]];
+
+/// Reject enhanced text whose word or character count strays outside
+/// `min_ratio..=max_ratio` of the original transcript. Catches small models
+/// that summarize the dictation or keep writing past the end of it.
+/// Whitespace isn't counted, so paragraph breaks are free.
+pub fn check_output_length(
+    input: &str,
+    output: &str,
+    min_ratio: f64,
+    max_ratio: f64,
+) -> Result<()> {
+    let input_words = input.split_whitespace().count();
+    if input_words < MIN_GUARDED_WORDS {
+        return Ok(());
+    }
+
+    let output_words = output.split_whitespace().count();
+    let input_chars = visible_chars(input);
+    let output_chars = visible_chars(output);
+
+    for (unit, before, after) in [
+        ("words", input_words, output_words),
+        ("characters", input_chars, output_chars),
+    ] {
+        let ratio = after as f64 / before as f64;
+        if ratio < min_ratio || ratio > max_ratio {
+            return Err(RejectedOutput(
+                OutputCheck::Length,
+                format!(
+                    "enhanced text has {} {} for {} in the transcript (ratio {:.2}, allowed {:.2} to {:.2})",
+                    after, unit, before, ratio, min_ratio, max_ratio
+                ),
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Reject enhanced text keeping less than `min_overlap` of the distinct
+/// words of the original transcript. Case and the punctuation around words
+/// don't count, and neither do numbers written out, which may be put in
+/// digits.
+pub fn check_word_overlap(input: &str, output: &str, min_overlap: f64) -> Result<()> {
+    if input.split_whitespace().count() < MIN_GUARDED_WORDS {
+        return Ok(());
+    }
+
+    let kept = word_set(output);
+    let words: HashSet<String> = word_set(input)
+        .into_iter()
+        .filter(|word| !is_number_word(word))
+        .collect();
+    if words.is_empty() {
+        return Ok(());
+    }
+
+    let overlap = words.intersection(&kept).count() as f64 / words.len() as f64;
+    if overlap < min_overlap {
+        return Err(RejectedOutput(
+            OutputCheck::Overlap,
+            format!(
+                "enhanced text keeps {:.0}% of the transcript's words (at least {:.0}% required)",
+                overlap * 100.0,
+                min_overlap * 100.0
+            ),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// The distinct words of `text` in lowercase, without the punctuation
+/// around them
+fn word_set(text: &str) -> HashSet<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// `text` without spoken commands for breaks like "new paragraph", to check
+/// a layout asked for by voice against the transcript without them
+pub fn without_spoken_breaks(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let key = |word: &str| {
+        word.trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
+    };
+
+    let mut kept = Vec::with_capacity(words.len());
+    let mut i = 0;
+    while i < words.len() {
+        let is_break = words.get(i..i + 2).is_some_and(|pair| {
+            SPOKEN_BREAKS
+                .iter()
+                .any(|command| key(pair[0]) == command[0] && key(pair[1]) == command[1])
+        });
+        if is_break {
+            i += 2;
+        } else {
+            kept.push(words[i]);
+            i += 1;
+        }
+    }
+    kept.join(" ")
+}
+
+fn visible_chars(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: f64 = 0.6;
+    const MAX: f64 = 1.6;
+    const FILLER_MIN: f64 = 0.4;
+
+    const DICTATION: &str = "so i talked to sarah this morning about the quarterly numbers and she thinks \
+        we should push the launch back two weeks because the vendor still hasn't delivered the new \
+        packaging and marketing needs more time to finish the landing page before we announce anything";
+
+    #[test]
+    fn test_accepts_ordinary_corrections() {
+        let corrected = "So I talked to Sarah this morning about the quarterly numbers, and she thinks \
+            we should push the launch back two weeks because the vendor still hasn't delivered the new \
+            packaging, and marketing needs more time to finish the landing page before we announce anything.";
+
+        assert!(check_output_length(DICTATION, corrected, MIN, MAX).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_summaries() {
+        let summary = "Sarah suggests delaying the launch by two weeks. \
+            The vendor and marketing need more time.";
+
+        assert!(check_output_length(DICTATION, summary, MIN, MAX).is_err());
+    }
+
+    #[test]
+    fn test_rejects_runaway_output() {
+        let runaway = "So I talked to Sarah this morning about the quarterly numbers, and she thinks \
+            we should push the launch back two weeks because the vendor still hasn't delivered the new \
+            packaging, and marketing needs more time to finish the landing page before we announce anything. \
+            I agree with her assessment. A two-week delay will also give the support team time to prepare \
+            documentation, and it lets us coordinate the press release with the partner announcement. \
+            Let me know if you have any questions or would like to discuss further.";
+
+        assert!(check_output_length(DICTATION, runaway, MIN, MAX).is_err());
+    }
+
+    #[test]
+    fn test_filler_removal_needs_the_looser_bound() {
+        let input = "um so like i was uh thinking that we um should like you know go tomorrow";
+        let output = "I was thinking that we should go tomorrow.";
+
+        assert!(check_output_length(input, output, MIN, MAX).is_err());
+        assert!(check_output_length(input, output, FILLER_MIN, MAX).is_ok());
+    }
+
+    #[test]
+    fn test_paragraph_breaks_are_not_counted() {
+        let transcript = include_str!("fixtures/long_dictation.txt");
+        let paragraphs = include_str!("fixtures/long_dictation_topics.txt");
+        assert!(paragraphs.matches("\n\n").count() >= 4);
+
+        assert!(check_output_length(transcript, paragraphs, 1.0, 1.0).is_ok());
+        assert!(
+            check_output_length(transcript, &paragraphs.replace('\n', "\r\n"), 1.0, 1.0).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_spoken_breaks_are_not_counted() {
+        let transcript = "hi sarah new paragraph thanks for sending the slides over i'll go \
+            through them tonight and get back to you new paragraph best tom";
+        let email = "Hi Sarah,\n\nThanks for sending the slides over. I'll go through them \
+            tonight and get back to you.\n\nBest,\nTom";
+
+        assert!(check_output_length(transcript, email, 0.9, 1.1).is_err());
+        assert!(check_output_length(&without_spoken_breaks(transcript), email, 0.9, 1.1).is_ok());
+        assert_eq!(
+            without_spoken_breaks("a new line, New Paragraph b new ideas"),
+            "a b new ideas"
+        );
+    }
+
+    #[test]
+    fn test_split_run_ons_are_accepted() {
+        let transcript = include_str!("fixtures/run_on_dictation.txt");
+        let split = include_str!("fixtures/run_on_dictation_split.txt");
+        assert!(split.matches(". ").count() >= 8);
+
+        // The added periods are all that's new, well inside the tight bounds
+        assert!(check_output_length(transcript, split, 1.0, 1.05).is_ok());
+    }
+
+    #[test]
+    fn test_short_transcripts_are_not_checked() {
+        assert!(check_output_length("twenty five dollars", "$25", MIN, MAX).is_ok());
+    }
+
+    /// The transcript, its correction and a model's compliance with it on
+    /// each line of `fixtures/injection_transcripts.txt`
+    fn injection_transcripts() -> Vec<(&'static str, &'static str, &'static str)> {
+        include_str!("fixtures/injection_transcripts.txt")
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .map(|line| {
+                let columns: Vec<&str> = line.split(" | ").collect();
+                (columns[0], columns[1], columns[2])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_injected_instructions_are_corrected_not_followed() {
+        let transcripts = injection_transcripts();
+        assert!(transcripts.len() >= 10);
+
+        for (transcript, corrected, complied) in transcripts {
+            // Nothing but punctuation and casing was added to the correction
+            assert_eq!(word_set(corrected), word_set(transcript), "{}", transcript);
+            assert!(check_word_overlap(transcript, corrected, MIN_WORD_OVERLAP).is_ok());
+            assert!(check_output_length(transcript, corrected, MIN, MAX).is_ok());
+
+            let rejected = check_word_overlap(transcript, complied, MIN_WORD_OVERLAP)
+                .expect_err(complied)
+                .downcast::<RejectedOutput>()
+                .unwrap();
+            assert_eq!(rejected.0, OutputCheck::Overlap);
+        }
+    }
+
+    #[test]
+    fn test_overlap_allows_ordinary_corrections() {
+        let corrected = "So I talked to Sarah this morning about the quarterly numbers, and she thinks \
+            we should push the launch back 2 weeks because the vendor still hasn't delivered the new \
+            packaging.";
+        assert!(check_word_overlap(DICTATION, corrected, MIN_WORD_OVERLAP).is_ok());
+
+        // Numbers written out may come back in digits, and fillers go
+        let transcript = "um we need two hundred and fifty three chairs for the hall by friday";
+        let corrected = "We need 253 chairs for the hall by Friday.";
+        assert!(check_word_overlap(transcript, corrected, MIN_WORD_OVERLAP).is_ok());
+        assert!(check_word_overlap("what is the capital", "Canberra", MIN_WORD_OVERLAP).is_ok());
+    }
+}
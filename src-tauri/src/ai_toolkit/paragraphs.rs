@@ -0,0 +1,60 @@
+use super::chunking::sentences;
+
+/// Sentences per paragraph when breaking up a transcript without the model
+pub const SENTENCES_PER_PARAGRAPH: usize = 4;
+
+/// Break `text` into paragraphs of `sentences_per_paragraph` sentences,
+/// separated by a blank line. Stands in for the model's topic-based breaks
+/// when it isn't available. Text that already has line breaks is left alone.
+pub fn split_paragraphs(text: &str, sentences_per_paragraph: usize) -> String {
+    let text = text.trim();
+    if text.contains('\n') {
+        return text.to_string();
+    }
+
+    sentences(text)
+        .chunks(sentences_per_paragraph.max(1))
+        .map(|paragraph| paragraph.concat().trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = include_str!("fixtures/long_dictation.txt");
+    const AFTER: &str = include_str!("fixtures/long_dictation_paragraphs.txt");
+
+    #[test]
+    fn test_long_dictation_fixture() {
+        assert!(BEFORE.split_whitespace().count() >= 300);
+        assert_eq!(
+            split_paragraphs(BEFORE, SENTENCES_PER_PARAGRAPH),
+            AFTER.trim_end()
+        );
+    }
+
+    #[test]
+    fn test_paragraphs_keep_every_word() {
+        let paragraphs = split_paragraphs(BEFORE, SENTENCES_PER_PARAGRAPH);
+        assert_eq!(
+            paragraphs.split_whitespace().collect::<Vec<_>>(),
+            BEFORE.split_whitespace().collect::<Vec<_>>()
+        );
+        for paragraph in paragraphs.split("\n\n") {
+            assert!(sentences(paragraph).len() <= SENTENCES_PER_PARAGRAPH);
+        }
+    }
+
+    #[test]
+    fn test_short_and_laid_out_text_is_left_alone() {
+        let short = "See you at three. Bring the slides.";
+        assert_eq!(split_paragraphs(short, SENTENCES_PER_PARAGRAPH), short);
+
+        let laid_out = "Dear Sam,\nThanks for the notes. They help. A lot. Really. Truly.";
+        assert_eq!(split_paragraphs(laid_out, 2), laid_out);
+
+        assert_eq!(split_paragraphs("", SENTENCES_PER_PARAGRAPH), "");
+    }
+}
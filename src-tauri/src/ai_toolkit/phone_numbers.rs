@@ -0,0 +1,375 @@
+use super::tokens::tokens;
+use crate::settings::AiPhoneStyle;
+
+const DIGIT_WORDS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Said for "0" in the middle of a number, as in "oh two oh"
+const ZERO_WORDS: &[&str] = &["oh", "o"];
+
+/// Words saying the digit after them comes two or three times
+const REPEAT_WORDS: &[(&str, usize)] = &[("double", 2), ("triple", 3)];
+
+/// Words that make the digits shortly after them a phone number
+const CUE_WORDS: &[&str] = &[
+    "call",
+    "calling",
+    "phone",
+    "cell",
+    "mobile",
+    "telephone",
+    "tel",
+    "dial",
+    "text",
+    "fax",
+    "reach",
+];
+
+/// How many words before a run of digits a cue may be
+const CUE_DISTANCE: usize = 4;
+
+/// Words before "number" that make it some other number than a phone's
+const OTHER_NUMBERS: &[&str] = &[
+    "account",
+    "card",
+    "order",
+    "tracking",
+    "reference",
+    "invoice",
+    "serial",
+    "policy",
+    "case",
+    "routing",
+    "ticket",
+    "confirmation",
+];
+
+/// Punctuation that ends a run of digits, kept after the formatted number
+const ENDING_PUNCTUATION: &[char] = &['.', '!', '?', ';', ':'];
+
+/// A run of tokens read as one number: where it starts and ends, its
+/// digits, and whether it was written grouped like a phone number
+struct Run {
+    start: usize,
+    end: usize,
+    digits: String,
+    grouped: bool,
+}
+
+/// Write phone numbers dictated as digit words ("five five five one two
+/// three four") or a raw digit string in `style`. Only 7, 10 and 11 digit
+/// runs (11 and 12 for UK numbers) are phone numbers, and only when a cue
+/// like "call" or "number is" comes shortly before them or their digits
+/// were already grouped like one ("555 123 4567"). Longer runs like card
+/// numbers are never touched, and neither are years, amounts, ZIP codes or
+/// account numbers.
+pub fn format_phone_numbers(text: &str, style: AiPhoneStyle) -> String {
+    let tokens = tokens(text);
+    let keys: Vec<String> = tokens
+        .iter()
+        .map(|(token, _)| {
+            token
+                .trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .collect();
+
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let Some(run) = digit_run(&tokens, &keys, i) else {
+            result.push_str(tokens[i].0);
+            result.push_str(tokens[i].1);
+            i += 1;
+            continue;
+        };
+        let cued = run.grouped || cued(&keys, run.start);
+        // A run that isn't a phone number is kept whole, so no part of a
+        // longer number is taken for one
+        let Some(formatted) = cued.then(|| format_digits(&run.digits, style)).flatten() else {
+            for (token, space) in &tokens[run.start..run.end] {
+                result.push_str(token);
+                result.push_str(space);
+            }
+            i = run.end;
+            continue;
+        };
+
+        let (last, space) = tokens[run.end - 1];
+        let core = last.trim_end_matches(|c: char| c == ',' || ENDING_PUNCTUATION.contains(&c));
+        result.push_str(&formatted);
+        result.push_str(&last[core.len()..]);
+        result.push_str(space);
+        i = run.end;
+    }
+    result
+}
+
+/// The digits said or written from `tokens[start]` on, if a number starts
+/// there
+fn digit_run(tokens: &[(&str, &str)], keys: &[String], start: usize) -> Option<Run> {
+    let mut digits = String::new();
+    let mut all_written = true;
+    let mut groups = vec![];
+    let mut i = start;
+    while i < tokens.len() {
+        let key = keys[i].as_str();
+        let repeat = REPEAT_WORDS
+            .iter()
+            .find(|(word, _)| *word == key)
+            .and_then(|(_, times)| Some((*times, digit_word(keys.get(i + 1)?)?)));
+        let mut used = 1;
+        if let Some((times, digit)) = repeat {
+            digits.extend(std::iter::repeat_n(digit, times));
+            all_written = false;
+            used = 2;
+        } else if let Some(digit) = digit_word(key) {
+            digits.push(digit);
+            all_written = false;
+        } else if ZERO_WORDS.contains(&key)
+            && (!digits.is_empty() || keys.get(i + 1).is_some_and(|next| is_digits(next)))
+        {
+            digits.push('0');
+            all_written = false;
+        } else if let Some(written) = written_digits(tokens[i].0) {
+            groups.extend(
+                written
+                    .split(['-', '.'])
+                    .map(|group| group.chars().filter(char::is_ascii_digit).count()),
+            );
+            digits.push_str(
+                &written
+                    .chars()
+                    .filter(char::is_ascii_digit)
+                    .collect::<String>(),
+            );
+        } else {
+            break;
+        }
+        i += used;
+
+        // A sentence ends the number, while commas may group its digits
+        let last = tokens[i - 1].0;
+        if last.ends_with(ENDING_PUNCTUATION) || tokens[i - 1].1.contains('\n') {
+            break;
+        }
+    }
+
+    if digits.is_empty() {
+        return None;
+    }
+    Some(Run {
+        start,
+        end: i,
+        grouped: all_written && is_phone_grouping(&groups),
+        digits,
+    })
+}
+
+fn digit_word(key: &str) -> Option<char> {
+    DIGIT_WORDS
+        .iter()
+        .position(|word| *word == key)
+        .map(|digit| char::from(b'0' + digit as u8))
+}
+
+fn is_digits(key: &str) -> bool {
+    digit_word(key).is_some() || (!key.is_empty() && key.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// The number written in `token`, made of digits and the marks phone
+/// numbers are grouped with, without the punctuation ending it
+fn written_digits(token: &str) -> Option<&str> {
+    let core = token.trim_end_matches(|c: char| c == ',' || ENDING_PUNCTUATION.contains(&c));
+    let is_mark = |c: char| matches!(c, '-' | '.' | '(' | ')' | '+');
+    (core.chars().any(|c| c.is_ascii_digit())
+        && core.chars().all(|c| c.is_ascii_digit() || is_mark(c)))
+    .then_some(core)
+}
+
+/// Whether digits written in groups of `groups` digits look like a phone
+/// number, as "555 123 4567" or "+44 20 7946 0958" do and "4111 1111 1111
+/// 1111", "5 10 15 20" or "2025" don't on length alone
+fn is_phone_grouping(groups: &[usize]) -> bool {
+    let groups: Vec<usize> = groups.iter().copied().filter(|&len| len > 0).collect();
+    groups.len() >= 2
+        && (1..=3).contains(&groups[0])
+        && groups[1..].iter().all(|len| (2..=6).contains(len))
+        && groups[groups.len() - 1] >= 4
+}
+
+/// Whether a word within [`CUE_DISTANCE`] words before `start` says a phone
+/// number follows
+fn cued(keys: &[String], start: usize) -> bool {
+    let from = start.saturating_sub(CUE_DISTANCE);
+    (from..start).any(|i| {
+        let key = keys[i].as_str();
+        if CUE_WORDS.contains(&key) {
+            return true;
+        }
+        let other = i
+            .checked_sub(1)
+            .is_some_and(|before| OTHER_NUMBERS.contains(&keys[before].as_str()));
+        key == "number" && !other && keys.get(i + 1).is_some_and(|next| next == "is")
+    })
+}
+
+/// `digits` written as a phone number in `style`, when there are as many
+/// as a phone number there has
+fn format_digits(digits: &str, style: AiPhoneStyle) -> Option<String> {
+    match style {
+        AiPhoneStyle::Us => match digits.len() {
+            7 => Some(format!("{}-{}", &digits[..3], &digits[3..])),
+            10 => Some(format!(
+                "({}) {}-{}",
+                &digits[..3],
+                &digits[3..6],
+                &digits[6..]
+            )),
+            11 if digits.starts_with('1') => Some(format!(
+                "+1 ({}) {}-{}",
+                &digits[1..4],
+                &digits[4..7],
+                &digits[7..]
+            )),
+            _ => None,
+        },
+        AiPhoneStyle::Uk => {
+            let national = match digits.len() {
+                11 if digits.starts_with('0') => &digits[1..],
+                12 if digits.starts_with("44") => &digits[2..],
+                _ => return None,
+            };
+            // London and other "02" numbers group 2-4-4, the rest 4-6
+            if national.starts_with('2') {
+                Some(format!(
+                    "+44 {} {} {}",
+                    &national[..2],
+                    &national[2..6],
+                    &national[6..]
+                ))
+            } else {
+                Some(format!("+44 {} {}", &national[..4], &national[4..]))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cued_numbers_are_formatted() {
+        for (dictated, formatted) in [
+            (
+                "Call me at five five five one two three four five six seven.",
+                "Call me at (555) 123-4567.",
+            ),
+            ("My number is 5551234567", "My number is (555) 123-4567"),
+            ("Text 555 1234 tonight", "Text 555-1234 tonight"),
+            (
+                "Phone: one eight hundred five five five one two one two",
+                "Phone: one eight hundred 555-1212",
+            ),
+            (
+                "call one five five five one two three four five six seven, thanks",
+                "call +1 (555) 123-4567, thanks",
+            ),
+            ("dial triple five double two three four", "dial 555-2234"),
+            (
+                "Reach me on five five five, one two three, four five six seven!",
+                "Reach me on (555) 123-4567!",
+            ),
+        ] {
+            assert_eq!(
+                format_phone_numbers(dictated, AiPhoneStyle::Us),
+                formatted,
+                "{:?}",
+                dictated
+            );
+        }
+    }
+
+    #[test]
+    fn test_grouped_numbers_need_no_cue() {
+        for (written, formatted) in [
+            ("Ring 555-123-4567 later", "Ring (555) 123-4567 later"),
+            ("It's 555.123.4567.", "It's (555) 123-4567."),
+            ("Try 1 555 123 4567", "Try +1 (555) 123-4567"),
+            ("Or (555) 123-4567", "Or (555) 123-4567"),
+        ] {
+            assert_eq!(
+                format_phone_numbers(written, AiPhoneStyle::Us),
+                formatted,
+                "{:?}",
+                written
+            );
+        }
+    }
+
+    #[test]
+    fn test_uk_numbers() {
+        for (dictated, formatted) in [
+            (
+                "Call me on oh two oh seven nine four six oh nine five eight.",
+                "Call me on +44 20 7946 0958.",
+            ),
+            ("mobile 07700 900123", "mobile +44 7700 900123"),
+            ("Ring +44 20 7946 0958", "Ring +44 20 7946 0958"),
+            // Seven digits are a US number only
+            ("call 555 1234", "call 555 1234"),
+        ] {
+            assert_eq!(
+                format_phone_numbers(dictated, AiPhoneStyle::Uk),
+                formatted,
+                "{:?}",
+                dictated
+            );
+        }
+    }
+
+    #[test]
+    fn test_false_positive_traps_are_left_alone() {
+        for text in [
+            // Card numbers, however they are grouped or cued
+            "My card number is 4111 1111 1111 1111",
+            "call about 4111111111111111",
+            "Pay with 4111-1111-1111-1111 today",
+            "Amex 3782 822463 10005",
+            // IDs and accounts without a phone cue
+            "Order 5551234567 has shipped",
+            "My account number is 5551234567",
+            "The tracking number is 1234567",
+            // Years, ZIP codes, amounts, dates and versions
+            "Between 1999 and 2024",
+            "in 1999 2000 2001",
+            "Mail it to 90210 or 12345-6789",
+            "It costs $5551234567",
+            "Due 2025-03-03 at 10.30",
+            "Update to 10.0.19045",
+            "Oh, I see five cats",
+            "Count 5 10 15 20 and 25",
+            // Too short or too long even with a cue
+            "call 911",
+            "call me at five five five one two three",
+            "phone 55512345678901",
+        ] {
+            assert_eq!(
+                format_phone_numbers(text, AiPhoneStyle::Us),
+                text,
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_keeps_layout() {
+        assert_eq!(
+            format_phone_numbers("  call 5551234567\n\nnext  line", AiPhoneStyle::Us),
+            "  call (555) 123-4567\n\nnext  line"
+        );
+    }
+}
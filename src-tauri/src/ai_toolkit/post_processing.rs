@@ -0,0 +1,123 @@
+use super::capitalization::enforce_capitalization;
+use super::contractions::apply_contraction_style;
+use super::echo_detection::remove_echo;
+use super::graphemes::to_nfc;
+use super::number_format::fix_number_separators;
+use super::number_words::spell_small_numbers;
+use super::output_cleanup::clean_output;
+use super::proper_nouns::correct_names;
+use super::spelling_variants::{enforce_spelling_variant, restore_spelling_variants};
+use super::vocabulary::apply_vocabulary;
+use crate::settings::{
+    AiCapitalizationStyle, AiContractionStyle, AiNumberLocale, AiProperNoun, AiSpellingVariant,
+    AiVocabularyEntry,
+};
+use anyhow::Result;
+
+/// The rule passes model output goes through before it is checked and
+/// pasted, with what each of them applies. A pass left at `None` is skipped.
+#[derive(Debug, Clone, Copy)]
+pub struct OutputPasses<'a> {
+    pub vocabulary: &'a [AiVocabularyEntry],
+    pub proper_nouns: &'a [AiProperNoun],
+    /// The locale whose separators numbers are written with, when they are
+    /// normalized
+    pub number_locale: Option<AiNumberLocale>,
+    /// Numbers below this are spelled out, when they are normalized
+    pub number_style_threshold: u32,
+    pub capitalization: Option<AiCapitalizationStyle>,
+    /// Whether the output is in English, which "I" is capitalized in
+    pub english: bool,
+    pub spelling_variant: Option<AiSpellingVariant>,
+    pub contraction_style: Option<AiContractionStyle>,
+}
+
+impl OutputPasses<'_> {
+    /// `output` the model generated for `input` with `prompt`, in NFC with
+    /// every pass applied. Fails when it only repeats the prompt.
+    pub fn apply(&self, input: &str, prompt: &str, output: &str) -> Result<String> {
+        let output = remove_echo(input, prompt, &to_nfc(output))?;
+        let mut text = apply_vocabulary(&clean_output(input, &output), self.vocabulary);
+        text = correct_names(&text, self.proper_nouns);
+        if let Some(locale) = self.number_locale {
+            text = fix_number_separators(&text, locale);
+            text = spell_small_numbers(&text, self.number_style_threshold);
+        }
+        if let Some(style) = self.capitalization {
+            text = enforce_capitalization(&text, style, self.english);
+        }
+        text = match self.spelling_variant {
+            Some(AiSpellingVariant::Preserve) => restore_spelling_variants(input, &text),
+            Some(variant) => enforce_spelling_variant(&text, variant),
+            None => text,
+        };
+        if let Some(style) = self.contraction_style {
+            text = apply_contraction_style(&text, style);
+        }
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::unicode_samples::{
+        has_split_clusters, non_ascii_graphemes, sample_texts,
+    };
+    use unicode_normalization::is_nfc;
+
+    const PROMPT: &str = "Fix the text.\n\nText: {text}\n\nCorrected:";
+
+    fn every_pass() -> OutputPasses<'static> {
+        OutputPasses {
+            vocabulary: &[],
+            proper_nouns: &[],
+            number_locale: Some(AiNumberLocale::EnUs),
+            number_style_threshold: 10,
+            capitalization: Some(AiCapitalizationStyle::Sentence),
+            english: true,
+            spelling_variant: Some(AiSpellingVariant::Preserve),
+            contraction_style: Some(AiContractionStyle::Expand),
+        }
+    }
+
+    /// Accents written either way, joined emoji and CJK text with its own
+    /// punctuation come out of every pass as they went in, composed
+    #[test]
+    fn test_passes_keep_every_cluster_whole() {
+        let passes = every_pass();
+        for text in sample_texts(500, 24) {
+            let prompt = PROMPT.replace("{text}", &text);
+            let Ok(output) = passes.apply(&text, &prompt, &text) else {
+                continue;
+            };
+
+            assert!(is_nfc(&output), "{:?} became {:?}", text, output);
+            assert!(
+                !has_split_clusters(&output),
+                "{:?} became {:?}",
+                text,
+                output
+            );
+            assert_eq!(
+                non_ascii_graphemes(&output),
+                non_ascii_graphemes(&to_nfc(&text)),
+                "{:?} became {:?}",
+                text,
+                output
+            );
+        }
+    }
+
+    #[test]
+    fn test_model_output_is_composed() {
+        let output = every_pass()
+            .apply(
+                "the cafe was nice",
+                PROMPT,
+                "\"The cafe\u{301} was nice \u{1F44D}\u{1F3FD}.\"",
+            )
+            .unwrap();
+        assert_eq!(output, "The café was nice 👍🏽.");
+    }
+}
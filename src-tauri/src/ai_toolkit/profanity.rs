@@ -0,0 +1,253 @@
+use super::tokens::token_ranges;
+use crate::settings::AiProfanityMask;
+use std::ops::Range;
+
+/// Characters written in place of letters, as in "sh!t" or "$h1t"
+const LEET: &[(char, char)] = &[
+    ('@', 'a'),
+    ('4', 'a'),
+    ('3', 'e'),
+    ('1', 'i'),
+    ('!', 'i'),
+    ('0', 'o'),
+    ('$', 's'),
+    ('5', 's'),
+    ('7', 't'),
+];
+
+/// Endings a listed word is still recognized with, its last letter doubled
+/// or not ("fucks", "shitty")
+const SUFFIXES: &[&str] = &["s", "es", "ed", "er", "ers", "ing", "in", "y"];
+
+/// Marks put between letters spelled out one by one, as in "f-u-c-k"
+const LETTER_SEPARATORS: &[char] = &['-', '.', '_'];
+
+/// Fewest letters spelled out one by one that are put together into a word
+const MIN_SPELLED_LETTERS: usize = 3;
+
+/// Mask the words of `words` in `text` as `mask` says. Only whole words are
+/// masked, with their endings, so "Scunthorpe" and "Dickens" are left alone
+/// whatever is listed. Leetspeak ("sh!t") and letters spelled out with spaces
+/// or dashes between them ("f u c k") are masked too.
+pub fn mask_profanity(text: &str, words: &[String], mask: AiProfanityMask) -> String {
+    let words: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+    let cores: Vec<Range<usize>> = token_ranges(text).map(|t| core(text, t)).collect();
+
+    let mut masked: Vec<Range<usize>> = Vec::new();
+    let mut i = 0;
+    while i < cores.len() {
+        // Letters spelled out with spaces between them, longest run first
+        let run = cores[i..]
+            .iter()
+            .take_while(|core| text[(*core).clone()].chars().count() == 1)
+            .count();
+        let spelled = (MIN_SPELLED_LETTERS..=run).rev().find(|&n| {
+            let letters: String = cores[i..i + n].iter().map(|c| &text[c.clone()]).collect();
+            is_profane(&letters, &words)
+        });
+        if let Some(n) = spelled {
+            masked.push(cores[i].start..cores[i + n - 1].end);
+            i += n;
+            continue;
+        }
+
+        masked.extend(profane_parts(text, cores[i].clone(), &words));
+        i += 1;
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for range in masked {
+        result.push_str(&text[last..range.start]);
+        result.push_str(&mask_word(&text[range.clone()], mask));
+        last = range.end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// The parts of the word at `range` to mask: all of it when its letters are
+/// spelled out with dashes or dots, and otherwise each part between them
+/// that is listed
+fn profane_parts(text: &str, range: Range<usize>, words: &[String]) -> Vec<Range<usize>> {
+    let word = &text[range.clone()];
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (index, c) in word.char_indices() {
+        if LETTER_SEPARATORS.contains(&c) {
+            parts.push(start..index);
+            start = index + c.len_utf8();
+        }
+    }
+    parts.push(start..word.len());
+
+    let spelled = parts.len() >= MIN_SPELLED_LETTERS
+        && parts
+            .iter()
+            .all(|part| word[part.clone()].chars().count() == 1);
+    if spelled {
+        let letters: String = parts.iter().map(|part| &word[part.clone()]).collect();
+        return if is_profane(&letters, words) {
+            vec![range]
+        } else {
+            Vec::new()
+        };
+    }
+
+    parts
+        .into_iter()
+        .filter(|part| is_profane(&word[part.clone()], words))
+        .map(|part| range.start + part.start..range.start + part.end)
+        .collect()
+}
+
+/// Whether `word` is one of `words`, all lowercase, or one of them with an
+/// ending, once leetspeak and drawn-out letters ("fuuuck") are undone
+fn is_profane(word: &str, words: &[String]) -> bool {
+    // Without a letter it is a number, not leetspeak
+    if !word.chars().any(char::is_alphabetic) {
+        return false;
+    }
+
+    let letters: Vec<char> = word
+        .chars()
+        .filter(|&c| c != '\'')
+        .map(|c| {
+            let c = LEET
+                .iter()
+                .find(|(leet, _)| *leet == c)
+                .map_or(c, |(_, letter)| *letter);
+            c.to_lowercase().next().unwrap_or(c)
+        })
+        .collect();
+    let mut normalized = String::new();
+    let mut i = 0;
+    while i < letters.len() {
+        let run = letters[i..]
+            .iter()
+            .take_while(|&&c| c == letters[i])
+            .count();
+        // A letter drawn out is said once, while a double may be spelled so
+        let kept = if run > 2 { 1 } else { run };
+        normalized.extend(std::iter::repeat_n(letters[i], kept));
+        i += run;
+    }
+
+    words
+        .iter()
+        .filter(|listed| !listed.is_empty())
+        .any(|listed| {
+            let Some(ending) = normalized.strip_prefix(listed.as_str()) else {
+                return false;
+            };
+            let doubled = listed
+                .chars()
+                .last()
+                .and_then(|last| ending.strip_prefix(last));
+            ending.is_empty()
+                || SUFFIXES
+                    .iter()
+                    .any(|suffix| ending == *suffix || doubled == Some(*suffix))
+        })
+}
+
+/// `word` masked as `mask` says, with one mark for each letter
+fn mask_word(word: &str, mask: AiProfanityMask) -> String {
+    let mut letters = word
+        .chars()
+        .filter(|c| !c.is_whitespace() && !LETTER_SEPARATORS.contains(c) && *c != '\'');
+    match mask {
+        AiProfanityMask::FirstLetter => match letters.next() {
+            Some(first) => std::iter::once(first).chain(letters.map(|_| '*')).collect(),
+            None => String::new(),
+        },
+        AiProfanityMask::Asterisks => letters.map(|_| '*').collect(),
+    }
+}
+
+/// The word within the token at `range`, without the punctuation around it.
+/// Leetspeak is kept, except for a "!" ending the word.
+fn core(text: &str, range: Range<usize>) -> Range<usize> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '@' || c == '$';
+    let token = &text[range.clone()];
+    let start = token.len() - token.trim_start_matches(|c: char| !is_word_char(c)).len();
+    let end = token
+        .trim_end_matches(|c: char| !c.is_alphanumeric())
+        .len()
+        .max(start);
+    range.start + start..range.start + end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed() -> Vec<String> {
+        ["fuck", "shit", "cunt", "dick", "cock", "ass"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect()
+    }
+
+    fn first_letter(text: &str) -> String {
+        mask_profanity(text, &listed(), AiProfanityMask::FirstLetter)
+    }
+
+    #[test]
+    fn test_masks_listed_words_and_their_endings() {
+        let cases = [
+            ("What the fuck is this?", "What the f*** is this?"),
+            (
+                "Fucking hell, that's shitty.",
+                "F****** hell, that's s*****.",
+            ),
+            ("He fucked it up. Shit!", "He f***** it up. S***!"),
+            ("(shit)", "(s***)"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(first_letter(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_masks_leetspeak_and_spelled_out_letters() {
+        let cases = [
+            ("that's sh!t", "that's s***"),
+            ("$h1t happens", "$*** happens"),
+            ("fuuuuck this", "f****** this"),
+            ("f u c k this", "f*** this"),
+            ("I said f-u-c-k.", "I said f***."),
+            ("a holy-shit moment", "a holy-s*** moment"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(first_letter(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_keeps_words_containing_listed_ones() {
+        let kept = [
+            "We drove through Scunthorpe to see the cocktail bar.",
+            "She reads Dickens for the class assessment.",
+            "Shiitake and shitake mushrooms are in the basement.",
+            "Passing the assistant a peacock feather",
+            "Room 5 1 7 is at 10:30",
+            "I am a b c d student",
+        ];
+        for input in kept {
+            assert_eq!(first_letter(input), input, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_asterisks_mask_every_letter() {
+        assert_eq!(
+            mask_profanity("oh shit", &listed(), AiProfanityMask::Asterisks),
+            "oh ****"
+        );
+        assert_eq!(
+            mask_profanity("oh shit", &[], AiProfanityMask::Asterisks),
+            "oh shit"
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use crate::settings::AiProperNoun;
+use anyhow::{anyhow, Result};
+use std::cmp::Reverse;
+use std::ops::Range;
+use strsim::levenshtein;
+
+/// Most edits a near-match may be away from a name
+const MAX_EDIT_DISTANCE: usize = 2;
+
+/// Letters of a name per edit allowed, so that "Sam" has to match exactly and
+/// "same" is never taken for it
+const LETTERS_PER_EDIT: usize = 4;
+
+/// Trim a name and its misrecognitions and drop blank or duplicate ones
+pub fn normalize_proper_noun(entry: AiProperNoun) -> Result<AiProperNoun> {
+    let name = collapse_whitespace(&entry.name);
+    if name.is_empty() {
+        return Err(anyhow!("A name can't be empty"));
+    }
+
+    let mut misrecognitions: Vec<String> = Vec::new();
+    for form in entry.misrecognitions {
+        let form = collapse_whitespace(&form);
+        if !form.is_empty()
+            && fold(&form) != fold(&name)
+            && !misrecognitions.iter().any(|f| fold(f) == fold(&form))
+        {
+            misrecognitions.push(form);
+        }
+    }
+
+    Ok(AiProperNoun {
+        name,
+        misrecognitions,
+    })
+}
+
+/// Whether `a` and `b` are the same name, in any casing or accents and
+/// however its words are spaced
+pub fn same_name(a: &str, b: &str) -> bool {
+    fold(&collapse_whitespace(a)) == fold(&collapse_whitespace(b))
+}
+
+/// Names from `nouns` that `text` seems to mention in any spelling or casing,
+/// to list in the prompt
+pub fn names_in<'a>(text: &str, nouns: &'a [AiProperNoun]) -> Vec<&'a str> {
+    let mut names: Vec<&str> = Vec::new();
+    for (_, name) in find_names(text, nouns, true) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Write every mention of a name the way it is stored: its misrecognitions
+/// and other casings or accents always, and capitalized words a couple of
+/// letters off with the same initial ("Siofre" for "Síofra")
+pub fn correct_names(text: &str, nouns: &[AiProperNoun]) -> String {
+    let mut corrected = String::with_capacity(text.len());
+    let mut end = 0;
+    for (range, name) in find_names(text, nouns, false) {
+        corrected.push_str(&text[end..range.start]);
+        corrected.push_str(name);
+        end = range.end;
+    }
+    corrected.push_str(&text[end..]);
+    corrected
+}
+
+/// Byte ranges of mentions of names in `text`, in order and not overlapping,
+/// with the name each stands for. Longer names are tried first. Near-matches
+/// in lowercase only count when `any_case` is set.
+fn find_names<'a>(
+    text: &str,
+    nouns: &'a [AiProperNoun],
+    any_case: bool,
+) -> Vec<(Range<usize>, &'a str)> {
+    let mut forms: Vec<(Vec<String>, &str, bool)> = nouns
+        .iter()
+        .flat_map(|noun| {
+            let name = noun.name.as_str();
+            std::iter::once((name, true))
+                .chain(
+                    noun.misrecognitions
+                        .iter()
+                        .map(|form| (form.as_str(), false)),
+                )
+                .map(move |(form, fuzzy)| (folded_words(form), name, fuzzy))
+        })
+        .filter(|(words, _, _)| !words.is_empty())
+        .collect();
+    forms.sort_by_key(|(words, _, _)| Reverse(words.len()));
+
+    let words = words(text);
+    let folded: Vec<String> = words.iter().map(|word| fold(&text[word.clone()])).collect();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let mention = forms.iter().find_map(|(form, name, fuzzy)| {
+            let candidate = folded.get(i..i + form.len())?;
+            let first = &text[words[i].clone()];
+            let matches = candidate == form.as_slice()
+                || (*fuzzy
+                    && (any_case || first.starts_with(char::is_uppercase))
+                    && is_near(&candidate.join(" "), &form.join(" ")));
+            matches.then_some((form.len(), *name))
+        });
+
+        match mention {
+            Some((len, name)) => {
+                found.push((words[i].start..words[i + len - 1].end, name));
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    found
+}
+
+/// Whether `candidate` is a misspelling of `name`, both folded: the same
+/// initial and only a few edits away for the length of the name
+fn is_near(candidate: &str, name: &str) -> bool {
+    let allowed = (name.chars().count() / LETTERS_PER_EDIT).min(MAX_EDIT_DISTANCE);
+    candidate.chars().next() == name.chars().next() && levenshtein(candidate, name) <= allowed
+}
+
+/// Words of `text` as byte ranges. Apostrophes and hyphens split words, so
+/// "Nguyen's" mentions "Nguyen".
+fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                words.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn folded_words(text: &str) -> Vec<String> {
+    words(text)
+        .into_iter()
+        .map(|word| fold(&text[word]))
+        .collect()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lowercase `text` and drop the accents of Latin letters, so "Síofra" and
+/// "siofra" compare equal
+fn fold(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à'..='å' | 'ā' | 'ă' | 'ą' | 'ạ' | 'ả' | 'ấ' | 'ầ' | 'ẩ' | 'ẫ' | 'ậ' => {
+                'a'
+            }
+            'ç' | 'ć' | 'č' => 'c',
+            'ď' | 'đ' => 'd',
+            'è'..='ë' | 'ē' | 'ė' | 'ę' | 'ě' | 'ẹ' | 'ẻ' | 'ẽ' | 'ế' | 'ề' | 'ể' | 'ễ' | 'ệ' => {
+                'e'
+            }
+            'ğ' => 'g',
+            'ì'..='ï' | 'ī' | 'į' | 'ı' | 'ỉ' | 'ị' => 'i',
+            'ł' | 'ľ' => 'l',
+            'ñ' | 'ń' | 'ň' => 'n',
+            'ò'..='ö' | 'ø' | 'ō' | 'ő' | 'ọ' | 'ỏ' | 'ố' | 'ồ' | 'ổ' | 'ỗ' | 'ộ' | 'ơ' => {
+                'o'
+            }
+            'ř' => 'r',
+            'ś' | 'š' | 'ş' | 'ș' => 's',
+            'ť' | 'ţ' | 'ț' => 't',
+            'ù'..='ü' | 'ū' | 'ů' | 'ű' | 'ų' | 'ư' | 'ụ' | 'ủ' => 'u',
+            'ý' | 'ÿ' | 'ỳ' | 'ỹ' => 'y',
+            'ź' | 'ż' | 'ž' => 'z',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noun(name: &str, misrecognitions: &[&str]) -> AiProperNoun {
+        AiProperNoun {
+            name: name.to_string(),
+            misrecognitions: misrecognitions.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn colleagues() -> Vec<AiProperNoun> {
+        vec![
+            noun("Síofra", &["sheeffra"]),
+            noun("Nguyen", &["win"]),
+            noun("Mary Ellen Ó Súilleabháin", &[]),
+            noun("Sam", &[]),
+            noun("Bartholomew", &[]),
+        ]
+    }
+
+    #[test]
+    fn test_corrects_names() {
+        let nouns = colleagues();
+        let cases = [
+            ("ask siofra about it", "ask Síofra about it"),
+            ("ask SIOFRA about it", "ask Síofra about it"),
+            ("Sheeffra said hi", "Síofra said hi"),
+            ("Siofre said hi", "Síofra said hi"),
+            ("nguyen's notes", "Nguyen's notes"),
+            ("Nguyan's notes", "Nguyen's notes"),
+            ("Bartholomeu and Bartolomew", "Bartholomew and Bartholomew"),
+            ("thanks Sam!", "thanks Sam!"),
+            ("thanks sam", "thanks Sam"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(correct_names(input, &nouns), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_multi_word_names() {
+        let nouns = colleagues();
+        assert_eq!(
+            correct_names("cc mary ellen o suilleabhain on it", &nouns),
+            "cc Mary Ellen Ó Súilleabháin on it"
+        );
+        assert_eq!(
+            correct_names("cc Mary Ellen O Suilleabhan", &nouns),
+            "cc Mary Ellen Ó Súilleabháin"
+        );
+        // Part of the name alone isn't the name
+        assert_eq!(correct_names("cc mary on it", &nouns), "cc mary on it");
+    }
+
+    #[test]
+    fn test_leaves_unrelated_words_alone() {
+        let nouns = colleagues();
+        let cases = [
+            "Same here, see you soon",
+            "the same thing",
+            "Siofra's cousin Fiofra",
+            "we should wine and dine",
+            "Nguyen",
+            "guyen and nguyenish",
+            "bartholomew's",
+            "",
+        ];
+        for input in cases {
+            let expected = input
+                .replace("Siofra", "Síofra")
+                .replace("bartholomew", "Bartholomew");
+            assert_eq!(correct_names(input, &nouns), expected, "{:?}", input);
+        }
+
+        // Near-matches in lowercase are likely ordinary words
+        assert_eq!(correct_names("siofre", &nouns), "siofre");
+    }
+
+    #[test]
+    fn test_names_in_the_prompt() {
+        let nouns = colleagues();
+        assert_eq!(
+            names_in("siofre and nguyen met sam and siofra", &nouns),
+            vec!["Síofra", "Nguyen", "Sam"]
+        );
+        assert!(names_in("nobody we know", &nouns).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_proper_noun() {
+        let entry = normalize_proper_noun(noun(
+            "  Mary   Ellen ",
+            &["mary ellen", "merry Ellen", " Merry  ellen", ""],
+        ))
+        .unwrap();
+        assert_eq!(entry, noun("Mary Ellen", &["merry Ellen"]));
+
+        assert!(normalize_proper_noun(noun("  ", &[])).is_err());
+    }
+
+    #[test]
+    fn test_same_name() {
+        assert!(same_name("Síofra", " siofra"));
+        assert!(same_name("Mary Ellen", "mary  ellen"));
+        assert!(!same_name("Mary Ellen", "Mary"));
+    }
+}
@@ -0,0 +1,565 @@
+use super::output_guard::{OutputCheck, RejectedOutput};
+use super::tokens::token_ranges;
+use anyhow::Result;
+use std::ops::Range;
+
+/// Brackets around the placeholders standing in for protected spans, chosen
+/// because they never turn up in a transcript
+pub const PLACEHOLDER_OPEN: char = '⟦';
+pub const PLACEHOLDER_CLOSE: char = '⟧';
+
+const URL_SCHEMES: &[&str] = &["http://", "https://", "ftp://", "file://", "ssh://"];
+
+/// Top-level domains a bare host name like "example.com" is recognised by
+const DOMAIN_SUFFIXES: &[&str] = &[
+    "com", "org", "net", "io", "dev", "app", "ai", "co", "edu", "gov", "me", "de", "uk",
+];
+
+/// Extensions a relative path like "src/main.rs" is recognised by
+const FILE_EXTENSIONS: &[&str] = &[
+    "c", "cpp", "css", "csv", "go", "h", "html", "java", "js", "json", "jsx", "kt", "md", "pdf",
+    "png", "py", "rb", "rs", "sh", "sql", "swift", "toml", "ts", "tsx", "txt", "xml", "yaml",
+    "yml",
+];
+
+/// Characters around a token that belong to the sentence rather than to it
+const LEADING_PUNCTUATION: &[char] = &['(', '[', '{', '<', '"', '\'', '“', '‘', '«'];
+const TRAILING_PUNCTUATION: &[char] =
+    &['.', ',', ';', ':', '!', '?', '"', '\'', '”', '’', '»', '>'];
+
+/// A transcript with URLs, email addresses, file paths and code replaced by
+/// placeholders the model is told to leave alone
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtectedText {
+    text: String,
+    spans: Vec<String>,
+}
+
+impl ProtectedText {
+    /// `text` passed through with nothing protected
+    pub fn unprotected(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            spans: vec![],
+        }
+    }
+
+    /// The transcript with placeholders, to send to the model
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Put the protected spans back into the model's `output`. Fails with
+    /// [`RejectedOutput`] unless every placeholder appears exactly once and
+    /// no unknown one was made up.
+    pub fn restore(&self, output: &str) -> Result<String> {
+        if self.spans.is_empty() {
+            return Ok(output.to_string());
+        }
+
+        let mut restored = output.to_string();
+
+        for (index, span) in self.spans.iter().enumerate() {
+            let placeholder = placeholder(index);
+            let count = restored.matches(&placeholder).count();
+            if count != 1 {
+                return Err(RejectedOutput(
+                    OutputCheck::Placeholders,
+                    format!(
+                        "placeholder {} for {:?} appears {} times in the enhanced text",
+                        placeholder, span, count
+                    ),
+                )
+                .into());
+            }
+            restored = restored.replacen(&placeholder, span, 1);
+        }
+
+        if restored.contains(PLACEHOLDER_OPEN) {
+            return Err(RejectedOutput(
+                OutputCheck::Placeholders,
+                format!(
+                    "enhanced text has placeholders that weren't in the transcript: {:?}",
+                    output
+                ),
+            )
+            .into());
+        }
+        Ok(restored)
+    }
+
+    /// Best-effort [`Self::restore`] for text still being streamed, which
+    /// drops a placeholder cut off at the end
+    pub fn reveal(&self, partial: &str) -> String {
+        let mut revealed = partial.to_string();
+        if let Some(open) = revealed.rfind(PLACEHOLDER_OPEN) {
+            if !revealed[open..].contains(PLACEHOLDER_CLOSE) {
+                revealed.truncate(open);
+            }
+        }
+
+        for (index, span) in self.spans.iter().enumerate() {
+            revealed = revealed.replace(&placeholder(index), span);
+        }
+        revealed
+    }
+}
+
+fn placeholder(index: usize) -> String {
+    format!("{}H{}{}", PLACEHOLDER_OPEN, index + 1, PLACEHOLDER_CLOSE)
+}
+
+/// Replace the spans of `text` a model tends to "correct" into something
+/// broken with numbered placeholders like `⟦H1⟧`: URLs, email addresses,
+/// file paths, `backticked` code and tokens that look like code. A
+/// transcript that already contains a placeholder bracket is left as is.
+pub fn protect_spans(text: &str) -> ProtectedText {
+    protect_ranges(text, true, vec![])
+}
+
+/// Replace the byte `ranges` of `text` with placeholders, like the spoken
+/// emoji the model would otherwise reword, along with the spans
+/// [`protect_spans`] finds when `code` is set. The given ranges win over
+/// code they overlap.
+pub fn protect_ranges(text: &str, code: bool, mut ranges: Vec<Range<usize>>) -> ProtectedText {
+    if text.contains(PLACEHOLDER_OPEN) {
+        return ProtectedText::unprotected(text);
+    }
+
+    if code {
+        let overlaps = |ranges: &[Range<usize>], span: &Range<usize>| {
+            ranges
+                .iter()
+                .any(|range| range.start < span.end && span.start < range.end)
+        };
+        for span in fenced_code_spans(text)
+            .into_iter()
+            .chain(backtick_spans(text))
+        {
+            if !overlaps(&ranges, &span) {
+                ranges.push(span);
+            }
+        }
+        for token in token_ranges(text) {
+            if overlaps(&ranges, &token) {
+                continue;
+            }
+
+            let core = trim_punctuation(text, token);
+            if is_protected(&text[core.clone()]) {
+                ranges.push(core);
+            }
+        }
+    }
+    ranges.sort_by_key(|range| range.start);
+
+    let mut protected = ProtectedText::unprotected("");
+    let mut end = 0;
+    for range in ranges {
+        protected.text.push_str(&text[end..range.start]);
+        protected.text.push_str(&placeholder(protected.spans.len()));
+        protected.spans.push(text[range.clone()].to_string());
+        end = range.end;
+    }
+    protected.text.push_str(&text[end..]);
+    protected
+}
+
+/// Byte ranges of code blocks fenced by lines starting with three
+/// backticks, fences included. A block that is never closed runs to the end
+/// of the text.
+pub fn fenced_code_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut open = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let fence = line.trim_start_matches([' ', '\t']);
+        if !fence.starts_with("```") {
+            continue;
+        }
+        match open.take() {
+            None => open = Some(start + line.len() - fence.len()),
+            Some(from) => spans.push(from..start + line.trim_end().len()),
+        }
+    }
+    if let Some(from) = open {
+        spans.push(from..text.trim_end().len().max(from));
+    }
+    spans
+}
+
+/// Byte ranges of `code` spans, backticks included, that don't cross a line
+fn backtick_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut from = 0;
+
+    while let Some(open) = text[from..].find('`').map(|i| from + i) {
+        let Some(close) = text[open + 1..].find('`').map(|i| open + 1 + i) else {
+            break;
+        };
+        let inner = &text[open + 1..close];
+        if inner.contains('\n') {
+            from = open + 1;
+            continue;
+        }
+        if !inner.trim().is_empty() {
+            spans.push(open..close + 1);
+        }
+        from = close + 1;
+    }
+
+    spans
+}
+
+/// `token` without surrounding sentence punctuation. A closing bracket is
+/// only dropped when the token doesn't open it, so "foo()" stays whole.
+fn trim_punctuation(text: &str, token: Range<usize>) -> Range<usize> {
+    let mut core = &text[token.clone()];
+    let start = token.start;
+
+    while let Some(c) = core
+        .chars()
+        .next()
+        .filter(|c| LEADING_PUNCTUATION.contains(c))
+    {
+        core = &core[c.len_utf8()..];
+    }
+    let start = start + (token.len() - core.len());
+
+    while let Some(c) = core.chars().next_back() {
+        let trailing = match c {
+            ')' => core.matches('(').count() < core.matches(')').count(),
+            ']' => core.matches('[').count() < core.matches(']').count(),
+            '}' => core.matches('{').count() < core.matches('}').count(),
+            _ => TRAILING_PUNCTUATION.contains(&c),
+        };
+        if !trailing {
+            break;
+        }
+        core = &core[..core.len() - c.len_utf8()];
+    }
+
+    start..start + core.len()
+}
+
+fn is_protected(token: &str) -> bool {
+    !token.is_empty() && (is_url(token) || is_email(token) || is_path(token) || is_code(token))
+}
+
+fn is_url(token: &str) -> bool {
+    let lower = token.to_lowercase();
+    if let Some(rest) = URL_SCHEMES.iter().find_map(|s| lower.strip_prefix(s)) {
+        return !rest.is_empty();
+    }
+    if let Some(rest) = lower.strip_prefix("www.") {
+        return rest.contains('.');
+    }
+
+    // A bare host name, optionally followed by a path
+    let host = lower.split('/').next().unwrap_or_default();
+    is_domain(host)
+        && DOMAIN_SUFFIXES
+            .iter()
+            .any(|s| host.ends_with(&format!(".{}", s)))
+}
+
+fn is_email(token: &str) -> bool {
+    let Some((local, domain)) = token.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-'))
+        && is_domain(domain)
+}
+
+/// Dot-separated labels ending in an alphabetic top-level domain
+fn is_domain(host: &str) -> bool {
+    let labels: Vec<&str> = host.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(char::is_alphabetic))
+}
+
+fn is_path(token: &str) -> bool {
+    let rooted = ["/", "~/", "./", "../"]
+        .iter()
+        .find_map(|prefix| token.strip_prefix(prefix))
+        .or_else(|| {
+            // A Windows drive like C:\
+            let mut chars = token.chars();
+            let drive = chars.next()?.is_ascii_alphabetic()
+                && chars.next()? == ':'
+                && chars.next()? == '\\';
+            drive.then(|| &token[3..])
+        });
+    if let Some(rest) = rooted {
+        return rest
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || c == '.' || c == '_');
+    }
+
+    // A relative path is only recognised by the extension of its file name
+    let Some((_, name)) = token.rsplit_once(['/', '\\']) else {
+        return false;
+    };
+    name.rsplit_once('.').is_some_and(|(stem, extension)| {
+        !stem.is_empty() && FILE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+    })
+}
+
+fn is_code(token: &str) -> bool {
+    if ["::", "->", "=>", "=="].iter().any(|op| token.contains(op)) {
+        return true;
+    }
+    if let Some(flag) = token.strip_prefix("--") {
+        return flag.chars().next().is_some_and(|c| c.is_ascii_alphabetic());
+    }
+    if !token
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '(' | ')'))
+    {
+        return false;
+    }
+
+    // foo(), config.load()
+    if let Some(name) = token.strip_suffix("()") {
+        return !name.is_empty() && !name.contains(['(', ')']);
+    }
+    // snake_case and SCREAMING_CASE, with an underscore between characters
+    let snake = token
+        .trim_matches('_')
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .count()
+        >= 2;
+    // camelCase, starting lowercase with a capital further on
+    let camel = token.starts_with(|c: char| c.is_ascii_lowercase())
+        && token.chars().skip(1).any(|c| c.is_ascii_uppercase());
+    snake || camel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(text: &str) -> Vec<String> {
+        protect_spans(text).spans
+    }
+
+    #[test]
+    fn test_detects_protected_spans() {
+        let cases: &[(&str, &[&str])] = &[
+            (
+                "see https://example.com/a?b=c, it's good",
+                &["https://example.com/a?b=c"],
+            ),
+            ("go to www.rust-lang.org.", &["www.rust-lang.org"]),
+            (
+                "(docs at github.com/tauri-apps)",
+                &["github.com/tauri-apps"],
+            ),
+            (
+                "mail jake.smith@example.com or ops+alerts@mail.co.uk!",
+                &["jake.smith@example.com", "ops+alerts@mail.co.uk"],
+            ),
+            (
+                "edit ~/.config/handy/settings.json now",
+                &["~/.config/handy/settings.json"],
+            ),
+            (
+                "open /etc/hosts and C:\\Users\\me",
+                &["/etc/hosts", "C:\\Users\\me"],
+            ),
+            ("it's in src/main.rs: line two", &["src/main.rs"]),
+            (
+                "run `cargo test -p handy` first",
+                &["`cargo test -p handy`"],
+            ),
+            (
+                "call get_settings() then foo()",
+                &["get_settings()", "foo()"],
+            ),
+            (
+                "set ai_features and MAX_TOKENS",
+                &["ai_features", "MAX_TOKENS"],
+            ),
+            ("the onChange handler", &["onChange"]),
+            ("use std::fs with --release", &["std::fs", "--release"]),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(spans(text), *expected, "text: {:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_leaves_ordinary_words_alone() {
+        for text in [
+            "so um can you please call me back when you get this",
+            "we'll split it fifty/fifty, e.g. half and/or 1/2 each.",
+            "Mr. Smith said twenty-five, i.e. not Node.js or km/h.",
+            "I'll be there at 3:30 (maybe later)!",
+            "email me at jake dot smith at example dot com",
+        ] {
+            assert!(spans(text).is_empty(), "text: {:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_restores_placeholders_the_model_kept() {
+        let protected = protect_spans("um check https://example.com and src/main.rs");
+        assert_eq!(protected.text(), "um check ⟦H1⟧ and ⟦H2⟧");
+
+        assert_eq!(
+            protected.restore("Check ⟦H1⟧ and ⟦H2⟧.").unwrap(),
+            "Check https://example.com and src/main.rs."
+        );
+        // The model may reorder the placeholders
+        assert_eq!(
+            protected.restore("⟦H2⟧, then ⟦H1⟧").unwrap(),
+            "src/main.rs, then https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_rejects_lost_or_invented_placeholders() {
+        let protected = protect_spans("check https://example.com and src/main.rs");
+
+        for output in [
+            "Check https://example.co and ⟦H2⟧.",
+            "Check ⟦H1⟧ and ⟦H1⟧ and ⟦H2⟧.",
+            "Check [H1] and ⟦H2⟧.",
+            "Check ⟦H1⟧ and ⟦H2⟧ and ⟦H3⟧.",
+        ] {
+            assert!(protected.restore(output).is_err(), "output: {:?}", output);
+        }
+    }
+
+    #[test]
+    fn test_reveals_streamed_text() {
+        let protected = protect_spans("see https://example.com now");
+
+        assert_eq!(protected.reveal("See ⟦H1⟧"), "See https://example.com");
+        assert_eq!(protected.reveal("See ⟦H"), "See ");
+    }
+
+    #[test]
+    fn test_protects_given_ranges() {
+        let text = "thumbs up emoji for `cargo test`";
+        let emoji = 0.."thumbs up emoji".len();
+        let phrases = protect_ranges(text, false, vec![emoji.clone()]);
+        assert_eq!(phrases.text(), "⟦H1⟧ for `cargo test`");
+
+        let both = protect_ranges(text, true, vec![emoji]);
+        assert_eq!(both.text(), "⟦H1⟧ for ⟦H2⟧");
+        assert_eq!(
+            both.restore("⟦H1⟧ for ⟦H2⟧.").unwrap(),
+            "thumbs up emoji for `cargo test`."
+        );
+    }
+
+    #[test]
+    fn test_protects_fenced_code_blocks() {
+        let text = "Run this:\n\n```bash\ncargo test -- --nocapture\n```\n\nthen `push`";
+        let protected = protect_spans(text);
+        assert_eq!(protected.text(), "Run this:\n\n⟦H1⟧\n\nthen ⟦H2⟧");
+        assert_eq!(
+            protected
+                .restore("Run this:\n\n⟦H1⟧\n\nThen ⟦H2⟧.")
+                .unwrap(),
+            "Run this:\n\n```bash\ncargo test -- --nocapture\n```\n\nThen `push`."
+        );
+
+        // One left open runs to the end
+        let open = "notes\n```\nlet x = 1;\n";
+        let spans: Vec<&str> = fenced_code_spans(open)
+            .into_iter()
+            .map(|span| &open[span])
+            .collect();
+        assert_eq!(spans, vec!["```\nlet x = 1;"]);
+    }
+
+    #[test]
+    fn test_text_with_placeholder_brackets_is_not_protected() {
+        let protected = protect_spans("keep ⟦H1⟧ and https://example.com");
+        assert_eq!(protected.text(), "keep ⟦H1⟧ and https://example.com");
+        assert_eq!(
+            protected
+                .restore("Keep ⟦H1⟧ and https://example.com.")
+                .unwrap(),
+            "Keep ⟦H1⟧ and https://example.com."
+        );
+    }
+
+    /// Protecting and restoring is the identity when the model echoes every
+    /// placeholder, for sentences built from a mix of words, spans and
+    /// punctuation
+    #[test]
+    fn test_round_trip_is_identity() {
+        const PIECES: &[&str] = &[
+            "so",
+            "um",
+            "send",
+            "it",
+            "to",
+            "the",
+            "team",
+            "jake.smith@example.com",
+            "https://example.com/path?q=1",
+            "src/main.rs",
+            "`npm run dev`",
+            "get_settings()",
+            "~/notes.md",
+            "onChange",
+            "and/or",
+            "e.g.",
+            "(see",
+            "this)",
+            "\"quoted\"",
+            "twenty-five",
+            "C:\\temp",
+            "www.example.org",
+            "--verbose",
+            "std::fmt",
+            "’",
+            "…",
+            "ünïcödé",
+        ];
+        const SEPARATORS: &[&str] = &[" ", "  ", "\n", ", ", ". ", " (", ") ", "\t"];
+
+        // Small linear congruential generator so the cases are reproducible
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) as usize % bound
+        };
+
+        for _ in 0..500 {
+            let mut text = String::new();
+            for _ in 0..1 + next(12) {
+                text.push_str(PIECES[next(PIECES.len())]);
+                text.push_str(SEPARATORS[next(SEPARATORS.len())]);
+            }
+
+            let protected = protect_spans(&text);
+            assert_eq!(
+                protected.restore(protected.text()).unwrap(),
+                text,
+                "text: {:?}",
+                text
+            );
+            assert_eq!(protected.reveal(protected.text()), text, "text: {:?}", text);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+
+/// Pasted transcripts kept for undoing their enhancement
+pub const RECENT_TRANSCRIPTS: usize = 10;
+
+/// A transcript as dictated and the text that was pasted for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PastedTranscript {
+    pub original: String,
+    pub pasted: String,
+}
+
+/// The last few transcripts in the order they were pasted, which isn't the
+/// order they were dictated in when a slow enhancement is overtaken by a
+/// later transcript that skips it
+#[derive(Debug)]
+pub struct RecentTranscripts {
+    capacity: usize,
+    /// Oldest first
+    entries: VecDeque<PastedTranscript>,
+}
+
+impl RecentTranscripts {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Remember a transcript as the one pasted last, forgetting the oldest
+    /// past the capacity
+    pub fn record(&mut self, original: &str, pasted: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(PastedTranscript {
+            original: original.to_string(),
+            pasted: pasted.to_string(),
+        });
+    }
+
+    /// The transcript pasted last
+    pub fn last(&self) -> Option<&PastedTranscript> {
+        self.entries.back()
+    }
+
+    /// Take back the transcript pasted last, so undoing again reaches the
+    /// one before it
+    pub fn undo(&mut self) -> Option<PastedTranscript> {
+        self.entries.pop_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_walks_back_in_paste_order() {
+        let mut recent = RecentTranscripts::new(RECENT_TRANSCRIPTS);
+        assert_eq!(recent.last(), None);
+
+        // The first transcript's enhancement finishes after the second,
+        // short one was pasted as it was
+        recent.record("ok", "ok");
+        recent.record("meet at noon", "Meet at noon.");
+        assert_eq!(recent.last().unwrap().original, "meet at noon");
+
+        assert_eq!(recent.undo().unwrap().pasted, "Meet at noon.");
+        assert_eq!(recent.last().unwrap().original, "ok");
+        assert_eq!(recent.undo().unwrap().original, "ok");
+        assert_eq!(recent.undo(), None);
+    }
+
+    #[test]
+    fn test_forgets_the_oldest() {
+        let mut recent = RecentTranscripts::new(2);
+        for original in ["one", "two", "three"] {
+            recent.record(original, &original.to_uppercase());
+        }
+
+        assert_eq!(recent.undo().unwrap().original, "three");
+        assert_eq!(recent.undo().unwrap().original, "two");
+        assert_eq!(recent.undo(), None);
+
+        let mut disabled = RecentTranscripts::new(0);
+        disabled.record("one", "ONE");
+        assert_eq!(disabled.last(), None);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::settings::AiRedaction;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::cmp::Reverse;
+use std::ops::Range;
+
+/// Stands for the kind of information in a redaction's placeholder
+const CATEGORY: &str = "{category}";
+
+/// Longest placeholder accepted
+const MAX_PLACEHOLDER_CHARS: usize = 40;
+
+/// Digits a card number has, from the shortest to the longest issued
+const CARD_DIGITS: Range<usize> = 13..20;
+
+/// Digits an international phone number has with its country code
+const INTERNATIONAL_PHONE_DIGITS: Range<usize> = 8..16;
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[A-Za-z0-9][A-Za-z0-9._%+-]*@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}")
+        .unwrap()
+});
+
+/// North American numbers with their area code, grouped 3-3-4
+static NORTH_AMERICAN_PHONE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:\+?1[ .-]?)?(?:\(\d{3}\) ?|\d{3}[ .-])\d{3}[ .-]\d{4}").unwrap());
+
+/// Numbers written with a country code, like "+44 20 7946 0958"
+static INTERNATIONAL_PHONE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\+\d{1,3}(?:[ .-]?\d{1,4}){2,5}").unwrap());
+
+/// Digits grouped the way card numbers are printed, or not grouped at all
+static CARD_NUMBER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b\d{3,6}(?:[ -]\d{3,6}){2,4}\b|\b\d{13,19}\b").unwrap());
+
+/// How many of each kind of information a text had redacted, sent with
+/// `ai-redactions-applied` so the placeholders come as no surprise
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct AiRedactionCounts {
+    pub emails: u32,
+    pub phone_numbers: u32,
+    pub card_numbers: u32,
+}
+
+impl AiRedactionCounts {
+    pub fn total(&self) -> u32 {
+        self.emails + self.phone_numbers + self.card_numbers
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Email,
+    Phone,
+    Card,
+}
+
+impl Category {
+    /// What "{category}" becomes in the placeholder
+    fn name(self) -> &'static str {
+        match self {
+            Category::Email => "email",
+            Category::Phone => "phone",
+            Category::Card => "card",
+        }
+    }
+}
+
+/// Check the placeholder written in place of each redaction
+pub fn validate_redaction_placeholder(placeholder: &str) -> Result<()> {
+    if placeholder.trim().is_empty() {
+        return Err(anyhow!("Redactions need a placeholder to write"));
+    }
+    if placeholder.chars().count() > MAX_PLACEHOLDER_CHARS {
+        return Err(anyhow!(
+            "The redaction placeholder can be at most {} characters",
+            MAX_PLACEHOLDER_CHARS
+        ));
+    }
+    Ok(())
+}
+
+/// Replace the email addresses, phone numbers and card numbers of `text`
+/// that `redaction` covers with its placeholder, and count them. Phone
+/// numbers are only found written with their area or country code, and card
+/// numbers only when they pass the Luhn check. Where matches overlap, the
+/// one starting first wins, and the longer of two starting together, so a
+/// number in an email address is redacted as part of the address.
+pub fn redact_sensitive(text: &str, redaction: &AiRedaction) -> (String, AiRedactionCounts) {
+    let mut found: Vec<(Range<usize>, Category)> = vec![];
+    if redaction.emails {
+        found.extend(matches(&EMAIL, text).map(|range| (range, Category::Email)));
+    }
+    if redaction.phone_numbers {
+        let international = matches(&INTERNATIONAL_PHONE, text)
+            .filter(|range| INTERNATIONAL_PHONE_DIGITS.contains(&digits(&text[range.clone()])));
+        found.extend(
+            matches(&NORTH_AMERICAN_PHONE, text)
+                .chain(international)
+                .map(|range| (range, Category::Phone)),
+        );
+    }
+    if redaction.card_numbers {
+        let cards =
+            matches(&CARD_NUMBER, text).filter(|range| is_card_number(&text[range.clone()]));
+        found.extend(cards.map(|range| (range, Category::Card)));
+    }
+    found.sort_by_key(|(range, _)| (range.start, Reverse(range.end)));
+
+    let mut result = String::with_capacity(text.len());
+    let mut counts = AiRedactionCounts::default();
+    let mut end = 0;
+    for (range, category) in found {
+        if range.start < end {
+            continue;
+        }
+        result.push_str(&text[end..range.start]);
+        result.push_str(&redaction.placeholder.replace(CATEGORY, category.name()));
+        end = range.end;
+        match category {
+            Category::Email => counts.emails += 1,
+            Category::Phone => counts.phone_numbers += 1,
+            Category::Card => counts.card_numbers += 1,
+        }
+    }
+    result.push_str(&text[end..]);
+    (result, counts)
+}
+
+/// Byte ranges of the matches of `pattern` that stand on their own, rather
+/// than being part of a longer number or word
+fn matches<'a>(pattern: &'a Regex, text: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+    pattern
+        .find_iter(text)
+        .map(|found| found.range())
+        .filter(|range| stands_alone(text, range))
+}
+
+fn stands_alone(text: &str, range: &Range<usize>) -> bool {
+    let joins = |c: char| c.is_alphanumeric() || matches!(c, '@' | '_' | '+');
+    let before = text[..range.start].chars().next_back();
+    let mut after = text[range.end..].chars();
+    let continues = match after.next() {
+        Some(c) if joins(c) => true,
+        // "555-123-4567-89" goes on, while a full stop ends the sentence
+        Some('-' | '.' | '/') => after.next().is_some_and(|c| c.is_ascii_digit()),
+        _ => false,
+    };
+    !before.is_some_and(|c| joins(c) || matches!(c, '-' | '.' | '/')) && !continues
+}
+
+fn digits(text: &str) -> usize {
+    text.chars().filter(char::is_ascii_digit).count()
+}
+
+/// Whether `number` has a card number's length and passes the Luhn check,
+/// which a mistyped or made-up number fails nine times out of ten
+fn is_card_number(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !CARD_DIGITS.contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| match index % 2 {
+            0 => digit,
+            _ if digit * 2 > 9 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::protected_spans::protect_spans;
+
+    fn redact(text: &str) -> (String, AiRedactionCounts) {
+        redact_sensitive(text, &AiRedaction::default())
+    }
+
+    #[test]
+    fn test_redacts_emails() {
+        let (text, counts) = redact("Mail jane.doe+work@example.co.uk or (bob@mail.example.com).");
+        assert_eq!(text, "Mail [email] or ([email]).");
+        assert_eq!(counts.emails, 2);
+        assert_eq!(counts.total(), 2);
+    }
+
+    #[test]
+    fn test_redacts_phone_numbers() {
+        for number in [
+            "(555) 123-4567",
+            "555-123-4567",
+            "555.123.4567",
+            "1-555-123-4567",
+            "+1 555 123 4567",
+            "+44 20 7946 0958",
+            "+15551234567",
+        ] {
+            let (text, counts) = redact(&format!("Call {} today.", number));
+            assert_eq!(text, "Call [phone] today.", "{}", number);
+            assert_eq!(counts.phone_numbers, 1, "{}", number);
+        }
+    }
+
+    #[test]
+    fn test_redacts_card_numbers() {
+        for number in [
+            "4111 1111 1111 1111",
+            "5555-5555-5555-4444",
+            "4111111111111111",
+            "3782 822463 10005",
+        ] {
+            let (text, counts) = redact(&format!("My card is {}, thanks", number));
+            assert_eq!(text, "My card is [card], thanks", "{}", number);
+            assert_eq!(counts.card_numbers, 1, "{}", number);
+        }
+    }
+
+    #[test]
+    fn test_near_misses_are_kept() {
+        for text in [
+            "Ping @jane about it",
+            "Log in as root@localhost",
+            "Write to jane@example",
+            "It's due 2024-01-15 at 10:30",
+            "The SSN field looks like 123-45-6789",
+            "Dial extension 555-1234",
+            "Card 4111 1111 1111 1112 was declined",
+            "Order 1234 5678 9012 is on its way",
+            "Version 1.2.3 runs on 192.168.1.1",
+            "We raised $1,250,000 and +5 points",
+            "Tracking 555-123-4567-89 arrived",
+        ] {
+            let (redacted, counts) = redact(text);
+            assert_eq!(redacted, text);
+            assert_eq!(counts, AiRedactionCounts::default(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_overlapping_matches_are_redacted_once() {
+        // The number is part of the address
+        let (text, counts) = redact("Text 555-123-4567@sms.example.com now");
+        assert_eq!(text, "Text [email] now");
+        assert_eq!(
+            counts,
+            AiRedactionCounts {
+                emails: 1,
+                ..AiRedactionCounts::default()
+            }
+        );
+
+        // Both phone patterns find it, but it is one number
+        let (text, counts) = redact("Call +1 555-123-4567");
+        assert_eq!(text, "Call [phone]");
+        assert_eq!(counts.total(), 1);
+
+        let (text, counts) = redact("jane@example.com, 555-123-4567 and 4111 1111 1111 1111");
+        assert_eq!(text, "[email], [phone] and [card]");
+        assert_eq!(
+            counts,
+            AiRedactionCounts {
+                emails: 1,
+                phone_numbers: 1,
+                card_numbers: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_categories_and_placeholder_follow_the_settings() {
+        let text = "jane@example.com, 555-123-4567 and 4111 1111 1111 1111";
+        let redaction = AiRedaction {
+            phone_numbers: false,
+            placeholder: "<redacted {category}>".to_string(),
+            ..AiRedaction::default()
+        };
+        let (redacted, counts) = redact_sensitive(text, &redaction);
+        assert_eq!(
+            redacted,
+            "<redacted email>, 555-123-4567 and <redacted card>"
+        );
+        assert_eq!(counts.phone_numbers, 0);
+
+        let redaction = AiRedaction {
+            placeholder: "[REDACTED]".to_string(),
+            ..AiRedaction::default()
+        };
+        assert_eq!(
+            redact_sensitive(text, &redaction).0,
+            "[REDACTED], [REDACTED] and [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_redacts_after_protected_spans_are_restored() {
+        let text = "send it to jane@example.com and call 555-123-4567";
+        let protected = protect_spans(text);
+        // The model only ever sees the address as a placeholder
+        assert!(!protected.text().contains("jane@example.com"));
+        assert_eq!(redact(protected.text()).1.emails, 0);
+
+        let restored = protected.restore(protected.text()).unwrap();
+        assert_eq!(redact(&restored).0, "send it to [email] and call [phone]");
+    }
+
+    #[test]
+    fn test_validate_redaction_placeholder() {
+        assert!(validate_redaction_placeholder("[{category}]").is_ok());
+        assert!(validate_redaction_placeholder("[REDACTED]").is_ok());
+        assert!(validate_redaction_placeholder("  ").is_err());
+        assert!(validate_redaction_placeholder(&"x".repeat(MAX_PLACEHOLDER_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_luhn_check() {
+        assert!(is_card_number("4111111111111111"));
+        assert!(is_card_number("6011 1111 1111 1117"));
+        assert!(!is_card_number("4111111111111112"));
+        // Passes the check, but is too short for a card
+        assert!(!is_card_number("4242424242"));
+    }
+}
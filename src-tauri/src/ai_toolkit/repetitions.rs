@@ -0,0 +1,298 @@
+use super::graphemes::{trim_end_graphemes, trim_start_graphemes};
+
+/// Longest phrase, in words, that is checked for being said twice in a row
+const MAX_PHRASE_WORDS: usize = 4;
+
+/// A word or phrase said more times than this in a row is taken to be meant,
+/// as in "go go go go", and left alone
+const MAX_COLLAPSED_REPEATS: usize = 3;
+
+/// Words repeated on purpose, for emphasis or because the grammar needs it
+/// ("he had had enough", "what it is is")
+const INTENTIONAL_REPEATS: &[&str] = &[
+    "bye", "ha", "haha", "had", "hey", "is", "la", "no", "now", "ok", "okay", "really", "so",
+    "that", "very", "yeah", "yes",
+];
+
+/// Spoken numbers, which repeat in amounts like "ninety nine ninety nine"
+/// and are kept whole for the model to convert
+const NUMBER_WORDS: &[&str] = &[
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+    "twenty",
+    "thirty",
+    "forty",
+    "fifty",
+    "sixty",
+    "seventy",
+    "eighty",
+    "ninety",
+    "hundred",
+    "thousand",
+    "million",
+    "billion",
+    "point",
+];
+
+/// Collapse words and phrases of up to four words said twice or three times
+/// in a row into one, keeping the first and the punctuation after the last:
+/// "I, I think the the plan" becomes "I think the plan". Repetitions that are
+/// usually meant, numbers and phrases ending a sentence are kept.
+pub fn collapse_repetitions(text: &str) -> String {
+    let mut words = words(text);
+    let mut i = 0;
+    while i < words.len() {
+        match (1..=MAX_PHRASE_WORDS)
+            .rev()
+            .find_map(|n| repeats_at(&words, i, n).map(|repeats| (n, repeats)))
+        {
+            Some((n, repeats)) => {
+                // The last copy's punctuation, as in "we should, we should go"
+                let last = i + n * repeats - 1;
+                let ending = trailing_punctuation(&words[last].text).to_string();
+                let kept_end = i + n - 1;
+                let kept = trim_end_graphemes(&words[kept_end].text, is_punctuation_grapheme);
+                words[kept_end].text = format!("{}{}", kept, ending);
+                words.drain(i + n..=last);
+            }
+            None => i += 1,
+        }
+    }
+
+    words
+        .iter()
+        .map(|word| format!("{}{}", word.text, word.space))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// How many copies of the `n` words starting at `i` follow them, when they
+/// are a collapsible repetition
+fn repeats_at(words: &[Word], i: usize, n: usize) -> Option<usize> {
+    let phrase = words.get(i..i + n)?;
+    if phrase.iter().all(|word| is_intentional(&word.key))
+        || ends_sentence(&phrase[n - 1].text)
+        || is_periodic(phrase)
+        || follows_copy(words, i, phrase)
+    {
+        return None;
+    }
+
+    let mut copies = 1;
+    while let Some(next) = words.get(i + n * copies..i + n * (copies + 1)) {
+        if !same_words(next, phrase) {
+            break;
+        }
+        copies += 1;
+        if ends_sentence(&next[n - 1].text) {
+            break;
+        }
+    }
+
+    (2..=MAX_COLLAPSED_REPEATS)
+        .contains(&copies)
+        .then_some(copies)
+}
+
+/// Whether `phrase` is itself a shorter phrase repeated, like "go go", which
+/// is left to be judged at that length
+fn is_periodic(phrase: &[Word]) -> bool {
+    (1..phrase.len()).any(|period| {
+        phrase.len().is_multiple_of(period)
+            && phrase
+                .iter()
+                .enumerate()
+                .all(|(k, word)| word.key == phrase[k % period].key)
+    })
+}
+
+/// Whether the words before `i` say `phrase` too, so that the tail of a run
+/// too long to collapse isn't collapsed on its own
+fn follows_copy(words: &[Word], i: usize, phrase: &[Word]) -> bool {
+    i.checked_sub(phrase.len())
+        .is_some_and(|start| same_words(&words[start..i], phrase))
+}
+
+fn same_words(a: &[Word], b: &[Word]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a.key == b.key)
+}
+
+fn is_intentional(key: &str) -> bool {
+    key.is_empty()
+        || INTENTIONAL_REPEATS.contains(&key)
+        || NUMBER_WORDS.contains(&key)
+        || key.chars().any(|c| c.is_ascii_digit())
+}
+
+pub fn ends_sentence(word: &str) -> bool {
+    trailing_punctuation(word).contains(['.', '!', '?'])
+}
+
+/// The punctuation `word` ends with, in whole grapheme clusters, so a
+/// combining mark stays on its letter and an emoji isn't split
+pub fn trailing_punctuation(word: &str) -> &str {
+    &word[trim_end_graphemes(word, is_punctuation_grapheme).len()..]
+}
+
+pub fn is_punctuation(c: char) -> bool {
+    !c.is_alphanumeric() && c != '\''
+}
+
+/// Whether the grapheme cluster `grapheme` is punctuation, going by the
+/// character it is built on
+pub fn is_punctuation_grapheme(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(is_punctuation)
+}
+
+/// `word` without the punctuation around it, in whole grapheme clusters
+pub fn trim_punctuation(word: &str) -> &str {
+    trim_start_graphemes(
+        trim_end_graphemes(word, is_punctuation_grapheme),
+        is_punctuation_grapheme,
+    )
+}
+
+/// A word as written, the whitespace after it and the lowercase form it is
+/// compared by
+pub struct Word {
+    pub text: String,
+    pub space: String,
+    pub key: String,
+}
+
+pub fn words(text: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(end);
+        let space_end = after
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(after.len());
+        words.push(Word {
+            text: word.to_string(),
+            space: after[..space_end].to_string(),
+            key: trim_punctuation(word).to_lowercase(),
+        });
+        rest = &after[space_end..];
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::unicode_samples::{
+        has_split_clusters, non_ascii_graphemes, sample_texts,
+    };
+
+    #[test]
+    fn test_collapses_stutters() {
+        let cases = [
+            ("the the report is ready", "the report is ready"),
+            ("I I think we should go", "I think we should go"),
+            ("I, I think so", "I think so"),
+            ("The the report", "The report"),
+            ("we should we should go now", "we should go now"),
+            ("we should, we should go", "we should go"),
+            ("can you can you send it", "can you send it"),
+            (
+                "send it to the team to the team tomorrow",
+                "send it to the team tomorrow",
+            ),
+            ("it's it's fine", "it's fine"),
+            ("the the the plan", "the plan"),
+            (
+                "I think that I think that we're done",
+                "I think that we're done",
+            ),
+            ("ends with with.", "ends with."),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(collapse_repetitions(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_keeps_intentional_repetition() {
+        let cases = [
+            "that was very very good",
+            "no no no, not that one",
+            "he had had enough",
+            "I know that that is true",
+            "what it is is a mess",
+            "bye bye",
+            "yeah yeah I know",
+            "go go go go",
+            "room 4 4 2",
+            "It works. It works.",
+            "we talked about the plan and the budget",
+            "",
+        ];
+        for input in cases {
+            assert_eq!(collapse_repetitions(input), input, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_clusters_stay_whole() {
+        assert_eq!(
+            collapse_repetitions("the café, the café is open 👍🏽 👍🏽"),
+            "the café is open 👍🏽 👍🏽"
+        );
+        assert_eq!(
+            collapse_repetitions("see the the 東京タワー。"),
+            "see the 東京タワー。"
+        );
+        // An accent isn't punctuation, even written apart from its letter
+        assert_eq!(trailing_punctuation("cafe\u{301}"), "");
+        assert_eq!(trailing_punctuation("cafe\u{301}!👍🏽"), "!👍🏽");
+        assert_eq!(words("cafe\u{301},")[0].key, "cafe\u{301}");
+
+        for text in sample_texts(300, 24) {
+            let collapsed = collapse_repetitions(&text);
+            assert!(!has_split_clusters(&collapsed), "{:?}", text);
+            let kept = non_ascii_graphemes(&text);
+            for grapheme in non_ascii_graphemes(&collapsed) {
+                assert!(kept.contains(&grapheme), "{:?} in {:?}", grapheme, text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_keeps_spoken_amounts() {
+        // The model sees every amount whole, to convert it in one piece
+        let fixture = include_str!("fixtures/spoken_currency.txt");
+        for line in fixture.lines().filter(|line| !line.starts_with('#')) {
+            let spoken = line.split('|').next().unwrap().trim();
+            assert_eq!(collapse_repetitions(spoken), spoken);
+        }
+    }
+
+    #[test]
+    fn test_keeps_layout() {
+        assert_eq!(
+            collapse_repetitions("First line the the end.\n\nSecond  paragraph"),
+            "First line the end.\n\nSecond  paragraph"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use crate::settings::{AiReplacementRule, AiRuleStage};
+use anyhow::{anyhow, Result};
+use log::warn;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Most rules that can be set
+const MAX_RULES: usize = 100;
+
+/// Longest pattern accepted
+const MAX_PATTERN_CHARS: usize = 500;
+
+/// Most memory a compiled pattern may take. Matching runs in time linear in
+/// the text, so no pattern can backtrack catastrophically, and this bounds
+/// the work done for each character of it.
+const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+
+/// Text a pattern is tried on to tell whether it matches nothing at all,
+/// with word boundaries and line ends for the anchors to find
+const EMPTY_MATCH_PROBE: &str = "Ab 1.\ncd";
+
+/// What the rules alone make of a text, without the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiReplacementRulesPreview {
+    /// The text as the model would get it
+    pub before_ai: String,
+    /// The text as pasted, had the model left it as it was
+    pub after_ai: String,
+}
+
+/// Check `rules` can all be applied: their patterns compile within the
+/// size limit and can't match empty text, and their replacements only
+/// refer to groups the pattern has
+pub fn validate_replacement_rules(rules: &[AiReplacementRule]) -> Result<()> {
+    if rules.len() > MAX_RULES {
+        return Err(anyhow!("At most {} rules can be added", MAX_RULES));
+    }
+    for (index, rule) in rules.iter().enumerate() {
+        validate_rule(rule).map_err(|e| anyhow!("Rule {}: {}", index + 1, e))?;
+    }
+    Ok(())
+}
+
+fn validate_rule(rule: &AiReplacementRule) -> Result<()> {
+    if rule.pattern.is_empty() {
+        return Err(anyhow!("it needs a pattern"));
+    }
+    if rule.pattern.chars().count() > MAX_PATTERN_CHARS {
+        return Err(anyhow!(
+            "the pattern can be at most {} characters",
+            MAX_PATTERN_CHARS
+        ));
+    }
+    let regex = compile(&rule.pattern)?;
+    if regex.is_match("") || regex.find_iter(EMPTY_MATCH_PROBE).any(|m| m.is_empty()) {
+        return Err(anyhow!(
+            "the pattern can match empty text, which would put the replacement between every character"
+        ));
+    }
+    if let Some(group) = group_references(&rule.replacement)
+        .into_iter()
+        .find(|group| !has_group(&regex, group))
+    {
+        return Err(anyhow!(
+            "the replacement refers to ${{{}}}, which the pattern has no group for. Write ${{1}}a rather than $1a for a group followed by letters",
+            group
+        ));
+    }
+    Ok(())
+}
+
+/// Apply the rules of `rules` for `stage` to `text`, in the order given, so
+/// each rule sees what the ones before it made of the text. A rule that no
+/// longer compiles is skipped.
+pub fn apply_replacement_rules(
+    text: &str,
+    rules: &[AiReplacementRule],
+    stage: AiRuleStage,
+) -> String {
+    let mut text = text.to_string();
+    for rule in rules.iter().filter(|rule| rule.stage == stage) {
+        match compile(&rule.pattern) {
+            Ok(regex) => {
+                text = regex
+                    .replace_all(&text, rule.replacement.as_str())
+                    .into_owned()
+            }
+            Err(e) => warn!("Skipping replacement rule {:?}: {}", rule.pattern, e),
+        }
+    }
+    text
+}
+
+/// What `rules` make of `text` before and after the model
+pub fn preview_replacement_rules(
+    text: &str,
+    rules: &[AiReplacementRule],
+) -> AiReplacementRulesPreview {
+    let before_ai = apply_replacement_rules(text, rules, AiRuleStage::BeforeAi);
+    let after_ai = apply_replacement_rules(&before_ai, rules, AiRuleStage::AfterAi);
+    AiReplacementRulesPreview {
+        before_ai,
+        after_ai,
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .dfa_size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+        .map_err(|e| anyhow!("invalid pattern: {}", e))
+}
+
+/// The groups `replacement` refers to, by number or name: `$1`, `$name`,
+/// `${1}` or `${name}`, with `$$` standing for a dollar sign
+fn group_references(replacement: &str) -> Vec<&str> {
+    let mut groups = vec![];
+    let mut rest = replacement;
+    while let Some(at) = rest.find('$') {
+        rest = &rest[at + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            rest = after;
+        } else if let Some(braced) = rest.strip_prefix('{') {
+            if let Some(end) = braced.find('}') {
+                groups.push(&braced[..end]);
+                rest = &braced[end + 1..];
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            if end > 0 {
+                groups.push(&rest[..end]);
+            }
+            rest = &rest[end..];
+        }
+    }
+    groups
+}
+
+fn has_group(regex: &Regex, group: &str) -> bool {
+    match group.parse::<usize>() {
+        Ok(index) => index < regex.captures_len(),
+        Err(_) => regex.capture_names().flatten().any(|name| name == group),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, replacement: &str, stage: AiRuleStage) -> AiReplacementRule {
+        AiReplacementRule {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            stage,
+        }
+    }
+
+    #[test]
+    fn test_applies_rules_of_the_stage() {
+        let rules = [
+            rule(r"(?i)\bHQ one\b", "HQ1", AiRuleStage::BeforeAi),
+            rule(
+                r"(?i)\byou know what I mean,?\s*",
+                "",
+                AiRuleStage::BeforeAi,
+            ),
+            rule(r"\bTODO\b", "To do", AiRuleStage::AfterAi),
+        ];
+        let text = "Meet at hq one, you know what I mean, TODO";
+        assert_eq!(
+            apply_replacement_rules(text, &rules, AiRuleStage::BeforeAi),
+            "Meet at HQ1, TODO"
+        );
+        assert_eq!(
+            apply_replacement_rules(text, &rules, AiRuleStage::AfterAi),
+            "Meet at hq one, you know what I mean, To do"
+        );
+    }
+
+    #[test]
+    fn test_replacements_use_capture_groups() {
+        let rules = [
+            rule(r"(\d+) by (\d+)", "${1}x$2", AiRuleStage::BeforeAi),
+            rule(r"ticket (?P<id>\d+)", "#$id", AiRuleStage::BeforeAi),
+            rule(r"price", "$$5", AiRuleStage::BeforeAi),
+        ];
+        assert_eq!(
+            apply_replacement_rules(
+                "a 2 by 4 for ticket 42 at price",
+                &rules,
+                AiRuleStage::BeforeAi
+            ),
+            "a 2x4 for #42 at $5"
+        );
+        assert!(validate_replacement_rules(&rules).is_ok());
+    }
+
+    #[test]
+    fn test_rules_apply_in_order() {
+        let spell = rule(r"\bHQ one\b", "HQ1", AiRuleStage::BeforeAi);
+        let expand = rule(r"\bHQ1\b", "Headquarters 1", AiRuleStage::BeforeAi);
+        let text = "Go to HQ one";
+        // Each rule sees what the ones before it made
+        assert_eq!(
+            apply_replacement_rules(
+                text,
+                &[spell.clone(), expand.clone()],
+                AiRuleStage::BeforeAi
+            ),
+            "Go to Headquarters 1"
+        );
+        assert_eq!(
+            apply_replacement_rules(text, &[expand, spell], AiRuleStage::BeforeAi),
+            "Go to HQ1"
+        );
+    }
+
+    #[test]
+    fn test_preview_runs_both_stages() {
+        let rules = [
+            rule(r"\bHQ1\b", "Headquarters 1", AiRuleStage::AfterAi),
+            rule(r"\bHQ one\b", "HQ1", AiRuleStage::BeforeAi),
+        ];
+        assert_eq!(
+            preview_replacement_rules("Go to HQ one", &rules),
+            AiReplacementRulesPreview {
+                before_ai: "Go to HQ1".to_string(),
+                after_ai: "Go to Headquarters 1".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        for (pattern, replacement) in [
+            ("", "x"),
+            ("(unclosed", "x"),
+            ("[z-a]", "x"),
+            ("x*", "y"),
+            (r"\b", "|"),
+            ("(?m)^", "> "),
+            (r"(\w+) (\w+)", "$3"),
+            (r"(\w+)", "$1a"),
+            (r"(?P<word>\w+)", "${other}"),
+            (r"(?:\w{100}){100}", "x"),
+        ] {
+            let rules = [rule(pattern, replacement, AiRuleStage::BeforeAi)];
+            assert!(
+                validate_replacement_rules(&rules).is_err(),
+                "{:?} → {:?}",
+                pattern,
+                replacement
+            );
+        }
+        let long = rule(
+            &"a".repeat(MAX_PATTERN_CHARS + 1),
+            "",
+            AiRuleStage::BeforeAi,
+        );
+        assert!(validate_replacement_rules(&[long]).is_err());
+
+        let many = vec![rule("a", "b", AiRuleStage::BeforeAi); MAX_RULES + 1];
+        assert!(validate_replacement_rules(&many).is_err());
+    }
+
+    #[test]
+    fn test_invalid_rule_is_named() {
+        let rules = [
+            rule("fine", "ok", AiRuleStage::BeforeAi),
+            rule("(broken", "x", AiRuleStage::AfterAi),
+        ];
+        let error = validate_replacement_rules(&rules).unwrap_err().to_string();
+        assert!(error.starts_with("Rule 2:"), "{}", error);
+
+        // A rule saved before it broke is skipped rather than applied
+        assert_eq!(
+            apply_replacement_rules("fine then", &rules, AiRuleStage::BeforeAi),
+            "ok then"
+        );
+        assert_eq!(
+            apply_replacement_rules("(broken", &rules, AiRuleStage::AfterAi),
+            "(broken"
+        );
+    }
+}
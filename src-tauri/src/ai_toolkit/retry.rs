@@ -0,0 +1,139 @@
+use super::ollama_client::OllamaOptions;
+use super::output_guard::{OutputCheck, RejectedOutput};
+use anyhow::Result;
+use log::info;
+use std::future::Future;
+
+/// Added to the temperature for the second attempt, so it doesn't come up
+/// with the same output again
+pub const RETRY_TEMPERATURE_STEP: f32 = 0.2;
+
+/// Whether the model's output is worth a second attempt: it came back empty,
+/// or an output check rejected it. Failing to reach the model is not.
+pub fn is_unusable(output: &Result<String>) -> bool {
+    match output {
+        Ok(text) => text.trim().is_empty(),
+        Err(e) => e.is::<RejectedOutput>(),
+    }
+}
+
+/// Generate with `attempt`, and once more with a nudged configuration when
+/// the output is unusable. `attempt` gets the attempt number, starting at 1,
+/// and the options to use. Returns the result used, which is the second
+/// whenever there was one, and whether there was.
+///
+/// Output that is still empty after the retry is rejected, so the original
+/// text is kept rather than pasting nothing.
+pub async fn with_retry<F, Fut>(options: &OllamaOptions, mut attempt: F) -> (Result<String>, bool)
+where
+    F: FnMut(u32, OllamaOptions) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let first = attempt(1, options.clone()).await;
+    if !is_unusable(&first) {
+        return (first, false);
+    }
+
+    match &first {
+        Ok(_) => info!("Model returned no text, retrying once"),
+        Err(e) => info!("{}, retrying once", e),
+    }
+    let nudged = OllamaOptions {
+        temperature: options.temperature + RETRY_TEMPERATURE_STEP,
+        ..options.clone()
+    };
+    let second = match attempt(2, nudged).await {
+        Ok(text) if text.trim().is_empty() => {
+            Err(RejectedOutput(OutputCheck::Empty, "the model returned no text".to_string()).into())
+        }
+        second => second,
+    };
+    (second, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::mock_transport::{MockReply, MockTransport};
+    use crate::ai_toolkit::ollama_client::{OllamaClient, OllamaError};
+
+    fn generate(replies: Vec<MockReply>) -> (Result<String>, bool, Vec<serde_json::Value>) {
+        let client = OllamaClient::with_transport(MockTransport::new(replies));
+        let client = &client;
+        let (result, retried) = tauri::async_runtime::block_on(with_retry(
+            &OllamaOptions::default(),
+            |_, options| async move { client.generate("gemma2:2b", "prompt", &options).await },
+        ));
+        let bodies = client
+            .transport()
+            .requests()
+            .into_iter()
+            .map(|(_, _, body)| body.unwrap())
+            .collect();
+        (result, retried, bodies)
+    }
+
+    #[test]
+    fn test_empty_output_is_retried_once() {
+        let (result, retried, requests) = generate(vec![
+            MockReply::Body(200, r#"{"response":"  \n","done":true}"#),
+            MockReply::Body(
+                200,
+                r#"{"response":"The meeting is at three.","done":true}"#,
+            ),
+        ]);
+
+        assert_eq!(result.unwrap(), "The meeting is at three.");
+        assert!(retried);
+        assert_eq!(requests.len(), 2);
+        let temperature = |request: &serde_json::Value| request["options"]["temperature"].as_f64();
+        assert!(temperature(&requests[1]) > temperature(&requests[0]));
+    }
+
+    #[test]
+    fn test_usable_output_and_failures_are_not_retried() {
+        let (result, retried, requests) = generate(vec![MockReply::Body(
+            200,
+            r#"{"response":"The meeting is at three.","done":true}"#,
+        )]);
+        assert_eq!(result.unwrap(), "The meeting is at three.");
+        assert_eq!((retried, requests.len()), (false, 1));
+
+        let (result, retried, requests) = generate(vec![MockReply::Unreachable]);
+        assert!(result.is_err());
+        assert_eq!((retried, requests.len()), (false, 1));
+
+        let (result, retried, _) = generate(vec![MockReply::Body(
+            404,
+            r#"{"error":"model 'gemma2:2b' not found, try pulling it first"}"#,
+        )]);
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<OllamaError>(),
+            Some(OllamaError::ModelNotFound { .. })
+        ));
+        assert!(!retried);
+    }
+
+    #[test]
+    fn test_rejected_output_is_retried_and_empty_again_rejected() {
+        let mut attempts = Vec::new();
+        let (result, retried) =
+            tauri::async_runtime::block_on(with_retry(&OllamaOptions::default(), |attempt, _| {
+                attempts.push(attempt);
+                async move {
+                    match attempt {
+                        1 => Err(RejectedOutput(OutputCheck::Echo, "echo".to_string()).into()),
+                        _ => Ok(String::new()),
+                    }
+                }
+            }));
+
+        assert!(retried);
+        assert_eq!(attempts, vec![1, 2]);
+        let rejected = result.unwrap_err();
+        assert_eq!(
+            rejected.downcast_ref::<RejectedOutput>().map(|r| r.0),
+            Some(OutputCheck::Empty)
+        );
+    }
+}
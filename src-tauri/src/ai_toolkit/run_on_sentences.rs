@@ -0,0 +1,213 @@
+use super::capitalization::capitalize_first;
+use super::chunking::sentences;
+use anyhow::{anyhow, Result};
+
+/// Bounds on the sentence length past which sentences are split by rule
+const MIN_SENTENCE_WORDS: u32 = 10;
+const MAX_SENTENCE_WORDS: u32 = 100;
+
+/// Fewest words left on either side of a split, so a connector near the
+/// start or end of a sentence doesn't strand a fragment
+const MIN_CLAUSE_WORDS: usize = 4;
+
+/// Words a new sentence can start with when a run-on is split, longest first
+/// so "and after that" is found before "after that"
+const CONNECTORS: &[&[&str]] = &[
+    &["and", "after", "that"],
+    &["and", "then"],
+    &["but", "then"],
+    &["so", "then"],
+    &["after", "that"],
+    &["and", "so"],
+    &["so"],
+];
+
+/// Words a connector ending in "so" has to be followed by to start a
+/// sentence. "so that" and "so much" go on with the sentence they are in.
+const SO_SUBJECTS: &[&str] = &["i", "we", "you", "he", "she", "they", "it", "there"];
+
+/// Break sentences of more than `max_words` words into shorter ones before
+/// connectors like "and then" and "so we", without reordering or dropping
+/// words. Stands in for the model splitting run-ons when it isn't available.
+/// Each line is split on its own, so lists and paragraphs keep their layout.
+pub fn split_long_sentences(text: &str, max_words: usize) -> String {
+    text.split('\n')
+        .map(|line| {
+            sentences(line)
+                .into_iter()
+                .map(|sentence| split_sentence(sentence, max_words))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Check the sentence length set for splitting run-ons
+pub fn validate_max_sentence_words(max_words: u32) -> Result<()> {
+    if !(MIN_SENTENCE_WORDS..=MAX_SENTENCE_WORDS).contains(&max_words) {
+        return Err(anyhow!(
+            "Sentences can be split past {} to {} words, not {}",
+            MIN_SENTENCE_WORDS,
+            MAX_SENTENCE_WORDS,
+            max_words
+        ));
+    }
+    Ok(())
+}
+
+fn split_sentence(sentence: &str, max_words: usize) -> String {
+    let words: Vec<&str> = sentence.split_whitespace().collect();
+    if words.len() <= max_words {
+        return sentence.to_string();
+    }
+
+    let mut clauses: Vec<Vec<&str>> = vec![vec![]];
+    for (i, word) in words.iter().enumerate() {
+        if let Some(len) = connector_at(&words, i) {
+            let before = clauses.last().map_or(0, Vec::len);
+            let after = words.len() - i - len;
+            if before >= MIN_CLAUSE_WORDS && after >= MIN_CLAUSE_WORDS {
+                clauses.push(vec![]);
+            }
+        }
+        clauses.last_mut().unwrap().push(word);
+    }
+    if clauses.len() == 1 {
+        return sentence.to_string();
+    }
+
+    let last = clauses.len() - 1;
+    let split: Vec<String> = clauses
+        .iter()
+        .enumerate()
+        .map(|(i, clause)| {
+            let mut clause = clause.join(" ");
+            if i > 0 {
+                clause = capitalize_first(&clause);
+            }
+            if i < last {
+                clause = end_sentence(&clause);
+            }
+            clause
+        })
+        .collect();
+
+    let leading = &sentence[..sentence.len() - sentence.trim_start().len()];
+    let trailing = &sentence[sentence.trim_end().len()..];
+    format!("{}{}{}", leading, split.join(" "), trailing)
+}
+
+/// Length in words of the connector starting at `words[i]`, if one does
+fn connector_at(words: &[&str], i: usize) -> Option<usize> {
+    let bare_at = |j: usize| words.get(j).map(|word| bare(word));
+
+    CONNECTORS
+        .iter()
+        .find(|connector| {
+            let len = connector.len();
+            // A comma inside the phrase or after it means it isn't one
+            let matches = i + len <= words.len()
+                && (0..len).all(|k| bare_at(i + k).as_deref() == Some(connector[k]))
+                && words[i..i + len].iter().all(|word| !word.ends_with(','));
+            let starts_clause = connector.last() != Some(&"so")
+                || bare_at(i + len).is_some_and(|next| SO_SUBJECTS.contains(&next.as_str()));
+            matches && starts_clause
+        })
+        .map(|connector| connector.len())
+}
+
+/// `word` lowercased, without the punctuation around it
+fn bare(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+        .to_lowercase()
+}
+
+/// `clause` ending in a period, in place of the comma or semicolon it ran on
+/// with. One already ending in a question mark or the like is kept as it is.
+fn end_sentence(clause: &str) -> String {
+    let trimmed = clause.trim_end_matches([',', ';', ':']);
+    if trimmed.ends_with(['.', '!', '?', '…']) {
+        trimmed.to_string()
+    } else {
+        format!("{}.", trimmed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN_ON: &str = include_str!("fixtures/run_on_dictation.txt");
+    const SPLIT: &str = include_str!("fixtures/run_on_dictation_split.txt");
+
+    const MAX_WORDS: usize = 30;
+
+    #[test]
+    fn test_run_on_fixture() {
+        assert!(RUN_ON.split_whitespace().count() >= 60);
+        for (before, after) in RUN_ON.lines().zip(SPLIT.lines()) {
+            assert!(before.split_whitespace().count() >= 60);
+            assert_eq!(split_long_sentences(before, MAX_WORDS), after);
+        }
+        assert_eq!(RUN_ON.lines().count(), SPLIT.lines().count());
+    }
+
+    #[test]
+    fn test_splits_keep_every_word_in_order() {
+        for (before, after) in RUN_ON.lines().zip(SPLIT.lines()) {
+            let bare_words =
+                |text: &str| -> Vec<String> { text.split_whitespace().map(bare).collect() };
+            assert_eq!(bare_words(before), bare_words(after));
+            assert!(sentences(after).len() > 1);
+        }
+    }
+
+    #[test]
+    fn test_short_sentences_are_left_alone() {
+        let short = "I went to the store and then I went home so we could eat.";
+        assert_eq!(split_long_sentences(short, MAX_WORDS), short);
+        assert_eq!(
+            split_long_sentences(short, 5),
+            "I went to the store. And then I went home so we could eat."
+        );
+        assert_eq!(split_long_sentences("", MAX_WORDS), "");
+    }
+
+    #[test]
+    fn test_only_connectors_starting_a_clause_split() {
+        let text = "we worked so hard on the launch so that the customers would be happy and so much of it paid off in the end";
+        assert_eq!(split_long_sentences(text, 10), text);
+
+        let text = "the build broke again this morning, so we rolled back the release and then we wrote it up";
+        assert_eq!(
+            split_long_sentences(text, 10),
+            "the build broke again this morning. So we rolled back the release. And then we wrote it up"
+        );
+    }
+
+    #[test]
+    fn test_fragments_are_not_stranded() {
+        let text = "and then we spent the whole afternoon going through the backlog one ticket at a time and then left";
+        assert_eq!(split_long_sentences(text, 10), text);
+    }
+
+    #[test]
+    fn test_lines_and_questions_keep_their_shape() {
+        let text = "Did you see the numbers from last week? and then the ones from this week\n- milk\n- eggs";
+        assert_eq!(split_long_sentences(text, 5), text);
+
+        let text = "First, I called the vendor about the delay and then I emailed the team about it.\nDone.";
+        assert_eq!(
+            split_long_sentences(text, 10),
+            "First, I called the vendor about the delay. And then I emailed the team about it.\nDone."
+        );
+    }
+
+    #[test]
+    fn test_validate_max_sentence_words() {
+        assert!(validate_max_sentence_words(30).is_ok());
+        assert!(validate_max_sentence_words(MIN_SENTENCE_WORDS).is_ok());
+        assert!(validate_max_sentence_words(MIN_SENTENCE_WORDS - 1).is_err());
+        assert!(validate_max_sentence_words(MAX_SENTENCE_WORDS + 1).is_err());
+    }
+}
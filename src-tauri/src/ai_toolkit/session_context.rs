@@ -0,0 +1,190 @@
+use super::chunking::estimate_tokens;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Most tokens of preceding text shown to the model, so the context never
+/// crowds out the transcript in a small model's window
+pub const CONTEXT_TOKEN_BUDGET: usize = 150;
+
+/// What was pasted for the last few utterances dictated into the same
+/// application, shown to the model so that a burst continuing a sentence
+/// carries on with its casing, pronouns and numbers
+#[derive(Debug, Default)]
+pub struct SessionContext {
+    /// Oldest first
+    utterances: VecDeque<String>,
+    /// Application they were dictated into, when it could be told
+    target: Option<String>,
+    last_at: Option<Instant>,
+}
+
+impl SessionContext {
+    /// Text pasted just before an utterance dictated into `target` at `now`,
+    /// cut to [`CONTEXT_TOKEN_BUDGET`] from its start. Everything is forgotten
+    /// first when the target application changed or nothing was dictated for
+    /// `idle_gap`, as the new utterance then starts something new.
+    pub fn preceding(
+        &mut self,
+        target: Option<&str>,
+        now: Instant,
+        idle_gap: Duration,
+    ) -> Option<String> {
+        let idle = self
+            .last_at
+            .is_some_and(|at| now.saturating_duration_since(at) > idle_gap);
+        if idle || self.target.as_deref() != target {
+            self.clear();
+        }
+        self.latest()
+    }
+
+    /// Text pasted for the last few utterances, as an utterance continuing
+    /// them would be shown it, without forgetting anything
+    pub fn latest(&self) -> Option<String> {
+        let text = self
+            .utterances
+            .iter()
+            .map(|utterance| utterance.trim())
+            .filter(|utterance| !utterance.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let fitting = fit_from_end(&text, CONTEXT_TOKEN_BUDGET);
+        (!fitting.is_empty()).then(|| fitting.to_string())
+    }
+
+    /// Remember `pasted` as the utterance dictated last into `target`,
+    /// keeping at most `max_utterances`
+    pub fn record(
+        &mut self,
+        pasted: &str,
+        target: Option<&str>,
+        now: Instant,
+        max_utterances: usize,
+    ) {
+        if self.target.as_deref() != target {
+            self.clear();
+            self.target = target.map(str::to_string);
+        }
+        self.utterances.push_back(pasted.to_string());
+        while self.utterances.len() > max_utterances {
+            self.utterances.pop_front();
+        }
+        self.last_at = Some(now);
+    }
+
+    pub fn clear(&mut self) {
+        self.utterances.clear();
+        self.target = None;
+        self.last_at = None;
+    }
+}
+
+/// The end of `text` within `max_tokens`, starting at a word
+fn fit_from_end(text: &str, max_tokens: usize) -> &str {
+    let mut start = 0;
+    while estimate_tokens(&text[start..]) > max_tokens {
+        match text[start..]
+            .char_indices()
+            .find(|(_, c)| c.is_whitespace())
+        {
+            Some((space, c)) => start += space + c.len_utf8(),
+            None => return "",
+        }
+    }
+    text[start..].trim_start()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE_GAP: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_preceding_text_is_the_last_utterances() {
+        let mut context = SessionContext::default();
+        let start = Instant::now();
+        assert_eq!(context.preceding(Some("editor"), start, IDLE_GAP), None);
+
+        context.record("We met on Tuesday.", Some("editor"), start, 2);
+        context.record("She said", Some("editor"), start, 2);
+        context.record("it would ship in 3 weeks", Some("editor"), start, 2);
+        assert_eq!(
+            context.preceding(Some("editor"), start + Duration::from_secs(5), IDLE_GAP),
+            Some("She said it would ship in 3 weeks".to_string())
+        );
+    }
+
+    #[test]
+    fn test_switching_application_resets_the_context() {
+        let mut context = SessionContext::default();
+        let start = Instant::now();
+        context.record("Dear Sam,", Some("mail"), start, 3);
+
+        assert_eq!(context.preceding(Some("chat"), start, IDLE_GAP), None);
+        // Going back doesn't bring it back either
+        assert_eq!(context.preceding(Some("mail"), start, IDLE_GAP), None);
+
+        // Recording into another application starts over from there
+        context.record("Dear Sam,", Some("mail"), start, 3);
+        context.record("lol ok", Some("chat"), start, 3);
+        assert_eq!(
+            context.preceding(Some("chat"), start, IDLE_GAP),
+            Some("lol ok".to_string())
+        );
+    }
+
+    #[test]
+    fn test_idle_gap_resets_the_context() {
+        let mut context = SessionContext::default();
+        let start = Instant::now();
+        context.record("The plan is simple.", None, start, 3);
+
+        assert_eq!(
+            context.preceding(None, start + IDLE_GAP, IDLE_GAP),
+            Some("The plan is simple.".to_string())
+        );
+        assert_eq!(
+            context.preceding(None, start + IDLE_GAP + Duration::from_secs(1), IDLE_GAP),
+            None
+        );
+    }
+
+    #[test]
+    fn test_latest_leaves_the_context_as_it_is() {
+        let mut context = SessionContext::default();
+        let start = Instant::now();
+        assert_eq!(context.latest(), None);
+
+        context.record("Dear Sam,", Some("mail"), start, 3);
+        assert_eq!(context.latest(), Some("Dear Sam,".to_string()));
+        assert_eq!(
+            context.preceding(Some("mail"), start, IDLE_GAP),
+            Some("Dear Sam,".to_string())
+        );
+    }
+
+    #[test]
+    fn test_clear_forgets_everything() {
+        let mut context = SessionContext::default();
+        let start = Instant::now();
+        context.record("First part.", Some("editor"), start, 3);
+        context.clear();
+        assert_eq!(context.preceding(Some("editor"), start, IDLE_GAP), None);
+    }
+
+    #[test]
+    fn test_preceding_text_keeps_to_the_budget() {
+        let mut context = SessionContext::default();
+        let start = Instant::now();
+        let long = "word ".repeat(CONTEXT_TOKEN_BUDGET * 2);
+        context.record(&long, None, start, 3);
+        context.record("and the end.", None, start, 3);
+
+        let preceding = context.preceding(None, start, IDLE_GAP).unwrap();
+        assert!(estimate_tokens(&preceding) <= CONTEXT_TOKEN_BUDGET);
+        assert!(preceding.starts_with("word"));
+        assert!(preceding.ends_with("and the end."));
+        assert_eq!(fit_from_end("unbrokenbutfartoolong", 2), "");
+    }
+}
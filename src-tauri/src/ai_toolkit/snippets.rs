@@ -0,0 +1,451 @@
+use crate::settings::AiSnippet;
+use anyhow::{anyhow, Result};
+use std::ops::{Range, RangeInclusive};
+
+/// Brackets around the markers standing in for snippets until the model is
+/// done, distinct from those of protected spans so the two never mix
+pub const SNIPPET_OPEN: char = '⟪';
+pub const SNIPPET_CLOSE: char = '⟫';
+
+/// How strict snippet matching can be set, from loosely to letter for letter
+pub const SNIPPET_STRICTNESS: RangeInclusive<f64> = 0.5..=1.0;
+
+/// Longest snippet accepted
+const MAX_SNIPPET_CHARS: usize = 5000;
+
+/// How many more or fewer words than its trigger a snippet can be said in
+const WORD_SLACK: usize = 2;
+
+/// A transcript with the snippets said in it replaced by markers like
+/// `⟪S1⟫`, which [`SnippetText::expand`] turns into their text
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnippetText {
+    text: String,
+    snippets: Vec<String>,
+}
+
+impl SnippetText {
+    /// The transcript with markers, to enhance
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snippets.is_empty()
+    }
+
+    /// Whether the transcript was a snippet's trigger and nothing else
+    pub fn is_whole(&self) -> bool {
+        self.snippets.len() == 1 && self.text == marker(0)
+    }
+
+    /// Put the snippets' text in place of their markers in `text`, the
+    /// enhancement of [`Self::text`]. A marker the model dropped is simply
+    /// missing.
+    pub fn expand(&self, text: &str) -> String {
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(open) = rest.find(SNIPPET_OPEN) {
+            expanded.push_str(&rest[..open]);
+            rest = &rest[open..];
+            let found = self
+                .snippets
+                .iter()
+                .enumerate()
+                .find_map(|(index, snippet)| {
+                    rest.strip_prefix(marker(index).as_str())
+                        .map(|after| (snippet, after))
+                });
+            match found {
+                Some((snippet, after)) => {
+                    expanded.push_str(snippet);
+                    rest = after;
+                }
+                None => {
+                    expanded.push(SNIPPET_OPEN);
+                    rest = &rest[SNIPPET_OPEN.len_utf8()..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+fn marker(index: usize) -> String {
+    format!("{}S{}{}", SNIPPET_OPEN, index + 1, SNIPPET_CLOSE)
+}
+
+/// Trim a snippet's trigger and check it has something to insert
+pub fn normalize_snippet(snippet: AiSnippet) -> Result<AiSnippet> {
+    let trigger = collapse_whitespace(&snippet.trigger);
+    if key(&trigger).is_empty() {
+        return Err(anyhow!("A snippet needs a trigger phrase"));
+    }
+    if snippet.text.trim().is_empty() {
+        return Err(anyhow!("A snippet needs text to insert"));
+    }
+    if snippet.text.chars().count() > MAX_SNIPPET_CHARS {
+        return Err(anyhow!(
+            "A snippet can be at most {} characters",
+            MAX_SNIPPET_CHARS
+        ));
+    }
+    Ok(AiSnippet { trigger, ..snippet })
+}
+
+/// Whether `a` and `b` name the same snippet: the same trigger in any
+/// casing, however its words are spaced
+pub fn same_trigger(a: &str, b: &str) -> bool {
+    collapse_whitespace(a).eq_ignore_ascii_case(&collapse_whitespace(b))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn validate_snippet_strictness(strictness: f64) -> Result<()> {
+    if !SNIPPET_STRICTNESS.contains(&strictness) {
+        return Err(anyhow!(
+            "Snippet strictness must be between {} and {}",
+            SNIPPET_STRICTNESS.start(),
+            SNIPPET_STRICTNESS.end()
+        ));
+    }
+    Ok(())
+}
+
+/// Replace the snippets said in `text` with markers. A transcript that is a
+/// trigger and nothing else becomes its snippet, and so does a trigger said
+/// within a longer one when its snippet is set to be inserted there.
+///
+/// What was said is compared to a trigger letter by letter, ignoring case,
+/// spaces and punctuation, so "boiler plate disclaimer" is "boilerplate
+/// disclaimer". It matches when the share of its letters that would have to
+/// change is at most `1 - strictness`, which at the default of 0.8 lets
+/// "insert signature" through for "insert my signature". Where matches
+/// overlap, the closest wins.
+pub fn find_snippets(text: &str, snippets: &[AiSnippet], strictness: f64) -> SnippetText {
+    let words = words(text);
+    if words.is_empty() || text.contains(SNIPPET_OPEN) {
+        return SnippetText {
+            text: text.to_string(),
+            snippets: vec![],
+        };
+    }
+
+    let said = key(text);
+    let whole = snippets
+        .iter()
+        .map(|snippet| (similarity(&said, &key(&snippet.trigger)), snippet))
+        .filter(|(score, _)| *score >= strictness)
+        .max_by(|a, b| a.0.total_cmp(&b.0));
+    if let Some((_, snippet)) = whole {
+        return SnippetText {
+            text: marker(0),
+            snippets: vec![snippet.text.clone()],
+        };
+    }
+
+    // Every run of words about as long as a trigger is a candidate for it
+    let mut candidates: Vec<(f64, Range<usize>, &AiSnippet)> = vec![];
+    for snippet in snippets.iter().filter(|snippet| snippet.within_text) {
+        let trigger = key(&snippet.trigger);
+        let trigger_words = words_in(&snippet.trigger);
+        let lengths = trigger_words.saturating_sub(WORD_SLACK).max(1)..=trigger_words + WORD_SLACK;
+        for start in 0..words.len() {
+            for length in lengths.clone() {
+                let Some(run) = words.get(start..start + length) else {
+                    break;
+                };
+                let said = key(&text[run[0].start..run[length - 1].end]);
+                let score = similarity(&said, &trigger);
+                if score >= strictness {
+                    candidates.push((score, start..start + length, snippet));
+                }
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.start.cmp(&b.1.start)));
+    let mut chosen: Vec<(Range<usize>, &AiSnippet)> = vec![];
+    for (_, run, snippet) in candidates {
+        if !chosen
+            .iter()
+            .any(|(taken, _)| taken.start < run.end && run.start < taken.end)
+        {
+            chosen.push((run, snippet));
+        }
+    }
+    chosen.sort_by_key(|(run, _)| run.start);
+
+    let mut found = SnippetText {
+        text: String::with_capacity(text.len()),
+        snippets: vec![],
+    };
+    let mut end = 0;
+    for (run, snippet) in chosen {
+        let range = words[run.start].start..words[run.end - 1].end;
+        found.text.push_str(&text[end..range.start]);
+        found.text.push_str(&marker(found.snippets.len()));
+        found.snippets.push(snippet.text.clone());
+        end = range.end;
+    }
+    found.text.push_str(&text[end..]);
+    found
+}
+
+/// Byte ranges of the snippet markers in `text`, for hiding them from the
+/// model like any other protected span
+pub fn snippet_marker_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    for (start, _) in text.match_indices(SNIPPET_OPEN) {
+        let rest = &text[start + SNIPPET_OPEN.len_utf8()..];
+        let Some(number) = rest.strip_prefix('S') else {
+            continue;
+        };
+        let digits = number.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 && number[digits..].starts_with(SNIPPET_CLOSE) {
+            let end = start + SNIPPET_OPEN.len_utf8() + 1 + digits + SNIPPET_CLOSE.len_utf8();
+            spans.push(start..end);
+        }
+    }
+    spans
+}
+
+/// Byte ranges of the words of `text`: letters and digits, with the
+/// apostrophes between them
+fn words(text: &str) -> Vec<Range<usize>> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut words = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].1.is_alphanumeric() {
+            i += 1;
+            continue;
+        }
+        let start = chars[i].0;
+        let mut j = i + 1;
+        while let Some(&(_, c)) = chars.get(j) {
+            let inner_apostrophe = matches!(c, '\'' | '’')
+                && chars
+                    .get(j + 1)
+                    .is_some_and(|(_, next)| next.is_alphanumeric());
+            if !c.is_alphanumeric() && !inner_apostrophe {
+                break;
+            }
+            j += 1;
+        }
+        let end = chars.get(j).map_or(text.len(), |(index, _)| *index);
+        words.push(start..end);
+        i = j;
+    }
+    words
+}
+
+fn words_in(text: &str) -> usize {
+    words(text).len()
+}
+
+/// What `text` is compared by: its letters and digits, lowercase
+fn key(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// How alike `a` and `b` are, from 0 to 1: the share of the longer one's
+/// characters that needn't change to turn one into the other
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// Levenshtein distance: the fewest insertions, deletions and substitutions
+/// turning `a` into `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::protected_spans::protect_ranges;
+
+    const SIGNATURE: &str = "Best regards,\nJane Doe\nAcme Corp";
+    const DEFAULT_STRICTNESS: f64 = 0.8;
+
+    fn snippets() -> Vec<AiSnippet> {
+        vec![
+            AiSnippet {
+                trigger: "insert my signature".to_string(),
+                text: SIGNATURE.to_string(),
+                within_text: true,
+            },
+            AiSnippet {
+                trigger: "boilerplate disclaimer".to_string(),
+                text: "This message is confidential.".to_string(),
+                within_text: false,
+            },
+        ]
+    }
+
+    fn expand(text: &str, strictness: f64) -> String {
+        let found = find_snippets(text, &snippets(), strictness);
+        found.expand(found.text())
+    }
+
+    #[test]
+    fn test_expands_a_dictated_trigger() {
+        for text in [
+            "Insert my signature.",
+            "insert signature",
+            "Insert my signiture",
+            "insert, my signature!",
+        ] {
+            let found = find_snippets(text, &snippets(), DEFAULT_STRICTNESS);
+            assert!(found.is_whole(), "{}", text);
+            assert_eq!(found.expand(found.text()), SIGNATURE, "{}", text);
+        }
+        assert_eq!(
+            expand("Boiler plate disclaimer.", DEFAULT_STRICTNESS),
+            "This message is confidential."
+        );
+    }
+
+    #[test]
+    fn test_near_misses_are_not_expanded() {
+        for text in [
+            "insert my picture",
+            "insert the date",
+            "signature",
+            "please sign it",
+        ] {
+            let found = find_snippets(text, &snippets(), DEFAULT_STRICTNESS);
+            assert!(found.is_empty(), "{}", text);
+            assert_eq!(found.text(), text);
+        }
+    }
+
+    #[test]
+    fn test_strictness() {
+        // Letter for letter still ignores case and punctuation
+        assert_eq!(expand("Insert my signature.", 1.0), SIGNATURE);
+        assert_eq!(expand("insert signature", 1.0), "insert signature");
+        assert_eq!(expand("insert my picture", 0.8), "insert my picture");
+        assert_eq!(expand("insert my picture", 0.5), SIGNATURE);
+    }
+
+    #[test]
+    fn test_expands_within_text_only_when_set() {
+        assert_eq!(
+            expand(
+                "Thanks for the help. Insert my signature",
+                DEFAULT_STRICTNESS
+            ),
+            format!("Thanks for the help. {}", SIGNATURE)
+        );
+        assert_eq!(
+            expand(
+                "thanks, insert signature and then boilerplate disclaimer",
+                DEFAULT_STRICTNESS
+            ),
+            format!("thanks, {} and then boilerplate disclaimer", SIGNATURE)
+        );
+
+        // Said twice, it is inserted twice
+        let found = find_snippets(
+            "insert my signature or insert my signature",
+            &snippets(),
+            DEFAULT_STRICTNESS,
+        );
+        assert_eq!(found.text(), "⟪S1⟫ or ⟪S2⟫");
+        assert!(!found.is_whole());
+    }
+
+    #[test]
+    fn test_markers_are_hidden_from_the_model() {
+        let found = find_snippets(
+            "Thanks, see https://example.com. Insert my signature",
+            &snippets(),
+            DEFAULT_STRICTNESS,
+        );
+        assert_eq!(found.text(), "Thanks, see https://example.com. ⟪S1⟫");
+        assert_eq!(snippet_marker_spans(found.text()), vec![33..41]);
+
+        let protected = protect_ranges(found.text(), true, snippet_marker_spans(found.text()));
+        assert_eq!(protected.text(), "Thanks, see ⟦H1⟧. ⟦H2⟧");
+        let restored = protected.restore("Thanks, see ⟦H1⟧. ⟦H2⟧").unwrap();
+        assert_eq!(
+            found.expand(&restored),
+            format!("Thanks, see https://example.com. {}", SIGNATURE)
+        );
+    }
+
+    #[test]
+    fn test_text_with_marker_brackets_is_left_alone() {
+        let text = "⟪S1⟫ insert my signature";
+        let found = find_snippets(text, &snippets(), DEFAULT_STRICTNESS);
+        assert!(found.is_empty());
+        assert_eq!(found.expand(text), text);
+        assert!(snippet_marker_spans("⟪S⟫ ⟪Sx⟫ ⟪S1").is_empty());
+    }
+
+    #[test]
+    fn test_normalize_snippet() {
+        let snippet = normalize_snippet(AiSnippet {
+            trigger: "  insert   my signature ".to_string(),
+            text: SIGNATURE.to_string(),
+            within_text: false,
+        })
+        .unwrap();
+        assert_eq!(snippet.trigger, "insert my signature");
+        assert_eq!(snippet.text, SIGNATURE);
+
+        for (trigger, text) in [("", "x"), ("...", "x"), ("sign", "  \n")] {
+            let snippet = AiSnippet {
+                trigger: trigger.to_string(),
+                text: text.to_string(),
+                within_text: false,
+            };
+            assert!(normalize_snippet(snippet).is_err(), "{:?}", trigger);
+        }
+    }
+
+    #[test]
+    fn test_same_trigger() {
+        assert!(same_trigger("sign off", " Sign  off\t"));
+        assert!(!same_trigger("sign off", "signoff"));
+    }
+
+    #[test]
+    fn test_validate_snippet_strictness() {
+        assert!(validate_snippet_strictness(0.8).is_ok());
+        assert!(validate_snippet_strictness(1.0).is_ok());
+        assert!(validate_snippet_strictness(0.4).is_err());
+        assert!(validate_snippet_strictness(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("abc", "abc"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("abcd", "abed"), 0.75);
+        assert_eq!(similarity("abc", ""), 0.0);
+    }
+}
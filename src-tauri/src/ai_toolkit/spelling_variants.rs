@@ -0,0 +1,241 @@
+use super::capitalization::in_casing_of;
+use crate::settings::AiSpellingVariant;
+use std::collections::HashSet;
+
+/// American and British spellings of the most common words that differ,
+/// every form listed on its own. Australian spelling follows British for
+/// all of them. Words spelled differently only in some senses, like
+/// "program" or "check", are left out.
+const VARIANT_PAIRS: &[(&str, &str)] = &[
+    ("color", "colour"),
+    ("colors", "colours"),
+    ("colored", "coloured"),
+    ("colorful", "colourful"),
+    ("favor", "favour"),
+    ("favors", "favours"),
+    ("favorite", "favourite"),
+    ("favorites", "favourites"),
+    ("behavior", "behaviour"),
+    ("behaviors", "behaviours"),
+    ("honor", "honour"),
+    ("honors", "honours"),
+    ("labor", "labour"),
+    ("neighbor", "neighbour"),
+    ("neighbors", "neighbours"),
+    ("neighborhood", "neighbourhood"),
+    ("humor", "humour"),
+    ("flavor", "flavour"),
+    ("flavors", "flavours"),
+    ("rumor", "rumour"),
+    ("harbor", "harbour"),
+    ("center", "centre"),
+    ("centers", "centres"),
+    ("centered", "centred"),
+    ("theater", "theatre"),
+    ("liter", "litre"),
+    ("liters", "litres"),
+    ("fiber", "fibre"),
+    ("organize", "organise"),
+    ("organized", "organised"),
+    ("organizes", "organises"),
+    ("organizing", "organising"),
+    ("organization", "organisation"),
+    ("organizations", "organisations"),
+    ("realize", "realise"),
+    ("realized", "realised"),
+    ("realizing", "realising"),
+    ("recognize", "recognise"),
+    ("recognized", "recognised"),
+    ("apologize", "apologise"),
+    ("apologized", "apologised"),
+    ("prioritize", "prioritise"),
+    ("summarize", "summarise"),
+    ("analyze", "analyse"),
+    ("analyzed", "analysed"),
+    ("catalog", "catalogue"),
+    ("traveled", "travelled"),
+    ("traveling", "travelling"),
+    ("traveler", "traveller"),
+    ("canceled", "cancelled"),
+    ("canceling", "cancelling"),
+    ("labeled", "labelled"),
+    ("modeling", "modelling"),
+    ("defense", "defence"),
+    ("offense", "offence"),
+    ("gray", "grey"),
+    ("jewelry", "jewellery"),
+    ("aluminum", "aluminium"),
+    ("pajamas", "pyjamas"),
+    ("skeptical", "sceptical"),
+    ("fulfill", "fulfil"),
+    ("enrollment", "enrolment"),
+    ("aging", "ageing"),
+];
+
+/// Write the words of `text` in [`VARIANT_PAIRS`] in `variant`, as the
+/// model may not have ("color" → "colour" for British spelling). A word
+/// capitalized inside a sentence is taken for a name and kept, so "Pearl
+/// Harbor" stays as it is. Nothing changes when variants are preserved,
+/// which [`restore_spelling_variants`] handles.
+pub fn enforce_spelling_variant(text: &str, variant: AiSpellingVariant) -> String {
+    let british = match variant {
+        AiSpellingVariant::Us => false,
+        AiSpellingVariant::Uk | AiSpellingVariant::Au => true,
+        AiSpellingVariant::Preserve => return text.to_string(),
+    };
+    replace_words(text, |word| {
+        VARIANT_PAIRS
+            .iter()
+            .find(|(us, uk)| word == if british { *us } else { *uk })
+            .map(|(us, uk)| if british { *uk } else { *us })
+    })
+}
+
+/// Put back the spelling variant `original` used for each word of `output`
+/// the model switched to the other one ("colour" it wrote as "color"), so
+/// the dictated variants are preserved. Names are kept as they are.
+pub fn restore_spelling_variants(original: &str, output: &str) -> String {
+    let said: HashSet<String> = original
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphabetic())
+                .to_lowercase()
+        })
+        .collect();
+    replace_words(output, |word| {
+        let partner = VARIANT_PAIRS.iter().find_map(|&(us, uk)| {
+            if word == us {
+                Some(uk)
+            } else if word == uk {
+                Some(us)
+            } else {
+                None
+            }
+        })?;
+        (said.contains(partner) && !said.contains(word)).then_some(partner)
+    })
+}
+
+/// `text` with each word that `replacement` gives another spelling for,
+/// looked up in lowercase, written that way in the word's own casing.
+/// Words capitalized inside a sentence are names and never replaced, while
+/// words in all capitals are only shouted.
+fn replace_words<'a>(text: &str, replacement: impl Fn(&str) -> Option<&'a str>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut sentence_start = true;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let start = rest.find(char::is_alphabetic).unwrap_or(rest.len());
+        let (between, from_word) = rest.split_at(start);
+        if between.contains(['.', '!', '?', '\n']) {
+            sentence_start = true;
+        }
+        result.push_str(between);
+
+        let end = from_word
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(from_word.len());
+        let (word, after) = from_word.split_at(end);
+        let lowercase = word.to_lowercase();
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase)
+            && word.chars().any(char::is_lowercase);
+        match replacement(&lowercase).filter(|_| sentence_start || !capitalized) {
+            Some(spelling) => result.push_str(&in_casing_of(word, spelling)),
+            None => result.push_str(word),
+        }
+        if !word.is_empty() {
+            sentence_start = false;
+        }
+        rest = after;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_pairs_are_distinct() {
+        let mut seen = HashSet::new();
+        for (us, uk) in VARIANT_PAIRS {
+            assert_ne!(us, uk);
+            assert!(seen.insert(*us), "{} is listed twice", us);
+            assert!(seen.insert(*uk), "{} is listed twice", uk);
+            for word in [us, uk] {
+                assert!(word.chars().all(|c| c.is_ascii_lowercase()), "{}", word);
+            }
+        }
+    }
+
+    #[test]
+    fn test_enforces_the_variant() {
+        let mixed = "My favourite color is gray, and we organized the theatre.";
+        assert_eq!(
+            enforce_spelling_variant(mixed, AiSpellingVariant::Uk),
+            "My favourite colour is grey, and we organised the theatre."
+        );
+        assert_eq!(
+            enforce_spelling_variant(mixed, AiSpellingVariant::Au),
+            "My favourite colour is grey, and we organised the theatre."
+        );
+        assert_eq!(
+            enforce_spelling_variant(mixed, AiSpellingVariant::Us),
+            "My favorite color is gray, and we organized the theater."
+        );
+        assert_eq!(
+            enforce_spelling_variant(mixed, AiSpellingVariant::Preserve),
+            mixed
+        );
+    }
+
+    #[test]
+    fn test_keeps_casing_and_words_around() {
+        assert_eq!(
+            enforce_spelling_variant("Color me GRAY. Colorado's colors!", AiSpellingVariant::Uk),
+            "Colour me GREY. Colorado's colours!"
+        );
+        assert_eq!(
+            enforce_spelling_variant("  defence\n\nCentre-left ", AiSpellingVariant::Us),
+            "  defense\n\nCenter-left "
+        );
+    }
+
+    #[test]
+    fn test_names_are_excluded() {
+        for (text, variant) in [
+            ("We flew to Pearl Harbor last week.", AiSpellingVariant::Uk),
+            ("She votes for the Labor Party.", AiSpellingVariant::Uk),
+            ("Meet me at the World Trade Center.", AiSpellingVariant::Uk),
+            (
+                "They played at the Royal Albert Centre.",
+                AiSpellingVariant::Us,
+            ),
+        ] {
+            assert_eq!(enforce_spelling_variant(text, variant), text, "{:?}", text);
+        }
+        // A word starting a sentence is no name for being capitalized
+        assert_eq!(
+            enforce_spelling_variant("Harbor views. Labor is hard.", AiSpellingVariant::Uk),
+            "Harbour views. Labour is hard."
+        );
+    }
+
+    #[test]
+    fn test_restores_the_dictated_variants() {
+        let original = "The colour of the neighbourhood center";
+        assert_eq!(
+            restore_spelling_variants(original, "The color of the neighborhood center."),
+            "The colour of the neighbourhood center."
+        );
+        // Words used in both variants, or in neither, are left to the model
+        assert_eq!(
+            restore_spelling_variants("color and colour", "color and color"),
+            "color and color"
+        );
+        assert_eq!(
+            restore_spelling_variants("the harbour", "We sailed to Pearl Harbor, the harbor."),
+            "We sailed to Pearl Harbor, the harbour."
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use super::repetitions::{is_punctuation, trailing_punctuation};
+use super::tokens::{keyed_tokens, phrase_at, Token};
+use crate::settings::AiSpokenEmoji;
+use anyhow::{anyhow, Result};
+use std::cmp::Reverse;
+use std::ops::Range;
+
+/// Word said after an emoji's name to ask for it
+const EMOJI_WORD: &str = "emoji";
+
+/// Words that make an emoticon phrase after them something talked about, as
+/// in "she drew a smiley face", rather than an emoticon
+const NOUN_MARKERS: &[&str] = &[
+    "a", "an", "the", "my", "your", "his", "her", "its", "our", "their", "this", "that", "these",
+    "those", "every", "each", "another", "one",
+];
+
+/// Collapse the whitespace in an emoji's phrase, and check it is said in
+/// words and writes something
+pub fn normalize_spoken_emoji(entry: AiSpokenEmoji) -> Result<AiSpokenEmoji> {
+    let phrase = entry
+        .phrase
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if phrase.is_empty() {
+        return Err(anyhow!("An emoji needs a phrase"));
+    }
+    if phrase.contains(|c: char| is_punctuation(c) && c != ' ') {
+        return Err(anyhow!(
+            "An emoji is said in words, without punctuation: \"{}\"",
+            phrase
+        ));
+    }
+    if phrase.to_lowercase().split(' ').next_back() == Some(EMOJI_WORD) {
+        return Err(anyhow!(
+            "Leave \"{}\" off the phrase, it is matched with or without it",
+            EMOJI_WORD
+        ));
+    }
+    let emoji = entry.emoji.trim().to_string();
+    if emoji.is_empty() {
+        return Err(anyhow!("The phrase \"{}\" needs an emoji to write", phrase));
+    }
+
+    Ok(AiSpokenEmoji {
+        phrase,
+        emoji,
+        ..entry
+    })
+}
+
+/// Byte ranges of the emoji said in `text`, which are kept from the model
+/// so it can't reword them before they are converted
+pub fn spoken_emoji_spans(text: &str, dictionary: &[AiSpokenEmoji]) -> Vec<Range<usize>> {
+    find_spoken_emoji(text, dictionary)
+        .into_iter()
+        .map(|(range, _)| range)
+        .collect()
+}
+
+/// Write the emoji of `dictionary` said in `text` as the emoji. An emoji is
+/// said by name followed by "emoji" ("thumbs up emoji"), or for emoticons
+/// also on its own: ending the text or a clause, and not after a word that
+/// makes it a noun ("a smiley face"). The longest phrase wins, so "heart
+/// eyes emoji" is 😍 and not "heart 👀".
+pub fn convert_spoken_emoji(text: &str, dictionary: &[AiSpokenEmoji]) -> String {
+    let mut converted = String::with_capacity(text.len());
+    let mut end = 0;
+    for (range, emoji) in find_spoken_emoji(text, dictionary) {
+        converted.push_str(&text[end..range.start]);
+        converted.push_str(emoji);
+        end = range.end;
+    }
+    converted.push_str(&text[end..]);
+    converted
+}
+
+fn find_spoken_emoji<'a>(
+    text: &str,
+    dictionary: &'a [AiSpokenEmoji],
+) -> Vec<(Range<usize>, &'a str)> {
+    let mut phrases: Vec<(Vec<String>, &AiSpokenEmoji)> = dictionary
+        .iter()
+        .map(|entry| {
+            let phrase = entry
+                .phrase
+                .split_whitespace()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>();
+            (phrase, entry)
+        })
+        .filter(|(phrase, _)| !phrase.is_empty())
+        .collect();
+    phrases.sort_by_key(|(phrase, _)| Reverse(phrase.len()));
+
+    let tokens = keyed_tokens(text);
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let said = phrases.iter().find_map(|(phrase, entry)| {
+            said_at(text, &tokens, i, phrase, entry.emoticon).map(|len| (len, entry))
+        });
+        let Some((len, entry)) = said else {
+            i += 1;
+            continue;
+        };
+
+        let first = &text[tokens[i].range.clone()];
+        let last = &text[tokens[i + len - 1].range.clone()];
+        let start =
+            tokens[i].range.start + (first.len() - first.trim_start_matches(is_punctuation).len());
+        let end = tokens[i + len - 1].range.end - trailing_punctuation(last).len();
+        found.push((start..end, entry.emoji.as_str()));
+        i += len;
+    }
+    found
+}
+
+/// Number of tokens from `tokens[i]` taken up by `phrase` said as an emoji,
+/// "emoji" included
+fn said_at(
+    text: &str,
+    tokens: &[Token],
+    i: usize,
+    phrase: &[String],
+    emoticon: bool,
+) -> Option<usize> {
+    if !phrase_at(text, tokens, i, phrase) {
+        return None;
+    }
+
+    let n = phrase.len();
+    let ending = |token: &Token| trailing_punctuation(&text[token.range.clone()]);
+    let last = &tokens[i + n - 1];
+    let next = tokens.get(i + n);
+    if ending(last).is_empty() && next.is_some_and(|next| next.key == EMOJI_WORD) {
+        return Some(n + 1);
+    }
+
+    let ends_clause = match next {
+        None => true,
+        Some(next) => {
+            !ending(last).is_empty() || text[last.range.end..next.range.start].contains('\n')
+        }
+    };
+    let after_noun_marker = i > 0
+        && ending(&tokens[i - 1]).is_empty()
+        && NOUN_MARKERS.contains(&tokens[i - 1].key.as_str());
+    (emoticon && ends_clause && !after_noun_marker).then_some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(phrase: &str, emoji: &str, emoticon: bool) -> AiSpokenEmoji {
+        AiSpokenEmoji {
+            phrase: phrase.to_string(),
+            emoji: emoji.to_string(),
+            emoticon,
+        }
+    }
+
+    fn dictionary() -> Vec<AiSpokenEmoji> {
+        vec![
+            entry("thumbs up", "👍", false),
+            entry("heart", "❤️", false),
+            entry("red heart", "❤️", false),
+            entry("heart eyes", "😍", false),
+            entry("eyes", "👀", false),
+            entry("smiley face", "🙂", true),
+            entry("face", "😐", false),
+        ]
+    }
+
+    #[test]
+    fn test_named_emoji_in_any_casing() {
+        let dictionary = dictionary();
+        for (text, expected) in [
+            ("great work thumbs up emoji", "great work 👍"),
+            ("Great work, Thumbs Up Emoji!", "Great work, 👍!"),
+            ("(heart emoji) see you", "(❤️) see you"),
+            ("thumbs up emoji thumbs up emoji", "👍 👍"),
+        ] {
+            assert_eq!(convert_spoken_emoji(text, &dictionary), expected);
+        }
+    }
+
+    #[test]
+    fn test_overlapping_phrases_take_the_longest() {
+        let dictionary = dictionary();
+        assert_eq!(
+            convert_spoken_emoji("love it heart eyes emoji", &dictionary),
+            "love it 😍"
+        );
+        assert_eq!(
+            convert_spoken_emoji("a big red heart emoji for you", &dictionary),
+            "a big ❤️ for you"
+        );
+        assert_eq!(
+            convert_spoken_emoji("smiley face emoji and eyes emoji", &dictionary),
+            "🙂 and 👀"
+        );
+        // "face emoji" is only taken when "smiley" isn't part of it
+        assert_eq!(convert_spoken_emoji("my face emoji", &dictionary), "my 😐");
+    }
+
+    #[test]
+    fn test_emoticons_only_on_their_own() {
+        let dictionary = dictionary();
+        for (text, expected) in [
+            ("thanks so much smiley face", "thanks so much 🙂"),
+            ("Thanks, smiley face. See you", "Thanks, 🙂. See you"),
+            (
+                "see you soon smiley face\nBest, Sam",
+                "see you soon 🙂\nBest, Sam",
+            ),
+        ] {
+            assert_eq!(convert_spoken_emoji(text, &dictionary), expected);
+        }
+        for text in [
+            "she drew a smiley face on the card",
+            "she drew a smiley face.",
+            "smiley face stickers are cheap",
+            "I love that smiley face",
+            "thumbs up everyone",
+            "the heart is a muscle",
+        ] {
+            assert_eq!(convert_spoken_emoji(text, &dictionary), text);
+        }
+    }
+
+    #[test]
+    fn test_phrases_broken_by_punctuation_are_left_alone() {
+        let dictionary = dictionary();
+        let text = "thumbs, up emoji";
+        assert_eq!(convert_spoken_emoji(text, &dictionary), text);
+        let text = "thumbs up. emoji";
+        assert_eq!(convert_spoken_emoji(text, &dictionary), text);
+    }
+
+    #[test]
+    fn test_spans_cover_what_is_converted() {
+        let dictionary = dictionary();
+        let text = "nice (thumbs up emoji), smiley face";
+        let spans = spoken_emoji_spans(text, &dictionary);
+        let said: Vec<&str> = spans.iter().map(|span| &text[span.clone()]).collect();
+        assert_eq!(said, vec!["thumbs up emoji", "smiley face"]);
+        assert_eq!(convert_spoken_emoji(text, &dictionary), "nice (👍), 🙂");
+    }
+
+    #[test]
+    fn test_normalize_spoken_emoji() {
+        let normalized = normalize_spoken_emoji(entry("  party   parrot ", " 🦜 ", false)).unwrap();
+        assert_eq!(normalized, entry("party parrot", "🦜", false));
+
+        assert!(normalize_spoken_emoji(entry(" ", "🦜", false)).is_err());
+        assert!(normalize_spoken_emoji(entry("party-parrot", "🦜", false)).is_err());
+        assert!(normalize_spoken_emoji(entry("party parrot emoji", "🦜", false)).is_err());
+        assert!(normalize_spoken_emoji(entry("party parrot", " ", false)).is_err());
+    }
+}
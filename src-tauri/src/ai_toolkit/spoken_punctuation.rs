@@ -0,0 +1,307 @@
+use super::capitalization::capitalize_first;
+use super::repetitions::{ends_sentence, is_punctuation, trailing_punctuation, words, Word};
+use crate::settings::{AiSpokenCommand, AiSpokenCommandKind};
+use anyhow::{anyhow, Result};
+use std::cmp::Reverse;
+
+/// Punctuation that closes a clause. The transcriber may already have put
+/// some around a spoken command, which the command's own mark replaces.
+const CLAUSE_MARKS: &[char] = &[',', ';', ':', '.', '!', '?'];
+
+/// Words that make the word after them a noun, so "the period" and "a comma"
+/// are meant as said
+const NOUN_MARKERS: &[&str] = &[
+    "a", "an", "the", "my", "your", "his", "its", "our", "their", "every", "each", "another", "per",
+];
+
+/// Words that make the word before them a noun, as in "period of time" and
+/// "comma key"
+const NOUN_FOLLOWERS: &[&str] = &[
+    "of",
+    "is",
+    "was",
+    "are",
+    "were",
+    "key",
+    "keys",
+    "sign",
+    "symbol",
+    "character",
+    "button",
+];
+
+/// Collapse the whitespace in a command's phrase, and check it is said in
+/// words and writes something
+pub fn normalize_spoken_command(command: AiSpokenCommand) -> Result<AiSpokenCommand> {
+    let phrase = command
+        .phrase
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if phrase.is_empty() {
+        return Err(anyhow!("A spoken command needs a phrase"));
+    }
+    if phrase.contains(|c: char| is_punctuation(c) && c != ' ') {
+        return Err(anyhow!(
+            "A spoken command is said in words, without punctuation: \"{}\"",
+            phrase
+        ));
+    }
+    if command.symbol.is_empty() {
+        return Err(anyhow!(
+            "The command \"{}\" needs something to write",
+            phrase
+        ));
+    }
+
+    Ok(AiSpokenCommand { phrase, ..command })
+}
+
+/// Write the spoken punctuation and layout commands of `commands` ("comma",
+/// "new line") as what they stand for. A command is only taken as one where
+/// punctuation can go: said as words of its own, not right after a word
+/// that makes it a noun ("the period", "a comma") or before one ("period of
+/// time", "comma key"), and with a word to attach to.
+pub fn apply_spoken_punctuation(text: &str, commands: &[AiSpokenCommand]) -> String {
+    let mut phrases: Vec<(Vec<String>, &AiSpokenCommand)> = commands
+        .iter()
+        .map(|command| {
+            let phrase = command
+                .phrase
+                .split_whitespace()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>();
+            (phrase, command)
+        })
+        .filter(|(phrase, _)| !phrase.is_empty())
+        .collect();
+    // The longest phrase wins, so "question mark" goes before "mark" could
+    phrases.sort_by_key(|(phrase, _)| Reverse(phrase.len()));
+
+    let mut words = words(text);
+    let mut i = 0;
+    while i < words.len() {
+        match command_at(&words, i, &phrases) {
+            Some((n, command)) => apply_command(&mut words, i, n, command),
+            None => i += 1,
+        }
+    }
+
+    let indent = &text[..text.len() - text.trim_start().len()];
+    let mut result = indent.to_string();
+    for word in &words {
+        result.push_str(&word.text);
+        result.push_str(&word.space);
+    }
+    result
+}
+
+/// How many words the command starting at `i` takes, and which it is, when
+/// it stands where punctuation can go
+fn command_at<'a>(
+    words: &[Word],
+    i: usize,
+    phrases: &[(Vec<String>, &'a AiSpokenCommand)],
+) -> Option<(usize, &'a AiSpokenCommand)> {
+    phrases.iter().find_map(|(phrase, command)| {
+        let n = phrase.len();
+        let candidate = words.get(i..i + n)?;
+        // Only punctuation closing the clause may follow the command
+        let said = candidate
+            .iter()
+            .zip(phrase)
+            .all(|(word, key)| word.key == *key && !word.text.starts_with(is_punctuation))
+            && candidate[..n - 1]
+                .iter()
+                .all(|word| trailing_punctuation(&word.text).is_empty())
+            && trailing_punctuation(&candidate[n - 1].text)
+                .chars()
+                .all(|c| CLAUSE_MARKS.contains(&c));
+        if !said {
+            return None;
+        }
+
+        // Punctuation between the command and a word sets the two apart
+        let before = i.checked_sub(1).map(|before| &words[before]);
+        let after = words.get(i + n);
+        let noun_before = before.is_some_and(|word| {
+            trailing_punctuation(&word.text).is_empty() && NOUN_MARKERS.contains(&word.key.as_str())
+        });
+        let noun_after = after.is_some_and(|word| {
+            trailing_punctuation(&candidate[n - 1].text).is_empty()
+                && NOUN_FOLLOWERS.contains(&word.key.as_str())
+        });
+        let attached = match command.kind {
+            AiSpokenCommandKind::Closing => before.is_some(),
+            AiSpokenCommandKind::Opening => after.is_some(),
+            AiSpokenCommandKind::Break => before.is_some() || after.is_some(),
+        };
+        (attached && !noun_before && !noun_after).then_some((n, *command))
+    })
+}
+
+/// Replace the `n` words of the command at `i` with what it writes
+fn apply_command(words: &mut Vec<Word>, i: usize, n: usize, command: &AiSpokenCommand) {
+    let space = words[i + n - 1].space.clone();
+    let starts_sentence = i == 0 || ends_sentence(&words[i - 1].text);
+    let capitalized = words[i].text.starts_with(char::is_uppercase);
+    words.drain(i..i + n);
+
+    let symbol = &command.symbol;
+    match command.kind {
+        AiSpokenCommandKind::Closing => {
+            let before = &mut words[i - 1];
+            if symbol.chars().all(|c| CLAUSE_MARKS.contains(&c)) {
+                let kept = before.text.trim_end_matches(CLAUSE_MARKS).len();
+                before.text.truncate(kept);
+            }
+            before.text.push_str(symbol);
+            before.space = space;
+        }
+        AiSpokenCommandKind::Opening => words[i].text.insert_str(0, symbol),
+        AiSpokenCommandKind::Break => match i.checked_sub(1) {
+            Some(before) => words[before].space = symbol.clone(),
+            None => words[i].text.insert_str(0, symbol),
+        },
+    }
+
+    // A sentence ended by the command, or started by it, starts with a capital
+    let ends = command.kind == AiSpokenCommandKind::Closing && ends_sentence(symbol);
+    if ends || (starts_sentence && capitalized) {
+        if let Some(next) = words.get_mut(i) {
+            next.text = capitalize_first(&next.text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::AiFeatures;
+
+    fn spoken(text: &str) -> String {
+        apply_spoken_punctuation(text, &AiFeatures::default().spoken_commands)
+    }
+
+    #[test]
+    fn test_writes_spoken_punctuation() {
+        let cases = [
+            (
+                "hello comma how are you question mark",
+                "hello, how are you?",
+            ),
+            (
+                "I am fine period see you tomorrow",
+                "I am fine. See you tomorrow",
+            ),
+            ("It works full stop", "It works."),
+            (
+                "he said open quote hi close quote and left",
+                "he said \"hi\" and left",
+            ),
+            (
+                "bring milk open paren two liters close paren",
+                "bring milk (two liters)",
+            ),
+            ("one thing colon the rest semicolon", "one thing: the rest;"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(spoken(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_replaces_the_transcribers_punctuation() {
+        let cases = [
+            (
+                "Hello, comma, how are you? Question mark.",
+                "Hello, how are you?",
+            ),
+            ("Done. Period. Next", "Done. Next"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(spoken(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_writes_breaks() {
+        let cases = [
+            ("first line new line second line", "first line\nsecond line"),
+            (
+                "It works period new paragraph Next topic",
+                "It works.\n\nNext topic",
+            ),
+            ("the end new line", "the end\n"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(spoken(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_keeps_commands_meant_as_words() {
+        let kept = [
+            "The period of time was short.",
+            "Put a comma here.",
+            "Press the comma key twice.",
+            "Add a new line to the list.",
+            "The car came to a full stop at the light.",
+            "Use comma-separated values.",
+            "Periods and commas are hard.",
+            "Period. That is final.",
+            "Comma is a punctuation mark.",
+        ];
+        for input in kept {
+            assert_eq!(spoken(input), input, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_custom_commands() {
+        let commands = vec![AiSpokenCommand {
+            phrase: "next bullet".to_string(),
+            symbol: "\n- ".to_string(),
+            kind: AiSpokenCommandKind::Break,
+        }];
+        assert_eq!(
+            apply_spoken_punctuation("milk next bullet eggs", &commands),
+            "milk\n- eggs"
+        );
+        // Only listed commands are written
+        assert_eq!(
+            apply_spoken_punctuation("milk comma eggs", &commands),
+            "milk comma eggs"
+        );
+    }
+
+    #[test]
+    fn test_keeps_layout() {
+        assert_eq!(
+            spoken("  Hello comma there\n\nSecond  paragraph "),
+            "  Hello, there\n\nSecond  paragraph "
+        );
+    }
+
+    #[test]
+    fn test_normalize_spoken_command() {
+        let command = AiSpokenCommand {
+            phrase: "  full   stop ".to_string(),
+            symbol: ".".to_string(),
+            kind: AiSpokenCommandKind::Closing,
+        };
+        assert_eq!(
+            normalize_spoken_command(command).unwrap().phrase,
+            "full stop"
+        );
+
+        for (phrase, symbol) in [("", "."), ("full-stop", "."), ("dot", "")] {
+            let command = AiSpokenCommand {
+                phrase: phrase.to_string(),
+                symbol: symbol.to_string(),
+                kind: AiSpokenCommandKind::Closing,
+            };
+            assert!(normalize_spoken_command(command).is_err(), "{:?}", phrase);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::ai_toolkit::model_registry::parse_model_name;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use sysinfo::Disks;
+
+/// Where Ollama keeps its models and how much room they take. Local disk
+/// figures are omitted for remote hosts, whose disks we can't see.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct OllamaStorageInfo {
+    pub models_dir: Option<String>,
+    /// Combined size of all installed models as reported by Ollama
+    pub used_bytes: u64,
+    /// Free space on the volume holding `models_dir`
+    pub free_bytes: Option<u64>,
+}
+
+/// Whether an Ollama base URL points at this machine
+pub fn is_local_host(base_url: &str) -> bool {
+    if base_url.starts_with("unix://") {
+        return true;
+    }
+
+    let Ok(url) = reqwest::Url::parse(base_url) else {
+        return false;
+    };
+    match url.host_str() {
+        Some(host) => {
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            host == "localhost" || host == "::1" || host == "0.0.0.0" || host.starts_with("127.")
+        }
+        None => false,
+    }
+}
+
+/// The directory Ollama stores models in: `OLLAMA_MODELS` when set, otherwise
+/// `~/.ollama/models`. On Linux the packaged service runs as its own user, so
+/// its directory is used when the per-user one doesn't exist.
+pub fn resolve_models_dir(ollama_models: Option<String>, home: Option<&Path>) -> Option<PathBuf> {
+    if let Some(dir) = ollama_models.filter(|dir| !dir.trim().is_empty()) {
+        return Some(PathBuf::from(dir.trim()));
+    }
+
+    let user_dir = home.map(|home| home.join(".ollama").join("models"));
+
+    #[cfg(target_os = "linux")]
+    {
+        let service_dir = PathBuf::from("/usr/share/ollama/.ollama/models");
+        if !user_dir.as_ref().is_some_and(|dir| dir.exists()) && service_dir.exists() {
+            return Some(service_dir);
+        }
+    }
+
+    user_dir
+}
+
+/// Free bytes on the volume containing `path`, which need not exist yet
+pub fn free_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?.canonicalize().ok()?;
+
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| existing.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// A file a model is made of. Models built on the same weights share them,
+/// and they are only removed with the last model using them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelBlob {
+    pub digest: String,
+    pub size: u64,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    config: Option<ModelBlob>,
+    #[serde(default)]
+    layers: Vec<ModelBlob>,
+}
+
+/// Where Ollama keeps the manifest of the model it calls `name`
+pub fn manifest_path(models_dir: &Path, name: &str) -> PathBuf {
+    let reference = parse_model_name(name);
+    let mut path = models_dir.join("manifests").join(&reference.registry);
+    path.extend(reference.repository.split('/'));
+    path.join(&reference.tag)
+}
+
+/// The blobs of the model `name`, read from its manifest. `None` when the
+/// manifest can't be read, as for models on another machine.
+pub fn model_blobs(models_dir: &Path, name: &str) -> Option<Vec<ModelBlob>> {
+    let contents = fs::read_to_string(manifest_path(models_dir, name)).ok()?;
+    let manifest: Manifest = serde_json::from_str(&contents).ok()?;
+    Some(manifest.config.into_iter().chain(manifest.layers).collect())
+}
+
+/// Bytes freed by deleting the models made of `removed`, while the ones
+/// made of `kept` stay. Blobs still used by a kept model aren't counted,
+/// and blobs shared between removed models are counted once.
+pub fn reclaimable_bytes(removed: &[Vec<ModelBlob>], kept: &[Vec<ModelBlob>]) -> u64 {
+    let kept: HashSet<&str> = kept
+        .iter()
+        .flatten()
+        .map(|blob| blob.digest.as_str())
+        .collect();
+    let mut counted = HashSet::new();
+    removed
+        .iter()
+        .flatten()
+        .filter(|blob| !kept.contains(blob.digest.as_str()))
+        .filter(|blob| counted.insert(blob.digest.as_str()))
+        .map(|blob| blob.size)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob(digest: &str, size: u64) -> ModelBlob {
+        ModelBlob {
+            digest: digest.to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_is_local_host() {
+        assert!(is_local_host("http://localhost:11434"));
+        assert!(is_local_host("http://127.0.0.1:11434"));
+        assert!(is_local_host("http://[::1]:11434"));
+        assert!(is_local_host("unix:///var/run/ollama.sock"));
+        assert!(!is_local_host("http://gpu-box.lan:11434"));
+        assert!(!is_local_host("https://192.168.1.20"));
+    }
+
+    #[test]
+    fn test_resolve_models_dir_prefers_env_override() {
+        let home = Path::new("/home/handy");
+
+        assert_eq!(
+            resolve_models_dir(Some("/mnt/models".to_string()), Some(home)),
+            Some(PathBuf::from("/mnt/models"))
+        );
+
+        // A blank override is ignored
+        assert_eq!(
+            resolve_models_dir(Some(" ".to_string()), Some(home)),
+            resolve_models_dir(None, Some(home))
+        );
+    }
+
+    #[test]
+    fn test_manifest_path_follows_ollama_layout() {
+        let dir = Path::new("/models");
+        assert_eq!(
+            manifest_path(dir, "llama3.2:1b"),
+            PathBuf::from("/models/manifests/registry.ollama.ai/library/llama3.2/1b")
+        );
+        assert_eq!(
+            manifest_path(dir, "myregistry.local/team/custom"),
+            PathBuf::from("/models/manifests/myregistry.local/team/custom/latest")
+        );
+    }
+
+    #[test]
+    fn test_model_blobs_reads_config_and_layers() {
+        let dir = std::env::temp_dir().join(format!("handy-manifests-{}", std::process::id()));
+        let path = manifest_path(&dir, "gemma2:2b");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            r#"{"schemaVersion":2,"config":{"digest":"sha256:c","size":5},"layers":[{"mediaType":"application/vnd.ollama.image.model","digest":"sha256:w","size":100}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            model_blobs(&dir, "gemma2:2b"),
+            Some(vec![blob("sha256:c", 5), blob("sha256:w", 100)])
+        );
+        assert_eq!(model_blobs(&dir, "gemma2:9b"), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reclaimable_bytes_skips_shared_blobs() {
+        let weights = blob("sha256:weights", 1000);
+        let base = vec![weights.clone(), blob("sha256:base", 10)];
+        let custom = vec![weights, blob("sha256:custom", 20)];
+        let other = vec![blob("sha256:other", 500)];
+
+        // The weights stay with the kept model
+        assert_eq!(
+            reclaimable_bytes(std::slice::from_ref(&custom), std::slice::from_ref(&base)),
+            20
+        );
+        // Removing both frees the weights once
+        assert_eq!(
+            reclaimable_bytes(&[base, custom], std::slice::from_ref(&other)),
+            1030
+        );
+        assert_eq!(reclaimable_bytes(&[other], &[]), 500);
+    }
+}
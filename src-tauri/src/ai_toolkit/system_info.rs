@@ -1,3 +1,4 @@
+use super::model_registry::qualify_model_id;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use sysinfo::System;
@@ -7,6 +8,7 @@ pub struct SystemInfo {
     pub total_ram_gb: f64,
     pub available_ram_gb: f64,
     pub cpu_cores: usize,
+    pub physical_cores: Option<usize>,
     pub os: String,
 }
 
@@ -17,15 +19,61 @@ pub fn get_system_info() -> SystemInfo {
     let total_ram = sys.total_memory() as f64 / 1_073_741_824.0; // Convert bytes to GB
     let available_ram = sys.available_memory() as f64 / 1_073_741_824.0;
     let cpu_cores = sys.cpus().len();
+    let physical_cores = sys.physical_core_count();
 
     SystemInfo {
         total_ram_gb: (total_ram * 10.0).round() / 10.0,  // Round to 1 decimal
         available_ram_gb: (available_ram * 10.0).round() / 10.0,
         cpu_cores,
+        physical_cores,
         os: std::env::consts::OS.to_string(),
     }
 }
 
+/// Pick a generation thread count from the CPU topology.
+///
+/// Hybrid CPUs usually only enable SMT on their performance cores, so when there
+/// are more logical than physical cores (but not twice as many) the difference is
+/// the number of performance cores. Otherwise every physical core is used.
+pub fn recommend_num_thread(logical_cores: usize, physical_cores: Option<usize>) -> u32 {
+    let physical = physical_cores.unwrap_or(logical_cores).max(1);
+
+    let threads = if logical_cores > physical && logical_cores < physical * 2 {
+        logical_cores - physical
+    } else {
+        physical
+    };
+
+    threads.max(1) as u32
+}
+
+/// Detect the thread count to use for generation without a full system refresh
+pub fn detect_num_thread() -> u32 {
+    let mut sys = System::new();
+    sys.refresh_cpu();
+    recommend_num_thread(sys.cpus().len(), sys.physical_core_count())
+}
+
+/// Describe this machine's CPU and memory, so measurements taken on it can be
+/// told apart from those taken on other hardware
+pub fn hardware_fingerprint() -> String {
+    let mut sys = System::new();
+    sys.refresh_cpu();
+    sys.refresh_memory();
+    let brand = sys
+        .cpus()
+        .first()
+        .map(|cpu| cpu.brand().trim().to_string())
+        .unwrap_or_default();
+    format!(
+        "{} {}, {} cores, {} GB",
+        std::env::consts::OS,
+        brand,
+        sys.cpus().len(),
+        (sys.total_memory() as f64 / 1_073_741_824.0).round()
+    )
+}
+
 pub fn recommend_ai_model(info: &SystemInfo) -> &'static str {
     // Recommendations based on total RAM
     if info.total_ram_gb < 8.0 {
@@ -48,49 +96,78 @@ pub fn recommend_ai_model(info: &SystemInfo) -> &'static str {
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct AiModelInfo {
     pub id: String,
+    /// Name used with Ollama, which includes the registry prefix when one is configured
+    pub registry_id: String,
     pub size_mb: u32,
     pub speed: String,
     pub quality: String,
     pub notes: String,
 }
 
-pub fn get_available_models() -> Vec<AiModelInfo> {
+pub fn get_available_models(registry_prefix: Option<&str>) -> Vec<AiModelInfo> {
+    let model = |id: &str, size_mb: u32, speed: &str, quality: &str, notes: &str| AiModelInfo {
+        id: id.to_string(),
+        registry_id: qualify_model_id(id, registry_prefix),
+        size_mb,
+        speed: speed.to_string(),
+        quality: quality.to_string(),
+        notes: notes.to_string(),
+    };
+
     vec![
-        AiModelInfo {
-            id: "gemma2:2b".to_string(),
-            size_mb: 270,
-            speed: "Fastest".to_string(),
-            quality: "Good".to_string(),
-            notes: "Best for low RAM systems (< 8GB)".to_string(),
-        },
-        AiModelInfo {
-            id: "qwen2.5:0.5b".to_string(),
-            size_mb: 500,
-            speed: "Very Fast".to_string(),
-            quality: "Good".to_string(),
-            notes: "Ultra lightweight option".to_string(),
-        },
-        AiModelInfo {
-            id: "llama3.2:1b".to_string(),
-            size_mb: 1000,
-            speed: "Fast".to_string(),
-            quality: "Excellent".to_string(),
-            notes: "Recommended default - best balance".to_string(),
-        },
-        AiModelInfo {
-            id: "gemma2:1b".to_string(),
-            size_mb: 1000,
-            speed: "Fast".to_string(),
-            quality: "Very Good".to_string(),
-            notes: "Alternative 1B model".to_string(),
-        },
-        AiModelInfo {
-            id: "qwen2.5:1.5b".to_string(),
-            size_mb: 1500,
-            speed: "Moderate".to_string(),
-            quality: "Best".to_string(),
-            notes: "Highest quality (16GB+ RAM recommended)".to_string(),
-        },
+        model(
+            "gemma2:2b",
+            270,
+            "Fastest",
+            "Good",
+            "Best for low RAM systems (< 8GB)",
+        ),
+        model(
+            "qwen2.5:0.5b",
+            500,
+            "Very Fast",
+            "Good",
+            "Ultra lightweight option",
+        ),
+        model(
+            "llama3.2:1b",
+            1000,
+            "Fast",
+            "Excellent",
+            "Recommended default - best balance",
+        ),
+        model(
+            "gemma2:1b",
+            1000,
+            "Fast",
+            "Very Good",
+            "Alternative 1B model",
+        ),
+        model(
+            "qwen2.5:1.5b",
+            1500,
+            "Moderate",
+            "Best",
+            "Highest quality (16GB+ RAM recommended)",
+        ),
     ]
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend_num_thread() {
+        // Uniform SMT: one thread per physical core
+        assert_eq!(recommend_num_thread(16, Some(8)), 8);
+        // No SMT (e.g. Apple Silicon)
+        assert_eq!(recommend_num_thread(10, Some(10)), 10);
+        // Hybrid 4P (with SMT) + 8E: only the performance cores
+        assert_eq!(recommend_num_thread(16, Some(12)), 4);
+        // Unknown physical count falls back to logical cores
+        assert_eq!(recommend_num_thread(4, None), 4);
+        assert_eq!(recommend_num_thread(0, None), 1);
+    }
+}
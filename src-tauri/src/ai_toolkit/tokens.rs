@@ -0,0 +1,120 @@
+use super::repetitions::{is_punctuation, trailing_punctuation};
+use std::ops::Range;
+
+/// A whitespace-separated word of the text, and the lowercase form it is
+/// compared by
+pub struct Token {
+    pub range: Range<usize>,
+    pub key: String,
+}
+
+/// Tokens of `text` and the whitespace after each. Whitespace leading the
+/// text comes after an empty token.
+pub fn tokens(text: &str) -> Vec<(&str, &str)> {
+    tokens_between(text, char::is_whitespace)
+}
+
+/// Tokens of `text` and the breaks after each, where `is_break` tells the
+/// characters that separate tokens. Breaks leading the text come after an
+/// empty token.
+pub fn tokens_between(text: &str, is_break: impl Fn(char) -> bool + Copy) -> Vec<(&str, &str)> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest.find(is_break).unwrap_or(rest.len());
+        let (token, after) = rest.split_at(end);
+        let space_end = after.len() - after.trim_start_matches(is_break).len();
+        tokens.push((token, &after[..space_end]));
+        rest = &after[space_end..];
+    }
+    tokens
+}
+
+/// Byte ranges of the whitespace-separated tokens of `text`
+pub fn token_ranges(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = None;
+    text.char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+        .filter_map(move |(i, c)| match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                start = None;
+                Some(s..i)
+            }
+            (false, None) => {
+                start = Some(i);
+                None
+            }
+            _ => None,
+        })
+}
+
+/// The whitespace-separated words of `text`, each keyed by its lowercase
+/// form without the punctuation around it
+pub fn keyed_tokens(text: &str) -> Vec<Token> {
+    token_ranges(text)
+        .map(|range| Token {
+            key: text[range.clone()]
+                .trim_matches(is_punctuation)
+                .to_lowercase(),
+            range,
+        })
+        .collect()
+}
+
+/// Whether `phrase`, in lowercase words, is said from `tokens[i]` on.
+/// Punctuation inside the phrase breaks it up.
+pub fn phrase_at(text: &str, tokens: &[Token], i: usize, phrase: &[impl AsRef<str>]) -> bool {
+    let Some(said) = tokens.get(i..i + phrase.len()) else {
+        return false;
+    };
+    let Some((_, inside)) = said.split_last() else {
+        return false;
+    };
+    said.iter()
+        .zip(phrase)
+        .all(|(token, word)| token.key == word.as_ref())
+        && inside
+            .iter()
+            .all(|token| trailing_punctuation(&text[token.range.clone()]).is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_keep_the_whitespace_after_them() {
+        assert_eq!(
+            tokens("  call me\n\nat five "),
+            vec![
+                ("", "  "),
+                ("call", " "),
+                ("me", "\n\n"),
+                ("at", " "),
+                ("five", " ")
+            ]
+        );
+        assert!(tokens("").is_empty());
+    }
+
+    #[test]
+    fn test_phrases_are_said_in_any_casing_without_breaks() {
+        let text = "Bullet  Point: milk, bullet. point";
+        let tokens = keyed_tokens(text);
+        assert_eq!(tokens[1].range, 8..14);
+        assert_eq!(tokens[1].key, "point");
+        assert!(phrase_at(text, &tokens, 0, &["bullet", "point"]));
+        assert!(!phrase_at(text, &tokens, 3, &["bullet", "point"]));
+        assert!(!phrase_at(text, &tokens, 4, &["point", "milk"]));
+        assert!(!phrase_at(text, &tokens, 0, &[] as &[&str]));
+    }
+
+    #[test]
+    fn test_tokens_between_split_only_at_breaks() {
+        let is_break = |c: char| c == ' ';
+        assert_eq!(
+            tokens_between("12\u{a0}500 apples", is_break),
+            vec![("12\u{a0}500", " "), ("apples", "")]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{anyhow, Result};
+use futures_util::{Stream, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// A fully buffered HTTP response
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl TransportResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Raw body chunks of a streaming response
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>>> + Send>>;
+
+/// The HTTP operations `OllamaClient` needs. Paths are relative to the Ollama
+/// host (e.g. `/api/tags`), so implementations decide how to reach it.
+pub trait OllamaTransport: Send + Sync {
+    fn get(
+        &self,
+        path: &str,
+        timeout: Option<Duration>,
+    ) -> impl Future<Output = Result<TransportResponse>> + Send;
+
+    fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+        timeout: Option<Duration>,
+    ) -> impl Future<Output = Result<TransportResponse>> + Send;
+
+    /// POST and hand back the status with the unbuffered body stream
+    fn post_stream(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> impl Future<Output = Result<(u16, ByteStream)>> + Send;
+
+    fn delete(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> impl Future<Output = Result<TransportResponse>> + Send;
+}
+
+/// Default transport talking HTTP to the Ollama host with reqwest
+pub struct ReqwestTransport {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send(
+        request: reqwest::RequestBuilder,
+        timeout: Option<Duration>,
+    ) -> Result<TransportResponse> {
+        let request = match timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        };
+
+        let response = request.send().await?;
+        let status = response.status().as_u16();
+        let body = response.text().await?;
+
+        Ok(TransportResponse { status, body })
+    }
+}
+
+impl OllamaTransport for ReqwestTransport {
+    async fn get(&self, path: &str, timeout: Option<Duration>) -> Result<TransportResponse> {
+        Self::send(self.client.get(self.url(path)), timeout).await
+    }
+
+    async fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+        timeout: Option<Duration>,
+    ) -> Result<TransportResponse> {
+        Self::send(self.client.post(self.url(path)).json(body), timeout).await
+    }
+
+    async fn post_stream(&self, path: &str, body: &serde_json::Value) -> Result<(u16, ByteStream)> {
+        let response = self.client.post(self.url(path)).json(body).send().await?;
+        let status = response.status().as_u16();
+        let stream = response.bytes_stream().map(|chunk| {
+            chunk
+                .map(|bytes| bytes.to_vec())
+                .map_err(anyhow::Error::from)
+        });
+
+        Ok((status, Box::pin(stream)))
+    }
+
+    async fn delete(&self, path: &str, body: &serde_json::Value) -> Result<TransportResponse> {
+        Self::send(self.client.delete(self.url(path)).json(body), None).await
+    }
+}
+
+/// Transport selected from the configured Ollama base URL
+pub enum HostTransport {
+    Http(ReqwestTransport),
+    #[cfg(unix)]
+    Unix(super::unix_transport::UnixSocketTransport),
+}
+
+impl HostTransport {
+    /// Accepts `http://` and `https://` hosts, plus `unix:///path/to/ollama.sock`
+    /// on platforms with unix domain sockets
+    pub fn from_base_url(base_url: &str) -> Result<Self> {
+        let base_url = base_url.trim();
+
+        if let Some(socket_path) = base_url.strip_prefix("unix://") {
+            #[cfg(unix)]
+            {
+                if socket_path.is_empty() {
+                    return Err(anyhow!("Unix socket URL is missing the socket path"));
+                }
+                return Ok(HostTransport::Unix(
+                    super::unix_transport::UnixSocketTransport::new(socket_path),
+                ));
+            }
+
+            #[cfg(not(unix))]
+            {
+                let _ = socket_path;
+                return Err(anyhow!(
+                    "Unix socket Ollama hosts are not supported on this platform"
+                ));
+            }
+        }
+
+        if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+            return Err(anyhow!(
+                "Ollama host must start with http://, https:// or unix://"
+            ));
+        }
+
+        Ok(HostTransport::Http(ReqwestTransport::new(base_url)))
+    }
+}
+
+impl OllamaTransport for HostTransport {
+    async fn get(&self, path: &str, timeout: Option<Duration>) -> Result<TransportResponse> {
+        match self {
+            HostTransport::Http(transport) => transport.get(path, timeout).await,
+            #[cfg(unix)]
+            HostTransport::Unix(transport) => transport.get(path, timeout).await,
+        }
+    }
+
+    async fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+        timeout: Option<Duration>,
+    ) -> Result<TransportResponse> {
+        match self {
+            HostTransport::Http(transport) => transport.post(path, body, timeout).await,
+            #[cfg(unix)]
+            HostTransport::Unix(transport) => transport.post(path, body, timeout).await,
+        }
+    }
+
+    async fn post_stream(&self, path: &str, body: &serde_json::Value) -> Result<(u16, ByteStream)> {
+        match self {
+            HostTransport::Http(transport) => transport.post_stream(path, body).await,
+            #[cfg(unix)]
+            HostTransport::Unix(transport) => transport.post_stream(path, body).await,
+        }
+    }
+
+    async fn delete(&self, path: &str, body: &serde_json::Value) -> Result<TransportResponse> {
+        match self {
+            HostTransport::Http(transport) => transport.delete(path, body).await,
+            #[cfg(unix)]
+            HostTransport::Unix(transport) => transport.delete(path, body).await,
+        }
+    }
+}
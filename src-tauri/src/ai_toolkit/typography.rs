@@ -0,0 +1,309 @@
+use super::protected_spans::protect_spans;
+
+/// Contractions starting with an apostrophe, which isn't an opening quote
+/// ("'tis", "rock 'n' roll")
+const LEADING_ELISIONS: &[&str] = &["cause", "em", "n", "til", "tis", "twas"];
+
+/// Words after which a spoken "dash" is the word itself, as in "a dash of
+/// salt" or "I had to dash"
+const DASH_BLOCKERS: &[&str] = &[
+    "a", "an", "another", "each", "every", "i", "no", "one", "that", "the", "this", "to", "we",
+    "you", "they",
+];
+
+/// Words after which "dash" is a verb, as in "dash off a note"
+const DASH_VERB_PARTICLES: &[&str] = &["away", "off", "out", "over", "to"];
+
+/// Turn straight quotes into curly ones, `--` and a spoken "dash" into an em
+/// dash and `...` or a spoken "dot dot dot" into an ellipsis. URLs, paths
+/// and code are left as they are.
+pub fn smart_typography(text: &str) -> String {
+    let protected = protect_spans(text);
+    let typeset = quotes(&dashes(&ellipses(&spoken_marks(protected.text()))));
+    protected
+        .restore(&typeset)
+        .unwrap_or_else(|_| text.to_string())
+}
+
+/// Replace the words for marks, "dot dot dot", "em dash" and "dash", with
+/// the marks themselves, attached to the word before them
+fn spoken_marks(text: &str) -> String {
+    let words = words(text);
+    let key = |i: usize| words.get(i).map(|(word, _)| word.to_lowercase());
+    let is = |i: usize, expected: &str| key(i).as_deref() == Some(expected);
+
+    let mut typeset = String::new();
+    let mut i = 0;
+    while i < words.len() {
+        let previous = i.checked_sub(1).and_then(key);
+        let next = key(i + 1);
+        let dash_blocked = !matches!(&previous, Some(p) if !DASH_BLOCKERS.contains(&p.as_str()))
+            || !matches!(&next, Some(n) if !DASH_VERB_PARTICLES.contains(&n.as_str()));
+
+        let mark = if is(i, "dot") && is(i + 1, "dot") && is(i + 2, "dot") {
+            Some(('…', 3))
+        } else if is(i, "em") && is(i + 1, "dash") && i + 2 < words.len() {
+            Some(('—', 2))
+        } else if is(i, "dash") && !dash_blocked {
+            Some(('—', 1))
+        } else {
+            None
+        };
+
+        match mark {
+            Some((mark, len)) if i > 0 => {
+                typeset.truncate(typeset.trim_end().len());
+                typeset.push(mark);
+                // A dash joins the words on both sides
+                if mark == '…' {
+                    typeset.push_str(words[i + len - 1].1);
+                }
+                i += len;
+            }
+            _ => {
+                typeset.push_str(words[i].0);
+                typeset.push_str(words[i].1);
+                i += 1;
+            }
+        }
+    }
+    typeset
+}
+
+/// Replace runs of exactly three dots with an ellipsis
+fn ellipses(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut typeset = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let run = chars[i..].iter().take_while(|c| **c == '.').count();
+        match run {
+            0 => {
+                typeset.push(chars[i]);
+                i += 1;
+            }
+            3 => {
+                typeset.push('…');
+                i += 3;
+            }
+            _ => {
+                typeset.extend(&chars[i..i + run]);
+                i += run;
+            }
+        }
+    }
+    typeset
+}
+
+/// Replace `--` standing between words, with spaces around it or without,
+/// by an em dash. Command-line flags like `--verbose` are left alone.
+fn dashes(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut typeset = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let run = chars[i..].iter().take_while(|c| **c == '-').count();
+        let before = typeset.chars().last();
+        let after = chars.get(i + run).copied();
+        let spaced =
+            before.is_some_and(char::is_whitespace) && after.is_some_and(char::is_whitespace);
+        let joined =
+            before.is_some_and(char::is_alphanumeric) && after.is_some_and(char::is_alphanumeric);
+
+        if run == 2 && (spaced || joined) {
+            typeset.truncate(typeset.trim_end().len());
+            typeset.push('—');
+            i += run;
+            while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+                i += 1;
+            }
+        } else if run > 0 {
+            typeset.extend(&chars[i..i + run]);
+            i += run;
+        } else {
+            typeset.push(chars[i]);
+            i += 1;
+        }
+    }
+    typeset
+}
+
+/// Replace straight quotes with curly ones, telling opening from closing by
+/// what comes before them. Apostrophes become closing single quotes, and the
+/// foot and inch marks in `6'2"` stay straight.
+fn quotes(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut typeset = String::with_capacity(text.len());
+    let mut double_open = false;
+    let mut single_open = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        // Typeset already, so a quote right after an opening one opens too
+        let before = typeset.chars().last();
+        let after = chars.get(i + 1).copied();
+        let opening = !matches!(before, Some(b) if !opens_quote(b));
+        let after_digit = before.is_some_and(|b| b.is_ascii_digit());
+
+        let curly = match c {
+            '"' if opening => {
+                double_open = true;
+                '“'
+            }
+            '"' if after_digit && !double_open => '"',
+            '"' => {
+                double_open = false;
+                '”'
+            }
+            '\'' if opening && after.is_some_and(char::is_alphanumeric) => {
+                if is_elision(&chars[i + 1..]) {
+                    '’'
+                } else {
+                    single_open = true;
+                    '‘'
+                }
+            }
+            '\'' if after_digit && !single_open && !after.is_some_and(char::is_alphabetic) => '\'',
+            '\'' => {
+                // An apostrophe inside a word leaves the quote open
+                if !(before.is_some_and(char::is_alphanumeric)
+                    && after.is_some_and(char::is_alphanumeric))
+                {
+                    single_open = false;
+                }
+                '’'
+            }
+            c => c,
+        };
+        typeset.push(curly);
+    }
+    typeset
+}
+
+/// Whether a quote directly before `c` opens a quotation
+fn opens_quote(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | '[' | '{' | '/' | '—' | '–' | '-' | '“' | '‘')
+}
+
+/// Whether the text after an apostrophe is a contraction like "'tis" or a
+/// year like "'90s" rather than quoted
+fn is_elision(rest: &[char]) -> bool {
+    let word: String = rest.iter().take_while(|c| c.is_alphanumeric()).collect();
+    let digits = word.chars().take_while(char::is_ascii_digit).count();
+    LEADING_ELISIONS.contains(&word.to_lowercase().as_str())
+        || (digits == 2 && word[digits..].chars().all(|c| c == 's'))
+}
+
+/// Words of `text`, each with the whitespace after it
+fn words(text: &str) -> Vec<(&str, &str)> {
+    let mut words = Vec::new();
+    let mut rest = text;
+    let leading = rest.len() - rest.trim_start().len();
+    if leading > 0 {
+        words.push(("", &rest[..leading]));
+        rest = &rest[leading..];
+    }
+    while !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(end);
+        let space_end = after
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(after.len());
+        words.push((word, &after[..space_end]));
+        rest = &after[space_end..];
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotes() {
+        let cases = [
+            (r#""Hello," she said."#, "“Hello,” she said."),
+            (r#"She said "no" twice"#, "She said “no” twice"),
+            (
+                r#"She said "he told me 'no' twice""#,
+                "She said “he told me ‘no’ twice”",
+            ),
+            (r#"('nested' and "quoted")"#, "(‘nested’ and “quoted”)"),
+            ("'quoted'", "‘quoted’"),
+            (r#""'Hi,' he wrote""#, "“‘Hi,’ he wrote”"),
+            (r#"the year "2024" was odd"#, "the year “2024” was odd"),
+            ("'1999' was a year", "‘1999’ was a year"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(smart_typography(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_apostrophes() {
+        let cases = [
+            ("don't", "don’t"),
+            ("we'll see, won't we", "we’ll see, won’t we"),
+            (
+                "James' car and the kids' toys",
+                "James’ car and the kids’ toys",
+            ),
+            ("'Tis the season", "’Tis the season"),
+            ("rock 'n' roll", "rock ’n’ roll"),
+            ("'cause I said so", "’cause I said so"),
+            ("back in the '90s", "back in the ’90s"),
+            ("the 90's", "the 90’s"),
+            ("'I don't know,' he said", "‘I don’t know,’ he said"),
+            (r#"He's 6'2" tall"#, r#"He’s 6'2" tall"#),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(smart_typography(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_dashes_and_ellipses() {
+        let cases = [
+            ("wait -- no", "wait—no"),
+            ("well--maybe", "well—maybe"),
+            ("I'll be there dash probably", "I’ll be there—probably"),
+            (
+                "I was going to say em dash never mind",
+                "I was going to say—never mind",
+            ),
+            ("so...", "so…"),
+            ("and then dot dot dot nothing", "and then… nothing"),
+            ("And then Dot dot dot", "And then…"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(smart_typography(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_leaves_other_text_alone() {
+        let cases = [
+            "run it with --verbose",
+            "a dash of salt",
+            "I had to dash",
+            "dash off a note",
+            "pages 3-5",
+            "wait....",
+            "a - b",
+            "see https://example.com/a--b...c",
+            "run `echo \"hi\" -- 'there'` now",
+            "“Already” typeset — don’t touch…",
+            "",
+        ];
+        for input in cases {
+            assert_eq!(smart_typography(input), input, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_is_idempotent() {
+        let text = r#"She said "it's fine -- really..." and left"#;
+        let once = smart_typography(text);
+        assert_eq!(once, "She said “it’s fine—really…” and left");
+        assert_eq!(smart_typography(&once), once);
+    }
+}
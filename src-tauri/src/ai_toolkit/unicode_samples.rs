@@ -0,0 +1,91 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Pieces the sample texts are made of: accents composed and decomposed,
+/// emoji joined with ZWJ, skin tones and flags, CJK text and punctuation,
+/// and the plain words and punctuation the passes act on
+const PIECES: &[&str] = &[
+    "the",
+    "I",
+    "think",
+    "we",
+    "don't",
+    "colour",
+    "café",
+    "cafe\u{301}",
+    "Cafe\u{301}",
+    "nai\u{308}ve",
+    "Ångström",
+    "e\u{301}",
+    "👨‍👩‍👧‍👦",
+    "👍🏽",
+    "🇯🇵",
+    "❤️‍🔥",
+    "☕",
+    "日本語",
+    "東京に行きます",
+    "中文",
+    "한국어",
+    "。",
+    "、",
+    "「",
+    "」",
+    "！",
+    "？",
+    ",",
+    ".",
+    "?",
+    "\"",
+    "'",
+    "2",
+    "1,234.5",
+    " ",
+    " ",
+    " ",
+    "\n",
+    "\u{3000}",
+];
+
+/// `count` random texts of up to `max_pieces` pieces each for property
+/// tests, the same on every run
+pub fn sample_texts(count: usize, max_pieces: usize) -> Vec<String> {
+    // xorshift64, which is plenty for picking pieces
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut next = move |below: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % below as u64) as usize
+    };
+
+    (0..count)
+        .map(|_| {
+            (0..=next(max_pieces))
+                .map(|_| PIECES[next(PIECES.len())])
+                .collect()
+        })
+        .collect()
+}
+
+/// The grapheme clusters of `text` beyond ASCII, besides whitespace, in
+/// lowercase and sorted. A pass that splits or loses one changes them.
+pub fn non_ascii_graphemes(text: &str) -> Vec<String> {
+    let mut graphemes: Vec<String> = text
+        .graphemes(true)
+        .filter(|grapheme| !grapheme.is_ascii() && !grapheme.chars().all(char::is_whitespace))
+        .map(str::to_lowercase)
+        .collect();
+    graphemes.sort();
+    graphemes
+}
+
+/// Whether a grapheme cluster of `text` starts with a combining mark, joiner
+/// or emoji modifier, which only happens when it was cut from what it
+/// belonged to
+pub fn has_split_clusters(text: &str) -> bool {
+    text.graphemes(true).any(|grapheme| {
+        grapheme.chars().next().is_some_and(|c| {
+            is_combining_mark(c) || matches!(c, '\u{200D}' | '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}')
+        })
+    })
+}
@@ -0,0 +1,331 @@
+use super::transport::{ByteStream, OllamaTransport, TransportResponse};
+use anyhow::{anyhow, Result};
+use futures_util::{stream, StreamExt};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Speaks plain HTTP/1.1 to an Ollama server listening on a unix domain socket.
+///
+/// Every request opens a fresh connection with `Connection: close`, which keeps
+/// the response framing simple: either a `Content-Length` body, a chunked body
+/// (used by the streaming endpoints) or everything until the server hangs up.
+pub struct UnixSocketTransport {
+    socket_path: PathBuf,
+}
+
+struct ResponseHead {
+    status: u16,
+    content_length: Option<usize>,
+    chunked: bool,
+}
+
+impl UnixSocketTransport {
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+        }
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<(ResponseHead, BufReader<UnixStream>)> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to {}: {}", self.socket_path.display(), e))?;
+
+        let body = match body {
+            Some(body) => serde_json::to_vec(body)?,
+            None => Vec::new(),
+        };
+
+        let mut request = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAccept: */*\r\n",
+            method, path
+        );
+        if method != "GET" {
+            request.push_str("Content-Type: application/json\r\n");
+            request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        request.push_str("\r\n");
+
+        // Head and body go out in one write, so a server answering as soon
+        // as it has read the head can't leave the body with a broken pipe
+        let mut request = request.into_bytes();
+        request.extend_from_slice(&body);
+        stream.write_all(&request).await?;
+        stream.flush().await?;
+
+        let mut reader = BufReader::new(stream);
+        let head = read_head(&mut reader).await?;
+
+        Ok((head, reader))
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<TransportResponse> {
+        let response = async {
+            let (head, reader) = self.send(method, path, body).await?;
+            let status = head.status;
+
+            let mut body = Vec::new();
+            let mut chunks = body_stream(head, reader);
+            while let Some(chunk) = chunks.next().await {
+                body.extend_from_slice(&chunk?);
+            }
+
+            Ok::<_, anyhow::Error>(TransportResponse {
+                status,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            })
+        };
+
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, response)
+                .await
+                .map_err(|_| anyhow!("Request to {} timed out", path))?,
+            None => response.await,
+        }
+    }
+}
+
+impl OllamaTransport for UnixSocketTransport {
+    async fn get(&self, path: &str, timeout: Option<Duration>) -> Result<TransportResponse> {
+        self.request("GET", path, None, timeout).await
+    }
+
+    async fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+        timeout: Option<Duration>,
+    ) -> Result<TransportResponse> {
+        self.request("POST", path, Some(body), timeout).await
+    }
+
+    async fn post_stream(&self, path: &str, body: &serde_json::Value) -> Result<(u16, ByteStream)> {
+        let (head, reader) = self.send("POST", path, Some(body)).await?;
+        let status = head.status;
+        Ok((status, body_stream(head, reader)))
+    }
+
+    async fn delete(&self, path: &str, body: &serde_json::Value) -> Result<TransportResponse> {
+        self.request("DELETE", path, Some(body), None).await
+    }
+}
+
+async fn read_head(reader: &mut BufReader<UnixStream>) -> Result<ResponseHead> {
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+
+    let status = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| anyhow!("Malformed HTTP status line: {}", line.trim()))?;
+
+    let mut content_length = None;
+    let mut chunked = false;
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            break;
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().ok();
+            } else if name.eq_ignore_ascii_case("transfer-encoding")
+                && value.to_ascii_lowercase().contains("chunked")
+            {
+                chunked = true;
+            }
+        }
+    }
+
+    Ok(ResponseHead {
+        status,
+        content_length,
+        chunked,
+    })
+}
+
+/// Read one chunk of a chunked body, `None` once the terminating chunk is reached
+async fn read_chunk(reader: &mut BufReader<UnixStream>) -> Result<Option<Vec<u8>>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+
+    let size = line.trim().split(';').next().unwrap_or("");
+    let size = usize::from_str_radix(size, 16)
+        .map_err(|_| anyhow!("Malformed chunk size: {}", line.trim()))?;
+    if size == 0 {
+        return Ok(None);
+    }
+
+    let mut chunk = vec![0u8; size];
+    reader.read_exact(&mut chunk).await?;
+
+    // Trailing CRLF after every chunk
+    let mut crlf = [0u8; 2];
+    reader.read_exact(&mut crlf).await?;
+
+    Ok(Some(chunk))
+}
+
+fn body_stream(head: ResponseHead, reader: BufReader<UnixStream>) -> ByteStream {
+    if head.chunked {
+        return Box::pin(stream::unfold(Some(reader), |state| async move {
+            let mut reader = state?;
+            match read_chunk(&mut reader).await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some(reader))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        }));
+    }
+
+    Box::pin(stream::unfold(
+        Some((reader, head.content_length)),
+        |state| async move {
+            let (mut reader, remaining) = state?;
+            if remaining == Some(0) {
+                return None;
+            }
+
+            let mut buf = vec![0u8; remaining.unwrap_or(8192).min(8192)];
+            match reader.read(&mut buf).await {
+                Ok(0) => None,
+                Ok(read) => {
+                    buf.truncate(read);
+                    Some((Ok(buf), Some((reader, remaining.map(|r| r - read)))))
+                }
+                Err(e) => Some((Err(anyhow::Error::from(e)), None)),
+            }
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ai_toolkit::OllamaClient;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixListener;
+
+    /// Minimal HTTP server answering the endpoints the client test touches
+    async fn serve(listener: UnixListener) {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            // The whole request, body included, is read before answering,
+            // as a server that hangs up early would break the client's pipe
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            let mut expected = None;
+            while expected.is_none_or(|length| request.len() < length) {
+                match socket.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+                if expected.is_none() {
+                    expected = request_length(&request);
+                }
+            }
+            let request = String::from_utf8_lossy(&request).to_string();
+
+            let response = if request.starts_with("GET /api/tags") {
+                let body = r#"{"models":[{"name":"gemma2:2b","size":42,"modified_at":"now"}]}"#;
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else if request.starts_with("POST /api/pull") {
+                let first = "{\"status\":\"pulling manifest\"}\n{\"sta";
+                let second = "tus\":\"success\"}\n";
+                format!(
+                    "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+                    first.len(),
+                    first,
+                    second.len(),
+                    second
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string()
+            };
+
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        }
+    }
+
+    /// Length of the request starting with `request`, head and body, once
+    /// its head has been read
+    fn request_length(request: &[u8]) -> Option<usize> {
+        let head_end = request.windows(4).position(|w| w == b"\r\n\r\n")? + 4;
+        let head = String::from_utf8_lossy(&request[..head_end]);
+        let body_length = head
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        Some(head_end + body_length)
+    }
+
+    #[test]
+    fn test_client_over_unix_socket() {
+        let socket_path =
+            std::env::temp_dir().join(format!("handy-ollama-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket_path);
+
+        tauri::async_runtime::block_on(async {
+            let listener = UnixListener::bind(&socket_path).unwrap();
+            tauri::async_runtime::spawn(serve(listener));
+
+            let client =
+                OllamaClient::with_base_url(&format!("unix://{}", socket_path.display())).unwrap();
+
+            assert!(client.is_available().await);
+
+            let models = client.list_models().await.unwrap();
+            assert_eq!(models.len(), 1);
+            assert_eq!(models[0].name, "gemma2:2b");
+
+            let statuses = Arc::new(Mutex::new(Vec::new()));
+            let recorded = statuses.clone();
+            client
+                .pull_model_with_progress(
+                    "gemma2:2b",
+                    Duration::from_secs(1),
+                    false,
+                    move |status, _, _| recorded.lock().unwrap().push(status),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                *statuses.lock().unwrap(),
+                vec!["pulling manifest", "success"]
+            );
+        });
+
+        let _ = std::fs::remove_file(&socket_path);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::settings::AiVocabularyEntry;
+use anyhow::{anyhow, Result};
+use std::cmp::Reverse;
+
+/// Trim an entry's forms and drop blank or duplicate spoken forms
+pub fn normalize_vocabulary_entry(entry: AiVocabularyEntry) -> Result<AiVocabularyEntry> {
+    let written_form = entry.written_form.trim().to_string();
+    if written_form.is_empty() {
+        return Err(anyhow!("A vocabulary entry needs a written form"));
+    }
+
+    let mut spoken_forms: Vec<String> = Vec::new();
+    for form in entry.spoken_forms {
+        let form = collapse_whitespace(&form);
+        if !form.is_empty()
+            && !spoken_forms
+                .iter()
+                .any(|f| f.to_lowercase() == form.to_lowercase())
+        {
+            spoken_forms.push(form);
+        }
+    }
+
+    Ok(AiVocabularyEntry {
+        spoken_forms,
+        written_form,
+    })
+}
+
+/// Whether `a` and `b` are the same entry's written form, in any casing and
+/// however its words are spaced
+pub fn same_written_form(a: &str, b: &str) -> bool {
+    collapse_whitespace(a).to_lowercase() == collapse_whitespace(b).to_lowercase()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Replace whole-word occurrences of each entry's spoken forms, and of its
+/// written form in any other casing, with the written form exactly as given.
+/// Matching ignores case and treats any run of whitespace as a single space.
+/// Where forms overlap the longest match wins, so "cube era" is replaced
+/// before "cube" is considered.
+pub fn apply_vocabulary(text: &str, entries: &[AiVocabularyEntry]) -> String {
+    let mut forms: Vec<(&str, &str)> = entries
+        .iter()
+        .flat_map(|entry| {
+            let written = entry.written_form.as_str();
+            entry
+                .spoken_forms
+                .iter()
+                .map(String::as_str)
+                .chain(std::iter::once(written))
+                .map(move |form| (form, written))
+        })
+        .filter(|(form, _)| !form.trim().is_empty())
+        .collect();
+    if forms.is_empty() {
+        return text.to_string();
+    }
+    forms.sort_by_key(|(form, _)| Reverse(form.chars().count()));
+
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut at_word_start = true;
+
+    while let Some(c) = rest.chars().next() {
+        if at_word_start {
+            let found = forms
+                .iter()
+                .find_map(|(form, written)| match_form(rest, form).map(|len| (len, *written)));
+            if let Some((len, written)) = found {
+                output.push_str(written);
+                rest = &rest[len..];
+                at_word_start = false;
+                continue;
+            }
+        }
+
+        output.push(c);
+        rest = &rest[c.len_utf8()..];
+        at_word_start = !is_word_char(c);
+    }
+
+    output
+}
+
+/// Byte length of a match for `form` at the start of `text`, provided the
+/// match ends at a word boundary
+fn match_form(text: &str, form: &str) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    let mut end = 0;
+
+    for (index, word) in form.split_whitespace().enumerate() {
+        if index > 0 {
+            if !chars.peek().is_some_and(|(_, c)| c.is_whitespace()) {
+                return None;
+            }
+            while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        }
+
+        for expected in word.chars() {
+            let (i, c) = chars.next()?;
+            if !c.to_lowercase().eq(expected.to_lowercase()) {
+                return None;
+            }
+            end = i + c.len_utf8();
+        }
+    }
+
+    match chars.peek() {
+        Some((_, c)) if is_word_char(*c) => None,
+        _ => Some(end),
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(spoken: &[&str], written: &str) -> AiVocabularyEntry {
+        AiVocabularyEntry {
+            spoken_forms: spoken.iter().map(|s| s.to_string()).collect(),
+            written_form: written.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_replaces_spoken_forms_and_fixes_case() {
+        let vocabulary = [
+            entry(&["cube era", "cuba ra"], "Kubera"),
+            entry(&["fuzzy f", "fuzz f"], "fzf"),
+        ];
+
+        assert_eq!(
+            apply_vocabulary(
+                "I track it in cube era and search with Fuzzy F",
+                &vocabulary
+            ),
+            "I track it in Kubera and search with fzf"
+        );
+        assert_eq!(
+            apply_vocabulary("KUBERA and FZF", &vocabulary),
+            "Kubera and fzf"
+        );
+    }
+
+    #[test]
+    fn test_longest_overlapping_form_wins() {
+        let vocabulary = [entry(&["cube"], "Qube"), entry(&["cube era"], "Kubera")];
+
+        assert_eq!(
+            apply_vocabulary("the cube era budget and the cube", &vocabulary),
+            "the Kubera budget and the Qube"
+        );
+        assert_eq!(
+            apply_vocabulary("the cube\n  era", &vocabulary),
+            "the Kubera"
+        );
+    }
+
+    #[test]
+    fn test_matches_whole_words_next_to_punctuation() {
+        let vocabulary = [entry(&["cube era"], "Kubera")];
+
+        assert_eq!(
+            apply_vocabulary("(cube era), cube era's app. \"Cube era\"!", &vocabulary),
+            "(Kubera), Kubera's app. \"Kubera\"!"
+        );
+        assert_eq!(
+            apply_vocabulary("cube erasure and subcube era", &vocabulary),
+            "cube erasure and subcube era"
+        );
+    }
+
+    #[test]
+    fn test_matching_is_unicode_aware() {
+        let vocabulary = [entry(&["zurich"], "Zürich"), entry(&["cafe"], "Café")];
+
+        assert_eq!(
+            apply_vocabulary("ZÜRICH office near zurich", &vocabulary),
+            "Zürich office near Zürich"
+        );
+        // Non-ASCII letters count as part of a word
+        assert_eq!(
+            apply_vocabulary("ßcafe cafeé café", &vocabulary),
+            "ßcafe cafeé Café"
+        );
+    }
+
+    #[test]
+    fn test_normalize_vocabulary_entry() {
+        let normalized =
+            normalize_vocabulary_entry(entry(&[" cube  era ", "", "Cube Era", "cuba"], " Kubera "))
+                .unwrap();
+        assert_eq!(normalized, entry(&["cube era", "cuba"], "Kubera"));
+
+        assert!(normalize_vocabulary_entry(entry(&["cube"], "  ")).is_err());
+    }
+
+    #[test]
+    fn test_same_written_form() {
+        assert!(same_written_form("Visual Studio", " visual  studio"));
+        assert!(!same_written_form("Kubera", "Qube"));
+    }
+}
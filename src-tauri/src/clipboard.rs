@@ -12,6 +12,17 @@ use crate::utils::is_wayland;
 #[cfg(target_os = "linux")]
 use std::process::Command;
 
+/// Line ending other apps expect in text from the clipboard
+#[cfg(target_os = "windows")]
+const LINE_ENDING: &str = "\r\n";
+#[cfg(not(target_os = "windows"))]
+const LINE_ENDING: &str = "\n";
+
+/// `text` with every line break written as `line_ending`
+fn with_line_endings(text: &str, line_ending: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', line_ending)
+}
+
 /// Sends a Ctrl+V or Cmd+V paste command using platform-specific virtual key codes.
 /// This ensures the paste works regardless of keyboard layout (e.g., Russian, AZERTY, DVORAK).
 fn send_paste_ctrl_v() -> Result<(), String> {
@@ -241,6 +252,9 @@ pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
         text
     };
 
+    // Typed text needs no conversion, each \n is already an Enter key press
+    let clipboard_text = with_line_endings(&text, LINE_ENDING);
+
     info!("Using paste method: {:?}", paste_method);
 
     // Perform the paste operation
@@ -249,18 +263,36 @@ pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
             // Intentionally do not perform any paste action; history/clipboard update
             info!("PasteMethod::None selected - skipping paste action");
         }
-        PasteMethod::CtrlV => paste_via_clipboard_ctrl_v(&text, &app_handle)?,
+        PasteMethod::CtrlV => paste_via_clipboard_ctrl_v(&clipboard_text, &app_handle)?,
         PasteMethod::Direct => paste_via_direct_input(&text)?,
-        PasteMethod::ShiftInsert => paste_via_clipboard_shift_insert(&text, &app_handle)?,
+        PasteMethod::ShiftInsert => paste_via_clipboard_shift_insert(&clipboard_text, &app_handle)?,
     }
 
     // After pasting, optionally copy to clipboard based on settings
     if settings.clipboard_handling == ClipboardHandling::CopyToClipboard {
         let clipboard = app_handle.clipboard();
         clipboard
-            .write_text(&text)
+            .write_text(&clipboard_text)
             .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_line_endings() {
+        let paragraphs = "First paragraph.\n\nSecond one.\r\nThird line.";
+        assert_eq!(
+            with_line_endings(paragraphs, "\r\n"),
+            "First paragraph.\r\n\r\nSecond one.\r\nThird line."
+        );
+        assert_eq!(
+            with_line_endings(paragraphs, "\n"),
+            "First paragraph.\n\nSecond one.\nThird line."
+        );
+    }
+}
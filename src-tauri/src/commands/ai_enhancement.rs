@@ -1,8 +1,61 @@
-use crate::ai_toolkit::{get_available_models, get_system_info, recommend_ai_model, AiModelInfo, SystemInfo};
-use crate::managers::ai_enhancement::AiEnhancementManager;
-use crate::settings::{get_settings, write_settings, AiFeatures};
+use crate::ai_toolkit::acronyms::{normalize_acronym, same_acronym};
+use crate::ai_toolkit::diff::diff_words;
+use crate::ai_toolkit::enhancement_cache::MAX_CACHE_SIZE;
+use crate::ai_toolkit::filler_words::normalize_filler_words;
+use crate::ai_toolkit::language::validate_language_code;
+use crate::ai_toolkit::model_registry::normalize_registry_prefix;
+use crate::ai_toolkit::model_updates::AiModelUpdateCheck;
+use crate::ai_toolkit::number_words::validate_number_style_threshold;
+use crate::ai_toolkit::prompt_template::{
+    validate_examples, validate_instruction, validate_prompt_template, validate_summary_max_words,
+};
+use crate::ai_toolkit::proper_nouns::{normalize_proper_noun, same_name};
+use crate::ai_toolkit::redaction::validate_redaction_placeholder;
+use crate::ai_toolkit::replacement_rules::{
+    preview_replacement_rules, validate_replacement_rules, AiReplacementRulesPreview,
+};
+use crate::ai_toolkit::run_on_sentences::validate_max_sentence_words;
+use crate::ai_toolkit::snippets::{normalize_snippet, same_trigger, validate_snippet_strictness};
+use crate::ai_toolkit::spoken_emoji::normalize_spoken_emoji;
+use crate::ai_toolkit::spoken_punctuation::normalize_spoken_command;
+use crate::ai_toolkit::storage::OllamaStorageInfo;
+use crate::ai_toolkit::vocabulary::{normalize_vocabulary_entry, same_written_form};
+use crate::ai_toolkit::{
+    get_available_models, get_system_info, recommend_ai_model, AiModelInfo, OllamaRunningModel,
+    SystemInfo,
+};
+use crate::managers::ai_benchmark::{AiBenchmarkReport, BenchmarkContext};
+use crate::managers::ai_endpoints::SharedOllamaEndpoints;
+use crate::managers::ai_enhancement::{
+    self, AiEnhancementCacheStats, AiEnhancementManager, AiEnhancementPreview, AiModelComparison,
+    AiModelStatusReport, AiPromptPreview, EnhanceMode, EnhancementError, PullContext,
+    RatingContext, SharedRecentTranscripts, SharedSessionContext,
+};
+use crate::managers::ai_enhancement_history::{
+    AiEnhancementHistoryEntry, AiEnhancementRating, AiRating, SharedEnhancementHistory,
+};
+use crate::managers::ai_enhancement_metrics::{AiEnhancementMetrics, SharedEnhancementMetrics};
+use crate::managers::ai_enhancement_queue::{take_turn, SharedEnhancementQueue};
+use crate::managers::ai_model_degradation::SharedModelDegradation;
+use crate::managers::ai_model_usage::{AiUnusedModels, SharedModelUsage};
+use crate::managers::ai_pull_queue::{AiModelPullProgress, AiPullQueueItem};
+use crate::managers::ai_pull_records::AiPullRecord;
+use crate::managers::ai_refinement::AiReenhancement;
+use crate::managers::ai_test_suite::{
+    normalize_test_sample, run_test_suite, suite_samples, AiTestSuiteReport,
+};
+use crate::managers::ai_usage_stats::{AiUsageRange, AiUsageStats, SharedUsageStats};
+use crate::settings::{
+    get_settings, write_settings, AiAcronym, AiCapitalizationStyle, AiExample, AiFeatures,
+    AiGenerationOptions, AiGpuOffload, AiLanguagePolicy, AiLongTextPolicy, AiOllamaEndpoint,
+    AiOutputGuard, AiProperNoun, AiQueuePolicy, AiReenhanceOverrides, AiReplacementRule,
+    AiSamplingOptions, AiSnippet, AiSpokenCommand, AiSpokenEmoji, AiSummaryStyle, AiTestSample,
+    AiTwoPass, AiVocabularyEntry,
+};
+use std::path::Path;
 use std::sync::Arc;
-use tauri::{AppHandle, State};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
 use tokio::sync::Mutex;
 
 type SharedAiManager = Arc<Mutex<AiEnhancementManager>>;
@@ -22,87 +75,404 @@ pub async fn get_recommended_ai_model() -> Result<String, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_available_ai_models() -> Result<Vec<AiModelInfo>, String> {
-    Ok(get_available_models())
+pub async fn get_available_ai_models(app: AppHandle) -> Result<Vec<AiModelInfo>, String> {
+    let registry_prefix = get_settings(&app).ai_model_registry_prefix;
+    Ok(get_available_models(registry_prefix.as_deref()))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn check_ollama_available(
-    ai_manager: State<'_, SharedAiManager>,
+    app: AppHandle,
+    endpoints: State<'_, SharedOllamaEndpoints>,
 ) -> Result<bool, String> {
-    let manager = ai_manager.lock().await;
-    Ok(manager.is_available().await)
+    Ok(ai_enhancement::select_endpoint(&app, &endpoints)
+        .await
+        .is_some())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_ollama_version(
+    endpoints: State<'_, SharedOllamaEndpoints>,
+) -> Result<String, String> {
+    let client = endpoints.client_for(None).map_err(|e| e.to_string())?;
+    client
+        .version()
+        .await
+        .map_err(|e| format!("Ollama health check failed: {}", e))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn list_ollama_models(
-    ai_manager: State<'_, SharedAiManager>,
+    app: AppHandle,
+    endpoints: State<'_, SharedOllamaEndpoints>,
+    endpoint: Option<String>,
 ) -> Result<Vec<String>, String> {
-    let manager = ai_manager.lock().await;
-    manager
-        .list_models()
+    let client = endpoints
+        .client_for(endpoint.as_deref())
+        .map_err(|e| e.to_string())?;
+    ai_enhancement::list_models(&app, &client)
         .await
         .map_err(|e| format!("Failed to list models: {}", e))
 }
 
+/// Report where Ollama stores models and how much disk they use
 #[tauri::command]
 #[specta::specta]
-pub async fn pull_ollama_model(
+pub async fn get_ollama_storage_info(
     app: AppHandle,
-    ai_manager: State<'_, SharedAiManager>,
+    endpoints: State<'_, SharedOllamaEndpoints>,
+    endpoint: Option<String>,
+) -> Result<OllamaStorageInfo, String> {
+    let endpoint = endpoints
+        .endpoint(endpoint.as_deref())
+        .map_err(|e| e.to_string())?;
+    ai_enhancement::storage_info(&app, &endpoint)
+        .await
+        .map_err(|e| format!("Failed to read model storage: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_ollama_running_models(
+    endpoints: State<'_, SharedOllamaEndpoints>,
+) -> Result<Vec<OllamaRunningModel>, String> {
+    let client = endpoints.client_for(None).map_err(|e| e.to_string())?;
+    client
+        .list_running_models()
+        .await
+        .map_err(|e| format!("Failed to list running models: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn pull_ollama_model(
+    pulls: State<'_, PullContext>,
     model: String,
+    endpoint: Option<String>,
+) -> Result<AiPullQueueItem, String> {
+    pulls
+        .enqueue(&model, endpoint)
+        .map_err(|e| format!("Failed to pull model: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_ai_pull_queue(
+    pulls: State<'_, PullContext>,
+) -> Result<Vec<AiPullQueueItem>, String> {
+    Ok(pulls.queue())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_active_model_pulls(
+    pulls: State<'_, PullContext>,
+) -> Result<Vec<AiModelPullProgress>, String> {
+    Ok(pulls.active())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_queued_pull(pulls: State<'_, PullContext>, id: String) -> Result<(), String> {
+    pulls.cancel(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_resumable_model_pulls(
+    pulls: State<'_, PullContext>,
+) -> Result<Vec<AiPullRecord>, String> {
+    Ok(pulls.resumable().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_model_pull(
+    pulls: State<'_, PullContext>,
+    model_id: String,
+) -> Result<Option<AiPullQueueItem>, String> {
+    pulls
+        .resume(&model_id)
+        .await
+        .map_err(|e| format!("Failed to resume pull: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn dismiss_model_pull(
+    pulls: State<'_, PullContext>,
+    model_id: String,
 ) -> Result<(), String> {
-    let manager = ai_manager.lock().await;
-    manager
-        .pull_model(&model, &app)
+    pulls.dismiss(&model_id);
+    Ok(())
+}
+
+/// Which installed models have a newer version in their registry
+#[tauri::command]
+#[specta::specta]
+pub async fn check_ai_model_updates(app: AppHandle) -> Result<Vec<AiModelUpdateCheck>, String> {
+    ai_enhancement::check_model_updates(&app)
         .await
-        .map_err(|e| format!("Failed to pull model: {}", e))
+        .map_err(|e| format!("Failed to check for model updates: {}", e))
+}
+
+/// Download the latest version of an installed model
+#[tauri::command]
+#[specta::specta]
+pub async fn update_ai_model(
+    pulls: State<'_, PullContext>,
+    model: String,
+) -> Result<AiPullQueueItem, String> {
+    pulls
+        .update(&model)
+        .await
+        .map_err(|e| format!("Failed to update model: {}", e))
+}
+
+/// Import a local GGUF file as an Ollama model and return the model name
+#[tauri::command]
+#[specta::specta]
+pub async fn import_gguf_model(
+    app: AppHandle,
+    endpoints: State<'_, SharedOllamaEndpoints>,
+    path: String,
+    name: String,
+    endpoint: Option<String>,
+) -> Result<String, String> {
+    let client = endpoints
+        .client_for(endpoint.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    ai_enhancement::import_gguf(&app, &client, Path::new(&path), &name)
+        .await
+        .map_err(|e| format!("Failed to import model: {}", e))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn delete_ollama_model(
-    ai_manager: State<'_, SharedAiManager>,
+    app: AppHandle,
+    endpoints: State<'_, SharedOllamaEndpoints>,
     model: String,
+    endpoint: Option<String>,
 ) -> Result<(), String> {
-    let manager = ai_manager.lock().await;
-    manager
-        .delete_model(&model)
+    let client = endpoints
+        .client_for(endpoint.as_deref())
+        .map_err(|e| e.to_string())?;
+    ai_enhancement::delete_model(&app, &client, &model)
         .await
         .map_err(|e| format!("Failed to delete model: {}", e))
 }
 
+/// Installed models not used within the last `days` days
+#[tauri::command]
+#[specta::specta]
+pub async fn get_unused_ai_models(
+    app: AppHandle,
+    endpoints: State<'_, SharedOllamaEndpoints>,
+    usage: State<'_, SharedModelUsage>,
+    days: u32,
+) -> Result<AiUnusedModels, String> {
+    let endpoint = endpoints.endpoint(None).map_err(|e| e.to_string())?;
+    ai_enhancement::unused_models(&app, &endpoint, &usage, days)
+        .await
+        .map_err(|e| format!("Failed to find unused models: {}", e))
+}
+
+/// Delete the installed models not used within the last `days` days,
+/// never the selected one, and return them
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_unused_ai_models(
+    app: AppHandle,
+    endpoints: State<'_, SharedOllamaEndpoints>,
+    usage: State<'_, SharedModelUsage>,
+    days: u32,
+) -> Result<Vec<String>, String> {
+    let endpoint = endpoints.endpoint(None).map_err(|e| e.to_string())?;
+    ai_enhancement::delete_unused_models(&app, &endpoint, &usage, days)
+        .await
+        .map_err(|e| format!("Failed to delete unused models: {}", e))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn test_ai_enhancement(
     ai_manager: State<'_, SharedAiManager>,
     app_handle: AppHandle,
     text: String,
-) -> Result<String, String> {
+    mode: Option<EnhanceMode>,
+) -> Result<AiEnhancementPreview, EnhancementError> {
     let settings = get_settings(&app_handle);
     
     if !settings.ai_enhancement_enabled {
-        return Err("AI enhancement is not enabled".to_string());
+        return Err(EnhancementError::Disabled);
     }
 
     let model = settings
         .ai_selected_model
-        .ok_or("No AI model selected")?;
+        .ok_or(EnhancementError::NoModelSelected)?;
 
     let features = AiFeatures {
         punctuation_and_capitalization: settings.ai_features.punctuation_and_capitalization,
+        capitalization_style: settings.ai_features.capitalization_style,
         remove_filler_words: settings.ai_features.remove_filler_words,
+        filler_words: settings.ai_features.filler_words.clone(),
+        spoken_punctuation: settings.ai_features.spoken_punctuation,
+        spoken_commands: settings.ai_features.spoken_commands.clone(),
         normalize_numbers: settings.ai_features.normalize_numbers,
+        number_locale: settings.ai_features.number_locale,
+        number_style_threshold: settings.ai_features.number_style_threshold,
+        normalize_dates_times: settings.ai_features.normalize_dates_times,
+        date_style: settings.ai_features.date_style,
+        normalize_units: settings.ai_features.normalize_units,
+        unit_style: settings.ai_features.unit_style,
+        normalize_currency: settings.ai_features.normalize_currency,
+        currency_style: settings.ai_features.currency_style,
+        format_phone_numbers: settings.ai_features.format_phone_numbers,
+        phone_style: settings.ai_features.phone_style,
         fix_spelling: settings.ai_features.fix_spelling,
+        spelling_variant: settings.ai_features.spelling_variant,
+        fix_grammar: settings.ai_features.fix_grammar,
+        contraction_style: settings.ai_features.contraction_style,
+        remove_repetitions: settings.ai_features.remove_repetitions,
+        protect_spans: settings.ai_features.protect_spans,
+        split_paragraphs: settings.ai_features.split_paragraphs,
+        split_long_sentences: settings.ai_features.split_long_sentences,
+        max_sentence_words: settings.ai_features.max_sentence_words,
+        format_lists: settings.ai_features.format_lists,
+        list_style: settings.ai_features.list_style,
+        smart_typography: settings.ai_features.smart_typography,
+        mask_profanity: settings.ai_features.mask_profanity,
+        profanity_mask: settings.ai_features.profanity_mask,
+        profane_words: settings.ai_features.profane_words.clone(),
+        convert_spoken_emoji: settings.ai_features.convert_spoken_emoji,
+        spoken_emoji: settings.ai_features.spoken_emoji.clone(),
+        redact_sensitive: settings.ai_features.redact_sensitive,
+        redaction: settings.ai_features.redaction.clone(),
+        translate_to: settings.ai_features.translate_to.clone(),
+        tone: settings.ai_features.tone,
+        instructions: settings.ai_features.instructions.clone(),
     };
 
     let mut manager = ai_manager.lock().await;
-    manager
-        .test_enhancement(&text, &model, &features)
+    let started = Instant::now();
+    let enhanced = manager
+        .test_enhancement(&text, &model, &features, mode.unwrap_or_default())
+        .await?;
+    Ok(AiEnhancementPreview {
+        diff: diff_words(&text, &enhanced.text),
+        duration_ms: started.elapsed().as_millis() as u32,
+        original: text,
+        text: enhanced.text,
+        outcome: enhanced.outcome,
+    })
+}
+
+/// The prompts enhancing `text` would send with the current settings, and
+/// about how many tokens they take, without asking the model
+#[tauri::command]
+#[specta::specta]
+pub fn preview_ai_prompt(
+    app: AppHandle,
+    context: State<'_, SharedSessionContext>,
+    text: String,
+    mode: Option<EnhanceMode>,
+) -> Result<AiPromptPreview, String> {
+    let settings = get_settings(&app);
+    let preceding = context.lock().unwrap().latest();
+    Ok(ai_enhancement::preview_prompts(
+        &text,
+        &settings,
+        mode.unwrap_or_default(),
+        preceding.as_deref(),
+    ))
+}
+
+/// Correct the same sample text with two models, one after the other, to
+/// compare their output and speed. A model that fails, e.g. for not being
+/// installed, reports why on its side without failing the other.
+#[tauri::command]
+#[specta::specta]
+pub async fn compare_ai_models(
+    ai_manager: State<'_, SharedAiManager>,
+    queue: State<'_, SharedEnhancementQueue>,
+    app_handle: AppHandle,
+    text: String,
+    model_a: String,
+    model_b: String,
+) -> Result<AiModelComparison, String> {
+    let features = get_settings(&app_handle).ai_features;
+
+    let mut sides = Vec::with_capacity(2);
+    for model in [&model_a, &model_b] {
+        // Each model takes its turn with dictated transcripts
+        let _slot = take_turn(&queue).await;
+        let side = ai_manager
+            .lock()
+            .await
+            .compare_enhancement(&text, model, &features)
+            .await;
+        sides.push(side);
+    }
+    let b = sides.pop().unwrap();
+    let a = sides.pop().unwrap();
+    Ok(AiModelComparison { a, b })
+}
+
+/// Enhance the text pasted last again, with a heavier model or more
+/// features, returning the result and what it changed. `None` applies the
+/// overrides the `reenhance_last` shortcut uses. Fails when no enhancement
+/// has finished yet, or a newer dictation has started since.
+#[tauri::command]
+#[specta::specta]
+pub async fn reenhance_last(
+    ai_manager: State<'_, SharedAiManager>,
+    queue: State<'_, SharedEnhancementQueue>,
+    app_handle: AppHandle,
+    overrides: Option<AiReenhanceOverrides>,
+) -> Result<AiReenhancement, String> {
+    let overrides = overrides.unwrap_or_else(|| get_settings(&app_handle).ai_reenhance);
+    let _slot = take_turn(&queue).await;
+    ai_manager
+        .lock()
+        .await
+        .reenhance_last(&overrides)
         .await
-        .map_err(|e| format!("Enhancement failed: {}", e))
+        .map_err(|e| e.to_string())
+}
+
+/// Run the built-in samples for the enabled features and the user's own
+/// through the selected model, emitting `ai-test-suite-progress` before each.
+/// A sample that fails reports why in its result.
+#[tauri::command]
+#[specta::specta]
+pub async fn run_ai_enhancement_test_suite(
+    ai_manager: State<'_, SharedAiManager>,
+    queue: State<'_, SharedEnhancementQueue>,
+    app_handle: AppHandle,
+) -> Result<AiTestSuiteReport, EnhancementError> {
+    let settings = get_settings(&app_handle);
+    if !settings.ai_enhancement_enabled {
+        return Err(EnhancementError::Disabled);
+    }
+    let model = settings
+        .ai_selected_model
+        .ok_or(EnhancementError::NoModelSelected)?;
+
+    let samples = suite_samples(&settings.ai_features, &settings.ai_test_samples);
+    Ok(run_test_suite(
+        &app_handle,
+        &ai_manager,
+        &queue,
+        &model,
+        &settings.ai_features,
+        samples,
+    )
+    .await)
 }
 
 // Settings commands
@@ -112,6 +482,9 @@ pub fn change_ai_enhancement_enabled(app: AppHandle, enabled: bool) -> Result<()
     let mut settings = get_settings(&app);
     settings.ai_enhancement_enabled = enabled;
     write_settings(&app, settings);
+    if !enabled {
+        ai_enhancement::cancel_preload(&app);
+    }
     Ok(())
 }
 
@@ -121,18 +494,1238 @@ pub fn change_ai_model(app: AppHandle, model: String) -> Result<(), String> {
     let mut settings = get_settings(&app);
     settings.ai_selected_model = Some(model);
     write_settings(&app, settings);
+    ai_enhancement::verify_selected_model(&app);
     Ok(())
 }
 
+/// Check again whether the selected model is installed, also reporting it
+/// through `ai-model-status`. `None` when no model is selected.
+#[tauri::command]
+#[specta::specta]
+pub async fn recheck_ai_model_status(
+    app: AppHandle,
+) -> Result<Option<AiModelStatusReport>, String> {
+    Ok(ai_enhancement::check_selected_model(&app).await)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_ai_features(
     app: AppHandle,
-    features: AiFeatures,
+    mut features: AiFeatures,
 ) -> Result<(), String> {
+    let custom = &features.instructions;
+    for (feature, instruction) in [
+        (
+            "punctuation and capitalization",
+            &custom.punctuation_and_capitalization,
+        ),
+        ("filler word", &custom.remove_filler_words),
+        ("number", &custom.normalize_numbers),
+        ("spelling", &custom.fix_spelling),
+    ] {
+        if let Some(instruction) = instruction {
+            validate_instruction(feature, instruction).map_err(|e| e.to_string())?;
+        }
+    }
+    validate_max_sentence_words(features.max_sentence_words).map_err(|e| e.to_string())?;
+    validate_number_style_threshold(features.number_style_threshold).map_err(|e| e.to_string())?;
+    validate_redaction_placeholder(&features.redaction.placeholder).map_err(|e| e.to_string())?;
+
     let mut settings = get_settings(&app);
+    if let Some(code) = &mut features.translate_to {
+        *code = code.trim().to_lowercase();
+        validate_language_code(code).map_err(|e| e.to_string())?;
+        settings.ai_translate_target = code.clone();
+    }
     settings.ai_features = features;
     write_settings(&app, settings);
     Ok(())
 }
 
+/// Set how punctuation and capitalization cases the text, leaving the other
+/// features as they are
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_capitalization_style(
+    app: AppHandle,
+    style: AiCapitalizationStyle,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_features.capitalization_style = style;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_ai_filler_words(app: AppHandle) -> Result<Vec<String>, String> {
+    Ok(get_settings(&app).ai_features.filler_words)
+}
+
+/// Replace the words and phrases removed as fillers. Returns the list as
+/// saved, trimmed and without duplicates.
+#[tauri::command]
+#[specta::specta]
+pub fn set_ai_filler_words(app: AppHandle, fillers: Vec<String>) -> Result<Vec<String>, String> {
+    let fillers = normalize_filler_words(fillers);
+    let mut settings = get_settings(&app);
+    settings.ai_features.filler_words = fillers.clone();
+    write_settings(&app, settings);
+    Ok(fillers)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_ai_spoken_commands(app: AppHandle) -> Result<Vec<AiSpokenCommand>, String> {
+    Ok(get_settings(&app).ai_features.spoken_commands)
+}
+
+/// Add a spoken punctuation command, replacing any said the same way in
+/// another casing. Returns the updated commands.
+#[tauri::command]
+#[specta::specta]
+pub fn add_ai_spoken_command(
+    app: AppHandle,
+    command: AiSpokenCommand,
+) -> Result<Vec<AiSpokenCommand>, String> {
+    let command = normalize_spoken_command(command).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    let commands = &mut settings.ai_features.spoken_commands;
+    commands.retain(|existing| existing.phrase.to_lowercase() != command.phrase.to_lowercase());
+    commands.push(command);
+    let commands = commands.clone();
+    write_settings(&app, settings);
+    Ok(commands)
+}
+
+/// Remove the spoken punctuation command said as `phrase`. Returns the
+/// updated commands.
+#[tauri::command]
+#[specta::specta]
+pub fn remove_ai_spoken_command(
+    app: AppHandle,
+    phrase: String,
+) -> Result<Vec<AiSpokenCommand>, String> {
+    let mut settings = get_settings(&app);
+    let commands = &mut settings.ai_features.spoken_commands;
+    let before = commands.len();
+    commands.retain(|command| command.phrase.to_lowercase() != phrase.trim().to_lowercase());
+    if commands.len() == before {
+        return Err(format!("No spoken command for \"{}\"", phrase));
+    }
+
+    let commands = commands.clone();
+    write_settings(&app, settings);
+    Ok(commands)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_ai_spoken_emoji(app: AppHandle) -> Result<Vec<AiSpokenEmoji>, String> {
+    Ok(get_settings(&app).ai_features.spoken_emoji)
+}
+
+/// Add an emoji to say by name, replacing any said the same way in another
+/// casing. Returns the updated emoji.
+#[tauri::command]
+#[specta::specta]
+pub fn add_ai_spoken_emoji(
+    app: AppHandle,
+    entry: AiSpokenEmoji,
+) -> Result<Vec<AiSpokenEmoji>, String> {
+    let entry = normalize_spoken_emoji(entry).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    let emoji = &mut settings.ai_features.spoken_emoji;
+    emoji.retain(|existing| existing.phrase.to_lowercase() != entry.phrase.to_lowercase());
+    emoji.push(entry);
+    let emoji = emoji.clone();
+    write_settings(&app, settings);
+    Ok(emoji)
+}
+
+/// Remove the emoji said as `phrase`. Returns the updated emoji.
+#[tauri::command]
+#[specta::specta]
+pub fn remove_ai_spoken_emoji(
+    app: AppHandle,
+    phrase: String,
+) -> Result<Vec<AiSpokenEmoji>, String> {
+    let mut settings = get_settings(&app);
+    let emoji = &mut settings.ai_features.spoken_emoji;
+    let before = emoji.len();
+    emoji.retain(|entry| entry.phrase.to_lowercase() != phrase.trim().to_lowercase());
+    if emoji.len() == before {
+        return Err(format!("No emoji for \"{}\"", phrase));
+    }
+
+    let emoji = emoji.clone();
+    write_settings(&app, settings);
+    Ok(emoji)
+}
+
+/// Switch translation into the last used target language on or off. Returns
+/// the language now translated into, if any.
+#[tauri::command]
+#[specta::specta]
+pub fn toggle_ai_translation(app: AppHandle) -> Result<Option<String>, String> {
+    let mut settings = get_settings(&app);
+    settings.ai_features.translate_to = match settings.ai_features.translate_to.take() {
+        Some(_) => None,
+        None => Some(settings.ai_translate_target.clone()),
+    };
+    let translate_to = settings.ai_features.translate_to.clone();
+    write_settings(&app, settings);
+    Ok(translate_to)
+}
+
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_pull_stall_timeout(app: AppHandle, seconds: u64) -> Result<(), String> {
+    if seconds == 0 {
+        return Err("Stall timeout must be at least one second".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_pull_stall_timeout_secs = seconds;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_pull_auto_retry(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_pull_auto_retry = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_auto_pull_missing_model(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_auto_pull_missing_model = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_check_model_updates(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_check_model_updates = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_num_thread(app: AppHandle, threads: Option<u32>) -> Result<(), String> {
+    if threads == Some(0) {
+        return Err("Thread count must be at least 1".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_num_thread = threads;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_chunk_max_tokens(app: AppHandle, tokens: u32) -> Result<(), String> {
+    if tokens < 32 {
+        return Err("Chunk size must be at least 32 tokens".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_chunk_max_tokens = tokens;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_fallback_to_original(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_fallback_to_original = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_offline_fallback(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_offline_fallback = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_enhancement_timeout_ms(app: AppHandle, timeout_ms: u32) -> Result<(), String> {
+    if !(1000..=60_000).contains(&timeout_ms) {
+        return Err("Timeout must be between 1 and 60 seconds".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_enhancement_timeout_ms = timeout_ms;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_degrade_after_failures(app: AppHandle, failures: u32) -> Result<(), String> {
+    if failures > 10 {
+        return Err("Failures before switching must be at most 10".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_degrade_after_failures = failures;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_latency_budget_ms(app: AppHandle, budget_ms: u32) -> Result<(), String> {
+    if budget_ms > 30_000 {
+        return Err("Latency budget must be at most 30 seconds".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_latency_budget_ms = budget_ms;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// What the `reenhance_last` shortcut changes about the last enhancement
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_reenhance(app: AppHandle, overrides: AiReenhanceOverrides) -> Result<(), String> {
+    let model = overrides.model.map(|model| model.trim().to_string());
+    let mut settings = get_settings(&app);
+    settings.ai_reenhance = AiReenhanceOverrides {
+        model: model.filter(|model| !model.is_empty()),
+        ..overrides
+    };
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Enhance with a fast model first and refine with a better one in the
+/// background, or turn the two-pass mode off with `None`
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_two_pass(app: AppHandle, two_pass: Option<AiTwoPass>) -> Result<(), String> {
+    let two_pass = two_pass.map(|two_pass| AiTwoPass {
+        fast_model: two_pass.fast_model.trim().to_string(),
+        quality_model: two_pass.quality_model.trim().to_string(),
+    });
+    if let Some(two_pass) = &two_pass {
+        if two_pass.fast_model.is_empty() || two_pass.quality_model.is_empty() {
+            return Err("Both a fast and a quality model are needed".to_string());
+        }
+        if two_pass.fast_model == two_pass.quality_model {
+            return Err("The quality model must differ from the fast model".to_string());
+        }
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_two_pass = two_pass;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Go back to the selected model after the session moved to a smaller one
+#[tauri::command]
+#[specta::specta]
+pub fn reset_ai_model_degradation(
+    app: AppHandle,
+    degradation: State<'_, SharedModelDegradation>,
+) -> Result<(), String> {
+    if let Some(model) = degradation.lock().unwrap().reset() {
+        let _ = app.emit("ai-model-restored", model);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_min_words_for_enhancement(app: AppHandle, words: u32) -> Result<(), String> {
+    if !(1..=20).contains(&words) {
+        return Err("Minimum word count must be between 1 and 20".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_min_words_for_enhancement = words;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_max_words_for_enhancement(app: AppHandle, words: u32) -> Result<(), String> {
+    if !(50..=5000).contains(&words) {
+        return Err("Maximum word count must be between 50 and 5000".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_max_words_for_enhancement = words;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_long_text_policy(app: AppHandle, policy: AiLongTextPolicy) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_long_text_policy = policy;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_warm_up_on_battery(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_warm_up_on_battery = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_preload_on_startup(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_preload_on_startup = enabled;
+    write_settings(&app, settings);
+    if !enabled {
+        ai_enhancement::cancel_preload(&app);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_preload_keep_alive(app: AppHandle, minutes: u32) -> Result<(), String> {
+    if !(1..=1440).contains(&minutes) {
+        return Err("Keep alive must be between 1 and 1440 minutes".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_preload_keep_alive_mins = minutes;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_idle_unload_minutes(app: AppHandle, minutes: u32) -> Result<(), String> {
+    if minutes > 1440 {
+        return Err("Idle unload must be at most 1440 minutes".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_idle_unload_minutes = minutes;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn change_ai_cache_enabled(
+    ai_manager: State<'_, SharedAiManager>,
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_cache_enabled = enabled;
+    write_settings(&app, settings);
+
+    if !enabled {
+        ai_manager.lock().await.clear_cache();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_cache_size(app: AppHandle, size: u32) -> Result<(), String> {
+    if !(1..=MAX_CACHE_SIZE).contains(&size) {
+        return Err(format!(
+            "The cache can hold 1 to {} results, not {}",
+            MAX_CACHE_SIZE, size
+        ));
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_cache_size = size;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Forget every cached enhancement, so the next dictations go to the model
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_ai_enhancement_cache(
+    ai_manager: State<'_, SharedAiManager>,
+) -> Result<(), String> {
+    ai_manager.lock().await.clear_cache();
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_ai_enhancement_cache_stats(
+    ai_manager: State<'_, SharedAiManager>,
+) -> Result<AiEnhancementCacheStats, String> {
+    Ok(ai_manager.lock().await.cache_stats())
+}
+
+/// How often enhancement ran, fell back or was rejected, and how fast it was,
+/// since the metrics were last reset
+#[tauri::command]
+#[specta::specta]
+pub fn get_ai_enhancement_metrics(
+    metrics: State<'_, SharedEnhancementMetrics>,
+) -> Result<AiEnhancementMetrics, String> {
+    Ok(metrics.lock().unwrap().snapshot())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn reset_ai_enhancement_metrics(
+    metrics: State<'_, SharedEnhancementMetrics>,
+) -> Result<(), String> {
+    metrics.lock().unwrap().reset();
+    Ok(())
+}
+
+/// Daily counts of what came of enhancement over `range`, kept while usage
+/// stats are enabled
+#[tauri::command]
+#[specta::specta]
+pub fn get_ai_usage_stats(
+    usage: State<'_, SharedUsageStats>,
+    range: AiUsageRange,
+) -> Result<AiUsageStats, String> {
+    let today = chrono::Local::now().date_naive();
+    Ok(usage.lock().unwrap().stats(range, today))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn clear_ai_usage_stats(usage: State<'_, SharedUsageStats>) -> Result<(), String> {
+    usage.lock().unwrap().clear();
+    Ok(())
+}
+
+/// Measure how fast `model` corrects a few sample transcripts on this machine.
+/// The samples take turns with dictated transcripts rather than holding them up.
+#[tauri::command]
+#[specta::specta]
+pub async fn benchmark_ai_model(
+    benchmarks: State<'_, BenchmarkContext>,
+    queue: State<'_, SharedEnhancementQueue>,
+    model: String,
+) -> Result<AiBenchmarkReport, String> {
+    benchmarks
+        .run(&queue, &model)
+        .await
+        .map_err(|e| format!("Failed to benchmark {}: {}", model, e))
+}
+
+/// Benchmark every installed model in turn, reporting each through
+/// `ai-benchmark-progress`
+#[tauri::command]
+#[specta::specta]
+pub async fn benchmark_all_installed_models(
+    benchmarks: State<'_, BenchmarkContext>,
+    queue: State<'_, SharedEnhancementQueue>,
+) -> Result<Vec<AiBenchmarkReport>, String> {
+    benchmarks
+        .run_all(&queue)
+        .await
+        .map_err(|e| format!("Failed to benchmark models: {}", e))
+}
+
+/// Benchmarks measured with this version of Handy on this machine
+#[tauri::command]
+#[specta::specta]
+pub async fn get_ai_benchmarks(
+    benchmarks: State<'_, BenchmarkContext>,
+) -> Result<Vec<AiBenchmarkReport>, String> {
+    Ok(benchmarks.reports())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_history_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_history_enabled = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_withhold_original_from_events(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_withhold_original_from_events = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_usage_stats_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_usage_stats_enabled = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_queue_policy(app: AppHandle, policy: AiQueuePolicy) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_queue_policy = policy;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_context_enabled(
+    app: AppHandle,
+    context: State<'_, SharedSessionContext>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_context_enabled = enabled;
+    write_settings(&app, settings);
+    if !enabled {
+        context.lock().unwrap().clear();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_context_utterances(app: AppHandle, count: u32) -> Result<(), String> {
+    if !(1..=10).contains(&count) {
+        return Err("Preceding utterances must be between 1 and 10".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_context_utterances = count;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_context_idle_secs(app: AppHandle, seconds: u32) -> Result<(), String> {
+    if !(10..=3600).contains(&seconds) {
+        return Err("Idle gap must be between 10 seconds and an hour".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_context_idle_secs = seconds;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Forget the preceding text, so the next utterance is enhanced on its own
+#[tauri::command]
+#[specta::specta]
+pub fn clear_ai_context(context: State<'_, SharedSessionContext>) -> Result<(), String> {
+    context.lock().unwrap().clear();
+    Ok(())
+}
+
+/// Up to `limit` enhancement history entries after skipping `offset`, newest
+/// first
+#[tauri::command]
+#[specta::specta]
+pub fn get_enhancement_history(
+    history: State<'_, SharedEnhancementHistory>,
+    offset: u32,
+    limit: u32,
+) -> Result<Vec<AiEnhancementHistoryEntry>, String> {
+    Ok(history
+        .lock()
+        .unwrap()
+        .page(offset as usize, limit as usize))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_enhancement_history_entry(
+    history: State<'_, SharedEnhancementHistory>,
+    id: u32,
+) -> Result<(), String> {
+    history
+        .lock()
+        .unwrap()
+        .delete(id)
+        .map_err(|e| e.to_string())
+}
+
+/// Rate the enhancement finished last with a thumbs up or down and an
+/// optional note, to tell which models and features do badly. Ratings are
+/// only kept on this device.
+#[tauri::command]
+#[specta::specta]
+pub fn rate_last_enhancement(
+    ratings: State<'_, RatingContext>,
+    rating: AiRating,
+    note: Option<String>,
+) -> Result<(), String> {
+    let rating = AiEnhancementRating::new(rating, note.as_deref()).map_err(|e| e.to_string())?;
+    ratings.rate_last(rating).map_err(|e| e.to_string())
+}
+
+/// Rate the enhancement saved as history entry `history_id`, as
+/// [`rate_last_enhancement`] does
+#[tauri::command]
+#[specta::specta]
+pub fn rate_enhancement(
+    ratings: State<'_, RatingContext>,
+    history_id: u32,
+    rating: AiRating,
+    note: Option<String>,
+) -> Result<(), String> {
+    let rating = AiEnhancementRating::new(rating, note.as_deref()).map_err(|e| e.to_string())?;
+    ratings.rate(history_id, rating).map_err(|e| e.to_string())
+}
+
+/// Transcript as dictated for the text pasted last, if any
+#[tauri::command]
+#[specta::specta]
+pub fn get_last_original_transcript(
+    recent: State<'_, SharedRecentTranscripts>,
+) -> Result<Option<String>, String> {
+    Ok(recent
+        .lock()
+        .unwrap()
+        .last()
+        .map(|transcript| transcript.original.clone()))
+}
+
+/// Take back the text pasted last and return its original transcript, to
+/// re-paste or copy. Undoing again reaches the text pasted before it.
+#[tauri::command]
+#[specta::specta]
+pub fn undo_last_enhancement(recent: State<'_, SharedRecentTranscripts>) -> Result<String, String> {
+    recent
+        .lock()
+        .unwrap()
+        .undo()
+        .map(|transcript| transcript.original)
+        .ok_or_else(|| "No pasted transcript to undo".to_string())
+}
+
+/// Stop the enhancement being generated and paste its original transcript
+/// instead. Returns whether one was running.
+#[tauri::command]
+#[specta::specta]
+pub fn abort_ai_enhancement(app: AppHandle) -> Result<bool, String> {
+    Ok(ai_enhancement::abort_enhancement(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_output_guard(app: AppHandle, guard: AiOutputGuard) -> Result<(), String> {
+    if !(guard.min_ratio > 0.0 && guard.min_ratio <= 1.0 && guard.max_ratio >= 1.0) {
+        return Err("Length ratios must satisfy 0 < min <= 1 <= max".to_string());
+    }
+    if !(guard.filler_min_ratio > 0.0 && guard.filler_min_ratio <= guard.min_ratio) {
+        return Err("Filler removal ratio must be between 0 and the minimum ratio".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.ai_output_guard = guard;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+fn validate_sampling_options(mode: &str, options: &AiSamplingOptions) -> Result<(), String> {
+    if !(0.0..=2.0).contains(&options.temperature) {
+        return Err(format!("{} temperature must be between 0 and 2", mode));
+    }
+    if !(64..=8192).contains(&options.max_output_tokens) {
+        return Err(format!(
+            "{} output token limit must be between 64 and 8192",
+            mode
+        ));
+    }
+    if !(0.5..=2.0).contains(&options.repeat_penalty) {
+        return Err(format!("{} repeat penalty must be between 0.5 and 2", mode));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_generation_options(
+    app: AppHandle,
+    options: AiGenerationOptions,
+) -> Result<(), String> {
+    validate_sampling_options("Correction", &options.correct)?;
+    validate_sampling_options("Summary", &options.summarize)?;
+    validate_sampling_options("Tone", &options.tone)?;
+    let mut settings = get_settings(&app);
+    settings.ai_generation_options = options;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// The built-in generation options, for resetting the settings to them
+#[tauri::command]
+#[specta::specta]
+pub fn get_default_ai_generation_options() -> Result<AiGenerationOptions, String> {
+    Ok(AiGenerationOptions::default())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_custom_prompt(app: AppHandle, template: String) -> Result<(), String> {
+    validate_prompt_template(&template).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings.ai_custom_prompt_template = Some(template);
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Go back to the built-in prompt
+#[tauri::command]
+#[specta::specta]
+pub fn reset_ai_prompt_template(app: AppHandle) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_custom_prompt_template = None;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_prompt_all_instructions(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_prompt_all_instructions = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_examples(app: AppHandle, examples: Vec<AiExample>) -> Result<(), String> {
+    validate_examples(&examples).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings.ai_examples = examples;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_ai_vocabulary(app: AppHandle) -> Result<Vec<AiVocabularyEntry>, String> {
+    Ok(get_settings(&app).ai_vocabulary)
+}
+
+/// Add a vocabulary entry, replacing any entry with the same written form.
+/// Returns the updated vocabulary.
+#[tauri::command]
+#[specta::specta]
+pub fn add_ai_vocabulary_entry(
+    app: AppHandle,
+    entry: AiVocabularyEntry,
+) -> Result<Vec<AiVocabularyEntry>, String> {
+    let entry = normalize_vocabulary_entry(entry).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings
+        .ai_vocabulary
+        .retain(|existing| !same_written_form(&existing.written_form, &entry.written_form));
+    settings.ai_vocabulary.push(entry);
+    let vocabulary = settings.ai_vocabulary.clone();
+    write_settings(&app, settings);
+    Ok(vocabulary)
+}
+
+/// Remove the vocabulary entry for `written_form`. Returns the updated vocabulary.
+#[tauri::command]
+#[specta::specta]
+pub fn remove_ai_vocabulary_entry(
+    app: AppHandle,
+    written_form: String,
+) -> Result<Vec<AiVocabularyEntry>, String> {
+    let mut settings = get_settings(&app);
+    let before = settings.ai_vocabulary.len();
+    settings
+        .ai_vocabulary
+        .retain(|entry| !same_written_form(&entry.written_form, &written_form));
+    if settings.ai_vocabulary.len() == before {
+        return Err(format!("No vocabulary entry for {}", written_form));
+    }
+
+    let vocabulary = settings.ai_vocabulary.clone();
+    write_settings(&app, settings);
+    Ok(vocabulary)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_ai_replacement_rules(app: AppHandle) -> Result<Vec<AiReplacementRule>, String> {
+    Ok(get_settings(&app).ai_replacement_rules)
+}
+
+/// Replace the replacement rules, which apply in the order given, after
+/// checking each can be applied
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_replacement_rules(
+    app: AppHandle,
+    rules: Vec<AiReplacementRule>,
+) -> Result<(), String> {
+    validate_replacement_rules(&rules).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings.ai_replacement_rules = rules;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// What the saved replacement rules make of `text` before and after the
+/// model, without running it
+#[tauri::command]
+#[specta::specta]
+pub fn test_ai_replacement_rules(
+    app: AppHandle,
+    text: String,
+) -> Result<AiReplacementRulesPreview, String> {
+    Ok(preview_replacement_rules(
+        &text,
+        &get_settings(&app).ai_replacement_rules,
+    ))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_ai_snippets(app: AppHandle) -> Result<Vec<AiSnippet>, String> {
+    Ok(get_settings(&app).ai_snippets)
+}
+
+/// Add a snippet, replacing any with the same trigger. Returns the updated
+/// snippets.
+#[tauri::command]
+#[specta::specta]
+pub fn add_ai_snippet(app: AppHandle, snippet: AiSnippet) -> Result<Vec<AiSnippet>, String> {
+    let snippet = normalize_snippet(snippet).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings
+        .ai_snippets
+        .retain(|existing| !same_trigger(&existing.trigger, &snippet.trigger));
+    settings.ai_snippets.push(snippet);
+    let snippets = settings.ai_snippets.clone();
+    write_settings(&app, settings);
+    Ok(snippets)
+}
+
+/// Remove the snippet inserted by `trigger`. Returns the updated snippets.
+#[tauri::command]
+#[specta::specta]
+pub fn remove_ai_snippet(app: AppHandle, trigger: String) -> Result<Vec<AiSnippet>, String> {
+    let mut settings = get_settings(&app);
+    let before = settings.ai_snippets.len();
+    settings
+        .ai_snippets
+        .retain(|snippet| !same_trigger(&snippet.trigger, &trigger));
+    if settings.ai_snippets.len() == before {
+        return Err(format!("No snippet for {}", trigger));
+    }
+
+    let snippets = settings.ai_snippets.clone();
+    write_settings(&app, settings);
+    Ok(snippets)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_snippet_strictness(app: AppHandle, strictness: f64) -> Result<(), String> {
+    validate_snippet_strictness(strictness).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings.ai_snippet_strictness = strictness;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_ai_acronyms(app: AppHandle) -> Result<Vec<AiAcronym>, String> {
+    Ok(get_settings(&app).ai_acronyms)
+}
+
+/// Add an acronym, replacing any written the same way in another casing.
+/// Returns the updated acronyms.
+#[tauri::command]
+#[specta::specta]
+pub fn add_ai_acronym(app: AppHandle, written_form: String) -> Result<Vec<AiAcronym>, String> {
+    let written_form = normalize_acronym(&written_form).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings
+        .ai_acronyms
+        .retain(|existing| !same_acronym(&existing.written_form, &written_form));
+    settings.ai_acronyms.push(AiAcronym {
+        written_form,
+        enabled: true,
+    });
+    let acronyms = settings.ai_acronyms.clone();
+    write_settings(&app, settings);
+    Ok(acronyms)
+}
+
+/// Remove the acronym `written_form`. Returns the updated acronyms.
+#[tauri::command]
+#[specta::specta]
+pub fn remove_ai_acronym(app: AppHandle, written_form: String) -> Result<Vec<AiAcronym>, String> {
+    let mut settings = get_settings(&app);
+    let before = settings.ai_acronyms.len();
+    settings
+        .ai_acronyms
+        .retain(|acronym| !same_acronym(&acronym.written_form, &written_form));
+    if settings.ai_acronyms.len() == before {
+        return Err(format!("No acronym {}", written_form));
+    }
+
+    let acronyms = settings.ai_acronyms.clone();
+    write_settings(&app, settings);
+    Ok(acronyms)
+}
+
+/// Turn casing of the acronym `written_form` on or off, as needed for the
+/// ones that are also common words. Returns the updated acronyms.
+#[tauri::command]
+#[specta::specta]
+pub fn set_ai_acronym_enabled(
+    app: AppHandle,
+    written_form: String,
+    enabled: bool,
+) -> Result<Vec<AiAcronym>, String> {
+    let mut settings = get_settings(&app);
+    let acronym = settings
+        .ai_acronyms
+        .iter_mut()
+        .find(|acronym| same_acronym(&acronym.written_form, &written_form))
+        .ok_or_else(|| format!("No acronym {}", written_form))?;
+    acronym.enabled = enabled;
+
+    let acronyms = settings.ai_acronyms.clone();
+    write_settings(&app, settings);
+    Ok(acronyms)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_ai_proper_nouns(app: AppHandle) -> Result<Vec<AiProperNoun>, String> {
+    Ok(get_settings(&app).ai_proper_nouns)
+}
+
+/// Add a name, replacing the entry for the same name. Returns the updated
+/// names.
+#[tauri::command]
+#[specta::specta]
+pub fn add_ai_proper_noun(
+    app: AppHandle,
+    entry: AiProperNoun,
+) -> Result<Vec<AiProperNoun>, String> {
+    let entry = normalize_proper_noun(entry).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings
+        .ai_proper_nouns
+        .retain(|existing| !same_name(&existing.name, &entry.name));
+    settings.ai_proper_nouns.push(entry);
+    let nouns = settings.ai_proper_nouns.clone();
+    write_settings(&app, settings);
+    Ok(nouns)
+}
+
+/// Remove the entry for `name`. Returns the updated names.
+#[tauri::command]
+#[specta::specta]
+pub fn remove_ai_proper_noun(app: AppHandle, name: String) -> Result<Vec<AiProperNoun>, String> {
+    let mut settings = get_settings(&app);
+    let before = settings.ai_proper_nouns.len();
+    settings
+        .ai_proper_nouns
+        .retain(|entry| !same_name(&entry.name, &name));
+    if settings.ai_proper_nouns.len() == before {
+        return Err(format!("No entry for the name {}", name));
+    }
+
+    let nouns = settings.ai_proper_nouns.clone();
+    write_settings(&app, settings);
+    Ok(nouns)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_ai_test_samples(app: AppHandle) -> Result<Vec<AiTestSample>, String> {
+    Ok(get_settings(&app).ai_test_samples)
+}
+
+/// Add a test sample, replacing the one with the same name. Returns the
+/// updated samples.
+#[tauri::command]
+#[specta::specta]
+pub fn add_ai_test_sample(
+    app: AppHandle,
+    sample: AiTestSample,
+) -> Result<Vec<AiTestSample>, String> {
+    let sample = normalize_test_sample(sample).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings
+        .ai_test_samples
+        .retain(|existing| existing.name != sample.name);
+    settings.ai_test_samples.push(sample);
+    let samples = settings.ai_test_samples.clone();
+    write_settings(&app, settings);
+    Ok(samples)
+}
+
+/// Remove the test sample named `name`. Returns the updated samples.
+#[tauri::command]
+#[specta::specta]
+pub fn remove_ai_test_sample(app: AppHandle, name: String) -> Result<Vec<AiTestSample>, String> {
+    let mut settings = get_settings(&app);
+    let before = settings.ai_test_samples.len();
+    settings
+        .ai_test_samples
+        .retain(|sample| sample.name != name);
+    if settings.ai_test_samples.len() == before {
+        return Err(format!("No test sample named {}", name));
+    }
+
+    let samples = settings.ai_test_samples.clone();
+    write_settings(&app, settings);
+    Ok(samples)
+}
+
+/// Set the languages dictated in, as ISO 639-3 codes like "eng" or "deu"
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_languages(app: AppHandle, languages: Vec<String>) -> Result<(), String> {
+    let mut codes: Vec<String> = Vec::new();
+    for language in languages {
+        let code = language.trim().to_lowercase();
+        validate_language_code(&code).map_err(|e| e.to_string())?;
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+
+    let mut settings = get_settings(&app);
+    settings.ai_languages = codes;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_language_policy(app: AppHandle, policy: AiLanguagePolicy) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_language_policy = policy;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_summary_style(app: AppHandle, style: AiSummaryStyle) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_summary_style = style;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_summary_max_words(app: AppHandle, max_words: u32) -> Result<(), String> {
+    validate_summary_max_words(max_words).map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings.ai_summary_max_words = max_words;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_gpu_offload(app: AppHandle, offload: AiGpuOffload) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_gpu_offload = offload;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_main_gpu(app: AppHandle, gpu: Option<u32>) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_main_gpu = gpu;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn change_ai_ollama_endpoints(
+    app: AppHandle,
+    ollama_endpoints: State<'_, SharedOllamaEndpoints>,
+    endpoints: Vec<AiOllamaEndpoint>,
+) -> Result<(), String> {
+    let endpoints: Vec<AiOllamaEndpoint> = endpoints
+        .into_iter()
+        .map(|endpoint| AiOllamaEndpoint {
+            base_url: endpoint.base_url.trim().trim_end_matches('/').to_string(),
+            preferred_model: endpoint
+                .preferred_model
+                .map(|m| m.trim().to_string())
+                .filter(|m| !m.is_empty()),
+        })
+        .collect();
+
+    ollama_endpoints
+        .set(&endpoints)
+        .map_err(|e| e.to_string())?;
+
+    let mut settings = get_settings(&app);
+    settings.ai_ollama_endpoints = endpoints;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_model_registry_prefix(app: AppHandle, prefix: Option<String>) -> Result<(), String> {
+    let prefix = match prefix {
+        Some(prefix) => normalize_registry_prefix(&prefix).map_err(|e| e.to_string())?,
+        None => None,
+    };
+
+    let mut settings = get_settings(&app);
+    settings.ai_model_registry_prefix = prefix;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_ai_model_registry_insecure(app: AppHandle, insecure: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.ai_model_registry_insecure = insecure;
+    write_settings(&app, settings);
+    Ok(())
+}
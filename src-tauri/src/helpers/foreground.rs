@@ -0,0 +1,66 @@
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::process::Command;
+
+/// Identifies the application in the foreground, which is where dictated
+/// text gets pasted. Only meant for telling whether it changed: the value is
+/// opaque and differs between platforms. `None` when it can't be told.
+#[cfg(target_os = "macos")]
+pub fn foreground_app() -> Option<String> {
+    let output = Command::new("lsappinfo").arg("front").output().ok()?;
+    non_empty(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "linux")]
+pub fn foreground_app() -> Option<String> {
+    // Needs xdotool, and an X11 session or XWayland window
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowpid"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    non_empty(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "windows")]
+pub fn foreground_app() -> Option<String> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    // SAFETY: both calls only read window state, and `process_id` is a valid,
+    // writable u32
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_invalid() {
+            return None;
+        }
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(window, Some(&mut process_id));
+        (process_id != 0).then(|| process_id.to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+pub fn foreground_app() -> Option<String> {
+    None
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux", test))]
+fn non_empty(output: &str) -> Option<String> {
+    let output = output.trim();
+    (!output.is_empty()).then(|| output.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_output_is_trimmed() {
+        assert_eq!(
+            non_empty("ASN:0x0-0x1f01f:\n"),
+            Some("ASN:0x0-0x1f01f:".to_string())
+        );
+        assert_eq!(non_empty(" \n"), None);
+    }
+}
@@ -1 +1,3 @@
 pub mod clamshell;
+pub mod foreground;
+pub mod power;
@@ -0,0 +1,100 @@
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+/// Checks if the computer is running on battery power
+///
+/// Desktops, and platforms where the power source can't be read, count as
+/// plugged in.
+#[cfg(target_os = "macos")]
+pub fn on_battery() -> bool {
+    match Command::new("pmset").args(["-g", "batt"]).output() {
+        Ok(output) => pmset_on_battery(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn on_battery() -> bool {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+
+    let supplies: Vec<(String, bool)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let kind = std::fs::read_to_string(path.join("type")).ok()?;
+            let online = std::fs::read_to_string(path.join("online"))
+                .map(|online| online.trim() == "1")
+                .unwrap_or(false);
+            Some((kind.trim().to_string(), online))
+        })
+        .collect();
+    supplies_on_battery(&supplies)
+}
+
+#[cfg(target_os = "windows")]
+pub fn on_battery() -> bool {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // SAFETY: `status` is a valid, writable SYSTEM_POWER_STATUS
+    match unsafe { GetSystemPowerStatus(&mut status) } {
+        // 0 is offline, 1 online and 255 unknown
+        Ok(()) => status.ACLineStatus == 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+pub fn on_battery() -> bool {
+    false
+}
+
+/// Reads the power source from `pmset -g batt`, whose first line is like
+/// "Now drawing from 'Battery Power'"
+#[cfg(any(target_os = "macos", test))]
+fn pmset_on_battery(output: &str) -> bool {
+    output.contains("'Battery Power'")
+}
+
+/// Decides from the `(type, online)` of each power supply. On battery means
+/// there is a battery and no mains or USB supply is online.
+#[cfg(any(target_os = "linux", test))]
+fn supplies_on_battery(supplies: &[(String, bool)]) -> bool {
+    let has_battery = supplies.iter().any(|(kind, _)| kind == "Battery");
+    let plugged_in = supplies
+        .iter()
+        .any(|(kind, online)| kind != "Battery" && *online);
+    has_battery && !plugged_in
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pmset_power_source() {
+        let battery = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t80%; discharging; 4:01 remaining present: true\n";
+        let plugged = "Now drawing from 'AC Power'\n -InternalBattery-0 (id=1234)\t100%; charged; 0:00 remaining present: true\n";
+        assert!(pmset_on_battery(battery));
+        assert!(!pmset_on_battery(plugged));
+    }
+
+    #[test]
+    fn test_power_supplies() {
+        let supply = |kind: &str, online| (kind.to_string(), online);
+
+        assert!(supplies_on_battery(&[
+            supply("Mains", false),
+            supply("Battery", false)
+        ]));
+        assert!(!supplies_on_battery(&[
+            supply("Mains", true),
+            supply("Battery", false)
+        ]));
+        // Desktops have no battery at all
+        assert!(!supplies_on_battery(&[supply("Mains", true)]));
+        assert!(!supplies_on_battery(&[]));
+    }
+}
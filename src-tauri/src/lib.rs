@@ -19,7 +19,9 @@ use specta_typescript::{BigIntExportBehavior, Typescript};
 use tauri_specta::{collect_commands, Builder};
 
 use env_filter::Builder as EnvFilterBuilder;
-use managers::ai_enhancement::AiEnhancementManager;
+use managers::ai_enhancement::{AiEnhancementManager, SharedEnhancementDedup};
+use managers::ai_enhancement_queue::{EnhancementQueue, SharedEnhancementQueue};
+use managers::ai_request_dedup::RequestDedup;
 use managers::audio::AudioRecordingManager;
 use managers::history::HistoryManager;
 use managers::model::ModelManager;
@@ -120,7 +122,28 @@ fn initialize_core_logic(app_handle: &AppHandle) {
         TranscriptionManager::new(app_handle, model_manager.clone())
             .expect("Failed to initialize transcription manager"),
     );
-    let ai_manager = Arc::new(tokio::sync::Mutex::new(AiEnhancementManager::new()));
+    let ai_manager = AiEnhancementManager::new(app_handle);
+    let recent_transcripts = ai_manager.recent_transcripts();
+    let session_context = ai_manager.session_context();
+    let in_flight_enhancement = ai_manager.in_flight();
+    let ollama_endpoints = ai_manager.endpoints();
+    let model_pulls = ai_manager.pulls();
+    let model_benchmarks = ai_manager.benchmarks();
+    let enhancement_ratings = ai_manager.ratings();
+    let model_degradation = ai_manager.degradation();
+    let model_usage = ai_manager.model_usage();
+    let enhancement_metrics = ai_manager.metrics();
+    let enhancement_history = ai_manager.history();
+    let usage_stats = ai_manager.usage_stats();
+    let startup_preload = ai_manager.startup_preload();
+    let idle_model = ai_manager.idle_model();
+    let circuit_breaker = ai_manager.circuit_breaker();
+    let latency_profiles = ai_manager.latency_profiles();
+    let refinement = ai_manager.refinement();
+    ai_manager.watch_idle_model();
+    let ai_manager = Arc::new(tokio::sync::Mutex::new(ai_manager));
+    let enhancement_queue: SharedEnhancementQueue = Arc::new(EnhancementQueue::new());
+    let enhancement_dedup: SharedEnhancementDedup = Arc::new(RequestDedup::new());
     let history_manager =
         Arc::new(HistoryManager::new(app_handle).expect("Failed to initialize history manager"));
 
@@ -130,6 +153,32 @@ fn initialize_core_logic(app_handle: &AppHandle) {
     app_handle.manage(transcription_manager.clone());
     app_handle.manage(history_manager.clone());
     app_handle.manage(ai_manager.clone());
+    app_handle.manage(recent_transcripts);
+    app_handle.manage(session_context);
+    app_handle.manage(in_flight_enhancement);
+    app_handle.manage(ollama_endpoints);
+    app_handle.manage(model_pulls);
+    app_handle.manage(model_benchmarks);
+    app_handle.manage(enhancement_ratings);
+    app_handle.manage(model_degradation);
+    app_handle.manage(model_usage);
+    app_handle.manage(enhancement_metrics);
+    app_handle.manage(enhancement_history);
+    app_handle.manage(usage_stats);
+    app_handle.manage(startup_preload);
+    app_handle.manage(idle_model);
+    app_handle.manage(circuit_breaker);
+    app_handle.manage(latency_profiles);
+    app_handle.manage(refinement);
+    app_handle.manage(enhancement_queue);
+    app_handle.manage(enhancement_dedup);
+
+    // Offer to resume model downloads interrupted by the last shutdown
+    managers::ai_enhancement::announce_resumable_pulls(app_handle);
+    // Catch a selected model that has gone missing, e.g. after reinstalling Ollama
+    managers::ai_enhancement::verify_selected_model(app_handle);
+    managers::ai_enhancement::announce_model_updates(app_handle);
+    managers::ai_enhancement::preload_selected_model(app_handle);
 
     // Initialize the shortcuts
     shortcut::init_shortcuts(app_handle);
@@ -311,13 +360,125 @@ pub fn run() {
         commands::ai_enhancement::get_recommended_ai_model,
         commands::ai_enhancement::get_available_ai_models,
         commands::ai_enhancement::check_ollama_available,
+        commands::ai_enhancement::get_ollama_version,
         commands::ai_enhancement::list_ollama_models,
+        commands::ai_enhancement::list_ollama_running_models,
         commands::ai_enhancement::pull_ollama_model,
         commands::ai_enhancement::delete_ollama_model,
+        commands::ai_enhancement::get_unused_ai_models,
+        commands::ai_enhancement::delete_unused_ai_models,
         commands::ai_enhancement::test_ai_enhancement,
+        commands::ai_enhancement::preview_ai_prompt,
+        commands::ai_enhancement::compare_ai_models,
+        commands::ai_enhancement::reenhance_last,
+        commands::ai_enhancement::run_ai_enhancement_test_suite,
         commands::ai_enhancement::change_ai_enhancement_enabled,
         commands::ai_enhancement::change_ai_model,
+        commands::ai_enhancement::recheck_ai_model_status,
         commands::ai_enhancement::change_ai_features,
+        commands::ai_enhancement::change_ai_capitalization_style,
+        commands::ai_enhancement::get_ai_filler_words,
+        commands::ai_enhancement::set_ai_filler_words,
+        commands::ai_enhancement::list_ai_spoken_commands,
+        commands::ai_enhancement::add_ai_spoken_command,
+        commands::ai_enhancement::remove_ai_spoken_command,
+        commands::ai_enhancement::list_ai_spoken_emoji,
+        commands::ai_enhancement::add_ai_spoken_emoji,
+        commands::ai_enhancement::remove_ai_spoken_emoji,
+        commands::ai_enhancement::toggle_ai_translation,
+        commands::ai_enhancement::change_ai_pull_stall_timeout,
+        commands::ai_enhancement::change_ai_pull_auto_retry,
+        commands::ai_enhancement::change_ai_auto_pull_missing_model,
+        commands::ai_enhancement::change_ai_check_model_updates,
+        commands::ai_enhancement::change_ai_num_thread,
+        commands::ai_enhancement::change_ai_gpu_offload,
+        commands::ai_enhancement::change_ai_main_gpu,
+        commands::ai_enhancement::change_ai_ollama_endpoints,
+        commands::ai_enhancement::get_ai_pull_queue,
+        commands::ai_enhancement::cancel_queued_pull,
+        commands::ai_enhancement::get_active_model_pulls,
+        commands::ai_enhancement::get_resumable_model_pulls,
+        commands::ai_enhancement::resume_model_pull,
+        commands::ai_enhancement::dismiss_model_pull,
+        commands::ai_enhancement::check_ai_model_updates,
+        commands::ai_enhancement::update_ai_model,
+        commands::ai_enhancement::change_ai_model_registry_prefix,
+        commands::ai_enhancement::change_ai_model_registry_insecure,
+        commands::ai_enhancement::import_gguf_model,
+        commands::ai_enhancement::get_ollama_storage_info,
+        commands::ai_enhancement::change_ai_chunk_max_tokens,
+        commands::ai_enhancement::change_ai_fallback_to_original,
+        commands::ai_enhancement::change_ai_offline_fallback,
+        commands::ai_enhancement::change_ai_enhancement_timeout_ms,
+        commands::ai_enhancement::change_ai_degrade_after_failures,
+        commands::ai_enhancement::change_ai_latency_budget_ms,
+        commands::ai_enhancement::change_ai_two_pass,
+        commands::ai_enhancement::change_ai_reenhance,
+        commands::ai_enhancement::reset_ai_model_degradation,
+        commands::ai_enhancement::change_ai_min_words_for_enhancement,
+        commands::ai_enhancement::change_ai_max_words_for_enhancement,
+        commands::ai_enhancement::change_ai_long_text_policy,
+        commands::ai_enhancement::change_ai_warm_up_on_battery,
+        commands::ai_enhancement::change_ai_preload_on_startup,
+        commands::ai_enhancement::change_ai_preload_keep_alive,
+        commands::ai_enhancement::change_ai_idle_unload_minutes,
+        commands::ai_enhancement::change_ai_cache_enabled,
+        commands::ai_enhancement::change_ai_cache_size,
+        commands::ai_enhancement::clear_ai_enhancement_cache,
+        commands::ai_enhancement::get_ai_enhancement_cache_stats,
+        commands::ai_enhancement::get_ai_enhancement_metrics,
+        commands::ai_enhancement::reset_ai_enhancement_metrics,
+        commands::ai_enhancement::get_ai_usage_stats,
+        commands::ai_enhancement::clear_ai_usage_stats,
+        commands::ai_enhancement::benchmark_ai_model,
+        commands::ai_enhancement::benchmark_all_installed_models,
+        commands::ai_enhancement::get_ai_benchmarks,
+        commands::ai_enhancement::change_ai_history_enabled,
+        commands::ai_enhancement::change_ai_withhold_original_from_events,
+        commands::ai_enhancement::change_ai_usage_stats_enabled,
+        commands::ai_enhancement::change_ai_queue_policy,
+        commands::ai_enhancement::change_ai_context_enabled,
+        commands::ai_enhancement::change_ai_context_utterances,
+        commands::ai_enhancement::change_ai_context_idle_secs,
+        commands::ai_enhancement::clear_ai_context,
+        commands::ai_enhancement::get_enhancement_history,
+        commands::ai_enhancement::delete_enhancement_history_entry,
+        commands::ai_enhancement::rate_last_enhancement,
+        commands::ai_enhancement::rate_enhancement,
+        commands::ai_enhancement::get_last_original_transcript,
+        commands::ai_enhancement::undo_last_enhancement,
+        commands::ai_enhancement::abort_ai_enhancement,
+        commands::ai_enhancement::change_ai_output_guard,
+        commands::ai_enhancement::change_ai_generation_options,
+        commands::ai_enhancement::get_default_ai_generation_options,
+        commands::ai_enhancement::change_ai_custom_prompt,
+        commands::ai_enhancement::reset_ai_prompt_template,
+        commands::ai_enhancement::change_ai_prompt_all_instructions,
+        commands::ai_enhancement::change_ai_examples,
+        commands::ai_enhancement::list_ai_vocabulary,
+        commands::ai_enhancement::add_ai_vocabulary_entry,
+        commands::ai_enhancement::remove_ai_vocabulary_entry,
+        commands::ai_enhancement::list_ai_replacement_rules,
+        commands::ai_enhancement::change_ai_replacement_rules,
+        commands::ai_enhancement::test_ai_replacement_rules,
+        commands::ai_enhancement::list_ai_snippets,
+        commands::ai_enhancement::add_ai_snippet,
+        commands::ai_enhancement::remove_ai_snippet,
+        commands::ai_enhancement::change_ai_snippet_strictness,
+        commands::ai_enhancement::change_ai_languages,
+        commands::ai_enhancement::change_ai_language_policy,
+        commands::ai_enhancement::change_ai_summary_style,
+        commands::ai_enhancement::change_ai_summary_max_words,
+        commands::ai_enhancement::list_ai_acronyms,
+        commands::ai_enhancement::add_ai_acronym,
+        commands::ai_enhancement::remove_ai_acronym,
+        commands::ai_enhancement::set_ai_acronym_enabled,
+        commands::ai_enhancement::list_ai_proper_nouns,
+        commands::ai_enhancement::add_ai_proper_noun,
+        commands::ai_enhancement::remove_ai_proper_noun,
+        commands::ai_enhancement::list_ai_test_samples,
+        commands::ai_enhancement::add_ai_test_sample,
+        commands::ai_enhancement::remove_ai_test_sample,
     ]);
 
     #[cfg(debug_assertions)] // <- Only export on non-release builds
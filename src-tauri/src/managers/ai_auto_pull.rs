@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Failed automatic pulls of a model after which it is no longer pulled
+/// automatically, until Handy restarts
+const MAX_AUTO_PULL_FAILURES: u32 = 3;
+
+/// Minimum time between automatic pulls of a model whose last one failed
+const AUTO_PULL_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Sent when the selected model turned out to be missing and is being
+/// downloaded automatically. Its progress is reported through the
+/// `ai-model-pull-*` events for `pull_id`; transcripts keep their original
+/// text until it completes.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelAutoPull {
+    pub pull_id: String,
+    pub model_id: String,
+}
+
+struct AutoPull {
+    pulling: bool,
+    failures: u32,
+    last_attempt: Instant,
+}
+
+/// Automatic downloads of missing models, kept so a model whose download
+/// keeps failing isn't pulled again for every dictation
+#[derive(Default)]
+pub struct AutoPulls {
+    models: HashMap<String, AutoPull>,
+}
+
+impl AutoPulls {
+    /// Record an automatic pull of `model_id` starting at `now`, unless one
+    /// is running, failed too recently, or failed too often
+    pub fn try_start(&mut self, model_id: &str, now: Instant) -> bool {
+        if let Some(pull) = self.models.get(model_id) {
+            if pull.pulling
+                || pull.failures >= MAX_AUTO_PULL_FAILURES
+                || now.duration_since(pull.last_attempt) < AUTO_PULL_RETRY_DELAY
+            {
+                return false;
+            }
+        }
+
+        let pull = self.models.entry(model_id.to_string()).or_insert(AutoPull {
+            pulling: false,
+            failures: 0,
+            last_attempt: now,
+        });
+        pull.pulling = true;
+        pull.last_attempt = now;
+        true
+    }
+
+    /// Whether `model_id` is being downloaded automatically
+    pub fn is_pulling(&self, model_id: &str) -> bool {
+        self.models.get(model_id).is_some_and(|pull| pull.pulling)
+    }
+
+    /// Record how a download of `model_id` ended. Downloads that weren't
+    /// started automatically are ignored.
+    pub fn finish(&mut self, model_id: &str, succeeded: bool) {
+        if succeeded {
+            self.models.remove(model_id);
+        } else if let Some(pull) = self.models.get_mut(model_id) {
+            pull.pulling = false;
+            pull.failures += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_pull_at_a_time_until_it_succeeds() {
+        let mut pulls = AutoPulls::default();
+        let now = Instant::now();
+
+        assert!(pulls.try_start("llama3.2:1b", now));
+        assert!(pulls.is_pulling("llama3.2:1b"));
+        assert!(!pulls.try_start("llama3.2:1b", now + AUTO_PULL_RETRY_DELAY));
+
+        pulls.finish("llama3.2:1b", true);
+        assert!(!pulls.is_pulling("llama3.2:1b"));
+        // Deleted again later, so it is pulled again straight away
+        assert!(pulls.try_start("llama3.2:1b", now));
+    }
+
+    #[test]
+    fn test_failing_pulls_back_off_and_give_up() {
+        let mut pulls = AutoPulls::default();
+        let mut now = Instant::now();
+
+        for _ in 0..MAX_AUTO_PULL_FAILURES {
+            assert!(pulls.try_start("llama3.2:1b", now));
+            pulls.finish("llama3.2:1b", false);
+            assert!(!pulls.try_start("llama3.2:1b", now + Duration::from_secs(1)));
+            now += AUTO_PULL_RETRY_DELAY;
+        }
+
+        assert!(!pulls.try_start("llama3.2:1b", now + AUTO_PULL_RETRY_DELAY * 10));
+        // Other models are unaffected
+        assert!(pulls.try_start("gemma2:2b", now));
+    }
+
+    #[test]
+    fn test_ignores_pulls_it_did_not_start() {
+        let mut pulls = AutoPulls::default();
+        pulls.finish("llama3.2:1b", false);
+        assert!(pulls.try_start("llama3.2:1b", Instant::now()));
+    }
+}
@@ -0,0 +1,417 @@
+use crate::ai_toolkit::hardware_fingerprint;
+use crate::ai_toolkit::model_registry::qualify_model_id;
+use crate::ai_toolkit::ollama_client::{GenerateStats, OllamaClient, OllamaError, OllamaOptions};
+use crate::ai_toolkit::output_budget::{output_budget, DEFAULT_CONTEXT_TOKENS};
+use crate::ai_toolkit::prompt_template::build_prompt;
+use crate::ai_toolkit::transport::OllamaTransport;
+use crate::managers::ai_endpoints::SharedOllamaEndpoints;
+use crate::managers::ai_enhancement::{
+    forget_latency_profiles, hardware_options, list_models, select_endpoint,
+};
+use crate::managers::ai_enhancement_queue::{take_turn, EnhancementQueue};
+use crate::managers::ai_model_usage::SharedModelUsage;
+use crate::managers::json_store::JsonStore;
+use crate::settings::{get_settings, AiFeatures};
+use anyhow::Result;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Transcripts every model is benchmarked on, raw the way dictation leaves
+/// them and from a few words to a long ramble
+const BENCHMARK_TRANSCRIPTS: [&str; 4] = [
+    "remind me to call the dentist tomorrow morning",
+    "so um i think we should move the meeting to thursday because most of the team is out on wednesday",
+    "can you send me the the report from last week i need to check the numbers before the call with the client and i also want to see the the notes from the review",
+    "the new version fixes the crash on start up but theres still a problem with how settings get saved when you close the window right after changing them so maybe we could save them straight away instead of waiting for the window to close which is what it does now and i think thats also why the theme sometimes goes back to the old one",
+];
+
+/// How one benchmark transcript went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiBenchmarkPromptResult {
+    /// Time Ollama spent loading the model first, close to none when it
+    /// already was
+    pub load_ms: f64,
+    pub output_tokens: u32,
+    pub tokens_per_second: f64,
+    /// From sending the prompt to having all of the output
+    pub latency_ms: f64,
+}
+
+/// How fast a model corrects transcripts on this machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiBenchmarkReport {
+    pub model: String,
+    pub app_version: String,
+    /// The machine it was measured on, as given by [`hardware_fingerprint`]
+    pub hardware: String,
+    /// Unix timestamp (seconds) of when it was measured
+    pub measured_at: i64,
+    pub prompts: Vec<AiBenchmarkPromptResult>,
+    /// Loading time before the first transcript, when the model is loaded if
+    /// it wasn't already
+    pub load_ms: f64,
+    /// Generation speed over the output for every transcript
+    pub tokens_per_second: f64,
+    pub average_latency_ms: f64,
+}
+
+/// Sent before each model is benchmarked by `benchmark_all_installed_models`
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiBenchmarkProgress {
+    pub model: String,
+    /// 1-based position of the model among those being benchmarked
+    pub index: u32,
+    pub total: u32,
+}
+
+/// Benchmark reports persisted as JSON in the app data directory, one per
+/// model for the app version and hardware they were measured with
+pub struct BenchmarkCache {
+    reports: JsonStore<Vec<AiBenchmarkReport>>,
+}
+
+impl BenchmarkCache {
+    /// Load reports from `path`. A missing or unreadable file starts empty.
+    pub fn load(path: PathBuf) -> Self {
+        Self {
+            reports: JsonStore::load(path, "model benchmarks"),
+        }
+    }
+
+    /// Reports kept in memory only, used when the app data dir is unavailable
+    pub fn in_memory() -> Self {
+        Self {
+            reports: JsonStore::in_memory(),
+        }
+    }
+
+    /// Reports measured with `app_version` on `hardware`
+    pub fn reports(&self, app_version: &str, hardware: &str) -> Vec<AiBenchmarkReport> {
+        self.reports
+            .iter()
+            .filter(|report| report.app_version == app_version && report.hardware == hardware)
+            .cloned()
+            .collect()
+    }
+
+    /// Add `report`, replacing the model's last one. Reports from another app
+    /// version or other hardware are dropped, as they are never shown again.
+    pub fn insert(&mut self, report: AiBenchmarkReport) {
+        self.reports.retain(|kept| {
+            kept.app_version == report.app_version
+                && kept.hardware == report.hardware
+                && kept.model != report.model
+        });
+        self.reports.push(report);
+    }
+
+    pub fn save(&self) {
+        self.reports.save();
+    }
+}
+
+/// Benchmark state shared with the benchmark commands, so a benchmark never
+/// holds the manager lock
+#[derive(Clone)]
+pub struct BenchmarkContext {
+    app: AppHandle,
+    endpoints: SharedOllamaEndpoints,
+    auto_num_thread: u32,
+    /// This machine's [`hardware_fingerprint`], taken once at startup
+    hardware: String,
+    cache: Arc<Mutex<BenchmarkCache>>,
+    /// Benchmarks count as using the model, like enhancements
+    usage: SharedModelUsage,
+}
+
+impl BenchmarkContext {
+    pub fn new(
+        app: &AppHandle,
+        endpoints: SharedOllamaEndpoints,
+        auto_num_thread: u32,
+        cache: BenchmarkCache,
+        usage: SharedModelUsage,
+    ) -> Self {
+        Self {
+            app: app.clone(),
+            endpoints,
+            auto_num_thread,
+            hardware: hardware_fingerprint(),
+            cache: Arc::new(Mutex::new(cache)),
+            usage,
+        }
+    }
+
+    fn app_version(&self) -> String {
+        self.app.package_info().version.to_string()
+    }
+
+    /// Reports measured with this version of Handy on this machine
+    pub fn reports(&self) -> Vec<AiBenchmarkReport> {
+        self.cache
+            .lock()
+            .unwrap()
+            .reports(&self.app_version(), &self.hardware)
+    }
+
+    /// Run the benchmark transcripts through `model` and keep the report.
+    /// Each transcript waits its turn in `queue`, so dictations are never
+    /// held up by more than one of them.
+    pub async fn run(&self, queue: &EnhancementQueue, model: &str) -> Result<AiBenchmarkReport> {
+        let Some(endpoint) = select_endpoint(&self.app, &self.endpoints).await else {
+            return Err(OllamaError::Unavailable.into());
+        };
+        let settings = get_settings(&self.app);
+        let registry_model = qualify_model_id(model, settings.ai_model_registry_prefix.as_deref());
+        let sampling = settings.ai_generation_options.correct;
+        let options = OllamaOptions {
+            temperature: sampling.temperature,
+            num_predict: sampling.max_output_tokens as i32,
+            repeat_penalty: Some(sampling.repeat_penalty),
+            ..hardware_options(&settings, self.auto_num_thread)
+        };
+
+        info!("Benchmarking {}", model);
+        let prompts = run_prompts(&endpoint.client, &registry_model, &options, queue).await?;
+        let now = chrono::Utc::now().timestamp();
+        self.usage.lock().unwrap().record(model, now);
+        let report = summarize(model, &self.app_version(), &self.hardware, now, prompts);
+        info!(
+            "{} generated {:.1} tokens/s, {:.0} ms per transcript",
+            model, report.tokens_per_second, report.average_latency_ms
+        );
+
+        let mut cache = self.cache.lock().unwrap();
+        cache.insert(report.clone());
+        cache.save();
+        forget_latency_profiles(&self.app);
+        Ok(report)
+    }
+
+    /// Benchmark every installed model one after another, emitting
+    /// `ai-benchmark-progress` before each. A model that fails is logged and
+    /// left out of the reports.
+    pub async fn run_all(&self, queue: &EnhancementQueue) -> Result<Vec<AiBenchmarkReport>> {
+        let Some(endpoint) = select_endpoint(&self.app, &self.endpoints).await else {
+            return Err(OllamaError::Unavailable.into());
+        };
+        let models = list_models(&self.app, &endpoint.client).await?;
+
+        let mut reports = Vec::new();
+        for (index, model) in models.iter().enumerate() {
+            let _ = self.app.emit(
+                "ai-benchmark-progress",
+                AiBenchmarkProgress {
+                    model: model.clone(),
+                    index: index as u32 + 1,
+                    total: models.len() as u32,
+                },
+            );
+            match self.run(queue, model).await {
+                Ok(report) => reports.push(report),
+                Err(e) => warn!("Failed to benchmark {}: {}", model, e),
+            }
+        }
+        Ok(reports)
+    }
+}
+
+async fn run_prompts<T: OllamaTransport>(
+    client: &OllamaClient<T>,
+    model: &str,
+    options: &OllamaOptions,
+    queue: &EnhancementQueue,
+) -> Result<Vec<AiBenchmarkPromptResult>> {
+    let mut results = Vec::new();
+    for transcript in BENCHMARK_TRANSCRIPTS {
+        let prompt = build_prompt(
+            transcript,
+            &AiFeatures::default(),
+            None,
+            None,
+            &[],
+            &[],
+            None,
+        );
+        let options = OllamaOptions {
+            num_predict: output_budget(transcript, &prompt, DEFAULT_CONTEXT_TOKENS)
+                .min(options.num_predict),
+            ..options.clone()
+        };
+
+        let slot = take_turn(queue).await;
+        let started = Instant::now();
+        let (_, stats) = client.generate_with_stats(model, &prompt, &options).await?;
+        let latency = started.elapsed();
+        drop(slot);
+
+        results.push(prompt_result(&stats, latency));
+    }
+    Ok(results)
+}
+
+fn prompt_result(stats: &GenerateStats, latency: Duration) -> AiBenchmarkPromptResult {
+    let output_tokens = stats.output_tokens.unwrap_or(0);
+    let generation_secs = stats.generation_nanos.unwrap_or(0) as f64 / 1_000_000_000.0;
+    AiBenchmarkPromptResult {
+        load_ms: stats.load_nanos.unwrap_or(0) as f64 / 1_000_000.0,
+        output_tokens: output_tokens as u32,
+        tokens_per_second: if generation_secs > 0.0 {
+            output_tokens as f64 / generation_secs
+        } else {
+            0.0
+        },
+        latency_ms: latency.as_secs_f64() * 1000.0,
+    }
+}
+
+fn summarize(
+    model: &str,
+    app_version: &str,
+    hardware: &str,
+    measured_at: i64,
+    prompts: Vec<AiBenchmarkPromptResult>,
+) -> AiBenchmarkReport {
+    let output_tokens: u32 = prompts.iter().map(|prompt| prompt.output_tokens).sum();
+    let generation_secs: f64 = prompts
+        .iter()
+        .filter(|prompt| prompt.tokens_per_second > 0.0)
+        .map(|prompt| prompt.output_tokens as f64 / prompt.tokens_per_second)
+        .sum();
+    let total_latency_ms: f64 = prompts.iter().map(|prompt| prompt.latency_ms).sum();
+
+    AiBenchmarkReport {
+        model: model.to_string(),
+        app_version: app_version.to_string(),
+        hardware: hardware.to_string(),
+        measured_at,
+        load_ms: prompts.first().map_or(0.0, |prompt| prompt.load_ms),
+        tokens_per_second: if generation_secs > 0.0 {
+            output_tokens as f64 / generation_secs
+        } else {
+            0.0
+        },
+        average_latency_ms: if prompts.is_empty() {
+            0.0
+        } else {
+            total_latency_ms / prompts.len() as f64
+        },
+        prompts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::mock_transport::{MockReply, MockTransport};
+    use crate::managers::test_fixtures::temp_path;
+    use crate::settings::AiQueuePolicy;
+    use std::fs;
+
+    const STATS: &str = r#"{"response":"Fixed.","done":true,"load_duration":1500000000,"eval_count":40,"eval_duration":500000000}"#;
+
+    fn prompt(
+        output_tokens: u32,
+        tokens_per_second: f64,
+        latency_ms: f64,
+    ) -> AiBenchmarkPromptResult {
+        AiBenchmarkPromptResult {
+            load_ms: 0.0,
+            output_tokens,
+            tokens_per_second,
+            latency_ms,
+        }
+    }
+
+    fn report(model: &str, app_version: &str) -> AiBenchmarkReport {
+        summarize(
+            model,
+            app_version,
+            "linux",
+            0,
+            vec![prompt(10, 20.0, 600.0)],
+        )
+    }
+
+    #[test]
+    fn test_measures_every_transcript() {
+        let client = OllamaClient::with_transport(MockTransport::new(
+            BENCHMARK_TRANSCRIPTS
+                .iter()
+                .map(|_| MockReply::Body(200, STATS))
+                .collect(),
+        ));
+        let queue = EnhancementQueue::new();
+
+        let results = tauri::async_runtime::block_on(run_prompts(
+            &client,
+            "gemma2:2b",
+            &OllamaOptions::default(),
+            &queue,
+        ))
+        .unwrap();
+
+        assert_eq!(results.len(), BENCHMARK_TRANSCRIPTS.len());
+        assert_eq!(results[0].load_ms, 1500.0);
+        assert_eq!(results[0].output_tokens, 40);
+        assert_eq!(results[0].tokens_per_second, 80.0);
+        // Every turn was given back
+        assert!(tauri::async_runtime::block_on(
+            queue.join(AiQueuePolicy::QueueAll).slot.turn()
+        ));
+    }
+
+    #[test]
+    fn test_summarizes_prompts() {
+        let mut first = prompt(40, 80.0, 1000.0);
+        first.load_ms = 1500.0;
+        let summary = summarize(
+            "gemma2:2b",
+            "0.5.0",
+            "linux",
+            0,
+            vec![first, prompt(20, 20.0, 2000.0), prompt(0, 0.0, 300.0)],
+        );
+
+        assert_eq!(summary.load_ms, 1500.0);
+        assert_eq!(summary.tokens_per_second, 40.0);
+        assert_eq!(summary.average_latency_ms, 1100.0);
+    }
+
+    #[test]
+    fn test_keeps_one_report_per_model_for_the_current_version() {
+        let mut cache = BenchmarkCache::in_memory();
+        cache.insert(report("gemma2:2b", "0.5.0"));
+        cache.insert(report("llama3.2:1b", "0.5.0"));
+        cache.insert(report("gemma2:2b", "0.5.0"));
+        assert_eq!(cache.reports("0.5.0", "linux").len(), 2);
+        assert!(cache.reports("0.5.0", "macos").is_empty());
+
+        cache.insert(report("gemma2:2b", "0.6.0"));
+        assert!(cache.reports("0.5.0", "linux").is_empty());
+        assert_eq!(cache.reports("0.6.0", "linux").len(), 1);
+    }
+
+    #[test]
+    fn test_reports_survive_reload() {
+        let path = temp_path("benchmarks");
+        let _ = fs::remove_file(&path);
+
+        let mut cache = BenchmarkCache::load(path.clone());
+        cache.insert(report("gemma2:2b", "0.5.0"));
+        cache.save();
+
+        let reloaded = BenchmarkCache::load(path.clone());
+        assert_eq!(
+            reloaded.reports("0.5.0", "linux"),
+            vec![report("gemma2:2b", "0.5.0")]
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Enhancements in a row that found no Ollama host before the circuit opens
+pub const CIRCUIT_BREAKER_THRESHOLD: u32 = 3;
+
+/// Wait before the first probe of an open circuit, doubled after each
+/// failed probe. Matches how long endpoint health checks are reused, so
+/// each probe asks the hosts again.
+const FIRST_PROBE_DELAY: Duration = Duration::from_secs(15);
+
+/// Longest wait between probes of an open circuit
+const MAX_PROBE_DELAY: Duration = Duration::from_secs(120);
+
+/// Sent as `ai-circuit-changed` when enhancements start or stop skipping
+/// straight to the original transcript because Ollama can't be reached
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiCircuitChanged {
+    /// Whether enhancement is skipped until Ollama is back
+    pub open: bool,
+    /// Enhancements in a row that found no Ollama host before it opened
+    pub failures: u32,
+}
+
+/// Stops asking an unreachable Ollama for enhancements, which would each
+/// wait out the connection timeout before falling back. While open, the
+/// hosts are probed in the background with growing waits in between.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    failures: u32,
+    /// When the next probe is due and the wait before it, while open
+    probe: Option<(Instant, Duration)>,
+}
+
+pub type SharedCircuitBreaker = Arc<Mutex<CircuitBreaker>>;
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CIRCUIT_BREAKER_THRESHOLD)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            failures: 0,
+            probe: None,
+        }
+    }
+
+    /// Whether enhancements should skip straight to the original transcript
+    pub fn is_open(&self) -> bool {
+        self.probe.is_some()
+    }
+
+    /// Count an enhancement that found no Ollama host at `now`. Returns the
+    /// failures in a row when this opens the circuit.
+    pub fn record_failure(&mut self, now: Instant) -> Option<u32> {
+        self.failures += 1;
+        if self.is_open() || self.failures < self.threshold {
+            return None;
+        }
+        self.probe = Some((now + FIRST_PROBE_DELAY, FIRST_PROBE_DELAY));
+        Some(self.failures)
+    }
+
+    /// Count a request that reached Ollama, or a probe that found it.
+    /// Returns `true` when this closes the circuit.
+    pub fn record_success(&mut self) -> bool {
+        self.failures = 0;
+        self.probe.take().is_some()
+    }
+
+    /// How long from `now` until the next probe is due, or `None` once the
+    /// circuit is closed
+    pub fn until_probe(&self, now: Instant) -> Option<Duration> {
+        self.probe
+            .map(|(due, _)| due.saturating_duration_since(now))
+    }
+
+    /// Put off the next probe after one found no host at `now`
+    pub fn probe_failed(&mut self, now: Instant) {
+        if let Some((_, delay)) = self.probe {
+            let delay = (delay * 2).min(MAX_PROBE_DELAY);
+            self.probe = Some((now + delay, delay));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::new(3);
+
+        assert_eq!(breaker.record_failure(now), None);
+        assert_eq!(breaker.record_failure(now), None);
+        // A request getting through starts the count over
+        assert!(!breaker.record_success());
+        assert_eq!(breaker.record_failure(now), None);
+        assert_eq!(breaker.record_failure(now), None);
+        assert!(!breaker.is_open());
+
+        assert_eq!(breaker.record_failure(now), Some(3));
+        assert!(breaker.is_open());
+        // Already open, so it isn't announced again
+        assert_eq!(breaker.record_failure(now), None);
+    }
+
+    #[test]
+    fn test_probes_back_off_until_ollama_is_back() {
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new(1);
+        assert_eq!(breaker.until_probe(start), None);
+
+        breaker.record_failure(start);
+        assert_eq!(breaker.until_probe(start), Some(FIRST_PROBE_DELAY));
+        let later = start + Duration::from_secs(5);
+        assert_eq!(breaker.until_probe(later), Some(Duration::from_secs(10)));
+
+        let mut now = start + FIRST_PROBE_DELAY;
+        assert_eq!(breaker.until_probe(now), Some(Duration::ZERO));
+        let mut delays = Vec::new();
+        for _ in 0..5 {
+            breaker.probe_failed(now);
+            let delay = breaker.until_probe(now).unwrap();
+            delays.push(delay.as_secs());
+            now += delay;
+        }
+        assert_eq!(delays, vec![30, 60, 120, 120, 120]);
+
+        assert!(breaker.record_success());
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.until_probe(now), None);
+        assert!(!breaker.record_success());
+    }
+}
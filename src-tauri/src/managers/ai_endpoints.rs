@@ -0,0 +1,321 @@
+use crate::ai_toolkit::ollama_client::{OllamaClient, OLLAMA_BASE_URL};
+use crate::ai_toolkit::transport::{HostTransport, OllamaTransport};
+use crate::settings::AiOllamaEndpoint;
+use anyhow::{anyhow, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long an endpoint health check result is reused before probing again
+const ENDPOINT_HEALTH_TTL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEndpointChanged {
+    pub base_url: Option<String>,
+    pub previous: Option<String>,
+}
+
+/// A configured Ollama host with its client
+pub struct OllamaEndpoint<T: OllamaTransport = HostTransport> {
+    pub config: AiOllamaEndpoint,
+    pub client: Arc<OllamaClient<T>>,
+}
+
+impl<T: OllamaTransport> Clone for OllamaEndpoint<T> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            client: self.client.clone(),
+        }
+    }
+}
+
+/// The endpoint chosen by [`OllamaEndpoints::select`], and the change of the
+/// active endpoint it made, if any
+pub struct EndpointSelection<T: OllamaTransport = HostTransport> {
+    pub endpoint: Option<OllamaEndpoint<T>>,
+    pub changed: Option<AiEndpointChanged>,
+}
+
+struct EndpointState<T: OllamaTransport> {
+    endpoint: OllamaEndpoint<T>,
+    last_health: Option<(bool, Instant)>,
+}
+
+struct Endpoints<T: OllamaTransport> {
+    /// In order of preference, never empty
+    endpoints: Vec<EndpointState<T>>,
+    active: Option<String>,
+}
+
+/// The configured Ollama hosts and which of them is in use. Locked only to
+/// read or update them and never across a request, so health checks and
+/// model listings don't wait for an enhancement or a download.
+pub struct OllamaEndpoints<T: OllamaTransport = HostTransport> {
+    state: Mutex<Endpoints<T>>,
+}
+
+pub type SharedOllamaEndpoints = Arc<OllamaEndpoints>;
+
+impl OllamaEndpoints {
+    pub fn new(configs: &[AiOllamaEndpoint]) -> Result<Self> {
+        Ok(Self::with_clients(build_endpoints(configs)?))
+    }
+
+    /// Just the Ollama running on this machine
+    pub fn local() -> Self {
+        let config = AiOllamaEndpoint {
+            base_url: OLLAMA_BASE_URL.to_string(),
+            preferred_model: None,
+        };
+        Self::with_clients(vec![(config, OllamaClient::new())])
+    }
+
+    /// Replace the configured endpoints
+    pub fn set(&self, configs: &[AiOllamaEndpoint]) -> Result<()> {
+        let endpoints = build_endpoints(configs)?;
+        self.state.lock().unwrap().endpoints = endpoints.into_iter().map(state).collect();
+        info!(
+            "Using Ollama endpoints: {}",
+            configs
+                .iter()
+                .map(|c| c.base_url.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        Ok(())
+    }
+}
+
+impl<T: OllamaTransport> OllamaEndpoints<T> {
+    /// Endpoints in order of preference. `clients` must not be empty.
+    pub fn with_clients(clients: Vec<(AiOllamaEndpoint, OllamaClient<T>)>) -> Self {
+        Self {
+            state: Mutex::new(Endpoints {
+                endpoints: clients.into_iter().map(state).collect(),
+                active: None,
+            }),
+        }
+    }
+
+    /// Look up a configured endpoint by base URL, or the active (else first)
+    /// one for `None`
+    pub fn endpoint(&self, base_url: Option<&str>) -> Result<OllamaEndpoint<T>> {
+        let state = self.state.lock().unwrap();
+        let found = match base_url {
+            Some(base_url) => state
+                .endpoints
+                .iter()
+                .find(|e| e.endpoint.config.base_url == base_url)
+                .ok_or_else(|| anyhow!("Unknown Ollama endpoint: {}", base_url))?,
+            None => {
+                let active = state.active.as_deref();
+                state
+                    .endpoints
+                    .iter()
+                    .find(|e| Some(e.endpoint.config.base_url.as_str()) == active)
+                    .unwrap_or(&state.endpoints[0])
+            }
+        };
+        Ok(found.endpoint.clone())
+    }
+
+    /// Client for the given endpoint, or the active (else first) one when unspecified
+    pub fn client_for(&self, base_url: Option<&str>) -> Result<Arc<OllamaClient<T>>> {
+        Ok(self.endpoint(base_url)?.client)
+    }
+
+    /// Find the first healthy endpoint in preference order, reusing recent
+    /// health checks. Hosts are probed without holding the lock.
+    pub async fn select(&self) -> EndpointSelection<T> {
+        let candidates: Vec<(OllamaEndpoint<T>, Option<bool>)> = {
+            let state = self.state.lock().unwrap();
+            state
+                .endpoints
+                .iter()
+                .map(|e| {
+                    let cached = match e.last_health {
+                        Some((healthy, checked_at))
+                            if checked_at.elapsed() < ENDPOINT_HEALTH_TTL =>
+                        {
+                            Some(healthy)
+                        }
+                        _ => None,
+                    };
+                    (e.endpoint.clone(), cached)
+                })
+                .collect()
+        };
+
+        let mut selected = None;
+        for (endpoint, cached) in candidates {
+            let healthy = match cached {
+                Some(healthy) => healthy,
+                None => {
+                    let healthy = endpoint.client.is_available().await;
+                    self.record_health(&endpoint, Some((healthy, Instant::now())));
+                    healthy
+                }
+            };
+
+            if healthy {
+                selected = Some(endpoint);
+                break;
+            }
+        }
+
+        let base_url = selected.as_ref().map(|e| e.config.base_url.clone());
+        let mut state = self.state.lock().unwrap();
+        let mut changed = None;
+        if base_url != state.active {
+            info!(
+                "Active Ollama endpoint changed from {:?} to {:?}",
+                state.active, base_url
+            );
+            changed = Some(AiEndpointChanged {
+                base_url: base_url.clone(),
+                previous: std::mem::replace(&mut state.active, base_url),
+            });
+        }
+
+        EndpointSelection {
+            endpoint: selected,
+            changed,
+        }
+    }
+
+    /// Re-check this host before it is used again instead of trusting the cache
+    pub fn forget_health(&self, endpoint: &OllamaEndpoint<T>) {
+        self.record_health(endpoint, None);
+    }
+
+    fn record_health(&self, endpoint: &OllamaEndpoint<T>, health: Option<(bool, Instant)>) {
+        // The endpoints may have been replaced while the host was probed
+        let mut state = self.state.lock().unwrap();
+        if let Some(current) = state
+            .endpoints
+            .iter_mut()
+            .find(|e| Arc::ptr_eq(&e.endpoint.client, &endpoint.client))
+        {
+            current.last_health = health;
+        }
+    }
+}
+
+fn state<T: OllamaTransport>(
+    (config, client): (AiOllamaEndpoint, OllamaClient<T>),
+) -> EndpointState<T> {
+    EndpointState {
+        endpoint: OllamaEndpoint {
+            config,
+            client: Arc::new(client),
+        },
+        last_health: None,
+    }
+}
+
+fn build_endpoints(configs: &[AiOllamaEndpoint]) -> Result<Vec<(AiOllamaEndpoint, OllamaClient)>> {
+    let endpoints = configs
+        .iter()
+        .map(|config| {
+            let client = OllamaClient::with_base_url(&config.base_url)
+                .map_err(|e| anyhow!("{}: {}", config.base_url, e))?;
+            Ok((config.clone(), client))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if endpoints.is_empty() {
+        return Err(anyhow!("At least one Ollama endpoint is required"));
+    }
+
+    Ok(endpoints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::mock_transport::{MockReply, MockTransport};
+
+    const TAGS: &str = "{\"models\":[]}";
+
+    fn config(base_url: &str) -> AiOllamaEndpoint {
+        AiOllamaEndpoint {
+            base_url: base_url.to_string(),
+            preferred_model: None,
+        }
+    }
+
+    fn endpoints(hosts: Vec<(&str, Vec<MockReply>)>) -> OllamaEndpoints<MockTransport> {
+        OllamaEndpoints::with_clients(
+            hosts
+                .into_iter()
+                .map(|(base_url, replies)| {
+                    (
+                        config(base_url),
+                        OllamaClient::with_transport(MockTransport::new(replies)),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_selects_first_healthy_endpoint_and_reports_changes() {
+        let endpoints = endpoints(vec![
+            ("http://desktop:11434", vec![MockReply::Unreachable]),
+            ("http://localhost:11434", vec![MockReply::Body(200, TAGS)]),
+        ]);
+
+        let selection = tauri::async_runtime::block_on(endpoints.select());
+        let selected = selection.endpoint.unwrap();
+        assert_eq!(selected.config.base_url, "http://localhost:11434");
+        let changed = selection.changed.unwrap();
+        assert_eq!(changed.base_url.as_deref(), Some("http://localhost:11434"));
+        assert_eq!(changed.previous, None);
+
+        // Both results are cached, so nothing is probed and nothing changes
+        let selection = tauri::async_runtime::block_on(endpoints.select());
+        assert!(selection.endpoint.is_some());
+        assert!(selection.changed.is_none());
+        assert_eq!(
+            endpoints
+                .client_for(None)
+                .unwrap()
+                .transport()
+                .requests()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_availability_answers_during_slow_pull() {
+        let endpoints = endpoints(vec![(
+            "http://localhost:11434",
+            vec![
+                MockReply::Hang(200, vec!["{\"status\":\"pulling manifest\"}\n"]),
+                MockReply::Body(200, TAGS),
+            ],
+        )]);
+
+        tauri::async_runtime::block_on(async {
+            let client = endpoints.client_for(None).unwrap();
+            let pull = tauri::async_runtime::spawn({
+                let client = client.clone();
+                async move { client.pull_model("llama3.2:1b").await }
+            });
+            while client.transport().requests().is_empty() {
+                tokio::task::yield_now().await;
+            }
+
+            let started = Instant::now();
+            assert!(endpoints.select().await.endpoint.is_some());
+            assert!(started.elapsed() < Duration::from_millis(100));
+            assert!(endpoints.client_for(None).is_ok());
+
+            pull.abort();
+        });
+    }
+}
@@ -1,190 +1,4549 @@
-use crate::ai_toolkit::ollama_client::OllamaClient;
-use crate::settings::AiFeatures;
+use crate::ai_toolkit::chunking::{
+    enhance_chunks, estimate_tokens, split_into_chunks, with_outer_whitespace,
+};
+use crate::ai_toolkit::code_dictation::{code_spans, write_code};
+use crate::ai_toolkit::diff::{diff_words, DiffOp};
+use crate::ai_toolkit::enhancement_cache::EnhancementCache;
+use crate::ai_toolkit::filler_words::strip_fillers;
+use crate::ai_toolkit::gguf::{gguf_modelfile, validate_gguf_file, validate_model_name};
+use crate::ai_toolkit::graphemes::{self, to_nfc};
+use crate::ai_toolkit::in_flight::{Aborted, InFlight};
+use crate::ai_toolkit::language::{
+    detect_language, examples_in_language, language_name as language_name_for,
+    should_skip_language, DetectedLanguage,
+};
+use crate::ai_toolkit::markdown::{markdown_spans, write_markdown};
+use crate::ai_toolkit::model_registry::{display_model_id, qualify_model_id};
+use crate::ai_toolkit::model_updates::{
+    manifest_url, registry_digest, same_digest, AiModelUpdateCheck, AiModelUpdated,
+};
+use crate::ai_toolkit::number_format::resolve_number_locale;
+use crate::ai_toolkit::offline_enhancement::enhance_offline;
+use crate::ai_toolkit::ollama_client::{
+    generation_totals, OllamaClient, OllamaError, OllamaOptions, Prompt,
+};
+use crate::ai_toolkit::output_budget::{
+    max_input_tokens, output_budget, prompt_budget, DEFAULT_CONTEXT_TOKENS,
+};
+use crate::ai_toolkit::output_guard::{
+    check_output_length, check_word_overlap, without_spoken_breaks, OutputCheck, RejectedOutput,
+    MIN_WORD_OVERLAP,
+};
+use crate::ai_toolkit::post_processing::OutputPasses;
+use crate::ai_toolkit::profanity::mask_profanity;
+use crate::ai_toolkit::prompt_template::{
+    assemble_prompt, build_code_prompt, build_summary_prompt, chat_prompt, fit_prompt,
+    AiPromptComponent, FittedPrompt, Layout, PromptExtras, PROMPT_VERSION,
+};
+use crate::ai_toolkit::proper_nouns::names_in;
+use crate::ai_toolkit::protected_spans::{protect_ranges, ProtectedText};
+use crate::ai_toolkit::recent_transcripts::{RecentTranscripts, RECENT_TRANSCRIPTS};
+use crate::ai_toolkit::repetitions::collapse_repetitions;
+use crate::ai_toolkit::replacement_rules::apply_replacement_rules;
+use crate::ai_toolkit::retry::with_retry;
+use crate::ai_toolkit::session_context::SessionContext;
+use crate::ai_toolkit::snippets::{find_snippets, snippet_marker_spans, SnippetText};
+use crate::ai_toolkit::spoken_emoji::spoken_emoji_spans;
+use crate::ai_toolkit::spoken_punctuation::apply_spoken_punctuation;
+use crate::ai_toolkit::storage::{
+    free_space, is_local_host, model_blobs, reclaimable_bytes, resolve_models_dir,
+    OllamaStorageInfo,
+};
+use crate::ai_toolkit::vocabulary::apply_vocabulary;
+use crate::ai_toolkit::{detect_num_thread, get_available_models};
+use crate::helpers::foreground::foreground_app;
+use crate::helpers::power::on_battery;
+use crate::managers::ai_auto_pull::{AiModelAutoPull, AutoPulls};
+use crate::managers::ai_benchmark::{BenchmarkCache, BenchmarkContext};
+use crate::managers::ai_circuit_breaker::{AiCircuitChanged, CircuitBreaker, SharedCircuitBreaker};
+use crate::managers::ai_endpoints::{OllamaEndpoint, OllamaEndpoints, SharedOllamaEndpoints};
+use crate::managers::ai_enhancement_history::{
+    AiEnhancementHistoryEntry, AiEnhancementRating, AiRating, EnhancementHistory,
+    SharedEnhancementHistory,
+};
+use crate::managers::ai_enhancement_metrics::{
+    EnhancementMetrics, EnhancementRun, SharedEnhancementMetrics,
+};
+use crate::managers::ai_enhancement_queue::{take_turn, SharedEnhancementQueue};
+use crate::managers::ai_idle_unload::{
+    idle_keep_alive, idle_timeout, AiModelUnloaded, IdleModel, SharedIdleModel,
+};
+use crate::managers::ai_latency_budget::{
+    latency_profiles, pick_model, AiLatencyChoice, LatencyFit, LatencyProfiles, ModelLatency,
+    SharedLatencyProfiles,
+};
+use crate::managers::ai_model_degradation::{
+    smaller_model, AiModelDegraded, ModelDegradation, SharedModelDegradation,
+};
+use crate::managers::ai_model_usage::{
+    AiUnusedModelDeleted, AiUnusedModels, ModelUsage, SharedModelUsage,
+};
+use crate::managers::ai_preload::{wait_for_ollama, SharedStartupPreload, StartupPreload};
+use crate::managers::ai_pull_queue::{drain, AiModelPullProgress, AiPullQueueItem, PullQueue};
+use crate::managers::ai_pull_records::{is_model_installed, AiPullRecord, PullRecords};
+use crate::managers::ai_refinement::{
+    differs_meaningfully, AiEnhancementRefined, AiReenhancement, PendingRefinement,
+    SharedRefinement,
+};
+use crate::managers::ai_request_dedup::SharedRequestDedup;
+use crate::managers::ai_usage_stats::{SharedUsageStats, UsageStats};
+use crate::settings::{
+    get_settings, AiFeatures, AiGenerationOptions, AiLongTextPolicy, AiOutputGuard,
+    AiReenhanceOverrides, AiRuleStage, AiSamplingOptions, AiSummaryStyle, AiTone,
+    AiVocabularyEntry, AppSettings,
+};
 use anyhow::{anyhow, Result};
-use log::{info, warn};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::Mutex;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
-pub struct AiModelPullProgress {
+pub struct AiModelPullStalled {
+    pub pull_id: String,
     pub model_id: String,
-    pub status: String,
     pub completed: Option<u64>,
     pub total: Option<u64>,
-    pub percentage: f64,
+    pub retrying: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelPullComplete {
+    pub pull_id: String,
+    pub model_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelPullError {
+    pub pull_id: String,
+    pub model_id: String,
+    pub error: String,
+}
+
+/// Sent as soon as a transcript is handed over for enhancement. Exactly one of
+/// `ai-enhancement-complete`, `ai-enhancement-fallback` and
+/// `ai-enhancement-skipped` follows with the same `request_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementStarted {
+    pub request_id: String,
+    pub word_count: u32,
+    /// The selected model, before any host's preferred model replaces it
+    pub model: String,
+}
+
+/// Sent for a transcript passed through without asking the model
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementSkipped {
+    pub request_id: String,
+    pub reason: AiSkipReason,
+    /// The transcript pasted as it was
+    pub text: String,
+}
+
+/// Corrected text produced so far for the utterance identified by `request_id`
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementPartial {
+    pub request_id: String,
+    pub text_so_far: String,
+}
+
+/// Why an enhancement was abandoned in favour of the original transcript
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiFallbackReason {
+    Timeout,
+    OllamaUnavailable,
+    ModelMissing,
+    Failed,
+    /// The model's output could not be trusted as a correction of the transcript
+    RejectedOutput,
+    /// Stopped by the user, or by a new recording starting
+    Aborted,
+}
+
+/// Why a transcript was passed through without asking the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiSkipReason {
+    /// Fewer words than the configured minimum
+    TooShort,
+    /// Only numbers and punctuation, with nothing for the model to correct
+    NoLetters,
+    /// More words than the configured maximum
+    TooLong,
+    /// In a language enhancement isn't configured for
+    Language,
+    /// Only a snippet's trigger, which is inserted without the model
+    Snippet,
+    /// No installed model is expected to finish within the latency budget
+    LatencyBudget,
+}
+
+/// What happened to a transcript handed to [`AiEnhancementManager::enhance_text`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EnhancementOutcome {
+    Enhanced,
+    /// Not worth sending to the model
+    Skipped {
+        reason: AiSkipReason,
+    },
+    /// Enhancement failed and the original text was kept
+    Fallback {
+        reason: AiFallbackReason,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnhancedText {
+    pub text: String,
+    pub outcome: EnhancementOutcome,
+}
+
+/// The event an enhancement announced by `ai-enhancement-started` ends with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Terminal {
+    /// `ai-enhancement-complete`, carrying the text pasted
+    Complete,
+    Fallback(AiFallbackReason),
+    Skipped(AiSkipReason),
+    /// `ai-enhancement-fallback`, sent by [`abort_enhancement`] already
+    Aborted,
+}
+
+impl Terminal {
+    fn of(result: &Result<EnhancedText, EnhancementError>) -> Self {
+        match result {
+            Ok(enhanced) => match enhanced.outcome {
+                EnhancementOutcome::Enhanced => Terminal::Complete,
+                EnhancementOutcome::Skipped { reason } => Terminal::Skipped(reason),
+                EnhancementOutcome::Fallback {
+                    reason: AiFallbackReason::Aborted,
+                } => Terminal::Aborted,
+                EnhancementOutcome::Fallback { reason } => Terminal::Fallback(reason),
+            },
+            Err(e) => Terminal::of_error(e),
+        }
+    }
+
+    fn of_error(e: &EnhancementError) -> Self {
+        match e {
+            EnhancementError::Cancelled => Terminal::Aborted,
+            e => Terminal::Fallback(e.fallback_reason()),
+        }
+    }
+}
+
+/// Why an enhancement produced no text, returned when falling back to the
+/// original is off and by the enhancement commands
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EnhancementError {
+    /// AI enhancement is turned off in the settings
+    Disabled,
+    NoModelSelected,
+    OllamaUnavailable,
+    /// The model isn't installed on the Ollama host
+    ModelMissing {
+        model: String,
+    },
+    Timeout,
+    /// The model's output could not be trusted as a correction of the text
+    OutputRejected {
+        check: OutputCheck,
+    },
+    /// Stopped by the user, or by a new recording starting
+    Cancelled,
+    Failed {
+        message: String,
+    },
+}
+
+impl EnhancementError {
+    /// Classify an error from generating the enhancement
+    pub fn from_error(e: &anyhow::Error) -> Self {
+        match e.downcast_ref::<OllamaError>() {
+            Some(OllamaError::Unavailable) => EnhancementError::OllamaUnavailable,
+            Some(OllamaError::ModelNotFound { model }) => EnhancementError::ModelMissing {
+                model: model.clone(),
+            },
+            // Output cut off at the token limit falls short of the transcript
+            Some(OllamaError::Truncated { .. }) => EnhancementError::OutputRejected {
+                check: OutputCheck::Length,
+            },
+            None => {
+                if let Some(RejectedOutput(check, _)) = e.downcast_ref::<RejectedOutput>() {
+                    EnhancementError::OutputRejected { check: *check }
+                } else if e.is::<Aborted>() {
+                    EnhancementError::Cancelled
+                } else {
+                    EnhancementError::Failed {
+                        message: format!("{:#}", e),
+                    }
+                }
+            }
+            _ => EnhancementError::Failed {
+                message: format!("{:#}", e),
+            },
+        }
+    }
+
+    /// The reason reported when the original transcript is used instead
+    pub fn fallback_reason(&self) -> AiFallbackReason {
+        match self {
+            EnhancementError::OllamaUnavailable => AiFallbackReason::OllamaUnavailable,
+            EnhancementError::ModelMissing { .. } => AiFallbackReason::ModelMissing,
+            EnhancementError::Timeout => AiFallbackReason::Timeout,
+            EnhancementError::OutputRejected { .. } => AiFallbackReason::RejectedOutput,
+            EnhancementError::Cancelled => AiFallbackReason::Aborted,
+            EnhancementError::Disabled
+            | EnhancementError::NoModelSelected
+            | EnhancementError::Failed { .. } => AiFallbackReason::Failed,
+        }
+    }
+}
+
+impl fmt::Display for EnhancementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnhancementError::Disabled => write!(f, "AI enhancement is not enabled"),
+            EnhancementError::NoModelSelected => write!(f, "No AI model selected"),
+            EnhancementError::OllamaUnavailable => write!(f, "Ollama is not running"),
+            EnhancementError::ModelMissing { model } => {
+                write!(f, "Model '{}' is not installed", model)
+            }
+            EnhancementError::Timeout => write!(f, "AI enhancement timed out"),
+            EnhancementError::OutputRejected { check } => {
+                write!(f, "Model output rejected by the {:?} check", check)
+            }
+            EnhancementError::Cancelled => write!(f, "AI enhancement was cancelled"),
+            EnhancementError::Failed { message } => write!(f, "Enhancement failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for EnhancementError {}
+
+/// What enhancement does with a transcript, chosen for each invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EnhanceMode {
+    /// Fix transcription errors, keeping the wording
+    Correct,
+    /// Replace the transcript with a summary of it
+    Summarize {
+        style: AiSummaryStyle,
+        max_words: u32,
+    },
+    /// Fix transcription errors and lay the text out as an email, with
+    /// greeting, paragraphs and closing
+    EmailFormat,
+    /// Write the headings, lists, code blocks and emphasis dictated into a
+    /// note as Markdown, then fix transcription errors around them
+    Markdown,
+    /// Write the symbols, numbers and identifiers dictated into a code
+    /// editor by rule, then fix only misheard words around them
+    Code,
+}
+
+impl Default for EnhanceMode {
+    fn default() -> Self {
+        EnhanceMode::Correct
+    }
+}
+
+impl EnhanceMode {
+    /// Summarize mode as configured in `settings`
+    pub fn summarize(settings: &AppSettings) -> Self {
+        EnhanceMode::Summarize {
+            style: settings.ai_summary_style,
+            max_words: settings.ai_summary_max_words,
+        }
+    }
+
+    /// Name recorded with the history entries this mode produces
+    pub fn label(&self) -> &'static str {
+        match self {
+            EnhanceMode::Correct => "correct",
+            EnhanceMode::Summarize { .. } => "summarize",
+            EnhanceMode::EmailFormat => "email",
+            EnhanceMode::Markdown => "markdown",
+            EnhanceMode::Code => "code",
+        }
+    }
+
+    /// Whether the text is rewritten in the features' tone, which a summary
+    /// leaves to its own format and code never takes
+    pub fn applies_tone(&self) -> bool {
+        !matches!(self, EnhanceMode::Summarize { .. } | EnhanceMode::Code)
+    }
+
+    /// Whether the text is prose, which the rules for sentences, like
+    /// capitalization and typography, apply to
+    pub fn writes_prose(&self) -> bool {
+        !matches!(self, EnhanceMode::Code)
+    }
+}
+
+/// What happened on the way to an enhancement's result, for the metrics
+#[derive(Default)]
+struct RunNotes {
+    reached_model: bool,
+    cached: bool,
+    rejected_by: Option<OutputCheck>,
+    retries: u32,
+    truncated: u32,
+    /// The model picked to fit the latency budget, used as it is
+    latency_choice: Option<AiLatencyChoice>,
+    /// What the model or the cache answered with, announced once the text
+    /// to paste is final
+    completion: Option<Completion>,
+}
+
+/// The enhancement finished last, kept for rating it and enhancing it again
+struct LastEnhancement {
+    request_id: String,
+    model: String,
+    features: AiFeatures,
+    mode: EnhanceMode,
+    outcome: EnhancementOutcome,
+    /// What was pasted for it
+    text: String,
+    /// Set once the next dictation starts, which it is no longer the text
+    /// to enhance again in place of
+    superseded: bool,
+    /// Entry it was saved as, once the history has it
+    history_id: Option<u32>,
+    rating: Option<AiEnhancementRating>,
+}
+
+/// Everything decided about a transcript's enhancement before the model runs
+struct EnhanceRequest<'a> {
+    id: String,
+    features: &'a AiFeatures,
+    mode: EnhanceMode,
+    language: Option<DetectedLanguage>,
+    /// What was dictated past the maximum length, pasted as it is after the
+    /// enhanced text
+    rest: &'a str,
+    /// Use exactly the given model, even on an endpoint preferring another
+    pinned_model: bool,
+    /// Text dictated just before into the same application
+    preceding: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementFallback {
+    pub request_id: String,
+    pub reason: AiFallbackReason,
+    /// The transcript pasted instead
+    pub text: String,
+}
+
+/// Sent before each chunk of a transcript long enough to be split up
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementProgress {
+    pub request_id: String,
+    pub chunk: u32,
+    pub total_chunks: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementStats {
+    pub model: String,
+    pub duration_ms: u32,
+    pub streamed: bool,
+    /// Language detected in the transcript, for debugging the prompt
+    pub language: Option<DetectedLanguage>,
+    /// The text was answered from the cache without running the model
+    pub cached: bool,
+    /// The model `ai_latency_budget_ms` picked, when it is set
+    pub latency_choice: Option<AiLatencyChoice>,
+}
+
+/// How well the cache of enhanced texts is doing since the app started
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementCacheStats {
+    pub entries: u32,
+    pub hits: u32,
+    pub misses: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementComplete {
+    pub request_id: String,
+    /// The enhanced text, as pasted
+    pub text: String,
+    /// The text as dictated, unless it is withheld while the history is off
+    pub original: Option<String>,
+    /// Length of the text as dictated, in characters, sent even when the
+    /// text itself is withheld
+    pub original_chars: u32,
+    pub outcome: EnhancementOutcome,
+    pub stats: AiEnhancementStats,
+    /// The text was translated as well as corrected
+    pub translated: bool,
+    pub mode: EnhanceMode,
+    /// Tone the text was rewritten in, if any
+    pub tone: Option<AiTone>,
+    /// What enhancement changed, word by word, unless the texts were too
+    /// long or too different to diff
+    pub diff: Option<Vec<DiffOp>>,
+    /// Words at the end of `text` left as dictated, as the transcript was
+    /// longer than the maximum for enhancement
+    pub unenhanced_words: u32,
+}
+
+/// What `ai-enhancement-complete` reports of a run besides its texts
+#[derive(Debug, Clone)]
+struct Completion {
+    stats: AiEnhancementStats,
+    translated: bool,
+    mode: EnhanceMode,
+    tone: Option<AiTone>,
+    unenhanced_words: u32,
+}
+
+impl Completion {
+    fn new(
+        stats: AiEnhancementStats,
+        features: &AiFeatures,
+        mode: EnhanceMode,
+        rest: &str,
+    ) -> Self {
+        Self {
+            stats,
+            translated: features.translate_to.is_some(),
+            mode,
+            tone: features.tone.filter(|_| mode.applies_tone()),
+            unenhanced_words: rest.split_whitespace().count() as u32,
+        }
+    }
+
+    /// The event ending the run `request_id`, with `pasted` in place of the
+    /// `original` text
+    fn event(
+        self,
+        request_id: &str,
+        original: &str,
+        pasted: &str,
+        settings: &AppSettings,
+    ) -> AiEnhancementComplete {
+        AiEnhancementComplete {
+            request_id: request_id.to_string(),
+            text: pasted.to_string(),
+            original: original_for_event(original, settings),
+            original_chars: original.chars().count() as u32,
+            outcome: EnhancementOutcome::Enhanced,
+            stats: self.stats,
+            translated: self.translated,
+            mode: self.mode,
+            tone: self.tone,
+            diff: diff_words(original, pasted),
+            unenhanced_words: self.unenhanced_words,
+        }
+    }
+}
+
+/// The dictated text to send along with the enhanced one, or `None` when
+/// the settings keep it out of events
+fn original_for_event(original: &str, settings: &AppSettings) -> Option<String> {
+    (settings.ai_history_enabled || !settings.ai_withhold_original_from_events)
+        .then(|| original.to_string())
+}
+
+/// Whether the selected model can be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiModelStatus {
+    Installed,
+    Missing,
+    /// None of the Ollama hosts answered, so it couldn't be checked
+    OllamaUnreachable,
+}
+
+/// Sent as `ai-model-status` whenever the selected model is checked, at
+/// startup and when another one is selected
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelStatusReport {
+    pub model: String,
+    pub status: AiModelStatus,
+}
+
+/// Result of a test enhancement from the settings, with what changed
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementPreview {
+    pub original: String,
+    pub text: String,
+    pub diff: Option<Vec<DiffOp>>,
+    pub outcome: EnhancementOutcome,
+    /// How long the model took, in milliseconds
+    pub duration_ms: u32,
+}
+
+/// The prompts an enhancement would send, for checking what reaches the
+/// model
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiPromptPreview {
+    /// One prompt per chunk the transcript is enhanced in
+    pub prompts: Vec<String>,
+    /// Rough token count of all the prompts together
+    pub estimated_tokens: u32,
+    /// What was left out of the prompts to fit the model's context, in the
+    /// order it was dropped
+    pub dropped: Vec<AiPromptComponent>,
+    /// Language detected in the transcript
+    pub language: Option<DetectedLanguage>,
+}
+
+/// One model's output in a comparison, or why there is none
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelComparisonSide {
+    pub model: String,
+    pub text: Option<String>,
+    /// What changed from the sample text
+    pub diff: Option<Vec<DiffOp>>,
+    pub latency_ms: u32,
+    pub error: Option<EnhancementError>,
+}
+
+/// The same sample text corrected by two models
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelComparison {
+    pub a: AiModelComparisonSide,
+    pub b: AiModelComparisonSide,
+}
+
+/// Sent when an enhanced transcript is pasted, so the UI can offer to use the
+/// original instead
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementPasted {
+    pub original_text: String,
+    pub enhanced_text: String,
+}
+
+/// Maximum number of automatic retries after a stalled pull
+const MAX_PULL_RETRIES: u32 = 3;
+
+/// File in the app data directory tracking downloads that have not finished
+const PULL_RECORDS_FILE: &str = "ai_model_pulls.json";
+
+/// File in the app data directory holding the enhancement history
+const ENHANCEMENT_HISTORY_FILE: &str = "ai_enhancement_history.jsonl";
+
+/// File in the app data directory holding the enhancement metrics
+const ENHANCEMENT_METRICS_FILE: &str = "ai_enhancement_metrics.json";
+
+/// File in the app data directory holding the daily usage stats
+const USAGE_STATS_FILE: &str = "ai_usage_stats.json";
+
+/// File in the app data directory holding the model benchmarks
+const BENCHMARKS_FILE: &str = "ai_model_benchmarks.json";
+
+/// File in the app data directory holding when each model was last used
+const MODEL_USAGE_FILE: &str = "ai_model_usage.json";
+
+/// Factor the enhancement timeout is stretched by for summaries and
+/// translations, which rewrite the whole text
+const SLOW_MODE_TIMEOUT_FACTOR: u32 = 2;
+
+/// How long a model warmed up for a recording stays loaded, unless it is
+/// unloaded after going unused. A recording starting within this long of
+/// the last warm-up doesn't warm up again.
+const WARM_UP_KEEP_ALIVE: Duration = Duration::from_secs(300);
+
+/// How often the model is checked for having gone unused long enough to be
+/// unloaded
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Wait after launch before preloading the model, so loading it doesn't
+/// compete with Handy starting up
+const PRELOAD_DELAY: Duration = Duration::from_secs(5);
+
+/// Minimum time between `ai-enhancement-partial` events for one utterance
+const PARTIAL_EVENT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Factor the output length bounds are widened by when translating
+const TRANSLATION_RATIO_SLACK: f64 = 2.0;
+
+/// Factor the output length bounds are widened by when rewriting in a tone,
+/// which can expand shorthand into full sentences or condense rambling
+const TONE_RATIO_SLACK: f64 = 1.5;
+
+/// Pull state shared with the background worker and the pull commands, so
+/// neither a download nor managing one ever holds the manager lock
+#[derive(Clone)]
+pub struct PullContext {
+    app: AppHandle,
+    endpoints: SharedOllamaEndpoints,
+    queue: Arc<std::sync::Mutex<PullQueue>>,
+    records: Arc<std::sync::Mutex<PullRecords>>,
+    auto: Arc<std::sync::Mutex<AutoPulls>>,
+    /// Digests of the models being updated from before the download, to
+    /// tell afterwards whether they changed
+    updates: Arc<std::sync::Mutex<HashMap<String, String>>>,
+}
+
+/// Rating state shared with the rating commands, so rating an enhancement
+/// never waits on the manager, which may be busy with the next one
+#[derive(Clone)]
+pub struct RatingContext {
+    app: AppHandle,
+    last: Arc<std::sync::Mutex<Option<LastEnhancement>>>,
+    history: SharedEnhancementHistory,
+    metrics: SharedEnhancementMetrics,
 }
 
 pub struct AiEnhancementManager {
-    client: OllamaClient,
+    app_handle: AppHandle,
+    /// Ollama hosts, shared with the commands that only talk to Ollama
+    endpoints: SharedOllamaEndpoints,
     current_model: Option<String>,
+    auto_num_thread: u32,
+    pulls: PullContext,
+    benchmarks: BenchmarkContext,
+    /// Counter for the ids tagging enhancement events
+    next_request_id: u64,
+    /// Results of recent enhancements, reused for the same text
+    cache: EnhancementCache,
+    /// Written from blocking tasks, so saving an entry never delays the
+    /// paste, and shared with the commands listing it
+    history: SharedEnhancementHistory,
+    /// Transcripts pasted last, kept in memory for undoing their enhancement
+    recent: SharedRecentTranscripts,
+    /// The enhancement being generated, shared so it can be aborted while
+    /// the manager is busy generating it
+    in_flight: SharedInFlight,
+    /// What was pasted for the last utterances, shared so it can be cleared
+    /// while the manager is busy
+    context: SharedSessionContext,
+    /// Saved from blocking tasks like the history, and shared with the
+    /// commands reading it
+    metrics: SharedEnhancementMetrics,
+    /// Set from the blocking task saving its history entry too
+    last_enhancement: Arc<std::sync::Mutex<Option<LastEnhancement>>>,
+    /// Daily counts kept when usage stats are enabled, saved like the
+    /// metrics and shared with the commands reading them
+    usage: SharedUsageStats,
+    /// When each model was last used, shared with the benchmarks and the
+    /// commands cleaning up unused models
+    model_usage: SharedModelUsage,
+    /// When the manager was created at startup, to tell runs right after
+    /// boot apart in the usage stats
+    created: Instant,
+    /// Model warmed up last and when, so quick successive recordings don't
+    /// each load it again
+    last_warm_up: Arc<std::sync::Mutex<Option<(String, Instant)>>>,
+    /// The smaller model used this session in place of a failing one,
+    /// shared so it can be reset while the manager is busy
+    degradation: SharedModelDegradation,
+    /// The model preload at startup, shared so it can be stopped
+    startup_preload: SharedStartupPreload,
+    /// The model used last, shared with the task unloading it once idle
+    idle_model: SharedIdleModel,
+    /// Whether Ollama is skipped for being unreachable, shared with the
+    /// task probing for it
+    circuit: SharedCircuitBreaker,
+    /// How long each installed model takes, worked out from the benchmarks
+    /// for the latency budget, shared so it can be worked out again once
+    /// they change
+    latency: SharedLatencyProfiles,
+    /// The second pass of the two-pass mode, shared so the next dictation
+    /// can cancel it
+    refinement: SharedRefinement,
+    last_run: RunNotes,
 }
 
 impl AiEnhancementManager {
-    pub fn new() -> Self {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        let auto_num_thread = detect_num_thread();
+        debug!("Detected {} generation threads from CPU topology", auto_num_thread);
+
+        let settings = get_settings(app_handle);
+        let endpoints = OllamaEndpoints::new(&settings.ai_ollama_endpoints).unwrap_or_else(|e| {
+            warn!("Invalid Ollama endpoints, using the local default: {}", e);
+            OllamaEndpoints::local()
+        });
+        let endpoints = Arc::new(endpoints);
+
+        let (records, history, metrics, usage, benchmarks, model_usage) = match app_handle
+            .path()
+            .app_data_dir()
+        {
+            Ok(dir) => (
+                PullRecords::load(dir.join(PULL_RECORDS_FILE)),
+                EnhancementHistory::load(dir.join(ENHANCEMENT_HISTORY_FILE)),
+                EnhancementMetrics::load(dir.join(ENHANCEMENT_METRICS_FILE)),
+                UsageStats::load(dir.join(USAGE_STATS_FILE)),
+                BenchmarkCache::load(dir.join(BENCHMARKS_FILE)),
+                ModelUsage::load(dir.join(MODEL_USAGE_FILE)),
+            ),
+            Err(e) => {
+                warn!(
+                    "App data directory unavailable, pull records and enhancement history won't persist: {}",
+                    e
+                );
+                (
+                    PullRecords::in_memory(),
+                    EnhancementHistory::in_memory(),
+                    EnhancementMetrics::in_memory(),
+                    UsageStats::in_memory(),
+                    BenchmarkCache::in_memory(),
+                    ModelUsage::in_memory(),
+                )
+            }
+        };
+        let model_usage = Arc::new(std::sync::Mutex::new(model_usage));
+
         Self {
-            client: OllamaClient::new(),
+            app_handle: app_handle.clone(),
+            endpoints: endpoints.clone(),
             current_model: None,
+            auto_num_thread,
+            pulls: PullContext {
+                app: app_handle.clone(),
+                endpoints: endpoints.clone(),
+                queue: Arc::new(std::sync::Mutex::new(PullQueue::default())),
+                records: Arc::new(std::sync::Mutex::new(records)),
+                auto: Arc::new(std::sync::Mutex::new(AutoPulls::default())),
+                updates: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            },
+            benchmarks: BenchmarkContext::new(
+                app_handle,
+                endpoints,
+                auto_num_thread,
+                benchmarks,
+                model_usage.clone(),
+            ),
+            next_request_id: 0,
+            cache: EnhancementCache::new(settings.ai_cache_size as usize),
+            history: Arc::new(std::sync::Mutex::new(history)),
+            recent: Arc::new(std::sync::Mutex::new(RecentTranscripts::new(
+                RECENT_TRANSCRIPTS,
+            ))),
+            metrics: Arc::new(std::sync::Mutex::new(metrics)),
+            last_enhancement: Arc::new(std::sync::Mutex::new(None)),
+            usage: Arc::new(std::sync::Mutex::new(usage)),
+            model_usage,
+            created: Instant::now(),
+            in_flight: Arc::new(InFlight::default()),
+            context: Arc::new(std::sync::Mutex::new(SessionContext::default())),
+            last_warm_up: Arc::new(std::sync::Mutex::new(None)),
+            degradation: Arc::new(std::sync::Mutex::new(ModelDegradation::default())),
+            startup_preload: Arc::new(std::sync::Mutex::new(StartupPreload::new(auto_num_thread))),
+            idle_model: Arc::new(std::sync::Mutex::new(IdleModel::default())),
+            circuit: Arc::new(std::sync::Mutex::new(CircuitBreaker::default())),
+            latency: Arc::new(std::sync::Mutex::new(LatencyProfiles::default())),
+            refinement: Arc::new(PendingRefinement::default()),
+            last_run: RunNotes::default(),
         }
     }
 
-    /// Check if Ollama is available
-    pub async fn is_available(&self) -> bool {
-        self.client.is_available().await
+    /// The Ollama hosts, for commands that only need to talk to Ollama
+    pub fn endpoints(&self) -> SharedOllamaEndpoints {
+        self.endpoints.clone()
+    }
+
+    /// The model downloads, for the commands that manage them
+    pub fn pulls(&self) -> PullContext {
+        self.pulls.clone()
     }
 
-    /// Build prompt based on enabled features
-    fn build_prompt(&self, text: &str, features: &AiFeatures) -> String {
-        let mut instructions = vec![];
+    /// Model benchmarks, for the commands that run and list them
+    pub fn benchmarks(&self) -> BenchmarkContext {
+        self.benchmarks.clone()
+    }
 
-        if features.punctuation_and_capitalization {
-            instructions.push("- Add proper punctuation (periods, commas, question marks)");
-            instructions.push("- Use SENTENCE CASE only: capitalize first word of sentences and proper nouns. Do NOT capitalize every word");
-        }
-        if features.remove_filler_words {
-            instructions.push("- Remove filler words like 'um', 'uh', 'like' (only when used as fillers, not as verbs)");
-        }
-        if features.normalize_numbers {
-            instructions.push("- Convert spoken numbers to digits: 'twenty five' → '25', 'ten percent' → '10%'");
+    /// The model preload at startup, for stopping it and resuming it once
+    /// Ollama is up
+    pub fn startup_preload(&self) -> SharedStartupPreload {
+        self.startup_preload.clone()
+    }
+
+    /// The model used last, for the preload to mark it used
+    pub fn idle_model(&self) -> SharedIdleModel {
+        self.idle_model.clone()
+    }
+
+    /// Whether Ollama is skipped for being unreachable, for closing it
+    /// once a host is found
+    pub fn circuit_breaker(&self) -> SharedCircuitBreaker {
+        self.circuit.clone()
+    }
+
+    /// How long each installed model takes, for working it out again once
+    /// the benchmarks or the installed models change
+    pub fn latency_profiles(&self) -> SharedLatencyProfiles {
+        self.latency.clone()
+    }
+
+    /// The refinement in the background, for cancelling it when the next
+    /// dictation starts
+    pub fn refinement(&self) -> SharedRefinement {
+        self.refinement.clone()
+    }
+
+    /// When each model was last used, for finding the unused ones
+    pub fn model_usage(&self) -> SharedModelUsage {
+        self.model_usage.clone()
+    }
+
+    /// The enhancement metrics, for the commands reading and resetting them
+    pub fn metrics(&self) -> SharedEnhancementMetrics {
+        self.metrics.clone()
+    }
+
+    /// The enhancement history, for the commands listing and deleting entries
+    pub fn history(&self) -> SharedEnhancementHistory {
+        self.history.clone()
+    }
+
+    /// The daily usage stats, for the commands reading and clearing them
+    pub fn usage_stats(&self) -> SharedUsageStats {
+        self.usage.clone()
+    }
+
+    /// The model standing in for a failing one, for resetting it
+    pub fn degradation(&self) -> SharedModelDegradation {
+        self.degradation.clone()
+    }
+
+    /// Enhancement ratings, for the commands that rate them
+    pub fn ratings(&self) -> RatingContext {
+        RatingContext {
+            app: self.app_handle.clone(),
+            last: self.last_enhancement.clone(),
+            history: self.history.clone(),
+            metrics: self.metrics.clone(),
         }
-        if features.fix_spelling {
-            instructions.push("- Fix spelling mistakes and common homophones (their/there/they're)");
+    }
+
+    /// Start loading the selected model in the background so it is ready by
+    /// the time the transcript arrives. Best-effort: returns straight away,
+    /// and failures are only logged. Skipped when the same model was warmed
+    /// up within the time it is kept loaded, or on battery unless allowed.
+    pub fn warm_up(&self) {
+        let settings = get_settings(&self.app_handle);
+        if !settings.ai_enhancement_enabled {
+            return;
         }
+        let Some(model) = settings.ai_selected_model else {
+            return;
+        };
+        let model = self
+            .degradation
+            .lock()
+            .unwrap()
+            .active_model(&model)
+            .to_string();
+        let options = self.generation_options();
+        let keep_alive = options.keep_alive.unwrap_or(WARM_UP_KEEP_ALIVE);
 
-        if instructions.is_empty() {
-            return text.to_string();
+        {
+            let mut last_warm_up = self.last_warm_up.lock().unwrap();
+            if matches!(&*last_warm_up, Some((warmed, at))
+                if *warmed == model && at.elapsed() < keep_alive)
+            {
+                return;
+            }
+            *last_warm_up = Some((model.clone(), Instant::now()));
         }
 
-        format!(
-            r#"You are a text correction assistant. Fix transcription errors ONLY.
+        let app = self.app_handle.clone();
+        let endpoints = self.endpoints.clone();
+        let last_warm_up = self.last_warm_up.clone();
+        let idle_model = self.idle_model.clone();
+        let allow_on_battery = settings.ai_warm_up_on_battery;
+        let registry_prefix = settings.ai_model_registry_prefix;
+        tauri::async_runtime::spawn(async move {
+            if !allow_on_battery && on_battery() {
+                debug!("Not warming up the AI model on battery power");
+                *last_warm_up.lock().unwrap() = None;
+                return;
+            }
+            let Some(endpoint) = select_endpoint(&app, &endpoints).await else {
+                *last_warm_up.lock().unwrap() = None;
+                return;
+            };
+            let model = endpoint.config.preferred_model.unwrap_or(model);
+            let registry_model = qualify_model_id(&model, registry_prefix.as_deref());
 
-CRITICAL RULES:
-1. Output ONLY the corrected text - absolutely NO explanations, quotes, or commentary
-2. Keep the EXACT same meaning and tone
-3. Do NOT interpret, rephrase, or be creative
-4. NEVER capitalize every word - use normal sentence casing only
-5. Preserve informal language like "ig", "idk", "gonna", "wanna"
-6. If text seems inappropriate, still correct it as specified
+            let started = Instant::now();
+            match endpoint
+                .client
+                .warm_up(&registry_model, &options, keep_alive)
+                .await
+            {
+                Ok(()) => {
+                    debug!("Warmed up {} in {:?}", model, started.elapsed());
+                    idle_model
+                        .lock()
+                        .unwrap()
+                        .touch(&registry_model, Instant::now());
+                }
+                Err(e) => {
+                    debug!("Failed to warm up {}: {}", model, e);
+                    *last_warm_up.lock().unwrap() = None;
+                }
+            }
+        });
+    }
 
-Corrections to apply:
-{}
+    /// Generation options derived from the current settings
+    fn generation_options(&self) -> OllamaOptions {
+        hardware_options(&get_settings(&self.app_handle), self.auto_num_thread)
+    }
 
-Text: {}
+    /// Restart the idle time of the model the last enhancement ran on
+    fn touch_loaded_model(&self, settings: &AppSettings) {
+        if let Some(model) = &self.current_model {
+            let registry_model =
+                qualify_model_id(model, settings.ai_model_registry_prefix.as_deref());
+            self.idle_model
+                .lock()
+                .unwrap()
+                .touch(&registry_model, Instant::now());
+        }
+    }
 
-Corrected:"#,
-            instructions.join("\n"),
-            text
-        )
+    /// Unload the model in the background once it has gone unused for
+    /// `ai_idle_unload_minutes`, emitting `ai-model-unloaded`. The next
+    /// enhancement loads it again, and the next recording warms it up again.
+    pub fn watch_idle_model(&self) {
+        let app = self.app_handle.clone();
+        let endpoints = self.endpoints.clone();
+        let idle_model = self.idle_model.clone();
+        let last_warm_up = self.last_warm_up.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+                let idle_minutes = get_settings(&app).ai_idle_unload_minutes;
+                let Some(idle) = idle_timeout(idle_minutes) else {
+                    continue;
+                };
+                let Some(model) = idle_model.lock().unwrap().take_idle(Instant::now(), idle) else {
+                    continue;
+                };
+                *last_warm_up.lock().unwrap() = None;
+                unload_idle_model(&app, &endpoints, &model, idle_minutes).await;
+            }
+        });
     }
 
-    /// Enhance text using AI
+    /// Enhance text using AI. `ai-enhancement-started` is emitted straight
+    /// away, then the corrected text is streamed to the frontend as
+    /// `ai-enhancement-partial` events while the model produces it, followed
+    /// by `ai-enhancement-complete` carrying the returned text. The returned
+    /// text starts and ends with the same whitespace as `text`.
+    ///
+    /// When the enhancement fails or times out and falling back is enabled,
+    /// the original text is returned with a [`EnhancementOutcome::Fallback`]
+    /// outcome. A failure ends with `ai-enhancement-fallback` either way, as
+    /// the original text is what gets pasted, and a transcript not worth
+    /// enhancing with `ai-enhancement-skipped`.
+    ///
+    /// The metrics are then updated and, with history enabled, the text and
+    /// what came of it are added to the enhancement history, both in the
+    /// background.
+    ///
+    /// After `ai_degrade_after_failures` failures in a row the session moves
+    /// to the next smaller installed model, announced by `ai-model-degraded`,
+    /// and the transcript is tried again with it. `selected` stays selected,
+    /// and is used again once it works or the degradation is reset.
     pub async fn enhance_text(
         &mut self,
         text: &str,
-        model: &str,
+        selected: &str,
         features: &AiFeatures,
-    ) -> Result<String> {
-        // Skip very short text (less than 3 words)
-        if text.split_whitespace().count() < 3 {
-            info!("Skipping AI enhancement for very short text (< 3 words)");
-            return Ok(text.to_string());
+        mode: EnhanceMode,
+    ) -> Result<EnhancedText, EnhancementError> {
+        let started = Instant::now();
+        let generated_before = generation_totals();
+        self.last_run = RunNotes::default();
+        let request_id = self.next_request_id();
+        let settings = get_settings(&self.app_handle);
+        // In the two-pass mode the fast model goes first, and the quality
+        // model refines its text once it is pasted
+        let selected = match &settings.ai_two_pass {
+            Some(two_pass) => two_pass.fast_model.as_str(),
+            None => selected,
+        };
+        let mut model = self
+            .degradation
+            .lock()
+            .unwrap()
+            .active_model(selected)
+            .to_string();
+        // Under a latency budget the best model expected to finish in time
+        // stands in for the selected one
+        let fit = self.fit_latency_budget(text, &settings).await;
+        if let LatencyFit::Fits(choice) = &fit {
+            model = choice.model.clone();
+            self.last_run.latency_choice = Some(choice.clone());
+        }
+        self.emit_started(&request_id, text, &model);
+
+        // Summaries stand on their own, and aren't worth continuing from
+        let summarizing = matches!(mode, EnhanceMode::Summarize { .. });
+        let target = (settings.ai_context_enabled && !summarizing).then(foreground_app);
+        let preceding = target.as_ref().and_then(|target| {
+            let idle_gap = Duration::from_secs(settings.ai_context_idle_secs as u64);
+            self.context
+                .lock()
+                .unwrap()
+                .preceding(target.as_deref(), Instant::now(), idle_gap)
+        });
+
+        // Snippets are inserted by rule, and stand behind markers the model
+        // is kept from rewriting until it is done. The whitespace around the
+        // transcript is pasted with whatever it becomes, like the space that
+        // keeps one dictation off the next.
+        let snippets = snippets_in(text, &settings, mode);
+        let result = if snippets.is_whole() {
+            info!("Inserting a snippet without AI enhancement");
+            Ok(EnhancedText {
+                text: snippets.text().to_string(),
+                outcome: EnhancementOutcome::Skipped {
+                    reason: AiSkipReason::Snippet,
+                },
+            })
+        } else if fit == LatencyFit::TooSlow {
+            Ok(EnhancedText {
+                text: snippets.text().to_string(),
+                outcome: EnhancementOutcome::Skipped {
+                    reason: AiSkipReason::LatencyBudget,
+                },
+            })
+        } else {
+            let transcript = snippets.text().trim();
+            let preceding = preceding.as_deref();
+            let mut result = self
+                .run_enhancement(&request_id, transcript, &model, features, mode, preceding)
+                .await;
+            self.touch_loaded_model(&settings);
+            let now = chrono::Utc::now().timestamp();
+            self.model_usage.lock().unwrap().record(&model, now);
+            let threshold = settings.ai_degrade_after_failures;
+            if let Some(smaller) = self
+                .degrade_if_failing(selected, &model, &result, threshold)
+                .await
+            {
+                info!("Retrying the AI enhancement with {}", smaller);
+                model = smaller;
+                result = self
+                    .run_enhancement(&request_id, transcript, &model, features, mode, preceding)
+                    .await;
+                self.touch_loaded_model(&settings);
+                self.model_usage.lock().unwrap().record(&model, now);
+            }
+            result.map(|enhanced| punctuate_fallback(enhanced, transcript, &settings, mode))
+        };
+        let result = result.map(|enhanced| EnhancedText {
+            text: pasted_text(text, &snippets, &enhanced.text),
+            ..enhanced
+        });
+        let pasted = match &result {
+            Ok(enhanced) => enhanced.text.as_str(),
+            Err(_) => text,
+        };
+        self.emit_terminal(&request_id, Terminal::of(&result), text, pasted);
+        if let (Some(two_pass), Ok(enhanced)) = (&settings.ai_two_pass, &result) {
+            if enhanced.outcome == EnhancementOutcome::Enhanced && two_pass.quality_model != model {
+                spawn_refinement(
+                    &self.app_handle,
+                    Refinement {
+                        request_id: request_id.clone(),
+                        transcript: text.to_string(),
+                        pasted: pasted.to_string(),
+                        features: features.clone(),
+                        mode,
+                        model: two_pass.quality_model.clone(),
+                    },
+                );
+            }
+        }
+        if let Some(target) = &target {
+            self.context.lock().unwrap().record(
+                pasted,
+                target.as_deref(),
+                Instant::now(),
+                settings.ai_context_utterances as usize,
+            );
         }
+        let duration_ms = started.elapsed().as_millis() as u32;
+        self.record_metrics(
+            &result,
+            duration_ms,
+            generated_before,
+            settings.ai_usage_stats_enabled,
+        );
 
-        // Check if Ollama is available
-        if !self.is_available().await {
-            return Err(anyhow!("Ollama is not available. Please ensure Ollama is running."));
+        let (enhanced_text, outcome) = match &result {
+            Ok(enhanced) => (enhanced.text.clone(), enhanced.outcome.clone()),
+            Err(e) => (
+                text.to_string(),
+                EnhancementOutcome::Fallback {
+                    reason: e.fallback_reason(),
+                },
+            ),
+        };
+        *self.last_enhancement.lock().unwrap() = Some(LastEnhancement {
+            request_id: request_id.clone(),
+            model: model.clone(),
+            features: features.clone(),
+            mode,
+            outcome: outcome.clone(),
+            text: enhanced_text.clone(),
+            superseded: false,
+            history_id: None,
+            rating: None,
+        });
+        if !settings.ai_history_enabled {
+            return result;
         }
 
-        // Update current model
-        self.current_model = Some(model.to_string());
+        let mut entry = AiEnhancementHistoryEntry {
+            id: 0,
+            timestamp: chrono::Utc::now().timestamp(),
+            model,
+            features: features.clone(),
+            mode: mode.label().to_string(),
+            original_text: text.to_string(),
+            enhanced_text,
+            duration_ms,
+            outcome,
+            diff: None,
+            rating: None,
+            reenhanced_from: None,
+        };
+        let (history, last) = (self.history.clone(), self.last_enhancement.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            entry.diff = diff_words(&entry.original_text, &entry.enhanced_text);
+            // A rating given before the entry was saved is saved with it
+            let mut last = last.lock().unwrap();
+            let mut last = last.as_mut().filter(|last| last.request_id == request_id);
+            entry.rating = last.as_ref().and_then(|last| last.rating.clone());
+            let id = history.lock().unwrap().append(entry);
+            if let Some(last) = last.as_mut() {
+                last.history_id = Some(id);
+            }
+        });
+        result
+    }
 
-        // Build prompt
-        let prompt = self.build_prompt(text, features);
+    /// Correct the transcript of `refinement` again with its quality model,
+    /// offering the result through `ai-enhancement-refined` when it says
+    /// something other than the text pasted
+    async fn refine(&mut self, refinement: &Refinement) -> Result<()> {
+        let settings = get_settings(&self.app_handle);
+        let (features, mode) = (&refinement.features, refinement.mode);
+        let snippets = snippets_in(&refinement.transcript, &settings, mode);
+        let collapsed = remove_by_rule(snippets.text().trim(), features, mode);
+        let request = EnhanceRequest {
+            id: self.next_request_id(),
+            features,
+            mode,
+            language: detect_language(&collapsed),
+            rest: "",
+            pinned_model: true,
+            preceding: None,
+        };
+        let chunks = split_into_chunks(&collapsed, settings.ai_chunk_max_tokens as usize).len();
+        let timeout = enhancement_timeout(
+            settings.ai_enhancement_timeout_ms,
+            chunks,
+            mode,
+            features.translate_to.is_some(),
+        );
 
-        // Generate enhanced text
-        match self.client.generate(model, &prompt).await {
-            Ok(enhanced) => {
-                info!("AI enhancement successful");
-                Ok(enhanced)
+        let started = Instant::now();
+        let enhance = self.enhance(&collapsed, &refinement.model, &request, false);
+        let refined = tokio::time::timeout(timeout, enhance)
+            .await
+            .map_err(|_| anyhow!("Refining took longer than {:?}", timeout))??;
+        self.touch_loaded_model(&settings);
+        let now = chrono::Utc::now().timestamp();
+        self.model_usage
+            .lock()
+            .unwrap()
+            .record(&refinement.model, now);
+
+        let refined = pasted_text(&refinement.transcript, &snippets, &refined);
+        let differed = differs_meaningfully(&refinement.pasted, &refined);
+        let metrics = self.metrics.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut metrics = metrics.lock().unwrap();
+            metrics.record_refinement(differed);
+            metrics.save();
+        });
+        if !differed {
+            debug!("{} refined the text no differently", refinement.model);
+            return Ok(());
+        }
+
+        info!("{} refined the pasted text", refinement.model);
+        let _ = self.app_handle.emit(
+            "ai-enhancement-refined",
+            AiEnhancementRefined {
+                request_id: refinement.request_id.clone(),
+                model: refinement.model.clone(),
+                diff: diff_words(&refinement.pasted, &refined),
+                pasted: refinement.pasted.clone(),
+                text: refined,
+                duration_ms: started.elapsed().as_millis() as u32,
+            },
+        );
+        Ok(())
+    }
+
+    /// Enhance the text pasted for the last enhancement again, with the
+    /// model and features `overrides` asks for. Its history entry is kept
+    /// as it was, and a new one linked to it is appended. Fails when no
+    /// enhancement has finished yet, or a newer dictation has started since.
+    pub async fn reenhance_last(
+        &mut self,
+        overrides: &AiReenhanceOverrides,
+    ) -> Result<AiReenhancement> {
+        let settings = get_settings(&self.app_handle);
+        let (previous, mut features, mode, reenhanced_from) = {
+            let last = self.last_enhancement.lock().unwrap();
+            let last = last
+                .as_ref()
+                .ok_or_else(|| anyhow!("No enhancement has finished yet to enhance again"))?;
+            let aborted = last.outcome
+                == EnhancementOutcome::Fallback {
+                    reason: AiFallbackReason::Aborted,
+                };
+            if last.superseded || aborted {
+                return Err(anyhow!(
+                    "A newer dictation has started since the last enhancement, so it is no longer the text to enhance again"
+                ));
             }
-            Err(e) => {
-                warn!("AI enhancement failed: {}", e);
-                Err(e)
+            (
+                last.text.clone(),
+                last.features.clone(),
+                last.mode,
+                last.history_id,
+            )
+        };
+        if previous.trim().is_empty() {
+            return Err(anyhow!(
+                "The last enhancement pasted no text to enhance again"
+            ));
+        }
+        let model = overrides
+            .model
+            .clone()
+            .or_else(|| settings.ai_selected_model.clone())
+            .ok_or_else(|| anyhow!("No AI model is selected"))?;
+        features.fix_grammar |= overrides.fix_grammar;
+
+        let text = previous.trim();
+        let request = EnhanceRequest {
+            id: self.next_request_id(),
+            features: &features,
+            mode,
+            language: detect_language(text),
+            rest: "",
+            pinned_model: true,
+            preceding: None,
+        };
+        let chunks = split_into_chunks(text, settings.ai_chunk_max_tokens as usize).len();
+        let timeout = enhancement_timeout(
+            settings.ai_enhancement_timeout_ms,
+            chunks,
+            mode,
+            features.translate_to.is_some(),
+        );
+
+        let started = Instant::now();
+        let enhance = self.enhance(text, &model, &request, false);
+        let enhanced = tokio::time::timeout(timeout, enhance)
+            .await
+            .map_err(|_| anyhow!("Enhancing again took longer than {:?}", timeout))??;
+        self.touch_loaded_model(&settings);
+        let now = chrono::Utc::now().timestamp();
+        self.model_usage.lock().unwrap().record(&model, now);
+
+        let request_id = request.id;
+        let text = to_nfc(&with_outer_whitespace(&previous, &enhanced));
+        let reenhanced = AiReenhancement {
+            model: model.clone(),
+            diff: diff_words(&previous, &text),
+            text,
+            duration_ms: started.elapsed().as_millis() as u32,
+            previous,
+        };
+
+        // Enhancing it once more goes on from this pass
+        *self.last_enhancement.lock().unwrap() = Some(LastEnhancement {
+            request_id: request_id.clone(),
+            model: model.clone(),
+            features: features.clone(),
+            mode,
+            outcome: EnhancementOutcome::Enhanced,
+            text: reenhanced.text.clone(),
+            superseded: false,
+            history_id: None,
+            rating: None,
+        });
+        if !settings.ai_history_enabled {
+            return Ok(reenhanced);
+        }
+
+        let entry = AiEnhancementHistoryEntry {
+            id: 0,
+            timestamp: now,
+            model,
+            features,
+            mode: mode.label().to_string(),
+            original_text: reenhanced.previous.clone(),
+            enhanced_text: reenhanced.text.clone(),
+            duration_ms: reenhanced.duration_ms,
+            outcome: EnhancementOutcome::Enhanced,
+            diff: reenhanced.diff.clone(),
+            rating: None,
+            reenhanced_from,
+        };
+        let (history, last) = (self.history.clone(), self.last_enhancement.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            let id = history.lock().unwrap().append(entry);
+            let mut last = last.lock().unwrap();
+            if let Some(last) = last.as_mut().filter(|last| last.request_id == request_id) {
+                last.history_id = Some(id);
+            }
+        });
+        Ok(reenhanced)
+    }
+
+    /// The best installed model expected to enhance `text` within
+    /// `ai_latency_budget_ms`, going by its benchmark, or whether none is
+    async fn fit_latency_budget(&self, text: &str, settings: &AppSettings) -> LatencyFit {
+        let budget_ms = settings.ai_latency_budget_ms;
+        if budget_ms == 0 {
+            return LatencyFit::Unknown;
+        }
+        let profiles = self.installed_latency().await;
+        if profiles.is_empty() {
+            debug!("No installed model has been benchmarked, ignoring the latency budget");
+            return LatencyFit::Unknown;
+        }
+
+        let registry_prefix = settings.ai_model_registry_prefix.as_deref();
+        let loaded = self
+            .idle_model
+            .lock()
+            .unwrap()
+            .last_used()
+            .map(|model| display_model_id(model, registry_prefix));
+        match pick_model(&profiles, text, budget_ms, loaded.as_deref()) {
+            Some(choice) => {
+                info!(
+                    "Using {} for the {} ms latency budget, expected to take {} ms",
+                    choice.model, budget_ms, choice.estimated_ms
+                );
+                LatencyFit::Fits(choice)
+            }
+            None => {
+                info!(
+                    "No installed model is expected to finish within the {} ms latency budget, skipping AI enhancement",
+                    budget_ms
+                );
+                LatencyFit::TooSlow
             }
         }
     }
 
-    /// Test enhancement with sample text
-    pub async fn test_enhancement(
-        &mut self,
-        text: &str,
+    /// How long each installed model takes, worked out again whenever the
+    /// benchmarks or the installed models changed. Empty while no Ollama
+    /// host can be reached.
+    async fn installed_latency(&self) -> Vec<ModelLatency> {
+        if let Some(profiles) = self.latency.lock().unwrap().get() {
+            return profiles;
+        }
+        if self.circuit.lock().unwrap().is_open() {
+            return Vec::new();
+        }
+        let Some(endpoint) = select_endpoint(&self.app_handle, &self.endpoints).await else {
+            return Vec::new();
+        };
+        let installed = match list_models(&self.app_handle, &endpoint.client).await {
+            Ok(installed) => installed,
+            Err(e) => {
+                debug!("Couldn't list the models for the latency budget: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let profiles = latency_profiles(
+            &self.benchmarks.reports(),
+            &get_available_models(None),
+            &installed,
+        );
+        self.latency.lock().unwrap().set(profiles.clone());
+        profiles
+    }
+
+    /// Count a failed run with `model`, which stood in for `selected`. Once
+    /// `threshold` have failed in a row, move the session to the next
+    /// smaller installed model and return it, so the transcript can be
+    /// tried again with it.
+    async fn degrade_if_failing(
+        &self,
+        selected: &str,
         model: &str,
-        features: &AiFeatures,
-    ) -> Result<String> {
-        self.enhance_text(text, model, features).await
-    }
-
-    /// Get list of available models from Ollama
-    pub async fn list_models(&self) -> Result<Vec<String>> {
-        let models = self.client.list_models().await?;
-        Ok(models.into_iter().map(|m| m.name).collect())
-    }
-
-    /// Pull a model from Ollama with progress events
-    pub async fn pull_model(&self, model: &str, app: &AppHandle) -> Result<()> {
-        info!("Pulling model: {}", model);
-        
-        let model_id = model.to_string();
-        let app_handle = app.clone();
-        
-        self.client.pull_model_with_progress(model, move |status, completed, total| {
-            let percentage = if let (Some(c), Some(t)) = (completed, total) {
-                if t > 0 {
-                    (c as f64 / t as f64) * 100.0
-                } else {
-                    0.0
-                }
-            } else {
-                0.0
-            };
-            
-            let progress = AiModelPullProgress {
-                model_id: model_id.clone(),
-                status: status.clone(),
-                completed,
-                total,
-                percentage,
-            };
-            
-            let _ = app_handle.emit("ai-model-pull-progress", progress);
-        }).await?;
-        
-        // Emit completion event
-        let _ = app.emit("ai-model-pull-complete", model.to_string());
-        
-        Ok(())
+        result: &Result<EnhancedText, EnhancementError>,
+        threshold: u32,
+    ) -> Option<String> {
+        let reason = model_failure(result)?;
+        let failures = self
+            .degradation
+            .lock()
+            .unwrap()
+            .record_failure(model, threshold)?;
+
+        let endpoint = select_endpoint(&self.app_handle, &self.endpoints).await?;
+        let installed = match list_models(&self.app_handle, &endpoint.client).await {
+            Ok(installed) => installed,
+            Err(e) => {
+                debug!("Couldn't list the models to fall back on: {}", e);
+                return None;
+            }
+        };
+        let Some(smaller) = smaller_model(model, &get_available_models(None), &installed) else {
+            warn!(
+                "{} failed {} times in a row, and no smaller model is installed to fall back on",
+                model, failures
+            );
+            return None;
+        };
+
+        warn!(
+            "{} failed {} times in a row, using {} for the rest of the session",
+            model, failures, smaller
+        );
+        self.degradation.lock().unwrap().degrade(selected, &smaller);
+        let _ = self.app_handle.emit(
+            "ai-model-degraded",
+            AiModelDegraded {
+                model: selected.to_string(),
+                fallback_model: smaller.clone(),
+                failures,
+                reason,
+            },
+        );
+        Some(smaller)
     }
 
-    /// Delete a model
-    pub async fn delete_model(&self, model: &str) -> Result<()> {
-        info!("Deleting model: {}", model);
-        self.client.delete_model(model).await
+    /// Count a run in the metrics, and in the day's usage stats when
+    /// `usage_stats` is enabled, and save them off the hot path.
+    /// `generated_before` is the generation totals from before it started.
+    fn record_metrics(
+        &self,
+        result: &Result<EnhancedText, EnhancementError>,
+        duration_ms: u32,
+        generated_before: (u64, u64),
+        usage_stats: bool,
+    ) {
+        let (tokens, nanos) = generation_totals();
+        let run = EnhancementRun {
+            outcome: result
+                .as_ref()
+                .ok()
+                .map(|enhanced| enhanced.outcome.clone()),
+            duration_ms,
+            reached_model: self.last_run.reached_model,
+            cached: self.last_run.cached,
+            rejected_by: self.last_run.rejected_by,
+            retries: self.last_run.retries,
+            truncated: self.last_run.truncated,
+            generated_tokens: tokens.saturating_sub(generated_before.0),
+            generation_nanos: nanos.saturating_sub(generated_before.1),
+        };
+        let metrics = self.metrics.clone();
+        let usage = usage_stats.then(|| (self.usage.clone(), self.created.elapsed()));
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut metrics = metrics.lock().unwrap();
+            metrics.record_run(&run);
+            metrics.save();
+            if let Some((usage, since_start)) = usage {
+                let mut usage = usage.lock().unwrap();
+                usage.record_run(&run, chrono::Local::now().date_naive(), since_start);
+                usage.save();
+            }
+        });
     }
 
-    /// Get current model
-    pub fn get_current_model(&self) -> Option<String> {
-        self.current_model.clone()
+    /// Handle on the recently pasted transcripts, registered as app state so
+    /// pasting and undoing never wait for an enhancement to finish
+    pub fn recent_transcripts(&self) -> SharedRecentTranscripts {
+        self.recent.clone()
     }
-}
 
-impl Default for AiEnhancementManager {
-    fn default() -> Self {
-        Self::new()
+    /// Handle on the enhancement being generated, registered as app state so
+    /// it can be aborted without waiting for the manager
+    pub fn in_flight(&self) -> SharedInFlight {
+        self.in_flight.clone()
     }
-}
 
-/// Type alias for thread-safe AI manager
+    /// Handle on the preceding text shown to the model, registered as app
+    /// state so it can be cleared without waiting for the manager
+    pub fn session_context(&self) -> SharedSessionContext {
+        self.context.clone()
+    }
+
+    async fn run_enhancement(
+        &mut self,
+        request_id: &str,
+        text: &str,
+        model: &str,
+        features: &AiFeatures,
+        mode: EnhanceMode,
+        preceding: Option<&str>,
+    ) -> Result<EnhancedText, EnhancementError> {
+        // Stutters and hesitations are cheaper to drop here than to send to
+        // the model
+        let collapsed = remove_by_rule(text, features, mode);
+        let text = collapsed.as_str();
+        let settings = get_settings(&self.app_handle);
+        if let Some(reason) = skip_reason(text, features, settings.ai_min_words_for_enhancement) {
+            info!("Skipping AI enhancement for short text ({:?})", reason);
+            return Ok(EnhancedText {
+                text: text.to_string(),
+                outcome: EnhancementOutcome::Skipped { reason },
+            });
+        }
+
+        let language = detect_language(text);
+        let translating = features.translate_to.is_some();
+        if !translating
+            && should_skip_language(
+                language.as_ref(),
+                settings.ai_language_policy,
+                &settings.ai_languages,
+            )
+        {
+            info!(
+                "Skipping AI enhancement for text in an unconfigured language ({:?})",
+                language
+            );
+            return Ok(EnhancedText {
+                text: text.to_string(),
+                outcome: EnhancementOutcome::Skipped {
+                    reason: AiSkipReason::Language,
+                },
+            });
+        }
+
+        // Long dictations are handled as configured, so the paste doesn't
+        // keep them waiting
+        let max_words = settings.ai_max_words_for_enhancement as usize;
+        let Some((text, rest)) = limit_length(text, max_words, settings.ai_long_text_policy, mode)
+        else {
+            info!("Skipping AI enhancement for text over {} words", max_words);
+            return Ok(EnhancedText {
+                text: text.to_string(),
+                outcome: EnhancementOutcome::Skipped {
+                    reason: AiSkipReason::TooLong,
+                },
+            });
+        };
+
+        // A phrase dictated again is answered without asking the model,
+        // unless what came before it could change how it is corrected
+        let cache_context = (settings.ai_cache_enabled && preceding.is_none())
+            .then(|| cache_context(&settings, model, features, mode));
+        if let Some(context) = &cache_context {
+            self.cache.set_capacity(settings.ai_cache_size as usize);
+            let cached = self.cache.get(context, text);
+            self.metrics
+                .lock()
+                .unwrap()
+                .record_cache_lookup(cached.is_some());
+            if let Some(cached) = cached {
+                debug!("Using the cached enhancement of this text");
+                self.last_run.cached = true;
+                let stats = AiEnhancementStats {
+                    model: model.to_string(),
+                    duration_ms: 0,
+                    streamed: false,
+                    language,
+                    cached: true,
+                    latency_choice: self.last_run.latency_choice.clone(),
+                };
+                self.last_run.completion = Some(Completion::new(stats, features, mode, rest));
+                return Ok(EnhancedText {
+                    text: format!("{}{}", cached, rest),
+                    outcome: EnhancementOutcome::Enhanced,
+                });
+            }
+        }
+
+        let chunks = split_into_chunks(text, settings.ai_chunk_max_tokens as usize).len();
+        let timeout = enhancement_timeout(
+            settings.ai_enhancement_timeout_ms,
+            chunks,
+            mode,
+            translating,
+        );
+        let request = EnhanceRequest {
+            id: request_id.to_string(),
+            features,
+            mode,
+            language,
+            rest,
+            pinned_model: self.last_run.latency_choice.is_some(),
+            preceding,
+        };
+
+        // While no Ollama host can be reached the transcript is kept as it
+        // is, rather than waiting out the connection every time
+        if self.circuit.lock().unwrap().is_open() {
+            debug!("Ollama is unreachable, not asking it for an enhancement");
+            let unavailable = async { Err(OllamaError::Unavailable.into()) };
+            let mut result =
+                with_fallback(text, timeout, settings.ai_fallback_to_original, unavailable).await;
+            if let Ok(enhanced) = &mut result {
+                enhanced.text.push_str(rest);
+            }
+            return result;
+        }
+
+        self.last_run.reached_model = true;
+        let in_flight = self.in_flight.clone();
+        let aborted = in_flight.start(&request.id, &collapsed);
+        // Dropping the generation when aborted closes its request to Ollama
+        let enhance = self.enhance(text, model, &request, true);
+        let mut result = with_fallback(text, timeout, settings.ai_fallback_to_original, async {
+            tokio::select! {
+                result = enhance => result,
+                Ok(()) = aborted => Err(Aborted.into()),
+            }
+        })
+        .await;
+
+        // An abort that got to the result first has already delivered the
+        // original transcript in its place
+        if !in_flight.settle(&request.id) {
+            info!("AI enhancement {} aborted", request.id);
+            result = Ok(EnhancedText {
+                text: text.to_string(),
+                outcome: EnhancementOutcome::Fallback {
+                    reason: AiFallbackReason::Aborted,
+                },
+            });
+        }
+        self.record_reachability(&result);
+
+        // Only results that made it past the guard are worth repeating
+        if let (Some(context), Ok(enhanced)) = (&cache_context, &result) {
+            if enhanced.outcome == EnhancementOutcome::Enhanced {
+                self.cache.insert(context, text, &enhanced.text);
+            }
+        }
+
+        // The words past the maximum follow whatever became of the rest
+        if let Ok(enhanced) = &mut result {
+            enhanced.text.push_str(rest);
+        }
+        result
+    }
+
+    /// Count whether the enhancement found an Ollama host, opening the
+    /// circuit after [`CIRCUIT_BREAKER_THRESHOLD`] in a row that didn't
+    ///
+    /// [`CIRCUIT_BREAKER_THRESHOLD`]: crate::managers::ai_circuit_breaker::CIRCUIT_BREAKER_THRESHOLD
+    fn record_reachability(&self, result: &Result<EnhancedText, EnhancementError>) {
+        match Terminal::of(result) {
+            Terminal::Aborted => {}
+            Terminal::Fallback(AiFallbackReason::OllamaUnavailable) => {
+                let opened = self.circuit.lock().unwrap().record_failure(Instant::now());
+                if let Some(failures) = opened {
+                    open_circuit(&self.app_handle, &self.endpoints, &self.circuit, failures);
+                }
+            }
+            _ => {
+                if self.circuit.lock().unwrap().record_success() {
+                    announce_circuit(&self.app_handle, false, 0);
+                }
+            }
+        }
+    }
+
+    fn emit_started(&self, request_id: &str, text: &str, model: &str) {
+        let _ = self.app_handle.emit(
+            "ai-enhancement-started",
+            AiEnhancementStarted {
+                request_id: request_id.to_string(),
+                word_count: text.split_whitespace().count() as u32,
+                model: model.to_string(),
+            },
+        );
+    }
+
+    /// Emit the event ending `request_id`, unless an abort has sent it
+    /// already. `text` is what was pasted in place of `original`.
+    fn emit_terminal(&self, request_id: &str, terminal: Terminal, original: &str, text: &str) {
+        let _ = match terminal {
+            Terminal::Aborted => return,
+            Terminal::Complete => {
+                let Some(completion) = self.last_run.completion.clone() else {
+                    return;
+                };
+                // The last partial event always matches the text that gets pasted
+                if completion.stats.streamed {
+                    self.emit_partial(request_id, text);
+                }
+                let settings = get_settings(&self.app_handle);
+                self.app_handle.emit(
+                    "ai-enhancement-complete",
+                    completion.event(request_id, original, text, &settings),
+                )
+            }
+            Terminal::Fallback(reason) => self.app_handle.emit(
+                "ai-enhancement-fallback",
+                AiEnhancementFallback {
+                    request_id: request_id.to_string(),
+                    reason,
+                    text: text.to_string(),
+                },
+            ),
+            Terminal::Skipped(reason) => self.app_handle.emit(
+                "ai-enhancement-skipped",
+                AiEnhancementSkipped {
+                    request_id: request_id.to_string(),
+                    reason,
+                    text: text.to_string(),
+                },
+            ),
+        };
+    }
+
+    /// Test enhancement with sample text. It is announced and ends like any
+    /// other, but failures are reported rather than falling back. Short text
+    /// is enhanced too, so the preview always shows what the features do.
+    pub async fn test_enhancement(
+        &mut self,
+        text: &str,
+        model: &str,
+        features: &AiFeatures,
+        mode: EnhanceMode,
+    ) -> Result<EnhancedText, EnhancementError> {
+        let collapsed = remove_by_rule(text, features, mode);
+        let text = collapsed.as_str();
+        let request = EnhanceRequest {
+            id: self.next_request_id(),
+            features,
+            mode,
+            language: detect_language(text),
+            rest: "",
+            pinned_model: false,
+            preceding: None,
+        };
+        self.emit_started(&request.id, text, model);
+        let result = self
+            .enhance(text, model, &request, false)
+            .await
+            .map(|text| EnhancedText {
+                text,
+                outcome: EnhancementOutcome::Enhanced,
+            })
+            .map_err(|e| EnhancementError::from_error(&e));
+        let pasted = match &result {
+            Ok(enhanced) => enhanced.text.as_str(),
+            Err(_) => text,
+        };
+        self.emit_terminal(&request.id, Terminal::of(&result), text, pasted);
+        result
+    }
+
+    /// Correct `text` with exactly `model`, for comparing it with another
+    /// model on the same text. A failure is reported as part of the result.
+    pub async fn compare_enhancement(
+        &mut self,
+        text: &str,
+        model: &str,
+        features: &AiFeatures,
+    ) -> AiModelComparisonSide {
+        let collapsed = remove_by_rule(text, features, EnhanceMode::Correct);
+        let request = EnhanceRequest {
+            id: self.next_request_id(),
+            features,
+            mode: EnhanceMode::Correct,
+            language: detect_language(&collapsed),
+            rest: "",
+            pinned_model: true,
+            preceding: None,
+        };
+        self.emit_started(&request.id, &collapsed, model);
+
+        let started = Instant::now();
+        let result = self.enhance(&collapsed, model, &request, false).await;
+        let latency_ms = started.elapsed().as_millis() as u32;
+        match result {
+            Ok(enhanced) => {
+                self.emit_terminal(&request.id, Terminal::Complete, &collapsed, &enhanced);
+                AiModelComparisonSide {
+                    model: model.to_string(),
+                    diff: diff_words(text, &enhanced),
+                    text: Some(enhanced),
+                    latency_ms,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                warn!("Comparing {} failed: {:#}", model, e);
+                let error = EnhancementError::from_error(&e);
+                self.emit_terminal(
+                    &request.id,
+                    Terminal::of_error(&error),
+                    &collapsed,
+                    &collapsed,
+                );
+                AiModelComparisonSide {
+                    model: model.to_string(),
+                    text: None,
+                    diff: None,
+                    latency_ms,
+                    error: Some(error),
+                }
+            }
+        }
+    }
+
+    fn next_request_id(&mut self) -> String {
+        self.next_request_id += 1;
+        format!("enhance-{}", self.next_request_id)
+    }
+
+    async fn enhance(
+        &mut self,
+        text: &str,
+        model: &str,
+        request: &EnhanceRequest<'_>,
+        stream: bool,
+    ) -> Result<String> {
+        let (request_id, features, mode) = (request.id.as_str(), request.features, request.mode);
+        let language = request.language.as_ref();
+        let transcript = text;
+
+        // Pick the endpoint once so a single utterance is never split across hosts
+        let Some(endpoint) = select_endpoint(&self.app_handle, &self.endpoints).await else {
+            return Err(OllamaError::Unavailable.into());
+        };
+        let model = match &endpoint.config.preferred_model {
+            Some(preferred) if !request.pinned_model => preferred.clone(),
+            _ => model.to_string(),
+        };
+
+        // Update current model
+        self.current_model = Some(model.clone());
+        let settings = get_settings(&self.app_handle);
+        let registry_prefix = settings.ai_model_registry_prefix.clone();
+        let registry_model = qualify_model_id(&model, registry_prefix.as_deref());
+
+        // Transcripts keep their original text until the download completes
+        if self.pulls.auto.lock().unwrap().is_pulling(&model) {
+            debug!("{} is still downloading, not enhancing yet", model);
+            return Err(OllamaError::ModelNotFound {
+                model: registry_model,
+            }
+            .into());
+        }
+
+        let summary_language = output_language(features, language);
+        // Separators the model wrote for another locale are put right by
+        // rule, and so are small numbers it wrote in digits against the style
+        let number_locale = (features.normalize_numbers && mode.writes_prose())
+            .then(|| resolve_number_locale(features.number_locale, summary_language));
+
+        // The casing of each chunk's start and of "I" is left to a summary's
+        // own layout, and code is never capitalized
+        let summarizing = matches!(mode, EnhanceMode::Summarize { .. });
+        let capitalization =
+            (features.punctuation_and_capitalization && !summarizing && mode.writes_prose())
+                .then_some(features.capitalization_style);
+        let english = summary_language == Some("English");
+        // English words are held to the chosen spelling, or to the one they
+        // were dictated in, whichever the model wrote
+        let spelling_variant = (features.fix_spelling && english && mode.writes_prose())
+            .then_some(features.spelling_variant);
+        // Contractions too, though the ambiguous ones are the model's alone
+        let contraction_style =
+            (english && mode.writes_prose()).then_some(features.contraction_style);
+
+        // Known names are written correctly before the model sees them, and
+        // again afterwards in case it "corrected" them away
+        let vocabulary = &settings.ai_vocabulary;
+        let (protected, text) = model_input(text, features, mode, vocabulary);
+        let passes = OutputPasses {
+            vocabulary,
+            proper_nouns: &settings.ai_proper_nouns,
+            number_locale,
+            number_style_threshold: features.number_style_threshold,
+            capitalization,
+            english,
+            spelling_variant,
+            contraction_style,
+        };
+
+        let sampling = sampling_options(&settings.ai_generation_options, mode, features);
+        let options = OllamaOptions {
+            temperature: sampling.temperature,
+            num_predict: sampling.max_output_tokens as i32,
+            repeat_penalty: Some(sampling.repeat_penalty),
+            ..self.generation_options()
+        };
+        let chunks = model_chunks(&text, mode, settings.ai_chunk_max_tokens);
+        if chunks.len() > 1 {
+            info!("Enhancing transcript in {} chunks", chunks.len());
+        }
+
+        // Generate enhanced text
+        let client = endpoint.client.clone();
+        let output_guard = &settings.ai_output_guard;
+        let retries = AtomicU32::new(0);
+        let truncated = AtomicU32::new(0);
+        let started = Instant::now();
+        let this = &*self;
+        let result = enhance_chunks(&chunks, |chunk_index, chunk, done| {
+            if chunks.len() > 1 {
+                let _ = this.app_handle.emit(
+                    "ai-enhancement-progress",
+                    AiEnhancementProgress {
+                        request_id: request_id.to_string(),
+                        chunk: chunk_index as u32 + 1,
+                        total_chunks: chunks.len() as u32,
+                    },
+                );
+            }
+
+            let FittedPrompt { prompt, dropped } = chunk_prompt(
+                chunk,
+                features,
+                mode,
+                language,
+                request.preceding,
+                &settings,
+            );
+            if !dropped.is_empty() {
+                info!(
+                    "Left {:?} out of the prompt for chunk {} to fit the context",
+                    dropped,
+                    chunk_index + 1
+                );
+            }
+            // The transcript goes to the model in a message of its own, so
+            // that what it says is less likely taken for instructions
+            let chat = chat_prompt(&prompt, chunk);
+            // A summary is much shorter than what it summarizes. Anything
+            // else gets room for output a little longer than its input, as
+            // a fixed limit cut long corrections off mid-sentence.
+            let options = match mode {
+                EnhanceMode::Summarize { .. } => options.clone(),
+                _ => OllamaOptions {
+                    num_predict: output_budget(chunk, &prompt, DEFAULT_CONTEXT_TOKENS)
+                        .min(options.num_predict),
+                    ..options.clone()
+                },
+            };
+            let (client, model) = (&client, &registry_model);
+            let (protected, retries, truncated) = (&protected, &retries, &truncated);
+            async move {
+                // Empty or rejected output gets one more try before the
+                // chunk keeps its original text
+                let (prompt, done) = (&prompt, &done);
+                let request = match &chat {
+                    Some(chat) => Prompt::Chat {
+                        system: &chat.system,
+                        user: &chat.user,
+                    },
+                    None => Prompt::Completion(prompt),
+                };
+                let (output, retried) = with_retry(&options, |attempt, options| async move {
+                    let output = if stream {
+                        this.generate_streaming(client, model, request, &options, |streamed| {
+                            let partial = format!("{}{}", done, streamed);
+                            this.emit_partial(request_id, &protected.reveal(&partial));
+                        })
+                        .await
+                    } else {
+                        client.generate(model, request, &options).await
+                    };
+                    if let Ok(raw) = &output {
+                        debug!(
+                            "Raw output of attempt {} for chunk {}: {:?}",
+                            attempt,
+                            chunk_index + 1,
+                            raw
+                        );
+                    }
+                    let output = output.and_then(|output| passes.apply(chunk, prompt, &output))?;
+                    // A summary is meant to be much shorter than the transcript
+                    if !summarizing {
+                        check_length(chunk, &output, output_guard, features, mode)?;
+                    }
+                    Ok(output)
+                })
+                .await;
+                if retried {
+                    retries.fetch_add(1, Ordering::Relaxed);
+                }
+                // The chunk keeps its original text rather than losing its end
+                if let Err(e) = &output {
+                    if let Some(OllamaError::Truncated { .. }) = e.downcast_ref::<OllamaError>() {
+                        truncated.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                output
+            }
+        })
+        .await
+        .and_then(|enhanced| protected.restore(graphemes::trim(&enhanced)))
+        // Chunks that each pass can still add up to text that doesn't, which
+        // has to be caught before the result is announced
+        .and_then(|enhanced| {
+            if !summarizing {
+                check_length(transcript, &enhanced, output_guard, features, mode)?;
+            }
+            Ok(enhanced)
+        });
+        self.last_run.retries += retries.into_inner();
+        self.last_run.truncated += truncated.into_inner();
+
+        match result {
+            // Claimed before it is announced, so an abort racing it either
+            // wins outright or finds nothing left to abort
+            Ok(_) if !self.in_flight.settle(request_id) => Err(Aborted.into()),
+            Ok(enhanced) => {
+                info!("AI enhancement successful");
+                let restored = self.degradation.lock().unwrap().record_success(&model);
+                if let Some(restored) = restored {
+                    info!("{} works again, going back to it", restored);
+                    let _ = self.app_handle.emit("ai-model-restored", restored);
+                }
+                let stats = AiEnhancementStats {
+                    model,
+                    duration_ms: started.elapsed().as_millis() as u32,
+                    streamed: stream,
+                    language: language.cloned(),
+                    cached: false,
+                    latency_choice: self.last_run.latency_choice.clone(),
+                };
+                self.last_run.completion =
+                    Some(Completion::new(stats, features, mode, request.rest));
+                Ok(enhanced)
+            }
+            Err(e) => {
+                warn!("AI enhancement failed: {}", e);
+                match e.downcast_ref::<OllamaError>() {
+                    Some(OllamaError::ModelNotFound { model }) => {
+                        // Download it, or let the frontend offer to
+                        let model = display_model_id(model, registry_prefix.as_deref());
+                        let endpoint = Some(endpoint.config.base_url.clone());
+                        if !self.pulls.pull_missing(&model, endpoint) {
+                            let _ = self.app_handle.emit("ai-model-missing", model);
+                        }
+                    }
+                    // The host answered, it just ran out of output tokens
+                    Some(OllamaError::Truncated { .. }) => {}
+                    // The host answered, the model just didn't produce a usable correction
+                    None if e.is::<RejectedOutput>() => {
+                        self.last_run.rejected_by = e
+                            .downcast_ref::<RejectedOutput>()
+                            .map(|rejected| rejected.0);
+                    }
+                    // Re-check this host before the next utterance instead of trusting the cache
+                    _ => self.endpoints.forget_health(&endpoint),
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Generate with streaming, passing the text streamed so far to
+    /// `on_partial` at most once per [`PARTIAL_EVENT_INTERVAL`]
+    async fn generate_streaming(
+        &self,
+        client: &OllamaClient,
+        model: &str,
+        prompt: Prompt<'_>,
+        options: &OllamaOptions,
+        mut on_partial: impl FnMut(&str),
+    ) -> Result<String> {
+        let mut streamed = String::new();
+        let mut last_emitted: Option<Instant> = None;
+
+        client
+            .generate_stream(model, prompt, options, |fragment| {
+                streamed.push_str(fragment);
+                if last_emitted.is_some_and(|at| at.elapsed() < PARTIAL_EVENT_INTERVAL) {
+                    return;
+                }
+                last_emitted = Some(Instant::now());
+                on_partial(graphemes::trim(&streamed));
+            })
+            .await
+    }
+
+    fn emit_partial(&self, request_id: &str, text_so_far: &str) {
+        let _ = self.app_handle.emit(
+            "ai-enhancement-partial",
+            AiEnhancementPartial {
+                request_id: request_id.to_string(),
+                text_so_far: text_so_far.to_string(),
+            },
+        );
+    }
+
+    /// Get current model
+    pub fn get_current_model(&self) -> Option<String> {
+        self.current_model.clone()
+    }
+
+    /// Forget every cached enhancement
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn cache_stats(&self) -> AiEnhancementCacheStats {
+        AiEnhancementCacheStats {
+            entries: self.cache.len() as u32,
+            hits: self.cache.hits() as u32,
+            misses: self.cache.misses() as u32,
+        }
+    }
+}
+
+/// The prompts enhancing `text` in `mode` with `settings` would send, built
+/// the way [`AiEnhancementManager::enhance_text`] builds them but without
+/// asking the model, after the replacement rules a dictation gets first.
+/// `preceding` is the text dictated last, shown as context when that is
+/// enabled.
+pub fn preview_prompts(
+    text: &str,
+    settings: &AppSettings,
+    mode: EnhanceMode,
+    preceding: Option<&str>,
+) -> AiPromptPreview {
+    let features = &settings.ai_features;
+    let text = apply_replacement_rules(text, &settings.ai_replacement_rules, AiRuleStage::BeforeAi);
+    let text = snippets_in(&text, settings, mode);
+    let text = remove_by_rule(text.text(), features, mode);
+    let language = detect_language(&text);
+    let summarizing = matches!(mode, EnhanceMode::Summarize { .. });
+    let preceding = preceding.filter(|_| settings.ai_context_enabled && !summarizing);
+
+    let fitted = prompts_for(&text, settings, mode, language.as_ref(), preceding);
+    let estimated_tokens: usize = fitted
+        .iter()
+        .map(|fitted| estimate_tokens(&fitted.prompt))
+        .sum();
+    let mut dropped: Vec<AiPromptComponent> = vec![];
+    for component in fitted.iter().flat_map(|fitted| &fitted.dropped) {
+        if !dropped.contains(component) {
+            dropped.push(*component);
+        }
+    }
+    AiPromptPreview {
+        prompts: fitted.into_iter().map(|fitted| fitted.prompt).collect(),
+        estimated_tokens: estimated_tokens as u32,
+        dropped,
+        language,
+    }
+}
+
+/// The prompt for each chunk of `text`, the transcript after the rule passes
+fn prompts_for(
+    text: &str,
+    settings: &AppSettings,
+    mode: EnhanceMode,
+    language: Option<&DetectedLanguage>,
+    preceding: Option<&str>,
+) -> Vec<FittedPrompt> {
+    let features = &settings.ai_features;
+    let (_, text) = model_input(text, features, mode, &settings.ai_vocabulary);
+    model_chunks(&text, mode, settings.ai_chunk_max_tokens)
+        .into_iter()
+        .filter(|chunk| !chunk.trim().is_empty())
+        .map(|chunk| chunk_prompt(chunk.trim(), features, mode, language, preceding, settings))
+        .collect()
+}
+
+/// The snippets said in `text`. Only prose is checked for them, and not a
+/// summary, which would leave them out.
+fn snippets_in(text: &str, settings: &AppSettings, mode: EnhanceMode) -> SnippetText {
+    let summarizing = matches!(mode, EnhanceMode::Summarize { .. });
+    let snippets = if mode.writes_prose() && !summarizing {
+        settings.ai_snippets.as_slice()
+    } else {
+        &[]
+    };
+    find_snippets(text, snippets, settings.ai_snippet_strictness)
+}
+
+/// What is pasted for `transcript` once it has been enhanced into `text`:
+/// its snippets inserted, and the transcript's outer whitespace kept.
+/// Accents are pasted composed, however the model or a snippet wrote them,
+/// so the text can be searched for as typed.
+fn pasted_text(transcript: &str, snippets: &SnippetText, text: &str) -> String {
+    to_nfc(&with_outer_whitespace(transcript, &snippets.expand(text)))
+}
+
+/// `text` as the model gets it, and what it is restored from. Links, code,
+/// snippet markers and spoken emoji are hidden behind placeholders, so
+/// neither they nor the vocabulary pass can rewrite them, and the emoji are
+/// converted after enhancement. A note's structure is written as Markdown by rule first,
+/// and its syntax hidden the same way, as are code's symbols, numbers and
+/// identifiers. A summary is free to leave all of it
+/// out, so it gets the text as it is.
+fn model_input(
+    text: &str,
+    features: &AiFeatures,
+    mode: EnhanceMode,
+    vocabulary: &[AiVocabularyEntry],
+) -> (ProtectedText, String) {
+    let summarizing = matches!(mode, EnhanceMode::Summarize { .. });
+    let protected = if summarizing {
+        ProtectedText::unprotected(text)
+    } else {
+        let text = match mode {
+            EnhanceMode::Markdown => write_markdown(text),
+            EnhanceMode::Code => write_code(text),
+            _ => text.to_string(),
+        };
+        let mut ranges = match mode {
+            EnhanceMode::Markdown => markdown_spans(&text),
+            EnhanceMode::Code => code_spans(&text),
+            _ => vec![],
+        };
+        let overlaps = |ranges: &[Range<usize>], span: &Range<usize>| {
+            ranges
+                .iter()
+                .any(|range| range.start < span.end && span.start < range.end)
+        };
+        let markers: Vec<_> = snippet_marker_spans(&text)
+            .into_iter()
+            .filter(|span| !overlaps(&ranges, span))
+            .collect();
+        ranges.extend(markers);
+        if features.convert_spoken_emoji && mode.writes_prose() {
+            let emoji = spoken_emoji_spans(&text, &features.spoken_emoji);
+            let emoji: Vec<_> = emoji
+                .into_iter()
+                .filter(|span| !overlaps(&ranges, span))
+                .collect();
+            ranges.extend(emoji);
+        }
+        protect_ranges(&text, features.protect_spans, ranges)
+    };
+    let text = apply_vocabulary(protected.text(), vocabulary);
+    (protected, text)
+}
+
+/// Long transcripts are corrected in sentence-aligned chunks so none of them
+/// runs into the output length limit. A summary needs all of it, and so does
+/// an email for its greeting and closing, unless it is too long to fit the
+/// model's context with its prompt. A note's lines stand on their own, and
+/// so does code.
+fn model_chunks(text: &str, mode: EnhanceMode, max_tokens: u32) -> Vec<&str> {
+    let fits = max_input_tokens(DEFAULT_CONTEXT_TOKENS);
+    match mode {
+        EnhanceMode::Correct | EnhanceMode::Markdown | EnhanceMode::Code => {
+            split_into_chunks(text, (max_tokens as usize).min(fits))
+        }
+        EnhanceMode::Summarize { .. } | EnhanceMode::EmailFormat => {
+            if estimate_tokens(text) > fits {
+                split_into_chunks(text, fits)
+            } else {
+                vec![text]
+            }
+        }
+    }
+}
+
+/// The language the result is written in: the translation target, or the
+/// transcript's own when it was told with confidence
+fn output_language<'a>(
+    features: &'a AiFeatures,
+    language: Option<&'a DetectedLanguage>,
+) -> Option<&'a str> {
+    features
+        .translate_to
+        .as_deref()
+        .map(|code| language_name_for(code).unwrap_or(code))
+        .or(confident_language(language))
+}
+
+/// Mixed-language transcripts can't be pinned to one language, so the
+/// prompt only tells the model not to translate them
+fn confident_language(language: Option<&DetectedLanguage>) -> Option<&str> {
+    language
+        .filter(|language| language.is_confident())
+        .map(|language| language.name.as_str())
+}
+
+/// The prompt for `chunk` in `mode`, with the template, examples and names
+/// of `settings`. Those and the context are left out, as few as need be,
+/// when the prompt wouldn't leave the model room for its output.
+fn chunk_prompt(
+    chunk: &str,
+    features: &AiFeatures,
+    mode: EnhanceMode,
+    language: Option<&DetectedLanguage>,
+    preceding: Option<&str>,
+    settings: &AppSettings,
+) -> FittedPrompt {
+    let layout = match mode {
+        EnhanceMode::Correct => Layout::Kept,
+        EnhanceMode::EmailFormat => Layout::Email,
+        EnhanceMode::Markdown => Layout::Markdown,
+        EnhanceMode::Summarize { style, max_words } => {
+            let language = output_language(features, language);
+            return FittedPrompt {
+                prompt: build_summary_prompt(chunk, style, max_words, language),
+                dropped: vec![],
+            };
+        }
+        EnhanceMode::Code => {
+            return FittedPrompt {
+                prompt: build_code_prompt(chunk),
+                dropped: vec![],
+            }
+        }
+    };
+    let language_name = confident_language(language);
+    let template = settings.ai_custom_prompt_template.as_deref();
+    let examples = &examples_in_language(&settings.ai_examples, language);
+    let known_names = &names_in(chunk, &settings.ai_proper_nouns);
+    let extras = PromptExtras {
+        user_examples: examples,
+        known_names,
+        preceding,
+        built_in_examples: true,
+        all_instructions: settings.ai_prompt_all_instructions,
+    };
+    fit_prompt(
+        prompt_budget(chunk, DEFAULT_CONTEXT_TOKENS),
+        extras,
+        |extras| assemble_prompt(chunk, features, language_name, template, extras, layout),
+    )
+}
+
+/// Everything besides the transcript that an enhancement's result depends
+/// on, as the context it is cached in. Changing any of it drops the cache.
+fn cache_context(
+    settings: &AppSettings,
+    model: &str,
+    features: &AiFeatures,
+    mode: EnhanceMode,
+) -> String {
+    serde_json::json!({
+        "prompt_version": PROMPT_VERSION,
+        "model": model,
+        "features": features,
+        "mode": mode,
+        "template": settings.ai_custom_prompt_template,
+        "all_instructions": settings.ai_prompt_all_instructions,
+        "examples": settings.ai_examples,
+        "vocabulary": settings.ai_vocabulary,
+        "proper_nouns": settings.ai_proper_nouns,
+    })
+    .to_string()
+}
+
+/// Key of a request to enhance `text`, the same for every request that
+/// would come out the same
+pub fn request_key(
+    settings: &AppSettings,
+    text: &str,
+    model: &str,
+    features: &AiFeatures,
+    mode: EnhanceMode,
+) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    cache_context(settings, model, features, mode).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Why `text` should be passed through untouched, if it should. Text without
+/// letters never reaches the model; text under `min_words` words only does
+/// when it is to be translated.
+fn skip_reason(text: &str, features: &AiFeatures, min_words: u32) -> Option<AiSkipReason> {
+    if !text.chars().any(char::is_alphabetic) {
+        Some(AiSkipReason::NoLetters)
+    } else if features.translate_to.is_none()
+        && text.split_whitespace().count() < min_words as usize
+    {
+        Some(AiSkipReason::TooShort)
+    } else {
+        None
+    }
+}
+
+/// The configured sampling options for `mode`, rewriting in a tone counting
+/// as a kind of its own
+fn sampling_options(
+    options: &AiGenerationOptions,
+    mode: EnhanceMode,
+    features: &AiFeatures,
+) -> AiSamplingOptions {
+    match mode {
+        EnhanceMode::Summarize { .. } => options.summarize,
+        _ if features.tone.is_some() => options.tone,
+        _ => options.correct,
+    }
+}
+
+/// The part of `text` to enhance and the part to paste as dictated after it,
+/// or `None` when it is too long to enhance at all. Only transcripts over
+/// `max_words` words are affected, and never summaries, which are always of
+/// all of it.
+fn limit_length(
+    text: &str,
+    max_words: usize,
+    policy: AiLongTextPolicy,
+    mode: EnhanceMode,
+) -> Option<(&str, &str)> {
+    let summarizing = matches!(mode, EnhanceMode::Summarize { .. });
+    if summarizing || text.split_whitespace().count() <= max_words {
+        return Some((text, ""));
+    }
+
+    match policy {
+        AiLongTextPolicy::Skip => None,
+        AiLongTextPolicy::Chunked => {
+            info!("Enhancing all of a text over {} words", max_words);
+            Some((text, ""))
+        }
+        AiLongTextPolicy::FirstWords => {
+            info!("Enhancing only the first {} words of the text", max_words);
+            Some(split_after_words(text, max_words))
+        }
+    }
+}
+
+/// `text` split after its first `words` words, the second part keeping the
+/// whitespace that separated them
+fn split_after_words(text: &str, words: usize) -> (&str, &str) {
+    let mut in_word = false;
+    let mut seen = 0;
+    for (index, c) in text.char_indices() {
+        if c.is_whitespace() {
+            if in_word && seen == words {
+                return text.split_at(index);
+            }
+            in_word = false;
+        } else if !in_word {
+            in_word = true;
+            seen += 1;
+        }
+    }
+    (text, "")
+}
+
+/// The transcript with spoken punctuation written out, the fillers that
+/// can't be meant dropped, accidental repetitions collapsed and profanity
+/// masked, as far as each is enabled. Masked here, the words never reach the
+/// model. Punctuation goes first, as it sets fillers off as clauses. Code
+/// has its symbols written by its own rules instead.
+fn remove_by_rule(text: &str, features: &AiFeatures, mode: EnhanceMode) -> String {
+    let mut text = text.to_string();
+    if features.spoken_punctuation && mode.writes_prose() {
+        text = apply_spoken_punctuation(&text, &features.spoken_commands);
+    }
+    if features.remove_filler_words {
+        text = strip_fillers(&text, &features.filler_words);
+    }
+    if features.remove_repetitions {
+        text = collapse_repetitions(&text);
+    }
+    if features.mask_profanity {
+        text = mask_profanity(&text, &features.profane_words, features.profanity_mask);
+    }
+    text
+}
+
+/// Check `output` is about as long as `original` should come out with
+/// `features` in `mode`, and keeps enough of its words. A translation or a
+/// change of tone rewords the text, so only its length is checked.
+fn check_length(
+    original: &str,
+    output: &str,
+    guard: &AiOutputGuard,
+    features: &AiFeatures,
+    mode: EnhanceMode,
+) -> Result<()> {
+    let (min_ratio, max_ratio) = length_bounds(guard, features);
+
+    // The words asking for an email's breaks are gone once they are
+    // breaks, and so are those asking for a note's structure or code's
+    // symbols
+    let expected = match mode {
+        EnhanceMode::EmailFormat => without_spoken_breaks(original),
+        EnhanceMode::Markdown => write_markdown(original),
+        EnhanceMode::Code => write_code(original),
+        _ => original.to_string(),
+    };
+    check_output_length(&expected, output, min_ratio, max_ratio)?;
+    if features.translate_to.is_none() && features.tone.is_none() {
+        check_word_overlap(&expected, output, MIN_WORD_OVERLAP)?;
+    }
+    Ok(())
+}
+
+/// Output/input length ratios allowed for enhanced text with `features`
+fn length_bounds(guard: &AiOutputGuard, features: &AiFeatures) -> (f64, f64) {
+    let min_ratio = if features.remove_filler_words {
+        guard.filler_min_ratio
+    } else {
+        guard.min_ratio
+    };
+
+    // A translation's length depends on the language pair as much as on the
+    // model. Grammar fixes swap words for others about as long, so they keep
+    // the bounds tight.
+    let mut slack = 1.0;
+    if features.translate_to.is_some() {
+        slack *= TRANSLATION_RATIO_SLACK;
+    }
+    if features.tone.is_some() {
+        slack *= TONE_RATIO_SLACK;
+    }
+    (min_ratio / slack, guard.max_ratio * slack)
+}
+
+/// Time allowed for enhancing a transcript of `chunks` chunks, including
+/// finding a healthy endpoint, given the configured budget per chunk
+fn enhancement_timeout(
+    per_chunk_ms: u32,
+    chunks: usize,
+    mode: EnhanceMode,
+    translating: bool,
+) -> Duration {
+    let mut timeout = Duration::from_millis(per_chunk_ms as u64) * chunks.max(1) as u32;
+    if translating || matches!(mode, EnhanceMode::Summarize { .. }) {
+        timeout *= SLOW_MODE_TIMEOUT_FACTOR;
+    }
+    timeout
+}
+
+/// Await `enhance` for at most `timeout`. Failures are returned as errors
+/// unless `fallback` is set, in which case `original` comes back unchanged
+/// along with the reason.
+async fn with_fallback<F>(
+    original: &str,
+    timeout: Duration,
+    fallback: bool,
+    enhance: F,
+) -> Result<EnhancedText, EnhancementError>
+where
+    F: Future<Output = Result<String>>,
+{
+    let (error, detail) = match tokio::time::timeout(timeout, enhance).await {
+        Ok(Ok(text)) => {
+            return Ok(EnhancedText {
+                text,
+                outcome: EnhancementOutcome::Enhanced,
+            })
+        }
+        Ok(Err(e)) => (EnhancementError::from_error(&e), format!("{:#}", e)),
+        Err(_) => (
+            EnhancementError::Timeout,
+            format!("AI enhancement timed out after {:?}", timeout),
+        ),
+    };
+
+    if !fallback {
+        warn!("AI enhancement failed: {}", detail);
+        return Err(error);
+    }
+
+    let reason = error.fallback_reason();
+    warn!(
+        "AI enhancement failed, keeping the original transcript ({:?}): {}",
+        reason, detail
+    );
+
+    Ok(EnhancedText {
+        text: original.to_string(),
+        outcome: EnhancementOutcome::Fallback { reason },
+    })
+}
+
+/// Whether `outcome` left the original transcript to be pasted, rather than
+/// the user taking it back by aborting
+fn pastes_original(outcome: &EnhancementOutcome) -> bool {
+    matches!(
+        outcome,
+        EnhancementOutcome::Fallback { reason } if *reason != AiFallbackReason::Aborted
+    )
+}
+
+/// Whether the original transcript `outcome` leaves to be pasted is
+/// capitalized and punctuated by rule, which only prose is
+pub fn punctuates_fallback(
+    settings: &AppSettings,
+    mode: EnhanceMode,
+    outcome: &EnhancementOutcome,
+) -> bool {
+    settings.ai_offline_fallback && mode.writes_prose() && pastes_original(outcome)
+}
+
+/// `enhanced` with the original `transcript` it fell back to capitalized and
+/// punctuated by rule, without a model, when the settings ask for it
+pub fn punctuate_fallback(
+    mut enhanced: EnhancedText,
+    transcript: &str,
+    settings: &AppSettings,
+    mode: EnhanceMode,
+) -> EnhancedText {
+    if punctuates_fallback(settings, mode, &enhanced.outcome) {
+        let language = detect_language(transcript);
+        let english = confident_language(language.as_ref()) == Some("English");
+        enhanced.text = enhance_offline(&enhanced.text, english);
+    }
+    enhanced
+}
+
+/// Why `result` failed, when a smaller model might not have: the model
+/// timing out, or Ollama failing to run it, as when it runs out of memory
+fn model_failure(result: &Result<EnhancedText, EnhancementError>) -> Option<AiFallbackReason> {
+    let reason = match result {
+        Ok(EnhancedText {
+            outcome: EnhancementOutcome::Fallback { reason },
+            ..
+        }) => *reason,
+        Ok(_) => return None,
+        Err(e) => e.fallback_reason(),
+    };
+    matches!(reason, AiFallbackReason::Timeout | AiFallbackReason::Failed).then_some(reason)
+}
+
+/// Type alias for thread-safe AI manager
 pub type SharedAiEnhancementManager = Arc<Mutex<AiEnhancementManager>>;
 
+pub type SharedRecentTranscripts = Arc<std::sync::Mutex<RecentTranscripts>>;
+
+pub type SharedInFlight = Arc<InFlight>;
+
+pub type SharedSessionContext = Arc<std::sync::Mutex<SessionContext>>;
+
+/// Dictated transcripts being enhanced, shared with identical ones dictated
+/// meanwhile, with what was pasted for them
+pub type SharedEnhancementDedup = SharedRequestDedup<Option<String>>;
+
+/// Abort the enhancement being generated, if any, delivering its original
+/// transcript through `ai-enhancement-fallback` straight away. Its
+/// generation is dropped, which closes the request so Ollama stops on it.
+/// Returns whether there was one to abort.
+pub fn abort_enhancement(app: &AppHandle) -> bool {
+    let Some(in_flight) = app.try_state::<SharedInFlight>() else {
+        return false;
+    };
+    let Some(aborted) = in_flight.abort() else {
+        return false;
+    };
+
+    info!("Aborting AI enhancement {}", aborted.request_id);
+    let _ = app.emit(
+        "ai-enhancement-fallback",
+        AiEnhancementFallback {
+            request_id: aborted.request_id,
+            reason: AiFallbackReason::Aborted,
+            text: aborted.original,
+        },
+    );
+    true
+}
+
+/// A pasted enhancement for the quality model of the two-pass mode to refine
+struct Refinement {
+    /// The enhancement refined
+    request_id: String,
+    transcript: String,
+    pasted: String,
+    features: AiFeatures,
+    mode: EnhanceMode,
+    model: String,
+}
+
+/// Refine a pasted enhancement in the background once the transcripts
+/// ahead of it in the queue are done, unless the next dictation starts
+/// first and cancels it
+fn spawn_refinement(app: &AppHandle, refinement: Refinement) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (Some(manager), Some(queue), Some(pending)) = (
+            app.try_state::<Arc<Mutex<AiEnhancementManager>>>(),
+            app.try_state::<SharedEnhancementQueue>(),
+            app.try_state::<SharedRefinement>(),
+        ) else {
+            return;
+        };
+
+        let cancelled = pending.start(&refinement.request_id);
+        let refine = async {
+            let _turn = take_turn(&queue).await;
+            manager.lock().await.refine(&refinement).await
+        };
+        tokio::select! {
+            result = refine => {
+                if let Err(e) = result {
+                    warn!("Refining {} failed: {:#}", refinement.request_id, e);
+                }
+            }
+            Ok(()) = cancelled => {
+                info!("Refinement of {} cancelled", refinement.request_id);
+            }
+        }
+        pending.finish(&refinement.request_id);
+    });
+}
+
+/// Cancel the refinement of the last enhancement, as the next dictation
+/// makes it stale. `true` when one was waiting or running.
+pub fn cancel_refinement(app: &AppHandle) -> bool {
+    app.try_state::<SharedRefinement>()
+        .and_then(|pending| pending.cancel())
+        .is_some()
+}
+
+/// Mark the last enhancement as superseded once the next dictation starts,
+/// so a second pass is never run over text the user has moved on from
+pub fn supersede_last_enhancement(app: &AppHandle) {
+    if let Some(ratings) = app.try_state::<RatingContext>() {
+        if let Some(last) = ratings.last.lock().unwrap().as_mut() {
+            last.superseded = true;
+        }
+    }
+}
+
+/// Remember `pasted` as the text pasted last for the transcript `original`,
+/// announcing it through `ai-enhancement-pasted` when enhancement changed it.
+/// Called in paste order, so undo always reaches the text pasted last.
+pub fn record_pasted_transcript(app: &AppHandle, original: &str, pasted: &str) {
+    let Some(recent) = app.try_state::<SharedRecentTranscripts>() else {
+        return;
+    };
+    recent.lock().unwrap().record(original, pasted);
+
+    if original != pasted {
+        let _ = app.emit(
+            "ai-enhancement-pasted",
+            AiEnhancementPasted {
+                original_text: original.to_string(),
+                enhanced_text: pasted.to_string(),
+            },
+        );
+    }
+}
+
+impl RatingContext {
+    /// Rate the enhancement finished last, replacing any earlier rating of
+    /// it. The rating is counted in the metrics by model and feature, and
+    /// kept with its history entry when there is one.
+    pub fn rate_last(&self, rating: AiEnhancementRating) -> Result<()> {
+        let mut last = self.last.lock().unwrap();
+        let last = last
+            .as_mut()
+            .ok_or_else(|| anyhow!("No enhancement has finished yet to rate"))?;
+        check_ratable(&last.outcome)?;
+
+        if let Some(id) = last.history_id {
+            // Gone when the entry was deleted, which leaves the count
+            if let Err(e) = self.history.lock().unwrap().rate(id, rating.clone()) {
+                warn!("Not saving the rating in the history: {}", e);
+            }
+        }
+        let previous = last.rating.replace(rating.clone());
+        self.count(&last.model, &last.features, rating.rating, previous);
+        Ok(())
+    }
+
+    /// Rate the enhancement saved as history entry `id`, replacing any
+    /// earlier rating of it
+    pub fn rate(&self, id: u32, rating: AiEnhancementRating) -> Result<()> {
+        if !get_settings(&self.app).ai_history_enabled {
+            return Err(anyhow!(
+                "Enhancement history is disabled, so only the last enhancement can be rated"
+            ));
+        }
+        let mut last = self.last.lock().unwrap();
+        let mut history = self.history.lock().unwrap();
+        let entry = history
+            .get(id)
+            .ok_or_else(|| anyhow!("No enhancement history entry with id {}", id))?;
+        check_ratable(&entry.outcome)?;
+        let (model, features) = (entry.model.clone(), entry.features.clone());
+        let previous = entry.rating.clone();
+        history.rate(id, rating.clone())?;
+
+        // The last enhancement rated through its entry is rated all the same
+        if let Some(last) = last.as_mut().filter(|last| last.history_id == Some(id)) {
+            last.rating = Some(rating.clone());
+        }
+        self.count(&model, &features, rating.rating, previous);
+        Ok(())
+    }
+
+    fn count(
+        &self,
+        model: &str,
+        features: &AiFeatures,
+        rating: AiRating,
+        previous: Option<AiEnhancementRating>,
+    ) {
+        let previous = previous.map(|previous| previous.rating);
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.record_rating(model, features, rating, previous);
+        metrics.save();
+    }
+}
+
+/// An enhancement that never finished has no output of its own to rate
+fn check_ratable(outcome: &EnhancementOutcome) -> Result<()> {
+    match outcome {
+        EnhancementOutcome::Fallback {
+            reason: AiFallbackReason::Aborted,
+        } => Err(anyhow!(
+            "This enhancement was cancelled or replaced by a newer recording before it finished, so it has no output to rate"
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl PullContext {
+    /// Queue a model download. Pulls run one at a time in the background and
+    /// report through `ai-model-pull-*` events tagged with the returned id.
+    /// Requesting a model that is already queued or downloading returns the
+    /// existing entry, so every caller follows the same download.
+    pub fn enqueue(&self, model: &str, endpoint: Option<String>) -> Result<AiPullQueueItem> {
+        // Fail fast on an unknown endpoint rather than when the item starts
+        self.endpoints.client_for(endpoint.as_deref())?;
+
+        let (item, created) = self.queue.lock().unwrap().enqueue(model, endpoint);
+        if !created {
+            info!("Attaching to existing pull {} for model {}", item.id, model);
+            return Ok(item);
+        }
+
+        info!("Queued pull {} for model {}", item.id, model);
+        self.records
+            .lock()
+            .unwrap()
+            .start(model, item.endpoint.clone());
+        let _ = self.app.emit("ai-model-pull-queued", item.clone());
+
+        spawn_pull_worker(self.clone());
+        Ok(item)
+    }
+
+    /// Download a model found missing in the background, when automatic
+    /// downloads are enabled, announcing it through `ai-model-auto-pull`.
+    /// Returns whether it is being downloaded automatically.
+    pub fn pull_missing(&self, model_id: &str, endpoint: Option<String>) -> bool {
+        if !get_settings(&self.app).ai_auto_pull_missing_model {
+            return false;
+        }
+        let mut auto = self.auto.lock().unwrap();
+        if !auto.try_start(model_id, Instant::now()) {
+            return auto.is_pulling(model_id);
+        }
+        drop(auto);
+
+        match self.enqueue(model_id, endpoint) {
+            Ok(item) => {
+                info!("{} is missing, downloading it automatically", model_id);
+                let _ = self.app.emit(
+                    "ai-model-auto-pull",
+                    AiModelAutoPull {
+                        pull_id: item.id,
+                        model_id: item.model_id,
+                    },
+                );
+                true
+            }
+            Err(e) => {
+                warn!("Couldn't download missing model {}: {}", model_id, e);
+                self.auto.lock().unwrap().finish(model_id, false);
+                false
+            }
+        }
+    }
+
+    /// Download the latest version of an installed model through the queue,
+    /// with the usual progress events. Once it is done `ai-model-updated`
+    /// says whether the installed copy changed.
+    pub async fn update(&self, model_id: &str) -> Result<AiPullQueueItem> {
+        let client = self.endpoints.client_for(None)?;
+        let before = installed_digest(&self.app, &client, model_id)
+            .await?
+            .ok_or_else(|| anyhow!("{} is not installed", model_id))?;
+        self.updates
+            .lock()
+            .unwrap()
+            .insert(model_id.to_string(), before);
+        self.enqueue(model_id, None)
+    }
+
+    /// Pending and running downloads, in the order they will be processed
+    pub fn queue(&self) -> Vec<AiPullQueueItem> {
+        self.queue.lock().unwrap().items()
+    }
+
+    /// Progress of the downloads currently running, for windows opened mid-pull
+    pub fn active(&self) -> Vec<AiModelPullProgress> {
+        self.queue.lock().unwrap().active_pulls()
+    }
+
+    /// Remove a download that has not started yet
+    pub fn cancel(&self, id: &str) -> Result<()> {
+        let item = self.queue.lock().unwrap().cancel(id)?;
+        info!(
+            "Cancelled queued pull {} for model {}",
+            item.id, item.model_id
+        );
+        self.records.lock().unwrap().remove(&item.model_id);
+        // Not downloaded again automatically straight away
+        self.auto.lock().unwrap().finish(&item.model_id, false);
+        let _ = self.app.emit("ai-model-pull-cancelled", item);
+        Ok(())
+    }
+
+    /// Downloads interrupted by a previous shutdown that can be resumed. Records
+    /// for models that have since been installed some other way are dropped.
+    pub async fn resumable(&self) -> Vec<AiPullRecord> {
+        let records = self.records.lock().unwrap().records();
+        let queued: Vec<String> = self.queue().into_iter().map(|item| item.model_id).collect();
+
+        let mut resumable = Vec::new();
+        for record in records {
+            if queued.contains(&record.model_id) {
+                continue;
+            }
+
+            if self.is_installed(&record).await {
+                info!(
+                    "{} finished downloading outside Handy, dropping its pull record",
+                    record.model_id
+                );
+                self.records.lock().unwrap().remove(&record.model_id);
+                continue;
+            }
+
+            resumable.push(record);
+        }
+
+        resumable
+    }
+
+    /// Restart an interrupted download. Ollama skips the layers it already has.
+    /// Returns `None` when the model turned out to be installed already.
+    pub async fn resume(&self, model_id: &str) -> Result<Option<AiPullQueueItem>> {
+        let record = self
+            .records
+            .lock()
+            .unwrap()
+            .get(model_id)
+            .ok_or_else(|| anyhow!("No interrupted download for {}", model_id))?;
+
+        if self.is_installed(&record).await {
+            info!("{} is already installed, nothing to resume", model_id);
+            self.records.lock().unwrap().remove(model_id);
+            return Ok(None);
+        }
+
+        // The host may have been removed from the endpoint list since
+        let endpoint = record
+            .endpoint
+            .filter(|endpoint| self.endpoints.client_for(Some(endpoint)).is_ok());
+
+        self.enqueue(model_id, endpoint).map(Some)
+    }
+
+    /// Forget an interrupted download without resuming it
+    pub fn dismiss(&self, model_id: &str) {
+        self.records.lock().unwrap().remove(model_id);
+    }
+
+    /// Whether a recorded model is installed. Assumes not when Ollama can't be reached.
+    async fn is_installed(&self, record: &AiPullRecord) -> bool {
+        let endpoint = record
+            .endpoint
+            .as_deref()
+            .filter(|endpoint| self.endpoints.client_for(Some(endpoint)).is_ok());
+
+        let installed = match self.endpoints.client_for(endpoint) {
+            Ok(client) => list_models(&self.app, &client).await,
+            Err(e) => Err(e),
+        };
+        match installed {
+            Ok(installed) => is_model_installed(&record.model_id, &installed),
+            Err(e) => {
+                debug!(
+                    "Couldn't check whether {} is installed: {}",
+                    record.model_id, e
+                );
+                false
+            }
+        }
+    }
+}
+
+/// How to run the model on this machine: the configured threads and GPU
+/// offload, with `auto_num_thread` threads unless set
+pub fn hardware_options(settings: &AppSettings, auto_num_thread: u32) -> OllamaOptions {
+    OllamaOptions {
+        num_thread: Some(settings.ai_num_thread.unwrap_or(auto_num_thread)),
+        num_gpu: settings.ai_gpu_offload.to_num_gpu(),
+        main_gpu: settings.ai_main_gpu,
+        // Kept in step with the idle unload, so Ollama doesn't unload the
+        // model before Handy does
+        keep_alive: idle_keep_alive(settings.ai_idle_unload_minutes),
+        ..Default::default()
+    }
+}
+
+/// Find the first healthy Ollama endpoint, emitting `ai-endpoint-changed`
+/// when the choice changes
+pub async fn select_endpoint(
+    app: &AppHandle,
+    endpoints: &OllamaEndpoints,
+) -> Option<OllamaEndpoint> {
+    let selection = endpoints.select().await;
+    if let Some(changed) = selection.changed {
+        if changed.previous.is_none() && changed.base_url.is_some() {
+            if let Some(preload) = app.try_state::<SharedStartupPreload>() {
+                preload.lock().unwrap().ollama_available();
+            }
+            close_circuit(app);
+        }
+        // Another host may have other models installed
+        forget_latency_profiles(app);
+        let _ = app.emit("ai-endpoint-changed", changed);
+    }
+    selection.endpoint
+}
+
+/// Work out how long each installed model takes again before the next
+/// transcript, once the benchmarks or the installed models changed
+pub fn forget_latency_profiles(app: &AppHandle) {
+    if let Some(latency) = app.try_state::<SharedLatencyProfiles>() {
+        latency.lock().unwrap().invalidate();
+    }
+}
+
+/// Skip Ollama after `failures` enhancements in a row found no host,
+/// probing for one in the background until the circuit closes again
+fn open_circuit(
+    app: &AppHandle,
+    endpoints: &SharedOllamaEndpoints,
+    circuit: &SharedCircuitBreaker,
+    failures: u32,
+) {
+    warn!(
+        "No Ollama host reachable for {} enhancements in a row, pasting transcripts as they are until one is",
+        failures
+    );
+    announce_circuit(app, true, failures);
+
+    let app = app.clone();
+    let endpoints = endpoints.clone();
+    let circuit = circuit.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let Some(wait) = circuit.lock().unwrap().until_probe(Instant::now()) else {
+                return;
+            };
+            tokio::time::sleep(wait).await;
+            if !circuit.lock().unwrap().is_open() {
+                return;
+            }
+            // Finding a host closes the circuit through the endpoint change
+            if select_endpoint(&app, &endpoints).await.is_some() {
+                close_circuit(&app);
+                return;
+            }
+            circuit.lock().unwrap().probe_failed(Instant::now());
+        }
+    });
+}
+
+/// Stop skipping Ollama once a host is reachable again
+fn close_circuit(app: &AppHandle) {
+    let Some(circuit) = app.try_state::<SharedCircuitBreaker>() else {
+        return;
+    };
+    if circuit.lock().unwrap().record_success() {
+        announce_circuit(app, false, 0);
+    }
+}
+
+fn announce_circuit(app: &AppHandle, open: bool, failures: u32) {
+    if !open {
+        info!("Ollama is reachable again, resuming AI enhancement");
+    }
+    crate::tray::set_ai_offline(app, open);
+    let _ = app.emit("ai-circuit-changed", AiCircuitChanged { open, failures });
+}
+
+/// Get list of available models from an Ollama endpoint. Models from the
+/// configured registry are reported by their bare ids.
+pub async fn list_models(app: &AppHandle, client: &OllamaClient) -> Result<Vec<String>> {
+    let models = client.list_models().await?;
+    let registry_prefix = get_settings(app).ai_model_registry_prefix;
+    Ok(models
+        .into_iter()
+        .map(|m| display_model_id(&m.name, registry_prefix.as_deref()))
+        .collect())
+}
+
+/// Where the endpoint keeps its models and how much space they use. Free
+/// space is only reported for an Ollama running on this machine.
+pub async fn storage_info(app: &AppHandle, endpoint: &OllamaEndpoint) -> Result<OllamaStorageInfo> {
+    let models = endpoint.client.list_models().await?;
+    let used_bytes = models.iter().map(|m| m.size).sum();
+
+    if !is_local_host(&endpoint.config.base_url) {
+        return Ok(OllamaStorageInfo {
+            models_dir: None,
+            used_bytes,
+            free_bytes: None,
+        });
+    }
+
+    let home = app.path().home_dir().ok();
+    let models_dir = resolve_models_dir(std::env::var("OLLAMA_MODELS").ok(), home.as_deref());
+    Ok(OllamaStorageInfo {
+        free_bytes: models_dir.as_deref().and_then(free_space),
+        models_dir: models_dir.map(|dir| dir.display().to_string()),
+        used_bytes,
+    })
+}
+
+/// Installed models not used for an enhancement or benchmark in the last
+/// `days` days, and the space deleting them frees. The selected model is
+/// never among them.
+pub async fn unused_models(
+    app: &AppHandle,
+    endpoint: &OllamaEndpoint,
+    usage: &SharedModelUsage,
+    days: u32,
+) -> Result<AiUnusedModels> {
+    let settings = get_settings(app);
+    let registry_prefix = settings.ai_model_registry_prefix.as_deref();
+    let models = endpoint.client.list_models().await?;
+    let installed: Vec<(String, u64)> = models
+        .iter()
+        .map(|m| (display_model_id(&m.name, registry_prefix), m.size))
+        .collect();
+    let unused = usage.lock().unwrap().unused(
+        &installed,
+        settings.ai_selected_model.as_deref(),
+        chrono::Utc::now().timestamp(),
+        days,
+    );
+
+    // Models share blobs, like a custom model and the one it was made from,
+    // so their sizes can't be added up. The blobs are listed in the
+    // manifests, which can only be read on this machine.
+    let reclaimable = is_local_host(&endpoint.config.base_url)
+        .then(|| {
+            let home = app.path().home_dir().ok();
+            resolve_models_dir(std::env::var("OLLAMA_MODELS").ok(), home.as_deref())
+        })
+        .flatten()
+        .and_then(|models_dir| {
+            let (mut removed, mut kept) = (Vec::new(), Vec::new());
+            for (model, (id, _)) in models.iter().zip(&installed) {
+                let blobs = model_blobs(&models_dir, &model.name)?;
+                if unused.iter().any(|unused| &unused.model == id) {
+                    removed.push(blobs);
+                } else {
+                    kept.push(blobs);
+                }
+            }
+            Some(reclaimable_bytes(&removed, &kept))
+        });
+
+    Ok(AiUnusedModels {
+        models: unused,
+        reclaimable_bytes: reclaimable,
+    })
+}
+
+/// Delete the models [`unused_models`] reports, emitting
+/// `ai-unused-model-deleted` for each. A model that can't be deleted is
+/// reported and skipped. Returns the deleted models.
+pub async fn delete_unused_models(
+    app: &AppHandle,
+    endpoint: &OllamaEndpoint,
+    usage: &SharedModelUsage,
+    days: u32,
+) -> Result<Vec<String>> {
+    let unused = unused_models(app, endpoint, usage, days).await?;
+
+    let mut deleted = Vec::new();
+    for unused in unused.models {
+        let model = unused.model;
+        let result = delete_model(app, &endpoint.client, &model).await;
+        match &result {
+            Ok(()) => {
+                usage.lock().unwrap().forget(&model);
+                deleted.push(model.clone());
+            }
+            Err(e) => warn!("Failed to delete unused model {}: {}", model, e),
+        }
+        let _ = app.emit(
+            "ai-unused-model-deleted",
+            AiUnusedModelDeleted {
+                model,
+                error: result.err().map(|e| e.to_string()),
+            },
+        );
+    }
+    Ok(deleted)
+}
+
+/// Delete a model
+pub async fn delete_model(app: &AppHandle, client: &OllamaClient, model: &str) -> Result<()> {
+    info!("Deleting model: {}", model);
+    let registry_prefix = get_settings(app).ai_model_registry_prefix;
+    client
+        .delete_model(&qualify_model_id(model, registry_prefix.as_deref()))
+        .await?;
+    forget_latency_profiles(app);
+    Ok(())
+}
+
+/// Import a GGUF file from disk as an Ollama model called `name`. Status lines
+/// from `/api/create` are reported as `ai-model-pull-progress` events so the
+/// UI can show the import like a download.
+pub async fn import_gguf(
+    app: &AppHandle,
+    client: &OllamaClient,
+    path: &Path,
+    name: &str,
+) -> Result<String> {
+    let path = validate_gguf_file(path)?;
+    let name = validate_model_name(name)?;
+    info!("Importing {} as model {}", path.display(), name);
+
+    let import_id = format!("import-{}", name);
+    let progress_app = app.clone();
+    let (progress_id, model_id) = (import_id.clone(), name.clone());
+
+    client
+        .create_model_with_progress(&name, &gguf_modelfile(&path), move |status, completed, total| {
+            let percentage = match (completed, total) {
+                (Some(c), Some(t)) if t > 0 => (c as f64 / t as f64) * 100.0,
+                _ => 0.0,
+            };
+            let _ = progress_app.emit(
+                "ai-model-pull-progress",
+                AiModelPullProgress {
+                    pull_id: progress_id.clone(),
+                    model_id: model_id.clone(),
+                    status,
+                    completed,
+                    total,
+                    percentage,
+                },
+            );
+        })
+        .await?;
+
+    forget_latency_profiles(app);
+    let _ = app.emit(
+        "ai-model-pull-complete",
+        AiModelPullComplete {
+            pull_id: import_id,
+            model_id: name.clone(),
+        },
+    );
+
+    Ok(name)
+}
+
+/// Offer to resume downloads interrupted by the last shutdown through
+/// `ai-model-pull-resumable`, once the pulls are registered as app state
+pub fn announce_resumable_pulls(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let resumable = app.state::<PullContext>().resumable().await;
+        if !resumable.is_empty() {
+            info!("Found {} interrupted model download(s)", resumable.len());
+            let _ = app.emit("ai-model-pull-resumable", resumable);
+        }
+    });
+}
+
+/// Check the selected model is installed where it will be used, once the
+/// pulls are registered as app state. See [`check_selected_model`].
+pub fn verify_selected_model(app: &AppHandle) {
+    if !get_settings(app).ai_enhancement_enabled {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        check_selected_model(&app).await;
+    });
+}
+
+/// Check whether the selected model is installed on the endpoint it will be
+/// used from, reporting the result through `ai-model-status`. A missing model
+/// is downloaded when automatic downloads are enabled, and reported through
+/// `ai-model-missing` otherwise. `None` when no model is selected.
+pub async fn check_selected_model(app: &AppHandle) -> Option<AiModelStatusReport> {
+    let model = get_settings(app).ai_selected_model?;
+    let endpoints = app.state::<SharedOllamaEndpoints>().inner().clone();
+    let Some(endpoint) = select_endpoint(app, &endpoints).await else {
+        return Some(report_model_status(
+            app,
+            model,
+            AiModelStatus::OllamaUnreachable,
+        ));
+    };
+    let model = endpoint.config.preferred_model.clone().unwrap_or(model);
+    let installed = match list_models(app, &endpoint.client).await {
+        Ok(installed) => installed,
+        Err(e) => {
+            debug!("Couldn't check whether {} is installed: {}", model, e);
+            return Some(report_model_status(
+                app,
+                model,
+                AiModelStatus::OllamaUnreachable,
+            ));
+        }
+    };
+    if is_model_installed(&model, &installed) {
+        return Some(report_model_status(app, model, AiModelStatus::Installed));
+    }
+
+    warn!(
+        "Selected AI model {} is not installed on {}",
+        model, endpoint.config.base_url
+    );
+    let base_url = Some(endpoint.config.base_url);
+    if !app.state::<PullContext>().pull_missing(&model, base_url) {
+        let _ = app.emit("ai-model-missing", model.clone());
+    }
+    Some(report_model_status(app, model, AiModelStatus::Missing))
+}
+
+fn report_model_status(
+    app: &AppHandle,
+    model: String,
+    status: AiModelStatus,
+) -> AiModelStatusReport {
+    let report = AiModelStatusReport { model, status };
+    let _ = app.emit("ai-model-status", report.clone());
+    report
+}
+
+/// Work through the pull queue until it is empty. Safe to call for every
+/// enqueue: a worker that finds a download already running exits immediately.
+fn spawn_pull_worker(pulls: PullContext) {
+    tauri::async_runtime::spawn(async move {
+        drain(
+            &pulls.queue,
+            |item: AiPullQueueItem| {
+                let pulls = pulls.clone();
+                async move {
+                    let client = pulls.endpoints.client_for(item.endpoint.as_deref())?;
+                    pull_model(&client, &item, &pulls).await
+                }
+            },
+            |item, result: Result<()>| {
+                pulls
+                    .auto
+                    .lock()
+                    .unwrap()
+                    .finish(&item.model_id, result.is_ok());
+                let updating = pulls.updates.lock().unwrap().remove(&item.model_id);
+                match result {
+                    Ok(()) => {
+                        pulls.records.lock().unwrap().remove(&item.model_id);
+                        if let Some(before) = updating {
+                            spawn_update_check(pulls.clone(), item.clone(), before);
+                        }
+                        forget_latency_profiles(&pulls.app);
+                        let _ = pulls.app.emit(
+                            "ai-model-pull-complete",
+                            AiModelPullComplete {
+                                pull_id: item.id,
+                                model_id: item.model_id,
+                            },
+                        );
+                    }
+                    Err(e) => {
+                        // The record is kept so the download can be resumed later
+                        warn!("Pull {} of {} failed: {}", item.id, item.model_id, e);
+                        let _ = pulls.app.emit(
+                            "ai-model-pull-error",
+                            AiModelPullError {
+                                pull_id: item.id,
+                                model_id: item.model_id,
+                                error: e.to_string(),
+                            },
+                        );
+                    }
+                }
+            },
+        )
+        .await;
+    });
+}
+
+/// Tell whether the download of an update changed the installed model,
+/// emitting `ai-model-updated` once it is known
+fn spawn_update_check(pulls: PullContext, item: AiPullQueueItem, before: String) {
+    tauri::async_runtime::spawn(async move {
+        let after = match pulls.endpoints.client_for(item.endpoint.as_deref()) {
+            Ok(client) => installed_digest(&pulls.app, &client, &item.model_id).await,
+            Err(e) => Err(e),
+        };
+        match after {
+            Ok(Some(after)) => {
+                let updated = !same_digest(&before, &after);
+                info!("Updated {} (changed: {})", item.model_id, updated);
+                let _ = pulls.app.emit(
+                    "ai-model-updated",
+                    AiModelUpdated {
+                        model: item.model_id,
+                        updated,
+                    },
+                );
+            }
+            Ok(None) => warn!("{} is missing after updating it", item.model_id),
+            Err(e) => warn!("Couldn't check the update of {}: {}", item.model_id, e),
+        }
+    });
+}
+
+/// Digest Ollama reports for `model_id`, or `None` when it isn't installed
+async fn installed_digest(
+    app: &AppHandle,
+    client: &OllamaClient,
+    model_id: &str,
+) -> Result<Option<String>> {
+    let registry_prefix = get_settings(app).ai_model_registry_prefix;
+    let registry_model = qualify_model_id(model_id, registry_prefix.as_deref());
+    Ok(client
+        .list_models()
+        .await?
+        .into_iter()
+        .find(|m| is_model_installed(&registry_model, std::slice::from_ref(&m.name)))
+        .map(|m| m.digest))
+}
+
+/// Compare each installed model from the catalog with its registry,
+/// reporting which have a newer version there. A registry that can't be
+/// reached is reported for each model rather than failing the check.
+pub async fn check_model_updates(app: &AppHandle) -> Result<Vec<AiModelUpdateCheck>> {
+    let settings = get_settings(app);
+    let endpoints = app.state::<SharedOllamaEndpoints>().inner().clone();
+    let Some(endpoint) = select_endpoint(app, &endpoints).await else {
+        return Err(OllamaError::Unavailable.into());
+    };
+    let installed = endpoint.client.list_models().await?;
+    let http = reqwest::Client::new();
+
+    let mut checks = Vec::new();
+    for model in get_available_models(settings.ai_model_registry_prefix.as_deref()) {
+        let Some(local) = installed
+            .iter()
+            .find(|m| is_model_installed(&model.registry_id, std::slice::from_ref(&m.name)))
+        else {
+            continue;
+        };
+        let url = manifest_url(&local.name, settings.ai_model_registry_insecure);
+        let check = match registry_digest(&http, &url).await {
+            Ok(remote) => AiModelUpdateCheck {
+                model: model.id,
+                update_available: !same_digest(&local.digest, &remote),
+                error: None,
+            },
+            Err(e) => {
+                debug!("Couldn't check {} for updates: {}", model.id, e);
+                AiModelUpdateCheck {
+                    model: model.id,
+                    update_available: false,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        checks.push(check);
+    }
+    Ok(checks)
+}
+
+/// Look for updates to the installed models in the background, announcing
+/// any through `ai-model-updates-available`. Skipped unless enhancement and
+/// the startup check are both enabled.
+pub fn announce_model_updates(app: &AppHandle) {
+    let settings = get_settings(app);
+    if !settings.ai_enhancement_enabled || !settings.ai_check_model_updates {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match check_model_updates(&app).await {
+            Ok(checks) => {
+                let updates: Vec<AiModelUpdateCheck> = checks
+                    .into_iter()
+                    .filter(|check| check.update_available)
+                    .collect();
+                if !updates.is_empty() {
+                    info!("Found updates for {} AI model(s)", updates.len());
+                    let _ = app.emit("ai-model-updates-available", updates);
+                }
+            }
+            Err(e) => debug!("Couldn't check the AI models for updates: {}", e),
+        }
+    });
+}
+
+/// Load the selected model in the background shortly after launch, when
+/// preloading is enabled, so the first dictation doesn't wait for it. When
+/// Ollama isn't running yet, it is loaded once Ollama becomes available.
+/// Failures are only logged.
+pub fn preload_selected_model(app: &AppHandle) {
+    let settings = get_settings(app);
+    if !settings.ai_preload_on_startup
+        || !settings.ai_enhancement_enabled
+        || settings.ai_selected_model.is_none()
+    {
+        return;
+    }
+
+    let preload = app.state::<SharedStartupPreload>().inner().clone();
+    let task_app = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PRELOAD_DELAY).await;
+        preload_model(&task_app).await;
+    });
+    preload.lock().unwrap().start(task);
+}
+
+/// Stop the startup preload, as when AI enhancement is turned off
+pub fn cancel_preload(app: &AppHandle) {
+    if let Some(preload) = app.try_state::<SharedStartupPreload>() {
+        preload.lock().unwrap().cancel();
+    }
+}
+
+async fn preload_model(app: &AppHandle) {
+    let settings = get_settings(app);
+    if !settings.ai_warm_up_on_battery && on_battery() {
+        debug!("Not preloading the AI model on battery power");
+        return;
+    }
+
+    let endpoints = app.state::<SharedOllamaEndpoints>().inner().clone();
+    let (available, auto_num_thread) = {
+        let preload = app.state::<SharedStartupPreload>();
+        let preload = preload.lock().unwrap();
+        (preload.availability(), preload.auto_num_thread)
+    };
+    let mut waiting = false;
+    let endpoint = wait_for_ollama(&available, || {
+        if std::mem::replace(&mut waiting, true) {
+            debug!("Checking for Ollama to preload the AI model");
+        }
+        select_endpoint(app, &endpoints)
+    })
+    .await;
+
+    // The selection may have changed while waiting for Ollama
+    let settings = get_settings(app);
+    let Some(model) = settings.ai_selected_model.clone() else {
+        return;
+    };
+    let model = endpoint.config.preferred_model.unwrap_or(model);
+    let registry_model = qualify_model_id(&model, settings.ai_model_registry_prefix.as_deref());
+    let options = hardware_options(&settings, auto_num_thread);
+    // Going unused for the idle timeout unloads it sooner either way
+    let keep_alive = options.keep_alive.unwrap_or(Duration::from_secs(
+        settings.ai_preload_keep_alive_mins as u64 * 60,
+    ));
+
+    info!("Preloading {}", model);
+    let started = Instant::now();
+    match endpoint
+        .client
+        .warm_up(&registry_model, &options, keep_alive)
+        .await
+    {
+        Ok(()) => {
+            info!("Preloaded {} in {:?}", model, started.elapsed());
+            if let Some(idle_model) = app.try_state::<SharedIdleModel>() {
+                idle_model
+                    .lock()
+                    .unwrap()
+                    .touch(&registry_model, Instant::now());
+            }
+        }
+        Err(e) => warn!("Failed to preload {}: {}", model, e),
+    }
+}
+
+/// Unload `model` after it went unused for `idle_minutes`, unless Ollama
+/// already did, as when it needed the memory for another model
+async fn unload_idle_model(
+    app: &AppHandle,
+    endpoints: &OllamaEndpoints,
+    model: &str,
+    idle_minutes: u32,
+) {
+    let Some(endpoint) = select_endpoint(app, endpoints).await else {
+        return;
+    };
+    match endpoint.client.list_running_models().await {
+        Ok(running) => {
+            let names: Vec<String> = running.into_iter().map(|model| model.name).collect();
+            if !is_model_installed(model, &names) {
+                debug!("{} was already unloaded", model);
+                return;
+            }
+        }
+        Err(e) => debug!("Couldn't list the loaded models: {}", e),
+    }
+
+    match endpoint.client.unload(model).await {
+        Ok(()) => {
+            info!("Unloaded {} after {} idle minutes", model, idle_minutes);
+            let registry_prefix = get_settings(app).ai_model_registry_prefix;
+            let unloaded = AiModelUnloaded {
+                model: display_model_id(model, registry_prefix.as_deref()),
+                idle_minutes,
+            };
+            let _ = app.emit("ai-model-unloaded", unloaded);
+        }
+        Err(e) => warn!("Failed to unload {}: {}", model, e),
+    }
+}
+
+/// Pull a model from Ollama with progress events.
+///
+/// A pull that stops receiving data emits `ai-model-pull-stalled`. If auto-retry
+/// is enabled the pull is restarted, which lets Ollama resume from the layers
+/// it already has; otherwise the stall is returned as an error.
+async fn pull_model(client: &OllamaClient, item: &AiPullQueueItem, pulls: &PullContext) -> Result<()> {
+    let app = &pulls.app;
+    let model = item.model_id.as_str();
+    info!("Pulling model: {}", model);
+
+    let settings = get_settings(app);
+    let mut attempt = 0;
+
+    loop {
+        match pull_model_once(client, item, pulls, &settings).await {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                let Some(OllamaError::Stalled { completed, total }) =
+                    e.downcast_ref::<OllamaError>().cloned()
+                else {
+                    return Err(e);
+                };
+
+                let retrying = settings.ai_pull_auto_retry && attempt < MAX_PULL_RETRIES;
+                warn!(
+                    "Pull of {} stalled at {:?}/{:?} bytes (retrying: {})",
+                    model, completed, total, retrying
+                );
+
+                let _ = app.emit(
+                    "ai-model-pull-stalled",
+                    AiModelPullStalled {
+                        pull_id: item.id.clone(),
+                        model_id: model.to_string(),
+                        completed,
+                        total,
+                        retrying,
+                    },
+                );
+
+                if !retrying {
+                    return Err(e);
+                }
+                attempt += 1;
+            }
+        }
+    }
+}
+
+async fn pull_model_once(
+    client: &OllamaClient,
+    item: &AiPullQueueItem,
+    pulls: &PullContext,
+    settings: &AppSettings,
+) -> Result<()> {
+    let stall_timeout = Duration::from_secs(settings.ai_pull_stall_timeout_secs);
+    let registry_model =
+        qualify_model_id(&item.model_id, settings.ai_model_registry_prefix.as_deref());
+    let pull_id = item.id.clone();
+    let model_id = item.model_id.clone();
+    let pulls = pulls.clone();
+
+    client
+        .pull_model_with_progress(
+            &registry_model,
+            stall_timeout,
+            settings.ai_model_registry_insecure,
+            move |status, completed, total| {
+                let percentage = if let (Some(c), Some(t)) = (completed, total) {
+                    if t > 0 {
+                        (c as f64 / t as f64) * 100.0
+                    } else {
+                        0.0
+                    }
+                } else {
+                    0.0
+                };
+
+                let progress = AiModelPullProgress {
+                    pull_id: pull_id.clone(),
+                    model_id: model_id.clone(),
+                    status: status.clone(),
+                    completed,
+                    total,
+                    percentage,
+                };
+
+                pulls
+                    .records
+                    .lock()
+                    .unwrap()
+                    .update_progress(&model_id, completed, total);
+                pulls.queue.lock().unwrap().record_progress(progress.clone());
+                let _ = pulls.app.emit("ai-model-pull-progress", progress);
+            },
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::mock_transport::{MockReply, MockTransport};
+    use crate::settings::{get_default_settings, AiExample, AiProperNoun, AiSnippet};
+
+    const TRANSCRIPT: &str = "um so the meeting is at three";
+
+    fn enhance_with_fallback<F>(enhance: F) -> EnhancedText
+    where
+        F: Future<Output = Result<String>>,
+    {
+        tauri::async_runtime::block_on(with_fallback(
+            TRANSCRIPT,
+            Duration::from_millis(50),
+            true,
+            enhance,
+        ))
+        .unwrap()
+    }
+
+    fn fell_back(result: &EnhancedText, reason: AiFallbackReason) -> bool {
+        result.text == TRANSCRIPT && result.outcome == EnhancementOutcome::Fallback { reason }
+    }
+
+    #[test]
+    fn test_timeout_keeps_original_text() {
+        let result = enhance_with_fallback(std::future::pending());
+        assert!(fell_back(&result, AiFallbackReason::Timeout));
+    }
+
+    #[test]
+    fn test_ollama_down_keeps_original_text() {
+        let result = enhance_with_fallback(async { Err(OllamaError::Unavailable.into()) });
+        assert!(fell_back(&result, AiFallbackReason::OllamaUnavailable));
+
+        let client = OllamaClient::with_transport(MockTransport::new(vec![MockReply::Unreachable]));
+        let result = enhance_with_fallback(client.generate(
+            "gemma2:2b",
+            TRANSCRIPT,
+            &OllamaOptions::default(),
+        ));
+        assert!(fell_back(&result, AiFallbackReason::Failed));
+    }
+
+    #[test]
+    fn test_missing_model_keeps_original_text() {
+        let client = OllamaClient::with_transport(MockTransport::new(vec![MockReply::Body(
+            404,
+            r#"{"error":"model 'gemma2:2b' not found, try pulling it first"}"#,
+        )]));
+
+        let result = enhance_with_fallback(client.generate(
+            "gemma2:2b",
+            TRANSCRIPT,
+            &OllamaOptions::default(),
+        ));
+
+        assert!(fell_back(&result, AiFallbackReason::ModelMissing));
+    }
+
+    #[test]
+    fn test_slow_server_falls_back_at_configured_timeout() {
+        let client = OllamaClient::with_transport(MockTransport::new(vec![MockReply::Hang(
+            200,
+            vec!["{\"response\":\"So the\",\"done\":false}\n"],
+        )]));
+        let timeout = enhancement_timeout(150, 1, EnhanceMode::Correct, false);
+
+        let started = Instant::now();
+        let result = tauri::async_runtime::block_on(with_fallback(
+            TRANSCRIPT,
+            timeout,
+            true,
+            client.generate_stream("gemma2:2b", TRANSCRIPT, &OllamaOptions::default(), |_| {}),
+        ))
+        .unwrap();
+
+        assert!(fell_back(&result, AiFallbackReason::Timeout));
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(150));
+        assert!(elapsed < Duration::from_secs(2), "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn test_timeout_stretches_for_long_and_slow_enhancements() {
+        let summarize = EnhanceMode::Summarize {
+            style: AiSummaryStyle::default(),
+            max_words: 150,
+        };
+        let ms = |chunks, mode, translating| {
+            enhancement_timeout(8000, chunks, mode, translating).as_millis()
+        };
+
+        assert_eq!(ms(0, EnhanceMode::Correct, false), 8000);
+        assert_eq!(ms(3, EnhanceMode::Correct, false), 24000);
+        assert_eq!(ms(1, EnhanceMode::Correct, true), 16000);
+        assert_eq!(ms(2, summarize, false), 32000);
+    }
+
+    #[test]
+    fn test_without_fallback_errors_are_returned() {
+        let result = tauri::async_runtime::block_on(with_fallback(
+            TRANSCRIPT,
+            Duration::from_millis(50),
+            false,
+            async { Err(OllamaError::Unavailable.into()) },
+        ));
+        assert_eq!(result, Err(EnhancementError::OllamaUnavailable));
+
+        let result = tauri::async_runtime::block_on(with_fallback(
+            TRANSCRIPT,
+            Duration::from_millis(50),
+            false,
+            std::future::pending(),
+        ));
+        assert_eq!(result, Err(EnhancementError::Timeout));
+    }
+
+    #[test]
+    fn test_translation_loosens_length_bounds() {
+        let guard = AiOutputGuard::default();
+        let mut features = AiFeatures {
+            remove_filler_words: false,
+            ..AiFeatures::default()
+        };
+        assert_eq!(
+            length_bounds(&guard, &features),
+            (guard.min_ratio, guard.max_ratio)
+        );
+
+        features.translate_to = Some("eng".to_string());
+        let (min_ratio, max_ratio) = length_bounds(&guard, &features);
+        assert!(min_ratio < guard.min_ratio);
+        assert!(max_ratio > guard.max_ratio);
+
+        // A German transcript translated into noticeably shorter English
+        let transcript =
+            "also ich wollte dir nur kurz sagen dass ich heute leider etwas später komme";
+        let translation = "Just wanted to say I'll be late today.";
+        assert!(
+            check_output_length(transcript, translation, guard.min_ratio, guard.max_ratio).is_err()
+        );
+        assert!(check_output_length(transcript, translation, min_ratio, max_ratio).is_ok());
+    }
+
+    #[test]
+    fn test_grammar_keeps_length_bounds_tight() {
+        let guard = AiOutputGuard::default();
+        let features = AiFeatures {
+            fix_grammar: true,
+            ..AiFeatures::default()
+        };
+        assert_eq!(
+            length_bounds(&guard, &features),
+            length_bounds(&guard, &AiFeatures::default())
+        );
+
+        // A rambling rewrite passed off as grammar fixes is still rejected
+        let transcript = "he don't know where they was going";
+        let fixed = "He doesn't know where they were going.";
+        let rewritten =
+            "He has no idea at all where exactly the whole group of them was heading off to after work.";
+        let (min_ratio, max_ratio) = length_bounds(&guard, &features);
+        assert!(check_output_length(transcript, fixed, min_ratio, max_ratio).is_ok());
+        assert!(check_output_length(transcript, rewritten, min_ratio, max_ratio).is_err());
+    }
+
+    #[test]
+    fn test_tone_loosens_length_bounds() {
+        let guard = AiOutputGuard::default();
+        let mut features = AiFeatures {
+            remove_filler_words: false,
+            ..AiFeatures::default()
+        };
+        features.tone = Some(AiTone::Professional);
+        let (min_ratio, max_ratio) = length_bounds(&guard, &features);
+        assert!(min_ratio < guard.min_ratio);
+        assert!(max_ratio > guard.max_ratio);
+
+        // Text-speak expanded into a sentence fit for a client
+        let transcript = "hey can u get me that doc";
+        let rewritten = "Hello, could you please send me that document?";
+        assert!(
+            check_output_length(transcript, rewritten, guard.min_ratio, guard.max_ratio).is_err()
+        );
+        assert!(check_output_length(transcript, rewritten, min_ratio, max_ratio).is_ok());
+
+        // Both at once widen the bounds further still
+        features.translate_to = Some("eng".to_string());
+        let (both_min, both_max) = length_bounds(&guard, &features);
+        assert!(both_min < min_ratio);
+        assert!(both_max > max_ratio);
+    }
+
+    #[test]
+    fn test_short_text_is_still_translated() {
+        let mut features = AiFeatures::default();
+        assert_eq!(
+            skip_reason("danke schön", &features, 3),
+            Some(AiSkipReason::TooShort)
+        );
+
+        features.translate_to = Some("eng".to_string());
+        assert_eq!(skip_reason("danke schön", &features, 3), None);
+    }
+
+    #[test]
+    fn test_skip_threshold_and_text_without_letters() {
+        let features = AiFeatures::default();
+        assert_eq!(skip_reason("no", &features, 1), None);
+        assert_eq!(
+            skip_reason("no", &features, 2),
+            Some(AiSkipReason::TooShort)
+        );
+
+        // However many words, numbers and punctuation are never sent
+        for text in ["42", "1, 2, 3, 4, 5.", "?!", ""] {
+            assert_eq!(
+                skip_reason(text, &features, 1),
+                Some(AiSkipReason::NoLetters),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_result_ends_with_one_event() {
+        let ended = |outcome| {
+            Terminal::of(&Ok(EnhancedText {
+                text: TRANSCRIPT.to_string(),
+                outcome,
+            }))
+        };
+        assert_eq!(ended(EnhancementOutcome::Enhanced), Terminal::Complete);
+        assert_eq!(
+            ended(EnhancementOutcome::Skipped {
+                reason: AiSkipReason::TooShort
+            }),
+            Terminal::Skipped(AiSkipReason::TooShort)
+        );
+        assert_eq!(
+            ended(EnhancementOutcome::Fallback {
+                reason: AiFallbackReason::Timeout
+            }),
+            Terminal::Fallback(AiFallbackReason::Timeout)
+        );
+        // Without falling back the original transcript is pasted all the same
+        assert_eq!(
+            Terminal::of(&Err(EnhancementError::OllamaUnavailable)),
+            Terminal::Fallback(AiFallbackReason::OllamaUnavailable)
+        );
+    }
+
+    #[test]
+    fn test_original_is_withheld_from_events_only_without_history() {
+        let mut settings = get_default_settings();
+        assert_eq!(
+            original_for_event(TRANSCRIPT, &settings).as_deref(),
+            Some(TRANSCRIPT)
+        );
+
+        settings.ai_withhold_original_from_events = true;
+        assert_eq!(original_for_event(TRANSCRIPT, &settings), None);
+
+        // The history keeps the text anyway, so there is nothing to withhold
+        settings.ai_history_enabled = true;
+        assert_eq!(
+            original_for_event(TRANSCRIPT, &settings).as_deref(),
+            Some(TRANSCRIPT)
+        );
+    }
+
+    #[test]
+    fn test_complete_event_carries_both_texts() {
+        let event = AiEnhancementComplete {
+            request_id: "enhance-1".to_string(),
+            text: "The meeting is at three.".to_string(),
+            original: None,
+            original_chars: TRANSCRIPT.chars().count() as u32,
+            outcome: EnhancementOutcome::Enhanced,
+            stats: AiEnhancementStats {
+                model: "qwen2.5:1.5b".to_string(),
+                duration_ms: 420,
+                streamed: false,
+                language: None,
+                cached: false,
+                latency_choice: None,
+            },
+            translated: false,
+            mode: EnhanceMode::Correct,
+            tone: None,
+            diff: None,
+            unenhanced_words: 0,
+        };
+        let payload = serde_json::to_value(&event).unwrap();
+        assert_eq!(payload["text"], "The meeting is at three.");
+        assert_eq!(payload["original"], serde_json::Value::Null);
+        assert_eq!(payload["original_chars"], 29);
+        assert_eq!(payload["outcome"]["kind"], "enhanced");
+        assert_eq!(payload["stats"]["duration_ms"], 420);
+    }
+
+    #[test]
+    fn test_aborted_enhancement_ends_once() {
+        for fallback in [true, false] {
+            let in_flight = InFlight::default();
+            let aborted = in_flight.start("enhance-1", TRANSCRIPT);
+            // Aborting sends the fallback event, and only the first time
+            assert!(in_flight.abort().is_some());
+            assert_eq!(in_flight.abort(), None);
+
+            let result = tauri::async_runtime::block_on(with_fallback(
+                TRANSCRIPT,
+                Duration::from_secs(5),
+                fallback,
+                async {
+                    tokio::select! {
+                        result = std::future::pending::<Result<String>>() => result,
+                        Ok(()) = aborted => Err(Aborted.into()),
+                    }
+                },
+            ));
+
+            // The enhancement itself has nothing left to send
+            assert_eq!(Terminal::of(&result), Terminal::Aborted);
+            assert!(!in_flight.settle("enhance-1"));
+        }
+    }
+
+    #[test]
+    fn test_errors_are_classified() {
+        let missing = anyhow::Error::from(OllamaError::ModelNotFound {
+            model: "qwen2.5:1.5b".to_string(),
+        });
+        assert_eq!(
+            EnhancementError::from_error(&missing),
+            EnhancementError::ModelMissing {
+                model: "qwen2.5:1.5b".to_string()
+            }
+        );
+        assert_eq!(
+            EnhancementError::from_error(&OllamaError::Unavailable.into()),
+            EnhancementError::OllamaUnavailable
+        );
+        let rejected = anyhow::Error::from(RejectedOutput(OutputCheck::Echo, "echo".to_string()));
+        assert_eq!(
+            EnhancementError::from_error(&rejected),
+            EnhancementError::OutputRejected {
+                check: OutputCheck::Echo
+            }
+        );
+        assert_eq!(
+            EnhancementError::from_error(&Aborted.into()),
+            EnhancementError::Cancelled
+        );
+        assert_eq!(
+            EnhancementError::from_error(&anyhow!("HTTP 500")),
+            EnhancementError::Failed {
+                message: "HTTP 500".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_sampling_options_follow_the_mode() {
+        let options = AiGenerationOptions::default();
+        let mut features = AiFeatures::default();
+        let summarize = EnhanceMode::Summarize {
+            style: AiSummaryStyle::default(),
+            max_words: 150,
+        };
+
+        assert_eq!(
+            sampling_options(&options, EnhanceMode::Correct, &features),
+            options.correct
+        );
+        assert_eq!(
+            sampling_options(&options, summarize, &features),
+            options.summarize
+        );
+
+        features.tone = Some(AiTone::Casual);
+        assert_eq!(
+            sampling_options(&options, EnhanceMode::Correct, &features),
+            options.tone
+        );
+        // A summary has no tone of its own
+        assert_eq!(
+            sampling_options(&options, summarize, &features),
+            options.summarize
+        );
+    }
+
+    /// A voice memo of 500 words, in sentences of ten
+    fn long_transcript() -> String {
+        vec!["so then we talked about the budget for next year"; 50].join("\n")
+    }
+
+    #[test]
+    fn test_long_text_is_skipped() {
+        let transcript = long_transcript();
+        let limited = limit_length(
+            &transcript,
+            400,
+            AiLongTextPolicy::Skip,
+            EnhanceMode::Correct,
+        );
+        assert_eq!(limited, None);
+    }
+
+    #[test]
+    fn test_long_text_is_chunked_in_full() {
+        let transcript = long_transcript();
+        let limited = limit_length(
+            &transcript,
+            400,
+            AiLongTextPolicy::Chunked,
+            EnhanceMode::Correct,
+        );
+        assert_eq!(limited, Some((transcript.as_str(), "")));
+    }
+
+    #[test]
+    fn test_long_text_is_enhanced_up_to_the_maximum() {
+        let transcript = long_transcript();
+        let (head, rest) = limit_length(
+            &transcript,
+            400,
+            AiLongTextPolicy::FirstWords,
+            EnhanceMode::Correct,
+        )
+        .unwrap();
+
+        assert_eq!(head.split_whitespace().count(), 400);
+        assert_eq!(rest.split_whitespace().count(), 100);
+        // Nothing dictated is lost, nor the line break between the two
+        assert!(rest.starts_with('\n'));
+        assert_eq!(format!("{}{}", head, rest), transcript);
+    }
+
+    #[test]
+    fn test_text_within_the_maximum_and_summaries_are_unaffected() {
+        let transcript = long_transcript();
+        let summarize = EnhanceMode::Summarize {
+            style: AiSummaryStyle::default(),
+            max_words: 150,
+        };
+        for policy in [AiLongTextPolicy::Skip, AiLongTextPolicy::FirstWords] {
+            assert_eq!(
+                limit_length(&transcript, 500, policy, EnhanceMode::Correct),
+                Some((transcript.as_str(), ""))
+            );
+            assert_eq!(
+                limit_length(&transcript, 400, policy, summarize),
+                Some((transcript.as_str(), ""))
+            );
+        }
+    }
+
+    /// The prompts previewed for a transcript going through every part of the
+    /// assembly, compared against `ai_toolkit/snapshots/preview_prompts.txt`
+    #[test]
+    fn test_preview_prompt_snapshot() {
+        let mut settings = get_default_settings();
+        settings.ai_vocabulary = vec![AiVocabularyEntry {
+            spoken_forms: vec!["cube era".to_string()],
+            written_form: "Kubera".to_string(),
+        }];
+        settings.ai_proper_nouns = vec![AiProperNoun {
+            name: "Siobhan".to_string(),
+            misrecognitions: vec![],
+        }];
+        settings.ai_examples = vec![AiExample {
+            input: "please send the quarterly report to the whole team by friday".to_string(),
+            output: "Please send the quarterly report to the whole team by Friday.".to_string(),
+        }];
+        let english = DetectedLanguage {
+            code: "eng".to_string(),
+            name: "English".to_string(),
+            confidence: 1.0,
+        };
+        let transcript =
+            "so Siobhan shared the cube era dashboard at https://example.com and um it looks great";
+
+        let mut snapshot = String::new();
+        for (title, mode) in [
+            ("correct", EnhanceMode::Correct),
+            (
+                "summarize",
+                EnhanceMode::Summarize {
+                    style: AiSummaryStyle::Bullets,
+                    max_words: 60,
+                },
+            ),
+        ] {
+            for fitted in prompts_for(
+                transcript,
+                &settings,
+                mode,
+                Some(&english),
+                Some("We met on Tuesday."),
+            ) {
+                snapshot.push_str(&format!("===== {} =====\n", title));
+                snapshot.push_str(&fitted.prompt);
+                snapshot.push_str("\n\n");
+            }
+        }
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/ai_toolkit/snapshots/preview_prompts.txt");
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, &snapshot).unwrap();
+        }
+        assert_eq!(snapshot, std::fs::read_to_string(&path).unwrap());
+    }
+
+    #[test]
+    fn test_preview_leaves_out_context_where_enhancement_would() {
+        let mut settings = get_default_settings();
+        let transcript = "so we agreed to ship the release on friday after the review";
+        let summarize = EnhanceMode::Summarize {
+            style: AiSummaryStyle::Bullets,
+            max_words: 60,
+        };
+        let preceding = "We met on Tuesday.";
+        let shows_context = |settings: &AppSettings, mode| {
+            preview_prompts(transcript, settings, mode, Some(preceding))
+                .prompts
+                .iter()
+                .any(|prompt| prompt.contains(preceding))
+        };
+
+        settings.ai_context_enabled = true;
+        assert!(shows_context(&settings, EnhanceMode::Correct));
+        assert!(!shows_context(&settings, summarize));
+        settings.ai_context_enabled = false;
+        assert!(!shows_context(&settings, EnhanceMode::Correct));
+
+        let preview = preview_prompts(transcript, &settings, EnhanceMode::Correct, None);
+        assert_eq!(preview.prompts.len(), 1);
+        assert_eq!(
+            preview.estimated_tokens as usize,
+            estimate_tokens(&preview.prompts[0])
+        );
+    }
+
+    #[test]
+    fn test_preview_drops_what_does_not_fit_the_context() {
+        let mut settings = get_default_settings();
+        settings.ai_context_enabled = true;
+        let transcript = "so we agreed to ship the release on friday after the review";
+
+        let preview = preview_prompts(
+            transcript,
+            &settings,
+            EnhanceMode::Correct,
+            Some("We met on Tuesday."),
+        );
+        assert!(preview.dropped.is_empty());
+
+        // Context as long as the model's whole window is the first to go
+        let preceding = "We went over the plan again. ".repeat(300);
+        let preview = preview_prompts(
+            transcript,
+            &settings,
+            EnhanceMode::Correct,
+            Some(&preceding),
+        );
+        assert_eq!(preview.dropped, vec![AiPromptComponent::Context]);
+        assert!(!preview.prompts[0].contains("We went over the plan again."));
+        assert!(
+            preview.estimated_tokens as usize <= prompt_budget(transcript, DEFAULT_CONTEXT_TOKENS)
+        );
+    }
+
+    #[test]
+    fn test_transcripts_too_long_for_the_context_are_chunked() {
+        let fits = max_input_tokens(DEFAULT_CONTEXT_TOKENS);
+        let sentence = "We went over the numbers for the third quarter again. ";
+        let short = sentence.repeat(5);
+        assert_eq!(
+            model_chunks(&short, EnhanceMode::EmailFormat, 500),
+            [short.as_str()]
+        );
+
+        let long = sentence.repeat(100);
+        let chunks = model_chunks(&long, EnhanceMode::EmailFormat, 500);
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat(), long);
+        assert!(chunks.iter().all(|chunk| estimate_tokens(chunk) <= fits));
+
+        // A chunk size set larger than the context takes is capped to it
+        let chunks = model_chunks(&long, EnhanceMode::Correct, 10_000);
+        assert!(chunks.iter().all(|chunk| estimate_tokens(chunk) <= fits));
+    }
+
+    #[test]
+    fn test_cancelled_enhancements_cannot_be_rated() {
+        assert!(check_ratable(&EnhancementOutcome::Enhanced).is_ok());
+        let timed_out = EnhancementOutcome::Fallback {
+            reason: AiFallbackReason::Timeout,
+        };
+        assert!(check_ratable(&timed_out).is_ok());
+
+        let aborted = EnhancementOutcome::Fallback {
+            reason: AiFallbackReason::Aborted,
+        };
+        let error = check_ratable(&aborted).unwrap_err().to_string();
+        assert!(error.contains("cancelled"), "{}", error);
+    }
+
+    #[test]
+    fn test_markdown_structure_is_hidden_from_the_model() {
+        let settings = get_default_settings();
+        let features = &settings.ai_features;
+        let transcript =
+            "heading release plan. bullet point ship on friday code block cargo publish end code block";
+
+        let (protected, text) = model_input(transcript, features, EnhanceMode::Markdown, &[]);
+        assert_eq!(text, "⟦H1⟧ Release plan\n\n⟦H2⟧ Ship on friday\n\n⟦H3⟧");
+        assert_eq!(
+            protected
+                .restore("⟦H1⟧ Release plan\n\n⟦H2⟧ Ship on Friday.\n\n⟦H3⟧")
+                .unwrap(),
+            "## Release plan\n\n- Ship on Friday.\n\n```\ncargo publish\n```"
+        );
+
+        // Correcting leaves the words as they were said
+        let (_, text) = model_input(transcript, features, EnhanceMode::Correct, &[]);
+        assert_eq!(text, transcript);
+    }
+
+    #[test]
+    fn test_code_is_written_by_rule_and_hidden_from_the_model() {
+        let mut features = get_default_settings().ai_features;
+        features.spoken_punctuation = true;
+        let transcript = "Set max retries underscore count, equals five.";
+
+        let text = remove_by_rule(transcript, &features, EnhanceMode::Code);
+        assert_eq!(text, transcript);
+        let (protected, text) = model_input(&text, &features, EnhanceMode::Code, &[]);
+        assert_eq!(text, "set max ⟦H1⟧ ⟦H2⟧ ⟦H3⟧");
+        assert_eq!(
+            protected.restore("set max ⟦H1⟧ ⟦H2⟧ ⟦H3⟧").unwrap(),
+            "set max retries_count = 5"
+        );
+        assert!(!EnhanceMode::Code.applies_tone());
+        assert!(!EnhanceMode::Code.writes_prose());
+    }
+
+    #[test]
+    fn test_snippets_are_hidden_from_the_model() {
+        let mut settings = get_default_settings();
+        settings.ai_snippets = vec![AiSnippet {
+            trigger: "insert my signature".to_string(),
+            text: "Jane Doe\nAcme Corp".to_string(),
+            within_text: true,
+        }];
+        let transcript = "thanks for the update insert my signature";
+
+        let snippets = snippets_in(transcript, &settings, EnhanceMode::Correct);
+        let (protected, text) = model_input(
+            snippets.text(),
+            &settings.ai_features,
+            EnhanceMode::Correct,
+            &[],
+        );
+        assert_eq!(text, "thanks for the update ⟦H1⟧");
+        let restored = protected.restore("Thanks for the update. ⟦H1⟧").unwrap();
+        assert_eq!(
+            snippets.expand(&restored),
+            "Thanks for the update. Jane Doe\nAcme Corp"
+        );
+
+        // Code is only written by its own rules
+        assert!(snippets_in(transcript, &settings, EnhanceMode::Code).is_empty());
+    }
+
+    #[test]
+    fn test_complete_event_carries_the_pasted_snippet() {
+        let mut settings = get_default_settings();
+        settings.ai_snippets = vec![AiSnippet {
+            trigger: "insert my signature".to_string(),
+            text: "Jane Doe\nAcme Corp".to_string(),
+            within_text: true,
+        }];
+        let transcript = " thanks for the update insert my signature";
+
+        let snippets = snippets_in(transcript, &settings, EnhanceMode::Correct);
+        let (_, marker) = snippets.text().rsplit_once(' ').unwrap();
+        let answer = format!("Thanks for the update. {}", marker);
+        let pasted = pasted_text(transcript, &snippets, &answer);
+        assert_eq!(pasted, " Thanks for the update. Jane Doe\nAcme Corp");
+
+        let stats = AiEnhancementStats {
+            model: "llama3.2".to_string(),
+            duration_ms: 0,
+            streamed: true,
+            language: None,
+            cached: false,
+            latency_choice: None,
+        };
+        let features = &settings.ai_features;
+        let completion = Completion::new(stats, features, EnhanceMode::Correct, "");
+        let event = completion.event("request", transcript, &pasted, &settings);
+        assert_eq!(event.text, pasted);
+        assert!(!event.text.contains(marker));
+    }
+}
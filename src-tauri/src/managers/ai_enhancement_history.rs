@@ -0,0 +1,366 @@
+use crate::ai_toolkit::diff::DiffOp;
+use crate::managers::ai_enhancement::EnhancementOutcome;
+use crate::managers::json_store::write_replacing;
+use crate::settings::AiFeatures;
+use anyhow::{anyhow, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Most entries kept. Beyond this the oldest are dropped down to
+/// `PRUNED_ENTRIES`, so a full history isn't rewritten for every new entry.
+const MAX_ENTRIES: usize = 1000;
+const PRUNED_ENTRIES: usize = 900;
+
+/// Longest note kept with a rating
+const MAX_RATING_NOTE_CHARS: usize = 500;
+
+/// Thumbs up or down given to an enhancement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiRating {
+    Up,
+    Down,
+}
+
+/// How the user rated an enhancement right after it was pasted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiEnhancementRating {
+    pub rating: AiRating,
+    /// What was wrong or right with it, in the user's words
+    pub note: Option<String>,
+}
+
+impl AiEnhancementRating {
+    /// A rating with `note` trimmed, and dropped when blank
+    pub fn new(rating: AiRating, note: Option<&str>) -> Result<Self> {
+        let note = note.map(str::trim).filter(|note| !note.is_empty());
+        if note.is_some_and(|note| note.chars().count() > MAX_RATING_NOTE_CHARS) {
+            return Err(anyhow!(
+                "A rating's note can be at most {} characters",
+                MAX_RATING_NOTE_CHARS
+            ));
+        }
+        Ok(Self {
+            rating,
+            note: note.map(str::to_string),
+        })
+    }
+}
+
+/// A transcript handed to AI enhancement and what came of it
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementHistoryEntry {
+    pub id: u32,
+    /// Unix timestamp (seconds) of when the enhancement finished
+    pub timestamp: i64,
+    pub model: String,
+    pub features: AiFeatures,
+    /// Mode the text was enhanced in, like "correct" or "summarize"
+    pub mode: String,
+    pub original_text: String,
+    /// Text the enhancement returned, which is the original unless the
+    /// outcome is `enhanced`
+    pub enhanced_text: String,
+    pub duration_ms: u32,
+    pub outcome: EnhancementOutcome,
+    /// What enhancement changed, word by word, unless the texts were too
+    /// long or too different to diff
+    #[serde(default)]
+    pub diff: Option<Vec<DiffOp>>,
+    #[serde(default)]
+    pub rating: Option<AiEnhancementRating>,
+    /// The entry whose enhanced text was enhanced again into this one, on
+    /// demand. That entry is left as it was.
+    #[serde(default)]
+    pub reenhanced_from: Option<u32>,
+}
+
+/// Enhancements appended as JSON lines to a file in the app data directory,
+/// so what the model changed can be checked long after it was pasted
+pub struct EnhancementHistory {
+    path: Option<PathBuf>,
+    /// Oldest first
+    entries: Vec<AiEnhancementHistoryEntry>,
+    next_id: u32,
+}
+
+pub type SharedEnhancementHistory = Arc<Mutex<EnhancementHistory>>;
+
+impl EnhancementHistory {
+    /// Load the history from `path`. A missing file starts empty, and lines
+    /// that can't be read are skipped.
+    pub fn load(path: PathBuf) -> Self {
+        let mut entries: Vec<AiEnhancementHistoryEntry> = Vec::new();
+        if let Ok(contents) = fs::read_to_string(&path) {
+            for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+                match serde_json::from_str(line) {
+                    Ok(entry) => entries.push(entry),
+                    Err(e) => warn!(
+                        "Skipping unreadable enhancement history line in {}: {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+
+        let next_id = entries.iter().map(|entry| entry.id + 1).max().unwrap_or(1);
+        Self {
+            path: Some(path),
+            entries,
+            next_id,
+        }
+    }
+
+    /// History kept in memory only, used when the app data dir is unavailable
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Add `entry` under a new id, dropping the oldest entries past the cap.
+    /// Returns the id.
+    pub fn append(&mut self, mut entry: AiEnhancementHistoryEntry) -> u32 {
+        entry.id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(entry);
+
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - PRUNED_ENTRIES;
+            self.entries.drain(..excess);
+            self.rewrite();
+        } else if let Some(entry) = self.entries.last() {
+            self.append_line(entry);
+        }
+        self.next_id - 1
+    }
+
+    /// Up to `limit` entries after skipping `offset`, newest first
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<AiEnhancementHistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&AiEnhancementHistoryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Rate the entry `id`, replacing any earlier rating of it
+    pub fn rate(&mut self, id: u32, rating: AiEnhancementRating) -> Result<()> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| anyhow!("No enhancement history entry with id {}", id))?;
+        entry.rating = Some(rating);
+        self.rewrite();
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: u32) -> Result<()> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or_else(|| anyhow!("No enhancement history entry with id {}", id))?;
+        self.entries.remove(index);
+        self.rewrite();
+        Ok(())
+    }
+
+    fn append_line(&self, entry: &AiEnhancementHistoryEntry) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let result = serde_json::to_string(entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", json).map_err(anyhow::Error::from)
+            });
+        if let Err(e) = result {
+            warn!(
+                "Failed to append to the enhancement history at {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+
+    fn rewrite(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let result = self
+            .entries
+            .iter()
+            .map(|entry| serde_json::to_string(entry).map(|json| json + "\n"))
+            .collect::<serde_json::Result<String>>()
+            .map_err(anyhow::Error::from)
+            .and_then(|lines| write_replacing(path, &lines));
+        if let Err(e) = result {
+            warn!(
+                "Failed to save the enhancement history to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::test_fixtures::temp_path;
+
+    fn entry(original: &str) -> AiEnhancementHistoryEntry {
+        AiEnhancementHistoryEntry {
+            id: 0,
+            timestamp: 1_700_000_000,
+            model: "llama3.2:1b".to_string(),
+            features: AiFeatures::default(),
+            mode: "correct".to_string(),
+            original_text: original.to_string(),
+            enhanced_text: original.to_uppercase(),
+            duration_ms: 420,
+            outcome: EnhancementOutcome::Enhanced,
+            diff: None,
+            rating: None,
+            reenhanced_from: None,
+        }
+    }
+
+    fn originals(entries: &[AiEnhancementHistoryEntry]) -> Vec<&str> {
+        entries
+            .iter()
+            .map(|entry| entry.original_text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_entries_survive_reload() {
+        let path = temp_path("enhancement-history");
+        let _ = fs::remove_file(&path);
+
+        let mut history = EnhancementHistory::load(path.clone());
+        let one = history.append(entry("one"));
+        let two = history.append(entry("two"));
+        history.append(AiEnhancementHistoryEntry {
+            reenhanced_from: Some(one),
+            ..entry("three")
+        });
+        history.delete(two).unwrap();
+
+        let mut reloaded = EnhancementHistory::load(path.clone());
+        assert_eq!(originals(&reloaded.page(0, 10)), vec!["three", "one"]);
+        assert_eq!(reloaded.page(0, 1)[0].enhanced_text, "THREE");
+        assert_eq!(reloaded.page(0, 1)[0].reenhanced_from, Some(one));
+        assert_eq!(reloaded.get(one).unwrap().reenhanced_from, None);
+
+        // Ids keep counting up across restarts
+        assert_eq!(reloaded.append(entry("four")), 4);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_pages_newest_first() {
+        let mut history = EnhancementHistory::in_memory();
+        for original in ["a", "b", "c", "d"] {
+            history.append(entry(original));
+        }
+
+        assert_eq!(originals(&history.page(0, 2)), vec!["d", "c"]);
+        assert_eq!(originals(&history.page(2, 10)), vec!["b", "a"]);
+        assert!(history.page(4, 10).is_empty());
+        assert!(history.delete(99).is_err());
+    }
+
+    #[test]
+    fn test_prunes_the_oldest_entries() {
+        let path = temp_path("enhancement-history-cap");
+        let _ = fs::remove_file(&path);
+
+        let mut history = EnhancementHistory::load(path.clone());
+        for i in 0..MAX_ENTRIES + 5 {
+            history.append(entry(&i.to_string()));
+        }
+
+        let reloaded = EnhancementHistory::load(path.clone());
+        let all = reloaded.page(0, usize::MAX);
+        assert_eq!(all.len(), PRUNED_ENTRIES + 4);
+        assert_eq!(all[0].original_text, (MAX_ENTRIES + 4).to_string());
+        assert_eq!(
+            all.last().unwrap().original_text,
+            (MAX_ENTRIES + 1 - PRUNED_ENTRIES).to_string()
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_ratings_survive_reload() {
+        let path = temp_path("enhancement-history-ratings");
+        let _ = fs::remove_file(&path);
+
+        let mut history = EnhancementHistory::load(path.clone());
+        let rated = history.append(entry("rated"));
+        history.append(entry("unrated"));
+        let down = AiEnhancementRating::new(AiRating::Down, Some("  added a comma ")).unwrap();
+        history.rate(rated, down).unwrap();
+        // Rating again replaces the first one
+        let up = AiEnhancementRating::new(AiRating::Up, None).unwrap();
+        history.rate(rated, up.clone()).unwrap();
+        assert!(history.rate(99, up.clone()).is_err());
+
+        let reloaded = EnhancementHistory::load(path.clone());
+        assert_eq!(reloaded.get(rated).unwrap().rating, Some(up));
+        assert_eq!(reloaded.page(0, 1)[0].rating, None);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rating_notes_are_trimmed_and_capped() {
+        let rating = AiEnhancementRating::new(AiRating::Down, Some("  lost a name ")).unwrap();
+        assert_eq!(rating.note.as_deref(), Some("lost a name"));
+        let blank = AiEnhancementRating::new(AiRating::Up, Some("   ")).unwrap();
+        assert_eq!(blank.note, None);
+        let long = "x".repeat(MAX_RATING_NOTE_CHARS + 1);
+        assert!(AiEnhancementRating::new(AiRating::Up, Some(&long)).is_err());
+    }
+
+    #[test]
+    fn test_unreadable_lines_are_skipped() {
+        let path = temp_path("enhancement-history-corrupt");
+        let _ = fs::remove_file(&path);
+
+        let mut history = EnhancementHistory::load(path.clone());
+        history.append(entry("kept"));
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let reloaded = EnhancementHistory::load(path.clone());
+        assert_eq!(originals(&reloaded.page(0, 10)), vec!["kept"]);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,487 @@
+use crate::ai_toolkit::output_guard::OutputCheck;
+use crate::managers::ai_enhancement::{AiFallbackReason, EnhancementOutcome};
+use crate::managers::ai_enhancement_history::AiRating;
+use crate::managers::json_store::JsonStore;
+use crate::settings::AiFeatures;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Upper bounds of the latency histogram's buckets, in milliseconds. A last
+/// bucket takes everything slower.
+const LATENCY_BUCKETS_MS: [u32; 5] = [250, 500, 1000, 2000, 5000];
+
+/// How AI enhancement has been doing since `since`. Counted on this device
+/// only and saved in the app data directory; none of it is ever sent over
+/// the network.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct AiEnhancementMetrics {
+    /// Unix timestamp (seconds) of when counting started or was last reset
+    pub since: i64,
+    /// Transcripts handed to enhancement, including skipped and cached ones
+    pub runs: u32,
+    pub enhanced: u32,
+    /// Too short, without letters, or in a language enhancement is skipped for
+    pub skipped: u32,
+    pub cache_hits: u32,
+    pub cache_misses: u32,
+    /// Enhancements that failed with falling back to the original turned off
+    pub errors: u32,
+    pub fallbacks: AiFallbackCounts,
+    /// Which check threw out the model's output, for `rejected_output`
+    /// fallbacks and errors
+    pub rejections: AiRejectionCounts,
+    /// Outputs that came back empty or rejected and were generated again
+    pub retries: u32,
+    /// Outputs cut off at the output token limit, whose part of the
+    /// transcript kept its original text
+    pub truncated: u32,
+    /// Second passes of the two-pass mode
+    pub refinements: AiRefinementCounts,
+    /// Time taken by the runs that went to the model
+    pub latency: Vec<AiLatencyBucket>,
+    pub average_latency_ms: f64,
+    /// Generation speed Ollama reported, over everything it generated
+    pub tokens_per_second: f64,
+    pub total_latency_ms: f64,
+    pub generated_tokens: f64,
+    pub generation_secs: f64,
+    /// Ratings given to enhancements right after they were pasted
+    pub satisfaction: AiSatisfaction,
+    /// Ratings by the model that made the enhancement
+    pub satisfaction_by_model: HashMap<String, AiSatisfaction>,
+    /// Ratings by each feature that was enabled for the enhancement, like
+    /// "fix_spelling"
+    pub satisfaction_by_feature: HashMap<String, AiSatisfaction>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct AiFallbackCounts {
+    pub timeout: u32,
+    pub ollama_unavailable: u32,
+    pub model_missing: u32,
+    pub failed: u32,
+    pub rejected_output: u32,
+    pub aborted: u32,
+}
+
+impl AiFallbackCounts {
+    pub fn count(&mut self, reason: AiFallbackReason) {
+        *match reason {
+            AiFallbackReason::Timeout => &mut self.timeout,
+            AiFallbackReason::OllamaUnavailable => &mut self.ollama_unavailable,
+            AiFallbackReason::ModelMissing => &mut self.model_missing,
+            AiFallbackReason::Failed => &mut self.failed,
+            AiFallbackReason::RejectedOutput => &mut self.rejected_output,
+            AiFallbackReason::Aborted => &mut self.aborted,
+        } += 1;
+    }
+
+    /// Add the counts of `other` to these
+    pub fn add(&mut self, other: &AiFallbackCounts) {
+        self.timeout += other.timeout;
+        self.ollama_unavailable += other.ollama_unavailable;
+        self.model_missing += other.model_missing;
+        self.failed += other.failed;
+        self.rejected_output += other.rejected_output;
+        self.aborted += other.aborted;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct AiRejectionCounts {
+    /// Output far longer or shorter than the transcript
+    pub length: u32,
+    /// Output that only repeated the prompt
+    pub echo: u32,
+    /// Output that lost or duplicated protected spans
+    pub placeholders: u32,
+    /// Output that was empty even after a retry
+    pub empty: u32,
+    /// Output that kept too few of the transcript's words
+    pub overlap: u32,
+}
+
+/// How the second passes of the two-pass mode went
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct AiRefinementCounts {
+    /// Refinements the quality model finished before the next dictation
+    pub finished: u32,
+    /// Those that said something other than the pasted text, and were
+    /// offered in its place
+    pub differed: u32,
+}
+
+/// Thumbs up and down given to enhancements
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct AiSatisfaction {
+    pub up: u32,
+    pub down: u32,
+}
+
+impl AiSatisfaction {
+    fn count(&mut self, rating: AiRating) {
+        match rating {
+            AiRating::Up => self.up += 1,
+            AiRating::Down => self.down += 1,
+        }
+    }
+
+    fn uncount(&mut self, rating: AiRating) {
+        match rating {
+            AiRating::Up => self.up = self.up.saturating_sub(1),
+            AiRating::Down => self.down = self.down.saturating_sub(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiLatencyBucket {
+    /// Slowest run counted here, in milliseconds, or `null` for the last
+    /// bucket
+    pub up_to_ms: Option<u32>,
+    pub count: u32,
+}
+
+impl Default for AiEnhancementMetrics {
+    fn default() -> Self {
+        Self {
+            since: chrono::Utc::now().timestamp(),
+            runs: 0,
+            enhanced: 0,
+            skipped: 0,
+            cache_hits: 0,
+            cache_misses: 0,
+            errors: 0,
+            fallbacks: AiFallbackCounts::default(),
+            rejections: AiRejectionCounts::default(),
+            retries: 0,
+            truncated: 0,
+            refinements: AiRefinementCounts::default(),
+            latency: latency_buckets(),
+            average_latency_ms: 0.0,
+            tokens_per_second: 0.0,
+            total_latency_ms: 0.0,
+            generated_tokens: 0.0,
+            generation_secs: 0.0,
+            satisfaction: AiSatisfaction::default(),
+            satisfaction_by_model: HashMap::new(),
+            satisfaction_by_feature: HashMap::new(),
+        }
+    }
+}
+
+/// One transcript's trip through enhancement
+pub struct EnhancementRun {
+    /// What came of it, or `None` when it failed without falling back
+    pub outcome: Option<EnhancementOutcome>,
+    pub duration_ms: u32,
+    /// Whether the model was asked, rather than the run being skipped or
+    /// answered from the cache
+    pub reached_model: bool,
+    /// Whether it was answered from the cache
+    pub cached: bool,
+    pub rejected_by: Option<OutputCheck>,
+    /// Outputs generated again for being empty or rejected
+    pub retries: u32,
+    /// Outputs cut off at the output token limit
+    pub truncated: u32,
+    pub generated_tokens: u64,
+    pub generation_nanos: u64,
+}
+
+/// Enhancement metrics persisted as JSON in the app data directory, so the
+/// counts add up across restarts until they are reset
+pub struct EnhancementMetrics {
+    metrics: JsonStore<AiEnhancementMetrics>,
+}
+
+pub type SharedEnhancementMetrics = Arc<Mutex<EnhancementMetrics>>;
+
+impl EnhancementMetrics {
+    /// Load the metrics from `path`. A missing or unreadable file starts
+    /// from zero.
+    pub fn load(path: PathBuf) -> Self {
+        let mut metrics = JsonStore::<AiEnhancementMetrics>::load(path, "enhancement metrics");
+        // Saved with other bucket bounds, which can't be carried over
+        if metrics.latency.len() != LATENCY_BUCKETS_MS.len() + 1 {
+            metrics.latency = latency_buckets();
+        }
+
+        Self { metrics }
+    }
+
+    /// Metrics kept in memory only, used when the app data dir is unavailable
+    pub fn in_memory() -> Self {
+        Self {
+            metrics: JsonStore::in_memory(),
+        }
+    }
+
+    pub fn snapshot(&self) -> AiEnhancementMetrics {
+        self.metrics.clone()
+    }
+
+    pub fn record_cache_lookup(&mut self, hit: bool) {
+        if hit {
+            self.metrics.cache_hits += 1;
+        } else {
+            self.metrics.cache_misses += 1;
+        }
+    }
+
+    pub fn record_run(&mut self, run: &EnhancementRun) {
+        let metrics = &mut *self.metrics;
+        metrics.runs += 1;
+        match &run.outcome {
+            Some(EnhancementOutcome::Enhanced) => metrics.enhanced += 1,
+            Some(EnhancementOutcome::Skipped { .. }) => metrics.skipped += 1,
+            Some(EnhancementOutcome::Fallback { reason }) => metrics.fallbacks.count(*reason),
+            None => metrics.errors += 1,
+        }
+
+        if let Some(check) = run.rejected_by {
+            let rejections = &mut metrics.rejections;
+            *match check {
+                OutputCheck::Length => &mut rejections.length,
+                OutputCheck::Echo => &mut rejections.echo,
+                OutputCheck::Placeholders => &mut rejections.placeholders,
+                OutputCheck::Empty => &mut rejections.empty,
+                OutputCheck::Overlap => &mut rejections.overlap,
+            } += 1;
+        }
+
+        metrics.retries += run.retries;
+        metrics.truncated += run.truncated;
+
+        if run.reached_model {
+            let bucket = LATENCY_BUCKETS_MS
+                .iter()
+                .position(|bound| run.duration_ms <= *bound)
+                .unwrap_or(LATENCY_BUCKETS_MS.len());
+            metrics.latency[bucket].count += 1;
+            metrics.total_latency_ms += run.duration_ms as f64;
+            let timed: u32 = metrics.latency.iter().map(|bucket| bucket.count).sum();
+            metrics.average_latency_ms = metrics.total_latency_ms / timed as f64;
+        }
+
+        metrics.generated_tokens += run.generated_tokens as f64;
+        metrics.generation_secs += run.generation_nanos as f64 / 1_000_000_000.0;
+        if metrics.generation_secs > 0.0 {
+            metrics.tokens_per_second = metrics.generated_tokens / metrics.generation_secs;
+        }
+    }
+
+    /// Count a refinement the quality model finished, and whether it
+    /// `differed` from the pasted text
+    pub fn record_refinement(&mut self, differed: bool) {
+        self.metrics.refinements.finished += 1;
+        if differed {
+            self.metrics.refinements.differed += 1;
+        }
+    }
+
+    /// Count `rating` of an enhancement by `model` with `features`, in place
+    /// of `previous` when it was rated before. Only these totals are kept.
+    pub fn record_rating(
+        &mut self,
+        model: &str,
+        features: &AiFeatures,
+        rating: AiRating,
+        previous: Option<AiRating>,
+    ) {
+        let recount = |tally: &mut AiSatisfaction| {
+            if let Some(previous) = previous {
+                tally.uncount(previous);
+            }
+            tally.count(rating);
+        };
+        let metrics = &mut *self.metrics;
+        recount(&mut metrics.satisfaction);
+        recount(
+            metrics
+                .satisfaction_by_model
+                .entry(model.to_string())
+                .or_default(),
+        );
+        for feature in enabled_features(features) {
+            recount(metrics.satisfaction_by_feature.entry(feature).or_default());
+        }
+    }
+
+    /// Start counting from zero
+    pub fn reset(&mut self) {
+        *self.metrics = AiEnhancementMetrics::default();
+        self.save();
+    }
+
+    pub fn save(&self) {
+        self.metrics.save();
+    }
+}
+
+/// Names of the on/off features enabled in `features`
+fn enabled_features(features: &AiFeatures) -> Vec<String> {
+    match serde_json::to_value(features) {
+        Ok(serde_json::Value::Object(fields)) => fields
+            .into_iter()
+            .filter(|(_, value)| *value == serde_json::Value::Bool(true))
+            .map(|(name, _)| name)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn latency_buckets() -> Vec<AiLatencyBucket> {
+    LATENCY_BUCKETS_MS
+        .iter()
+        .map(|bound| Some(*bound))
+        .chain(std::iter::once(None))
+        .map(|up_to_ms| AiLatencyBucket { up_to_ms, count: 0 })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::ai_enhancement::AiSkipReason;
+    use crate::managers::test_fixtures::{run, temp_path};
+    use std::fs;
+
+    #[test]
+    fn test_counts_outcomes() {
+        let mut metrics = EnhancementMetrics::in_memory();
+        metrics.record_run(&run(Some(EnhancementOutcome::Enhanced), 300));
+        metrics.record_run(&run(Some(EnhancementOutcome::Enhanced), 900));
+        metrics.record_run(&EnhancementRun {
+            rejected_by: Some(OutputCheck::Echo),
+            retries: 1,
+            truncated: 1,
+            ..run(
+                Some(EnhancementOutcome::Fallback {
+                    reason: AiFallbackReason::RejectedOutput,
+                }),
+                7000,
+            )
+        });
+        metrics.record_run(&EnhancementRun {
+            reached_model: false,
+            generated_tokens: 0,
+            generation_nanos: 0,
+            ..run(
+                Some(EnhancementOutcome::Skipped {
+                    reason: AiSkipReason::TooShort,
+                }),
+                0,
+            )
+        });
+        metrics.record_run(&run(None, 100));
+        metrics.record_cache_lookup(true);
+        metrics.record_cache_lookup(false);
+        metrics.record_refinement(true);
+        metrics.record_refinement(false);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            (
+                snapshot.runs,
+                snapshot.enhanced,
+                snapshot.skipped,
+                snapshot.errors
+            ),
+            (5, 2, 1, 1)
+        );
+        assert_eq!(snapshot.fallbacks.rejected_output, 1);
+        assert_eq!(snapshot.rejections.echo, 1);
+        assert_eq!((snapshot.retries, snapshot.truncated), (1, 1));
+        assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (1, 1));
+        assert_eq!(
+            snapshot.refinements,
+            AiRefinementCounts {
+                finished: 2,
+                differed: 1,
+            }
+        );
+
+        let counts: Vec<u32> = snapshot.latency.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 1, 1, 0, 0, 1]);
+        assert_eq!(snapshot.average_latency_ms, 2075.0);
+        assert_eq!(snapshot.tokens_per_second, 80.0);
+    }
+
+    #[test]
+    fn test_ratings_break_down_by_model_and_feature() {
+        let mut metrics = EnhancementMetrics::in_memory();
+        let spelling = AiFeatures {
+            punctuation_and_capitalization: false,
+            remove_filler_words: false,
+            normalize_numbers: false,
+            fix_spelling: true,
+            ..AiFeatures::default()
+        };
+        let punctuation = AiFeatures {
+            punctuation_and_capitalization: true,
+            fix_spelling: false,
+            ..spelling.clone()
+        };
+        metrics.record_rating("llama3.2:1b", &spelling, AiRating::Down, None);
+        metrics.record_rating("llama3.2:1b", &punctuation, AiRating::Up, None);
+        metrics.record_rating("qwen2.5:3b", &punctuation, AiRating::Up, None);
+        // Changing a rating takes the first one back
+        metrics.record_rating(
+            "qwen2.5:3b",
+            &punctuation,
+            AiRating::Down,
+            Some(AiRating::Up),
+        );
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.satisfaction, AiSatisfaction { up: 1, down: 2 });
+        assert_eq!(
+            snapshot.satisfaction_by_model["llama3.2:1b"],
+            AiSatisfaction { up: 1, down: 1 }
+        );
+        assert_eq!(
+            snapshot.satisfaction_by_model["qwen2.5:3b"],
+            AiSatisfaction { up: 0, down: 1 }
+        );
+        assert_eq!(
+            snapshot.satisfaction_by_feature["fix_spelling"],
+            AiSatisfaction { up: 0, down: 1 }
+        );
+        assert_eq!(
+            snapshot.satisfaction_by_feature["punctuation_and_capitalization"],
+            AiSatisfaction { up: 1, down: 1 }
+        );
+        assert!(!snapshot
+            .satisfaction_by_feature
+            .contains_key("remove_filler_words"));
+    }
+
+    #[test]
+    fn test_counts_survive_reload_until_reset() {
+        let path = temp_path("enhancement-metrics");
+        let _ = fs::remove_file(&path);
+
+        let mut metrics = EnhancementMetrics::load(path.clone());
+        metrics.record_run(&run(Some(EnhancementOutcome::Enhanced), 300));
+        metrics.save();
+
+        let mut reloaded = EnhancementMetrics::load(path.clone());
+        assert_eq!(reloaded.snapshot(), metrics.snapshot());
+
+        reloaded.reset();
+        let reset = EnhancementMetrics::load(path.clone()).snapshot();
+        assert_eq!(reset.runs, 0);
+        assert_eq!(reset.latency, latency_buckets());
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::settings::{get_settings, AiQueuePolicy};
+use log::info;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::watch;
+
+/// Sent when a transcript joins the queue for enhancement
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementQueued {
+    pub id: u32,
+    /// Transcripts ahead of it, including the one being enhanced
+    pub ahead: u32,
+}
+
+/// Sent for a transcript dropped from the queue by a newer one under
+/// [`AiQueuePolicy::LatestWins`]. It is neither enhanced nor pasted.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementSuperseded {
+    pub id: u32,
+    pub superseded_by: u32,
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_id: u32,
+    /// Oldest first. The front transcript is the one being enhanced.
+    waiting: VecDeque<u32>,
+}
+
+/// Transcripts waiting to be enhanced and pasted, so they go one at a time
+/// and in the order they were dictated
+pub struct EnhancementQueue {
+    state: Mutex<QueueState>,
+    /// Bumped whenever a transcript leaves the queue
+    changed: watch::Sender<u64>,
+}
+
+pub type SharedEnhancementQueue = Arc<EnhancementQueue>;
+
+/// A transcript's place in the queue, given up when dropped
+pub struct QueueSlot<'a> {
+    queue: &'a EnhancementQueue,
+    pub id: u32,
+}
+
+/// A transcript that just joined the queue
+pub struct Joined<'a> {
+    pub slot: QueueSlot<'a>,
+    pub ahead: u32,
+    /// Waiting transcripts dropped to make way for this one
+    pub superseded: Vec<u32>,
+}
+
+impl EnhancementQueue {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            changed: watch::channel(0).0,
+        }
+    }
+
+    /// Add a transcript at the back. With [`AiQueuePolicy::LatestWins`] the
+    /// ones waiting behind the transcript being enhanced are dropped.
+    pub fn join(&self, policy: AiQueuePolicy) -> Joined<'_> {
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let id = state.next_id;
+
+        let mut superseded = Vec::new();
+        if policy == AiQueuePolicy::LatestWins && !state.waiting.is_empty() {
+            superseded = state.waiting.drain(1..).collect();
+        }
+        state.waiting.push_back(id);
+        let ahead = state.waiting.len() as u32 - 1;
+        drop(state);
+
+        if !superseded.is_empty() {
+            self.notify();
+        }
+        Joined {
+            slot: QueueSlot { queue: self, id },
+            ahead,
+            superseded,
+        }
+    }
+
+    fn finish(&self, id: u32) {
+        let mut state = self.state.lock().unwrap();
+        let Some(position) = state.waiting.iter().position(|waiting| *waiting == id) else {
+            return;
+        };
+        state.waiting.remove(position);
+        drop(state);
+        self.notify();
+    }
+
+    fn notify(&self) {
+        self.changed.send_modify(|version| *version += 1);
+    }
+}
+
+impl Default for EnhancementQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QueueSlot<'_> {
+    /// Wait until every transcript ahead has been pasted or dropped. `false`
+    /// when this one was dropped in the meantime.
+    pub async fn turn(&self) -> bool {
+        let mut changed = self.queue.changed.subscribe();
+        loop {
+            let position = self
+                .queue
+                .state
+                .lock()
+                .unwrap()
+                .waiting
+                .iter()
+                .position(|id| *id == self.id);
+            match position {
+                Some(0) => return true,
+                Some(_) => {}
+                None => return false,
+            }
+            if changed.changed().await.is_err() {
+                return false;
+            }
+        }
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.queue.finish(self.id);
+    }
+}
+
+/// Wait for a turn for work other than a dictated transcript, like a model
+/// benchmark or comparison. It joins again behind any transcript that drops
+/// it under [`AiQueuePolicy::LatestWins`], as it never replaces one.
+pub async fn take_turn(queue: &EnhancementQueue) -> QueueSlot<'_> {
+    loop {
+        let slot = queue.join(AiQueuePolicy::QueueAll).slot;
+        if slot.turn().await {
+            return slot;
+        }
+    }
+}
+
+/// Queue a transcript for enhancement under the configured policy, reporting
+/// it through `ai-enhancement-queued` and the transcripts it drops through
+/// `ai-enhancement-superseded`. Returns once it is this transcript's turn,
+/// or `None` when a newer transcript dropped it while it waited.
+pub async fn wait_for_turn<'a>(
+    app: &AppHandle,
+    queue: &'a EnhancementQueue,
+) -> Option<QueueSlot<'a>> {
+    let policy = get_settings(app).ai_queue_policy;
+    let Joined {
+        slot,
+        ahead,
+        superseded,
+    } = queue.join(policy);
+
+    let _ = app.emit(
+        "ai-enhancement-queued",
+        AiEnhancementQueued { id: slot.id, ahead },
+    );
+    for id in superseded {
+        info!(
+            "Transcript {} superseded by {} before enhancement",
+            id, slot.id
+        );
+        let _ = app.emit(
+            "ai-enhancement-superseded",
+            AiEnhancementSuperseded {
+                id,
+                superseded_by: slot.id,
+            },
+        );
+    }
+
+    if slot.turn().await {
+        Some(slot)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::{join, join_all};
+    use std::time::Duration;
+
+    /// Three transcripts dictated in quick succession, the first taking the
+    /// longest to enhance. Returns the ids pasted, in the order they were.
+    fn rapid_requests(policy: AiQueuePolicy) -> Vec<u32> {
+        let queue = EnhancementQueue::new();
+        let pasted = Mutex::new(Vec::new());
+        let joined: Vec<Joined<'_>> = (0..3).map(|_| queue.join(policy)).collect();
+
+        let requests = joined.into_iter().zip([60, 20, 0]).map(|(joined, millis)| {
+            let pasted = &pasted;
+            async move {
+                let slot = joined.slot;
+                if slot.turn().await {
+                    tokio::time::sleep(Duration::from_millis(millis)).await;
+                    pasted.lock().unwrap().push(slot.id);
+                }
+            }
+        });
+        tauri::async_runtime::block_on(join_all(requests));
+
+        assert!(queue.state.lock().unwrap().waiting.is_empty());
+        pasted.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_queue_all_pastes_in_dictation_order() {
+        assert_eq!(rapid_requests(AiQueuePolicy::QueueAll), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_latest_wins_drops_waiting_requests() {
+        assert_eq!(rapid_requests(AiQueuePolicy::LatestWins), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_latest_wins_reports_what_it_drops() {
+        let queue = EnhancementQueue::new();
+        let first = queue.join(AiQueuePolicy::LatestWins);
+        let second = queue.join(AiQueuePolicy::LatestWins);
+        assert_eq!((first.ahead, second.ahead), (0, 1));
+        assert!(second.superseded.is_empty());
+
+        let third = queue.join(AiQueuePolicy::LatestWins);
+        assert_eq!(third.superseded, vec![second.slot.id]);
+        assert_eq!(third.ahead, 1);
+        assert!(!tauri::async_runtime::block_on(second.slot.turn()));
+
+        // The first keeps its turn, the third gets it once the first is done
+        assert!(tauri::async_runtime::block_on(first.slot.turn()));
+        drop(first);
+        assert!(tauri::async_runtime::block_on(third.slot.turn()));
+    }
+
+    #[test]
+    fn test_take_turn_joins_again_behind_a_newer_transcript() {
+        let queue = EnhancementQueue::new();
+        let order = Mutex::new(Vec::new());
+        let first = queue.join(AiQueuePolicy::LatestWins).slot;
+
+        let benchmark = async {
+            let _slot = take_turn(&queue).await;
+            order.lock().unwrap().push("benchmark");
+        };
+        let dictation = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let latest = queue.join(AiQueuePolicy::LatestWins).slot;
+            drop(first);
+            if latest.turn().await {
+                order.lock().unwrap().push("dictation");
+            }
+        };
+        tauri::async_runtime::block_on(join(benchmark, dictation));
+
+        assert_eq!(order.into_inner().unwrap(), vec!["dictation", "benchmark"]);
+    }
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How much longer Ollama is asked to keep a model loaded than Handy waits
+/// before unloading it, so Handy unloads it first and reports it, while
+/// Ollama still does when Handy isn't running anymore
+const KEEP_ALIVE_MARGIN: Duration = Duration::from_secs(60);
+
+/// Sent as `ai-model-unloaded` once a model going unused was unloaded
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelUnloaded {
+    pub model: String,
+    pub idle_minutes: u32,
+}
+
+/// The model Handy used last and when, shared with the task unloading it
+/// once it goes unused
+#[derive(Default)]
+pub struct IdleModel {
+    last_used: Option<(String, Instant)>,
+}
+
+pub type SharedIdleModel = Arc<Mutex<IdleModel>>;
+
+impl IdleModel {
+    /// Record that `model`, named the way it is handed to Ollama, was used
+    /// at `now`
+    pub fn touch(&mut self, model: &str, now: Instant) {
+        self.last_used = Some((model.to_string(), now));
+    }
+
+    /// The model used last, named the way it is handed to Ollama, unless it
+    /// has been unloaded since
+    pub fn last_used(&self) -> Option<&str> {
+        self.last_used.as_ref().map(|(model, _)| model.as_str())
+    }
+
+    /// The model to unload at `now`, having gone unused for `idle`. It is
+    /// forgotten afterwards, so it is unloaded only once.
+    pub fn take_idle(&mut self, now: Instant, idle: Duration) -> Option<String> {
+        match &self.last_used {
+            Some((_, used)) if now.saturating_duration_since(*used) >= idle => {
+                self.last_used.take().map(|(model, _)| model)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How long a model may go unused before Handy unloads it, with
+/// `idle_minutes` of 0 meaning never
+pub fn idle_timeout(idle_minutes: u32) -> Option<Duration> {
+    (idle_minutes > 0).then(|| Duration::from_secs(idle_minutes as u64 * 60))
+}
+
+/// The `keep_alive` requests ask Ollama for when unloading after
+/// `idle_minutes`. `None` leaves Ollama's own default.
+pub fn idle_keep_alive(idle_minutes: u32) -> Option<Duration> {
+    idle_timeout(idle_minutes).map(|idle| idle + KEEP_ALIVE_MARGIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_is_unloaded_once_idle() {
+        let start = Instant::now();
+        let idle = Duration::from_secs(600);
+        let mut model = IdleModel::default();
+        assert_eq!(model.take_idle(start, idle), None);
+
+        model.touch("gemma2:2b", start);
+        assert_eq!(
+            model.take_idle(start + Duration::from_secs(599), idle),
+            None
+        );
+
+        // Using it again restarts the idle time
+        model.touch("gemma2:2b", start + Duration::from_secs(300));
+        assert_eq!(
+            model.take_idle(start + Duration::from_secs(600), idle),
+            None
+        );
+        assert_eq!(
+            model.take_idle(start + Duration::from_secs(900), idle),
+            Some("gemma2:2b".to_string())
+        );
+        assert_eq!(
+            model.take_idle(start + Duration::from_secs(2000), idle),
+            None
+        );
+    }
+
+    #[test]
+    fn test_keep_alive_outlasts_the_idle_timeout() {
+        assert_eq!(idle_timeout(0), None);
+        assert_eq!(idle_keep_alive(0), None);
+        assert_eq!(idle_timeout(10), Some(Duration::from_secs(600)));
+        assert!(idle_keep_alive(10).unwrap() > idle_timeout(10).unwrap());
+    }
+}
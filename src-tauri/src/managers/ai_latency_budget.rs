@@ -0,0 +1,305 @@
+use crate::ai_toolkit::chunking::estimate_tokens;
+use crate::ai_toolkit::AiModelInfo;
+use crate::managers::ai_benchmark::AiBenchmarkReport;
+use crate::managers::ai_pull_records::is_model_installed;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::{Arc, Mutex};
+
+/// Output tokens expected per estimated token of transcript. Corrections
+/// come out about as long as what was dictated, a little longer with
+/// punctuation added.
+const OUTPUT_TOKENS_PER_INPUT_TOKEN: f64 = 1.1;
+
+/// The model a latency budget picked for a transcript, sent along with
+/// `ai-enhancement-complete`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiLatencyChoice {
+    pub model: String,
+    /// How long the model was expected to take, from its benchmark
+    pub estimated_ms: u32,
+    pub budget_ms: u32,
+}
+
+/// What the latency budget decided for a transcript
+#[derive(Debug, Clone, PartialEq)]
+pub enum LatencyFit {
+    /// No budget is set, or no benchmark to go by, so the selected model is
+    /// used
+    Unknown,
+    Fits(AiLatencyChoice),
+    /// No installed model is expected to finish in time
+    TooSlow,
+}
+
+/// How long an installed model takes to correct a transcript, going by its
+/// benchmark on this machine
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelLatency {
+    pub model: String,
+    /// The catalog's quality rating as a rank, higher being better
+    quality: u8,
+    size_mb: u32,
+    /// Time each request takes besides generating, mostly reading the prompt
+    overhead_ms: f64,
+    ms_per_token: f64,
+    /// Time loading the model took before the benchmark, close to none when
+    /// it was loaded already
+    load_ms: f64,
+}
+
+impl ModelLatency {
+    /// The latency `report` measured for the catalog model `info`. `None`
+    /// when the benchmark generated nothing to go by.
+    pub fn from_report(report: &AiBenchmarkReport, info: &AiModelInfo) -> Option<Self> {
+        if report.tokens_per_second <= 0.0 {
+            return None;
+        }
+
+        let overheads: Vec<f64> = report
+            .prompts
+            .iter()
+            .filter(|prompt| prompt.tokens_per_second > 0.0)
+            .map(|prompt| {
+                let generation_ms = prompt.output_tokens as f64 * 1000.0 / prompt.tokens_per_second;
+                (prompt.latency_ms - prompt.load_ms - generation_ms).max(0.0)
+            })
+            .collect();
+        let overhead_ms = if overheads.is_empty() {
+            0.0
+        } else {
+            overheads.iter().sum::<f64>() / overheads.len() as f64
+        };
+
+        Some(Self {
+            model: info.id.clone(),
+            quality: quality_rank(&info.quality),
+            size_mb: info.size_mb,
+            overhead_ms,
+            ms_per_token: 1000.0 / report.tokens_per_second,
+            load_ms: report.load_ms,
+        })
+    }
+
+    /// Milliseconds expected to generate `output_tokens`, counting loading
+    /// the model first unless it is `loaded`
+    pub fn estimate_ms(&self, output_tokens: usize, loaded: bool) -> f64 {
+        let load_ms = if loaded { 0.0 } else { self.load_ms };
+        load_ms + self.overhead_ms + output_tokens as f64 * self.ms_per_token
+    }
+}
+
+/// Rank of a quality rating from the catalog, higher being better
+fn quality_rank(quality: &str) -> u8 {
+    match quality {
+        "Best" => 4,
+        "Excellent" => 3,
+        "Very Good" => 2,
+        "Good" => 1,
+        _ => 0,
+    }
+}
+
+/// Output tokens a correction of `text` is expected to take
+pub fn expected_output_tokens(text: &str) -> usize {
+    (estimate_tokens(text) as f64 * OUTPUT_TOKENS_PER_INPUT_TOKEN).ceil() as usize
+}
+
+/// The latency of each model from `catalog` that is `installed` and has a
+/// benchmark among `reports`, the best quality first and the larger of two
+/// rated alike before the smaller
+pub fn latency_profiles(
+    reports: &[AiBenchmarkReport],
+    catalog: &[AiModelInfo],
+    installed: &[String],
+) -> Vec<ModelLatency> {
+    let mut profiles: Vec<ModelLatency> = catalog
+        .iter()
+        .filter(|info| is_model_installed(&info.id, installed))
+        .filter_map(|info| {
+            let report = reports
+                .iter()
+                .find(|report| is_model_installed(&info.id, std::slice::from_ref(&report.model)))?;
+            ModelLatency::from_report(report, info)
+        })
+        .collect();
+    profiles.sort_by(|a, b| {
+        b.quality
+            .cmp(&a.quality)
+            .then_with(|| b.size_mb.cmp(&a.size_mb))
+    });
+    profiles
+}
+
+/// The best of `profiles` expected to correct `text` within `budget_ms`.
+/// `loaded` is the model Ollama has loaded, if any, which doesn't have to
+/// be loaded again.
+pub fn pick_model(
+    profiles: &[ModelLatency],
+    text: &str,
+    budget_ms: u32,
+    loaded: Option<&str>,
+) -> Option<AiLatencyChoice> {
+    let output_tokens = expected_output_tokens(text);
+    profiles.iter().find_map(|profile| {
+        let estimated_ms = profile.estimate_ms(output_tokens, loaded == Some(&profile.model));
+        (estimated_ms <= budget_ms as f64).then(|| AiLatencyChoice {
+            model: profile.model.clone(),
+            estimated_ms: estimated_ms.round() as u32,
+            budget_ms,
+        })
+    })
+}
+
+/// The latency of the installed models, worked out once and kept until the
+/// benchmarks or the installed models change
+#[derive(Default)]
+pub struct LatencyProfiles {
+    profiles: Option<Vec<ModelLatency>>,
+}
+
+pub type SharedLatencyProfiles = Arc<Mutex<LatencyProfiles>>;
+
+impl LatencyProfiles {
+    pub fn get(&self) -> Option<Vec<ModelLatency>> {
+        self.profiles.clone()
+    }
+
+    pub fn set(&mut self, profiles: Vec<ModelLatency>) {
+        self.profiles = Some(profiles);
+    }
+
+    /// Work the latency out again before the next transcript
+    pub fn invalidate(&mut self) {
+        self.profiles = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::get_available_models;
+    use crate::managers::ai_benchmark::AiBenchmarkPromptResult;
+
+    /// A benchmark of `model` with two transcripts, each taking 200 ms
+    /// besides generating at `tokens_per_second`, and a load of `load_ms`
+    fn report(model: &str, tokens_per_second: f64, load_ms: f64) -> AiBenchmarkReport {
+        let prompt = |load_ms: f64, output_tokens: u32| AiBenchmarkPromptResult {
+            load_ms,
+            output_tokens,
+            tokens_per_second,
+            latency_ms: load_ms + 200.0 + output_tokens as f64 * 1000.0 / tokens_per_second,
+        };
+        AiBenchmarkReport {
+            model: model.to_string(),
+            app_version: "0.5.0".to_string(),
+            hardware: "linux".to_string(),
+            measured_at: 0,
+            prompts: vec![prompt(load_ms, 20), prompt(0.0, 60)],
+            load_ms,
+            tokens_per_second,
+            average_latency_ms: 0.0,
+        }
+    }
+
+    fn installed(models: &[&str]) -> Vec<String> {
+        models.iter().map(|model| model.to_string()).collect()
+    }
+
+    #[test]
+    fn test_estimates_from_the_benchmark() {
+        let catalog = get_available_models(None);
+        let info = catalog
+            .iter()
+            .find(|info| info.id == "llama3.2:1b")
+            .unwrap();
+        let latency =
+            ModelLatency::from_report(&report("llama3.2:1b", 50.0, 1500.0), info).unwrap();
+
+        // 200 ms besides generating, and 20 ms per token at 50 tokens/s
+        assert_eq!(latency.estimate_ms(40, true), 1000.0);
+        assert_eq!(latency.estimate_ms(40, false), 2500.0);
+        assert_eq!(latency.estimate_ms(0, true), 200.0);
+
+        // A benchmark that generated nothing can't be gone by
+        assert_eq!(
+            ModelLatency::from_report(&report("llama3.2:1b", 0.0, 0.0), info),
+            None
+        );
+    }
+
+    #[test]
+    fn test_output_grows_with_the_transcript() {
+        assert_eq!(expected_output_tokens(""), 0);
+        let short = expected_output_tokens("remind me to call the dentist");
+        let long = expected_output_tokens(&"remind me to call the dentist ".repeat(10));
+        assert!(short > 0);
+        assert!(long >= short * 9, "{} vs {}", long, short);
+    }
+
+    #[test]
+    fn test_profiles_rank_installed_benchmarked_models_by_quality() {
+        let reports = vec![
+            report("qwen2.5:0.5b", 120.0, 0.0),
+            report("llama3.2:1b", 50.0, 0.0),
+            report("qwen2.5:1.5b", 20.0, 0.0),
+            report("gemma2:2b", 90.0, 0.0),
+        ];
+        let profiles = latency_profiles(
+            &reports,
+            &get_available_models(None),
+            // gemma2:2b was benchmarked, but has been deleted since, and
+            // gemma2:1b was never benchmarked
+            &installed(&[
+                "qwen2.5:0.5b",
+                "llama3.2:1b-instruct-q4_K_M",
+                "qwen2.5:1.5b",
+                "gemma2:1b",
+            ]),
+        );
+
+        let models: Vec<&str> = profiles.iter().map(|p| p.model.as_str()).collect();
+        assert_eq!(models, vec!["qwen2.5:1.5b", "llama3.2:1b", "qwen2.5:0.5b"]);
+    }
+
+    #[test]
+    fn test_picks_the_best_model_within_the_budget() {
+        let reports = vec![
+            report("qwen2.5:0.5b", 100.0, 800.0),
+            report("qwen2.5:1.5b", 20.0, 3000.0),
+        ];
+        let profiles = latency_profiles(
+            &reports,
+            &get_available_models(None),
+            &installed(&["qwen2.5:0.5b", "qwen2.5:1.5b"]),
+        );
+        let text = "so um i think we should move the meeting to thursday";
+        let tokens = expected_output_tokens(text) as f64;
+        let (fast_ms, best_ms) = (200.0 + tokens * 10.0, 200.0 + tokens * 50.0);
+
+        // Plenty of time for the better model
+        let choice = pick_model(&profiles, text, 5000, Some("qwen2.5:1.5b")).unwrap();
+        assert_eq!(choice.model, "qwen2.5:1.5b");
+        assert_eq!(choice.estimated_ms, best_ms.round() as u32);
+        assert_eq!(choice.budget_ms, 5000);
+
+        // Loading it first would take too long, but the smaller one is loaded
+        let choice = pick_model(&profiles, text, 2000, Some("qwen2.5:0.5b")).unwrap();
+        assert_eq!(choice.model, "qwen2.5:0.5b");
+        assert_eq!(choice.estimated_ms, fast_ms.round() as u32);
+
+        // Neither fits, loaded or not
+        assert_eq!(pick_model(&profiles, text, 300, Some("qwen2.5:0.5b")), None);
+        assert_eq!(pick_model(&[], text, 5000, None), None);
+    }
+
+    #[test]
+    fn test_profiles_are_worked_out_again_once_invalidated() {
+        let mut profiles = LatencyProfiles::default();
+        assert_eq!(profiles.get(), None);
+        profiles.set(Vec::new());
+        assert_eq!(profiles.get(), Some(Vec::new()));
+        profiles.invalidate();
+        assert_eq!(profiles.get(), None);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::ai_toolkit::AiModelInfo;
+use crate::managers::ai_enhancement::AiFallbackReason;
+use crate::managers::ai_pull_records::is_model_installed;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Sent as `ai-model-degraded` when the selected model kept failing and
+/// dictations go to a smaller one for the rest of the session
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelDegraded {
+    /// The selected model, which stays selected in the settings
+    pub model: String,
+    /// The smaller model used in its place
+    pub fallback_model: String,
+    /// Enhancements that failed in a row before switching
+    pub failures: u32,
+    /// Why the last of them failed
+    pub reason: AiFallbackReason,
+}
+
+/// Consecutive failures per model, and the smaller model standing in for
+/// the selected one after too many of them. Kept in memory only, so every
+/// session starts with the selected model.
+#[derive(Debug, Default)]
+pub struct ModelDegradation {
+    failures: HashMap<String, u32>,
+    /// The selected model and the one used in its place
+    degraded: Option<(String, String)>,
+}
+
+pub type SharedModelDegradation = Arc<Mutex<ModelDegradation>>;
+
+impl ModelDegradation {
+    /// The model to enhance with when `selected` is selected
+    pub fn active_model<'a>(&'a self, selected: &'a str) -> &'a str {
+        match &self.degraded {
+            Some((from, to)) if from == selected => to,
+            _ => selected,
+        }
+    }
+
+    /// Count a failed enhancement with `model`. Returns how many failed in
+    /// a row once that reaches `threshold`, when it is time to switch. A
+    /// `threshold` of 0 never switches.
+    pub fn record_failure(&mut self, model: &str, threshold: u32) -> Option<u32> {
+        let failures = self.failures.entry(model.to_string()).or_default();
+        *failures += 1;
+        (threshold > 0 && *failures >= threshold).then_some(*failures)
+    }
+
+    /// Use `to` in place of the selected model `from`
+    pub fn degrade(&mut self, from: &str, to: &str) {
+        self.failures.remove(to);
+        self.degraded = Some((from.to_string(), to.to_string()));
+    }
+
+    /// Count a successful enhancement with `model`. Working again, the
+    /// selected model is used from now on, and it is returned.
+    pub fn record_success(&mut self, model: &str) -> Option<String> {
+        self.failures.remove(model);
+        match &self.degraded {
+            Some((from, _)) if from == model => self.reset(),
+            _ => None,
+        }
+    }
+
+    /// Go back to the selected model, returning it if another was used in
+    /// its place
+    pub fn reset(&mut self) -> Option<String> {
+        self.failures.clear();
+        self.degraded.take().map(|(from, _)| from)
+    }
+}
+
+/// The installed model that comes before `model` in the catalog and is
+/// smaller than it, the nearest one first. `None` for models the catalog
+/// doesn't know the size of.
+pub fn smaller_model(model: &str, catalog: &[AiModelInfo], installed: &[String]) -> Option<String> {
+    let index = catalog.iter().position(|info| info.id == model)?;
+    let size_mb = catalog[index].size_mb;
+    catalog[..index]
+        .iter()
+        .rev()
+        .filter(|info| info.size_mb < size_mb)
+        .find(|info| is_model_installed(&info.id, installed))
+        .map(|info| info.id.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::get_available_models;
+
+    const SELECTED: &str = "qwen2.5:1.5b";
+
+    #[test]
+    fn test_switches_after_repeated_failures_and_restores() {
+        let catalog = get_available_models(None);
+        let installed = vec!["qwen2.5:1.5b".to_string(), "qwen2.5:0.5b".to_string()];
+        let mut degradation = ModelDegradation::default();
+
+        assert_eq!(degradation.record_failure(SELECTED, 3), None);
+        assert_eq!(degradation.record_failure(SELECTED, 3), None);
+        let failures = degradation.record_failure(SELECTED, 3);
+        assert_eq!(failures, Some(3));
+
+        let smaller = smaller_model(SELECTED, &catalog, &installed).unwrap();
+        assert_eq!(smaller, "qwen2.5:0.5b");
+        degradation.degrade(SELECTED, &smaller);
+        assert_eq!(degradation.active_model(SELECTED), "qwen2.5:0.5b");
+        // Another selected model is used as it is
+        assert_eq!(degradation.active_model("llama3.2:1b"), "llama3.2:1b");
+
+        // Working with the stand-in changes nothing
+        assert_eq!(degradation.record_success("qwen2.5:0.5b"), None);
+        assert_eq!(degradation.active_model(SELECTED), "qwen2.5:0.5b");
+
+        // Working with the selected model again restores it
+        assert_eq!(
+            degradation.record_success(SELECTED).as_deref(),
+            Some(SELECTED)
+        );
+        assert_eq!(degradation.active_model(SELECTED), SELECTED);
+        assert_eq!(degradation.record_failure(SELECTED, 3), None);
+    }
+
+    #[test]
+    fn test_only_failures_in_a_row_count() {
+        let mut degradation = ModelDegradation::default();
+        degradation.record_failure(SELECTED, 2);
+        degradation.record_success(SELECTED);
+        assert_eq!(degradation.record_failure(SELECTED, 2), None);
+        assert_eq!(degradation.record_failure(SELECTED, 2), Some(2));
+
+        // Disabled, it never switches
+        let mut degradation = ModelDegradation::default();
+        for _ in 0..10 {
+            assert_eq!(degradation.record_failure(SELECTED, 0), None);
+        }
+    }
+
+    #[test]
+    fn test_reset_restores_the_selected_model() {
+        let mut degradation = ModelDegradation::default();
+        assert_eq!(degradation.reset(), None);
+
+        degradation.degrade(SELECTED, "gemma2:2b");
+        assert_eq!(degradation.reset().as_deref(), Some(SELECTED));
+        assert_eq!(degradation.active_model(SELECTED), SELECTED);
+    }
+
+    #[test]
+    fn test_smaller_model_is_installed_and_smaller() {
+        let catalog = get_available_models(None);
+        let installed = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        // The nearest smaller one that is installed
+        assert_eq!(
+            smaller_model(
+                SELECTED,
+                &catalog,
+                &installed(&["gemma2:2b", "llama3.2:1b", SELECTED])
+            )
+            .as_deref(),
+            Some("llama3.2:1b")
+        );
+        // A model of the same size is no help
+        assert_eq!(
+            smaller_model(
+                "gemma2:1b",
+                &catalog,
+                &installed(&["llama3.2:1b", "gemma2:2b"])
+            )
+            .as_deref(),
+            Some("gemma2:2b")
+        );
+        assert_eq!(
+            smaller_model(SELECTED, &catalog, &installed(&[SELECTED])),
+            None
+        );
+        assert_eq!(smaller_model("gemma2:2b", &catalog, &installed(&[])), None);
+        assert_eq!(
+            smaller_model("mistral:7b", &catalog, &installed(&["gemma2:2b"])),
+            None
+        );
+    }
+}
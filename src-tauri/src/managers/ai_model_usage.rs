@@ -0,0 +1,208 @@
+use crate::managers::ai_pull_records::is_model_installed;
+use crate::managers::json_store::JsonStore;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// A use this soon after the recorded one isn't written to disk again, as
+/// models go unused for days
+const SAVE_RESOLUTION_SECS: i64 = 60 * 60;
+
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// An installed model that wasn't used within the window asked about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiUnusedModel {
+    pub model: String,
+    /// Size reported by Ollama, counting blobs shared with other models
+    pub size_bytes: u64,
+    /// Unix timestamp (seconds) of its last use, or of when Handy first
+    /// found it installed if it wasn't used since
+    pub last_used: i64,
+}
+
+/// Installed models that went unused, and the space deleting them frees
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiUnusedModels {
+    pub models: Vec<AiUnusedModel>,
+    /// Bytes freed by deleting all of them, leaving out blobs the other
+    /// models still use. `None` for an Ollama on another machine, whose
+    /// blobs can't be seen.
+    pub reclaimable_bytes: Option<u64>,
+}
+
+/// Sent as `ai-unused-model-deleted` for each model `delete_unused_ai_models`
+/// removed or failed to
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiUnusedModelDeleted {
+    pub model: String,
+    pub error: Option<String>,
+}
+
+/// When each model was last used for an enhancement or benchmark, persisted
+/// as JSON in the app data directory
+pub struct ModelUsage {
+    last_used: JsonStore<HashMap<String, i64>>,
+}
+
+pub type SharedModelUsage = Arc<Mutex<ModelUsage>>;
+
+impl ModelUsage {
+    /// Load last uses from `path`. A missing or unreadable file starts empty.
+    pub fn load(path: PathBuf) -> Self {
+        Self {
+            last_used: JsonStore::load(path, "model usage"),
+        }
+    }
+
+    /// Last uses kept in memory only, used when the app data dir is unavailable
+    pub fn in_memory() -> Self {
+        Self {
+            last_used: JsonStore::in_memory(),
+        }
+    }
+
+    /// Record that `model` was used at `now`
+    pub fn record(&mut self, model: &str, now: i64) {
+        let previous = self.last_used.insert(model.to_string(), now);
+        if previous.is_none_or(|used| now - used >= SAVE_RESOLUTION_SECS) {
+            self.last_used.save();
+        }
+    }
+
+    /// Forget a model once it is deleted, under any id it was used by
+    pub fn forget(&mut self, model: &str) {
+        let name = [model.to_string()];
+        let before = self.last_used.len();
+        self.last_used
+            .retain(|used, _| !is_model_installed(used, &name));
+        if self.last_used.len() != before {
+            self.last_used.save();
+        }
+    }
+
+    /// The `installed` models, given with their sizes, that weren't used in
+    /// the last `days` days. The `selected` model is never among them, used
+    /// or not. A model never seen before counts as used `now`, so models
+    /// installed before their use was tracked aren't all reported at once.
+    pub fn unused(
+        &mut self,
+        installed: &[(String, u64)],
+        selected: Option<&str>,
+        now: i64,
+        days: u32,
+    ) -> Vec<AiUnusedModel> {
+        let cutoff = now - days as i64 * DAY_SECS;
+        let mut first_seen = false;
+        let mut unused = Vec::new();
+        for (model, size_bytes) in installed {
+            let name = std::slice::from_ref(model);
+            if selected.is_some_and(|selected| is_model_installed(selected, name)) {
+                continue;
+            }
+
+            // Uses are recorded under the id the model was selected by,
+            // which may leave out the tag Ollama lists it with
+            let last_used = self
+                .last_used
+                .iter()
+                .filter(|(used, _)| is_model_installed(used, name))
+                .map(|(_, &at)| at)
+                .max();
+            let last_used = last_used.unwrap_or_else(|| {
+                first_seen = true;
+                self.last_used.insert(model.clone(), now);
+                now
+            });
+            if last_used < cutoff {
+                unused.push(AiUnusedModel {
+                    model: model.clone(),
+                    size_bytes: *size_bytes,
+                    last_used,
+                });
+            }
+        }
+
+        if first_seen {
+            self.last_used.save();
+        }
+        unused
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::test_fixtures::temp_path;
+    use std::fs;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn installed(models: &[&str]) -> Vec<(String, u64)> {
+        models
+            .iter()
+            .map(|model| (model.to_string(), 1_000_000_000))
+            .collect()
+    }
+
+    fn ids(unused: &[AiUnusedModel]) -> Vec<&str> {
+        unused.iter().map(|model| model.model.as_str()).collect()
+    }
+
+    #[test]
+    fn test_models_unused_within_the_window() {
+        let mut usage = ModelUsage::in_memory();
+        usage.record("llama3.2:1b", NOW - 40 * DAY_SECS);
+        usage.record("gemma2:2b", NOW - 2 * DAY_SECS);
+        usage.record("qwen2.5", NOW - 31 * DAY_SECS);
+
+        let models = installed(&["llama3.2:1b", "gemma2:2b", "qwen2.5:latest"]);
+        let unused = usage.unused(&models, None, NOW, 30);
+        assert_eq!(ids(&unused), vec!["llama3.2:1b", "qwen2.5:latest"]);
+        assert_eq!(unused[0].last_used, NOW - 40 * DAY_SECS);
+        assert!(usage.unused(&models, None, NOW, 60).is_empty());
+    }
+
+    #[test]
+    fn test_selected_model_is_never_unused() {
+        let mut usage = ModelUsage::in_memory();
+        usage.record("llama3.2:1b", NOW - 400 * DAY_SECS);
+        usage.record("qwen2.5", NOW - 400 * DAY_SECS);
+        let models = installed(&["llama3.2:1b", "qwen2.5:latest"]);
+
+        let unused = usage.unused(&models, Some("llama3.2:1b"), NOW, 0);
+        assert_eq!(ids(&unused), vec!["qwen2.5:latest"]);
+
+        // Also when selected without the tag Ollama lists it with
+        let unused = usage.unused(&models, Some("qwen2.5"), NOW, 0);
+        assert_eq!(ids(&unused), vec!["llama3.2:1b"]);
+    }
+
+    #[test]
+    fn test_models_seen_for_the_first_time_count_as_used() {
+        let mut usage = ModelUsage::in_memory();
+        let models = installed(&["gemma2:2b"]);
+
+        assert!(usage.unused(&models, None, NOW, 7).is_empty());
+        let later = NOW + 8 * DAY_SECS;
+        let unused = usage.unused(&models, None, later, 7);
+        assert_eq!(unused[0].last_used, NOW);
+
+        usage.forget("gemma2:2b");
+        assert!(usage.unused(&models, None, later, 7).is_empty());
+    }
+
+    #[test]
+    fn test_usage_persists_across_loads() {
+        let path = temp_path("model-usage");
+        let mut usage = ModelUsage::load(path.clone());
+        usage.record("gemma2:2b", NOW - 10 * DAY_SECS);
+
+        let mut reloaded = ModelUsage::load(path.clone());
+        let unused = reloaded.unused(&installed(&["gemma2:2b"]), None, NOW, 7);
+        assert_eq!(unused[0].last_used, NOW - 10 * DAY_SECS);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tokio::sync::Notify;
+
+/// How long a preload waiting for Ollama goes without checking on it, in
+/// case nothing else noticed it come up
+const OLLAMA_RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The preload of the selected model at startup, shared so it can resume
+/// once Ollama becomes available and be stopped when AI enhancement is
+/// turned off before it is done
+pub struct StartupPreload {
+    pub auto_num_thread: u32,
+    task: Option<JoinHandle<()>>,
+    available: Arc<Notify>,
+}
+
+pub type SharedStartupPreload = Arc<Mutex<StartupPreload>>;
+
+impl StartupPreload {
+    pub fn new(auto_num_thread: u32) -> Self {
+        Self {
+            auto_num_thread,
+            task: None,
+            available: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Track the task preloading the model, stopping an earlier one
+    pub fn start(&mut self, task: JoinHandle<()>) {
+        self.cancel();
+        self.task = Some(task);
+    }
+
+    /// Stop the preload, whether it is waiting or loading the model.
+    /// Dropping the request makes Ollama stop loading for it.
+    pub fn cancel(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+
+    /// Wake a preload waiting for Ollama
+    pub fn ollama_available(&self) {
+        self.available.notify_one();
+    }
+
+    /// What a preload waiting for Ollama waits on
+    pub fn availability(&self) -> Arc<Notify> {
+        self.available.clone()
+    }
+}
+
+/// Run `check` until it finds Ollama, trying again when `available` is
+/// notified, or after [`OLLAMA_RECHECK_INTERVAL`]
+pub async fn wait_for_ollama<T, F, Fut>(available: &Notify, mut check: F) -> T
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<T>>,
+{
+    loop {
+        if let Some(found) = check().await {
+            return found;
+        }
+        tokio::select! {
+            _ = available.notified() => {}
+            _ = tokio::time::sleep(OLLAMA_RECHECK_INTERVAL) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Instant;
+
+    #[test]
+    fn test_waiting_preload_resumes_when_ollama_becomes_available() {
+        let preload = StartupPreload::new(4);
+        let available = preload.availability();
+        let mut checks = 0;
+
+        let started = Instant::now();
+        let found = tauri::async_runtime::block_on(wait_for_ollama(&available, || {
+            checks += 1;
+            // Ollama comes up right after the first check
+            if checks == 1 {
+                preload.ollama_available();
+            }
+            let found = (checks > 1).then_some("http://localhost:11434");
+            async move { found }
+        }));
+
+        assert_eq!(found, "http://localhost:11434");
+        assert_eq!(checks, 2);
+        assert!(started.elapsed() < OLLAMA_RECHECK_INTERVAL);
+    }
+
+    #[test]
+    fn test_cancel_stops_the_preload() {
+        let mut preload = StartupPreload::new(4);
+        preload.cancel();
+
+        let loaded = Arc::new(AtomicBool::new(false));
+        let task_loaded = loaded.clone();
+        preload.start(tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            task_loaded.store(true, Ordering::SeqCst);
+        }));
+        preload.cancel();
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(!loaded.load(Ordering::SeqCst));
+    }
+}
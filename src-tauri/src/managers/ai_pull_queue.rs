@@ -0,0 +1,274 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiPullState {
+    Queued,
+    Downloading,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiPullQueueItem {
+    pub id: String,
+    pub model_id: String,
+    pub endpoint: Option<String>,
+    pub state: AiPullState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiModelPullProgress {
+    pub pull_id: String,
+    pub model_id: String,
+    pub status: String,
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+    pub percentage: f64,
+}
+
+/// Model downloads waiting to run, processed strictly one at a time.
+///
+/// The item being downloaded stays at the front of the queue until it is
+/// finished, so `items()` always reflects everything the user asked for.
+#[derive(Debug, Default)]
+pub struct PullQueue {
+    items: VecDeque<AiPullQueueItem>,
+    /// Latest progress of each started item, keyed by pull id
+    progress: HashMap<String, AiModelPullProgress>,
+    next_id: u64,
+}
+
+impl PullQueue {
+    /// Queue a download. Returns the existing entry instead when the same model
+    /// is already queued or downloading from the same endpoint, along with
+    /// whether a new entry was created.
+    pub fn enqueue(&mut self, model_id: &str, endpoint: Option<String>) -> (AiPullQueueItem, bool) {
+        if let Some(existing) = self
+            .items
+            .iter()
+            .find(|item| item.model_id == model_id && item.endpoint == endpoint)
+        {
+            return (existing.clone(), false);
+        }
+
+        self.next_id += 1;
+        let item = AiPullQueueItem {
+            id: format!("pull-{}", self.next_id),
+            model_id: model_id.to_string(),
+            endpoint,
+            state: AiPullState::Queued,
+        };
+        self.items.push_back(item.clone());
+        (item, true)
+    }
+
+    /// Mark the next queued item as downloading, unless a download is already running
+    pub fn start_next(&mut self) -> Option<AiPullQueueItem> {
+        if self
+            .items
+            .iter()
+            .any(|item| item.state == AiPullState::Downloading)
+        {
+            return None;
+        }
+
+        let item = self.items.front_mut()?;
+        item.state = AiPullState::Downloading;
+        Some(item.clone())
+    }
+
+    /// Drop an item once its download has ended, successfully or not
+    pub fn finish(&mut self, id: &str) {
+        self.items.retain(|item| item.id != id);
+        self.progress.remove(id);
+    }
+
+    pub fn record_progress(&mut self, progress: AiModelPullProgress) {
+        self.progress.insert(progress.pull_id.clone(), progress);
+    }
+
+    /// Latest progress of every download that has started
+    pub fn active_pulls(&self) -> Vec<AiModelPullProgress> {
+        self.items
+            .iter()
+            .filter(|item| item.state == AiPullState::Downloading)
+            .map(|item| {
+                self.progress
+                    .get(&item.id)
+                    .cloned()
+                    .unwrap_or_else(|| AiModelPullProgress {
+                        pull_id: item.id.clone(),
+                        model_id: item.model_id.clone(),
+                        status: "starting".to_string(),
+                        completed: None,
+                        total: None,
+                        percentage: 0.0,
+                    })
+            })
+            .collect()
+    }
+
+    /// Remove an item that has not started downloading yet
+    pub fn cancel(&mut self, id: &str) -> Result<AiPullQueueItem> {
+        let index = self
+            .items
+            .iter()
+            .position(|item| item.id == id)
+            .ok_or_else(|| anyhow!("No queued pull with id {}", id))?;
+
+        if self.items[index].state == AiPullState::Downloading {
+            return Err(anyhow!(
+                "Pull of {} has already started",
+                self.items[index].model_id
+            ));
+        }
+
+        Ok(self.items.remove(index).expect("index is in bounds"))
+    }
+
+    pub fn items(&self) -> Vec<AiPullQueueItem> {
+        self.items.iter().cloned().collect()
+    }
+}
+
+/// Run queued items through `pull` one at a time until the queue is empty,
+/// handing each result to `done` once the item has left the queue. Returns
+/// straight away if another caller is already downloading.
+pub async fn drain<P, Fut, D>(queue: &Mutex<PullQueue>, mut pull: P, mut done: D)
+where
+    P: FnMut(AiPullQueueItem) -> Fut,
+    Fut: Future,
+    D: FnMut(AiPullQueueItem, Fut::Output),
+{
+    loop {
+        let Some(item) = queue.lock().unwrap().start_next() else {
+            break;
+        };
+
+        let result = pull(item.clone()).await;
+        queue.lock().unwrap().finish(&item.id);
+        done(item, result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_toolkit::mock_transport::{MockReply, MockTransport};
+    use crate::ai_toolkit::OllamaClient;
+
+    fn model_ids(queue: &PullQueue) -> Vec<String> {
+        queue
+            .items()
+            .into_iter()
+            .map(|item| item.model_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_downloads_one_at_a_time() {
+        let mut queue = PullQueue::default();
+        let (first, _) = queue.enqueue("gemma2:2b", None);
+        queue.enqueue("llama3.2:1b", None);
+
+        assert_eq!(queue.start_next().unwrap().id, first.id);
+        assert!(queue.start_next().is_none());
+
+        queue.finish(&first.id);
+        assert_eq!(queue.start_next().unwrap().model_id, "llama3.2:1b");
+    }
+
+    #[test]
+    fn test_cancel_queued_item_keeps_order() {
+        let mut queue = PullQueue::default();
+        let (first, _) = queue.enqueue("gemma2:2b", None);
+        let (second, _) = queue.enqueue("llama3.2:1b", None);
+        queue.enqueue("qwen2.5:3b", None);
+        queue.start_next();
+
+        let cancelled = queue.cancel(&second.id).unwrap();
+        assert_eq!(cancelled.model_id, "llama3.2:1b");
+        assert_eq!(model_ids(&queue), vec!["gemma2:2b", "qwen2.5:3b"]);
+
+        // The running download is untouched and the next pick skips the cancelled item
+        queue.finish(&first.id);
+        assert_eq!(queue.start_next().unwrap().model_id, "qwen2.5:3b");
+
+        // Ids are never reused after a cancellation
+        let (fourth, _) = queue.enqueue("llama3.2:1b", None);
+        assert_ne!(fourth.id, second.id);
+    }
+
+    #[test]
+    fn test_cancel_rejects_started_and_unknown_items() {
+        let mut queue = PullQueue::default();
+        let (first, _) = queue.enqueue("gemma2:2b", None);
+        queue.start_next();
+
+        assert!(queue.cancel(&first.id).is_err());
+        assert!(queue.cancel("pull-42").is_err());
+        assert_eq!(model_ids(&queue), vec!["gemma2:2b"]);
+    }
+
+    #[test]
+    fn test_duplicate_pulls_share_one_request() {
+        let client = OllamaClient::with_transport(MockTransport::new(vec![MockReply::Stream(
+            200,
+            vec![
+                "{\"status\":\"pulling manifest\"}\n",
+                "{\"status\":\"success\"}\n",
+            ],
+        )]));
+        let queue = Mutex::new(PullQueue::default());
+
+        // Onboarding and settings both ask for the same model
+        let (first, created) = queue.lock().unwrap().enqueue("llama3.2:1b", None);
+        let (second, duplicate_created) = queue.lock().unwrap().enqueue("llama3.2:1b", None);
+        assert!(created);
+        assert!(!duplicate_created);
+        assert_eq!(first.id, second.id);
+
+        let mut finished = Vec::new();
+        tauri::async_runtime::block_on(drain(
+            &queue,
+            |item: AiPullQueueItem| {
+                let client = &client;
+                async move { client.pull_model(&item.model_id).await }
+            },
+            |item, result| finished.push((item.id, result.is_ok())),
+        ));
+
+        assert_eq!(client.transport().requests().len(), 1);
+        assert_eq!(finished, vec![(first.id, true)]);
+        assert!(queue.lock().unwrap().items().is_empty());
+    }
+
+    #[test]
+    fn test_active_pulls_report_latest_progress() {
+        let mut queue = PullQueue::default();
+        let (first, _) = queue.enqueue("gemma2:2b", None);
+        queue.enqueue("llama3.2:1b", None);
+        queue.start_next();
+
+        queue.record_progress(AiModelPullProgress {
+            pull_id: first.id.clone(),
+            model_id: first.model_id.clone(),
+            status: "downloading".to_string(),
+            completed: Some(5),
+            total: Some(10),
+            percentage: 50.0,
+        });
+
+        let active = queue.active_pulls();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].model_id, "gemma2:2b");
+        assert_eq!(active[0].completed, Some(5));
+
+        queue.finish(&first.id);
+        assert!(queue.active_pulls().is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::managers::json_store::JsonStore;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Minimum time between progress writes, so a fast download doesn't hammer the disk
+const PROGRESS_SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A model download that was running and has not finished, been cancelled or dismissed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiPullRecord {
+    pub model_id: String,
+    pub endpoint: Option<String>,
+    /// Unix timestamp (seconds) of when the download was first requested
+    pub started_at: i64,
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+}
+
+/// Pull records persisted as JSON in the app data directory, so downloads
+/// interrupted by quitting Handy can be offered for resumption on next launch
+pub struct PullRecords {
+    records: JsonStore<Vec<AiPullRecord>>,
+    last_saved: Option<Instant>,
+}
+
+impl PullRecords {
+    /// Load records from `path`. A missing or unreadable file starts empty.
+    pub fn load(path: PathBuf) -> Self {
+        Self {
+            records: JsonStore::load(path, "pull records"),
+            last_saved: None,
+        }
+    }
+
+    /// Records that are kept in memory only, used when the app data dir is unavailable
+    pub fn in_memory() -> Self {
+        Self {
+            records: JsonStore::in_memory(),
+            last_saved: None,
+        }
+    }
+
+    pub fn records(&self) -> Vec<AiPullRecord> {
+        self.records.clone()
+    }
+
+    pub fn get(&self, model_id: &str) -> Option<AiPullRecord> {
+        self.records
+            .iter()
+            .find(|r| r.model_id == model_id)
+            .cloned()
+    }
+
+    /// Record that a download started. A resumed download keeps its original start time.
+    pub fn start(&mut self, model_id: &str, endpoint: Option<String>) {
+        match self.records.iter_mut().find(|r| r.model_id == model_id) {
+            Some(record) => record.endpoint = endpoint,
+            None => self.records.push(AiPullRecord {
+                model_id: model_id.to_string(),
+                endpoint,
+                started_at: chrono::Utc::now().timestamp(),
+                completed: None,
+                total: None,
+            }),
+        }
+        self.save();
+    }
+
+    pub fn update_progress(&mut self, model_id: &str, completed: Option<u64>, total: Option<u64>) {
+        let Some(record) = self.records.iter_mut().find(|r| r.model_id == model_id) else {
+            return;
+        };
+        if completed.is_some() {
+            record.completed = completed;
+        }
+        if total.is_some() {
+            record.total = total;
+        }
+
+        if !matches!(self.last_saved, Some(saved) if saved.elapsed() < PROGRESS_SAVE_INTERVAL) {
+            self.save();
+        }
+    }
+
+    /// Forget a download once it completed, was cancelled or dismissed
+    pub fn remove(&mut self, model_id: &str) -> Option<AiPullRecord> {
+        let index = self.records.iter().position(|r| r.model_id == model_id)?;
+        let record = self.records.remove(index);
+        self.save();
+        Some(record)
+    }
+
+    fn save(&mut self) {
+        self.last_saved = Some(Instant::now());
+        self.records.save();
+    }
+}
+
+/// Whether `model_id` appears in Ollama's installed model list. Ollama reports
+/// untagged models with an explicit `:latest` tag, and a tag like `1b` also
+/// matches its longer aliases like `1b-instruct-q4_K_M`.
+pub fn is_model_installed(model_id: &str, installed: &[String]) -> bool {
+    let (base, tag) = name_and_tag(model_id);
+    installed.iter().any(|name| {
+        let (installed_base, installed_tag) = name_and_tag(name);
+        name == model_id
+            || (installed_base == base && tags_match(tag.as_deref(), installed_tag.as_deref()))
+    })
+}
+
+/// A model name split into its lowercased base and tag, dropping any
+/// `@sha256:...` digest. Untagged names have the `latest` tag, unless they
+/// are pinned to a digest, which says nothing about the tag.
+fn name_and_tag(name: &str) -> (String, Option<String>) {
+    let (name, digest) = match name.split_once('@') {
+        Some((name, _)) => (name, true),
+        None => (name, false),
+    };
+    let name = name.to_lowercase();
+    // A colon before the last slash belongs to a registry's port
+    match name.rsplit_once(':').filter(|(_, tag)| !tag.contains('/')) {
+        Some((base, tag)) => (base.to_string(), Some(tag.to_string())),
+        None if digest => (name, None),
+        None => (name, Some("latest".to_string())),
+    }
+}
+
+/// Whether `installed` is `tag` or one of its longer aliases
+fn tags_match(tag: Option<&str>, installed: Option<&str>) -> bool {
+    match (tag, installed) {
+        (Some(tag), Some(installed)) => {
+            tag == installed || installed.starts_with(&format!("{}-", tag))
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::test_fixtures::temp_path;
+    use std::fs;
+
+    #[test]
+    fn test_records_survive_reload() {
+        let path = temp_path("pull-records");
+        let _ = fs::remove_file(&path);
+
+        let mut records = PullRecords::load(path.clone());
+        records.start("llama3.2:1b", None);
+        records.start("gemma2:2b", Some("http://gpu-box:11434".to_string()));
+        records.remove("gemma2:2b");
+
+        let reloaded = PullRecords::load(path.clone());
+        let saved = reloaded.records();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].model_id, "llama3.2:1b");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_resume_keeps_original_start_time() {
+        let mut records = PullRecords::in_memory();
+        records.start("llama3.2:1b", None);
+        let started_at = records.get("llama3.2:1b").unwrap().started_at;
+
+        records.update_progress("llama3.2:1b", Some(10), Some(100));
+        records.start("llama3.2:1b", None);
+
+        let record = records.get("llama3.2:1b").unwrap();
+        assert_eq!(record.started_at, started_at);
+        assert_eq!(record.completed, Some(10));
+        assert_eq!(record.total, Some(100));
+    }
+
+    #[test]
+    fn test_corrupt_file_starts_empty() {
+        let path = temp_path("pull-records-corrupt");
+        fs::write(&path, "not json").unwrap();
+
+        assert!(PullRecords::load(path.clone()).records().is_empty());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_is_model_installed() {
+        let installed = vec!["llama3.2:1b".to_string(), "gemma2:latest".to_string()];
+        assert!(is_model_installed("llama3.2:1b", &installed));
+        assert!(is_model_installed("gemma2", &installed));
+        assert!(!is_model_installed("gemma2:2b", &installed));
+        assert!(!is_model_installed("llama3.2", &installed));
+    }
+
+    #[test]
+    fn test_is_model_installed_under_an_alias() {
+        let installed = vec![
+            "llama3.2:1b-instruct-q4_K_M".to_string(),
+            "Qwen2.5:1.5b".to_string(),
+        ];
+        assert!(is_model_installed("llama3.2:1b", &installed));
+        assert!(is_model_installed("qwen2.5:1.5b", &installed));
+        assert!(is_model_installed("llama3.2@sha256:a80c4f", &installed));
+        assert!(!is_model_installed("llama3.2:3b", &installed));
+        assert!(!is_model_installed("llama3.2:1b-instruct-q8_0", &installed));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::ai_toolkit::diff::DiffOp;
+use crate::ai_toolkit::repetitions::trim_punctuation;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Sent as `ai-enhancement-refined` when the quality model of the two-pass
+/// mode corrected a pasted transcript differently from the fast one
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiEnhancementRefined {
+    /// The enhancement refined, as announced by `ai-enhancement-started`
+    pub request_id: String,
+    pub model: String,
+    /// The fast model's text, as pasted
+    pub pasted: String,
+    pub text: String,
+    /// What the refinement changed in the pasted text, word by word
+    pub diff: Option<Vec<DiffOp>>,
+    pub duration_ms: u32,
+}
+
+/// A second pass over the last enhancement, run on demand by
+/// `reenhance_last` and sent as `ai-enhancement-reenhanced` when its
+/// shortcut ran it
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiReenhancement {
+    pub model: String,
+    /// The last enhancement's text, as pasted
+    pub previous: String,
+    pub text: String,
+    /// What the second pass changed in the pasted text, word by word
+    pub diff: Option<Vec<DiffOp>>,
+    pub duration_ms: u32,
+}
+
+/// The refinement waiting or running in the background, stopped when the
+/// next dictation starts
+#[derive(Default)]
+pub struct PendingRefinement {
+    running: Mutex<Option<(String, oneshot::Sender<()>)>>,
+}
+
+pub type SharedRefinement = Arc<PendingRefinement>;
+
+impl PendingRefinement {
+    /// Track the refinement of `request_id`, in place of any earlier one,
+    /// which is cancelled. The receiver resolves with `Ok` once it is
+    /// cancelled too.
+    pub fn start(&self, request_id: &str) -> oneshot::Receiver<()> {
+        let (cancel, cancelled) = oneshot::channel();
+        let earlier = self
+            .running
+            .lock()
+            .unwrap()
+            .replace((request_id.to_string(), cancel));
+        if let Some((_, earlier)) = earlier {
+            let _ = earlier.send(());
+        }
+        cancelled
+    }
+
+    /// Stop tracking the refinement of `request_id` once it is done
+    pub fn finish(&self, request_id: &str) {
+        let mut running = self.running.lock().unwrap();
+        if matches!(&*running, Some((id, _)) if id == request_id) {
+            *running = None;
+        }
+    }
+
+    /// Cancel the refinement being tracked, returning whose it was
+    pub fn cancel(&self) -> Option<String> {
+        let (request_id, cancel) = self.running.lock().unwrap().take()?;
+        let _ = cancel.send(());
+        Some(request_id)
+    }
+}
+
+/// Whether `refined` says something other than `pasted`, rather than only
+/// casing, punctuation or spacing differing
+pub fn differs_meaningfully(pasted: &str, refined: &str) -> bool {
+    let words = |text: &str| -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| trim_punctuation(word).to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect()
+    };
+    words(pasted) != words(refined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_changed_words_count() {
+        assert!(!differs_meaningfully(
+            "lets meet on thursday",
+            "Lets meet on Thursday."
+        ));
+        assert!(!differs_meaningfully(
+            "Send it, then call.",
+            "send it then  call"
+        ));
+        assert!(!differs_meaningfully("", " "));
+        // A dash standing on its own is punctuation all the same
+        assert!(!differs_meaningfully("Wait, what", "Wait — what"));
+
+        assert!(differs_meaningfully(
+            "lets meet on thursday",
+            "Let's meet on Thursday."
+        ));
+        assert!(differs_meaningfully(
+            "The report is their.",
+            "The report is there."
+        ));
+        assert!(differs_meaningfully("Send it.", "Send it now."));
+    }
+
+    #[test]
+    fn test_a_new_refinement_cancels_the_last() {
+        let pending = PendingRefinement::default();
+        assert_eq!(pending.cancel(), None);
+
+        let mut first = pending.start("enhance-1");
+        let mut second = pending.start("enhance-2");
+        assert_eq!(first.try_recv(), Ok(()));
+
+        // Finishing the cancelled one leaves the new one tracked
+        pending.finish("enhance-1");
+        assert_eq!(pending.cancel(), Some("enhance-2".to_string()));
+        assert_eq!(second.try_recv(), Ok(()));
+
+        let _third = pending.start("enhance-3");
+        pending.finish("enhance-3");
+        assert_eq!(pending.cancel(), None);
+    }
+}
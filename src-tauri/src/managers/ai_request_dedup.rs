@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// Enhancements under way, keyed by everything their result depends on, so
+/// an identical request arriving meanwhile waits for that result instead of
+/// asking the model again. A key is only held while its request runs, so
+/// the same dictation repeated later is enhanced afresh.
+pub struct RequestDedup<T> {
+    running: Mutex<HashMap<u64, watch::Receiver<Option<T>>>>,
+}
+
+pub type SharedRequestDedup<T> = Arc<RequestDedup<T>>;
+
+/// What joining [`RequestDedup`] made of a request
+pub enum DedupRequest<'a, T> {
+    /// No identical request is running, so this one runs
+    Lead(Lead<'a, T>),
+    /// An identical request is running, and its result is shared
+    Follow(Follow<T>),
+}
+
+/// The request that runs, sharing its result with the identical ones that
+/// join while it does. Dropping it unfinished leaves them without one.
+pub struct Lead<'a, T> {
+    dedup: &'a RequestDedup<T>,
+    key: u64,
+    result: watch::Sender<Option<T>>,
+}
+
+/// A request waiting for the result of an identical one
+pub struct Follow<T> {
+    result: watch::Receiver<Option<T>>,
+}
+
+impl<T: Clone> RequestDedup<T> {
+    pub fn new() -> Self {
+        Self {
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lead the request with `key`, or follow the identical one running
+    pub fn join(&self, key: u64) -> DedupRequest<'_, T> {
+        let mut running = self.running.lock().unwrap();
+        if let Some(result) = running.get(&key) {
+            return DedupRequest::Follow(Follow {
+                result: result.clone(),
+            });
+        }
+        let (sender, receiver) = watch::channel(None);
+        running.insert(key, receiver);
+        DedupRequest::Lead(Lead {
+            dedup: self,
+            key,
+            result: sender,
+        })
+    }
+}
+
+impl<T: Clone> Default for RequestDedup<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Lead<'_, T> {
+    /// Hand `result` to the requests following this one
+    pub fn finish(self, result: T) {
+        self.result.send_replace(Some(result));
+    }
+}
+
+impl<T> Drop for Lead<'_, T> {
+    fn drop(&mut self) {
+        self.dedup.running.lock().unwrap().remove(&self.key);
+    }
+}
+
+impl<T: Clone> Follow<T> {
+    /// The result of the request followed, or `None` when it ended
+    /// without one, as when it was dropped from the queue
+    pub async fn result(mut self) -> Option<T> {
+        match self.result.wait_for(Option::is_some).await {
+            Ok(result) => result.clone(),
+            Err(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::join;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    /// Enhance `text` under `key`, counting the requests sent to the model
+    async fn enhance(
+        dedup: &RequestDedup<String>,
+        key: u64,
+        text: &str,
+        requests: &AtomicU32,
+    ) -> Option<String> {
+        match dedup.join(key) {
+            DedupRequest::Lead(lead) => {
+                requests.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                let enhanced = format!("{}.", text);
+                lead.finish(enhanced.clone());
+                Some(enhanced)
+            }
+            DedupRequest::Follow(follow) => follow.result().await,
+        }
+    }
+
+    #[test]
+    fn test_identical_requests_share_one_result() {
+        let dedup = RequestDedup::new();
+        let requests = AtomicU32::new(0);
+
+        let (first, second) = tauri::async_runtime::block_on(join(
+            enhance(&dedup, 1, "hello there", &requests),
+            enhance(&dedup, 1, "hello there", &requests),
+        ));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(first.as_deref(), Some("hello there."));
+        assert_eq!(second, first);
+
+        // Once it is done, the same request runs again
+        tauri::async_runtime::block_on(enhance(&dedup, 1, "hello there", &requests));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_different_requests_run_separately() {
+        let dedup = RequestDedup::new();
+        let requests = AtomicU32::new(0);
+
+        tauri::async_runtime::block_on(join(
+            enhance(&dedup, 1, "hello there", &requests),
+            enhance(&dedup, 2, "hello again", &requests),
+        ));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_followers_of_a_dropped_request_get_nothing() {
+        let dedup = RequestDedup::<String>::new();
+        let DedupRequest::Lead(lead) = dedup.join(1) else {
+            panic!("nothing was running");
+        };
+        let DedupRequest::Follow(follow) = dedup.join(1) else {
+            panic!("the first request is running");
+        };
+
+        drop(lead);
+        assert_eq!(tauri::async_runtime::block_on(follow.result()), None);
+        assert!(matches!(dedup.join(1), DedupRequest::Lead(_)));
+    }
+}
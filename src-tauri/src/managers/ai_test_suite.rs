@@ -0,0 +1,393 @@
+use crate::ai_toolkit::diff::{diff_words, DiffOp};
+use crate::managers::ai_enhancement::{AiEnhancementManager, EnhanceMode, EnhancementError};
+use crate::managers::ai_enhancement_queue::{take_turn, EnhancementQueue};
+use crate::settings::{AiFeatures, AiTestSample};
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+/// A built-in transcript, run only while the feature it exercises is enabled
+struct BuiltInSample {
+    name: &'static str,
+    text: &'static str,
+    expected: &'static [&'static str],
+    unexpected: &'static [&'static str],
+    enabled: fn(&AiFeatures) -> bool,
+}
+
+const BUILT_IN_SAMPLES: [BuiltInSample; 12] = [
+    BuiltInSample {
+        name: "Punctuation",
+        text: "where are you going tonight i thought we were meeting at the station",
+        expected: &["Where", "?"],
+        unexpected: &[],
+        enabled: |features| features.punctuation_and_capitalization,
+    },
+    BuiltInSample {
+        name: "Filler words",
+        text: "um so i uh wanted to ask you about like the budget for next year",
+        expected: &["budget"],
+        unexpected: &["um", "uh"],
+        enabled: |features| features.remove_filler_words,
+    },
+    BuiltInSample {
+        name: "Numbers",
+        text: "sales went up by twenty five percent in the second quarter",
+        expected: &["25%"],
+        unexpected: &["twenty five"],
+        enabled: |features| features.normalize_numbers,
+    },
+    BuiltInSample {
+        name: "Spelling",
+        text: "i recieved the pakage yesterday and it was definately damaged",
+        expected: &["received", "package", "definitely"],
+        unexpected: &[],
+        enabled: |features| features.fix_spelling,
+    },
+    BuiltInSample {
+        name: "Homophones",
+        text: "their going to bring there laptops so we can look at the slides",
+        expected: &["They're", "their laptops"],
+        unexpected: &[],
+        enabled: |features| features.fix_spelling,
+    },
+    BuiltInSample {
+        name: "Grammar",
+        text: "he don't know where they was going after the meeting",
+        expected: &["doesn't", "were going"],
+        unexpected: &["don't", "was going"],
+        enabled: |features| features.fix_grammar,
+    },
+    BuiltInSample {
+        name: "Minimal grammar edits",
+        text: "the results was better than we expected so we shipped it early",
+        expected: &["results were better than we expected", "shipped it early"],
+        unexpected: &[],
+        enabled: |features| features.fix_grammar,
+    },
+    BuiltInSample {
+        name: "Repetitions",
+        text: "i i think we should should ship it on friday",
+        expected: &["I think we should ship"],
+        unexpected: &[],
+        enabled: |features| features.remove_repetitions,
+    },
+    BuiltInSample {
+        name: "Dates",
+        text: "the deadline moved to march twelfth at five pm",
+        expected: &["12"],
+        unexpected: &["twelfth"],
+        enabled: |features| features.normalize_dates_times,
+    },
+    BuiltInSample {
+        name: "Units",
+        text: "cut the board down to twelve millimeters and sand the edges",
+        expected: &["12"],
+        unexpected: &["twelve"],
+        enabled: |features| features.normalize_units,
+    },
+    BuiltInSample {
+        name: "Currency",
+        text: "the train ticket to berlin costs two hundred euros return",
+        expected: &["200"],
+        unexpected: &["two hundred"],
+        enabled: |features| features.normalize_currency,
+    },
+    BuiltInSample {
+        name: "Lists",
+        text: "we need three things first milk second bread and third eggs",
+        expected: &["\n"],
+        unexpected: &[],
+        enabled: |features| features.format_lists,
+    },
+];
+
+/// How one sample came through enhancement
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiTestSampleResult {
+    pub name: String,
+    pub input: String,
+    pub output: Option<String>,
+    /// What changed from the input, when the diff is cheap enough to show
+    pub diff: Option<Vec<DiffOp>>,
+    pub latency_ms: u32,
+    /// The output contains everything expected and nothing unexpected
+    pub passed: bool,
+    /// Why it didn't pass, e.g. `missing "25%"`
+    pub failures: Vec<String>,
+    pub error: Option<EnhancementError>,
+}
+
+/// Result of running every sample through the selected model
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiTestSuiteReport {
+    pub model: String,
+    pub results: Vec<AiTestSampleResult>,
+    pub passed: u32,
+}
+
+/// Sent before each sample is run by `run_ai_enhancement_test_suite`
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AiTestSuiteProgress {
+    pub name: String,
+    /// 1-based position of the sample in the suite
+    pub index: u32,
+    pub total: u32,
+}
+
+/// Trim a sample and drop blank expectations
+pub fn normalize_test_sample(sample: AiTestSample) -> Result<AiTestSample> {
+    let name = sample.name.trim().to_string();
+    if name.is_empty() {
+        return Err(anyhow!("A sample needs a name"));
+    }
+    let text = sample.text.trim().to_string();
+    if text.is_empty() {
+        return Err(anyhow!("A sample needs a transcript"));
+    }
+
+    let keep = |values: Vec<String>| -> Vec<String> {
+        values
+            .into_iter()
+            .filter(|value| !value.trim().is_empty())
+            .collect()
+    };
+    Ok(AiTestSample {
+        name,
+        text,
+        expected: keep(sample.expected),
+        unexpected: keep(sample.unexpected),
+    })
+}
+
+/// The built-in samples for the enabled features, then the user's own
+pub fn suite_samples(features: &AiFeatures, user_samples: &[AiTestSample]) -> Vec<AiTestSample> {
+    BUILT_IN_SAMPLES
+        .iter()
+        .filter(|sample| (sample.enabled)(features))
+        .map(|sample| AiTestSample {
+            name: sample.name.to_string(),
+            text: sample.text.to_string(),
+            expected: sample.expected.iter().map(|s| s.to_string()).collect(),
+            unexpected: sample.unexpected.iter().map(|s| s.to_string()).collect(),
+        })
+        .chain(user_samples.iter().cloned())
+        .collect()
+}
+
+/// Why `output` doesn't pass for `sample`, empty when it does. Expected text
+/// has to appear as written, while unexpected words count in any casing but
+/// only as whole words, so "um" is never found in "umbrella".
+pub fn check_output(sample: &AiTestSample, output: &str) -> Vec<String> {
+    let mut failures = Vec::new();
+    if output.trim().is_empty() {
+        failures.push("empty output".to_string());
+        return failures;
+    }
+
+    for expected in &sample.expected {
+        if !output.contains(expected.as_str()) {
+            failures.push(format!("missing {:?}", expected));
+        }
+    }
+
+    let output_words = words(output);
+    for unexpected in &sample.unexpected {
+        let phrase = words(unexpected);
+        if !phrase.is_empty()
+            && output_words
+                .windows(phrase.len())
+                .any(|window| window == phrase)
+        {
+            failures.push(format!("still contains {:?}", unexpected));
+        }
+    }
+    failures
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Run every sample through `model` with `features` one after another,
+/// emitting `ai-test-suite-progress` before each. Each sample waits its turn
+/// in `queue`, so dictations are never held up by more than one of them.
+pub async fn run_test_suite(
+    app: &AppHandle,
+    ai_manager: &Mutex<AiEnhancementManager>,
+    queue: &EnhancementQueue,
+    model: &str,
+    features: &AiFeatures,
+    samples: Vec<AiTestSample>,
+) -> AiTestSuiteReport {
+    info!("Running {} test samples through {}", samples.len(), model);
+
+    let mut results = Vec::with_capacity(samples.len());
+    for (index, sample) in samples.iter().enumerate() {
+        let _ = app.emit(
+            "ai-test-suite-progress",
+            AiTestSuiteProgress {
+                name: sample.name.clone(),
+                index: index as u32 + 1,
+                total: samples.len() as u32,
+            },
+        );
+
+        let slot = take_turn(queue).await;
+        let started = Instant::now();
+        let output = ai_manager
+            .lock()
+            .await
+            .test_enhancement(&sample.text, model, features, EnhanceMode::Correct)
+            .await
+            .map(|enhanced| enhanced.text);
+        let latency_ms = started.elapsed().as_millis() as u32;
+        drop(slot);
+
+        results.push(sample_result(sample, output, latency_ms));
+    }
+
+    let passed = results.iter().filter(|result| result.passed).count() as u32;
+    info!(
+        "{} passed {} of {} test samples",
+        model,
+        passed,
+        results.len()
+    );
+    AiTestSuiteReport {
+        model: model.to_string(),
+        results,
+        passed,
+    }
+}
+
+fn sample_result(
+    sample: &AiTestSample,
+    output: Result<String, EnhancementError>,
+    latency_ms: u32,
+) -> AiTestSampleResult {
+    match output {
+        Ok(output) => {
+            let failures = check_output(sample, &output);
+            AiTestSampleResult {
+                name: sample.name.clone(),
+                input: sample.text.clone(),
+                diff: diff_words(&sample.text, &output),
+                output: Some(output),
+                latency_ms,
+                passed: failures.is_empty(),
+                failures,
+                error: None,
+            }
+        }
+        Err(e) => {
+            warn!("Test sample {} failed: {}", sample.name, e);
+            AiTestSampleResult {
+                name: sample.name.clone(),
+                input: sample.text.clone(),
+                output: None,
+                diff: None,
+                latency_ms,
+                passed: false,
+                failures: Vec::new(),
+                error: Some(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(expected: &[&str], unexpected: &[&str]) -> AiTestSample {
+        AiTestSample {
+            name: "Sample".to_string(),
+            text: "um sales went up by twenty five percent".to_string(),
+            expected: expected.iter().map(|s| s.to_string()).collect(),
+            unexpected: unexpected.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_check_output_passes_with_everything_expected() {
+        let sample = sample(&["25%"], &["um", "twenty five"]);
+        assert!(check_output(&sample, "Sales went up by 25%.").is_empty());
+    }
+
+    #[test]
+    fn test_check_output_lists_each_failure() {
+        let sample = sample(&["25%", "Sales"], &["um", "twenty five"]);
+        assert_eq!(
+            check_output(&sample, "Um, sales went up by twenty-five percent."),
+            vec![
+                "missing \"25%\"",
+                "missing \"Sales\"",
+                "still contains \"um\"",
+                "still contains \"twenty five\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_output_matches_unexpected_words_whole() {
+        let sample = sample(&[], &["um"]);
+        assert!(check_output(&sample, "Bring an umbrella.").is_empty());
+        assert_eq!(check_output(&sample, "   "), vec!["empty output"]);
+    }
+
+    #[test]
+    fn test_suite_samples_follow_the_enabled_features() {
+        let features = AiFeatures {
+            normalize_currency: false,
+            ..AiFeatures::default()
+        };
+        let user = sample(&["25%"], &[]);
+        let samples = suite_samples(&features, std::slice::from_ref(&user));
+
+        assert!(samples.iter().any(|sample| sample.name == "Numbers"));
+        assert!(!samples.iter().any(|sample| sample.name == "Currency"));
+        assert!(!samples.iter().any(|sample| sample.name == "Grammar"));
+        assert_eq!(samples.last(), Some(&user));
+
+        let grammar = AiFeatures {
+            fix_grammar: true,
+            ..AiFeatures::default()
+        };
+        let names: Vec<String> = suite_samples(&grammar, &[])
+            .into_iter()
+            .map(|sample| sample.name)
+            .collect();
+        assert!(names.contains(&"Grammar".to_string()));
+        assert!(names.contains(&"Minimal grammar edits".to_string()));
+    }
+
+    #[test]
+    fn test_normalize_test_sample() {
+        let normalized = normalize_test_sample(AiTestSample {
+            name: "  Names ".to_string(),
+            text: " ask siobhan about it ".to_string(),
+            expected: vec!["Siobhan".to_string(), " ".to_string()],
+            unexpected: vec![String::new()],
+        })
+        .unwrap();
+        assert_eq!(normalized.name, "Names");
+        assert_eq!(normalized.text, "ask siobhan about it");
+        assert_eq!(normalized.expected, vec!["Siobhan"]);
+        assert!(normalized.unexpected.is_empty());
+
+        assert!(normalize_test_sample(sample(&[], &[])).is_ok());
+        assert!(normalize_test_sample(AiTestSample {
+            text: " ".to_string(),
+            ..sample(&[], &[])
+        })
+        .is_err());
+    }
+}
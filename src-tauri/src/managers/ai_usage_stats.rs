@@ -0,0 +1,358 @@
+use crate::managers::ai_enhancement::EnhancementOutcome;
+use crate::managers::ai_enhancement_metrics::{AiFallbackCounts, EnhancementRun};
+use crate::managers::json_store::JsonStore;
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Days of counts kept. Older days are dropped as new ones start.
+pub const RETENTION_DAYS: u64 = 90;
+
+/// Runs this soon after the app started count as right after boot, when
+/// Ollama may still be loading the model
+const AFTER_START_WINDOW: Duration = Duration::from_secs(300);
+
+/// How days are written in the stats
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Stretch of days the usage stats are asked for, ending today
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiUsageRange {
+    Week,
+    Month,
+    /// Every day still kept
+    All,
+}
+
+impl AiUsageRange {
+    fn days(self) -> u64 {
+        match self {
+            AiUsageRange::Week => 7,
+            AiUsageRange::Month => 30,
+            AiUsageRange::All => RETENTION_DAYS,
+        }
+    }
+}
+
+/// What came of the dictations of a day, or of a range of days
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct AiUsageCounts {
+    pub enhanced: u32,
+    pub skipped: u32,
+    pub fallbacks: AiFallbackCounts,
+    /// Enhancements that failed with falling back to the original turned off
+    pub errors: u32,
+    pub cache_hits: u32,
+    /// Runs that went to the model, which the latency is averaged over
+    pub timed_runs: u32,
+    pub total_latency_ms: f64,
+    pub average_latency_ms: f64,
+    /// Runs in the first minutes after the app started, and how many of
+    /// them fell back or failed, to tell whether a cold Ollama is to blame
+    pub runs_after_start: u32,
+    pub failures_after_start: u32,
+}
+
+impl AiUsageCounts {
+    fn add(&mut self, other: &AiUsageCounts) {
+        self.enhanced += other.enhanced;
+        self.skipped += other.skipped;
+        self.fallbacks.add(&other.fallbacks);
+        self.errors += other.errors;
+        self.cache_hits += other.cache_hits;
+        self.timed_runs += other.timed_runs;
+        self.total_latency_ms += other.total_latency_ms;
+        self.runs_after_start += other.runs_after_start;
+        self.failures_after_start += other.failures_after_start;
+        self.average_latency_ms = average(self.total_latency_ms, self.timed_runs);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiUsageDay {
+    /// Local date, like "2025-03-14"
+    pub date: String,
+    pub counts: AiUsageCounts,
+}
+
+/// Usage over a range of days, for charting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AiUsageStats {
+    /// Every day of the range oldest first, days without dictations included
+    pub days: Vec<AiUsageDay>,
+    /// The days of the range added up
+    pub total: AiUsageCounts,
+}
+
+/// Daily usage counts persisted as JSON in the app data directory. Only kept
+/// when the user opts in, and never sent anywhere.
+pub struct UsageStats {
+    /// Oldest first
+    days: JsonStore<Vec<AiUsageDay>>,
+}
+
+pub type SharedUsageStats = Arc<Mutex<UsageStats>>;
+
+impl UsageStats {
+    /// Load the counts from `path`. A missing or unreadable file starts from
+    /// nothing.
+    pub fn load(path: PathBuf) -> Self {
+        Self {
+            days: JsonStore::load(path, "usage stats"),
+        }
+    }
+
+    /// Counts kept in memory only, used when the app data dir is unavailable
+    pub fn in_memory() -> Self {
+        Self {
+            days: JsonStore::in_memory(),
+        }
+    }
+
+    /// Count `run` on `today`, `since_start` after the app started, dropping
+    /// the days past the retention window
+    pub fn record_run(&mut self, run: &EnhancementRun, today: NaiveDate, since_start: Duration) {
+        self.prune(today);
+        let date = today.format(DATE_FORMAT).to_string();
+        if self.days.last().map(|day| &day.date) != Some(&date) {
+            self.days.push(AiUsageDay {
+                date,
+                counts: AiUsageCounts::default(),
+            });
+        }
+        let counts = &mut self.days.last_mut().unwrap().counts;
+
+        let failed = match &run.outcome {
+            Some(EnhancementOutcome::Enhanced) => {
+                counts.enhanced += 1;
+                false
+            }
+            Some(EnhancementOutcome::Skipped { .. }) => {
+                counts.skipped += 1;
+                false
+            }
+            Some(EnhancementOutcome::Fallback { reason }) => {
+                counts.fallbacks.count(*reason);
+                true
+            }
+            None => {
+                counts.errors += 1;
+                true
+            }
+        };
+        if run.cached {
+            counts.cache_hits += 1;
+        }
+        if run.reached_model {
+            counts.timed_runs += 1;
+            counts.total_latency_ms += run.duration_ms as f64;
+            counts.average_latency_ms = average(counts.total_latency_ms, counts.timed_runs);
+        }
+        if since_start <= AFTER_START_WINDOW {
+            counts.runs_after_start += 1;
+            if failed {
+                counts.failures_after_start += 1;
+            }
+        }
+    }
+
+    /// Every day of `range` up to `today`, with their total
+    pub fn stats(&self, range: AiUsageRange, today: NaiveDate) -> AiUsageStats {
+        let mut total = AiUsageCounts::default();
+        let days = (0..range.days())
+            .rev()
+            .filter_map(|back| today.checked_sub_days(Days::new(back)))
+            .map(|date| {
+                let date = date.format(DATE_FORMAT).to_string();
+                let counts = self
+                    .days
+                    .iter()
+                    .find(|day| day.date == date)
+                    .map(|day| day.counts.clone())
+                    .unwrap_or_default();
+                total.add(&counts);
+                AiUsageDay { date, counts }
+            })
+            .collect();
+
+        AiUsageStats { days, total }
+    }
+
+    /// Forget every count
+    pub fn clear(&mut self) {
+        self.days.clear();
+        self.save();
+    }
+
+    pub fn save(&self) {
+        self.days.save();
+    }
+
+    /// Drop the days before the retention window ending `today`, and any
+    /// whose date can't be read
+    fn prune(&mut self, today: NaiveDate) {
+        let Some(oldest) = today.checked_sub_days(Days::new(RETENTION_DAYS - 1)) else {
+            return;
+        };
+        self.days.retain(|day| {
+            NaiveDate::parse_from_str(&day.date, DATE_FORMAT).is_ok_and(|date| date >= oldest)
+        });
+    }
+}
+
+fn average(total_ms: f64, runs: u32) -> f64 {
+    if runs == 0 {
+        0.0
+    } else {
+        total_ms / runs as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::ai_enhancement::{AiFallbackReason, AiSkipReason};
+    use crate::managers::test_fixtures::{run, temp_path};
+    use std::fs;
+
+    const AFTER_START: Duration = Duration::from_secs(10);
+    const LATER: Duration = Duration::from_secs(3600);
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, DATE_FORMAT).unwrap()
+    }
+
+    fn fallback(reason: AiFallbackReason) -> Option<EnhancementOutcome> {
+        Some(EnhancementOutcome::Fallback { reason })
+    }
+
+    #[test]
+    fn test_counts_each_day() {
+        let mut stats = UsageStats::in_memory();
+        let monday = date("2025-03-10");
+        let tuesday = date("2025-03-11");
+        stats.record_run(
+            &run(fallback(AiFallbackReason::Timeout), 4000),
+            monday,
+            AFTER_START,
+        );
+        stats.record_run(&run(Some(EnhancementOutcome::Enhanced), 400), monday, LATER);
+        stats.record_run(&run(Some(EnhancementOutcome::Enhanced), 800), monday, LATER);
+        stats.record_run(
+            &EnhancementRun {
+                reached_model: false,
+                cached: true,
+                ..run(Some(EnhancementOutcome::Enhanced), 0)
+            },
+            tuesday,
+            LATER,
+        );
+        stats.record_run(
+            &EnhancementRun {
+                reached_model: false,
+                ..run(
+                    Some(EnhancementOutcome::Skipped {
+                        reason: AiSkipReason::TooShort,
+                    }),
+                    0,
+                )
+            },
+            tuesday,
+            LATER,
+        );
+        stats.record_run(&run(None, 200), tuesday, AFTER_START);
+
+        let week = stats.stats(AiUsageRange::Week, tuesday);
+        assert_eq!(week.days.len(), 7);
+        assert_eq!(week.days[0].date, "2025-03-05");
+        assert_eq!(week.days[5].date, "2025-03-10");
+        assert_eq!(week.days[6].date, "2025-03-11");
+        assert_eq!(week.days[0].counts, AiUsageCounts::default());
+
+        let monday = &week.days[5].counts;
+        assert_eq!((monday.enhanced, monday.fallbacks.timeout), (2, 1));
+        assert_eq!(monday.average_latency_ms, 5200.0 / 3.0);
+        assert_eq!(
+            (monday.runs_after_start, monday.failures_after_start),
+            (1, 1)
+        );
+
+        let tuesday = &week.days[6].counts;
+        assert_eq!(
+            (
+                tuesday.enhanced,
+                tuesday.skipped,
+                tuesday.errors,
+                tuesday.cache_hits
+            ),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(tuesday.timed_runs, 1);
+
+        let total = &week.total;
+        assert_eq!((total.enhanced, total.skipped, total.errors), (3, 1, 1));
+        assert_eq!((total.runs_after_start, total.failures_after_start), (2, 2));
+        assert_eq!(total.average_latency_ms, 1350.0);
+    }
+
+    #[test]
+    fn test_days_past_the_retention_window_are_dropped() {
+        let mut stats = UsageStats::in_memory();
+        let first = date("2025-01-01");
+        stats.record_run(&run(Some(EnhancementOutcome::Enhanced), 300), first, LATER);
+
+        let last_kept = first
+            .checked_add_days(Days::new(RETENTION_DAYS - 1))
+            .unwrap();
+        stats.record_run(
+            &run(Some(EnhancementOutcome::Enhanced), 300),
+            last_kept,
+            LATER,
+        );
+        assert_eq!(stats.days.len(), 2);
+        assert_eq!(stats.stats(AiUsageRange::All, last_kept).total.enhanced, 2);
+
+        let dropped = last_kept.checked_add_days(Days::new(1)).unwrap();
+        stats.record_run(
+            &run(Some(EnhancementOutcome::Enhanced), 300),
+            dropped,
+            LATER,
+        );
+        assert_eq!(stats.days.len(), 2);
+        assert_eq!(
+            stats.days[0].date,
+            last_kept.format(DATE_FORMAT).to_string()
+        );
+    }
+
+    #[test]
+    fn test_counts_survive_reload_until_cleared() {
+        let path = temp_path("usage-stats");
+        let _ = fs::remove_file(&path);
+        let today = date("2025-03-11");
+
+        let mut stats = UsageStats::load(path.clone());
+        stats.record_run(&run(Some(EnhancementOutcome::Enhanced), 300), today, LATER);
+        stats.save();
+
+        let mut reloaded = UsageStats::load(path.clone());
+        assert_eq!(
+            reloaded.stats(AiUsageRange::Month, today),
+            stats.stats(AiUsageRange::Month, today)
+        );
+
+        reloaded.clear();
+        let cleared = UsageStats::load(path.clone());
+        assert_eq!(
+            cleared.stats(AiUsageRange::Month, today).total,
+            AiUsageCounts::default()
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+}
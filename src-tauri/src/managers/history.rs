@@ -31,6 +31,8 @@ static MIGRATIONS: &[M] = &[
     ),
     M::up("ALTER TABLE transcription_history ADD COLUMN post_processed_text TEXT;"),
     M::up("ALTER TABLE transcription_history ADD COLUMN post_process_prompt TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN ai_enhance_mode TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN ai_tone TEXT;"),
 ];
 
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
@@ -43,6 +45,17 @@ pub struct HistoryEntry {
     pub transcription_text: String,
     pub post_processed_text: Option<String>,
     pub post_process_prompt: Option<String>,
+    /// AI enhancement mode that produced the pasted text, like "summarize"
+    pub ai_enhance_mode: Option<String>,
+    /// Tone AI enhancement rewrote the text in, like "professional"
+    pub ai_tone: Option<String>,
+}
+
+/// How AI enhancement produced the pasted text, saved with the history entry
+#[derive(Clone, Debug, Default)]
+pub struct AiEnhancementInfo {
+    pub mode: Option<String>,
+    pub tone: Option<String>,
 }
 
 pub struct HistoryManager {
@@ -183,10 +196,10 @@ impl HistoryManager {
         transcription_text: String,
         post_processed_text: Option<String>,
         post_process_prompt: Option<String>,
+        ai_enhancement: AiEnhancementInfo,
     ) -> Result<()> {
         let timestamp = Utc::now().timestamp();
         let file_name = format!("handy-{}.wav", timestamp);
-        let title = self.format_timestamp_title(timestamp);
 
         // Save WAV file
         let file_path = self.recordings_dir.join(&file_name);
@@ -196,10 +209,10 @@ impl HistoryManager {
         self.save_to_database(
             file_name,
             timestamp,
-            title,
             transcription_text,
             post_processed_text,
             post_process_prompt,
+            ai_enhancement,
         )?;
 
         // Clean up old entries
@@ -217,15 +230,16 @@ impl HistoryManager {
         &self,
         file_name: String,
         timestamp: i64,
-        title: String,
         transcription_text: String,
         post_processed_text: Option<String>,
         post_process_prompt: Option<String>,
+        ai_enhancement: AiEnhancementInfo,
     ) -> Result<()> {
+        let title = self.format_timestamp_title(timestamp);
         let conn = self.get_connection()?;
         conn.execute(
-            "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![file_name, timestamp, false, title, transcription_text, post_processed_text, post_process_prompt],
+            "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, ai_enhance_mode, ai_tone) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![file_name, timestamp, false, title, transcription_text, post_processed_text, post_process_prompt, ai_enhancement.mode, ai_enhancement.tone],
         )?;
 
         debug!("Saved transcription to database");
@@ -355,7 +369,7 @@ impl HistoryManager {
     pub async fn get_history_entries(&self) -> Result<Vec<HistoryEntry>> {
         let conn = self.get_connection()?;
         let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt FROM transcription_history ORDER BY timestamp DESC"
+            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, ai_enhance_mode, ai_tone FROM transcription_history ORDER BY timestamp DESC"
         )?;
 
         let rows = stmt.query_map([], |row| {
@@ -368,6 +382,8 @@ impl HistoryManager {
                 transcription_text: row.get("transcription_text")?,
                 post_processed_text: row.get("post_processed_text")?,
                 post_process_prompt: row.get("post_process_prompt")?,
+                ai_enhance_mode: row.get("ai_enhance_mode")?,
+                ai_tone: row.get("ai_tone")?,
             })
         })?;
 
@@ -413,7 +429,7 @@ impl HistoryManager {
     pub async fn get_entry_by_id(&self, id: i64) -> Result<Option<HistoryEntry>> {
         let conn = self.get_connection()?;
         let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt
+            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, ai_enhance_mode, ai_tone
              FROM transcription_history WHERE id = ?1",
         )?;
 
@@ -428,6 +444,8 @@ impl HistoryManager {
                     transcription_text: row.get("transcription_text")?,
                     post_processed_text: row.get("post_processed_text")?,
                     post_process_prompt: row.get("post_process_prompt")?,
+                    ai_enhance_mode: row.get("ai_enhance_mode")?,
+                    ai_tone: row.get("ai_tone")?,
                 })
             })
             .optional()?;
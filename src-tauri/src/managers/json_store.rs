@@ -0,0 +1,123 @@
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+/// A value persisted as JSON in the app data directory, or kept in memory
+/// only when the app data dir is unavailable. Reads and changes go through
+/// to the value; nothing is written until [`JsonStore::save`].
+pub struct JsonStore<T> {
+    path: Option<PathBuf>,
+    /// What the value is, as named in the log
+    name: &'static str,
+    value: T,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> JsonStore<T> {
+    /// Load the `name`d value from `path`. A missing or unreadable file
+    /// starts from the default.
+    pub fn load(path: PathBuf, name: &'static str) -> Self {
+        let value = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable {} at {}: {}", name, path.display(), e);
+                T::default()
+            }),
+            Err(_) => T::default(),
+        };
+
+        Self {
+            path: Some(path),
+            name,
+            value,
+        }
+    }
+
+    /// A value kept in memory only, starting from the default
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            name: "",
+            value: T::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let result = serde_json::to_string_pretty(&self.value)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| write_replacing(path, &json));
+        if let Err(e) = result {
+            warn!("Failed to save {} to {}: {}", self.name, path.display(), e);
+        }
+    }
+}
+
+impl<T> Deref for JsonStore<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for JsonStore<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// Write `contents` to a temporary file beside `path` and rename it over
+/// `path`, so quitting mid-write never leaves the file half written
+pub fn write_replacing(path: &Path, contents: &str) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut temp = OsString::from(path.as_os_str());
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::test_fixtures::temp_path;
+
+    #[test]
+    fn test_saved_values_survive_reload() {
+        let path = temp_path("json-store");
+        let _ = fs::remove_file(&path);
+
+        let mut store: JsonStore<Vec<u32>> = JsonStore::load(path.clone(), "numbers");
+        assert!(store.is_empty());
+        store.push(7);
+        store.save();
+
+        let reloaded: JsonStore<Vec<u32>> = JsonStore::load(path.clone(), "numbers");
+        assert_eq!(*reloaded, vec![7]);
+        let mut temp = OsString::from(path.as_os_str());
+        temp.push(".tmp");
+        assert!(!PathBuf::from(temp).exists());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_unreadable_file_starts_from_the_default() {
+        let path = temp_path("json-store-corrupt");
+        fs::write(&path, "not json").unwrap();
+
+        let store: JsonStore<Vec<u32>> = JsonStore::load(path.clone(), "numbers");
+        assert!(store.is_empty());
+
+        let _ = fs::remove_file(&path);
+    }
+}
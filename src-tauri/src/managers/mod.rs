@@ -1,5 +1,26 @@
+pub mod ai_auto_pull;
+pub mod ai_benchmark;
+pub mod ai_circuit_breaker;
+pub mod ai_endpoints;
 pub mod ai_enhancement;
+pub mod ai_enhancement_history;
+pub mod ai_enhancement_metrics;
+pub mod ai_enhancement_queue;
+pub mod ai_idle_unload;
+pub mod ai_latency_budget;
+pub mod ai_model_degradation;
+pub mod ai_model_usage;
+pub mod ai_preload;
+pub mod ai_pull_queue;
+pub mod ai_pull_records;
+pub mod ai_refinement;
+pub mod ai_request_dedup;
+pub mod ai_test_suite;
+pub mod ai_usage_stats;
 pub mod audio;
 pub mod history;
+pub mod json_store;
 pub mod model;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
 pub mod transcription;
@@ -0,0 +1,24 @@
+use crate::managers::ai_enhancement::EnhancementOutcome;
+use crate::managers::ai_enhancement_metrics::EnhancementRun;
+use std::path::PathBuf;
+
+/// A file in the temp directory for the test `name`, apart from other test
+/// processes
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("handy-{}-{}.json", name, std::process::id()))
+}
+
+/// A run that reached the model and took `duration_ms`
+pub fn run(outcome: Option<EnhancementOutcome>, duration_ms: u32) -> EnhancementRun {
+    EnhancementRun {
+        outcome,
+        duration_ms,
+        reached_model: true,
+        cached: false,
+        rejected_by: None,
+        retries: 0,
+        truncated: 0,
+        generated_tokens: 40,
+        generation_nanos: 500_000_000,
+    }
+}
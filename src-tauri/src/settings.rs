@@ -10,25 +10,902 @@ use tauri_plugin_store::StoreExt;
 pub struct AiFeatures {
     #[serde(default = "default_true")]
     pub punctuation_and_capitalization: bool,
+    #[serde(default)]
+    pub capitalization_style: AiCapitalizationStyle,
     #[serde(default = "default_true")]
     pub remove_filler_words: bool,
+    /// Words and phrases removed as fillers, matched in any casing
+    #[serde(default = "default_filler_words")]
+    pub filler_words: Vec<String>,
+    /// Write spoken punctuation and layout commands ("comma", "new line") as
+    /// what they stand for, by rule and whether or not the model runs
+    #[serde(default)]
+    pub spoken_punctuation: bool,
+    #[serde(default = "default_spoken_commands")]
+    pub spoken_commands: Vec<AiSpokenCommand>,
     #[serde(default = "default_true")]
     pub normalize_numbers: bool,
+    /// Thousand and decimal separators of normalized numbers
+    #[serde(default)]
+    pub number_locale: AiNumberLocale,
+    /// Numbers below this are written as words and the rest as digits,
+    /// ordinals included, or all of them as digits when 0
+    #[serde(default)]
+    pub number_style_threshold: u32,
+    /// Write spoken dates and times as dates and times ("march third" →
+    /// "March 3")
+    #[serde(default)]
+    pub normalize_dates_times: bool,
+    #[serde(default)]
+    pub date_style: AiDateStyle,
+    /// Write spoken measurements with digits and units ("five millimeters" →
+    /// "5 mm")
+    #[serde(default)]
+    pub normalize_units: bool,
+    #[serde(default)]
+    pub unit_style: AiUnitStyle,
+    /// Write spoken money amounts with digits and a currency sign ("two
+    /// hundred euros" → "€200")
+    #[serde(default)]
+    pub normalize_currency: bool,
+    #[serde(default)]
+    pub currency_style: AiCurrencyStyle,
+    /// Write dictated phone numbers in the pattern of `phone_style`, only
+    /// where the text says they are phone numbers
+    #[serde(default)]
+    pub format_phone_numbers: bool,
+    #[serde(default)]
+    pub phone_style: AiPhoneStyle,
     #[serde(default = "default_true")]
     pub fix_spelling: bool,
+    /// Spelling the spelling fixes write, or `Preserve` to keep each word
+    /// in the variant it was dictated in
+    #[serde(default)]
+    pub spelling_variant: AiSpellingVariant,
+    /// Fix grammar mistakes like "he don't" with the fewest edits possible,
+    /// which some find too invasive to have with the spelling fixes
+    #[serde(default)]
+    pub fix_grammar: bool,
+    /// Whether contractions like "don't" are written out, used, or kept
+    /// as they were dictated
+    #[serde(default)]
+    pub contraction_style: AiContractionStyle,
+    /// Drop words and phrases said twice by accident ("the the", "I I think")
+    #[serde(default)]
+    pub remove_repetitions: bool,
+    #[serde(default = "default_true")]
+    pub protect_spans: bool,
+    /// Break long dictations into paragraphs where the topic shifts
+    #[serde(default)]
+    pub split_paragraphs: bool,
+    /// Let the model break run-on sentences at natural clause boundaries,
+    /// and split them before connectors like "and then" when it doesn't run
+    #[serde(default)]
+    pub split_long_sentences: bool,
+    /// Sentences longer than this many words are split by rule
+    #[serde(default = "default_max_sentence_words")]
+    pub max_sentence_words: u32,
+    /// Lay out spoken enumerations ("first ..., second ...") as a list
+    #[serde(default)]
+    pub format_lists: bool,
+    #[serde(default)]
+    pub list_style: AiListStyle,
+    /// Typeset quotes, dashes and ellipses after enhancement (“”, —, …)
+    #[serde(default)]
+    pub smart_typography: bool,
+    /// Mask swear words in the pasted text ("f***"), by rule and whether or
+    /// not the model ran
+    #[serde(default)]
+    pub mask_profanity: bool,
+    #[serde(default)]
+    pub profanity_mask: AiProfanityMask,
+    /// Words masked while profanity is masked, with their endings
+    #[serde(default = "default_profane_words")]
+    pub profane_words: Vec<String>,
+    /// Write emoji said by name ("thumbs up emoji") or as an emoticon on its
+    /// own ("smiley face") as the emoji, by rule after enhancement
+    #[serde(default)]
+    pub convert_spoken_emoji: bool,
+    /// Emoji written while spoken emoji are converted
+    #[serde(default = "default_spoken_emoji")]
+    pub spoken_emoji: Vec<AiSpokenEmoji>,
+    /// Replace email addresses, phone numbers and card numbers in the pasted
+    /// text with placeholders like "[email]", by rule after enhancement
+    #[serde(default)]
+    pub redact_sensitive: bool,
+    #[serde(default)]
+    pub redaction: AiRedaction,
+    /// ISO 639-3 code of the language to translate the transcript into
+    #[serde(default)]
+    pub translate_to: Option<String>,
+    /// Tone to rewrite the text in, which lets the model rephrase it
+    #[serde(default)]
+    pub tone: Option<AiTone>,
+    #[serde(default)]
+    pub instructions: AiFeatureInstructions,
+}
+
+/// Prompt instructions replacing the built-in ones for each feature, used
+/// while that feature is enabled
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Type)]
+pub struct AiFeatureInstructions {
+    #[serde(default)]
+    pub punctuation_and_capitalization: Option<String>,
+    #[serde(default)]
+    pub remove_filler_words: Option<String>,
+    #[serde(default)]
+    pub normalize_numbers: Option<String>,
+    #[serde(default)]
+    pub fix_spelling: Option<String>,
+}
+
+/// Register the text is rewritten in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiTone {
+    Casual,
+    Professional,
+    Neutral,
+}
+
+impl AiTone {
+    /// Name recorded with the history entries rewritten in this tone
+    pub fn label(&self) -> &'static str {
+        match self {
+            AiTone::Casual => "casual",
+            AiTone::Professional => "professional",
+            AiTone::Neutral => "neutral",
+        }
+    }
+}
+
+/// Marker put in front of each item of a formatted list
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiListStyle {
+    /// "- item"
+    Dash,
+    /// "1. item"
+    Numbered,
+}
+
+impl Default for AiListStyle {
+    fn default() -> Self {
+        AiListStyle::Dash
+    }
+}
+
+/// How normalized dates and times are written
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiDateStyle {
+    /// "March 3, 2025" and "2:30 PM"
+    MonthDay,
+    /// "3 March 2025" and "14:30"
+    DayMonth,
+    /// "2025-03-03" and "14:30"
+    Iso,
+}
+
+impl Default for AiDateStyle {
+    fn default() -> Self {
+        AiDateStyle::MonthDay
+    }
+}
+
+/// How units of normalized measurements are written
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiUnitStyle {
+    /// "5 mm", with a space before the unit as in SI
+    Symbols,
+    /// "5 millimeters"
+    Words,
+}
+
+impl Default for AiUnitStyle {
+    fn default() -> Self {
+        AiUnitStyle::Symbols
+    }
+}
+
+/// How punctuation and capitalization cases the text
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiCapitalizationStyle {
+    /// "Quarterly results for the New York office"
+    Sentence,
+    /// "Quarterly Results for the New York Office", for headings
+    Title,
+    /// "quarterly results for the new york office"
+    Lowercase,
+    /// As transcribed
+    Preserve,
+}
+
+impl Default for AiCapitalizationStyle {
+    fn default() -> Self {
+        AiCapitalizationStyle::Sentence
+    }
+}
+
+/// How normalized numbers separate thousands and decimals
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiNumberLocale {
+    /// As written in the language of the text, or the one it's translated to
+    Auto,
+    /// "1,234.5" and "10%"
+    EnUs,
+    /// "1.234,5" and "10 %"
+    DeDe,
+    /// "1 234,5" and "10 %", with narrow no-break spaces between thousands
+    FrFr,
+}
+
+impl Default for AiNumberLocale {
+    fn default() -> Self {
+        AiNumberLocale::Auto
+    }
+}
+
+/// Where the currency goes in normalized money amounts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiCurrencyStyle {
+    /// "$49.99" and "€200"
+    SymbolBefore,
+    /// "49,99 $" and "200 €", as written in much of Europe
+    SymbolAfter,
+    /// "49.99 USD" and "200 EUR"
+    IsoCode,
+}
+
+impl Default for AiCurrencyStyle {
+    fn default() -> Self {
+        AiCurrencyStyle::SymbolBefore
+    }
+}
+
+/// Pattern formatted phone numbers are written in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiPhoneStyle {
+    /// "(555) 123-4567", "+1 (555) 123-4567" with the country code
+    Us,
+    /// "+44 20 7946 0958"
+    Uk,
+}
+
+impl Default for AiPhoneStyle {
+    fn default() -> Self {
+        AiPhoneStyle::Us
+    }
+}
+
+/// Which of the English spellings the spelling fixes write
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiSpellingVariant {
+    /// "color", "organize", "center"
+    Us,
+    /// "colour", "organise", "centre"
+    Uk,
+    /// British spelling with Australian exceptions like "program"
+    Au,
+    /// Whichever each word was dictated in
+    Preserve,
+}
+
+impl Default for AiSpellingVariant {
+    fn default() -> Self {
+        AiSpellingVariant::Preserve
+    }
+}
+
+/// Whether contractions are written out in full or used
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiContractionStyle {
+    /// "don't" → "do not"
+    Expand,
+    /// "do not" → "don't"
+    Contract,
+    /// Whichever was dictated
+    Preserve,
+}
+
+impl Default for AiContractionStyle {
+    fn default() -> Self {
+        AiContractionStyle::Preserve
+    }
+}
+
+/// How masked profanity is written
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiProfanityMask {
+    /// "f***", keeping the first letter
+    FirstLetter,
+    /// "****"
+    Asterisks,
+}
+
+impl Default for AiProfanityMask {
+    fn default() -> Self {
+        AiProfanityMask::FirstLetter
+    }
+}
+
+/// Where what a spoken command writes attaches
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiSpokenCommandKind {
+    /// To the word before, as a comma or a closing quote
+    Closing,
+    /// To the word after, as an opening quote
+    Opening,
+    /// In place of the space around it, as a line break
+    Break,
+}
+
+/// A phrase dictated for punctuation or layout, and what it writes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiSpokenCommand {
+    /// What is said, matched in any casing
+    pub phrase: String,
+    pub symbol: String,
+    pub kind: AiSpokenCommandKind,
+}
+
+/// An emoji said by name, and what it writes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiSpokenEmoji {
+    /// What is said before "emoji", matched in any casing
+    pub phrase: String,
+    pub emoji: String,
+    /// Also written without "emoji" after it when said on its own, like
+    /// "smiley face" at the end of a sentence
+    #[serde(default)]
+    pub emoticon: bool,
+}
+
+/// What is redacted while sensitive information is, and what replaces it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiRedaction {
+    #[serde(default = "default_true")]
+    pub emails: bool,
+    #[serde(default = "default_true")]
+    pub phone_numbers: bool,
+    /// Numbers of 13 to 19 digits passing the Luhn check
+    #[serde(default = "default_true")]
+    pub card_numbers: bool,
+    /// Written in place of each redaction, with "{category}" standing for
+    /// "email", "phone" or "card"
+    #[serde(default = "default_redaction_placeholder")]
+    pub placeholder: String,
+}
+
+fn default_redaction_placeholder() -> String {
+    "[{category}]".to_string()
+}
+
+impl Default for AiRedaction {
+    fn default() -> Self {
+        Self {
+            emails: true,
+            phone_numbers: true,
+            card_numbers: true,
+            placeholder: default_redaction_placeholder(),
+        }
+    }
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_max_sentence_words() -> u32 {
+    30
+}
+
+/// Commands as other dictation tools take them, in both US and British
+/// English ("period" and "full stop")
+fn default_spoken_commands() -> Vec<AiSpokenCommand> {
+    [
+        ("comma", ",", AiSpokenCommandKind::Closing),
+        ("period", ".", AiSpokenCommandKind::Closing),
+        ("full stop", ".", AiSpokenCommandKind::Closing),
+        ("question mark", "?", AiSpokenCommandKind::Closing),
+        ("exclamation mark", "!", AiSpokenCommandKind::Closing),
+        ("exclamation point", "!", AiSpokenCommandKind::Closing),
+        ("colon", ":", AiSpokenCommandKind::Closing),
+        ("semicolon", ";", AiSpokenCommandKind::Closing),
+        ("open quote", "\"", AiSpokenCommandKind::Opening),
+        ("close quote", "\"", AiSpokenCommandKind::Closing),
+        ("end quote", "\"", AiSpokenCommandKind::Closing),
+        ("unquote", "\"", AiSpokenCommandKind::Closing),
+        ("open paren", "(", AiSpokenCommandKind::Opening),
+        ("open parenthesis", "(", AiSpokenCommandKind::Opening),
+        ("close paren", ")", AiSpokenCommandKind::Closing),
+        ("close parenthesis", ")", AiSpokenCommandKind::Closing),
+        ("new line", "\n", AiSpokenCommandKind::Break),
+        ("newline", "\n", AiSpokenCommandKind::Break),
+        ("new paragraph", "\n\n", AiSpokenCommandKind::Break),
+    ]
+    .iter()
+    .map(|&(phrase, symbol, kind)| AiSpokenCommand {
+        phrase: phrase.to_string(),
+        symbol: symbol.to_string(),
+        kind,
+    })
+    .collect()
+}
+
+/// Emoji common in chat, under the names people tend to say them by
+fn default_spoken_emoji() -> Vec<AiSpokenEmoji> {
+    [
+        ("smiley face", "🙂", true),
+        ("happy face", "🙂", true),
+        ("sad face", "🙁", true),
+        ("frowny face", "🙁", true),
+        ("winky face", "😉", true),
+        ("smile", "😊", false),
+        ("grin", "😁", false),
+        ("laughing", "😂", false),
+        ("crying laughing", "😂", false),
+        ("tears of joy", "😂", false),
+        ("rolling on the floor laughing", "🤣", false),
+        ("wink", "😉", false),
+        ("heart eyes", "😍", false),
+        ("kiss", "😘", false),
+        ("sunglasses", "😎", false),
+        ("thinking", "🤔", false),
+        ("thinking face", "🤔", false),
+        ("crying", "😢", false),
+        ("sad", "😢", false),
+        ("angry", "😠", false),
+        ("mind blown", "🤯", false),
+        ("skull", "💀", false),
+        ("eyes", "👀", false),
+        ("thumbs up", "👍", false),
+        ("thumbs down", "👎", false),
+        ("clap", "👏", false),
+        ("clapping", "👏", false),
+        ("wave", "👋", false),
+        ("ok hand", "👌", false),
+        ("pray", "🙏", false),
+        ("folded hands", "🙏", false),
+        ("muscle", "💪", false),
+        ("shrug", "🤷", false),
+        ("facepalm", "🤦", false),
+        ("heart", "❤️", false),
+        ("red heart", "❤️", false),
+        ("broken heart", "💔", false),
+        ("fire", "🔥", false),
+        ("party", "🎉", false),
+        ("party popper", "🎉", false),
+        ("rocket", "🚀", false),
+        ("hundred", "💯", false),
+        ("one hundred", "💯", false),
+        ("check mark", "✅", false),
+        ("cross mark", "❌", false),
+        ("warning", "⚠️", false),
+        ("sparkles", "✨", false),
+        ("star", "⭐", false),
+        ("light bulb", "💡", false),
+        ("coffee", "☕", false),
+    ]
+    .iter()
+    .map(|&(phrase, emoji, emoticon)| AiSpokenEmoji {
+        phrase: phrase.to_string(),
+        emoji: emoji.to_string(),
+        emoticon,
+    })
+    .collect()
+}
+
+fn default_profane_words() -> Vec<String> {
+    [
+        "fuck",
+        "motherfuck",
+        "shit",
+        "bullshit",
+        "bitch",
+        "bastard",
+        "asshole",
+        "cunt",
+        "dickhead",
+        "twat",
+        "wanker",
+        "bollocks",
+    ]
+    .iter()
+    .map(|word| word.to_string())
+    .collect()
+}
+
+fn default_filler_words() -> Vec<String> {
+    [
+        "um",
+        "uh",
+        "erm",
+        "hmm",
+        "like",
+        "you know",
+        "I mean",
+        "sort of",
+        "kind of",
+        "basically",
+    ]
+    .iter()
+    .map(|filler| filler.to_string())
+    .collect()
+}
+
 impl Default for AiFeatures {
     fn default() -> Self {
         Self {
             punctuation_and_capitalization: true,
+            capitalization_style: AiCapitalizationStyle::default(),
             remove_filler_words: true,
+            filler_words: default_filler_words(),
+            spoken_punctuation: false,
+            spoken_commands: default_spoken_commands(),
             normalize_numbers: true,
+            number_locale: AiNumberLocale::default(),
+            number_style_threshold: 0,
+            normalize_dates_times: false,
+            date_style: AiDateStyle::default(),
+            normalize_units: false,
+            unit_style: AiUnitStyle::default(),
+            normalize_currency: false,
+            currency_style: AiCurrencyStyle::default(),
+            format_phone_numbers: false,
+            phone_style: AiPhoneStyle::default(),
             fix_spelling: true,
+            spelling_variant: AiSpellingVariant::default(),
+            fix_grammar: false,
+            contraction_style: AiContractionStyle::default(),
+            remove_repetitions: false,
+            protect_spans: true,
+            split_paragraphs: false,
+            split_long_sentences: false,
+            max_sentence_words: default_max_sentence_words(),
+            format_lists: false,
+            list_style: AiListStyle::default(),
+            smart_typography: false,
+            mask_profanity: false,
+            profanity_mask: AiProfanityMask::default(),
+            profane_words: default_profane_words(),
+            convert_spoken_emoji: false,
+            spoken_emoji: default_spoken_emoji(),
+            redact_sensitive: false,
+            redaction: AiRedaction::default(),
+            translate_to: None,
+            tone: None,
+            instructions: AiFeatureInstructions::default(),
+        }
+    }
+}
+
+/// A transcript and its correction, shown to the model as an example
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiExample {
+    pub input: String,
+    pub output: String,
+}
+
+/// A word or name the transcript tends to get wrong, with how it should be written
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiVocabularyEntry {
+    /// What the transcript tends to contain instead
+    #[serde(default)]
+    pub spoken_forms: Vec<String>,
+    pub written_form: String,
+}
+
+/// A find/replace rule applied by rule, before the model sees the text or
+/// after it is done with it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiReplacementRule {
+    /// Regular expression matched against the text
+    pub pattern: String,
+    /// What each match becomes, with `$1` or `${name}` for its groups
+    #[serde(default)]
+    pub replacement: String,
+    #[serde(default)]
+    pub stage: AiRuleStage,
+}
+
+/// When a replacement rule is applied
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiRuleStage {
+    /// To the transcript, so the model never spends tokens on what it
+    /// replaces
+    BeforeAi,
+    /// To the enhanced text
+    AfterAi,
+}
+
+impl Default for AiRuleStage {
+    fn default() -> Self {
+        AiRuleStage::BeforeAi
+    }
+}
+
+/// Text inserted when a phrase like "insert my signature" is said
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiSnippet {
+    /// What is said to insert it
+    pub trigger: String,
+    /// What is inserted, line breaks and all
+    pub text: String,
+    /// Whether it is inserted where the trigger is said in a longer
+    /// dictation, rather than only when the trigger is all that was said
+    #[serde(default)]
+    pub within_text: bool,
+}
+
+/// An acronym written the same way wherever it turns up, in capitals or in
+/// its own mixed case like "OAuth"
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiAcronym {
+    pub written_form: String,
+    /// Off for acronyms that are also common words, like "IT" and "US",
+    /// until the user turns them on
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// A name the transcript tends to misspell or lowercase, like a colleague's
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiProperNoun {
+    /// The name exactly as it is written, accents included
+    pub name: String,
+    /// What the transcript tends to contain instead, beyond near-misses
+    #[serde(default)]
+    pub misrecognitions: Vec<String>,
+}
+
+/// A transcript of the user's own run through enhancement by the test suite,
+/// alongside the built-in ones
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiTestSample {
+    pub name: String,
+    /// The transcript, raw the way dictation leaves it
+    pub text: String,
+    /// Text the enhanced output has to contain, exactly as written
+    #[serde(default)]
+    pub expected: Vec<String>,
+    /// Words or phrases the enhanced output must no longer contain, in any
+    /// casing
+    #[serde(default)]
+    pub unexpected: Vec<String>,
+}
+
+/// What happens to a transcript in a language that isn't one of `ai_languages`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiLanguagePolicy {
+    /// Enhance it, telling the model to keep it in that language
+    Preserve,
+    /// Paste it without enhancement
+    SkipUnconfigured,
+}
+
+impl Default for AiLanguagePolicy {
+    fn default() -> Self {
+        AiLanguagePolicy::Preserve
+    }
+}
+
+/// What happens to transcripts waiting for enhancement when another one
+/// arrives
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiQueuePolicy {
+    /// Enhance and paste every one of them, in the order they were dictated
+    QueueAll,
+    /// Drop them, so only the newest is enhanced and pasted
+    LatestWins,
+}
+
+impl Default for AiQueuePolicy {
+    fn default() -> Self {
+        AiQueuePolicy::QueueAll
+    }
+}
+
+/// What to do with a transcript longer than the maximum length for
+/// enhancement
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiLongTextPolicy {
+    /// Paste it as dictated
+    Skip,
+    /// Enhance all of it, in chunks
+    Chunked,
+    /// Enhance the words up to the maximum and paste the rest as dictated
+    FirstWords,
+}
+
+impl Default for AiLongTextPolicy {
+    fn default() -> Self {
+        AiLongTextPolicy::Skip
+    }
+}
+
+/// How a summary of a dictation is laid out
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiSummaryStyle {
+    Bullets,
+    Paragraph,
+}
+
+impl Default for AiSummaryStyle {
+    fn default() -> Self {
+        AiSummaryStyle::Bullets
+    }
+}
+
+/// How many model layers Ollama should offload to the GPU
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AiGpuOffload {
+    Auto,
+    CpuOnly,
+    Layers(u32),
+}
+
+impl Default for AiGpuOffload {
+    fn default() -> Self {
+        AiGpuOffload::Auto
+    }
+}
+
+impl AiGpuOffload {
+    /// Value for Ollama's `num_gpu` option, `None` leaves the choice to Ollama
+    pub fn to_num_gpu(self) -> Option<i32> {
+        match self {
+            AiGpuOffload::Auto => None,
+            AiGpuOffload::CpuOnly => Some(0),
+            AiGpuOffload::Layers(layers) => Some(layers.min(i32::MAX as u32) as i32),
+        }
+    }
+}
+
+/// An Ollama host, tried in the order configured
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct AiOllamaEndpoint {
+    pub base_url: String,
+    /// Model to use on this host instead of the globally selected one
+    #[serde(default)]
+    pub preferred_model: Option<String>,
+}
+
+/// Models of the two-pass mode, where the fast one's correction is pasted
+/// at once and the quality one refines it in the background
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AiTwoPass {
+    /// Model whose correction is pasted, in place of the selected one
+    pub fast_model: String,
+    /// Model correcting the transcript again once the fast one's text is
+    /// pasted, offered through `ai-enhancement-refined` when it differs
+    pub quality_model: String,
+}
+
+/// What a second pass over the last enhancement changes, run on demand by
+/// the `reenhance_last` shortcut
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct AiReenhanceOverrides {
+    /// Model to run it with, or `None` for the selected one
+    pub model: Option<String>,
+    /// Fix grammar too, whether or not the enhancement did
+    pub fix_grammar: bool,
+}
+
+impl Default for AiReenhanceOverrides {
+    fn default() -> Self {
+        Self {
+            model: None,
+            fix_grammar: true,
+        }
+    }
+}
+
+/// Bounds on how much enhanced text may differ in length from the transcript,
+/// as output/input ratios of word and character counts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Type)]
+pub struct AiOutputGuard {
+    #[serde(default = "default_output_guard_min_ratio")]
+    pub min_ratio: f64,
+    #[serde(default = "default_output_guard_max_ratio")]
+    pub max_ratio: f64,
+    /// Lower bound used instead of `min_ratio` when filler words are being
+    /// removed, since that legitimately shortens the text
+    #[serde(default = "default_output_guard_filler_min_ratio")]
+    pub filler_min_ratio: f64,
+}
+
+fn default_output_guard_min_ratio() -> f64 {
+    0.6
+}
+
+fn default_output_guard_max_ratio() -> f64 {
+    1.6
+}
+
+fn default_output_guard_filler_min_ratio() -> f64 {
+    0.4
+}
+
+impl Default for AiOutputGuard {
+    fn default() -> Self {
+        Self {
+            min_ratio: default_output_guard_min_ratio(),
+            max_ratio: default_output_guard_max_ratio(),
+            filler_min_ratio: default_output_guard_filler_min_ratio(),
+        }
+    }
+}
+
+/// How the model samples its output for one kind of enhancement
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Type)]
+pub struct AiSamplingOptions {
+    pub temperature: f32,
+    /// Most tokens generated in one request, however long the input
+    pub max_output_tokens: u32,
+    /// Penalty on repeating recent tokens, 1.0 for none
+    pub repeat_penalty: f32,
+}
+
+/// Sampling options for each kind of enhancement. Corrections want the
+/// model as literal as possible, rewrites need some room to rephrase.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Type)]
+pub struct AiGenerationOptions {
+    #[serde(default = "default_correct_sampling")]
+    pub correct: AiSamplingOptions,
+    #[serde(default = "default_summarize_sampling")]
+    pub summarize: AiSamplingOptions,
+    /// Used when rewriting in a tone
+    #[serde(default = "default_tone_sampling")]
+    pub tone: AiSamplingOptions,
+}
+
+fn default_correct_sampling() -> AiSamplingOptions {
+    AiSamplingOptions {
+        temperature: 0.1,
+        max_output_tokens: 2048,
+        repeat_penalty: 1.1,
+    }
+}
+
+fn default_summarize_sampling() -> AiSamplingOptions {
+    AiSamplingOptions {
+        temperature: 0.3,
+        max_output_tokens: 1024,
+        repeat_penalty: 1.1,
+    }
+}
+
+fn default_tone_sampling() -> AiSamplingOptions {
+    AiSamplingOptions {
+        temperature: 0.5,
+        max_output_tokens: 2048,
+        repeat_penalty: 1.1,
+    }
+}
+
+impl Default for AiGenerationOptions {
+    fn default() -> Self {
+        Self {
+            correct: default_correct_sampling(),
+            summarize: default_summarize_sampling(),
+            tone: default_tone_sampling(),
         }
     }
 }
@@ -327,6 +1204,165 @@ pub struct AppSettings {
     pub ai_selected_model: Option<String>,
     #[serde(default)]
     pub ai_features: AiFeatures,
+    #[serde(default = "default_ai_pull_stall_timeout_secs")]
+    pub ai_pull_stall_timeout_secs: u64,
+    #[serde(default)]
+    pub ai_pull_auto_retry: bool,
+    /// Download the selected model when Ollama reports it missing
+    #[serde(default = "default_true")]
+    pub ai_auto_pull_missing_model: bool,
+    /// Ask the registry for newer versions of the installed models at
+    /// startup. Turned off on metered connections; checking from the
+    /// settings still works.
+    #[serde(default = "default_true")]
+    pub ai_check_model_updates: bool,
+    /// Generation thread count; `None` picks one from the CPU topology
+    #[serde(default)]
+    pub ai_num_thread: Option<u32>,
+    #[serde(default)]
+    pub ai_gpu_offload: AiGpuOffload,
+    #[serde(default)]
+    pub ai_main_gpu: Option<u32>,
+    #[serde(default = "default_ai_ollama_endpoints")]
+    pub ai_ollama_endpoints: Vec<AiOllamaEndpoint>,
+    #[serde(default)]
+    pub ai_model_registry_prefix: Option<String>,
+    #[serde(default)]
+    pub ai_model_registry_insecure: bool,
+    /// Transcripts estimated above this many tokens are enhanced in chunks
+    #[serde(default = "default_ai_chunk_max_tokens")]
+    pub ai_chunk_max_tokens: u32,
+    /// Paste the original transcript when AI enhancement fails or times out
+    #[serde(default = "default_ai_fallback_to_original")]
+    pub ai_fallback_to_original: bool,
+    /// Capitalize and punctuate the original transcript by rule when it is
+    /// pasted in place of an enhancement
+    #[serde(default)]
+    pub ai_offline_fallback: bool,
+    /// Milliseconds allowed for enhancing each chunk of a transcript before
+    /// giving up on it, doubled for summaries and translations
+    #[serde(default = "default_ai_enhancement_timeout_ms")]
+    pub ai_enhancement_timeout_ms: u32,
+    /// Enhancements failing in a row before the session moves to a smaller
+    /// installed model, or 0 to keep the selected one
+    #[serde(default = "default_ai_degrade_after_failures")]
+    pub ai_degrade_after_failures: u32,
+    /// Milliseconds a transcript may take to enhance, for which the best
+    /// benchmarked model expected to finish in time is used in place of the
+    /// selected one, or 0 to always use the selected one
+    #[serde(default)]
+    pub ai_latency_budget_ms: u32,
+    /// Enhance with a fast model and refine with a better one, or `None` to
+    /// enhance once with the selected model
+    #[serde(default)]
+    pub ai_two_pass: Option<AiTwoPass>,
+    /// What the `reenhance_last` shortcut changes about the last enhancement
+    #[serde(default)]
+    pub ai_reenhance: AiReenhanceOverrides,
+    /// Transcripts with fewer words are pasted as dictated, unless they are
+    /// to be translated
+    #[serde(default = "default_ai_min_words_for_enhancement")]
+    pub ai_min_words_for_enhancement: u32,
+    /// Transcripts with more words are handled as `ai_long_text_policy`
+    /// says. Summaries are always of the whole transcript.
+    #[serde(default = "default_ai_max_words_for_enhancement")]
+    pub ai_max_words_for_enhancement: u32,
+    #[serde(default)]
+    pub ai_long_text_policy: AiLongTextPolicy,
+    /// Load the model when recording starts even when running on battery
+    #[serde(default = "default_true")]
+    pub ai_warm_up_on_battery: bool,
+    /// Load the selected model shortly after Handy starts, or once Ollama
+    /// does, so the first dictation doesn't wait for it
+    #[serde(default)]
+    pub ai_preload_on_startup: bool,
+    /// Minutes the model preloaded at startup stays loaded without being used
+    #[serde(default = "default_ai_preload_keep_alive_mins")]
+    pub ai_preload_keep_alive_mins: u32,
+    /// Minutes without an enhancement before the model is unloaded to free
+    /// its memory, 0 to leave unloading to Ollama
+    #[serde(default)]
+    pub ai_idle_unload_minutes: u32,
+    /// Reuse the enhanced text when the same transcript comes up again
+    #[serde(default = "default_true")]
+    pub ai_cache_enabled: bool,
+    /// Most enhanced texts kept for reuse, in memory only
+    #[serde(default = "default_ai_cache_size")]
+    pub ai_cache_size: u32,
+    /// Keep every transcript and its enhancement in a local history file.
+    /// Off by default, as it stores everything dictated.
+    #[serde(default)]
+    pub ai_history_enabled: bool,
+    /// Leave the dictated text out of the `ai-enhancement-complete` event
+    /// and send only its length, while the history is off
+    #[serde(default)]
+    pub ai_withhold_original_from_events: bool,
+    /// Count what came of each enhancement per day, to chart how well it is
+    /// doing. Off by default, and the counts never leave this machine.
+    #[serde(default)]
+    pub ai_usage_stats_enabled: bool,
+    /// Show the model what was pasted for the last few utterances, so text
+    /// dictated in bursts stays consistent
+    #[serde(default)]
+    pub ai_context_enabled: bool,
+    /// Most utterances shown to the model as preceding text
+    #[serde(default = "default_ai_context_utterances")]
+    pub ai_context_utterances: u32,
+    /// Seconds without dictating after which the preceding text is forgotten
+    #[serde(default = "default_ai_context_idle_secs")]
+    pub ai_context_idle_secs: u32,
+    #[serde(default)]
+    pub ai_queue_policy: AiQueuePolicy,
+    #[serde(default)]
+    pub ai_output_guard: AiOutputGuard,
+    #[serde(default)]
+    pub ai_generation_options: AiGenerationOptions,
+    /// Prompt with `{instructions}` and `{text}` placeholders used instead of
+    /// the built-in one
+    #[serde(default)]
+    pub ai_custom_prompt_template: Option<String>,
+    /// Give every enabled feature's instruction, even when the transcript has
+    /// nothing for it, like numbers or filler words
+    #[serde(default)]
+    pub ai_prompt_all_instructions: bool,
+    /// Examples added to the prompt after the built-in ones for each feature
+    #[serde(default)]
+    pub ai_examples: Vec<AiExample>,
+    /// Replacements applied before the prompt is built and again to the output
+    #[serde(default)]
+    pub ai_vocabulary: Vec<AiVocabularyEntry>,
+    /// Find/replace rules applied in order around the model
+    #[serde(default)]
+    pub ai_replacement_rules: Vec<AiReplacementRule>,
+    /// Text inserted in place of a trigger phrase, out of the model's reach
+    #[serde(default)]
+    pub ai_snippets: Vec<AiSnippet>,
+    /// How close what was said has to be to a snippet's trigger, from 0.5
+    /// for loosely to 1 for letter for letter
+    #[serde(default = "default_ai_snippet_strictness")]
+    pub ai_snippet_strictness: f64,
+    /// Acronyms cased after enhancement
+    #[serde(default = "default_ai_acronyms")]
+    pub ai_acronyms: Vec<AiAcronym>,
+    /// Names listed in the prompt when mentioned and spelled right afterwards
+    #[serde(default)]
+    pub ai_proper_nouns: Vec<AiProperNoun>,
+    /// Transcripts of the user's own run by the enhancement test suite
+    #[serde(default)]
+    pub ai_test_samples: Vec<AiTestSample>,
+    /// Languages dictated in, as ISO 639-3 codes like "eng" or "deu"
+    #[serde(default = "default_ai_languages")]
+    pub ai_languages: Vec<String>,
+    #[serde(default)]
+    pub ai_language_policy: AiLanguagePolicy,
+    /// Language the translation toggle switches to, as an ISO 639-3 code
+    #[serde(default = "default_ai_translate_target")]
+    pub ai_translate_target: String,
+    /// Layout of summaries made with the summarize shortcut
+    #[serde(default)]
+    pub ai_summary_style: AiSummaryStyle,
+    #[serde(default = "default_ai_summary_max_words")]
+    pub ai_summary_max_words: u32,
 }
 
 fn default_model() -> String {
@@ -482,6 +1518,77 @@ fn default_post_process_prompts() -> Vec<LLMPrompt> {
     }]
 }
 
+fn default_ai_ollama_endpoints() -> Vec<AiOllamaEndpoint> {
+    vec![AiOllamaEndpoint {
+        base_url: "http://localhost:11434".to_string(),
+        preferred_model: None,
+    }]
+}
+
+fn default_ai_pull_stall_timeout_secs() -> u64 {
+    60
+}
+
+fn default_ai_chunk_max_tokens() -> u32 {
+    250
+}
+
+fn default_ai_acronyms() -> Vec<AiAcronym> {
+    crate::ai_toolkit::acronyms::default_acronyms()
+}
+
+fn default_ai_languages() -> Vec<String> {
+    vec!["eng".to_string()]
+}
+
+fn default_ai_translate_target() -> String {
+    "eng".to_string()
+}
+
+fn default_ai_summary_max_words() -> u32 {
+    150
+}
+
+fn default_ai_fallback_to_original() -> bool {
+    true
+}
+
+fn default_ai_enhancement_timeout_ms() -> u32 {
+    8000
+}
+
+fn default_ai_degrade_after_failures() -> u32 {
+    3
+}
+
+fn default_ai_preload_keep_alive_mins() -> u32 {
+    60
+}
+
+fn default_ai_min_words_for_enhancement() -> u32 {
+    3
+}
+
+fn default_ai_snippet_strictness() -> f64 {
+    0.8
+}
+
+fn default_ai_max_words_for_enhancement() -> u32 {
+    400
+}
+
+fn default_ai_cache_size() -> u32 {
+    100
+}
+
+fn default_ai_context_utterances() -> u32 {
+    3
+}
+
+fn default_ai_context_idle_secs() -> u32 {
+    120
+}
+
 fn default_experiments_enabled() -> bool {
     false
 }
@@ -543,6 +1650,17 @@ pub fn get_default_settings() -> AppSettings {
     let default_shortcut = "ctrl+space";
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     let default_shortcut = "alt+space";
+    let summary_shortcut = format!("shift+{}", default_shortcut);
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    let tone_modifier = "alt";
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let tone_modifier = "ctrl";
+    let professional_shortcut = format!("{}+{}", tone_modifier, default_shortcut);
+    let email_shortcut = format!("{}+shift+{}", tone_modifier, default_shortcut);
+    let heading_shortcut = format!("{}+shift+h", tone_modifier);
+    let markdown_shortcut = format!("{}+shift+m", tone_modifier);
+    let code_shortcut = format!("{}+shift+c", tone_modifier);
+    let reenhance_shortcut = format!("{}+shift+r", tone_modifier);
 
     let mut bindings = HashMap::new();
     bindings.insert(
@@ -555,6 +1673,79 @@ pub fn get_default_settings() -> AppSettings {
             current_binding: default_shortcut.to_string(),
         },
     );
+    bindings.insert(
+        "transcribe_summary".to_string(),
+        ShortcutBinding {
+            id: "transcribe_summary".to_string(),
+            name: "Transcribe & Summarize".to_string(),
+            description: "Converts your speech into text and pastes an AI summary of it."
+                .to_string(),
+            default_binding: summary_shortcut.clone(),
+            current_binding: summary_shortcut,
+        },
+    );
+    bindings.insert(
+        "transcribe_professional".to_string(),
+        ShortcutBinding {
+            id: "transcribe_professional".to_string(),
+            name: "Transcribe in Professional Tone".to_string(),
+            description: "Converts your speech into text rewritten in a professional tone."
+                .to_string(),
+            default_binding: professional_shortcut.clone(),
+            current_binding: professional_shortcut,
+        },
+    );
+    bindings.insert(
+        "transcribe_email".to_string(),
+        ShortcutBinding {
+            id: "transcribe_email".to_string(),
+            name: "Transcribe as Email".to_string(),
+            description: "Converts your speech into text laid out as an email.".to_string(),
+            default_binding: email_shortcut.clone(),
+            current_binding: email_shortcut,
+        },
+    );
+    bindings.insert(
+        "transcribe_markdown".to_string(),
+        ShortcutBinding {
+            id: "transcribe_markdown".to_string(),
+            name: "Transcribe as Markdown".to_string(),
+            description: "Converts your speech into a Markdown note.".to_string(),
+            default_binding: markdown_shortcut.clone(),
+            current_binding: markdown_shortcut,
+        },
+    );
+    bindings.insert(
+        "transcribe_code".to_string(),
+        ShortcutBinding {
+            id: "transcribe_code".to_string(),
+            name: "Transcribe as Code".to_string(),
+            description: "Converts your speech into code, with symbols written out.".to_string(),
+            default_binding: code_shortcut.clone(),
+            current_binding: code_shortcut,
+        },
+    );
+    bindings.insert(
+        "transcribe_heading".to_string(),
+        ShortcutBinding {
+            id: "transcribe_heading".to_string(),
+            name: "Transcribe as Heading".to_string(),
+            description: "Converts your speech into text in title case, for headings.".to_string(),
+            default_binding: heading_shortcut.clone(),
+            current_binding: heading_shortcut,
+        },
+    );
+    bindings.insert(
+        "reenhance_last".to_string(),
+        ShortcutBinding {
+            id: "reenhance_last".to_string(),
+            name: "Enhance Again".to_string(),
+            description: "Runs a heavier AI pass over the text pasted last and copies the result."
+                .to_string(),
+            default_binding: reenhance_shortcut.clone(),
+            current_binding: reenhance_shortcut,
+        },
+    );
     bindings.insert(
         "cancel".to_string(),
         ShortcutBinding {
@@ -606,6 +1797,57 @@ pub fn get_default_settings() -> AppSettings {
         ai_enhancement_enabled: false,
         ai_selected_model: None,
         ai_features: AiFeatures::default(),
+        ai_pull_stall_timeout_secs: default_ai_pull_stall_timeout_secs(),
+        ai_pull_auto_retry: false,
+        ai_auto_pull_missing_model: true,
+        ai_check_model_updates: true,
+        ai_num_thread: None,
+        ai_gpu_offload: AiGpuOffload::default(),
+        ai_main_gpu: None,
+        ai_ollama_endpoints: default_ai_ollama_endpoints(),
+        ai_model_registry_prefix: None,
+        ai_model_registry_insecure: false,
+        ai_chunk_max_tokens: default_ai_chunk_max_tokens(),
+        ai_fallback_to_original: default_ai_fallback_to_original(),
+        ai_offline_fallback: false,
+        ai_enhancement_timeout_ms: default_ai_enhancement_timeout_ms(),
+        ai_degrade_after_failures: default_ai_degrade_after_failures(),
+        ai_latency_budget_ms: 0,
+        ai_two_pass: None,
+        ai_reenhance: AiReenhanceOverrides::default(),
+        ai_min_words_for_enhancement: default_ai_min_words_for_enhancement(),
+        ai_max_words_for_enhancement: default_ai_max_words_for_enhancement(),
+        ai_long_text_policy: AiLongTextPolicy::default(),
+        ai_warm_up_on_battery: true,
+        ai_preload_on_startup: false,
+        ai_preload_keep_alive_mins: default_ai_preload_keep_alive_mins(),
+        ai_idle_unload_minutes: 0,
+        ai_cache_enabled: true,
+        ai_cache_size: default_ai_cache_size(),
+        ai_history_enabled: false,
+        ai_withhold_original_from_events: false,
+        ai_usage_stats_enabled: false,
+        ai_context_enabled: false,
+        ai_context_utterances: default_ai_context_utterances(),
+        ai_context_idle_secs: default_ai_context_idle_secs(),
+        ai_queue_policy: AiQueuePolicy::default(),
+        ai_output_guard: AiOutputGuard::default(),
+        ai_generation_options: AiGenerationOptions::default(),
+        ai_custom_prompt_template: None,
+        ai_prompt_all_instructions: false,
+        ai_examples: Vec::new(),
+        ai_vocabulary: Vec::new(),
+        ai_replacement_rules: Vec::new(),
+        ai_snippets: Vec::new(),
+        ai_snippet_strictness: default_ai_snippet_strictness(),
+        ai_acronyms: default_ai_acronyms(),
+        ai_proper_nouns: Vec::new(),
+        ai_test_samples: Vec::new(),
+        ai_languages: default_ai_languages(),
+        ai_language_policy: AiLanguagePolicy::default(),
+        ai_translate_target: default_ai_translate_target(),
+        ai_summary_style: AiSummaryStyle::default(),
+        ai_summary_max_words: default_ai_summary_max_words(),
     }
 }
 
@@ -738,3 +1980,96 @@ pub fn get_recording_retention_period(app: &AppHandle) -> RecordingRetentionPeri
     let settings = get_settings(app);
     settings.recording_retention_period
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ai_features_without_instructions_still_load() {
+        let json = r#"{
+            "punctuation_and_capitalization": true,
+            "remove_filler_words": false,
+            "normalize_numbers": true,
+            "fix_spelling": true
+        }"#;
+
+        let features: AiFeatures = serde_json::from_str(json).unwrap();
+
+        assert!(!features.remove_filler_words);
+        assert_eq!(features.filler_words, default_filler_words());
+        assert!(!features.mask_profanity);
+        assert!(!features.convert_spoken_emoji);
+        assert_eq!(features.spoken_emoji, default_spoken_emoji());
+        assert!(!features.redact_sensitive);
+        assert_eq!(features.redaction, AiRedaction::default());
+        assert_eq!(features.redaction.placeholder, "[{category}]");
+        assert!(!features.normalize_dates_times);
+        assert_eq!(features.date_style, AiDateStyle::MonthDay);
+        assert!(!features.normalize_units);
+        assert_eq!(features.number_style_threshold, 0);
+        assert_eq!(features.unit_style, AiUnitStyle::Symbols);
+        assert!(!features.normalize_currency);
+        assert_eq!(features.currency_style, AiCurrencyStyle::SymbolBefore);
+        assert!(!features.format_phone_numbers);
+        assert_eq!(features.phone_style, AiPhoneStyle::Us);
+        assert_eq!(features.spelling_variant, AiSpellingVariant::Preserve);
+        assert!(!features.fix_grammar);
+        assert_eq!(features.contraction_style, AiContractionStyle::Preserve);
+        assert!(!features.remove_repetitions);
+        assert!(features.protect_spans);
+        assert!(!features.split_paragraphs);
+        assert!(!features.split_long_sentences);
+        assert_eq!(features.max_sentence_words, 30);
+        assert!(!features.format_lists);
+        assert_eq!(features.list_style, AiListStyle::Dash);
+        assert!(!features.smart_typography);
+        assert_eq!(features.translate_to, None);
+        assert_eq!(features.tone, None);
+        assert_eq!(features.instructions, AiFeatureInstructions::default());
+    }
+
+    #[test]
+    fn test_ai_feature_instructions_round_trip() {
+        let json = r#"{
+            "fix_spelling": true,
+            "instructions": { "fix_spelling": "Fix spelling mistakes only" }
+        }"#;
+
+        let features: AiFeatures = serde_json::from_str(json).unwrap();
+        assert!(features.punctuation_and_capitalization);
+        assert_eq!(
+            features.instructions.fix_spelling.as_deref(),
+            Some("Fix spelling mistakes only")
+        );
+        assert_eq!(features.instructions.normalize_numbers, None);
+
+        let reloaded: AiFeatures =
+            serde_json::from_str(&serde_json::to_string(&features).unwrap()).unwrap();
+        assert_eq!(reloaded.instructions, features.instructions);
+    }
+
+    #[test]
+    fn test_generation_options_fill_in_missing_modes() {
+        let json = r#"{
+            "correct": { "temperature": 0.0, "max_output_tokens": 1024, "repeat_penalty": 1.0 }
+        }"#;
+
+        let options: AiGenerationOptions = serde_json::from_str(json).unwrap();
+        assert_eq!(options.correct.temperature, 0.0);
+        assert_eq!(options.summarize, default_summarize_sampling());
+        assert_eq!(options.tone, default_tone_sampling());
+
+        // Settings saved before there were any load with the defaults
+        let settings: AppSettings = serde_json::from_value(serde_json::json!({
+            "bindings": {},
+            "push_to_talk": false,
+            "audio_feedback": false
+        }))
+        .unwrap();
+        assert_eq!(
+            settings.ai_generation_options,
+            AiGenerationOptions::default()
+        );
+    }
+}
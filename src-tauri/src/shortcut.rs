@@ -858,6 +858,12 @@ pub fn register_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<()
                             action.start(ah, &binding_id_for_closure, &shortcut_string);
                         }
                         return;
+                    } else if binding_id_for_closure == "reenhance_last" {
+                        // A one-off, whether or not recording is push-to-talk
+                        if event.state == ShortcutState::Pressed {
+                            action.start(ah, &binding_id_for_closure, &shortcut_string);
+                        }
+                        return;
                     } else if settings.push_to_talk {
                         if event.state == ShortcutState::Pressed {
                             action.start(ah, &binding_id_for_closure, &shortcut_string);
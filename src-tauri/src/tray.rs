@@ -74,6 +74,19 @@ pub fn change_tray_icon(app: &AppHandle, icon: TrayIconState) {
     update_tray_menu(app, &icon);
 }
 
+/// Note in the tray tooltip while AI enhancement is skipped because Ollama
+/// can't be reached
+pub fn set_ai_offline(app: &AppHandle, offline: bool) {
+    let tooltip = if offline {
+        "Handy (AI temporarily offline)"
+    } else {
+        "Handy"
+    };
+    if let Some(tray) = app.try_state::<TrayIcon>() {
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
 pub fn update_tray_menu(app: &AppHandle, state: &TrayIconState) {
     let settings = settings::get_settings(app);
 
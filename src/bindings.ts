@@ -646,33 +646,108 @@ async checkOllamaAvailable() : Promise<Result<boolean, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async listOllamaModels() : Promise<Result<string[], string>> {
+async listOllamaModels(endpoint: string | null) : Promise<Result<string[], string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("list_ollama_models") };
+    return { status: "ok", data: await TAURI_INVOKE("list_ollama_models", { endpoint }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async pullOllamaModel(model: string) : Promise<Result<null, string>> {
+async pullOllamaModel(model: string, endpoint: string | null) : Promise<Result<AiPullQueueItem, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("pull_ollama_model", { model }) };
+    return { status: "ok", data: await TAURI_INVOKE("pull_ollama_model", { model, endpoint }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deleteOllamaModel(model: string) : Promise<Result<null, string>> {
+async deleteOllamaModel(model: string, endpoint: string | null) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_ollama_model", { model }) };
+    return { status: "ok", data: await TAURI_INVOKE("delete_ollama_model", { model, endpoint }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async testAiEnhancement(text: string) : Promise<Result<string, string>> {
+/**
+ * Installed models not used within the last `days` days
+ */
+async getUnusedAiModels(days: number) : Promise<Result<AiUnusedModels, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_unused_ai_models", { days }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Delete the installed models not used within the last `days` days,
+ * never the selected one, and return them
+ */
+async deleteUnusedAiModels(days: number) : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_unused_ai_models", { days }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async testAiEnhancement(text: string, mode: EnhanceMode | null) : Promise<Result<AiEnhancementPreview, EnhancementError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_ai_enhancement", { text, mode }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The prompts enhancing `text` would send with the current settings, and
+ * about how many tokens they take, without asking the model
+ */
+async previewAiPrompt(text: string, mode: EnhanceMode | null) : Promise<Result<AiPromptPreview, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preview_ai_prompt", { text, mode }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Correct the same sample text with two models, one after the other, to
+ * compare their output and speed. A model that fails, e.g. for not being
+ * installed, reports why on its side without failing the other.
+ */
+async compareAiModels(text: string, modelA: string, modelB: string) : Promise<Result<AiModelComparison, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("test_ai_enhancement", { text }) };
+    return { status: "ok", data: await TAURI_INVOKE("compare_ai_models", { text, modelA, modelB }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Enhance the text pasted last again, with a heavier model or more
+ * features, returning the result and what it changed. `None` applies the
+ * overrides the `reenhance_last` shortcut uses. Fails when no enhancement
+ * has finished yet, or a newer dictation has started since.
+ */
+async reenhanceLast(overrides: AiReenhanceOverrides | null) : Promise<Result<AiReenhancement, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reenhance_last", { overrides }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Run the built-in samples for the enabled features and the user's own
+ * through the selected model, emitting `ai-test-suite-progress` before each.
+ * A sample that fails reports why in its result.
+ */
+async runAiEnhancementTestSuite() : Promise<Result<AiTestSuiteReport, EnhancementError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_ai_enhancement_test_suite") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -694,6 +769,18 @@ async changeAiModel(model: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Check again whether the selected model is installed, also reporting it
+ * through `ai-model-status`. `None` when no model is selected.
+ */
+async recheckAiModelStatus() : Promise<Result<AiModelStatusReport | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("recheck_ai_model_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async changeAiFeatures(features: AiFeatures) : Promise<Result<null, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("change_ai_features", { features }) };
@@ -701,40 +788,2134 @@ async changeAiFeatures(features: AiFeatures) : Promise<Result<null, string>> {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+/**
+ * Set how punctuation and capitalization cases the text, leaving the other
+ * features as they are
+ */
+async changeAiCapitalizationStyle(style: AiCapitalizationStyle) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_capitalization_style", { style }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAiFillerWords() : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ai_filler_words") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
 }
+},
+/**
+ * Replace the words and phrases removed as fillers. Returns the list as
+ * saved, trimmed and without duplicates.
+ */
+async setAiFillerWords(fillers: string[]) : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_ai_filler_words", { fillers }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
 }
-
-/** user-defined events **/
-
-
-
-/** user-defined constants **/
-
-
-
-/** user-defined types **/
-
-export type AiFeatures = { punctuation_and_capitalization?: boolean; remove_filler_words?: boolean; normalize_numbers?: boolean; fix_spelling?: boolean }
-export type AiModelInfo = { id: string; size_mb: number; speed: string; quality: string; notes: string }
-export type AppSettings = { bindings: Partial<{ [key in string]: ShortcutBinding }>; push_to_talk: boolean; audio_feedback: boolean; audio_feedback_volume?: number; sound_theme?: SoundTheme; start_hidden?: boolean; autostart_enabled?: boolean; update_checks_enabled?: boolean; selected_model?: string; always_on_microphone?: boolean; selected_microphone?: string | null; clamshell_microphone?: string | null; selected_output_device?: string | null; translate_to_english?: boolean; selected_language?: string; overlay_position?: OverlayPosition; debug_mode?: boolean; log_level?: LogLevel; custom_words?: string[]; model_unload_timeout?: ModelUnloadTimeout; word_correction_threshold?: number; history_limit?: string; recording_retention_period?: RecordingRetentionPeriod; paste_method?: PasteMethod; clipboard_handling?: ClipboardHandling; post_process_enabled?: boolean; post_process_provider_id?: string; post_process_providers?: PostProcessProvider[]; post_process_api_keys?: Partial<{ [key in string]: string }>; post_process_models?: Partial<{ [key in string]: string }>; post_process_prompts?: LLMPrompt[]; post_process_selected_prompt_id?: string | null; mute_while_recording?: boolean; append_trailing_space?: boolean; experiments_enabled?: boolean; developer_mode?: boolean; ai_enhancement_enabled?: boolean; ai_selected_model?: string | null; ai_features?: AiFeatures }
-export type AudioDevice = { index: string; name: string; is_default: boolean }
-export type BindingResponse = { success: boolean; binding: ShortcutBinding | null; error: string | null }
-export type ClipboardHandling = "dont_modify" | "copy_to_clipboard"
-export type CustomSounds = { start: boolean; stop: boolean }
-export type EngineType = "Whisper" | "Parakeet"
-export type HistoryEntry = { id: string; file_name: string; timestamp: string; saved: boolean; title: string; transcription_text: string; post_processed_text: string | null; post_process_prompt: string | null }
-export type LLMPrompt = { id: string; name: string; prompt: string }
-export type LogLevel = "trace" | "debug" | "info" | "warn" | "error"
-export type ModelInfo = { id: string; name: string; description: string; filename: string; url: string | null; size_mb: string; is_downloaded: boolean; is_downloading: boolean; partial_size: string; is_directory: boolean; engine_type: EngineType; accuracy_score: number; speed_score: number }
-export type ModelLoadStatus = { is_loaded: boolean; current_model: string | null }
-export type ModelUnloadTimeout = "never" | "immediately" | "min_2" | "min_5" | "min_10" | "min_15" | "hour_1" | "sec_5"
+},
+async listAiSpokenCommands() : Promise<Result<AiSpokenCommand[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ai_spoken_commands") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a spoken punctuation command, replacing any said the same way in
+ * another casing. Returns the updated commands.
+ */
+async addAiSpokenCommand(command: AiSpokenCommand) : Promise<Result<AiSpokenCommand[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_ai_spoken_command", { command }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove the spoken punctuation command said as `phrase`. Returns the
+ * updated commands.
+ */
+async removeAiSpokenCommand(phrase: string) : Promise<Result<AiSpokenCommand[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_ai_spoken_command", { phrase }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listAiSpokenEmoji() : Promise<Result<AiSpokenEmoji[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ai_spoken_emoji") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add an emoji to say by name, replacing any said the same way in another
+ * casing. Returns the updated emoji.
+ */
+async addAiSpokenEmoji(entry: AiSpokenEmoji) : Promise<Result<AiSpokenEmoji[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_ai_spoken_emoji", { entry }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove the emoji said as `phrase`. Returns the updated emoji.
+ */
+async removeAiSpokenEmoji(phrase: string) : Promise<Result<AiSpokenEmoji[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_ai_spoken_emoji", { phrase }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Switch translation into the last used target language on or off. Returns
+ * the language now translated into, if any.
+ */
+async toggleAiTranslation() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("toggle_ai_translation") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiPullStallTimeout(seconds: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_pull_stall_timeout", { seconds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiPullAutoRetry(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_pull_auto_retry", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiAutoPullMissingModel(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_auto_pull_missing_model", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiCheckModelUpdates(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_check_model_updates", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getOllamaVersion() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ollama_version") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiNumThread(threads: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_num_thread", { threads }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listOllamaRunningModels() : Promise<Result<OllamaRunningModel[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ollama_running_models") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiOutputGuard(guard: AiOutputGuard) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_output_guard", { guard }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiGenerationOptions(options: AiGenerationOptions) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_generation_options", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The built-in generation options, for resetting the settings to them
+ */
+async getDefaultAiGenerationOptions() : Promise<Result<AiGenerationOptions, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_default_ai_generation_options") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiCustomPrompt(template: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_custom_prompt", { template }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Go back to the built-in prompt
+ */
+async resetAiPromptTemplate() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_ai_prompt_template") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiPromptAllInstructions(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_prompt_all_instructions", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiExamples(examples: AiExample[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_examples", { examples }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listAiVocabulary() : Promise<Result<AiVocabularyEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ai_vocabulary") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a vocabulary entry, replacing any entry with the same written form.
+ * Returns the updated vocabulary.
+ */
+async addAiVocabularyEntry(entry: AiVocabularyEntry) : Promise<Result<AiVocabularyEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_ai_vocabulary_entry", { entry }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove the vocabulary entry for `written_form`. Returns the updated vocabulary.
+ */
+async removeAiVocabularyEntry(writtenForm: string) : Promise<Result<AiVocabularyEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_ai_vocabulary_entry", { writtenForm }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listAiReplacementRules() : Promise<Result<AiReplacementRule[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ai_replacement_rules") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replace the replacement rules, which apply in the order given, after
+ * checking each can be applied
+ */
+async changeAiReplacementRules(rules: AiReplacementRule[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_replacement_rules", { rules }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * What the saved replacement rules make of `text` before and after the
+ * model, without running it
+ */
+async testAiReplacementRules(text: string) : Promise<Result<AiReplacementRulesPreview, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_ai_replacement_rules", { text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listAiSnippets() : Promise<Result<AiSnippet[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ai_snippets") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a snippet, replacing any with the same trigger. Returns the updated
+ * snippets.
+ */
+async addAiSnippet(snippet: AiSnippet) : Promise<Result<AiSnippet[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_ai_snippet", { snippet }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove the snippet inserted by `trigger`. Returns the updated snippets.
+ */
+async removeAiSnippet(trigger: string) : Promise<Result<AiSnippet[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_ai_snippet", { trigger }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiSnippetStrictness(strictness: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_snippet_strictness", { strictness }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Set the languages dictated in, as ISO 639-3 codes like "eng" or "deu"
+ */
+async changeAiLanguages(languages: string[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_languages", { languages }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiLanguagePolicy(policy: AiLanguagePolicy) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_language_policy", { policy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiSummaryStyle(style: AiSummaryStyle) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_summary_style", { style }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiSummaryMaxWords(maxWords: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_summary_max_words", { maxWords }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listAiAcronyms() : Promise<Result<AiAcronym[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ai_acronyms") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add an acronym, replacing any written the same way in another casing.
+ * Returns the updated acronyms.
+ */
+async addAiAcronym(writtenForm: string) : Promise<Result<AiAcronym[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_ai_acronym", { writtenForm }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove the acronym `written_form`. Returns the updated acronyms.
+ */
+async removeAiAcronym(writtenForm: string) : Promise<Result<AiAcronym[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_ai_acronym", { writtenForm }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Turn casing of the acronym `written_form` on or off, as needed for the
+ * ones that are also common words. Returns the updated acronyms.
+ */
+async setAiAcronymEnabled(writtenForm: string, enabled: boolean) : Promise<Result<AiAcronym[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_ai_acronym_enabled", { writtenForm, enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listAiProperNouns() : Promise<Result<AiProperNoun[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ai_proper_nouns") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a name, replacing the entry for the same name. Returns the updated
+ * names.
+ */
+async addAiProperNoun(entry: AiProperNoun) : Promise<Result<AiProperNoun[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_ai_proper_noun", { entry }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove the entry for `name`. Returns the updated names.
+ */
+async removeAiProperNoun(name: string) : Promise<Result<AiProperNoun[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_ai_proper_noun", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listAiTestSamples() : Promise<Result<AiTestSample[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_ai_test_samples") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a test sample, replacing the one with the same name. Returns the
+ * updated samples.
+ */
+async addAiTestSample(sample: AiTestSample) : Promise<Result<AiTestSample[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_ai_test_sample", { sample }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove the test sample named `name`. Returns the updated samples.
+ */
+async removeAiTestSample(name: string) : Promise<Result<AiTestSample[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_ai_test_sample", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiCacheEnabled(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_cache_enabled", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiCacheSize(size: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_cache_size", { size }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Forget every cached enhancement, so the next dictations go to the model
+ */
+async clearAiEnhancementCache() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_ai_enhancement_cache") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAiEnhancementCacheStats() : Promise<Result<AiEnhancementCacheStats, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ai_enhancement_cache_stats") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiHistoryEnabled(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_history_enabled", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiWithholdOriginalFromEvents(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_withhold_original_from_events", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiUsageStatsEnabled(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_usage_stats_enabled", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiQueuePolicy(policy: AiQueuePolicy) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_queue_policy", { policy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiContextEnabled(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_context_enabled", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiContextUtterances(count: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_context_utterances", { count }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiContextIdleSecs(seconds: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_context_idle_secs", { seconds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Forget the preceding text, so the next utterance is enhanced on its own
+ */
+async clearAiContext() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_ai_context") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Up to `limit` enhancement history entries after skipping `offset`, newest
+ * first
+ */
+async getEnhancementHistory(offset: number, limit: number) : Promise<Result<AiEnhancementHistoryEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_enhancement_history", { offset, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteEnhancementHistoryEntry(id: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_enhancement_history_entry", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Rate the enhancement finished last with a thumbs up or down and an
+ * optional note, to tell which models and features do badly. Ratings are
+ * only kept on this device.
+ */
+async rateLastEnhancement(rating: AiRating, note: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rate_last_enhancement", { rating, note }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Rate the enhancement saved as history entry `history_id`, as
+ * [`rate_last_enhancement`] does
+ */
+async rateEnhancement(historyId: number, rating: AiRating, note: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rate_enhancement", { historyId, rating, note }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Transcript as dictated for the text pasted last, if any
+ */
+async getLastOriginalTranscript() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_last_original_transcript") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Take back the text pasted last and return its original transcript, to
+ * re-paste or copy. Undoing again reaches the text pasted before it.
+ */
+async undoLastEnhancement() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("undo_last_enhancement") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop the enhancement being generated and paste its original transcript
+ * instead. Returns whether one was running.
+ */
+async abortAiEnhancement() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("abort_ai_enhancement") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * How often enhancement ran, fell back or was rejected, and how fast it was,
+ * since the metrics were last reset
+ */
+async getAiEnhancementMetrics() : Promise<Result<AiEnhancementMetrics, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ai_enhancement_metrics") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async resetAiEnhancementMetrics() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_ai_enhancement_metrics") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Daily counts of what came of enhancement over `range`, kept while usage
+ * stats are enabled
+ */
+async getAiUsageStats(range: AiUsageRange) : Promise<Result<AiUsageStats, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ai_usage_stats", { range }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearAiUsageStats() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_ai_usage_stats") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Measure how fast `model` corrects a few sample transcripts on this machine.
+ * The samples take turns with dictated transcripts rather than holding them up.
+ */
+async benchmarkAiModel(model: string) : Promise<Result<AiBenchmarkReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("benchmark_ai_model", { model }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Benchmark every installed model in turn, reporting each through
+ * `ai-benchmark-progress`
+ */
+async benchmarkAllInstalledModels() : Promise<Result<AiBenchmarkReport[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("benchmark_all_installed_models") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Benchmarks measured with this version of Handy on this machine
+ */
+async getAiBenchmarks() : Promise<Result<AiBenchmarkReport[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ai_benchmarks") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiGpuOffload(offload: AiGpuOffload) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_gpu_offload", { offload }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiMainGpu(gpu: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_main_gpu", { gpu }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiOllamaEndpoints(endpoints: AiOllamaEndpoint[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_ollama_endpoints", { endpoints }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAiPullQueue() : Promise<Result<AiPullQueueItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ai_pull_queue") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelQueuedPull(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_queued_pull", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getActiveModelPulls() : Promise<Result<AiModelPullProgress[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_active_model_pulls") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getResumableModelPulls() : Promise<Result<AiPullRecord[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_resumable_model_pulls") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async resumeModelPull(modelId: string) : Promise<Result<AiPullQueueItem | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resume_model_pull", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async dismissModelPull(modelId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("dismiss_model_pull", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Which installed models have a newer version in their registry
+ */
+async checkAiModelUpdates() : Promise<Result<AiModelUpdateCheck[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_ai_model_updates") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Download the latest version of an installed model
+ */
+async updateAiModel(model: string) : Promise<Result<AiPullQueueItem, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_ai_model", { model }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiModelRegistryPrefix(prefix: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_model_registry_prefix", { prefix }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiModelRegistryInsecure(insecure: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_model_registry_insecure", { insecure }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Import a local GGUF file as an Ollama model and return the model name
+ */
+async importGgufModel(path: string, name: string, endpoint: string | null) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_gguf_model", { path, name, endpoint }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Report where Ollama stores models and how much disk they use
+ */
+async getOllamaStorageInfo(endpoint: string | null) : Promise<Result<OllamaStorageInfo, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ollama_storage_info", { endpoint }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiChunkMaxTokens(tokens: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_chunk_max_tokens", { tokens }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiFallbackToOriginal(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_fallback_to_original", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiOfflineFallback(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_offline_fallback", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiEnhancementTimeoutMs(timeoutMs: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_enhancement_timeout_ms", { timeoutMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiDegradeAfterFailures(failures: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_degrade_after_failures", { failures }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiLatencyBudgetMs(budgetMs: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_latency_budget_ms", { budgetMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * What the `reenhance_last` shortcut changes about the last enhancement
+ */
+async changeAiReenhance(overrides: AiReenhanceOverrides) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_reenhance", { overrides }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Enhance with a fast model first and refine with a better one in the
+ * background, or turn the two-pass mode off with `None`
+ */
+async changeAiTwoPass(twoPass: AiTwoPass | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_two_pass", { twoPass }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Go back to the selected model after the session moved to a smaller one
+ */
+async resetAiModelDegradation() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_ai_model_degradation") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiMinWordsForEnhancement(words: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_min_words_for_enhancement", { words }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiMaxWordsForEnhancement(words: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_max_words_for_enhancement", { words }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiLongTextPolicy(policy: AiLongTextPolicy) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_long_text_policy", { policy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiWarmUpOnBattery(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_warm_up_on_battery", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiPreloadOnStartup(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_preload_on_startup", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiPreloadKeepAlive(minutes: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_preload_keep_alive", { minutes }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAiIdleUnloadMinutes(minutes: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ai_idle_unload_minutes", { minutes }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+export type AiAcronym = { written_form: string; 
+/**
+ * Off for acronyms that are also common words, like "IT" and "US",
+ * until the user turns them on
+ */
+enabled?: boolean }
+export type AiBenchmarkProgress = { model: string; 
+/**
+ * 1-based position of the model among those being benchmarked
+ */
+index: number; total: number }
+/**
+ * How one benchmark transcript went
+ */
+export type AiBenchmarkPromptResult = { 
+/**
+ * Time Ollama spent loading the model first, close to none when it
+ * already was
+ */
+load_ms: number; output_tokens: number; tokens_per_second: number; 
+/**
+ * From sending the prompt to having all of the output
+ */
+latency_ms: number }
+/**
+ * How fast a model corrects transcripts on this machine
+ */
+export type AiBenchmarkReport = { model: string; app_version: string; 
+/**
+ * The machine it was measured on, as given by [`hardware_fingerprint`]
+ */
+hardware: string; 
+/**
+ * Unix timestamp (seconds) of when it was measured
+ */
+measured_at: string; prompts: AiBenchmarkPromptResult[]; 
+/**
+ * Loading time before the first transcript, when the model is loaded if
+ * it wasn't already
+ */
+load_ms: number; 
+/**
+ * Generation speed over the output for every transcript
+ */
+tokens_per_second: number; average_latency_ms: number }
+export type AiCapitalizationStyle = 
+/**
+ * "Quarterly results for the New York office"
+ */
+"sentence" | 
+/**
+ * "Quarterly Results for the New York Office", for headings
+ */
+"title" | 
+/**
+ * "quarterly results for the new york office"
+ */
+"lowercase" | 
+/**
+ * As transcribed
+ */
+"preserve"
+/**
+ * Sent as `ai-circuit-changed` when enhancements start or stop skipping
+ * straight to the original transcript because Ollama can't be reached
+ */
+export type AiCircuitChanged = { 
+/**
+ * Whether enhancement is skipped until Ollama is back
+ */
+open: boolean; 
+/**
+ * Enhancements in a row that found no Ollama host before it opened
+ */
+failures: number }
+export type AiContractionStyle = 
+/**
+ * "don't" → "do not"
+ */
+"expand" | 
+/**
+ * "do not" → "don't"
+ */
+"contract" | 
+/**
+ * Whichever was dictated
+ */
+"preserve"
+export type AiCurrencyStyle = 
+/**
+ * "$49.99" and "€200"
+ */
+"symbol_before" | 
+/**
+ * "49,99 $" and "200 €", as written in much of Europe
+ */
+"symbol_after" | 
+/**
+ * "49.99 USD" and "200 EUR"
+ */
+"iso_code"
+export type AiDateStyle = 
+/**
+ * "March 3, 2025" and "2:30 PM"
+ */
+"month_day" | 
+/**
+ * "3 March 2025" and "14:30"
+ */
+"day_month" | 
+/**
+ * "2025-03-03" and "14:30"
+ */
+"iso"
+export type AiEndpointChanged = { base_url: string | null; previous: string | null }
+export type AiEnhancementCacheStats = { entries: number; hits: number; misses: number }
+export type AiEnhancementComplete = { request_id: string; 
+/**
+ * The enhanced text, as pasted
+ */
+text: string; 
+/**
+ * The text as dictated, unless it is withheld while the history is off
+ */
+original: string | null; 
+/**
+ * Length of the text as dictated, in characters, sent even when the
+ * text itself is withheld
+ */
+original_chars: number; outcome: EnhancementOutcome; stats: AiEnhancementStats; 
+/**
+ * The text was translated as well as corrected
+ */
+translated: boolean; mode: EnhanceMode; 
+/**
+ * Tone the text was rewritten in, if any
+ */
+tone: AiTone | null; 
+/**
+ * What enhancement changed, word by word, unless the texts were too
+ * long or too different to diff
+ */
+diff: DiffOp[] | null; 
+/**
+ * Words at the end of `text` left as dictated, as the transcript was
+ * longer than the maximum for enhancement
+ */
+unenhanced_words: number }
+export type AiEnhancementFallback = { request_id: string; reason: AiFallbackReason; 
+/**
+ * The transcript pasted instead
+ */
+text: string }
+export type AiEnhancementHistoryEntry = { id: number; 
+/**
+ * Unix timestamp (seconds) of when the enhancement finished
+ */
+timestamp: string; model: string; features: AiFeatures; 
+/**
+ * Mode the text was enhanced in, like "correct" or "summarize"
+ */
+mode: string; original_text: string; 
+/**
+ * Text the enhancement returned, which is the original unless the
+ * outcome is `enhanced`
+ */
+enhanced_text: string; duration_ms: number; outcome: EnhancementOutcome; 
+/**
+ * What enhancement changed, word by word, unless the texts were too
+ * long or too different to diff
+ */
+diff?: DiffOp[] | null; rating?: AiEnhancementRating | null; 
+/**
+ * The entry whose enhanced text was enhanced again into this one, on
+ * demand. That entry is left as it was.
+ */
+reenhanced_from?: number | null }
+export type AiEnhancementMetrics = { 
+/**
+ * Unix timestamp (seconds) of when counting started or was last reset
+ */
+since?: string; 
+/**
+ * Transcripts handed to enhancement, including skipped and cached ones
+ */
+runs?: number; enhanced?: number; 
+/**
+ * Too short, or in a language enhancement is skipped for
+ */
+skipped?: number; cache_hits?: number; cache_misses?: number; 
+/**
+ * Enhancements that failed with falling back to the original turned off
+ */
+errors?: number; fallbacks?: AiFallbackCounts; 
+/**
+ * Which check threw out the model's output, for `rejected_output`
+ * fallbacks and errors
+ */
+rejections?: AiRejectionCounts; 
+/**
+ * Outputs that came back empty or rejected and were generated again
+ */
+retries?: number; 
+/**
+ * Outputs cut off at the output token limit, whose part of the
+ * transcript kept its original text
+ */
+truncated?: number; 
+/**
+ * Second passes of the two-pass mode
+ */
+refinements?: AiRefinementCounts; 
+/**
+ * Time taken by the runs that went to the model
+ */
+latency?: AiLatencyBucket[]; average_latency_ms?: number; 
+/**
+ * Generation speed Ollama reported, over everything it generated
+ */
+tokens_per_second?: number; total_latency_ms?: number; generated_tokens?: number; generation_secs?: number; 
+/**
+ * Ratings given to enhancements right after they were pasted
+ */
+satisfaction?: AiSatisfaction; 
+/**
+ * Ratings by the model that made the enhancement
+ */
+satisfaction_by_model?: Partial<{ [key in string]: AiSatisfaction }>; 
+/**
+ * Ratings by each feature that was enabled for the enhancement, like
+ * "fix_spelling"
+ */
+satisfaction_by_feature?: Partial<{ [key in string]: AiSatisfaction }> }
+export type AiEnhancementPartial = { request_id: string; text_so_far: string }
+export type AiEnhancementPasted = { original_text: string; enhanced_text: string }
+export type AiEnhancementPreview = { original: string; text: string; diff: DiffOp[] | null; outcome: EnhancementOutcome; 
+/**
+ * How long the model took, in milliseconds
+ */
+duration_ms: number }
+export type AiEnhancementRating = { rating: AiRating; 
+/**
+ * What was wrong or right with it, in the user's words
+ */
+note: string | null }
+export type AiEnhancementProgress = { request_id: string; chunk: number; total_chunks: number }
+export type AiEnhancementQueued = { id: number; 
+/**
+ * Transcripts ahead of it, including the one being enhanced
+ */
+ahead: number }
+/**
+ * Sent as `ai-enhancement-refined` when the quality model of the two-pass
+ * mode corrected a pasted transcript differently from the fast one
+ */
+export type AiEnhancementRefined = { 
+/**
+ * The enhancement refined, as announced by `ai-enhancement-started`
+ */
+request_id: string; model: string; 
+/**
+ * The fast model's text, as pasted
+ */
+pasted: string; text: string; 
+/**
+ * What the refinement changed in the pasted text, word by word
+ */
+diff: DiffOp[] | null; duration_ms: number }
+/**
+ * Sent for a transcript passed through without asking the model
+ */
+export type AiEnhancementSkipped = { request_id: string; reason: AiSkipReason; 
+/**
+ * The transcript pasted as it was
+ */
+text: string }
+/**
+ * Sent as soon as a transcript is handed over for enhancement. Exactly one of
+ * `ai-enhancement-complete`, `ai-enhancement-fallback` and
+ * `ai-enhancement-skipped` follows with the same `request_id`.
+ */
+export type AiEnhancementStarted = { request_id: string; word_count: number; 
+/**
+ * The selected model, before any host's preferred model replaces it
+ */
+model: string }
+export type AiEnhancementStats = { model: string; duration_ms: number; streamed: boolean; 
+/**
+ * Language detected in the transcript, for debugging the prompt
+ */
+language: DetectedLanguage | null; 
+/**
+ * The text was answered from the cache without running the model
+ */
+cached: boolean; 
+/**
+ * The model `ai_latency_budget_ms` picked, when it is set
+ */
+latency_choice: AiLatencyChoice | null }
+export type AiEnhancementSuperseded = { id: number; superseded_by: number }
+export type AiExample = { input: string; output: string }
+export type AiFallbackCounts = { timeout?: number; ollama_unavailable?: number; model_missing?: number; failed?: number; rejected_output?: number; aborted?: number }
+export type AiFallbackReason = "timeout" | "ollama_unavailable" | "model_missing" | "failed" | 
+/**
+ * The model's output could not be trusted as a correction of the transcript
+ */
+"rejected_output" | 
+/**
+ * Stopped by the user, or by a new recording starting
+ */
+"aborted"
+export type AiFeatureInstructions = { punctuation_and_capitalization?: string | null; remove_filler_words?: string | null; normalize_numbers?: string | null; fix_spelling?: string | null }
+export type AiFeatures = { punctuation_and_capitalization?: boolean; capitalization_style?: AiCapitalizationStyle; remove_filler_words?: boolean; 
+/**
+ * Words and phrases removed as fillers, matched in any casing
+ */
+filler_words?: string[]; 
+/**
+ * Write spoken punctuation and layout commands ("comma", "new line") as
+ * what they stand for, by rule and whether or not the model runs
+ */
+spoken_punctuation?: boolean; spoken_commands?: AiSpokenCommand[]; normalize_numbers?: boolean; 
+/**
+ * Thousand and decimal separators of normalized numbers
+ */
+number_locale?: AiNumberLocale; 
+/**
+ * Numbers below this are written as words and the rest as digits,
+ * ordinals included, or all of them as digits when 0
+ */
+number_style_threshold?: number; 
+/**
+ * Write spoken dates and times as dates and times ("march third" →
+ * "March 3")
+ */
+normalize_dates_times?: boolean; date_style?: AiDateStyle; 
+/**
+ * Write spoken measurements with digits and units ("five millimeters" →
+ * "5 mm")
+ */
+normalize_units?: boolean; unit_style?: AiUnitStyle; 
+/**
+ * Write spoken money amounts with digits and a currency sign ("two
+ * hundred euros" → "€200")
+ */
+normalize_currency?: boolean; currency_style?: AiCurrencyStyle; 
+/**
+ * Write dictated phone numbers in the pattern of `phone_style`, only
+ * where the text says they are phone numbers
+ */
+format_phone_numbers?: boolean; phone_style?: AiPhoneStyle; fix_spelling?: boolean; 
+/**
+ * Spelling the spelling fixes write, or `Preserve` to keep each word
+ * in the variant it was dictated in
+ */
+spelling_variant?: AiSpellingVariant; 
+/**
+ * Fix grammar mistakes like "he don't" with the fewest edits possible,
+ * which some find too invasive to have with the spelling fixes
+ */
+fix_grammar?: boolean; 
+/**
+ * Whether contractions like "don't" are written out, used, or kept
+ * as they were dictated
+ */
+contraction_style?: AiContractionStyle; 
+/**
+ * Drop words and phrases said twice by accident ("the the", "I I think")
+ */
+remove_repetitions?: boolean; protect_spans?: boolean; 
+/**
+ * Break long dictations into paragraphs where the topic shifts
+ */
+split_paragraphs?: boolean; 
+/**
+ * Let the model break run-on sentences at natural clause boundaries,
+ * and split them before connectors like "and then" when it doesn't run
+ */
+split_long_sentences?: boolean; 
+/**
+ * Sentences longer than this many words are split by rule
+ */
+max_sentence_words?: number; 
+/**
+ * Lay out spoken enumerations ("first ..., second ...") as a list
+ */
+format_lists?: boolean; list_style?: AiListStyle; 
+/**
+ * Typeset quotes, dashes and ellipses after enhancement (“”, —, …)
+ */
+smart_typography?: boolean; 
+/**
+ * Mask swear words in the pasted text ("f***"), by rule and whether or
+ * not the model ran
+ */
+mask_profanity?: boolean; profanity_mask?: AiProfanityMask; 
+/**
+ * Words masked while profanity is masked, with their endings
+ */
+profane_words?: string[]; 
+/**
+ * Write emoji said by name ("thumbs up emoji") or as an emoticon on its
+ * own ("smiley face") as the emoji, by rule after enhancement
+ */
+convert_spoken_emoji?: boolean; 
+/**
+ * Emoji written while spoken emoji are converted
+ */
+spoken_emoji?: AiSpokenEmoji[]; 
+/**
+ * Replace email addresses, phone numbers and card numbers in the pasted
+ * text with placeholders like "[email]", by rule after enhancement
+ */
+redact_sensitive?: boolean; redaction?: AiRedaction; 
+/**
+ * ISO 639-3 code of the language to translate the transcript into
+ */
+translate_to?: string | null; 
+/**
+ * Tone to rewrite the text in, which lets the model rephrase it
+ */
+tone?: AiTone | null; instructions?: AiFeatureInstructions }
+export type AiGenerationOptions = { correct?: AiSamplingOptions; summarize?: AiSamplingOptions; 
+/**
+ * Used when rewriting in a tone
+ */
+tone?: AiSamplingOptions }
+export type AiGpuOffload = "auto" | "cpu_only" | { layers: number }
+export type AiLanguagePolicy = 
+/**
+ * Enhance it, telling the model to keep it in that language
+ */
+"preserve" | 
+/**
+ * Paste it without enhancement
+ */
+"skip_unconfigured"
+export type AiLatencyBucket = { 
+/**
+ * Slowest run counted here, in milliseconds, or `null` for the last
+ * bucket
+ */
+up_to_ms: number | null; count: number }
+/**
+ * The model a latency budget picked for a transcript, sent along with
+ * `ai-enhancement-complete`
+ */
+export type AiLatencyChoice = { model: string; 
+/**
+ * How long the model was expected to take, from its benchmark
+ */
+estimated_ms: number; budget_ms: number }
+export type AiListStyle = 
+/**
+ * "- item"
+ */
+"dash" | 
+/**
+ * "1. item"
+ */
+"numbered"
+export type AiLongTextPolicy = 
+/**
+ * Paste it as dictated
+ */
+"skip" | 
+/**
+ * Enhance all of it, in chunks
+ */
+"chunked" | 
+/**
+ * Enhance the words up to the maximum and paste the rest as dictated
+ */
+"first_words"
+export type AiModelAutoPull = { pull_id: string; model_id: string }
+/**
+ * The same sample text corrected by two models
+ */
+export type AiModelComparison = { a: AiModelComparisonSide; b: AiModelComparisonSide }
+/**
+ * One model's output in a comparison, or why there is none
+ */
+export type AiModelComparisonSide = { model: string; text: string | null; 
+/**
+ * What changed from the sample text
+ */
+diff: DiffOp[] | null; latency_ms: number; error: EnhancementError | null }
+/**
+ * Sent as `ai-model-degraded` when the selected model kept failing and
+ * dictations go to a smaller one for the rest of the session
+ */
+export type AiModelDegraded = { 
+/**
+ * The selected model, which stays selected in the settings
+ */
+model: string; 
+/**
+ * The smaller model used in its place
+ */
+fallback_model: string; 
+/**
+ * Enhancements that failed in a row before switching
+ */
+failures: number; 
+/**
+ * Why the last of them failed
+ */
+reason: AiFallbackReason }
+export type AiModelInfo = { id: string; /**
+ * Name used with Ollama, which includes the registry prefix when one is configured
+ */
+registry_id: string; size_mb: number; speed: string; quality: string; notes: string }
+export type AiModelPullComplete = { pull_id: string; model_id: string }
+export type AiModelPullError = { pull_id: string; model_id: string; error: string }
+export type AiModelPullProgress = { pull_id: string; model_id: string; status: string; completed: string | null; total: string | null; percentage: number }
+/**
+ * Whether the selected model can be used
+ */
+export type AiModelStatus = "installed" | "missing" | 
+/**
+ * None of the Ollama hosts answered, so it couldn't be checked
+ */
+"ollama_unreachable"
+/**
+ * Sent as `ai-model-status` whenever the selected model is checked, at
+ * startup and when another one is selected
+ */
+export type AiModelStatusReport = { model: string; status: AiModelStatus }
+/**
+ * Sent as `ai-model-unloaded` once a model going unused was unloaded
+ */
+export type AiModelUnloaded = { model: string; idle_minutes: number }
+export type AiModelUpdateCheck = { model: string; update_available: boolean; 
+/**
+ * Why the registry couldn't tell, in which case no update is reported
+ */
+error: string | null }
+export type AiModelUpdated = { model: string; 
+/**
+ * The installed copy changed. `false` when it was already the latest.
+ */
+updated: boolean }
+export type AiNumberLocale = 
+/**
+ * As written in the language of the text, or the one it's translated to
+ */
+"auto" | 
+/**
+ * "1,234.5" and "10%"
+ */
+"en_us" | 
+/**
+ * "1.234,5" and "10 %"
+ */
+"de_de" | 
+/**
+ * "1 234,5" and "10 %", with narrow no-break spaces between thousands
+ */
+"fr_fr"
+export type AiOllamaEndpoint = { base_url: string; /**
+ * Model to use on this host instead of the globally selected one
+ */
+preferred_model?: string | null }
+export type AiOutputGuard = { min_ratio?: number; max_ratio?: number; 
+/**
+ * Lower bound used instead of `min_ratio` when filler words are being
+ * removed, since that legitimately shortens the text
+ */
+filler_min_ratio?: number }
+export type AiPhoneStyle = 
+/**
+ * "(555) 123-4567", "+1 (555) 123-4567" with the country code
+ */
+"us" | 
+/**
+ * "+44 20 7946 0958"
+ */
+"uk"
+export type AiProfanityMask = 
+/**
+ * "f***", keeping the first letter
+ */
+"first_letter" | 
+/**
+ * "****"
+ */
+"asterisks"
+export type AiPromptComponent = 
+/**
+ * The text dictated just before
+ */
+"context" | 
+/**
+ * The user's own examples
+ */
+"user_examples" | 
+/**
+ * The examples of each feature's correction
+ */
+"built_in_examples" | 
+/**
+ * The names listed for the model to spell as given
+ */
+"known_names"
+export type AiPromptPreview = { 
+/**
+ * One prompt per chunk the transcript is enhanced in
+ */
+prompts: string[]; 
+/**
+ * Rough token count of all the prompts together
+ */
+estimated_tokens: number; 
+/**
+ * What was left out of the prompts to fit the model's context, in the
+ * order it was dropped
+ */
+dropped: AiPromptComponent[]; 
+/**
+ * Language detected in the transcript
+ */
+language: DetectedLanguage | null }
+export type AiProperNoun = { 
+/**
+ * The name exactly as it is written, accents included
+ */
+name: string; 
+/**
+ * What the transcript tends to contain instead, beyond near-misses
+ */
+misrecognitions?: string[] }
+export type AiPullQueueItem = { id: string; model_id: string; endpoint: string | null; state: AiPullState }
+export type AiPullRecord = { model_id: string; endpoint: string | null; /**
+ * Unix timestamp (seconds) of when the download was first requested
+ */
+started_at: string; completed: string | null; total: string | null }
+export type AiPullState = "queued" | "downloading"
+export type AiQueuePolicy = 
+/**
+ * Enhance and paste every one of them, in the order they were dictated
+ */
+"queue_all" | 
+/**
+ * Drop them, so only the newest is enhanced and pasted
+ */
+"latest_wins"
+export type AiRating = "up" | "down"
+export type AiRejectionCounts = { 
+/**
+ * Output far longer or shorter than the transcript
+ */
+length?: number; 
+/**
+ * Output that only repeated the prompt
+ */
+echo?: number; 
+/**
+ * Output that lost or duplicated protected spans
+ */
+placeholders?: number; 
+/**
+ * Output that was empty even after a retry
+ */
+empty?: number; 
+/**
+ * Output that kept too few of the transcript's words
+ */
+overlap?: number }
+/**
+ * What is redacted while sensitive information is, and what replaces it
+ */
+export type AiRedaction = { emails?: boolean; phone_numbers?: boolean; 
+/**
+ * Numbers of 13 to 19 digits passing the Luhn check
+ */
+card_numbers?: boolean; 
+/**
+ * Written in place of each redaction, with "{category}" standing for
+ * "email", "phone" or "card"
+ */
+placeholder?: string }
+/**
+ * How many of each kind of information a text had redacted, sent with
+ * `ai-redactions-applied` so the placeholders come as no surprise
+ */
+export type AiRedactionCounts = { emails: number; phone_numbers: number; card_numbers: number }
+/**
+ * What a second pass over the last enhancement changes, run on demand by
+ * the `reenhance_last` shortcut
+ */
+export type AiReenhanceOverrides = { 
+/**
+ * Model to run it with, or `None` for the selected one
+ */
+model?: string | null; 
+/**
+ * Fix grammar too, whether or not the enhancement did
+ */
+fix_grammar?: boolean }
+/**
+ * A second pass over the last enhancement, run on demand by
+ * `reenhance_last` and sent as `ai-enhancement-reenhanced` when its
+ * shortcut ran it
+ */
+export type AiReenhancement = { model: string; 
+/**
+ * The last enhancement's text, as pasted
+ */
+previous: string; text: string; 
+/**
+ * What the second pass changed in the pasted text, word by word
+ */
+diff: DiffOp[] | null; duration_ms: number }
+/**
+ * How the second passes of the two-pass mode went
+ */
+export type AiRefinementCounts = { 
+/**
+ * Refinements the quality model finished before the next dictation
+ */
+finished?: number; 
+/**
+ * Those that said something other than the pasted text, and were
+ * offered in its place
+ */
+differed?: number }
+/**
+ * A find/replace rule applied by rule, before the model sees the text or
+ * after it is done with it
+ */
+export type AiReplacementRule = { 
+/**
+ * Regular expression matched against the text
+ */
+pattern: string; 
+/**
+ * What each match becomes, with `$1` or `${name}` for its groups
+ */
+replacement?: string; stage?: AiRuleStage }
+/**
+ * What the rules alone make of a text, without the model
+ */
+export type AiReplacementRulesPreview = { 
+/**
+ * The text as the model would get it
+ */
+before_ai: string; 
+/**
+ * The text as pasted, had the model left it as it was
+ */
+after_ai: string }
+/**
+ * When a replacement rule is applied
+ */
+export type AiRuleStage = 
+/**
+ * To the transcript, so the model never spends tokens on what it
+ * replaces
+ */
+"before_ai" | 
+/**
+ * To the enhanced text
+ */
+"after_ai"
+export type AiSamplingOptions = { temperature: number; 
+/**
+ * Most tokens generated in one request, however long the input
+ */
+max_output_tokens: number; 
+/**
+ * Penalty on repeating recent tokens, 1.0 for none
+ */
+repeat_penalty: number }
+export type AiSatisfaction = { up?: number; down?: number }
+export type AiSkipReason = 
+/**
+ * Fewer words than the configured minimum
+ */
+"too_short" | 
+/**
+ * Only numbers and punctuation, with nothing for the model to correct
+ */
+"no_letters" | 
+/**
+ * More words than the configured maximum
+ */
+"too_long" | 
+/**
+ * In a language enhancement isn't configured for
+ */
+"language" | 
+/**
+ * Only a snippet's trigger, which is inserted without the model
+ */
+"snippet" | 
+/**
+ * No installed model is expected to finish within the latency budget
+ */
+"latency_budget"
+export type AiSpokenCommand = { 
+/**
+ * What is said, matched in any casing
+ */
+phrase: string; symbol: string; kind: AiSpokenCommandKind }
+export type AiSpokenCommandKind = 
+/**
+ * To the word before, as a comma or a closing quote
+ */
+"closing" | 
+/**
+ * To the word after, as an opening quote
+ */
+"opening" | 
+/**
+ * In place of the space around it, as a line break
+ */
+"break"
+export type AiSpokenEmoji = { 
+/**
+ * What is said before "emoji", matched in any casing
+ */
+phrase: string; emoji: string; 
+/**
+ * Also written without "emoji" after it when said on its own, like
+ * "smiley face" at the end of a sentence
+ */
+emoticon?: boolean }
+/**
+ * Text inserted when a phrase like "insert my signature" is said
+ */
+export type AiSnippet = { 
+/**
+ * What is said to insert it
+ */
+trigger: string; 
+/**
+ * What is inserted, line breaks and all
+ */
+text: string; 
+/**
+ * Whether it is inserted where the trigger is said in a longer
+ * dictation, rather than only when the trigger is all that was said
+ */
+within_text?: boolean }
+export type AiSpellingVariant = 
+/**
+ * "color", "organize", "center"
+ */
+"us" | 
+/**
+ * "colour", "organise", "centre"
+ */
+"uk" | 
+/**
+ * British spelling with Australian exceptions like "program"
+ */
+"au" | 
+/**
+ * Whichever each word was dictated in
+ */
+"preserve"
+export type AiSummaryStyle = "bullets" | "paragraph"
+/**
+ * A transcript of the user's own run through enhancement by the test suite,
+ * alongside the built-in ones
+ */
+export type AiTestSample = { name: string; 
+/**
+ * The transcript, raw the way dictation leaves it
+ */
+text: string; 
+/**
+ * Text the enhanced output has to contain, exactly as written
+ */
+expected?: string[]; 
+/**
+ * Words or phrases the enhanced output must no longer contain, in any
+ * casing
+ */
+unexpected?: string[] }
+/**
+ * How one sample came through enhancement
+ */
+export type AiTestSampleResult = { name: string; input: string; output: string | null; 
+/**
+ * What changed from the input, when the diff is cheap enough to show
+ */
+diff: DiffOp[] | null; latency_ms: number; 
+/**
+ * The output contains everything expected and nothing unexpected
+ */
+passed: boolean; 
+/**
+ * Why it didn't pass, e.g. `missing "25%"`
+ */
+failures: string[]; error: EnhancementError | null }
+/**
+ * Sent before each sample is run by `run_ai_enhancement_test_suite`
+ */
+export type AiTestSuiteProgress = { name: string; 
+/**
+ * 1-based position of the sample in the suite
+ */
+index: number; total: number }
+/**
+ * Result of running every sample through the selected model
+ */
+export type AiTestSuiteReport = { model: string; results: AiTestSampleResult[]; passed: number }
+export type AiTone = "casual" | "professional" | "neutral"
+/**
+ * Models of the two-pass mode, where the fast one's correction is pasted
+ * at once and the quality one refines it in the background
+ */
+export type AiTwoPass = { 
+/**
+ * Model whose correction is pasted, in place of the selected one
+ */
+fast_model: string; 
+/**
+ * Model correcting the transcript again once the fast one's text is
+ * pasted, offered through `ai-enhancement-refined` when it differs
+ */
+quality_model: string }
+export type AiUnitStyle = 
+/**
+ * "5 mm", with a space before the unit as in SI
+ */
+"symbols" | 
+/**
+ * "5 millimeters"
+ */
+"words"
+export type AiUnusedModel = { model: string; 
+/**
+ * Size reported by Ollama, counting blobs shared with other models
+ */
+size_bytes: string; 
+/**
+ * Unix timestamp (seconds) of its last use, or of when Handy first
+ * found it installed if it wasn't used since
+ */
+last_used: string }
+export type AiUnusedModelDeleted = { model: string; error: string | null }
+export type AiUnusedModels = { models: AiUnusedModel[]; 
+/**
+ * Bytes freed by deleting all of them, leaving out blobs the other
+ * models still use. `None` for an Ollama on another machine, whose
+ * blobs can't be seen.
+ */
+reclaimable_bytes: string | null }
+export type AiUsageCounts = { enhanced?: number; skipped?: number; fallbacks?: AiFallbackCounts; 
+/**
+ * Enhancements that failed with falling back to the original turned off
+ */
+errors?: number; cache_hits?: number; 
+/**
+ * Runs that went to the model, which the latency is averaged over
+ */
+timed_runs?: number; total_latency_ms?: number; average_latency_ms?: number; 
+/**
+ * Runs in the first minutes after the app started, and how many of
+ * them fell back or failed, to tell whether a cold Ollama is to blame
+ */
+runs_after_start?: number; failures_after_start?: number }
+export type AiUsageDay = { 
+/**
+ * Local date, like "2025-03-14"
+ */
+date: string; counts: AiUsageCounts }
+export type AiUsageRange = "week" | "month" | 
+/**
+ * Every day still kept
+ */
+"all"
+export type AiUsageStats = { 
+/**
+ * Every day of the range oldest first, days without dictations included
+ */
+days: AiUsageDay[]; 
+/**
+ * The days of the range added up
+ */
+total: AiUsageCounts }
+export type AiVocabularyEntry = { 
+/**
+ * What the transcript tends to contain instead
+ */
+spoken_forms?: string[]; written_form: string }
+export type AppSettings = { bindings: Partial<{ [key in string]: ShortcutBinding }>; push_to_talk: boolean; audio_feedback: boolean; audio_feedback_volume?: number; sound_theme?: SoundTheme; start_hidden?: boolean; autostart_enabled?: boolean; update_checks_enabled?: boolean; selected_model?: string; always_on_microphone?: boolean; selected_microphone?: string | null; clamshell_microphone?: string | null; selected_output_device?: string | null; translate_to_english?: boolean; selected_language?: string; overlay_position?: OverlayPosition; debug_mode?: boolean; log_level?: LogLevel; custom_words?: string[]; model_unload_timeout?: ModelUnloadTimeout; word_correction_threshold?: number; history_limit?: string; recording_retention_period?: RecordingRetentionPeriod; paste_method?: PasteMethod; clipboard_handling?: ClipboardHandling; post_process_enabled?: boolean; post_process_provider_id?: string; post_process_providers?: PostProcessProvider[]; post_process_api_keys?: Partial<{ [key in string]: string }>; post_process_models?: Partial<{ [key in string]: string }>; post_process_prompts?: LLMPrompt[]; post_process_selected_prompt_id?: string | null; mute_while_recording?: boolean; append_trailing_space?: boolean; experiments_enabled?: boolean; developer_mode?: boolean; ai_enhancement_enabled?: boolean; ai_selected_model?: string | null; ai_features?: AiFeatures; ai_pull_stall_timeout_secs?: string; ai_pull_auto_retry?: boolean; ai_auto_pull_missing_model?: boolean; ai_check_model_updates?: boolean; ai_num_thread?: number | null; ai_gpu_offload?: AiGpuOffload; ai_main_gpu?: number | null; ai_ollama_endpoints?: AiOllamaEndpoint[]; ai_model_registry_prefix?: string | null; ai_model_registry_insecure?: boolean; ai_chunk_max_tokens?: number; ai_fallback_to_original?: boolean; ai_offline_fallback?: boolean; ai_enhancement_timeout_ms?: number; ai_degrade_after_failures?: number; ai_latency_budget_ms?: number; ai_two_pass?: AiTwoPass | null; ai_reenhance?: AiReenhanceOverrides; ai_min_words_for_enhancement?: number; ai_max_words_for_enhancement?: number; ai_long_text_policy?: AiLongTextPolicy; ai_warm_up_on_battery?: boolean; ai_preload_on_startup?: boolean; ai_preload_keep_alive_mins?: number; ai_idle_unload_minutes?: number; ai_cache_enabled?: boolean; ai_cache_size?: number; ai_history_enabled?: boolean; ai_withhold_original_from_events?: boolean; ai_usage_stats_enabled?: boolean; ai_context_enabled?: boolean; ai_context_utterances?: number; ai_context_idle_secs?: number; ai_queue_policy?: AiQueuePolicy; ai_output_guard?: AiOutputGuard; ai_generation_options?: AiGenerationOptions; ai_custom_prompt_template?: string | null; ai_prompt_all_instructions?: boolean; ai_examples?: AiExample[]; ai_vocabulary?: AiVocabularyEntry[]; ai_replacement_rules?: AiReplacementRule[]; ai_snippets?: AiSnippet[]; ai_snippet_strictness?: number; ai_acronyms?: AiAcronym[]; ai_proper_nouns?: AiProperNoun[]; ai_test_samples?: AiTestSample[]; ai_languages?: string[]; ai_language_policy?: AiLanguagePolicy; ai_translate_target?: string; ai_summary_style?: AiSummaryStyle; ai_summary_max_words?: number }
+export type AudioDevice = { index: string; name: string; is_default: boolean }
+export type BindingResponse = { success: boolean; binding: ShortcutBinding | null; error: string | null }
+export type ClipboardHandling = "dont_modify" | "copy_to_clipboard"
+export type CustomSounds = { start: boolean; stop: boolean }
+export type DetectedLanguage = { 
+/**
+ * ISO 639-3 code, like "eng" or "deu"
+ */
+code: string; 
+/**
+ * English name of the language, like "German"
+ */
+name: string; confidence: number }
+export type DiffKind = "equal" | 
+/**
+ * Only in the enhanced text
+ */
+"insert" | 
+/**
+ * Only in the original text
+ */
+"delete"
+export type DiffOp = { kind: DiffKind; text: string }
+export type EngineType = "Whisper" | "Parakeet"
+export type EnhanceMode = 
+/**
+ * Fix transcription errors, keeping the wording
+ */
+"correct" | 
+/**
+ * Replace the transcript with a summary of it
+ */
+{ summarize: { style: AiSummaryStyle; max_words: number } } | 
+/**
+ * Fix transcription errors and lay the text out as an email, with
+ * greeting, paragraphs and closing
+ */
+"email_format" | 
+/**
+ * Write the headings, lists, code blocks and emphasis dictated into a
+ * note as Markdown, then fix transcription errors around them
+ */
+"markdown" | 
+/**
+ * Write the symbols, numbers and identifiers dictated into a code
+ * editor by rule, then fix only misheard words around them
+ */
+"code"
+/**
+ * Why an enhancement produced no text, returned when falling back to the
+ * original is off and by the enhancement commands
+ */
+export type EnhancementError = 
+/**
+ * AI enhancement is turned off in the settings
+ */
+{ kind: "disabled" } | { kind: "no_model_selected" } | { kind: "ollama_unavailable" } | 
+/**
+ * The model isn't installed on the Ollama host
+ */
+{ kind: "model_missing"; model: string } | { kind: "timeout" } | 
+/**
+ * The model's output could not be trusted as a correction of the text
+ */
+{ kind: "output_rejected"; check: OutputCheck } | 
+/**
+ * Stopped by the user, or by a new recording starting
+ */
+{ kind: "cancelled" } | { kind: "failed"; message: string }
+export type EnhancementOutcome = { kind: "enhanced" } | 
+/**
+ * Not worth sending to the model
+ */
+{ kind: "skipped"; reason: AiSkipReason } | 
+/**
+ * Enhancement failed and the original text was kept
+ */
+{ kind: "fallback"; reason: AiFallbackReason }
+export type HistoryEntry = { id: string; file_name: string; timestamp: string; saved: boolean; title: string; transcription_text: string; post_processed_text: string | null; post_process_prompt: string | null; 
+/**
+ * AI enhancement mode that produced the pasted text, like "summarize"
+ */
+ai_enhance_mode: string | null; 
+/**
+ * Tone AI enhancement rewrote the text in, like "professional"
+ */
+ai_tone: string | null }
+export type LLMPrompt = { id: string; name: string; prompt: string }
+export type LogLevel = "trace" | "debug" | "info" | "warn" | "error"
+export type ModelInfo = { id: string; name: string; description: string; filename: string; url: string | null; size_mb: string; is_downloaded: boolean; is_downloading: boolean; partial_size: string; is_directory: boolean; engine_type: EngineType; accuracy_score: number; speed_score: number }
+export type ModelLoadStatus = { is_loaded: boolean; current_model: string | null }
+export type ModelUnloadTimeout = "never" | "immediately" | "min_2" | "min_5" | "min_10" | "min_15" | "hour_1" | "sec_5"
+export type OllamaRunningModel = { name: string; size: string; size_vram?: string }
+export type OllamaStorageInfo = { models_dir: string | null; /**
+ * Combined size of all installed models as reported by Ollama
+ */
+used_bytes: string; /**
+ * Free space on the volume holding `models_dir`
+ */
+free_bytes: string | null }
+/**
+ * The check a model output failed
+ */
+export type OutputCheck = 
+/**
+ * Far longer or shorter than the transcript
+ */
+"length" | 
+/**
+ * Nothing but the prompt repeated back
+ */
+"echo" | 
+/**
+ * Protected spans lost or duplicated
+ */
+"placeholders" | 
+/**
+ * No text at all, even after a retry
+ */
+"empty" | 
+/**
+ * Too few of the transcript's words kept, as when the model answered
+ * or carried out the dictated text instead of correcting it
+ */
+"overlap"
 export type OverlayPosition = "none" | "top" | "bottom"
 export type PasteMethod = "ctrl_v" | "direct" | "none" | "shift_insert"
 export type PostProcessProvider = { id: string; label: string; base_url: string; allow_base_url_edit?: boolean; models_endpoint?: string | null }
 export type RecordingRetentionPeriod = "never" | "preserve_limit" | "days_3" | "weeks_2" | "months_3"
 export type ShortcutBinding = { id: string; name: string; description: string; default_binding: string; current_binding: string }
 export type SoundTheme = "marimba" | "pop" | "custom"
-export type SystemInfo = { total_ram_gb: number; available_ram_gb: number; cpu_cores: string; os: string }
+export type SystemInfo = { total_ram_gb: number; available_ram_gb: number; cpu_cores: string; physical_cores: string | null; os: string }
 
 /** tauri-specta globals **/
 
@@ -1,12 +1,33 @@
 import React, { useState, useEffect, useRef } from "react";
 import { listen } from "@tauri-apps/api/event";
-import { commands } from "@/bindings";
+import {
+  commands,
+  type AiCircuitChanged,
+  type AiEnhancementComplete,
+  type AiEnhancementFallback,
+  type AiEnhancementPasted,
+  type AiEnhancementRefined,
+  type AiReenhancement,
+  type AiEnhancementSkipped,
+  type AiEnhancementStarted,
+  type AiFallbackReason,
+  type AiModelAutoPull,
+  type AiModelDegraded,
+  type AiModelPullComplete,
+  type AiModelPullError,
+  type AiModelStatusReport,
+  type AiModelUpdateCheck,
+  type AiModelUpdated,
+  type AiRedactionCounts,
+} from "@/bindings";
 import { useSettings } from "../../hooks/useSettings";
 import { Sparkles, Loader2 } from "lucide-react";
+import { toast } from "sonner";
 
 type AiStatus = "ready" | "loading" | "pulling" | "error" | "off" | "no_ollama";
 
 interface AiPullProgress {
+  pull_id: string;
   model: string;
   status: string;
   completed: number;
@@ -14,12 +35,23 @@ interface AiPullProgress {
   percentage: number;
 }
 
+const fallbackDescriptions: Record<AiFallbackReason, string> = {
+  timeout: "The model took too long, so the original text was pasted",
+  ollama_unavailable: "Ollama isn't running, so the original text was pasted",
+  model_missing: "The selected model isn't installed, so the original text was pasted",
+  failed: "Enhancement failed, so the original text was pasted",
+  rejected_output:
+    "The model didn't return a usable correction, so the original text was pasted",
+  aborted: "Enhancement was stopped, so the original text was pasted",
+};
+
 const AiStatusIndicator: React.FC = () => {
   const [status, setStatus] = useState<AiStatus>("off");
   const [currentModel, setCurrentModel] = useState<string>("");
   const [downloadedModels, setDownloadedModels] = useState<string[]>([]);
   const [ollamaAvailable, setOllamaAvailable] = useState(false);
   const [pullProgress, setPullProgress] = useState<AiPullProgress | null>(null);
+  const [enhancing, setEnhancing] = useState<AiEnhancementStarted | null>(null);
   const [showTooltip, setShowTooltip] = useState(false);
   
   const { getSetting, updateSetting } = useSettings();
@@ -27,6 +59,8 @@ const AiStatusIndicator: React.FC = () => {
   const selectedModel = getSetting("ai_selected_model") ?? "";
   
   const tooltipRef = useRef<HTMLDivElement>(null);
+  // Download of the selected model started because it had gone missing
+  const autoPullRef = useRef<string | null>(null);
 
   useEffect(() => {
     checkStatus();
@@ -37,21 +71,329 @@ const AiStatusIndicator: React.FC = () => {
       (event) => {
         setPullProgress(event.payload);
         setStatus("pulling");
+        if (event.payload.pull_id === autoPullRef.current) {
+          toast.loading(
+            `Downloading your AI model (${Math.round(event.payload.percentage)}%)`,
+            {
+              id: event.payload.pull_id,
+              description: "Dictations will be enhanced once it's ready",
+            }
+          );
+        }
+      }
+    );
+
+    // Listen for the selected model being downloaded again automatically
+    const autoPullUnlisten = listen<AiModelAutoPull>(
+      "ai-model-auto-pull",
+      (event) => {
+        autoPullRef.current = event.payload.pull_id;
+        toast.loading("Downloading your AI model", {
+          id: event.payload.pull_id,
+          description: `${event.payload.model_id} is missing from Ollama. Dictations will be enhanced once it's ready`,
+        });
       }
     );
 
     // Listen for AI model pull complete
-    const pullCompleteUnlisten = listen<string>(
+    const pullCompleteUnlisten = listen<AiModelPullComplete>(
       "ai-model-pull-complete",
-      () => {
+      (event) => {
+        setPullProgress(null);
+        checkStatus();
+        if (event.payload.pull_id === autoPullRef.current) {
+          autoPullRef.current = null;
+          toast.success("Your AI model is ready", {
+            id: event.payload.pull_id,
+            description: "Dictations are enhanced again",
+          });
+        }
+      }
+    );
+
+    const pullErrorUnlisten = listen<AiModelPullError>(
+      "ai-model-pull-error",
+      (event) => {
         setPullProgress(null);
         checkStatus();
+        if (event.payload.pull_id === autoPullRef.current) {
+          autoPullRef.current = null;
+          toast.error("Couldn't download your AI model", {
+            id: event.payload.pull_id,
+            description: event.payload.error,
+          });
+        }
+      }
+    );
+
+    // Listen for the selected model having been removed from Ollama
+    const modelMissingUnlisten = listen<string>("ai-model-missing", (event) => {
+      const model = event.payload;
+      setStatus("error");
+      toast.error(`Model missing: ${model} is no longer installed in Ollama`, {
+        action: {
+          label: "Download now",
+          onClick: () => {
+            commands.pullOllamaModel(model, null);
+          },
+        },
+      });
+    });
+
+    // Listen for the selected model being checked against Ollama
+    const modelStatusUnlisten = listen<AiModelStatusReport>(
+      "ai-model-status",
+      (event) => {
+        if (autoPullRef.current) return;
+        switch (event.payload.status) {
+          case "installed":
+            setCurrentModel(event.payload.model);
+            setOllamaAvailable(true);
+            setStatus(aiEnabled ? "ready" : "off");
+            break;
+          case "missing":
+            setStatus("error");
+            break;
+          case "ollama_unreachable":
+            setOllamaAvailable(false);
+            setStatus("no_ollama");
+            break;
+        }
+      }
+    );
+
+    // Say when dictations moved to a smaller model, and offer to go back
+    const degradedUnlisten = listen<AiModelDegraded>(
+      "ai-model-degraded",
+      (event) => {
+        const { model, fallback_model, failures } = event.payload;
+        setCurrentModel(fallback_model);
+        toast.warning(`Switched to ${fallback_model}`, {
+          id: "ai-model-degraded",
+          description: `${model} failed ${failures} times in a row, so ${fallback_model} is used until it works again`,
+          duration: 10000,
+          action: {
+            label: `Use ${model}`,
+            onClick: () => {
+              commands.resetAiModelDegradation();
+            },
+          },
+        });
+      }
+    );
+
+    const restoredUnlisten = listen<string>("ai-model-restored", (event) => {
+      setCurrentModel(event.payload);
+      toast.dismiss("ai-model-degraded");
+    });
+
+    // Offer the updates found by the startup check
+    const updatesUnlisten = listen<AiModelUpdateCheck[]>(
+      "ai-model-updates-available",
+      (event) => {
+        const models = event.payload.map((check) => check.model);
+        toast.info(
+          models.length === 1
+            ? `An update is available for ${models[0]}`
+            : `Updates are available for ${models.length} AI models`,
+          {
+            id: "ai-model-updates",
+            description: models.length === 1 ? undefined : models.join(", "),
+            duration: 10000,
+            action: {
+              label: "Update",
+              onClick: () => {
+                models.forEach((model) => commands.updateAiModel(model));
+              },
+            },
+          }
+        );
+      }
+    );
+
+    const updatedUnlisten = listen<AiModelUpdated>(
+      "ai-model-updated",
+      (event) => {
+        const { model, updated } = event.payload;
+        if (updated) {
+          toast.success(`${model} is updated`);
+        } else {
+          toast(`${model} was already the latest version`);
+        }
+      }
+    );
+
+    // Dictations are pasted as they are while Ollama can't be reached
+    const circuitUnlisten = listen<AiCircuitChanged>(
+      "ai-circuit-changed",
+      (event) => {
+        if (event.payload.open) {
+          setOllamaAvailable(false);
+          setStatus("no_ollama");
+          toast.warning("AI temporarily offline", {
+            id: "ai-circuit",
+            description: `Ollama couldn't be reached ${event.payload.failures} times in a row, so dictations are pasted as they are until it's back`,
+            duration: 10000,
+          });
+        } else {
+          checkStatus();
+          toast.success("AI enhancement is back", { id: "ai-circuit" });
+        }
+      }
+    );
+
+    // Show the model thinking until the enhancement ends, one way or another
+    const startedUnlisten = listen<AiEnhancementStarted>(
+      "ai-enhancement-started",
+      (event) => {
+        setEnhancing(event.payload);
+      }
+    );
+    const finished = (requestId: string) =>
+      setEnhancing((current) =>
+        current?.request_id === requestId ? null : current
+      );
+
+    const skippedUnlisten = listen<AiEnhancementSkipped>(
+      "ai-enhancement-skipped",
+      (event) => {
+        finished(event.payload.request_id);
+      }
+    );
+
+    // Listen for enhancements that kept the original transcript
+    const fallbackUnlisten = listen<AiEnhancementFallback>(
+      "ai-enhancement-fallback",
+      (event) => {
+        finished(event.payload.request_id);
+        // The download toast already says why
+        if (autoPullRef.current && event.payload.reason === "model_missing") {
+          return;
+        }
+        toast("AI enhancement skipped", {
+          description: fallbackDescriptions[event.payload.reason],
+          duration: 3000,
+        });
+      }
+    );
+
+    // Say when the end of a long dictation was pasted as it was
+    const completeUnlisten = listen<AiEnhancementComplete>(
+      "ai-enhancement-complete",
+      (event) => {
+        finished(event.payload.request_id);
+        const unenhanced = event.payload.unenhanced_words;
+        if (unenhanced > 0) {
+          toast("Long dictation partly enhanced", {
+            description: `The last ${unenhanced} words were pasted as dictated`,
+            duration: 3000,
+          });
+        }
+      }
+    );
+
+    // Offer the transcript as dictated in place of the enhanced text
+    const pastedUnlisten = listen<AiEnhancementPasted>(
+      "ai-enhancement-pasted",
+      (event) => {
+        const original = event.payload.original_text;
+        toast("AI enhanced text pasted", {
+          duration: 5000,
+          action: {
+            label: "Use original",
+            onClick: () => {
+              navigator.clipboard.writeText(original);
+              toast.success("Original transcript copied to clipboard");
+            },
+          },
+        });
+      }
+    );
+
+    // Offer the better model's text once it refined the pasted one
+    const refinedUnlisten = listen<AiEnhancementRefined>(
+      "ai-enhancement-refined",
+      (event) => {
+        const { model, text } = event.payload;
+        toast(`${model} suggests a better version`, {
+          description: text,
+          duration: 8000,
+          action: {
+            label: "Copy",
+            onClick: () => {
+              navigator.clipboard.writeText(text.trim());
+              toast.success("Refined text copied to clipboard");
+            },
+          },
+        });
+      }
+    );
+
+    // The shortcut copied a second pass over the text pasted last
+    const reenhancedUnlisten = listen<AiReenhancement>(
+      "ai-enhancement-reenhanced",
+      (event) => {
+        const { model, diff } = event.payload;
+        const changed = diff?.filter((op) => op.kind !== "equal").length ?? 0;
+        toast.success(`Enhanced again with ${model}`, {
+          description:
+            changed === 0
+              ? "Nothing needed changing; copied to clipboard"
+              : "Copied to clipboard",
+          duration: 4000,
+        });
+      }
+    );
+    const reenhanceFailedUnlisten = listen<string>(
+      "ai-enhancement-reenhance-failed",
+      (event) => {
+        toast.error("Couldn't enhance the last text again", {
+          description: event.payload,
+        });
+      }
+    );
+
+    // Say what was redacted, so the placeholders come as no surprise
+    const redactionsUnlisten = listen<AiRedactionCounts>(
+      "ai-redactions-applied",
+      (event) => {
+        const counts = event.payload;
+        const kinds: [number, string][] = [
+          [counts.emails, "email"],
+          [counts.phone_numbers, "phone number"],
+          [counts.card_numbers, "card number"],
+        ];
+        const redacted = kinds
+          .filter(([count]) => count > 0)
+          .map(([count, name]) => `${count} ${name}${count === 1 ? "" : "s"}`);
+        toast("Sensitive information redacted", {
+          description: `Replaced ${redacted.join(", ")} with placeholders`,
+          duration: 4000,
+        });
       }
     );
 
     return () => {
       pullProgressUnlisten.then((fn) => fn());
+      startedUnlisten.then((fn) => fn());
+      skippedUnlisten.then((fn) => fn());
+      fallbackUnlisten.then((fn) => fn());
+      completeUnlisten.then((fn) => fn());
+      pastedUnlisten.then((fn) => fn());
+      refinedUnlisten.then((fn) => fn());
+      reenhancedUnlisten.then((fn) => fn());
+      reenhanceFailedUnlisten.then((fn) => fn());
+      redactionsUnlisten.then((fn) => fn());
       pullCompleteUnlisten.then((fn) => fn());
+      pullErrorUnlisten.then((fn) => fn());
+      autoPullUnlisten.then((fn) => fn());
+      modelMissingUnlisten.then((fn) => fn());
+      modelStatusUnlisten.then((fn) => fn());
+      degradedUnlisten.then((fn) => fn());
+      restoredUnlisten.then((fn) => fn());
+      updatesUnlisten.then((fn) => fn());
+      updatedUnlisten.then((fn) => fn());
+      circuitUnlisten.then((fn) => fn());
     };
   }, [aiEnabled, selectedModel]);
 
@@ -73,7 +415,7 @@ const AiStatusIndicator: React.FC = () => {
       }
 
       // Get list of downloaded models
-      const models = await commands.listOllamaModels();
+      const models = await commands.listOllamaModels(null);
       if (models.status === "ok") {
         setDownloadedModels(models.data);
         
@@ -98,6 +440,10 @@ const AiStatusIndicator: React.FC = () => {
   };
 
   const getStatusColor = (): string => {
+    if (enhancing && status === "ready") {
+      return "bg-logo-primary animate-pulse";
+    }
+
     switch (status) {
       case "ready":
         return "bg-logo-primary";
@@ -119,6 +465,9 @@ const AiStatusIndicator: React.FC = () => {
     if (status === "pulling" && pullProgress) {
       return `${Math.round(pullProgress.percentage)}%`;
     }
+    if (enhancing && status === "ready") {
+      return "Thinking...";
+    }
     
     switch (status) {
       case "ready":
@@ -141,6 +490,9 @@ const AiStatusIndicator: React.FC = () => {
     if (status === "pulling" && pullProgress) {
       return `Downloading ${pullProgress.model}: ${Math.round(pullProgress.percentage)}%`;
     }
+    if (enhancing && status === "ready") {
+      return `Enhancing ${enhancing.word_count} words with ${enhancing.model}...`;
+    }
     
     switch (status) {
       case "ready":
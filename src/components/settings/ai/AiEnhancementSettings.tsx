@@ -5,17 +5,37 @@ import { ToggleSwitch } from "../../ui/ToggleSwitch";
 import { SettingContainer } from "../../ui/SettingContainer";
 import { Button } from "../../ui/Button";
 import { Textarea } from "../../ui/Textarea";
+import { Slider } from "../../ui/Slider";
+import { Input } from "../../ui/Input";
 import { useSettings } from "../../../hooks/useSettings";
-import { commands } from "@/bindings";
+import { describeEnhancementError } from "../../../lib/utils/enhancementError";
+import { commands, type AiCapitalizationStyle, type AiContractionStyle, type AiCurrencyStyle, type AiDateStyle, type AiListStyle, type AiLongTextPolicy, type AiNumberLocale, type AiPhoneStyle, type AiProfanityMask, type AiPromptPreview, type AiRedaction, type AiSpellingVariant, type AiTone, type AiUnitStyle, type DiffOp } from "@/bindings";
 import { toast } from "sonner";
 import AiModelSelector from "./AiModelSelector";
+import { AiGpuOffloadSetting } from "./AiGpuOffload";
+import { AiGenerationOptionsSetting } from "./AiGenerationOptions";
+import { AiTwoPassSetting } from "./AiTwoPass";
+import { AiReenhanceSetting } from "./AiReenhance";
+import { AiOllamaEndpoints } from "./AiOllamaEndpoints";
+import { AiModelRegistry } from "./AiModelRegistry";
+import { AiModelUpdates } from "./AiModelUpdates";
+import { AiUnusedModels } from "./AiUnusedModels";
+import { AiFillerWords } from "./AiFillerWords";
+import { AiGgufImport } from "./AiGgufImport";
+import { AiModelComparisonPanel } from "./AiModelComparison";
+import { AiTestSuitePanel } from "./AiTestSuite";
+import { AiUsageStatsPanel } from "./AiUsageStats";
+import { HandyShortcut } from "../HandyShortcut";
+import { Dropdown } from "../../ui/Dropdown";
 import { Copy, Loader2 } from "lucide-react";
 
 export const AiEnhancementSettings: React.FC = () => {
   const [ollamaAvailable, setOllamaAvailable] = React.useState(false);
   const [testText, setTestText] = React.useState("");
   const [testResult, setTestResult] = React.useState("");
+  const [testDiff, setTestDiff] = React.useState<DiffOp[] | null>(null);
   const [isTesting, setIsTesting] = React.useState(false);
+  const [promptPreview, setPromptPreview] = React.useState<AiPromptPreview | null>(null);
   const [systemInfo, setSystemInfo] = React.useState<{ total_ram_gb: number } | null>(null);
   
   const { getSetting, updateSetting } = useSettings();
@@ -24,9 +44,108 @@ export const AiEnhancementSettings: React.FC = () => {
     punctuation_and_capitalization: true,
     remove_filler_words: true,
     normalize_numbers: true,
+    number_style_threshold: 0,
+    normalize_dates_times: false,
+    normalize_units: false,
+    normalize_currency: false,
+    format_phone_numbers: false,
     fix_spelling: true,
+    fix_grammar: false,
+    remove_repetitions: false,
+    protect_spans: true,
+    split_paragraphs: false,
+    split_long_sentences: false,
+    max_sentence_words: 30,
+    format_lists: false,
+    smart_typography: false,
+    spoken_punctuation: false,
+    convert_spoken_emoji: false,
+    redact_sensitive: false,
   };
 
+  const longTextPolicyOptions = [
+    { value: "skip", label: "Paste as dictated" },
+    { value: "chunked", label: "Enhance all of it" },
+    { value: "first_words", label: "Enhance up to the limit" },
+  ];
+
+  const capitalizationStyleOptions = [
+    { value: "sentence", label: "Sentence case" },
+    { value: "title", label: "Title Case" },
+    { value: "lowercase", label: "lowercase" },
+    { value: "preserve", label: "As Transcribed" },
+  ];
+
+  const numberLocaleOptions = [
+    { value: "auto", label: "Language of the Text" },
+    { value: "en_us", label: "US (1,234.5)" },
+    { value: "de_de", label: "German (1.234,5)" },
+    { value: "fr_fr", label: "French (1 234,5)" },
+  ];
+
+  const dateStyleOptions = [
+    { value: "month_day", label: "March 3, 2:30 PM" },
+    { value: "day_month", label: "3 March, 14:30" },
+    { value: "iso", label: "2025-03-03, 14:30" },
+  ];
+
+  const unitStyleOptions = [
+    { value: "symbols", label: "Symbols (5 mm)" },
+    { value: "words", label: "Words (5 millimeters)" },
+  ];
+
+  const currencyStyleOptions = [
+    { value: "symbol_before", label: "Sign Before ($49.99)" },
+    { value: "symbol_after", label: "Sign After (49,99 $)" },
+    { value: "iso_code", label: "Currency Code (49.99 USD)" },
+  ];
+
+  const phoneStyleOptions = [
+    { value: "us", label: "US ((555) 123-4567)" },
+    { value: "uk", label: "UK (+44 20 7946 0958)" },
+  ];
+
+  const spellingVariantOptions = [
+    { value: "preserve", label: "As Dictated" },
+    { value: "us", label: "American (color)" },
+    { value: "uk", label: "British (colour)" },
+    { value: "au", label: "Australian (colour, program)" },
+  ];
+
+  const contractionStyleOptions = [
+    { value: "preserve", label: "As Dictated" },
+    { value: "expand", label: "Expand (do not)" },
+    { value: "contract", label: "Contract (don't)" },
+  ];
+
+  const listStyleOptions = [
+    { value: "dash", label: "- Dashes" },
+    { value: "numbered", label: "1. Numbers" },
+  ];
+
+  const profanityMaskOptions = [
+    { value: "first_letter", label: "f***" },
+    { value: "asterisks", label: "****" },
+  ];
+
+  const redaction: AiRedaction = aiFeatures.redaction ?? {};
+  const redactionCategories: {
+    key: "emails" | "phone_numbers" | "card_numbers";
+    label: string;
+    description: string;
+  }[] = [
+    { key: "emails", label: "Redact Emails", description: "Email addresses like jane@example.com" },
+    { key: "phone_numbers", label: "Redact Phone Numbers", description: "Phone numbers written with their area or country code" },
+    { key: "card_numbers", label: "Redact Card Numbers", description: "Numbers of 13 to 19 digits that pass the card number checksum" },
+  ];
+
+  const toneOptions = [
+    { value: "none", label: "Keep As Dictated" },
+    { value: "casual", label: "Casual" },
+    { value: "professional", label: "Professional" },
+    { value: "neutral", label: "Neutral" },
+  ];
+
   React.useEffect(() => {
     checkOllama();
     loadSystemInfo();
@@ -60,13 +179,17 @@ export const AiEnhancementSettings: React.FC = () => {
 
     setIsTesting(true);
     setTestResult("");
+    setTestDiff(null);
     try {
-      const result = await commands.testAiEnhancement(testText);
+      const result = await commands.testAiEnhancement(testText, null);
       if (result.status === "ok") {
-        setTestResult(result.data);
-        toast.success("Enhancement complete!");
+        setTestResult(result.data.text);
+        setTestDiff(result.data.diff);
+        toast.success(
+          `Enhancement complete in ${(result.data.duration_ms / 1000).toFixed(1)}s`,
+        );
       } else {
-        toast.error(result.error || "Enhancement failed");
+        toast.error(describeEnhancementError(result.error));
       }
     } catch (e) {
       toast.error("Enhancement failed");
@@ -75,6 +198,19 @@ export const AiEnhancementSettings: React.FC = () => {
     }
   };
 
+  const handlePreviewPrompt = async () => {
+    try {
+      const result = await commands.previewAiPrompt(testText, null);
+      if (result.status === "ok") {
+        setPromptPreview(result.data);
+      } else {
+        toast.error(result.error);
+      }
+    } catch (e) {
+      toast.error("Failed to build the prompt");
+    }
+  };
+
   const copyResult = () => {
     navigator.clipboard.writeText(testResult);
     toast.success("Copied to clipboard!");
@@ -111,6 +247,8 @@ export const AiEnhancementSettings: React.FC = () => {
           </WarningBanner>
         )}
 
+        <AiOllamaEndpoints onChanged={checkOllama} />
+
         <ToggleSwitch
           checked={aiEnabled}
           onChange={(enabled) => updateSetting("ai_enhancement_enabled", enabled)}
@@ -123,6 +261,20 @@ export const AiEnhancementSettings: React.FC = () => {
           disabled={!ollamaAvailable}
         />
 
+        <ToggleSwitch
+          checked={aiFeatures.spoken_punctuation ?? false}
+          onChange={(checked) => updateSetting("ai_features", {
+            ...aiFeatures,
+            spoken_punctuation: checked
+          })}
+          isUpdating={false}
+          label="Spoken Punctuation"
+          description="Write punctuation and line breaks you dictate (comma, full stop, new line) as you say them, with or without AI enhancement"
+          descriptionMode="tooltip"
+          grouped={true}
+          tooltipPosition="bottom"
+        />
+
         {aiEnabled && (
           <>
             <SettingContainer
@@ -140,6 +292,316 @@ export const AiEnhancementSettings: React.FC = () => {
               </div>
             </SettingContainer>
 
+            <AiGgufImport />
+
+            <AiModelRegistry />
+
+            <AiModelUpdates />
+
+            <AiUnusedModels />
+
+            <AiGpuOffloadSetting />
+
+            <AiGenerationOptionsSetting />
+
+            <ToggleSwitch
+              checked={getSetting("ai_prompt_all_instructions") ?? false}
+              onChange={(enabled) => updateSetting("ai_prompt_all_instructions", enabled)}
+              isUpdating={false}
+              label="Give Every Instruction"
+              description="Send the model every enabled correction, even ones the transcript has nothing for, like numbers or filler words. Makes prompts longer and slower."
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={getSetting("ai_auto_pull_missing_model") ?? true}
+              onChange={(enabled) => updateSetting("ai_auto_pull_missing_model", enabled)}
+              isUpdating={false}
+              label="Download Missing Model"
+              description="Download the selected model again when Ollama no longer has it, enhancing dictations once it is ready"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={getSetting("ai_fallback_to_original") ?? true}
+              onChange={(enabled) => updateSetting("ai_fallback_to_original", enabled)}
+              isUpdating={false}
+              label="Fall Back to Original Text"
+              description="Paste the unmodified transcript when AI enhancement fails or times out"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {(getSetting("ai_fallback_to_original") ?? true) && (
+              <ToggleSwitch
+                checked={getSetting("ai_offline_fallback") ?? false}
+                onChange={(enabled) => updateSetting("ai_offline_fallback", enabled)}
+                isUpdating={false}
+                label="Basic Punctuation Without AI"
+                description="When the original transcript is pasted instead, still capitalize its sentences and end them with a period or question mark"
+                descriptionMode="tooltip"
+                grouped={true}
+                tooltipPosition="bottom"
+              />
+            )}
+
+            <Slider
+              value={(getSetting("ai_enhancement_timeout_ms") ?? 8000) / 1000}
+              onChange={(seconds) =>
+                updateSetting("ai_enhancement_timeout_ms", Math.round(seconds * 1000))
+              }
+              min={1}
+              max={30}
+              step={1}
+              label="Enhancement Timeout"
+              description="How long to wait for the model before giving up, per part of a long transcript. Summaries and translations get twice as long."
+              grouped={true}
+              formatValue={(seconds) => `${seconds}s`}
+            />
+
+            <Slider
+              value={getSetting("ai_degrade_after_failures") ?? 3}
+              onChange={(failures) => updateSetting("ai_degrade_after_failures", failures)}
+              min={0}
+              max={10}
+              step={1}
+              label="Switch to a Smaller Model"
+              description="After this many enhancements fail in a row, use the next smaller installed model until the selected one works again. The selected model stays selected."
+              grouped={true}
+              formatValue={(failures) =>
+                failures === 0 ? "Never" : `After ${failures} failures`
+              }
+            />
+
+            <Slider
+              value={(getSetting("ai_latency_budget_ms") ?? 0) / 1000}
+              onChange={(seconds) =>
+                updateSetting("ai_latency_budget_ms", Math.round(seconds * 1000))
+              }
+              min={0}
+              max={10}
+              step={0.5}
+              label="Latency Budget"
+              description="Use the best benchmarked model expected to enhance each dictation within this time instead of the selected one, pasting it as dictated when none is. Benchmark your models first."
+              grouped={true}
+              formatValue={(seconds) => (seconds === 0 ? "Off" : `${seconds}s`)}
+            />
+
+            <AiTwoPassSetting />
+
+            <Slider
+              value={getSetting("ai_min_words_for_enhancement") ?? 3}
+              onChange={(words) => updateSetting("ai_min_words_for_enhancement", words)}
+              min={1}
+              max={20}
+              step={1}
+              label="Minimum Words"
+              description="Shorter dictations are pasted as spoken, unless they are being translated. Dictations without any letters are never enhanced."
+              grouped={true}
+              formatValue={(words) => (words === 1 ? "1 word" : `${words} words`)}
+            />
+
+            <Slider
+              value={getSetting("ai_max_words_for_enhancement") ?? 400}
+              onChange={(words) => updateSetting("ai_max_words_for_enhancement", words)}
+              min={50}
+              max={2000}
+              step={50}
+              label="Maximum Words"
+              description="Longer dictations are handled as chosen below, so pasting them doesn't take long. Summaries always cover the whole dictation."
+              grouped={true}
+              formatValue={(words) => `${words} words`}
+            />
+
+            <SettingContainer
+              title="Longer Dictations"
+              description="What to do with dictations over the maximum number of words"
+              descriptionMode="tooltip"
+              grouped={true}
+            >
+              <Dropdown
+                options={longTextPolicyOptions}
+                selectedValue={getSetting("ai_long_text_policy") ?? "skip"}
+                onSelect={(value) =>
+                  updateSetting("ai_long_text_policy", value as AiLongTextPolicy)
+                }
+              />
+            </SettingContainer>
+
+            <ToggleSwitch
+              checked={getSetting("ai_warm_up_on_battery") ?? true}
+              onChange={(enabled) => updateSetting("ai_warm_up_on_battery", enabled)}
+              isUpdating={false}
+              label="Preload Model on Battery"
+              description="Start loading the model as soon as you start recording, even when running on battery"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={getSetting("ai_preload_on_startup") ?? false}
+              onChange={(enabled) => updateSetting("ai_preload_on_startup", enabled)}
+              isUpdating={false}
+              label="Load Model at Startup"
+              description="Load the model shortly after Handy starts, or once Ollama does, so your first dictation doesn't wait for it"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {getSetting("ai_preload_on_startup") && (
+              <Slider
+                value={getSetting("ai_preload_keep_alive_mins") ?? 60}
+                onChange={(minutes) => updateSetting("ai_preload_keep_alive_mins", minutes)}
+                min={5}
+                max={480}
+                step={5}
+                label="Keep Loaded For"
+                description="How long the model loaded at startup stays in memory before your first dictation"
+                grouped={true}
+                formatValue={(minutes) =>
+                  minutes >= 60 ? `${(minutes / 60).toFixed(minutes % 60 ? 1 : 0)} h` : `${minutes} min`
+                }
+              />
+            )}
+
+            <Slider
+              value={getSetting("ai_idle_unload_minutes") ?? 0}
+              onChange={(minutes) => updateSetting("ai_idle_unload_minutes", minutes)}
+              min={0}
+              max={120}
+              step={5}
+              label="Unload When Idle"
+              description="Free the model's memory after this long without dictating. It loads again with your next dictation."
+              grouped={true}
+              formatValue={(minutes) => (minutes === 0 ? "Never" : `${minutes} min`)}
+            />
+
+            <ToggleSwitch
+              checked={getSetting("ai_cache_enabled") ?? true}
+              onChange={(enabled) => updateSetting("ai_cache_enabled", enabled)}
+              isUpdating={false}
+              label="Reuse Recent Results"
+              description="Paste the earlier result when you dictate the same text again, without running the model"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={getSetting("ai_history_enabled") ?? false}
+              onChange={(enabled) => updateSetting("ai_history_enabled", enabled)}
+              isUpdating={false}
+              label="Keep Enhancement History"
+              description="Save each transcript next to its enhanced text on this device, to review what the model changed"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={getSetting("ai_withhold_original_from_events") ?? false}
+              onChange={(enabled) =>
+                updateSetting("ai_withhold_original_from_events", enabled)
+              }
+              isUpdating={false}
+              disabled={getSetting("ai_history_enabled") ?? false}
+              label="Withhold Dictated Text"
+              description="Send only the length of what you dictated, not the text itself, along with each enhanced result. Has no effect while the history is kept."
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={getSetting("ai_usage_stats_enabled") ?? false}
+              onChange={(enabled) => updateSetting("ai_usage_stats_enabled", enabled)}
+              isUpdating={false}
+              label="Track Usage"
+              description="Count how many dictations were enhanced, fell back or were skipped each day, and how long they took, to chart how enhancement is doing. Only counts are kept, on this device."
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={getSetting("ai_context_enabled") ?? false}
+              onChange={(enabled) => updateSetting("ai_context_enabled", enabled)}
+              isUpdating={false}
+              label="Continue From Previous Dictation"
+              description="Show the model what you dictated just before into the same app, so a sentence split across dictations keeps its casing and style. Dictating into another app or pausing starts over."
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {getSetting("ai_context_enabled") && (
+              <>
+                <Slider
+                  value={getSetting("ai_context_utterances") ?? 3}
+                  onChange={(count) => updateSetting("ai_context_utterances", count)}
+                  min={1}
+                  max={10}
+                  step={1}
+                  label="Previous Dictations"
+                  description="How many of the dictations just before to show the model. Only their last few sentences are ever shown."
+                  grouped={true}
+                  formatValue={(count) => (count === 1 ? "1 dictation" : `${count} dictations`)}
+                />
+
+                <Slider
+                  value={getSetting("ai_context_idle_secs") ?? 120}
+                  onChange={(seconds) => updateSetting("ai_context_idle_secs", seconds)}
+                  min={10}
+                  max={600}
+                  step={10}
+                  label="Start Over After"
+                  description="Pausing this long between dictations forgets the previous ones"
+                  grouped={true}
+                  formatValue={(seconds) => `${seconds}s`}
+                />
+
+                <SettingContainer
+                  title="Forget Previous Dictations"
+                  description="Enhance the next dictation on its own"
+                  descriptionMode="tooltip"
+                  grouped={true}
+                >
+                  <Button
+                    onClick={async () => {
+                      const result = await commands.clearAiContext();
+                      if (result.status === "error") {
+                        toast.error(result.error);
+                      }
+                    }}
+                    variant="secondary"
+                    size="sm"
+                  >
+                    Clear
+                  </Button>
+                </SettingContainer>
+              </>
+            )}
+
+            <ToggleSwitch
+              checked={getSetting("ai_queue_policy") === "latest_wins"}
+              onChange={(latestWins) =>
+                updateSetting("ai_queue_policy", latestWins ? "latest_wins" : "queue_all")
+              }
+              isUpdating={false}
+              label="Only Paste the Latest Dictation"
+              description="When you dictate again while an enhancement is running, skip the dictations still waiting instead of pasting each in turn. Skipped ones stay in your history."
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
             <div className="border-t border-mid-gray/20 my-2" />
 
             <ToggleSwitch
@@ -156,6 +618,24 @@ export const AiEnhancementSettings: React.FC = () => {
               tooltipPosition="bottom"
             />
 
+            {(aiFeatures.punctuation_and_capitalization ?? true) && (
+              <SettingContainer
+                title="Capitalization"
+                description="How the text is cased. The Transcribe as Heading shortcut uses title case whatever is set here"
+                descriptionMode="tooltip"
+                grouped={true}
+              >
+                <Dropdown
+                  options={capitalizationStyleOptions}
+                  selectedValue={aiFeatures.capitalization_style ?? "sentence"}
+                  onSelect={(value) => updateSetting("ai_features", {
+                    ...aiFeatures,
+                    capitalization_style: value as AiCapitalizationStyle
+                  })}
+                />
+              </SettingContainer>
+            )}
+
             <ToggleSwitch
               checked={aiFeatures.remove_filler_words ?? true}
               onChange={(checked) => updateSetting("ai_features", {
@@ -164,12 +644,14 @@ export const AiEnhancementSettings: React.FC = () => {
               })}
               isUpdating={false}
               label="Remove Filler Words"
-              description="Remove um, uh, like when used as fillers"
+              description="Remove um, uh, you know and other words from your list when used as fillers"
               descriptionMode="tooltip"
               grouped={true}
               tooltipPosition="bottom"
             />
 
+            {(aiFeatures.remove_filler_words ?? true) && <AiFillerWords />}
+
             <ToggleSwitch
               checked={aiFeatures.normalize_numbers ?? true}
               onChange={(checked) => updateSetting("ai_features", {
@@ -184,6 +666,169 @@ export const AiEnhancementSettings: React.FC = () => {
               tooltipPosition="bottom"
             />
 
+            {(aiFeatures.normalize_numbers ?? true) && (
+              <SettingContainer
+                title="Number Format"
+                description="How thousands and decimals are separated. Numbers the model separates the other way are fixed, while versions and IP addresses are left alone"
+                descriptionMode="tooltip"
+                grouped={true}
+              >
+                <Dropdown
+                  options={numberLocaleOptions}
+                  selectedValue={aiFeatures.number_locale ?? "auto"}
+                  onSelect={(value) => updateSetting("ai_features", {
+                    ...aiFeatures,
+                    number_locale: value as AiNumberLocale
+                  })}
+                />
+              </SettingContainer>
+            )}
+
+            {(aiFeatures.normalize_numbers ?? true) && (
+              <Slider
+                value={aiFeatures.number_style_threshold ?? 0}
+                onChange={(threshold) => updateSetting("ai_features", {
+                  ...aiFeatures,
+                  number_style_threshold: threshold
+                })}
+                min={0}
+                max={100}
+                step={1}
+                label="Spell Out Numbers"
+                description="Numbers below this are written as words, ordinals too (three, third), except with units, in dates and in ranges that go past it"
+                grouped={true}
+                formatValue={(threshold) => threshold === 0 ? "Always digits" : `Below ${threshold}`}
+              />
+            )}
+
+            <ToggleSwitch
+              checked={aiFeatures.normalize_dates_times ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                normalize_dates_times: checked
+              })}
+              isUpdating={false}
+              label="Normalize Dates & Times"
+              description="Write spoken dates and times as dates (march third at two thirty pm → March 3 at 2:30 PM). Relative dates like \"next Tuesday\" are kept as spoken"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {aiFeatures.normalize_dates_times && (
+              <SettingContainer
+                title="Date Style"
+                description="How dates and times are written"
+                descriptionMode="tooltip"
+                grouped={true}
+              >
+                <Dropdown
+                  options={dateStyleOptions}
+                  selectedValue={aiFeatures.date_style ?? "month_day"}
+                  onSelect={(value) => updateSetting("ai_features", {
+                    ...aiFeatures,
+                    date_style: value as AiDateStyle
+                  })}
+                />
+              </SettingContainer>
+            )}
+
+            <ToggleSwitch
+              checked={aiFeatures.normalize_units ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                normalize_units: checked
+              })}
+              isUpdating={false}
+              label="Normalize Units"
+              description="Write spoken measurements with units (five millimeters → 5 mm). Money amounts are left to Normalize Numbers"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {aiFeatures.normalize_units && (
+              <SettingContainer
+                title="Unit Style"
+                description="Write units as symbols or in words"
+                descriptionMode="tooltip"
+                grouped={true}
+              >
+                <Dropdown
+                  options={unitStyleOptions}
+                  selectedValue={aiFeatures.unit_style ?? "symbols"}
+                  onSelect={(value) => updateSetting("ai_features", {
+                    ...aiFeatures,
+                    unit_style: value as AiUnitStyle
+                  })}
+                />
+              </SettingContainer>
+            )}
+
+            <ToggleSwitch
+              checked={aiFeatures.normalize_currency ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                normalize_currency: checked
+              })}
+              isUpdating={false}
+              label="Normalize Currency"
+              description="Write spoken money amounts with a currency sign (two hundred euros → €200), without converting or rounding them"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {aiFeatures.normalize_currency && (
+              <SettingContainer
+                title="Currency Style"
+                description="Where the currency goes in an amount"
+                descriptionMode="tooltip"
+                grouped={true}
+              >
+                <Dropdown
+                  options={currencyStyleOptions}
+                  selectedValue={aiFeatures.currency_style ?? "symbol_before"}
+                  onSelect={(value) => updateSetting("ai_features", {
+                    ...aiFeatures,
+                    currency_style: value as AiCurrencyStyle
+                  })}
+                />
+              </SettingContainer>
+            )}
+
+            <ToggleSwitch
+              checked={aiFeatures.format_phone_numbers ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                format_phone_numbers: checked
+              })}
+              isUpdating={false}
+              label="Format Phone Numbers"
+              description="Write dictated phone numbers in one pattern (five five five one two three four five six seven → (555) 123-4567). Only numbers said to be phone numbers are touched, never card or account numbers"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {aiFeatures.format_phone_numbers && (
+              <SettingContainer
+                title="Phone Number Style"
+                description="The pattern phone numbers are written in"
+                descriptionMode="tooltip"
+                grouped={true}
+              >
+                <Dropdown
+                  options={phoneStyleOptions}
+                  selectedValue={aiFeatures.phone_style ?? "us"}
+                  onSelect={(value) => updateSetting("ai_features", {
+                    ...aiFeatures,
+                    phone_style: value as AiPhoneStyle
+                  })}
+                />
+              </SettingContainer>
+            )}
+
             <ToggleSwitch
               checked={aiFeatures.fix_spelling ?? true}
               onChange={(checked) => updateSetting("ai_features", {
@@ -197,6 +842,322 @@ export const AiEnhancementSettings: React.FC = () => {
               grouped={true}
               tooltipPosition="bottom"
             />
+
+            {(aiFeatures.fix_spelling ?? true) && (
+              <SettingContainer
+                title="Spelling Variant"
+                description="Whether English words are spelled the American, British or Australian way. As Dictated keeps colour and color as they were said, and names like Pearl Harbor are never respelled"
+                descriptionMode="tooltip"
+                grouped={true}
+              >
+                <Dropdown
+                  options={spellingVariantOptions}
+                  selectedValue={aiFeatures.spelling_variant ?? "preserve"}
+                  onSelect={(value) => updateSetting("ai_features", {
+                    ...aiFeatures,
+                    spelling_variant: value as AiSpellingVariant
+                  })}
+                />
+              </SettingContainer>
+            )}
+
+            <ToggleSwitch
+              checked={aiFeatures.fix_grammar ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                fix_grammar: checked
+              })}
+              isUpdating={false}
+              label="Fix Grammar"
+              description="Correct grammar mistakes like \"he don't\" with as few changes as possible, without rephrasing"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <SettingContainer
+              title="Contractions"
+              description="Write contractions out in full for formal documents (don't → do not), or use them for chat (do not → don't). Possessives like John's and quoted speech are never changed"
+              descriptionMode="tooltip"
+              grouped={true}
+            >
+              <Dropdown
+                options={contractionStyleOptions}
+                selectedValue={aiFeatures.contraction_style ?? "preserve"}
+                onSelect={(value) => updateSetting("ai_features", {
+                  ...aiFeatures,
+                  contraction_style: value as AiContractionStyle
+                })}
+              />
+            </SettingContainer>
+
+            <ToggleSwitch
+              checked={aiFeatures.remove_repetitions ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                remove_repetitions: checked
+              })}
+              isUpdating={false}
+              label="Remove Repetitions"
+              description="Drop words said twice by accident, like \"the the\", while keeping \"very very\""
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={aiFeatures.protect_spans ?? true}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                protect_spans: checked
+              })}
+              isUpdating={false}
+              label="Protect Links & Code"
+              description="Keep URLs, email addresses, file paths and code exactly as dictated"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={aiFeatures.split_paragraphs ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                split_paragraphs: checked
+              })}
+              isUpdating={false}
+              label="Split Into Paragraphs"
+              description="Break long dictations into paragraphs where the topic shifts"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={aiFeatures.split_long_sentences ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                split_long_sentences: checked
+              })}
+              isUpdating={false}
+              label="Split Run-On Sentences"
+              description="Break long run-on sentences into shorter ones without dropping or reordering words. When the model can't run, sentences past the length below are split before words like \"and then\" or \"so we\"."
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {aiFeatures.split_long_sentences && (
+              <Slider
+                value={aiFeatures.max_sentence_words ?? 30}
+                onChange={(words) => updateSetting("ai_features", {
+                  ...aiFeatures,
+                  max_sentence_words: words
+                })}
+                min={10}
+                max={100}
+                step={5}
+                label="Longest Sentence"
+                description="Sentences with more words than this are split by rule when the model doesn't run"
+                grouped={true}
+                formatValue={(words) => `${words} words`}
+              />
+            )}
+
+            <ToggleSwitch
+              checked={aiFeatures.format_lists ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                format_lists: checked
+              })}
+              isUpdating={false}
+              label="Format Lists"
+              description="Turn spoken enumerations like \"first ..., second ...\" into a list"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {aiFeatures.format_lists && (
+              <SettingContainer
+                title="List Style"
+                description="Marker in front of each list item"
+                descriptionMode="tooltip"
+                grouped={true}
+              >
+                <Dropdown
+                  options={listStyleOptions}
+                  selectedValue={aiFeatures.list_style ?? "dash"}
+                  onSelect={(value) => updateSetting("ai_features", {
+                    ...aiFeatures,
+                    list_style: value as AiListStyle
+                  })}
+                />
+              </SettingContainer>
+            )}
+
+            <ToggleSwitch
+              checked={aiFeatures.smart_typography ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                smart_typography: checked
+              })}
+              isUpdating={false}
+              label="Smart Typography"
+              description="Use curly quotes, em dashes and ellipses (\"...\" → “…”). Leave off when dictating code"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={aiFeatures.mask_profanity ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                mask_profanity: checked
+              })}
+              isUpdating={false}
+              label="Mask Profanity"
+              description="Mask swear words in the pasted text (\"f***\"), including spelled-out and leetspeak ones, without changing anything else"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {aiFeatures.mask_profanity && (
+              <>
+                <SettingContainer
+                  title="Masking Style"
+                  description="How masked words are written"
+                  descriptionMode="tooltip"
+                  grouped={true}
+                >
+                  <Dropdown
+                    options={profanityMaskOptions}
+                    selectedValue={aiFeatures.profanity_mask ?? "first_letter"}
+                    onSelect={(value) => updateSetting("ai_features", {
+                      ...aiFeatures,
+                      profanity_mask: value as AiProfanityMask
+                    })}
+                  />
+                </SettingContainer>
+
+                <SettingContainer
+                  title="Masked Words"
+                  description="Words to mask, comma separated. Endings like -ing and -ed are masked with them, but words that only contain them are not."
+                  descriptionMode="tooltip"
+                  grouped={true}
+                >
+                  <Input
+                    type="text"
+                    defaultValue={(aiFeatures.profane_words ?? []).join(", ")}
+                    onBlur={(event) => updateSetting("ai_features", {
+                      ...aiFeatures,
+                      profane_words: event.target.value
+                        .split(",")
+                        .map((word) => word.trim())
+                        .filter((word) => word.length > 0)
+                    })}
+                    variant="compact"
+                    className="min-w-[240px]"
+                  />
+                </SettingContainer>
+              </>
+            )}
+
+            <ToggleSwitch
+              checked={aiFeatures.convert_spoken_emoji ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                convert_spoken_emoji: checked
+              })}
+              isUpdating={false}
+              label="Convert Spoken Emoji"
+              description="Write emoji said by name (\"thumbs up emoji\" → 👍), and emoticons like \"smiley face\" said at the end of a sentence"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            <ToggleSwitch
+              checked={aiFeatures.redact_sensitive ?? false}
+              onChange={(checked) => updateSetting("ai_features", {
+                ...aiFeatures,
+                redact_sensitive: checked
+              })}
+              isUpdating={false}
+              label="Redact Sensitive Information"
+              description="Replace email addresses, phone numbers and card numbers in the pasted text with placeholders like [email], for notes pasted into shared documents"
+              descriptionMode="tooltip"
+              grouped={true}
+              tooltipPosition="bottom"
+            />
+
+            {aiFeatures.redact_sensitive && (
+              <>
+                {redactionCategories.map(({ key, label, description }) => (
+                  <ToggleSwitch
+                    key={key}
+                    checked={redaction[key] ?? true}
+                    onChange={(checked) => updateSetting("ai_features", {
+                      ...aiFeatures,
+                      redaction: { ...redaction, [key]: checked }
+                    })}
+                    isUpdating={false}
+                    label={label}
+                    description={description}
+                    descriptionMode="tooltip"
+                    grouped={true}
+                    tooltipPosition="bottom"
+                  />
+                ))}
+
+                <SettingContainer
+                  title="Redaction Placeholder"
+                  description="Written in place of each redaction. {category} becomes email, phone or card"
+                  descriptionMode="tooltip"
+                  grouped={true}
+                >
+                  <Input
+                    type="text"
+                    defaultValue={redaction.placeholder ?? "[{category}]"}
+                    onBlur={(event) => updateSetting("ai_features", {
+                      ...aiFeatures,
+                      redaction: { ...redaction, placeholder: event.target.value }
+                    })}
+                    variant="compact"
+                    className="min-w-[240px]"
+                  />
+                </SettingContainer>
+              </>
+            )}
+
+            <SettingContainer
+              title="Tone"
+              description="Rewrite the text in this tone, which lets the model rephrase it"
+              descriptionMode="tooltip"
+              grouped={true}
+            >
+              <Dropdown
+                options={toneOptions}
+                selectedValue={aiFeatures.tone ?? "none"}
+                onSelect={(value) => updateSetting("ai_features", {
+                  ...aiFeatures,
+                  tone: value === "none" ? null : (value as AiTone)
+                })}
+              />
+            </SettingContainer>
+
+            <div className="border-t border-mid-gray/20 my-2" />
+
+            <HandyShortcut shortcutId="transcribe_summary" grouped={true} />
+            <HandyShortcut shortcutId="transcribe_professional" grouped={true} />
+            <HandyShortcut shortcutId="transcribe_email" grouped={true} />
+            <HandyShortcut shortcutId="transcribe_markdown" grouped={true} />
+            <HandyShortcut shortcutId="transcribe_code" grouped={true} />
+            <HandyShortcut shortcutId="transcribe_heading" grouped={true} />
+            <HandyShortcut shortcutId="reenhance_last" grouped={true} />
+            <AiReenhanceSetting />
           </>
         )}
       </SettingsGroup>
@@ -216,7 +1177,15 @@ export const AiEnhancementSettings: React.FC = () => {
                 placeholder="Enter text to test enhancement... (e.g., 'um hey like my name is john and i have twenty five dollars')"
                 rows={3}
               />
-              <div className="flex justify-end">
+              <div className="flex justify-end gap-2">
+                <Button
+                  onClick={handlePreviewPrompt}
+                  disabled={!testText.trim()}
+                  variant="secondary"
+                  size="md"
+                >
+                  Show Prompt
+                </Button>
                 <Button
                   onClick={handleTestEnhancement}
                   disabled={isTesting || !testText.trim()}
@@ -233,6 +1202,32 @@ export const AiEnhancementSettings: React.FC = () => {
                   )}
                 </Button>
               </div>
+              {promptPreview && (
+                <div className="p-3 bg-mid-gray/10 rounded border border-mid-gray/20">
+                  <div className="flex items-center justify-between mb-2">
+                    <p className="text-xs font-medium text-mid-gray">
+                      Prompt (about {promptPreview.estimated_tokens} tokens):
+                    </p>
+                    <button
+                      onClick={() => setPromptPreview(null)}
+                      className="text-xs text-mid-gray hover:text-text"
+                    >
+                      Hide
+                    </button>
+                  </div>
+                  {promptPreview.dropped.length > 0 && (
+                    <p className="text-xs text-mid-gray mb-2">
+                      Left out to fit the model's context:{" "}
+                      {promptPreview.dropped.map((component) => component.replace(/_/g, " ")).join(", ")}
+                    </p>
+                  )}
+                  {promptPreview.prompts.map((prompt, i) => (
+                    <pre key={i} className="text-xs whitespace-pre-wrap font-mono mb-2 last:mb-0">
+                      {prompt}
+                    </pre>
+                  ))}
+                </div>
+              )}
               {testResult && (
                 <div className="p-3 bg-mid-gray/10 rounded border border-mid-gray/20">
                   <div className="flex items-center justify-between mb-2">
@@ -245,11 +1240,32 @@ export const AiEnhancementSettings: React.FC = () => {
                       Copy
                     </button>
                   </div>
-                  <p className="text-sm">{testResult}</p>
+                  {testDiff ? (
+                    <p className="text-sm whitespace-pre-wrap">
+                      {testDiff.map((op, i) =>
+                        op.kind === "insert" ? (
+                          <span key={i} className="bg-green-500/20 text-green-400">
+                            {op.text}
+                          </span>
+                        ) : op.kind === "delete" ? (
+                          <span key={i} className="bg-red-500/20 text-red-400 line-through">
+                            {op.text}
+                          </span>
+                        ) : (
+                          <span key={i}>{op.text}</span>
+                        ),
+                      )}
+                    </p>
+                  ) : (
+                    <p className="text-sm">{testResult}</p>
+                  )}
                 </div>
               )}
             </div>
           </SettingContainer>
+          <AiModelComparisonPanel />
+          <AiTestSuitePanel />
+          {getSetting("ai_usage_stats_enabled") && <AiUsageStatsPanel />}
         </SettingsGroup>
       )}
     </>
@@ -0,0 +1,47 @@
+import React, { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { commands } from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Input } from "../../ui/Input";
+import { SettingContainer } from "../../ui/SettingContainer";
+
+export const AiFillerWords: React.FC = () => {
+  const { getSetting, refreshSettings } = useSettings();
+  const fillers = (getSetting("ai_features")?.filler_words ?? []).join(", ");
+  const [localFillers, setLocalFillers] = useState(fillers);
+
+  useEffect(() => {
+    setLocalFillers(fillers);
+  }, [fillers]);
+
+  const handleBlur = async () => {
+    if (localFillers.trim() === fillers) return;
+
+    const result = await commands.setAiFillerWords(localFillers.split(","));
+    if (result.status === "error") {
+      toast.error(result.error);
+      setLocalFillers(fillers);
+      return;
+    }
+    await refreshSettings();
+  };
+
+  return (
+    <SettingContainer
+      title="Filler Words"
+      description="Words and phrases to remove when they are only fillers, comma separated. Take one off the list to keep it."
+      descriptionMode="tooltip"
+      grouped={true}
+    >
+      <Input
+        type="text"
+        value={localFillers}
+        onChange={(event) => setLocalFillers(event.target.value)}
+        onBlur={handleBlur}
+        placeholder="um, uh, you know"
+        variant="compact"
+        className="min-w-[240px]"
+      />
+    </SettingContainer>
+  );
+};
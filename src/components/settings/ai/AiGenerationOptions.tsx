@@ -0,0 +1,107 @@
+import React, { useState } from "react";
+import { toast } from "sonner";
+import {
+  commands,
+  type AiGenerationOptions,
+  type AiSamplingOptions,
+} from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Button } from "../../ui/Button";
+import { Dropdown } from "../../ui/Dropdown";
+import { SettingContainer } from "../../ui/SettingContainer";
+import { Slider } from "../../ui/Slider";
+
+type SamplingMode = keyof AiGenerationOptions;
+
+const modeOptions = [
+  { value: "correct", label: "Corrections" },
+  { value: "summarize", label: "Summaries" },
+  { value: "tone", label: "Tone Rewrites" },
+];
+
+// Mirrors the defaults in settings.rs, until the settings have loaded
+const fallbackSampling: AiSamplingOptions = {
+  temperature: 0.1,
+  max_output_tokens: 2048,
+  repeat_penalty: 1.1,
+};
+
+export const AiGenerationOptionsSetting: React.FC = () => {
+  const { getSetting, updateSetting } = useSettings();
+  const [mode, setMode] = useState<SamplingMode>("correct");
+
+  const options = getSetting("ai_generation_options") ?? {};
+  const sampling = options[mode] ?? fallbackSampling;
+
+  const updateSampling = (changes: Partial<AiSamplingOptions>) =>
+    updateSetting("ai_generation_options", {
+      ...options,
+      [mode]: { ...sampling, ...changes },
+    });
+
+  const resetToDefaults = async () => {
+    const defaults = await commands.getDefaultAiGenerationOptions();
+    if (defaults.status === "error") {
+      toast.error(defaults.error);
+      return;
+    }
+    await updateSetting("ai_generation_options", defaults.data);
+    toast.success("Generation settings reset to defaults");
+  };
+
+  return (
+    <>
+      <SettingContainer
+        title="Generation Settings"
+        description="How freely the model writes, for each kind of enhancement"
+        descriptionMode="tooltip"
+        grouped={true}
+      >
+        <div className="flex items-center gap-2">
+          <Dropdown
+            options={modeOptions}
+            selectedValue={mode}
+            onSelect={(value) => setMode(value as SamplingMode)}
+          />
+          <Button variant="secondary" size="sm" onClick={resetToDefaults}>
+            Reset to Defaults
+          </Button>
+        </div>
+      </SettingContainer>
+
+      <Slider
+        value={sampling.temperature}
+        onChange={(temperature) => updateSampling({ temperature })}
+        min={0}
+        max={2}
+        step={0.05}
+        label="Temperature"
+        description="Lower keeps the model to the most likely wording, higher lets it rephrase more"
+        grouped={true}
+      />
+
+      <Slider
+        value={sampling.max_output_tokens}
+        onChange={(tokens) => updateSampling({ max_output_tokens: tokens })}
+        min={64}
+        max={8192}
+        step={64}
+        label="Output Limit"
+        description="Most tokens the model may write for one request, however long the dictation"
+        grouped={true}
+        formatValue={(tokens) => `${tokens} tokens`}
+      />
+
+      <Slider
+        value={sampling.repeat_penalty}
+        onChange={(penalty) => updateSampling({ repeat_penalty: penalty })}
+        min={0.5}
+        max={2}
+        step={0.05}
+        label="Repeat Penalty"
+        description="Discourages the model from repeating itself. 1.0 turns it off."
+        grouped={true}
+      />
+    </>
+  );
+};
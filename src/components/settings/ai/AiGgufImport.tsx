@@ -0,0 +1,80 @@
+import React, { useState } from "react";
+import { toast } from "sonner";
+import { commands } from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Button } from "../../ui/Button";
+import { Input } from "../../ui/Input";
+import { SettingContainer } from "../../ui/SettingContainer";
+
+export const AiGgufImport: React.FC = () => {
+  const { updateSetting } = useSettings();
+  const [path, setPath] = useState("");
+  const [name, setName] = useState("");
+  const [isImporting, setIsImporting] = useState(false);
+
+  const handleImport = async () => {
+    setIsImporting(true);
+    try {
+      const result = await commands.importGgufModel(
+        path.trim(),
+        name.trim(),
+        null,
+      );
+      if (result.status === "error") {
+        toast.error(result.error);
+        return;
+      }
+
+      const model = result.data;
+      toast.success(`Imported ${model}`, {
+        action: {
+          label: "Use",
+          onClick: () => updateSetting("ai_selected_model", model),
+        },
+      });
+      setPath("");
+      setName("");
+    } finally {
+      setIsImporting(false);
+    }
+  };
+
+  return (
+    <SettingContainer
+      title="Import GGUF"
+      description="Create an Ollama model from a GGUF file already on disk instead of downloading it"
+      descriptionMode="tooltip"
+      grouped={true}
+      layout="stacked"
+    >
+      <div className="flex items-center gap-2">
+        <Input
+          type="text"
+          value={path}
+          onChange={(event) => setPath(event.target.value)}
+          placeholder="/path/to/model.gguf"
+          variant="compact"
+          className="flex-1 min-w-[200px]"
+          disabled={isImporting}
+        />
+        <Input
+          type="text"
+          value={name}
+          onChange={(event) => setName(event.target.value)}
+          placeholder="Model name"
+          variant="compact"
+          className="w-[140px]"
+          disabled={isImporting}
+        />
+        <Button
+          variant="secondary"
+          size="sm"
+          onClick={handleImport}
+          disabled={isImporting || !path.trim() || !name.trim()}
+        >
+          {isImporting ? "Importing..." : "Import"}
+        </Button>
+      </div>
+    </SettingContainer>
+  );
+};
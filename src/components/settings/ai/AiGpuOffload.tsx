@@ -0,0 +1,77 @@
+import React, { useEffect, useState } from "react";
+import { commands, type AiGpuOffload, type OllamaRunningModel } from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Dropdown } from "../../ui/Dropdown";
+import { SettingContainer } from "../../ui/SettingContainer";
+
+const offloadOptions = [
+  { value: "auto", label: "Auto" },
+  { value: "cpu_only", label: "CPU only" },
+  { value: "8", label: "8 layers" },
+  { value: "16", label: "16 layers" },
+  { value: "32", label: "32 layers" },
+  { value: "999", label: "All layers" },
+];
+
+const toOptionValue = (offload: AiGpuOffload): string =>
+  typeof offload === "string" ? offload : offload.layers.toString();
+
+const fromOptionValue = (value: string): AiGpuOffload =>
+  value === "auto" || value === "cpu_only"
+    ? value
+    : { layers: parseInt(value, 10) };
+
+export const AiGpuOffloadSetting: React.FC = () => {
+  const { getSetting, updateSetting } = useSettings();
+  const [runningModel, setRunningModel] = useState<OllamaRunningModel | null>(
+    null,
+  );
+
+  const selectedModel = getSetting("ai_selected_model") ?? "";
+  const offload = getSetting("ai_gpu_offload") ?? "auto";
+
+  const loadRunningModel = async () => {
+    const result = await commands.listOllamaRunningModels();
+    if (result.status === "ok") {
+      setRunningModel(
+        result.data.find((model) => model.name === selectedModel) ?? null,
+      );
+    }
+  };
+
+  useEffect(() => {
+    loadRunningModel();
+  }, [selectedModel, offload]);
+
+  const placement = (): string | null => {
+    if (!runningModel) return null;
+    const vram = Number(runningModel.size_vram ?? 0);
+    const size = Number(runningModel.size);
+    if (vram === 0) return "Loaded model is running on CPU";
+    if (vram >= size) return "Loaded model is running on GPU";
+    return `Loaded model is ${Math.round((vram / size) * 100)}% on GPU`;
+  };
+
+  return (
+    <SettingContainer
+      title="GPU Offload"
+      description="How many model layers Ollama should place on the GPU"
+      descriptionMode="tooltip"
+      grouped={true}
+    >
+      <div className="flex flex-col items-end gap-1">
+        <Dropdown
+          options={offloadOptions}
+          selectedValue={toOptionValue(offload)}
+          onSelect={(value) =>
+            updateSetting("ai_gpu_offload", fromOptionValue(value))
+          }
+          onRefresh={loadRunningModel}
+        />
+        {placement() && (
+          <p className="text-xs text-mid-gray">{placement()}</p>
+        )}
+      </div>
+    </SettingContainer>
+  );
+};
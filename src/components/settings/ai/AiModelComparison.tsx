@@ -0,0 +1,165 @@
+import React, { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { Loader2 } from "lucide-react";
+import {
+  commands,
+  type AiModelComparison,
+  type AiModelComparisonSide,
+} from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { describeEnhancementError } from "../../../lib/utils/enhancementError";
+import { Button } from "../../ui/Button";
+import { Dropdown } from "../../ui/Dropdown";
+import { SettingContainer } from "../../ui/SettingContainer";
+import { Textarea } from "../../ui/Textarea";
+
+const ComparisonSide: React.FC<{
+  side: AiModelComparisonSide;
+  selected: boolean;
+  onUse: () => void;
+}> = ({ side, selected, onUse }) => (
+  <div className="p-3 bg-mid-gray/10 rounded border border-mid-gray/20 flex flex-col gap-2">
+    <div className="flex items-center justify-between">
+      <p className="text-xs font-medium">{side.model}</p>
+      <p className="text-xs text-mid-gray">
+        {(side.latency_ms / 1000).toFixed(1)}s
+      </p>
+    </div>
+    {side.error ? (
+      <p className="text-sm text-red-400">{describeEnhancementError(side.error)}</p>
+    ) : side.diff ? (
+      <p className="text-sm whitespace-pre-wrap">
+        {side.diff.map((op, i) =>
+          op.kind === "insert" ? (
+            <span key={i} className="bg-green-500/20 text-green-400">
+              {op.text}
+            </span>
+          ) : op.kind === "delete" ? (
+            <span key={i} className="bg-red-500/20 text-red-400 line-through">
+              {op.text}
+            </span>
+          ) : (
+            <span key={i}>{op.text}</span>
+          ),
+        )}
+      </p>
+    ) : (
+      <p className="text-sm">{side.text}</p>
+    )}
+    <div className="flex justify-end mt-auto">
+      <Button
+        size="sm"
+        variant="secondary"
+        onClick={onUse}
+        disabled={selected || side.error !== null}
+      >
+        {selected ? "In Use" : "Use This Model"}
+      </Button>
+    </div>
+  </div>
+);
+
+export const AiModelComparisonPanel: React.FC = () => {
+  const { getSetting, updateSetting } = useSettings();
+  const [models, setModels] = useState<string[]>([]);
+  const [modelA, setModelA] = useState<string | null>(null);
+  const [modelB, setModelB] = useState<string | null>(null);
+  const [text, setText] = useState("");
+  const [comparison, setComparison] = useState<AiModelComparison | null>(null);
+  const [isComparing, setIsComparing] = useState(false);
+
+  const selectedModel = getSetting("ai_selected_model") || "";
+
+  useEffect(() => {
+    const loadModels = async () => {
+      const result = await commands.listOllamaModels(null);
+      if (result.status === "ok") {
+        setModels(result.data);
+      }
+    };
+    loadModels();
+  }, []);
+
+  const handleCompare = async () => {
+    if (!modelA || !modelB) return;
+    setIsComparing(true);
+    setComparison(null);
+    try {
+      const result = await commands.compareAiModels(text, modelA, modelB);
+      if (result.status === "ok") {
+        setComparison(result.data);
+      } else {
+        toast.error(result.error);
+      }
+    } finally {
+      setIsComparing(false);
+    }
+  };
+
+  const handleUse = async (model: string) => {
+    await updateSetting("ai_selected_model", model);
+    toast.success(`Switched to ${model}`);
+  };
+
+  const options = models.map((model) => ({ value: model, label: model }));
+
+  return (
+    <SettingContainer
+      title="Compare Models"
+      description="Correct the same text with two installed models and see their output side by side"
+      grouped={true}
+      layout="stacked"
+    >
+      <div className="space-y-3">
+        <Textarea
+          value={text}
+          onChange={(e) => setText(e.target.value)}
+          placeholder="Enter text to compare the models on..."
+          rows={3}
+        />
+        <div className="flex items-center gap-2">
+          <Dropdown
+            options={options}
+            selectedValue={modelA}
+            onSelect={setModelA}
+            placeholder="First model..."
+          />
+          <span className="text-xs text-mid-gray">vs</span>
+          <Dropdown
+            options={options}
+            selectedValue={modelB}
+            onSelect={setModelB}
+            placeholder="Second model..."
+          />
+          <Button
+            onClick={handleCompare}
+            disabled={isComparing || !text.trim() || !modelA || !modelB}
+            variant="secondary"
+            size="md"
+          >
+            {isComparing ? (
+              <>
+                <Loader2 className="w-4 h-4 mr-2 animate-spin" />
+                Comparing...
+              </>
+            ) : (
+              "Compare"
+            )}
+          </Button>
+        </div>
+        {comparison && (
+          <div className="grid grid-cols-2 gap-2">
+            {[comparison.a, comparison.b].map((side, i) => (
+              <ComparisonSide
+                key={i}
+                side={side}
+                selected={side.model === selectedModel}
+                onUse={() => handleUse(side.model)}
+              />
+            ))}
+          </div>
+        )}
+      </div>
+    </SettingContainer>
+  );
+};
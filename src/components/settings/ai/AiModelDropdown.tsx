@@ -1,6 +1,7 @@
 import React from "react";
 import { Button } from "../../ui/Button";
-import { Download, Trash2, Check, Star } from "lucide-react";
+import { Download, Trash2, Check, Star, Gauge } from "lucide-react";
+import type { AiBenchmarkReport, OllamaStorageInfo } from "@/bindings";
 
 interface AiModel {
   id: string;
@@ -11,6 +12,7 @@ interface AiModel {
 }
 
 interface PullProgress {
+  pull_id: string;
   model_id: string;
   status: string;
   completed: number | null;
@@ -18,15 +20,27 @@ interface PullProgress {
   percentage: number;
 }
 
+const formatBytes = (bytes: string | number): string => {
+  const gb = Number(bytes) / 1024 ** 3;
+  return gb >= 1 ? `${gb.toFixed(1)} GB` : `${Math.round(gb * 1024)} MB`;
+};
+
 interface AiModelDropdownProps {
   models: AiModel[];
   downloadedModels: string[];
   selectedModel: string;
   recommendedModel?: string;
   pullProgress: Map<string, PullProgress>;
+  storageInfo: OllamaStorageInfo | null;
+  benchmarks: Map<string, AiBenchmarkReport>;
+  // Model being benchmarked, "" before the first of "Benchmark All"
+  benchmarkingModel: string | null;
   onModelSelect: (modelId: string) => void;
   onModelPull: (modelId: string) => void;
   onModelDelete: (modelId: string) => void;
+  onPullCancel: (pullId: string) => void;
+  onModelBenchmark: (modelId: string) => void;
+  onBenchmarkAll: () => void;
 }
 
 const AiModelDropdown: React.FC<AiModelDropdownProps> = ({
@@ -35,9 +49,15 @@ const AiModelDropdown: React.FC<AiModelDropdownProps> = ({
   selectedModel,
   recommendedModel,
   pullProgress,
+  storageInfo,
+  benchmarks,
+  benchmarkingModel,
   onModelSelect,
   onModelPull,
   onModelDelete,
+  onPullCancel,
+  onModelBenchmark,
+  onBenchmarkAll,
 }) => {
   const isModelDownloaded = (modelId: string) => {
     return downloadedModels.some((m) => m.startsWith(modelId));
@@ -57,6 +77,18 @@ const AiModelDropdown: React.FC<AiModelDropdownProps> = ({
         <p className="text-xs font-medium text-mid-gray uppercase">
           AI Enhancement Models
         </p>
+        {storageInfo && (
+          <p className="text-xs text-mid-gray mt-1">
+            {formatBytes(storageInfo.used_bytes)} used
+            {storageInfo.free_bytes !== null &&
+              ` • ${formatBytes(storageInfo.free_bytes)} free`}
+            {storageInfo.models_dir && (
+              <span className="block truncate" title={storageInfo.models_dir}>
+                {storageInfo.models_dir}
+              </span>
+            )}
+          </p>
+        )}
       </div>
 
       <div className="p-2 space-y-2">
@@ -66,6 +98,7 @@ const AiModelDropdown: React.FC<AiModelDropdownProps> = ({
           const progress = getModelProgress(model.id);
           const isPulling = progress !== undefined;
           const recommended = isRecommended(model.id);
+          const benchmark = benchmarks.get(model.id);
 
           return (
             <div
@@ -118,7 +151,13 @@ const AiModelDropdown: React.FC<AiModelDropdownProps> = ({
                     <div className="flex flex-wrap gap-2 text-xs text-mid-gray">
                       <span>{model.size_mb}MB</span>
                       <span>•</span>
-                      <span>{model.speed}</span>
+                      {benchmark ? (
+                        <span title="Measured on this machine">
+                          {Math.round(benchmark.tokens_per_second)} tok/s
+                        </span>
+                      ) : (
+                        <span>{model.speed}</span>
+                      )}
                       <span>•</span>
                       <span>{model.quality}</span>
                     </div>
@@ -126,7 +165,15 @@ const AiModelDropdown: React.FC<AiModelDropdownProps> = ({
                 </div>
 
                 <div className="flex flex-col gap-1 flex-shrink-0">
-                  {isPulling ? (
+                  {isPulling && progress?.status === "queued" ? (
+                    <Button
+                      size="sm"
+                      variant="secondary"
+                      onClick={() => onPullCancel(progress.pull_id)}
+                    >
+                      Cancel
+                    </Button>
+                  ) : isPulling ? (
                     <Button
                       size="sm"
                       variant="secondary"
@@ -144,6 +191,21 @@ const AiModelDropdown: React.FC<AiModelDropdownProps> = ({
                       >
                         {isSelected ? "Active" : "Use"}
                       </Button>
+                      <Button
+                        size="sm"
+                        variant="secondary"
+                        onClick={() => onModelBenchmark(model.id)}
+                        disabled={benchmarkingModel !== null}
+                        title={
+                          benchmarkingModel === model.id
+                            ? "Benchmarking..."
+                            : "Measure speed on this machine"
+                        }
+                      >
+                        <Gauge
+                          className={`w-3 h-3 ${benchmarkingModel === model.id ? "animate-pulse" : ""}`}
+                        />
+                      </Button>
                       <Button
                         size="sm"
                         variant="secondary"
@@ -169,10 +231,20 @@ const AiModelDropdown: React.FC<AiModelDropdownProps> = ({
         })}
       </div>
 
-      <div className="p-3 border-t border-mid-gray/20 bg-mid-gray/5">
+      <div className="p-3 border-t border-mid-gray/20 bg-mid-gray/5 flex items-center justify-between gap-2">
         <p className="text-xs text-mid-gray">
           Models are managed by Ollama. Use Pull to download.
         </p>
+        {downloadedModels.length > 0 && (
+          <Button
+            size="sm"
+            variant="secondary"
+            onClick={onBenchmarkAll}
+            disabled={benchmarkingModel !== null}
+          >
+            {benchmarkingModel !== null ? "Benchmarking..." : "Benchmark All"}
+          </Button>
+        )}
       </div>
     </div>
   );
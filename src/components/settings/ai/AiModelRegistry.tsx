@@ -0,0 +1,68 @@
+import React, { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { commands } from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Input } from "../../ui/Input";
+import { SettingContainer } from "../../ui/SettingContainer";
+import { ToggleSwitch } from "../../ui/ToggleSwitch";
+
+export const AiModelRegistry: React.FC = () => {
+  const { getSetting, updateSetting, refreshSettings, isUpdating } =
+    useSettings();
+  const prefix = getSetting("ai_model_registry_prefix") ?? "";
+  const insecure = getSetting("ai_model_registry_insecure") ?? false;
+  const [localPrefix, setLocalPrefix] = useState(prefix);
+
+  useEffect(() => {
+    setLocalPrefix(prefix);
+  }, [prefix]);
+
+  const handleBlur = async () => {
+    const value = localPrefix.trim();
+    if (value === prefix) return;
+
+    const result = await commands.changeAiModelRegistryPrefix(value || null);
+    if (result.status === "error") {
+      toast.error(result.error);
+      setLocalPrefix(prefix);
+      return;
+    }
+    await refreshSettings();
+  };
+
+  return (
+    <>
+      <SettingContainer
+        title="Model Registry"
+        description="Pull models from a custom registry or mirror, e.g. myregistry.local/library. Leave empty to use the default Ollama registry."
+        descriptionMode="tooltip"
+        grouped={true}
+      >
+        <Input
+          type="text"
+          value={localPrefix}
+          onChange={(event) => setLocalPrefix(event.target.value)}
+          onBlur={handleBlur}
+          placeholder="registry.ollama.ai/library"
+          variant="compact"
+          className="min-w-[240px]"
+        />
+      </SettingContainer>
+
+      {prefix && (
+        <ToggleSwitch
+          checked={insecure}
+          onChange={(enabled) =>
+            updateSetting("ai_model_registry_insecure", enabled)
+          }
+          isUpdating={isUpdating("ai_model_registry_insecure")}
+          label="Allow Insecure Registry"
+          description="Accept self-signed certificates or plain HTTP when pulling from the custom registry"
+          descriptionMode="tooltip"
+          grouped={true}
+          tooltipPosition="bottom"
+        />
+      )}
+    </>
+  );
+};
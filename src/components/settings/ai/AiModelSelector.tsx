@@ -1,6 +1,13 @@
 import React, { useState, useRef, useEffect } from "react";
 import { listen } from "@tauri-apps/api/event";
-import { commands } from "@/bindings";
+import {
+  commands,
+  type AiBenchmarkProgress,
+  type AiBenchmarkReport,
+  type AiPullQueueItem,
+  type AiPullRecord,
+  type OllamaStorageInfo,
+} from "@/bindings";
 import { useSettings } from "../../../hooks/useSettings";
 import { toast } from "sonner";
 import AiModelStatusButton from "./AiModelStatusButton";
@@ -16,7 +23,16 @@ interface AiModel {
   notes: string;
 }
 
+interface PullStalled {
+  pull_id: string;
+  model_id: string;
+  completed: number | null;
+  total: number | null;
+  retrying: boolean;
+}
+
 interface PullProgress {
+  pull_id: string;
   model_id: string;
   status: string;
   completed: number | null;
@@ -24,6 +40,21 @@ interface PullProgress {
   percentage: number;
 }
 
+interface PullFinished {
+  pull_id: string;
+  model_id: string;
+  error?: string;
+}
+
+const queuedProgress = (item: AiPullQueueItem): PullProgress => ({
+  pull_id: item.id,
+  model_id: item.model_id,
+  status: item.state,
+  completed: null,
+  total: null,
+  percentage: 0,
+});
+
 const AiModelSelector: React.FC = () => {
   const { getSetting, updateSetting } = useSettings();
   const [availableModels, setAvailableModels] = useState<AiModel[]>([]);
@@ -33,14 +64,49 @@ const AiModelSelector: React.FC = () => {
   const [ollamaAvailable, setOllamaAvailable] = useState(false);
   const [pullProgress, setPullProgress] = useState<Map<string, PullProgress>>(new Map());
   const [recommendedModel, setRecommendedModel] = useState<string>("");
+  const [storageInfo, setStorageInfo] = useState<OllamaStorageInfo | null>(null);
+  const [benchmarks, setBenchmarks] = useState<Map<string, AiBenchmarkReport>>(new Map());
+  const [benchmarkingModel, setBenchmarkingModel] = useState<string | null>(null);
   const dropdownRef = useRef<HTMLDivElement>(null);
+  // Installed models, read by listeners registered before they were loaded
+  const downloadedRef = useRef<string[]>([]);
 
   const selectedModel = getSetting("ai_selected_model") || "";
 
+  const clearPullProgress = (modelId: string) => {
+    setPullProgress((prev) => {
+      const newMap = new Map(prev);
+      newMap.delete(modelId);
+      return newMap;
+    });
+  };
+
   useEffect(() => {
     loadModels();
     checkOllama();
     loadRecommendation();
+    loadPullQueue();
+    loadResumablePulls();
+    loadBenchmarks();
+
+    // Listen for downloads interrupted by the last shutdown
+    const resumableUnlisten = listen<AiPullRecord[]>("ai-model-pull-resumable", (event) => {
+      event.payload.forEach(offerResume);
+    });
+
+    // Listen for downloads joining the queue
+    const queuedUnlisten = listen<AiPullQueueItem>("ai-model-pull-queued", (event) => {
+      const item = event.payload;
+      setPullProgress((prev) => {
+        const newMap = new Map(prev);
+        newMap.set(item.model_id, queuedProgress(item));
+        return newMap;
+      });
+    });
+
+    const cancelledUnlisten = listen<AiPullQueueItem>("ai-model-pull-cancelled", (event) => {
+      clearPullProgress(event.payload.model_id);
+    });
 
     // Listen for pull progress
     const progressUnlisten = listen<PullProgress>("ai-model-pull-progress", (event) => {
@@ -54,16 +120,37 @@ const AiModelSelector: React.FC = () => {
     });
 
     // Listen for pull completion
-    const completeUnlisten = listen<string>("ai-model-pull-complete", (event) => {
-      const modelId = event.payload;
-      setPullProgress((prev) => {
-        const newMap = new Map(prev);
-        newMap.delete(modelId);
-        return newMap;
-      });
+    const completeUnlisten = listen<PullFinished>("ai-model-pull-complete", (event) => {
+      const modelId = event.payload.model_id;
+      clearPullProgress(modelId);
+      // Updating an installed model leaves the selection alone
+      const wasInstalled = downloadedRef.current.includes(modelId);
       loadModels();
+      if (wasInstalled) return;
       toast.success(`${modelId} downloaded successfully!`);
-      setModelStatus("ready");
+      handleModelSelect(modelId);
+    });
+
+    const errorUnlisten = listen<PullFinished>("ai-model-pull-error", (event) => {
+      const { model_id, error } = event.payload;
+      clearPullProgress(model_id);
+      toast.error(error || `Failed to pull ${model_id}`);
+      setModelStatus("error");
+    });
+
+    // Listen for stalled pulls (connection lost mid-download)
+    const stalledUnlisten = listen<PullStalled>("ai-model-pull-stalled", (event) => {
+      const { model_id, retrying } = event.payload;
+      if (retrying) {
+        toast.warning(`Connection lost while pulling ${model_id}, retrying...`);
+      } else {
+        toast.error(`Pulling ${model_id} stalled. Retry to resume the download.`);
+      }
+    });
+
+    // Listen for the model being measured by "Benchmark All"
+    const benchmarkUnlisten = listen<AiBenchmarkProgress>("ai-benchmark-progress", (event) => {
+      setBenchmarkingModel(event.payload.model);
     });
 
     // Click outside to close dropdown
@@ -80,11 +167,119 @@ const AiModelSelector: React.FC = () => {
     
     return () => {
       document.removeEventListener("mousedown", handleClickOutside);
+      resumableUnlisten.then((fn) => fn());
+      queuedUnlisten.then((fn) => fn());
+      cancelledUnlisten.then((fn) => fn());
       progressUnlisten.then((fn) => fn());
       completeUnlisten.then((fn) => fn());
+      errorUnlisten.then((fn) => fn());
+      stalledUnlisten.then((fn) => fn());
+      benchmarkUnlisten.then((fn) => fn());
     };
   }, []);
 
+  // Re-attach to downloads started before this window was opened
+  const loadPullQueue = async () => {
+    const [queue, active] = await Promise.all([
+      commands.getAiPullQueue(),
+      commands.getActiveModelPulls(),
+    ]);
+    if (queue.status !== "ok" || queue.data.length === 0) return;
+
+    const progress = new Map(
+      queue.data.map((item) => [item.model_id, queuedProgress(item)]),
+    );
+    if (active.status === "ok") {
+      for (const pull of active.data) {
+        progress.set(pull.model_id, {
+          ...pull,
+          completed: pull.completed === null ? null : Number(pull.completed),
+          total: pull.total === null ? null : Number(pull.total),
+        });
+      }
+    }
+    setPullProgress(progress);
+    setModelStatus("pulling");
+  };
+
+  const loadResumablePulls = async () => {
+    const result = await commands.getResumableModelPulls();
+    if (result.status === "ok") {
+      result.data.forEach(offerResume);
+    }
+  };
+
+  const offerResume = (record: AiPullRecord) => {
+    const progress =
+      record.completed && record.total
+        ? ` (${Math.round((Number(record.completed) / Number(record.total)) * 100)}% done)`
+        : "";
+    // Keyed by model so the startup event and the initial fetch share one toast
+    toast.info(`Download of ${record.model_id} was interrupted${progress}`, {
+      id: `resume-pull-${record.model_id}`,
+      duration: Infinity,
+      action: {
+        label: "Resume",
+        onClick: async () => {
+          const result = await commands.resumeModelPull(record.model_id);
+          if (result.status === "error") {
+            toast.error(result.error);
+          } else if (result.data === null) {
+            toast.success(`${record.model_id} is already installed`);
+            loadModels();
+          }
+        },
+      },
+      cancel: {
+        label: "Dismiss",
+        onClick: () => {
+          commands.dismissModelPull(record.model_id);
+        },
+      },
+    });
+  };
+
+  const addBenchmarks = (reports: AiBenchmarkReport[]) => {
+    setBenchmarks((prev) => {
+      const newMap = new Map(prev);
+      reports.forEach((report) => newMap.set(report.model, report));
+      return newMap;
+    });
+  };
+
+  const loadBenchmarks = async () => {
+    const result = await commands.getAiBenchmarks();
+    if (result.status === "ok") {
+      addBenchmarks(result.data);
+    }
+  };
+
+  const handleModelBenchmark = async (modelId: string) => {
+    setBenchmarkingModel(modelId);
+    const result = await commands.benchmarkAiModel(modelId);
+    setBenchmarkingModel(null);
+    if (result.status === "ok") {
+      addBenchmarks([result.data]);
+      toast.success(
+        `${modelId}: ${Math.round(result.data.tokens_per_second)} tokens/s, ${(result.data.average_latency_ms / 1000).toFixed(1)}s per dictation`,
+      );
+    } else {
+      toast.error(result.error);
+    }
+  };
+
+  const handleBenchmarkAll = async () => {
+    setBenchmarkingModel("");
+    const result = await commands.benchmarkAllInstalledModels();
+    setBenchmarkingModel(null);
+    if (result.status === "ok") {
+      addBenchmarks(result.data);
+      toast.success(`Benchmarked ${result.data.length} models`);
+    } else {
+      toast.error(result.error);
+    }
+  };
+
   const loadRecommendation = async () => {
     try {
       const result = await commands.getRecommendedAiModel();
@@ -124,10 +319,16 @@ const AiModelSelector: React.FC = () => {
       }
 
       // Get downloaded models from Ollama
-      const downloadedResult = await commands.listOllamaModels();
+      const downloadedResult = await commands.listOllamaModels(null);
       if (downloadedResult.status === "ok") {
+        downloadedRef.current = downloadedResult.data;
         setDownloadedModels(downloadedResult.data);
       }
+
+      const storageResult = await commands.getOllamaStorageInfo(null);
+      if (storageResult.status === "ok") {
+        setStorageInfo(storageResult.data);
+      }
     } catch (err) {
       console.error("Failed to load AI models:", err);
     }
@@ -145,31 +346,35 @@ const AiModelSelector: React.FC = () => {
   };
 
   const handleModelPull = async (modelId: string) => {
+    // Free space is only known when Ollama runs on this machine
+    const model = availableModels.find((m) => m.id === modelId);
+    if (model && storageInfo?.free_bytes != null) {
+      const freeMb = Number(storageInfo.free_bytes) / 1024 ** 2;
+      if (freeMb < model.size_mb) {
+        const confirmed = confirm(
+          `${modelId} needs about ${model.size_mb}MB but only ${Math.round(freeMb)}MB is free. Pull anyway?`,
+        );
+        if (!confirmed) return;
+      }
+    }
+
     try {
-      setModelStatus("pulling");
-      toast.info(`Pulling ${modelId}... This may take a few minutes.`);
-      
-      const result = await commands.pullOllamaModel(modelId);
+      const result = await commands.pullOllamaModel(modelId, null);
       if (result.status === "ok") {
-        // Success toast and model selection handled by event listener
-        await handleModelSelect(modelId);
+        // Progress, completion and model selection are handled by event listeners
+        toast.info(`Queued ${modelId} for download. This may take a few minutes.`);
       } else {
         toast.error(result.error || "Failed to pull model");
-        setModelStatus("error");
-        setPullProgress((prev) => {
-          const newMap = new Map(prev);
-          newMap.delete(modelId);
-          return newMap;
-        });
       }
     } catch (err) {
       toast.error("Failed to pull model");
-      setModelStatus("error");
-      setPullProgress((prev) => {
-        const newMap = new Map(prev);
-        newMap.delete(modelId);
-        return newMap;
-      });
+    }
+  };
+
+  const handlePullCancel = async (pullId: string) => {
+    const result = await commands.cancelQueuedPull(pullId);
+    if (result.status === "error") {
+      toast.error(result.error);
     }
   };
 
@@ -178,7 +383,7 @@ const AiModelSelector: React.FC = () => {
     if (!confirmed) return;
 
     try {
-      const result = await commands.deleteOllamaModel(modelId);
+      const result = await commands.deleteOllamaModel(modelId, null);
       if (result.status === "ok") {
         toast.success(`${modelId} deleted`);
         await loadModels();
@@ -238,9 +443,15 @@ const AiModelSelector: React.FC = () => {
           selectedModel={selectedModel}
           recommendedModel={recommendedModel}
           pullProgress={pullProgress}
+          storageInfo={storageInfo}
+          benchmarks={benchmarks}
+          benchmarkingModel={benchmarkingModel}
           onModelSelect={handleModelSelect}
           onModelPull={handleModelPull}
           onModelDelete={handleModelDelete}
+          onPullCancel={handlePullCancel}
+          onModelBenchmark={handleModelBenchmark}
+          onBenchmarkAll={handleBenchmarkAll}
         />
       )}
     </div>
@@ -0,0 +1,99 @@
+import React, { useState } from "react";
+import { toast } from "sonner";
+import { commands, type AiModelUpdateCheck } from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Button } from "../../ui/Button";
+import { SettingContainer } from "../../ui/SettingContainer";
+import { ToggleSwitch } from "../../ui/ToggleSwitch";
+
+export const AiModelUpdates: React.FC = () => {
+  const { getSetting, updateSetting, isUpdating } = useSettings();
+  const [checks, setChecks] = useState<AiModelUpdateCheck[] | null>(null);
+  const [isChecking, setIsChecking] = useState(false);
+
+  const handleCheck = async () => {
+    setIsChecking(true);
+    try {
+      const result = await commands.checkAiModelUpdates();
+      if (result.status === "error") {
+        toast.error(result.error);
+        return;
+      }
+      setChecks(result.data);
+    } finally {
+      setIsChecking(false);
+    }
+  };
+
+  const handleUpdate = async (model: string) => {
+    const result = await commands.updateAiModel(model);
+    if (result.status === "error") {
+      toast.error(result.error);
+      return;
+    }
+    // Progress and the outcome are reported by event listeners
+    toast.info(`Queued the update of ${model}`);
+    setChecks((prev) => prev?.filter((check) => check.model !== model) ?? null);
+  };
+
+  const updates = checks?.filter((check) => check.update_available) ?? [];
+  const failures = checks?.filter((check) => check.error) ?? [];
+
+  return (
+    <>
+      <ToggleSwitch
+        checked={getSetting("ai_check_model_updates") ?? true}
+        onChange={(enabled) => updateSetting("ai_check_model_updates", enabled)}
+        isUpdating={isUpdating("ai_check_model_updates")}
+        label="Check for Model Updates"
+        description="Ask the registry for newer versions of your installed models at startup. Turn off on metered connections."
+        descriptionMode="tooltip"
+        grouped={true}
+        tooltipPosition="bottom"
+      />
+
+      <SettingContainer
+        title="Model Updates"
+        description="Compare your installed models with their registry and download newer versions"
+        descriptionMode="tooltip"
+        grouped={true}
+        layout="stacked"
+      >
+        <div className="flex flex-col gap-2">
+          <div className="flex items-center gap-2">
+            <Button
+              variant="secondary"
+              size="sm"
+              onClick={handleCheck}
+              disabled={isChecking}
+            >
+              {isChecking ? "Checking..." : "Check Now"}
+            </Button>
+            {checks && updates.length === 0 && failures.length === 0 && (
+              <span className="text-xs text-mid-gray">
+                All installed models are up to date
+              </span>
+            )}
+          </div>
+          {updates.map((check) => (
+            <div key={check.model} className="flex items-center gap-2 text-sm">
+              <span className="flex-1">{check.model} has an update</span>
+              <Button
+                variant="secondary"
+                size="sm"
+                onClick={() => handleUpdate(check.model)}
+              >
+                Update
+              </Button>
+            </div>
+          ))}
+          {failures.map((check) => (
+            <p key={check.model} className="text-xs text-red-400">
+              {check.model}: {check.error}
+            </p>
+          ))}
+        </div>
+      </SettingContainer>
+    </>
+  );
+};
@@ -0,0 +1,137 @@
+import React, { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { commands, type AiEndpointChanged, type AiOllamaEndpoint } from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Button } from "../../ui/Button";
+import { Input } from "../../ui/Input";
+import { SettingContainer } from "../../ui/SettingContainer";
+
+const DEFAULT_ENDPOINTS: AiOllamaEndpoint[] = [
+  { base_url: "http://localhost:11434", preferred_model: null },
+];
+
+interface AiOllamaEndpointsProps {
+  onChanged?: () => void;
+}
+
+export const AiOllamaEndpoints: React.FC<AiOllamaEndpointsProps> = ({
+  onChanged,
+}) => {
+  const { getSetting, refreshSettings } = useSettings();
+  const endpoints = getSetting("ai_ollama_endpoints") ?? DEFAULT_ENDPOINTS;
+  const [localEndpoints, setLocalEndpoints] = useState(endpoints);
+  const [activeEndpoint, setActiveEndpoint] = useState<string | null>(null);
+
+  useEffect(() => {
+    setLocalEndpoints(endpoints);
+  }, [endpoints]);
+
+  useEffect(() => {
+    const unlisten = listen<AiEndpointChanged>(
+      "ai-endpoint-changed",
+      (event) => {
+        setActiveEndpoint(event.payload.base_url);
+        if (event.payload.previous && event.payload.base_url) {
+          toast.info(`Switched Ollama host to ${event.payload.base_url}`);
+        }
+      },
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const save = async (next: AiOllamaEndpoint[]) => {
+    if (JSON.stringify(next) === JSON.stringify(endpoints)) return;
+
+    const result = await commands.changeAiOllamaEndpoints(next);
+    if (result.status === "error") {
+      toast.error(result.error);
+      setLocalEndpoints(endpoints);
+      return;
+    }
+    await refreshSettings();
+    onChanged?.();
+  };
+
+  const updateEndpoint = (index: number, patch: Partial<AiOllamaEndpoint>) => {
+    setLocalEndpoints((current) =>
+      current.map((endpoint, i) =>
+        i === index ? { ...endpoint, ...patch } : endpoint,
+      ),
+    );
+  };
+
+  const removeEndpoint = (index: number) => {
+    const next = localEndpoints.filter((_, i) => i !== index);
+    setLocalEndpoints(next);
+    save(next);
+  };
+
+  const addEndpoint = () => {
+    setLocalEndpoints([
+      ...localEndpoints,
+      { base_url: "", preferred_model: null },
+    ]);
+  };
+
+  return (
+    <SettingContainer
+      title="Ollama Hosts"
+      description="Ollama servers tried in order; the first one that responds is used. Accepts http(s):// or unix:///path/to/ollama.sock"
+      descriptionMode="tooltip"
+      grouped={true}
+      layout="stacked"
+    >
+      <div className="space-y-2">
+        {localEndpoints.map((endpoint, index) => (
+          <div key={index} className="flex items-center gap-2">
+            <span
+              className={`w-2 h-2 rounded-full ${
+                endpoint.base_url === activeEndpoint
+                  ? "bg-green-500"
+                  : "bg-mid-gray/40"
+              }`}
+            />
+            <Input
+              type="text"
+              value={endpoint.base_url}
+              onChange={(event) =>
+                updateEndpoint(index, { base_url: event.target.value })
+              }
+              onBlur={() => endpoint.base_url.trim() && save(localEndpoints)}
+              placeholder="http://localhost:11434"
+              variant="compact"
+              className="flex-1 min-w-[200px]"
+            />
+            <Input
+              type="text"
+              value={endpoint.preferred_model ?? ""}
+              onChange={(event) =>
+                updateEndpoint(index, {
+                  preferred_model: event.target.value || null,
+                })
+              }
+              onBlur={() => endpoint.base_url.trim() && save(localEndpoints)}
+              placeholder="Preferred model (optional)"
+              variant="compact"
+              className="w-[160px]"
+            />
+            <Button
+              variant="ghost"
+              size="sm"
+              onClick={() => removeEndpoint(index)}
+              disabled={localEndpoints.length <= 1}
+            >
+              Remove
+            </Button>
+          </div>
+        ))}
+        <Button variant="secondary" size="sm" onClick={addEndpoint}>
+          Add host
+        </Button>
+      </div>
+    </SettingContainer>
+  );
+};
@@ -0,0 +1,68 @@
+import React, { useEffect, useState } from "react";
+import { commands } from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Dropdown } from "../../ui/Dropdown";
+import { SettingContainer } from "../../ui/SettingContainer";
+import { ToggleSwitch } from "../../ui/ToggleSwitch";
+
+const SELECTED = "";
+
+export const AiReenhanceSetting: React.FC = () => {
+  const { getSetting, updateSetting } = useSettings();
+  const [models, setModels] = useState<string[]>([]);
+
+  const overrides = getSetting("ai_reenhance") ?? {
+    model: null,
+    fix_grammar: true,
+  };
+
+  useEffect(() => {
+    const loadModels = async () => {
+      const result = await commands.listOllamaModels(null);
+      if (result.status === "ok") {
+        setModels(result.data);
+      }
+    };
+    loadModels();
+  }, []);
+
+  const options = [
+    { value: SELECTED, label: "Selected model" },
+    ...models.map((model) => ({ value: model, label: model })),
+  ];
+
+  return (
+    <>
+      <SettingContainer
+        title="Enhance Again With"
+        description="Model the Enhance Again shortcut runs over the text pasted last"
+        descriptionMode="tooltip"
+        grouped={true}
+      >
+        <Dropdown
+          options={options}
+          selectedValue={overrides.model ?? SELECTED}
+          onSelect={(value) =>
+            updateSetting("ai_reenhance", {
+              ...overrides,
+              model: value === SELECTED ? null : value,
+            })
+          }
+        />
+      </SettingContainer>
+
+      <ToggleSwitch
+        checked={overrides.fix_grammar ?? true}
+        onChange={(enabled) =>
+          updateSetting("ai_reenhance", { ...overrides, fix_grammar: enabled })
+        }
+        isUpdating={false}
+        label="Fix Grammar When Enhancing Again"
+        description="Have the Enhance Again shortcut fix grammar too, even when your dictations are only corrected"
+        descriptionMode="tooltip"
+        grouped={true}
+        tooltipPosition="bottom"
+      />
+    </>
+  );
+};
@@ -0,0 +1,245 @@
+import React, { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { Check, Loader2, Trash2, X } from "lucide-react";
+import {
+  commands,
+  type AiTestSample,
+  type AiTestSampleResult,
+  type AiTestSuiteProgress,
+  type AiTestSuiteReport,
+} from "@/bindings";
+import { describeEnhancementError } from "../../../lib/utils/enhancementError";
+import { Button } from "../../ui/Button";
+import { Input } from "../../ui/Input";
+import { SettingContainer } from "../../ui/SettingContainer";
+import { Textarea } from "../../ui/Textarea";
+
+const splitList = (value: string): string[] =>
+  value
+    .split(",")
+    .map((item) => item.trim())
+    .filter((item) => item.length > 0);
+
+const SampleResult: React.FC<{ result: AiTestSampleResult }> = ({ result }) => (
+  <div className="p-2 bg-mid-gray/10 rounded border border-mid-gray/20 space-y-1">
+    <div className="flex items-center justify-between">
+      <p className="flex items-center gap-1 text-xs font-medium">
+        {result.passed ? (
+          <Check className="w-3 h-3 text-green-400" />
+        ) : (
+          <X className="w-3 h-3 text-red-400" />
+        )}
+        {result.name}
+      </p>
+      <p className="text-xs text-mid-gray">
+        {(result.latency_ms / 1000).toFixed(1)}s
+      </p>
+    </div>
+    {result.error ? (
+      <p className="text-sm text-red-400">
+        {describeEnhancementError(result.error)}
+      </p>
+    ) : result.diff ? (
+      <p className="text-sm whitespace-pre-wrap">
+        {result.diff.map((op, i) =>
+          op.kind === "insert" ? (
+            <span key={i} className="bg-green-500/20 text-green-400">
+              {op.text}
+            </span>
+          ) : op.kind === "delete" ? (
+            <span key={i} className="bg-red-500/20 text-red-400 line-through">
+              {op.text}
+            </span>
+          ) : (
+            <span key={i}>{op.text}</span>
+          ),
+        )}
+      </p>
+    ) : (
+      <p className="text-sm whitespace-pre-wrap">{result.output}</p>
+    )}
+    {result.failures.length > 0 && (
+      <p className="text-xs text-red-400">{result.failures.join(", ")}</p>
+    )}
+  </div>
+);
+
+export const AiTestSuitePanel: React.FC = () => {
+  const [samples, setSamples] = useState<AiTestSample[]>([]);
+  const [report, setReport] = useState<AiTestSuiteReport | null>(null);
+  const [progress, setProgress] = useState<AiTestSuiteProgress | null>(null);
+  const [isRunning, setIsRunning] = useState(false);
+  const [name, setName] = useState("");
+  const [text, setText] = useState("");
+  const [expected, setExpected] = useState("");
+  const [unexpected, setUnexpected] = useState("");
+
+  useEffect(() => {
+    const loadSamples = async () => {
+      const result = await commands.listAiTestSamples();
+      if (result.status === "ok") {
+        setSamples(result.data);
+      }
+    };
+    loadSamples();
+
+    // Listen for the sample being run
+    const progressUnlisten = listen<AiTestSuiteProgress>(
+      "ai-test-suite-progress",
+      (event) => {
+        setProgress(event.payload);
+      },
+    );
+
+    return () => {
+      progressUnlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleRun = async () => {
+    setIsRunning(true);
+    setReport(null);
+    try {
+      const result = await commands.runAiEnhancementTestSuite();
+      if (result.status === "ok") {
+        setReport(result.data);
+      } else {
+        toast.error(describeEnhancementError(result.error));
+      }
+    } finally {
+      setIsRunning(false);
+      setProgress(null);
+    }
+  };
+
+  const handleAdd = async () => {
+    const result = await commands.addAiTestSample({
+      name,
+      text,
+      expected: splitList(expected),
+      unexpected: splitList(unexpected),
+    });
+    if (result.status === "ok") {
+      setSamples(result.data);
+      setName("");
+      setText("");
+      setExpected("");
+      setUnexpected("");
+    } else {
+      toast.error(result.error);
+    }
+  };
+
+  const handleRemove = async (sampleName: string) => {
+    const result = await commands.removeAiTestSample(sampleName);
+    if (result.status === "ok") {
+      setSamples(result.data);
+    } else {
+      toast.error(result.error);
+    }
+  };
+
+  return (
+    <SettingContainer
+      title="Test Suite"
+      description="Run sample transcripts for each enabled feature, and your own, through the selected model and check what comes out"
+      grouped={true}
+      layout="stacked"
+    >
+      <div className="space-y-3">
+        <div className="flex items-center justify-between">
+          <p className="text-xs text-mid-gray">
+            {report
+              ? `${report.passed} of ${report.results.length} samples passed with ${report.model}`
+              : `Built-in samples plus ${samples.length} of your own`}
+          </p>
+          <Button
+            onClick={handleRun}
+            disabled={isRunning}
+            variant="secondary"
+            size="md"
+          >
+            {isRunning ? (
+              <>
+                <Loader2 className="w-4 h-4 mr-2 animate-spin" />
+                {progress
+                  ? `${progress.name} (${progress.index}/${progress.total})`
+                  : "Running..."}
+              </>
+            ) : (
+              "Run Test Suite"
+            )}
+          </Button>
+        </div>
+        {report && (
+          <div className="space-y-2">
+            {report.results.map((result, i) => (
+              <SampleResult key={i} result={result} />
+            ))}
+          </div>
+        )}
+        {samples.length > 0 && (
+          <div className="space-y-1">
+            {samples.map((sample) => (
+              <div
+                key={sample.name}
+                className="flex items-center justify-between text-xs"
+              >
+                <span className="truncate">
+                  <span className="font-medium">{sample.name}</span>{" "}
+                  <span className="text-mid-gray">{sample.text}</span>
+                </span>
+                <button
+                  onClick={() => handleRemove(sample.name)}
+                  className="text-mid-gray hover:text-red-400"
+                  title="Remove sample"
+                >
+                  <Trash2 className="w-3 h-3" />
+                </button>
+              </div>
+            ))}
+          </div>
+        )}
+        <div className="space-y-2">
+          <Input
+            value={name}
+            onChange={(e) => setName(e.target.value)}
+            placeholder="Sample name"
+            variant="compact"
+          />
+          <Textarea
+            value={text}
+            onChange={(e) => setText(e.target.value)}
+            placeholder="Transcript as dictated..."
+            rows={2}
+          />
+          <div className="flex items-center gap-2">
+            <Input
+              value={expected}
+              onChange={(e) => setExpected(e.target.value)}
+              placeholder="Must contain, comma separated"
+              variant="compact"
+              className="flex-1"
+            />
+            <Input
+              value={unexpected}
+              onChange={(e) => setUnexpected(e.target.value)}
+              placeholder="Must not contain, comma separated"
+              variant="compact"
+              className="flex-1"
+            />
+            <Button
+              onClick={handleAdd}
+              disabled={!name.trim() || !text.trim()}
+              variant="secondary"
+              size="sm"
+            >
+              Add Sample
+            </Button>
+          </div>
+        </div>
+      </div>
+    </SettingContainer>
+  );
+};
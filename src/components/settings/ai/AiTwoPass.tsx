@@ -0,0 +1,74 @@
+import React, { useEffect, useState } from "react";
+import { commands } from "@/bindings";
+import { useSettings } from "../../../hooks/useSettings";
+import { Dropdown } from "../../ui/Dropdown";
+import { SettingContainer } from "../../ui/SettingContainer";
+
+const OFF = "";
+
+export const AiTwoPassSetting: React.FC = () => {
+  const { getSetting, updateSetting } = useSettings();
+  const [models, setModels] = useState<string[]>([]);
+
+  const twoPass = getSetting("ai_two_pass") ?? null;
+  const [fastModel, setFastModel] = useState(twoPass?.fast_model ?? OFF);
+  const [qualityModel, setQualityModel] = useState(
+    twoPass?.quality_model ?? OFF,
+  );
+
+  useEffect(() => {
+    const loadModels = async () => {
+      const result = await commands.listOllamaModels(null);
+      if (result.status === "ok") {
+        setModels(result.data);
+      }
+    };
+    loadModels();
+  }, []);
+
+  // Saved once both models are picked, and turned off with either
+  const choose = (fast: string, quality: string) => {
+    setFastModel(fast);
+    setQualityModel(quality);
+    if (fast === OFF || quality === OFF) {
+      if (twoPass) updateSetting("ai_two_pass", null);
+    } else if (fast !== quality) {
+      updateSetting("ai_two_pass", {
+        fast_model: fast,
+        quality_model: quality,
+      });
+    }
+  };
+
+  const options = [
+    { value: OFF, label: "Off" },
+    ...models.map((model) => ({ value: model, label: model })),
+  ];
+
+  return (
+    <SettingContainer
+      title="Two-Pass Enhancement"
+      description="Paste a fast model's correction right away, then let a better model correct the dictation again in the background and offer its text when it differs. Dictating again cancels it."
+      descriptionMode="tooltip"
+      grouped={true}
+    >
+      <div className="flex items-center gap-2">
+        <Dropdown
+          options={options}
+          selectedValue={fastModel}
+          onSelect={(value) => choose(value, qualityModel)}
+          placeholder="Fast model..."
+        />
+        <span className="text-xs text-mid-gray">then</span>
+        <Dropdown
+          options={options.filter(
+            (option) => option.value === OFF || option.value !== fastModel,
+          )}
+          selectedValue={qualityModel}
+          onSelect={(value) => choose(fastModel, value)}
+          placeholder="Quality model..."
+        />
+      </div>
+    </SettingContainer>
+  );
+};
@@ -0,0 +1,146 @@
+import React, { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import {
+  commands,
+  type AiUnusedModelDeleted,
+  type AiUnusedModels as AiUnusedModelsReport,
+} from "@/bindings";
+import { formatModelSize } from "../../../lib/utils/format";
+import { Button } from "../../ui/Button";
+import { Dropdown } from "../../ui/Dropdown";
+import { SettingContainer } from "../../ui/SettingContainer";
+
+const windowOptions = [
+  { value: "7", label: "7 days" },
+  { value: "30", label: "30 days" },
+  { value: "90", label: "90 days" },
+];
+
+const formatBytes = (bytes: string) => formatModelSize(Number(bytes) / 1024 ** 2);
+
+export const AiUnusedModels: React.FC = () => {
+  const [days, setDays] = useState("30");
+  const [report, setReport] = useState<AiUnusedModelsReport | null>(null);
+  const [isBusy, setIsBusy] = useState(false);
+
+  useEffect(() => {
+    // Deleted one by one, so a failure doesn't hide the rest
+    const deletedUnlisten = listen<AiUnusedModelDeleted>(
+      "ai-unused-model-deleted",
+      (event) => {
+        const { model, error } = event.payload;
+        if (error) {
+          toast.error(`Couldn't delete ${model}: ${error}`);
+        } else {
+          toast.success(`${model} deleted`);
+        }
+      },
+    );
+    return () => {
+      deletedUnlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleFind = async () => {
+    setIsBusy(true);
+    try {
+      const result = await commands.getUnusedAiModels(Number(days));
+      if (result.status === "error") {
+        toast.error(result.error);
+        return;
+      }
+      setReport(result.data);
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  const handleDelete = async () => {
+    if (!report) return;
+    const confirmed = confirm(
+      `Delete ${report.models.map((m) => m.model).join(", ")}? This will remove them from Ollama.`,
+    );
+    if (!confirmed) return;
+
+    setIsBusy(true);
+    try {
+      const result = await commands.deleteUnusedAiModels(Number(days));
+      if (result.status === "error") {
+        toast.error(result.error);
+      }
+      setReport(null);
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  // Shared blobs are only known for an Ollama on this machine
+  const savings = report
+    ? report.reclaimable_bytes !== null
+      ? formatBytes(report.reclaimable_bytes)
+      : `up to ${formatBytes(
+          String(report.models.reduce((sum, m) => sum + Number(m.size_bytes), 0)),
+        )}`
+    : "";
+
+  return (
+    <SettingContainer
+      title="Unused Models"
+      description="Find installed models you haven't dictated or benchmarked with lately and delete them. The selected model is always kept."
+      descriptionMode="tooltip"
+      grouped={true}
+      layout="stacked"
+    >
+      <div className="flex flex-col gap-2">
+        <div className="flex items-center gap-2">
+          <span className="text-sm">Not used in</span>
+          <Dropdown
+            options={windowOptions}
+            selectedValue={days}
+            onSelect={(value) => {
+              setDays(value);
+              setReport(null);
+            }}
+          />
+          <Button
+            variant="secondary"
+            size="sm"
+            onClick={handleFind}
+            disabled={isBusy}
+          >
+            Find
+          </Button>
+        </div>
+        {report && report.models.length === 0 && (
+          <span className="text-xs text-mid-gray">
+            Every installed model was used in the last {days} days
+          </span>
+        )}
+        {report && report.models.length > 0 && (
+          <>
+            {report.models.map((model) => (
+              <div key={model.model} className="flex items-center gap-2 text-sm">
+                <span className="flex-1">{model.model}</span>
+                <span className="text-xs text-mid-gray">
+                  {formatBytes(model.size_bytes)} • last used{" "}
+                  {new Date(Number(model.last_used) * 1000).toLocaleDateString()}
+                </span>
+              </div>
+            ))}
+            <div>
+              <Button
+                variant="secondary"
+                size="sm"
+                onClick={handleDelete}
+                disabled={isBusy}
+              >
+                Delete {report.models.length} (frees {savings})
+              </Button>
+            </div>
+          </>
+        )}
+      </div>
+    </SettingContainer>
+  );
+};
@@ -0,0 +1,118 @@
+import React, { useEffect, useState } from "react";
+import { toast } from "sonner";
+import {
+  commands,
+  type AiFallbackCounts,
+  type AiUsageCounts,
+  type AiUsageRange,
+  type AiUsageStats,
+} from "@/bindings";
+import { Button } from "../../ui/Button";
+import { Dropdown } from "../../ui/Dropdown";
+import { SettingContainer } from "../../ui/SettingContainer";
+
+const RANGE_OPTIONS: { value: AiUsageRange; label: string }[] = [
+  { value: "week", label: "Last 7 days" },
+  { value: "month", label: "Last 30 days" },
+  { value: "all", label: "Last 90 days" },
+];
+
+const fallbackTotal = (fallbacks: AiFallbackCounts): number =>
+  (fallbacks.timeout ?? 0) +
+  (fallbacks.ollama_unavailable ?? 0) +
+  (fallbacks.model_missing ?? 0) +
+  (fallbacks.failed ?? 0) +
+  (fallbacks.rejected_output ?? 0) +
+  (fallbacks.aborted ?? 0);
+
+const failedRuns = (counts: AiUsageCounts): number =>
+  fallbackTotal(counts.fallbacks ?? {}) + (counts.errors ?? 0);
+
+const dayRuns = (counts: AiUsageCounts): number =>
+  (counts.enhanced ?? 0) + (counts.skipped ?? 0) + failedRuns(counts);
+
+export const AiUsageStatsPanel: React.FC = () => {
+  const [range, setRange] = useState<AiUsageRange>("week");
+  const [stats, setStats] = useState<AiUsageStats | null>(null);
+
+  const loadStats = async (selected: AiUsageRange) => {
+    const result = await commands.getAiUsageStats(selected);
+    if (result.status === "ok") {
+      setStats(result.data);
+    }
+  };
+
+  useEffect(() => {
+    loadStats(range);
+  }, [range]);
+
+  const handleClear = async () => {
+    const result = await commands.clearAiUsageStats();
+    if (result.status === "ok") {
+      await loadStats(range);
+    } else {
+      toast.error(result.error);
+    }
+  };
+
+  const busiest = Math.max(
+    1,
+    ...(stats?.days ?? []).map((day) => dayRuns(day.counts)),
+  );
+  const total = stats?.total;
+
+  return (
+    <SettingContainer
+      title="Usage"
+      description="What came of each day's dictations. Counted on this device only, and never sent anywhere."
+      grouped={true}
+      layout="stacked"
+    >
+      <div className="space-y-3">
+        <div className="flex items-center justify-between gap-2">
+          <Dropdown
+            options={RANGE_OPTIONS}
+            selectedValue={range}
+            onSelect={(value) => setRange(value as AiUsageRange)}
+          />
+          <Button onClick={handleClear} variant="secondary" size="sm">
+            Clear
+          </Button>
+        </div>
+        {stats && (
+          <div className="flex items-end gap-px h-24">
+            {stats.days.map((day) => {
+              const runs = dayRuns(day.counts);
+              const failed = failedRuns(day.counts);
+              return (
+                <div
+                  key={day.date}
+                  className="flex-1 flex flex-col justify-end h-full"
+                  title={`${day.date}: ${day.counts.enhanced ?? 0} enhanced, ${failed} fell back, ${day.counts.skipped ?? 0} skipped`}
+                >
+                  <div
+                    className="bg-red-400/70"
+                    style={{ height: `${(failed / busiest) * 100}%` }}
+                  />
+                  <div
+                    className="bg-logo-primary/70"
+                    style={{ height: `${((runs - failed) / busiest) * 100}%` }}
+                  />
+                </div>
+              );
+            })}
+          </div>
+        )}
+        {total && (
+          <p className="text-xs text-mid-gray">
+            {`${total.enhanced ?? 0} enhanced, ${failedRuns(total)} fell back, ${total.skipped ?? 0} skipped, ${total.cache_hits ?? 0} from the cache`}
+            {(total.timed_runs ?? 0) > 0 &&
+              `, ${((total.average_latency_ms ?? 0) / 1000).toFixed(1)}s on average`}
+            {(total.runs_after_start ?? 0) > 0 &&
+              `. ${total.failures_after_start ?? 0} of ${total.runs_after_start} right after startup fell back.`}
+          </p>
+        )}
+      </div>
+    </SettingContainer>
+  );
+};
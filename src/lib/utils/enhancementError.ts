@@ -0,0 +1,30 @@
+import type { EnhancementError, OutputCheck } from "@/bindings";
+
+const rejectedBy: Record<OutputCheck, string> = {
+  length: "its length was far off from the text",
+  echo: "it only repeated the prompt",
+  placeholders: "it lost links or code from the text",
+  empty: "it was empty",
+  overlap: "it didn't keep the words of the text",
+};
+
+export const describeEnhancementError = (error: EnhancementError): string => {
+  switch (error.kind) {
+    case "disabled":
+      return "AI enhancement is not enabled";
+    case "no_model_selected":
+      return "No AI model selected";
+    case "ollama_unavailable":
+      return "Ollama is not running";
+    case "model_missing":
+      return `${error.model} is not installed`;
+    case "timeout":
+      return "The model took too long to respond";
+    case "output_rejected":
+      return `The output was discarded because ${rejectedBy[error.check]}`;
+    case "cancelled":
+      return "Enhancement was cancelled";
+    case "failed":
+      return error.message;
+  }
+};
@@ -6,15 +6,29 @@ import {
   CancelIcon,
 } from "../components/icons";
 import "./RecordingOverlay.css";
-import { commands } from "@/bindings";
+import {
+  commands,
+  type AiEnhancementComplete,
+  type AiEnhancementFallback,
+  type AiEnhancementPartial,
+} from "@/bindings";
 
 type OverlayState = "recording" | "transcribing";
 
+// Enhancement request ids are "enhance-N" with N increasing per utterance
+const requestNumber = (requestId: string) =>
+  Number(requestId.split("-").pop()) || 0;
+
+// Only the tail of the corrected text fits in the overlay
+const ENHANCED_TEXT_CHARS = 24;
+
 const RecordingOverlay: React.FC = () => {
   const [isVisible, setIsVisible] = useState(false);
   const [state, setState] = useState<OverlayState>("recording");
   const [levels, setLevels] = useState<number[]>(Array(16).fill(0));
   const smoothedLevelsRef = useRef<number[]>(Array(16).fill(0));
+  const [enhancedText, setEnhancedText] = useState("");
+  const latestRequestRef = useRef(0);
 
   useEffect(() => {
     const setupEventListeners = async () => {
@@ -23,13 +37,43 @@ const RecordingOverlay: React.FC = () => {
         const overlayState = event.payload as OverlayState;
         setState(overlayState);
         setIsVisible(true);
+        if (overlayState === "recording") {
+          setEnhancedText("");
+        }
       });
 
       // Listen for hide-overlay event from Rust
       const unlistenHide = await listen("hide-overlay", () => {
         setIsVisible(false);
+        setEnhancedText("");
       });
 
+      // Show AI-corrected text as it streams in, ignoring superseded utterances
+      const showEnhancedText = (requestId: string, text: string) => {
+        const request = requestNumber(requestId);
+        if (request < latestRequestRef.current) return;
+        latestRequestRef.current = request;
+        setEnhancedText(text);
+      };
+
+      const unlistenPartial = await listen<AiEnhancementPartial>(
+        "ai-enhancement-partial",
+        (event) =>
+          showEnhancedText(event.payload.request_id, event.payload.text_so_far),
+      );
+
+      const unlistenComplete = await listen<AiEnhancementComplete>(
+        "ai-enhancement-complete",
+        (event) =>
+          showEnhancedText(event.payload.request_id, event.payload.text),
+      );
+
+      // The original transcript is pasted instead, so drop any streamed text
+      const unlistenFallback = await listen<AiEnhancementFallback>(
+        "ai-enhancement-fallback",
+        (event) => showEnhancedText(event.payload.request_id, ""),
+      );
+
       // Listen for mic-level updates
       const unlistenLevel = await listen<number[]>("mic-level", (event) => {
         const newLevels = event.payload as number[];
@@ -49,6 +93,9 @@ const RecordingOverlay: React.FC = () => {
         unlistenShow();
         unlistenHide();
         unlistenLevel();
+        unlistenPartial();
+        unlistenComplete();
+        unlistenFallback();
       };
     };
 
@@ -83,9 +130,16 @@ const RecordingOverlay: React.FC = () => {
             ))}
           </div>
         )}
-        {state === "transcribing" && (
+        {state === "transcribing" && !enhancedText && (
           <div className="transcribing-text">Transcribing...</div>
         )}
+        {state === "transcribing" && enhancedText && (
+          <div className="enhanced-text" title={enhancedText}>
+            {enhancedText.length > ENHANCED_TEXT_CHARS
+              ? `…${enhancedText.slice(-ENHANCED_TEXT_CHARS)}`
+              : enhancedText}
+          </div>
+        )}
       </div>
 
       <div className="overlay-right">
@@ -99,6 +153,17 @@ const RecordingOverlay: React.FC = () => {
             <CancelIcon />
           </div>
         )}
+        {state === "transcribing" && enhancedText && (
+          <div
+            className="cancel-button"
+            title="Paste the original text"
+            onClick={() => {
+              commands.abortAiEnhancement();
+            }}
+          >
+            <CancelIcon />
+          </div>
+        )}
       </div>
     </div>
   );
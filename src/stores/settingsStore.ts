@@ -1,6 +1,14 @@
 import { create } from "zustand";
 import { subscribeWithSelector } from "zustand/middleware";
-import type { AppSettings as Settings, AudioDevice } from "@/bindings";
+import type {
+  AiGenerationOptions,
+  AiLongTextPolicy,
+  AiOllamaEndpoint,
+  AiReenhanceOverrides,
+  AiTwoPass,
+  AppSettings as Settings,
+  AudioDevice,
+} from "@/bindings";
 import { commands } from "@/bindings";
 
 interface SettingsStore {
@@ -131,6 +139,92 @@ const settingUpdaters: {
     commands.changeAiModel(value as string),
   ai_features: (value) =>
     commands.changeAiFeatures(value as any),
+  ai_pull_stall_timeout_secs: (value) =>
+    commands.changeAiPullStallTimeout(value as string),
+  ai_pull_auto_retry: (value) =>
+    commands.changeAiPullAutoRetry(value as boolean),
+  ai_auto_pull_missing_model: (value) =>
+    commands.changeAiAutoPullMissingModel(value as boolean),
+  ai_check_model_updates: (value) =>
+    commands.changeAiCheckModelUpdates(value as boolean),
+  ai_num_thread: (value) =>
+    commands.changeAiNumThread(value as number | null),
+  ai_gpu_offload: (value) =>
+    commands.changeAiGpuOffload(value as any),
+  ai_main_gpu: (value) =>
+    commands.changeAiMainGpu(value as number | null),
+  ai_ollama_endpoints: (value) =>
+    commands.changeAiOllamaEndpoints(value as AiOllamaEndpoint[]),
+  ai_model_registry_prefix: (value) =>
+    commands.changeAiModelRegistryPrefix(value as string | null),
+  ai_model_registry_insecure: (value) =>
+    commands.changeAiModelRegistryInsecure(value as boolean),
+  ai_chunk_max_tokens: (value) =>
+    commands.changeAiChunkMaxTokens(value as number),
+  ai_fallback_to_original: (value) =>
+    commands.changeAiFallbackToOriginal(value as boolean),
+  ai_offline_fallback: (value) =>
+    commands.changeAiOfflineFallback(value as boolean),
+  ai_enhancement_timeout_ms: (value) =>
+    commands.changeAiEnhancementTimeoutMs(value as number),
+  ai_degrade_after_failures: (value) =>
+    commands.changeAiDegradeAfterFailures(value as number),
+  ai_latency_budget_ms: (value) =>
+    commands.changeAiLatencyBudgetMs(value as number),
+  ai_two_pass: (value) =>
+    commands.changeAiTwoPass(value as AiTwoPass | null),
+  ai_reenhance: (value) =>
+    commands.changeAiReenhance(value as AiReenhanceOverrides),
+  ai_min_words_for_enhancement: (value) =>
+    commands.changeAiMinWordsForEnhancement(value as number),
+  ai_max_words_for_enhancement: (value) =>
+    commands.changeAiMaxWordsForEnhancement(value as number),
+  ai_long_text_policy: (value) =>
+    commands.changeAiLongTextPolicy(value as AiLongTextPolicy),
+  ai_warm_up_on_battery: (value) =>
+    commands.changeAiWarmUpOnBattery(value as boolean),
+  ai_preload_on_startup: (value) =>
+    commands.changeAiPreloadOnStartup(value as boolean),
+  ai_preload_keep_alive_mins: (value) =>
+    commands.changeAiPreloadKeepAlive(value as number),
+  ai_idle_unload_minutes: (value) =>
+    commands.changeAiIdleUnloadMinutes(value as number),
+  ai_cache_enabled: (value) => commands.changeAiCacheEnabled(value as boolean),
+  ai_cache_size: (value) => commands.changeAiCacheSize(value as number),
+  ai_history_enabled: (value) =>
+    commands.changeAiHistoryEnabled(value as boolean),
+  ai_withhold_original_from_events: (value) =>
+    commands.changeAiWithholdOriginalFromEvents(value as boolean),
+  ai_usage_stats_enabled: (value) =>
+    commands.changeAiUsageStatsEnabled(value as boolean),
+  ai_queue_policy: (value) => commands.changeAiQueuePolicy(value as any),
+  ai_context_enabled: (value) =>
+    commands.changeAiContextEnabled(value as boolean),
+  ai_context_utterances: (value) =>
+    commands.changeAiContextUtterances(value as number),
+  ai_context_idle_secs: (value) =>
+    commands.changeAiContextIdleSecs(value as number),
+  ai_output_guard: (value) =>
+    commands.changeAiOutputGuard(value as any),
+  ai_generation_options: (value) =>
+    commands.changeAiGenerationOptions(value as AiGenerationOptions),
+  ai_custom_prompt_template: (value) =>
+    value === null
+      ? commands.resetAiPromptTemplate()
+      : commands.changeAiCustomPrompt(value as string),
+  ai_prompt_all_instructions: (value) =>
+    commands.changeAiPromptAllInstructions(value as boolean),
+  ai_examples: (value) => commands.changeAiExamples(value as any),
+  ai_replacement_rules: (value) =>
+    commands.changeAiReplacementRules(value as any),
+  ai_snippet_strictness: (value) =>
+    commands.changeAiSnippetStrictness(value as number),
+  ai_languages: (value) => commands.changeAiLanguages(value as any),
+  ai_language_policy: (value) =>
+    commands.changeAiLanguagePolicy(value as any),
+  ai_summary_style: (value) => commands.changeAiSummaryStyle(value as any),
+  ai_summary_max_words: (value) =>
+    commands.changeAiSummaryMaxWords(value as number),
   log_level: (value) => commands.setLogLevel(value as any),
 };
 